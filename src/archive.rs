@@ -0,0 +1,204 @@
+//! Writing the files of an extraction into a zip archive rather than a
+//! directory, split into volumes of limited size if asked to be.
+//!
+//! Each volume is a zip archive of its own, which can be extracted without
+//! the others. Files are never split across volumes.
+
+use crate::error::{ExtractError, IoContext};
+use crate::report::{ExtractionReport, FileOutcome};
+use crate::vfs::MemoryFs;
+use serde::Serialize;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Component, Path, PathBuf};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+/// The most bytes a zip archive takes besides its entries: the end of central
+/// directory record, along with its zip64 variant and locator.
+const ARCHIVE_OVERHEAD: u64 = 22 + 56 + 20;
+
+/// A volume of an archive, as written by [`write_archive`].
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Volume {
+    /// The path of the volume's zip file.
+    pub path: PathBuf,
+    /// The size of the volume's zip file in bytes.
+    pub size: u64,
+    /// The paths of the files in the volume, relative to the output
+    /// directory, sorted.
+    pub files: Vec<PathBuf>,
+}
+
+/// Returns the most bytes a file of `size` bytes at `name` can take in a zip
+/// archive: its local header and central directory header, along with their
+/// zip64 extra fields, and its contents, which deflating makes at most a few
+/// bytes larger.
+fn entry_size(name: &str, size: u64) -> u64 {
+    let headers = 30 + 46 + 2 * (name.len() as u64 + 28);
+    // Incompressible data is kept in stored blocks, each with a 5-byte
+    // header, which hold at least 16 KiB.
+    let contents = size + 5 * (size / 16384 + 1);
+
+    headers + contents
+}
+
+/// Returns the name of the entry for the file at `path`, with `/` separators.
+fn entry_name(path: &Path) -> String {
+    path.components()
+        .filter_map(|component| match component {
+            Component::Normal(name) => Some(name.to_string_lossy()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Assigns the `files`, given as their paths and sizes, to volumes of at most
+/// `limit` bytes each, or to a single volume without a limit.
+///
+/// Files are assigned from largest to smallest, each to the first volume it
+/// fits in, and are sorted by path within each volume. Fails if a single file
+/// can't fit in a volume of its own.
+///
+/// # Examples
+/// ```
+/// use extract_minecraft_assets::archive;
+/// use std::path::PathBuf;
+///
+/// let files = [
+///     (PathBuf::from("a.ogg"), 600),
+///     (PathBuf::from("b.ogg"), 500),
+///     (PathBuf::from("c.json"), 300),
+/// ];
+/// let volumes = archive::plan_volumes(&files, Some(1200))?;
+///
+/// assert_eq!(volumes, [vec![PathBuf::from("a.ogg")], vec![PathBuf::from("b.ogg"), PathBuf::from("c.json")]]);
+/// # Ok::<(), extract_minecraft_assets::error::ExtractError>(())
+/// ```
+pub fn plan_volumes(
+    files: &[(PathBuf, u64)],
+    limit: Option<u64>,
+) -> Result<Vec<Vec<PathBuf>>, ExtractError> {
+    let Some(limit) = limit else {
+        let mut paths: Vec<_> = files.iter().map(|(path, _)| path.clone()).collect();
+        paths.sort();
+
+        return Ok(vec![paths]);
+    };
+
+    let mut sized: Vec<_> = files
+        .iter()
+        .map(|(path, size)| (path, *size, entry_size(&entry_name(path), *size)))
+        .collect();
+    sized.sort_by(|a, b| b.2.cmp(&a.2).then_with(|| a.0.cmp(b.0)));
+
+    // The paths of each volume, along with the bytes left in it.
+    let mut volumes: Vec<(Vec<PathBuf>, u64)> = Vec::new();
+    for (path, size, needed) in sized {
+        match volumes.iter_mut().find(|(_, left)| *left >= needed) {
+            Some((paths, left)) => {
+                paths.push(path.clone());
+                *left -= needed;
+            }
+            None if needed + ARCHIVE_OVERHEAD <= limit => {
+                volumes.push((vec![path.clone()], limit - ARCHIVE_OVERHEAD - needed));
+            }
+            None => {
+                return Err(ExtractError::TooLargeForVolume {
+                    path: path.clone(),
+                    size,
+                    limit,
+                });
+            }
+        }
+    }
+
+    Ok(volumes
+        .into_iter()
+        .map(|(mut paths, _)| {
+            paths.sort();
+            paths
+        })
+        .collect())
+}
+
+/// Returns the path of volume `number` (starting at 1) of the archive at
+/// `path`, e.g. `pack.part2.zip` for `pack.zip`.
+pub fn volume_path(path: &Path, number: usize) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let extension = path.extension().map_or(String::new(), |extension| {
+        format!(".{}", extension.to_string_lossy())
+    });
+
+    path.with_file_name(format!("{stem}.part{number}{extension}"))
+}
+
+/// Writes the files written by the extraction of `report` into `fs` to the
+/// zip archive at `path`, split into volumes of at most `limit` bytes each if
+/// given.
+///
+/// A single volume is written to `path` itself, and several to the
+/// [numbered paths](volume_path) beside it. Entries keep their paths relative
+/// to the output directory, and their unix permissions.
+pub fn write_archive(
+    fs: &MemoryFs,
+    report: &ExtractionReport,
+    path: &Path,
+    limit: Option<u64>,
+) -> Result<Vec<Volume>, ExtractError> {
+    let output_dir = &report.options.output_dir;
+    let files: Vec<_> = report
+        .files
+        .iter()
+        .filter_map(|file| match file.outcome {
+            FileOutcome::Written { bytes } => Some((file.path.clone(), bytes)),
+            _ => None,
+        })
+        .collect();
+
+    let planned = plan_volumes(&files, limit)?;
+    let count = planned.len();
+    let mut volumes = Vec::new();
+    for (i, files) in planned.into_iter().enumerate() {
+        let volume_path = if count == 1 {
+            path.to_owned()
+        } else {
+            volume_path(path, i + 1)
+        };
+
+        let file = File::create(&volume_path).writing(&volume_path)?;
+        let mut zip = ZipWriter::new(BufWriter::new(file));
+        for file_path in &files {
+            let full_path = output_dir.join(file_path);
+            let contents = fs.read(&full_path).ok_or_else(|| {
+                ExtractError::read_output(&full_path)(io::ErrorKind::NotFound.into())
+            })?;
+
+            let mut options = SimpleFileOptions::default()
+                .compression_method(CompressionMethod::Deflated)
+                .large_file(contents.len() as u64 >= u64::from(u32::MAX));
+            if let Some(mode) = fs.unix_mode(&full_path) {
+                options = options.unix_permissions(mode);
+            }
+            zip.start_file(entry_name(file_path), options)
+                .map_err(io::Error::from)
+                .writing(&volume_path)?;
+            zip.write_all(&contents).writing(&volume_path)?;
+        }
+        zip.finish()
+            .map_err(io::Error::from)
+            .and_then(|mut writer| writer.flush())
+            .writing(&volume_path)?;
+
+        let size = std::fs::metadata(&volume_path).writing(&volume_path)?.len();
+        volumes.push(Volume {
+            path: volume_path,
+            size,
+            files,
+        });
+    }
+
+    Ok(volumes)
+}
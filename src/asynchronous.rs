@@ -0,0 +1,853 @@
+//! Async versions of the extraction functions, for use with [tokio].
+//!
+//! Available with the `async` feature.
+//!
+//! File IO is done on tokio's blocking thread pool, with at most
+//! [`concurrency`](ExtractOptions::concurrency) files being extracted at once.
+//! Progress is reported as [`ProgressEvent`]s sent through a channel, so that
+//! it can be received by another task, such as one updating a UI.
+//!
+//! # Cancellation
+//! Extraction stops when its [token](ExtractOptions::cancellation) is
+//! cancelled: files which are already being written are finished, and the
+//! partial report is returned.
+//!
+//! Extraction also stops when its future is dropped. Each file is written to a
+//! `.part` file next to it which is only renamed into place once complete, so
+//! a file which was being extracted at the time is either finished or not
+//! written at all: no half-written file is left in the output directory.
+
+use crate::error::ExtractError;
+use crate::hashed::{self, IndexFile, Object};
+use crate::jar::{self, ModeOutcome, UnixModes};
+use crate::options::{ExtractOptions, Verify};
+use crate::progress::Summary;
+use crate::report::{ExtractionReport, FileOutcome, FileRecord, FileWarning};
+use crate::search::FileSource;
+use crate::transform::Transform;
+use crate::validate::{self, CheckingReader};
+use std::io::{Cursor, Read};
+use std::panic;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::mpsc::UnboundedSender;
+use tokio::task::{self, JoinSet};
+
+/// An event of an async extraction, mirroring the methods of
+/// [`ProgressSink`](crate::progress::ProgressSink).
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ProgressEvent {
+    /// Sent once before any files are extracted, with the number of files and
+    /// bytes which are going to be extracted.
+    Begin {
+        total_files: usize,
+        total_bytes: u64,
+    },
+    /// A file started being extracted.
+    ///
+    /// The path is relative to the output directory.
+    FileStarted(PathBuf),
+    /// A file has been extracted, with the number of bytes written.
+    FileDone { path: PathBuf, bytes: u64 },
+    /// A file was skipped because it already exists, as set by
+    /// [`Overwrite::Skip`] or decided with [`Overwrite::Ask`].
+    FileSkipped(PathBuf),
+    /// A file failed to be extracted, but the extraction continues.
+    FileFailed { path: PathBuf, error: ExtractError },
+    /// A file was extracted, but not as asked, e.g. when a language file
+    /// couldn't be converted and was extracted as it is.
+    FileWarning { path: PathBuf, message: String },
+    /// Sent once after every file has been extracted.
+    Finish(Summary),
+}
+
+/// The async version of [`jar::extract_jar`].
+///
+/// The jar file is read into memory so that its entries can be extracted
+/// concurrently. Entries which can't be read or written are sent as
+/// [`ProgressEvent::FileFailed`], rather than aborting the extraction, unless
+/// the output volume is full: no more entries are started then.
+///
+/// # Examples
+/// ```no_run
+/// use extract_minecraft_assets::asynchronous::{self, ProgressEvent};
+/// use extract_minecraft_assets::options::ExtractOptions;
+/// use std::path::Path;
+/// use tokio::sync::mpsc;
+///
+/// # async fn extract() -> Result<(), extract_minecraft_assets::error::ExtractError> {
+/// let (sender, mut receiver) = mpsc::unbounded_channel();
+///
+/// tokio::spawn(async move {
+///     while let Some(event) = receiver.recv().await {
+///         if let ProgressEvent::FileDone { path, .. } = event {
+///             println!("Extracted {}", path.display());
+///         }
+///     }
+/// });
+///
+/// let report = asynchronous::extract_jar_async(
+///     Path::new(".minecraft/versions/1.20.1/1.20.1.jar"),
+///     &ExtractOptions::new().output("extracted"),
+///     Some(sender),
+/// )
+/// .await?;
+/// println!("Extracted {report}");
+/// # Ok(())
+/// # }
+/// ```
+pub async fn extract_jar_async(
+    jar_file: &Path,
+    options: &ExtractOptions,
+    progress: Option<UnboundedSender<ProgressEvent>>,
+) -> Result<ExtractionReport, ExtractError> {
+    let contents = {
+        let jar_file = jar_file.to_owned();
+        let retry = options.locked_retry;
+
+        blocking(move || {
+            retry
+                .run(|| std::fs::read(&jar_file))
+                .map_err(|error| ExtractError::Zip {
+                    path: jar_file,
+                    source: error.into(),
+                })
+        })
+        .await?
+    };
+
+    let (archive, entries, duplicates, unsafe_names) = {
+        let jar_file = jar_file.to_owned();
+        let options = options.clone();
+
+        blocking(move || {
+            let unsafe_names = jar::unsafe_name_warnings(&jar_file, Cursor::new(&contents))?;
+            let (reader, duplicates) = jar::resolve_duplicates(
+                &jar_file,
+                Cursor::new(Arc::<[u8]>::from(contents)),
+                &options,
+                |path| options.includes(path),
+            )?;
+            let (mut archive, top_level_dir) = jar::read_archive(&jar_file, reader)?;
+            let mut entries = Vec::new();
+            let mut file_paths = Vec::new();
+
+            for i in 0..archive.len() {
+                let file = archive
+                    .by_index_raw(i)
+                    .map_err(ExtractError::zip(&jar_file))?;
+
+                let Some(path) = file.enclosed_name() else {
+                    continue;
+                };
+                let Some((path, root)) =
+                    jar::selected_path(&path, top_level_dir.as_deref(), &options.contents)
+                else {
+                    continue;
+                };
+                if !options.includes(path) {
+                    continue;
+                }
+
+                let output_path = options.relative_output_path(
+                    root.output_path(path, options.ignore_top_level),
+                    file.is_dir(),
+                );
+                if !file.is_dir() {
+                    file_paths.push((path.to_owned(), output_path.clone()));
+                }
+
+                entries.push(JarEntry {
+                    index: i,
+                    entry: path.to_owned(),
+                    path: output_path,
+                    transform: Transform::of(&options, path, file.size()),
+                    is_dir: file.is_dir(),
+                    size: file.size(),
+                    unix_mode: file.unix_mode(),
+                });
+            }
+
+            options.check_strip_namespace(file_paths)?;
+
+            Ok::<_, ExtractError>((archive, entries, duplicates, unsafe_names))
+        })
+        .await?
+    };
+
+    let (dirs, files): (Vec<JarEntry>, Vec<JarEntry>) =
+        entries.into_iter().partition(|entry| entry.is_dir);
+    // Directories are only created to contain files when files are grouped.
+    let dirs = if options.group_by.is_some() {
+        Vec::new()
+    } else {
+        dirs
+    };
+
+    let mut tally = Tally::begin(
+        progress,
+        options,
+        FileSource::Jar,
+        files.len(),
+        files.iter().map(|entry| entry.size).sum(),
+    );
+
+    for (path, message) in jar::duplicate_warnings(duplicates, options).chain(unsafe_names) {
+        tally.file_warning(path, message);
+    }
+
+    // Directories are created up front, as they are cheap and files may be
+    // extracted into them in any order.
+    let modes = Arc::new(UnixModes::default());
+    let dir_modes = {
+        let options = options.clone();
+        let modes = modes.clone();
+
+        blocking(move || {
+            dirs.into_iter()
+                .map(|entry| {
+                    let output_path = options.output_dir.join(&entry.path);
+                    let mode = options.make_way(&output_path, true).and_then(|clear| {
+                        if !clear {
+                            return Ok(None);
+                        }
+
+                        options
+                            .vfs
+                            .create_dir_all(&output_path)
+                            .map_err(ExtractError::write_file(&output_path))?;
+                        modes.set(&options, &output_path, entry.unix_mode).map(Some)
+                    });
+
+                    (entry.path, mode)
+                })
+                .collect::<Vec<_>>()
+        })
+        .await
+    };
+    for (path, mode) in dir_modes {
+        match mode {
+            Ok(Some(mode)) => tally.mode_set(path, mode),
+            Ok(None) => tally.file_skipped(path),
+            Err(error) => tally.file_failed(path, error),
+        }
+    }
+
+    let task_options = Arc::new(options.clone());
+    let mut tasks = JoinSet::new();
+
+    for entry in files {
+        if options.is_cancelled() {
+            tally.report.summary.cancelled = true;
+            break;
+        }
+        if tally.report.summary.disk_full {
+            break;
+        }
+
+        // A transformed file is written, and so skipped, under its new name.
+        let path = match entry.transform {
+            Some(transform) => transform.output_path(&entry.path),
+            None => entry.path.clone(),
+        };
+        let output_path = options.output_dir.join(&path);
+
+        match make_way(&task_options, &output_path).await {
+            Ok(true) => {}
+            Ok(false) => {
+                tally.file_skipped(path);
+                continue;
+            }
+            Err(error) => {
+                tally.file_failed(path, error);
+                continue;
+            }
+        }
+
+        if tasks.len() >= options.concurrency
+            && let Some(task) = join_next(&mut tasks).await
+        {
+            tally.task_finished(task);
+        }
+
+        tally.file_started(&path);
+
+        let mut archive = archive.clone();
+        let jar_file = jar_file.to_owned();
+        let options = task_options.clone();
+        let modes = modes.clone();
+
+        tasks.spawn_blocking(move || {
+            let mut file = match archive.by_index(entry.index) {
+                Ok(file) => file,
+                Err(source) => {
+                    let error = ExtractError::ReadEntry {
+                        path: jar_file,
+                        entry: entry.entry,
+                        source,
+                    };
+
+                    return TaskOutput::single(path, Err(error));
+                }
+            };
+
+            let Some(transform) = entry.transform else {
+                return write_checked(&options, &modes, path, entry.unix_mode, &mut file);
+            };
+
+            let mut contents = Vec::new();
+            if let Err(error) = file.read_to_end(&mut contents) {
+                let error = ExtractError::ReadEntry {
+                    path: jar_file,
+                    entry: entry.entry,
+                    source: error.into(),
+                };
+
+                return TaskOutput::single(path, Err(error));
+            }
+
+            write_transformed(
+                &options,
+                &modes,
+                &entry.path,
+                contents,
+                transform,
+                entry.unix_mode,
+            )
+        });
+    }
+
+    while let Some(task) = join_next(&mut tasks).await {
+        tally.task_finished(task);
+    }
+
+    Ok(tally.finish())
+}
+
+/// The async version of [`hashed::extract_hashed_assets`].
+///
+/// Files which can't be read or written are sent as
+/// [`ProgressEvent::FileFailed`], rather than aborting the extraction, unless
+/// the output volume is full: no more files are started then.
+pub async fn extract_hashed_assets_async(
+    hashed_assets_dir: &Path,
+    index: &IndexFile,
+    options: &ExtractOptions,
+    progress: Option<UnboundedSender<ProgressEvent>>,
+) -> Result<ExtractionReport, ExtractError> {
+    let index = &*options.included_objects(index);
+    // Paths are reported relative to the output directory
+    let assets_dir = if options.ignore_top_level {
+        PathBuf::new()
+    } else {
+        PathBuf::from("assets")
+    };
+
+    options.check_strip_namespace(index.entries().map(|entry| {
+        let virtual_path = options.index_path(entry.virtual_path);
+
+        (
+            Path::new("assets").join(&virtual_path),
+            options.relative_output_path(assets_dir.join(&virtual_path), false),
+        )
+    }))?;
+
+    let objects_dir = hashed_assets_dir.join("objects");
+    let mut tally = Tally::begin(
+        progress,
+        options,
+        FileSource::Hashed,
+        index.len(),
+        index.total_size(),
+    );
+    let task_options = Arc::new(options.clone());
+    let mut tasks = JoinSet::new();
+
+    for entry in index.entries() {
+        if options.is_cancelled() {
+            tally.report.summary.cancelled = true;
+            break;
+        }
+        if tally.report.summary.disk_full {
+            break;
+        }
+
+        let virtual_path = entry.virtual_path.to_owned();
+        let decoded_path = options.index_path(entry.virtual_path);
+        let original_path = options.relative_output_path(assets_dir.join(&decoded_path), false);
+        let transform = Transform::of(
+            options,
+            &Path::new("assets").join(&decoded_path),
+            entry.size,
+        );
+        // A transformed file is written, and so skipped, under its new name.
+        let path = match transform {
+            Some(transform) => transform.output_path(&original_path),
+            None => original_path.clone(),
+        };
+        let output_file = options.output_dir.join(&path);
+
+        match make_way(&task_options, &output_file).await {
+            Ok(true) => {}
+            Ok(false) => {
+                tally.object_started(entry.hash);
+                tally.file_skipped(path);
+                continue;
+            }
+            Err(error) => {
+                tally.object_started(entry.hash);
+                tally.file_failed(path, error);
+                continue;
+            }
+        }
+
+        if tasks.len() >= options.concurrency
+            && let Some(task) = join_next(&mut tasks).await
+        {
+            tally.task_finished(task);
+        }
+
+        tally.file_started(&path);
+
+        let object_path = objects_dir.join(entry.object_path);
+        let options = task_options.clone();
+        let object = Object {
+            hash: entry.hash.to_owned(),
+            size: entry.size,
+        };
+
+        tasks.spawn_blocking(move || {
+            let contents =
+                match hashed::read_object(&virtual_path, &object_path, &options.locked_retry) {
+                    Ok(contents) => contents,
+                    Err(error) => return TaskOutput::single(path, Err(error)).hash(object.hash),
+                };
+            let unverified = options.verify.and_then(|verify| {
+                let mismatch = object.verify(&contents).err()?;
+                let error = ExtractError::CorruptObject {
+                    path: virtual_path,
+                    mismatch,
+                };
+
+                Some((verify, error))
+            });
+            if let Some((Verify::Strict, error)) = unverified {
+                return TaskOutput::single(path, Err(error))
+                    .unverified()
+                    .hash(object.hash);
+            }
+
+            // Hashed files have no permissions to set.
+            let modes = UnixModes::default();
+            let mut output = match transform {
+                Some(transform) => {
+                    write_transformed(&options, &modes, &original_path, contents, transform, None)
+                }
+                None => write_checked(&options, &modes, path, None, &mut contents.as_slice()),
+            };
+            if let Some((_, error)) = unverified {
+                output
+                    .warnings
+                    .insert(0, (original_path, error.to_string()));
+                output = output.unverified();
+            }
+
+            output.hash(object.hash)
+        });
+    }
+
+    while let Some(task) = join_next(&mut tasks).await {
+        tally.task_finished(task);
+    }
+
+    Ok(tally.finish())
+}
+
+/// An entry of a jar file to be extracted.
+struct JarEntry {
+    /// The index of the entry within the archive.
+    index: usize,
+    /// The path of the entry relative to the jar's top-level directory (if
+    /// any).
+    entry: PathBuf,
+    /// The path of the entry relative to the output directory.
+    path: PathBuf,
+    /// How the entry is transformed, if it is.
+    transform: Option<Transform>,
+    is_dir: bool,
+    /// The uncompressed size of the entry in bytes.
+    size: u64,
+    unix_mode: Option<u32>,
+}
+
+/// The outcome of a task extracting a single file.
+struct TaskOutput {
+    /// The outcome of writing each file, by path relative to the output
+    /// directory: usually just the file itself, but a converted language
+    /// file may be written along with its original.
+    files: Vec<(PathBuf, Result<u64, ExtractError>)>,
+    /// The paths and messages of the warnings about the file.
+    warnings: Vec<(PathBuf, String)>,
+    /// The number of bytes saved by minifying the file.
+    bytes_saved: u64,
+    /// The number of files whose line endings were normalized.
+    normalized: usize,
+    /// The paths of the files written whose contents are invalid, along with
+    /// why.
+    invalid: Vec<(PathBuf, String)>,
+    /// The outcome of setting the permissions of each file written.
+    modes: Vec<(PathBuf, ModeOutcome)>,
+    /// The hash of the hashed asset the files were extracted from, if any.
+    hash: Option<String>,
+    /// Whether the hashed asset didn't match the index file when
+    /// [verified](ExtractOptions::verify).
+    unverified: bool,
+}
+
+impl TaskOutput {
+    /// The outcome of writing a single file to `path`, without a warning.
+    fn single(path: PathBuf, result: Result<u64, ExtractError>) -> Self {
+        Self {
+            files: vec![(path, result)],
+            warnings: Vec::new(),
+            bytes_saved: 0,
+            normalized: 0,
+            invalid: Vec::new(),
+            modes: Vec::new(),
+            hash: None,
+            unverified: false,
+        }
+    }
+
+    /// The same outcome, of a hashed asset which didn't match the index file.
+    fn unverified(self) -> Self {
+        Self {
+            unverified: true,
+            ..self
+        }
+    }
+
+    /// The same outcome, of the files extracted from the hashed asset with
+    /// `hash`.
+    fn hash(self, hash: String) -> Self {
+        Self {
+            hash: Some(hash),
+            ..self
+        }
+    }
+}
+
+/// Runs `f` on the blocking thread pool, resuming its panic if it panics.
+async fn blocking<T: Send + 'static>(f: impl FnOnce() -> T + Send + 'static) -> T {
+    task::spawn_blocking(f)
+        .await
+        .unwrap_or_else(|error| panic::resume_unwind(error.into_panic()))
+}
+
+/// Waits for the next task in `tasks` to finish, resuming its panic if it
+/// panics.
+async fn join_next(tasks: &mut JoinSet<TaskOutput>) -> Option<TaskOutput> {
+    tasks
+        .join_next()
+        .await
+        .map(|result| result.unwrap_or_else(|error| panic::resume_unwind(error.into_panic())))
+}
+
+/// Clears the way for a file to be written at `output_path`, as by
+/// [`ExtractOptions::make_way`], returning whether it should be written.
+async fn make_way(options: &Arc<ExtractOptions>, output_path: &Path) -> Result<bool, ExtractError> {
+    let (options, output_path) = (options.clone(), output_path.to_owned());
+
+    blocking(move || options.make_way(&output_path, false)).await
+}
+
+/// Writes `contents` to a new file at `output_path`, creating its parent
+/// directories and setting its permissions to `unix_mode` with `modes`, and
+/// returns the number of bytes written along with the outcome of setting
+/// them.
+///
+/// The file is written to a `.part` file which is renamed to `output_path`
+/// once complete, and removed if it couldn't be written.
+fn write_atomically(
+    options: &ExtractOptions,
+    modes: &UnixModes,
+    output_path: &Path,
+    unix_mode: Option<u32>,
+    contents: &mut dyn Read,
+) -> Result<(u64, ModeOutcome), ExtractError> {
+    let vfs = &*options.vfs;
+    let mut part_name = output_path.file_name().unwrap_or_default().to_owned();
+    part_name.push(".part");
+    let part_path = output_path.with_file_name(part_name);
+
+    let result = (|| {
+        if let Some(parent) = output_path.parent() {
+            vfs.create_dir_all(parent)
+                .map_err(ExtractError::write_file(parent))?;
+        }
+
+        let bytes = vfs
+            .write_file(&part_path, contents)
+            .map_err(ExtractError::write_file(output_path))?;
+        let mode = modes.set(options, &part_path, unix_mode)?;
+        vfs.rename(&part_path, output_path)
+            .map_err(ExtractError::write_file(output_path))?;
+
+        Ok((bytes, mode))
+    })();
+
+    if result.is_err() {
+        let _ = vfs.remove_file(&part_path);
+    }
+
+    result
+}
+
+/// Writes `contents` to the file at `path`, relative to the output
+/// directory, as by [`write_atomically`], checking them as they are written
+/// if their format is [validated](ExtractOptions::validate_content).
+fn write_checked(
+    options: &ExtractOptions,
+    modes: &UnixModes,
+    path: PathBuf,
+    unix_mode: Option<u32>,
+    contents: &mut dyn Read,
+) -> TaskOutput {
+    let output_path = options.output_dir.join(&path);
+    let mut contents = CheckingReader::new(contents, validate::content_check(options, &path));
+    let result = write_atomically(options, modes, &output_path, unix_mode, &mut contents);
+    let (result, invalid, mode) = match result {
+        Ok((bytes, mode)) => (
+            Ok(bytes),
+            contents.finish().map(|reason| (path.clone(), reason)),
+            Some((path.clone(), mode)),
+        ),
+        Err(error) => (Err(error), None, None),
+    };
+
+    TaskOutput {
+        invalid: invalid.into_iter().collect(),
+        modes: mode.into_iter().collect(),
+        ..TaskOutput::single(path, result)
+    }
+}
+
+/// Transforms the `contents` of the file being extracted to `path`,
+/// relative to the output directory, and writes the files to keep as by
+/// [`Transform::apply`].
+fn write_transformed(
+    options: &ExtractOptions,
+    modes: &UnixModes,
+    path: &Path,
+    contents: Vec<u8>,
+    transform: Transform,
+    unix_mode: Option<u32>,
+) -> TaskOutput {
+    let transformed = transform.apply(path, contents, options.keep_original);
+    let mut invalid = Vec::new();
+    let mut modes_set = Vec::new();
+    let files = transformed
+        .files
+        .into_iter()
+        .map(|(path, contents)| {
+            let output_path = options.output_dir.join(&path);
+            let result = write_atomically(
+                options,
+                modes,
+                &output_path,
+                unix_mode,
+                &mut contents.as_slice(),
+            )
+            .map(|(bytes, mode)| {
+                modes_set.push((path.clone(), mode));
+                bytes
+            });
+            if result.is_ok()
+                && let Some(reason) = validate::check(options, &path, &contents)
+            {
+                invalid.push((path.clone(), reason));
+            }
+
+            (path, result)
+        })
+        .collect();
+
+    TaskOutput {
+        files,
+        warnings: transformed
+            .warning
+            .map(|message| (path.to_owned(), message))
+            .into_iter()
+            .collect(),
+        bytes_saved: transformed.bytes_saved,
+        normalized: transformed.normalized,
+        invalid,
+        modes: modes_set,
+        hash: None,
+        unverified: false,
+    }
+}
+
+/// Tallies the events of an async extraction into an [`ExtractionReport`]
+/// while sending them as [`ProgressEvent`]s.
+struct Tally {
+    sender: Option<UnboundedSender<ProgressEvent>>,
+    started: Instant,
+    report: ExtractionReport,
+    /// Where the files are extracted from.
+    source: FileSource,
+    /// The hash of the hashed asset whose files are being recorded, if any.
+    hash: Option<String>,
+    /// The number of bytes to be extracted.
+    total_bytes: u64,
+}
+
+impl Tally {
+    /// Starts tallying an extraction with `options` of `total_files` files and
+    /// `total_bytes` bytes from `source`.
+    fn begin(
+        sender: Option<UnboundedSender<ProgressEvent>>,
+        options: &ExtractOptions,
+        source: FileSource,
+        total_files: usize,
+        total_bytes: u64,
+    ) -> Self {
+        let tally = Self {
+            sender,
+            started: Instant::now(),
+            report: ExtractionReport::new(options.clone()),
+            source,
+            hash: None,
+            total_bytes,
+        };
+        tally.send(ProgressEvent::Begin {
+            total_files,
+            total_bytes,
+        });
+
+        tally
+    }
+
+    fn send(&self, event: ProgressEvent) {
+        if let Some(sender) = &self.sender {
+            // Nobody may be listening anymore, which is fine
+            let _ = sender.send(event);
+        }
+    }
+
+    fn record(&mut self, path: &Path, outcome: FileOutcome) {
+        self.report.files.push(FileRecord {
+            path: path.to_owned(),
+            source: self.source,
+            hash: self.hash.clone(),
+            outcome,
+        });
+    }
+
+    /// Records the files of the hashed asset with `hash` from now on.
+    fn object_started(&mut self, hash: &str) {
+        self.hash = Some(hash.to_owned());
+    }
+
+    fn file_started(&self, path: &Path) {
+        self.send(ProgressEvent::FileStarted(path.to_owned()));
+    }
+
+    fn file_done(&mut self, path: PathBuf, bytes: u64) {
+        self.report.summary.files += 1;
+        self.report.summary.bytes += bytes;
+        self.record(&path, FileOutcome::Written { bytes });
+        self.send(ProgressEvent::FileDone { path, bytes });
+    }
+
+    fn file_skipped(&mut self, path: PathBuf) {
+        self.report.summary.skipped += 1;
+        self.record(&path, FileOutcome::Skipped);
+        self.send(ProgressEvent::FileSkipped(path));
+    }
+
+    /// Records a file as failed, stopping the extraction if the output volume
+    /// is full.
+    fn file_failed(&mut self, path: PathBuf, error: ExtractError) {
+        self.report.summary.failures += 1;
+        self.report.summary.disk_full |= error.is_disk_full();
+        self.record(
+            &path,
+            FileOutcome::Failed {
+                error: error.to_string(),
+            },
+        );
+        self.send(ProgressEvent::FileFailed { path, error });
+    }
+
+    /// Records a file as [done](Tally::file_done) or [failed](Tally::file_failed),
+    /// depending on `result`.
+    fn file_finished(&mut self, path: PathBuf, result: Result<u64, ExtractError>) {
+        match result {
+            Ok(bytes) => self.file_done(path, bytes),
+            Err(error) => self.file_failed(path, error),
+        }
+    }
+
+    fn file_warning(&mut self, path: PathBuf, message: String) {
+        self.report.warnings.push(FileWarning {
+            path: path.clone(),
+            message: message.clone(),
+        });
+        self.send(ProgressEvent::FileWarning { path, message });
+    }
+
+    /// Records that the contents of the file written to `path` are invalid,
+    /// as a warning giving the `reason`.
+    fn file_invalid(&mut self, path: PathBuf, reason: String) {
+        self.report.summary.invalid += 1;
+        self.file_warning(path, reason);
+    }
+
+    /// Records the outcome of setting the permissions of the file or
+    /// directory written to `path`.
+    fn mode_set(&mut self, path: PathBuf, outcome: ModeOutcome) {
+        if let ModeOutcome::NotSet(warning) = outcome {
+            self.report.summary.permissions_not_set += 1;
+            if let Some(message) = warning {
+                self.file_warning(path, message);
+            }
+        }
+    }
+
+    /// Records the files written by a task as [finished](Tally::file_finished).
+    fn task_finished(&mut self, task: TaskOutput) {
+        self.hash = task.hash;
+        for (path, message) in task.warnings {
+            self.file_warning(path, message);
+        }
+        self.report.summary.unverified += usize::from(task.unverified);
+        self.report.summary.bytes_saved += task.bytes_saved;
+        self.report.summary.normalized += task.normalized;
+        for (path, result) in task.files {
+            self.file_finished(path, result);
+        }
+        for (path, reason) in task.invalid {
+            self.file_invalid(path, reason);
+        }
+        for (path, mode) in task.modes {
+            self.mode_set(path, mode);
+        }
+    }
+
+    /// Finishes the extraction, returning its report.
+    fn finish(mut self) -> ExtractionReport {
+        let summary = &mut self.report.summary;
+        if summary.disk_full {
+            // Files finish in any order, so what remains is only known from
+            // what was written.
+            summary.remaining_bytes = self.total_bytes.saturating_sub(summary.bytes);
+        }
+        self.report.summary.duration = self.started.elapsed();
+        self.send(ProgressEvent::Finish(self.report.summary.clone()));
+
+        self.report
+    }
+}
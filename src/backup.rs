@@ -0,0 +1,483 @@
+//! Extracting hashed assets straight out of a backup of `.minecraft/assets`
+//! in a zip or tar archive, without restoring it first.
+
+use crate::error::ExtractError;
+use crate::hashed::{IndexError, IndexFile, IndexFileLocation, Object, ObjectExtraction};
+use crate::options::ExtractOptions;
+use crate::progress::ProgressSink;
+use crate::report::ExtractionReport;
+use crate::util;
+use flate2::read::MultiGzDecoder;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::path::{Path, PathBuf};
+use zip::ZipArchive;
+use zip::result::ZipError;
+
+/// The format of an archive holding a backup of `.minecraft/assets`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum BackupFormat {
+    Zip,
+    Tar,
+    /// A tar archive compressed with gzip, e.g. `.tar.gz` or `.tgz`.
+    TarGz,
+}
+
+impl BackupFormat {
+    /// Detects the format of the archive at `path` from its first bytes, or
+    /// failing that from its extension. Returns [`None`] if it isn't an
+    /// archive in a known format.
+    pub fn detect(path: &Path) -> io::Result<Option<Self>> {
+        let mut header = Vec::with_capacity(262);
+        File::open(path)?.take(262).read_to_end(&mut header)?;
+
+        if header.starts_with(b"PK\x03\x04") || header.starts_with(b"PK\x05\x06") {
+            return Ok(Some(Self::Zip));
+        }
+        if header.starts_with(&[0x1f, 0x8b]) {
+            return Ok(Some(Self::TarGz));
+        }
+        if header.get(257..262) == Some(b"ustar") {
+            return Ok(Some(Self::Tar));
+        }
+
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        let format = if name.ends_with(".zip") {
+            Some(Self::Zip)
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(Self::TarGz)
+        } else if name.ends_with(".tar") {
+            Some(Self::Tar)
+        } else {
+            None
+        };
+
+        Ok(format)
+    }
+}
+
+/// A backup of `.minecraft/assets` in a zip or tar archive, from which
+/// hashed assets can be [extracted](AssetsBackup::extract_hashed_assets)
+/// directly.
+///
+/// The `indexes` and `objects` directories may be at the top of the archive
+/// or within a wrapper directory, e.g. `assets/` or
+/// `backup-2024-01-01/.minecraft/assets/`.
+///
+/// Zip archives are read with random access. Tar archives can only be read
+/// from start to end, so opening one reads its index files in a first pass,
+/// and extracting from it takes a second pass collecting the objects needed.
+///
+/// # Examples
+/// ```no_run
+/// use extract_minecraft_assets::backup::AssetsBackup;
+/// use extract_minecraft_assets::hashed::IndexFileLocation;
+/// use extract_minecraft_assets::options::ExtractOptions;
+/// use extract_minecraft_assets::progress::NoProgress;
+/// use std::path::Path;
+///
+/// let backup = AssetsBackup::open(Path::new("assets-backup.tar.gz"))?;
+/// let index = backup.index(&IndexFileLocation::Version("17".to_owned()))?;
+///
+/// let report = backup.extract_hashed_assets(
+///     &index,
+///     &ExtractOptions::new().output("extracted"),
+///     &mut NoProgress,
+/// )?;
+/// println!("Extracted {report}");
+/// # Ok::<(), extract_minecraft_assets::error::ExtractError>(())
+/// ```
+#[derive(Clone, Debug)]
+pub struct AssetsBackup {
+    path: PathBuf,
+    format: BackupFormat,
+    /// The directory within the archive containing `indexes` and `objects`,
+    /// ending in `/` unless they are at the top of the archive.
+    prefix: String,
+    /// The index files within the archive by id, along with their contents
+    /// if they were read while opening it.
+    indexes: BTreeMap<String, Option<Vec<u8>>>,
+}
+
+/// A member of a backup which is an index file or a hashed file.
+enum Member<'a> {
+    /// An index file, with its id.
+    Index(&'a str),
+    /// A hashed file, with its hash.
+    Object(&'a str),
+}
+
+impl AssetsBackup {
+    /// Opens the backup at `path`, finding the `indexes` and `objects`
+    /// directories within it.
+    ///
+    /// Fails with [`ExtractError::ReadBackup`] if `path` isn't an archive in
+    /// a known [format](BackupFormat), or it has neither directory.
+    pub fn open(path: &Path) -> Result<Self, ExtractError> {
+        let read_error = ExtractError::read_backup(path);
+        let format = match BackupFormat::detect(path) {
+            Ok(Some(format)) => format,
+            Ok(None) => {
+                return Err(read_error(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "not a zip or tar archive",
+                )));
+            }
+            Err(error) => return Err(read_error(error)),
+        };
+
+        let mut backup = Self {
+            path: path.to_owned(),
+            format,
+            prefix: String::new(),
+            indexes: BTreeMap::new(),
+        };
+        let mut prefix = None;
+
+        if format == BackupFormat::Zip {
+            let archive = backup.zip_archive()?;
+
+            for name in archive.file_names() {
+                backup.add_member(&mut prefix, name, || Ok(None))?;
+            }
+        } else {
+            let mut archive = backup.tar_archive()?;
+
+            for entry in archive.entries().map_err(ExtractError::read_backup(path))? {
+                let mut entry = entry.map_err(ExtractError::read_backup(path))?;
+                let name = String::from_utf8_lossy(&entry.path_bytes()).into_owned();
+
+                backup.add_member(&mut prefix, &name, || {
+                    let mut contents = Vec::new();
+                    entry
+                        .read_to_end(&mut contents)
+                        .map_err(ExtractError::read_backup(path))?;
+
+                    Ok(Some(contents))
+                })?;
+            }
+        }
+
+        backup.prefix = prefix.ok_or_else(|| {
+            ExtractError::read_backup(path)(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "no `indexes` or `objects` directory found in the archive",
+            ))
+        })?;
+
+        Ok(backup)
+    }
+
+    /// Records the member called `name`, taking the directory of the first
+    /// index file or hashed file found as that of every other. Index files
+    /// are stored with the contents returned by `read`.
+    fn add_member(
+        &mut self,
+        prefix: &mut Option<String>,
+        name: &str,
+        read: impl FnOnce() -> Result<Option<Vec<u8>>, ExtractError>,
+    ) -> Result<(), ExtractError> {
+        let Some((member_prefix, member)) = parse_member(name) else {
+            return Ok(());
+        };
+        let prefix = prefix.get_or_insert_with(|| member_prefix.to_owned());
+
+        if let Member::Index(id) = member
+            && member_prefix == prefix
+        {
+            let id = id.to_owned();
+            self.indexes.insert(id, read()?);
+        }
+
+        Ok(())
+    }
+
+    /// The path to the archive.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The format of the archive.
+    pub fn format(&self) -> BackupFormat {
+        self.format
+    }
+
+    /// The ids of the index files within the backup, sorted.
+    pub fn index_ids(&self) -> impl Iterator<Item = &str> {
+        self.indexes.keys().map(String::as_str)
+    }
+
+    /// Reads the index file at `location`: a [version](IndexFileLocation::Version)
+    /// from the `indexes` directory within the backup, or a
+    /// [file](IndexFileLocation::File) outside of it.
+    ///
+    /// Fails with [`ExtractError::MissingIndex`] if the backup has no index
+    /// file of that version.
+    pub fn index(&self, location: &IndexFileLocation) -> Result<IndexFile, ExtractError> {
+        let id = match location {
+            IndexFileLocation::File(path) => return IndexFile::from_path(path),
+            IndexFileLocation::Version(id) => id,
+        };
+        let name = format!("indexes/{id}.json");
+        let index_error = |source| ExtractError::Index {
+            path: self.member_path(&name),
+            source,
+        };
+
+        match self.indexes.get(id) {
+            Some(Some(contents)) => IndexFile::from_reader(&contents[..]).map_err(index_error),
+            Some(None) => {
+                let mut archive = self.zip_archive()?;
+                let entry = archive
+                    .by_name(&format!("{}{name}", self.prefix))
+                    .map_err(|error| index_error(IndexError::Io(error.into())))?;
+
+                IndexFile::from_reader(entry).map_err(index_error)
+            }
+            None => {
+                let mut available: Vec<_> = self.index_ids().map(str::to_owned).collect();
+                available.sort();
+
+                Err(ExtractError::MissingIndex {
+                    path: self.member_path(&name),
+                    suggestions: util::suggest(id, available.iter().cloned()),
+                    available,
+                })
+            }
+        }
+    }
+
+    /// Reads the hashed file of `object` for the asset at `path`.
+    ///
+    /// A tar archive is read up to the hashed file, so use
+    /// [`extract_hashed_assets`](AssetsBackup::extract_hashed_assets) to
+    /// read many.
+    pub fn read_object(&self, path: &Path, object: &Object) -> Result<Vec<u8>, ExtractError> {
+        if self.format == BackupFormat::Zip {
+            return self.read_zip_object(&mut self.zip_archive()?, path, object);
+        }
+
+        let mut archive = self.tar_archive()?;
+        for entry in archive
+            .entries()
+            .map_err(ExtractError::read_backup(&self.path))?
+        {
+            let mut entry = entry.map_err(ExtractError::read_backup(&self.path))?;
+            let name = String::from_utf8_lossy(&entry.path_bytes()).into_owned();
+
+            if self.is_object(&name, &object.hash) {
+                let mut contents = Vec::new();
+                entry
+                    .read_to_end(&mut contents)
+                    .map_err(|source| ExtractError::ReadObject {
+                        object_path: self.object_path(object),
+                        source,
+                    })?;
+
+                return Ok(contents);
+            }
+        }
+
+        Err(self.missing_object(path, object))
+    }
+
+    /// Extracts every object listed in `index` from the backup into the
+    /// [output directory](ExtractOptions::output_dir), like
+    /// [`hashed::extract_hashed_assets`](crate::hashed::extract_hashed_assets).
+    ///
+    /// Objects missing from the backup are reported as failures, as missing
+    /// hashed files are. Objects are extracted in the order they are found
+    /// within a tar archive.
+    pub fn extract_hashed_assets(
+        &self,
+        index: &IndexFile,
+        options: &ExtractOptions,
+        progress: &mut dyn ProgressSink,
+    ) -> Result<ExtractionReport, ExtractError> {
+        let index = &*options.included_objects(index);
+        let mut extraction = ObjectExtraction::begin(index, options, progress)?;
+
+        if self.format == BackupFormat::Zip {
+            let mut archive = self.zip_archive()?;
+
+            for (file_path, object) in &index.objects {
+                let read = || self.read_zip_object(&mut archive, file_path, object);
+
+                if let Err(stop) = extraction.extract(file_path, object, read) {
+                    return Ok(extraction.stop(stop));
+                }
+            }
+
+            return Ok(extraction.finish());
+        }
+
+        // The assets using each hash, which are extracted as soon as it is
+        // found within the archive.
+        let mut remaining: HashMap<&str, Vec<(&Path, &Object)>> = HashMap::new();
+        for (file_path, object) in &index.objects {
+            remaining
+                .entry(&object.hash)
+                .or_default()
+                .push((file_path, object));
+        }
+
+        let mut archive = self.tar_archive()?;
+        for entry in archive
+            .entries()
+            .map_err(ExtractError::read_backup(&self.path))?
+        {
+            if remaining.is_empty() {
+                break;
+            }
+
+            let mut entry = entry.map_err(ExtractError::read_backup(&self.path))?;
+            let name = String::from_utf8_lossy(&entry.path_bytes()).into_owned();
+            let Some(assets) = parse_member(&name)
+                .filter(|(prefix, _)| *prefix == self.prefix)
+                .and_then(|(_, member)| match member {
+                    Member::Object(hash) => remaining.remove(hash),
+                    Member::Index(_) => None,
+                })
+            else {
+                continue;
+            };
+
+            // Each asset using the hash gets its own copy of the contents, or
+            // of the error reading them.
+            let mut contents = Vec::new();
+            let read_result = entry.read_to_end(&mut contents);
+            for (file_path, object) in assets {
+                let read = || match &read_result {
+                    Ok(_) => Ok(contents.clone()),
+                    Err(error) => Err(ExtractError::ReadObject {
+                        object_path: self.object_path(object),
+                        source: io::Error::new(error.kind(), error.to_string()),
+                    }),
+                };
+
+                if let Err(stop) = extraction.extract(file_path, object, read) {
+                    return Ok(extraction.stop(stop));
+                }
+            }
+        }
+
+        let mut missing: Vec<_> = remaining.into_values().flatten().collect();
+        missing.sort_by_key(|(file_path, _)| *file_path);
+        for (file_path, object) in missing {
+            let read = || Err(self.missing_object(file_path, object));
+
+            if let Err(stop) = extraction.extract(file_path, object, read) {
+                return Ok(extraction.stop(stop));
+            }
+        }
+
+        Ok(extraction.finish())
+    }
+
+    /// Opens the archive as a zip archive.
+    fn zip_archive(&self) -> Result<ZipArchive<BufReader<File>>, ExtractError> {
+        let file = File::open(&self.path).map_err(ExtractError::read_backup(&self.path))?;
+
+        ZipArchive::new(BufReader::new(file))
+            .map_err(|error| ExtractError::read_backup(&self.path)(error.into()))
+    }
+
+    /// Opens the archive as a tar archive, decompressing it if needed.
+    fn tar_archive(&self) -> Result<tar::Archive<Box<dyn Read>>, ExtractError> {
+        let file = File::open(&self.path).map_err(ExtractError::read_backup(&self.path))?;
+        let reader: Box<dyn Read> = match self.format {
+            BackupFormat::TarGz => Box::new(MultiGzDecoder::new(BufReader::new(file))),
+            BackupFormat::Zip | BackupFormat::Tar => Box::new(BufReader::new(file)),
+        };
+
+        Ok(tar::Archive::new(reader))
+    }
+
+    /// Reads the hashed file of `object` for the asset at `path` from the
+    /// zip `archive`.
+    fn read_zip_object(
+        &self,
+        archive: &mut ZipArchive<BufReader<File>>,
+        path: &Path,
+        object: &Object,
+    ) -> Result<Vec<u8>, ExtractError> {
+        let name = format!("{}objects/{}", self.prefix, object_name(object));
+        let read_error = |source| ExtractError::ReadObject {
+            object_path: self.object_path(object),
+            source,
+        };
+
+        let mut entry = match archive.by_name(&name) {
+            Ok(entry) => entry,
+            Err(ZipError::FileNotFound) => return Err(self.missing_object(path, object)),
+            Err(error) => return Err(read_error(error.into())),
+        };
+        let mut contents = Vec::with_capacity(entry.size() as usize);
+        entry.read_to_end(&mut contents).map_err(read_error)?;
+
+        Ok(contents)
+    }
+
+    /// Whether the member called `name` is the hashed file with `hash`.
+    fn is_object(&self, name: &str, hash: &str) -> bool {
+        matches!(
+            parse_member(name),
+            Some((prefix, Member::Object(member_hash))) if prefix == self.prefix && member_hash == hash
+        )
+    }
+
+    /// Returns an [`ExtractError::MissingObject`] error for `object`.
+    fn missing_object(&self, path: &Path, object: &Object) -> ExtractError {
+        ExtractError::MissingObject {
+            path: path.to_owned(),
+            object_path: self.object_path(object),
+        }
+    }
+
+    /// Returns the path of the hashed file of `object`, as the archive's
+    /// path followed by the file's path within it.
+    fn object_path(&self, object: &Object) -> PathBuf {
+        self.member_path(&format!("objects/{}", object_name(object)))
+    }
+
+    /// Returns the path of `name` within the `indexes` and `objects`
+    /// directories, as the archive's path followed by its path within it.
+    fn member_path(&self, name: &str) -> PathBuf {
+        self.path.join(format!("{}{name}", self.prefix))
+    }
+}
+
+/// Returns the name of the hashed file of `object` within the `objects`
+/// directory of an archive, which always separates directories with `/`.
+fn object_name(object: &Object) -> String {
+    let parent = object.hash.get(..2).unwrap_or(&object.hash);
+
+    format!("{parent}/{}", object.hash)
+}
+
+/// Splits the name of a member of a backup into the directory containing its
+/// `indexes` or `objects` directory, and which index file or hashed file it
+/// is, if it is either.
+fn parse_member(name: &str) -> Option<(&str, Member<'_>)> {
+    let name = name.strip_prefix("./").unwrap_or(name);
+    let (dir, file) = name.rsplit_once('/')?;
+    let is_prefix = |prefix: &str| prefix.is_empty() || prefix.ends_with('/');
+
+    if let Some(prefix) = dir.strip_suffix("indexes")
+        && is_prefix(prefix)
+    {
+        let id = file.strip_suffix(".json").filter(|id| !id.is_empty())?;
+
+        return Some((prefix, Member::Index(id)));
+    }
+
+    let (objects_dir, parent) = dir.rsplit_once('/')?;
+    let prefix = objects_dir.strip_suffix("objects")?;
+
+    (is_prefix(prefix) && Object::is_valid_hash(file) && file.get(..2) == Some(parent))
+        .then_some((prefix, Member::Object(file)))
+}
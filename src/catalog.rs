@@ -0,0 +1,171 @@
+//! Catalogs of the files written by an extraction, grouped by namespace and
+//! by [`Category`], e.g. for a site to browse the extracted files.
+//!
+//! Catalogs are built from an [`ExtractionReport`], so the output directory
+//! isn't read again.
+
+use crate::category::{self, Category};
+use crate::report::{ExtractionReport, FileOutcome};
+use crate::search::FileSource;
+use crate::stats::Total;
+use crate::util;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// The files written by an extraction, grouped by namespace and by
+/// [`Category`].
+///
+/// Files without a namespace, such as `pack.mcmeta`, are listed under an
+/// empty namespace. Namespaces are sorted by name, categories in the order of
+/// [`Category::ALL`], and files by path, so that catalogs of different
+/// versions can be compared.
+///
+/// # Examples
+/// ```
+/// use extract_minecraft_assets::catalog::Catalog;
+/// use extract_minecraft_assets::category::Category;
+/// use extract_minecraft_assets::options::ExtractOptions;
+/// use extract_minecraft_assets::report::{ExtractionReport, FileOutcome, FileRecord};
+/// use extract_minecraft_assets::search::FileSource;
+///
+/// let mut report = ExtractionReport::new(ExtractOptions::new());
+/// report.files.push(FileRecord {
+///     path: "assets/minecraft/textures/block/stone.png".into(),
+///     source: FileSource::Jar,
+///     hash: None,
+///     outcome: FileOutcome::Written { bytes: 300 },
+/// });
+///
+/// let catalog = Catalog::from_report(&report);
+/// let textures = &catalog.namespaces["minecraft"][&Category::Textures];
+///
+/// assert_eq!(catalog.total.bytes, 300);
+/// assert_eq!(textures.files[0].source, FileSource::Jar);
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Catalog {
+    pub total: Total,
+    pub namespaces: BTreeMap<String, BTreeMap<Category, CatalogGroup>>,
+}
+
+/// The files of one namespace and category in a [`Catalog`].
+#[derive(Clone, PartialEq, Eq, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CatalogGroup {
+    pub total: Total,
+    /// The files, sorted by path.
+    pub files: Vec<CatalogEntry>,
+}
+
+/// A file listed in a [`Catalog`].
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CatalogEntry {
+    /// The path of the file relative to the output directory.
+    pub path: PathBuf,
+    /// The size of the file in bytes, as it was written.
+    pub size: u64,
+    pub source: FileSource,
+    /// The SHA-1 hash of the hashed asset the file was extracted from, if it
+    /// was extracted from an index file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hash: Option<String>,
+}
+
+impl Catalog {
+    /// Returns the catalog of the files written by the extraction of
+    /// `report`.
+    ///
+    /// Skipped and failed files aren't listed. A file written more than once,
+    /// as when hashed assets replace those of the jar file, is listed as it
+    /// was last written.
+    pub fn from_report(report: &ExtractionReport) -> Self {
+        let mut written = BTreeMap::new();
+        for file in &report.files {
+            if let FileOutcome::Written { bytes } = file.outcome {
+                written.insert(&file.path, (bytes, file));
+            }
+        }
+
+        let mut catalog = Self::default();
+        for (path, (size, file)) in written {
+            // Without the top-level directory, paths already start with the
+            // namespace, after the directory they are grouped into, if any.
+            let options = &report.options;
+            let within_root: PathBuf = path
+                .components()
+                .skip(
+                    usize::from(!options.ignore_top_level)
+                        + usize::from(options.group_by.is_some()),
+                )
+                .collect();
+            let group = catalog
+                .namespaces
+                .entry(
+                    category::namespace(&within_root)
+                        .unwrap_or_default()
+                        .to_owned(),
+                )
+                .or_default()
+                .entry(Category::of(&within_root))
+                .or_default();
+
+            catalog.total.add(size);
+            group.total.add(size);
+            group.files.push(CatalogEntry {
+                path: path.to_owned(),
+                size,
+                source: file.source,
+                hash: file.hash.clone(),
+            });
+        }
+
+        catalog
+    }
+
+    /// Writes the catalog as a Markdown document to `writer`, with a section
+    /// per namespace and a table of files per category.
+    pub fn write_markdown(&self, writer: &mut impl Write) -> io::Result<()> {
+        writeln!(writer, "# Catalog\n\n{}", describe(self.total))?;
+
+        for (namespace, categories) in &self.namespaces {
+            let namespace = match namespace.as_str() {
+                "" => "(no namespace)",
+                namespace => namespace,
+            };
+            writeln!(writer, "\n## {namespace}")?;
+
+            for (category, group) in categories {
+                writeln!(writer, "\n### {category}\n\n{}\n", describe(group.total))?;
+                writeln!(writer, "| Path | Size | Source | SHA-1 |")?;
+                writeln!(writer, "| --- | --- | --- | --- |")?;
+
+                for file in &group.files {
+                    writeln!(
+                        writer,
+                        "| `{}` | {} | {} | {} |",
+                        escape(&file.path),
+                        util::format_size(file.size),
+                        file.source.name(),
+                        file.hash.as_deref().unwrap_or_default()
+                    )?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Describes `total`, e.g. `12 files (3.4 KiB)`.
+fn describe(total: Total) -> String {
+    format!("{} files ({})", total.files, util::format_size(total.bytes))
+}
+
+/// Escapes the `|` in `path`, which would otherwise end a table cell.
+fn escape(path: &Path) -> String {
+    path.to_string_lossy().replace('|', "\\|")
+}
@@ -0,0 +1,172 @@
+//! Classifying files within `assets` and `data` by namespace and by
+//! [`Category`].
+//!
+//! Paths are relative to the `assets` or `data` directory, i.e. they start
+//! with the namespace, as in an [index file](crate::hashed::IndexFile):
+//! `minecraft/textures/block/stone.png`.
+
+use serde::Serialize;
+use std::fmt::{self, Display, Formatter};
+use std::path::{Component, Path};
+
+/// The kind of an asset or data file, decided by the directory it is in
+/// within its namespace.
+///
+/// # Examples
+/// ```
+/// use extract_minecraft_assets::category::Category;
+/// use std::path::Path;
+///
+/// assert_eq!(Category::of(Path::new("minecraft/textures/block/stone.png")), Category::Textures);
+/// assert_eq!(Category::of(Path::new("minecraft/recipe/stick.json")), Category::Recipes);
+/// assert_eq!(Category::of(Path::new("pack.mcmeta")), Category::Other);
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum Category {
+    Textures,
+    Models,
+    Blockstates,
+    Sounds,
+    Lang,
+    Fonts,
+    Shaders,
+    Particles,
+    Atlases,
+    Texts,
+    Worldgen,
+    Recipes,
+    LootTables,
+    Advancements,
+    Tags,
+    Structures,
+    /// Anything not in any other category, such as `pack.mcmeta`.
+    Other,
+}
+
+/// The directories (or files) within a namespace which make up each
+/// [`Category`].
+///
+/// Some `data` directories were renamed to the singular in 1.21, so both
+/// names are listed.
+const PREFIXES: &[(&str, Category)] = &[
+    ("textures", Category::Textures),
+    ("models", Category::Models),
+    ("blockstates", Category::Blockstates),
+    ("sounds", Category::Sounds),
+    ("sounds.json", Category::Sounds),
+    ("lang", Category::Lang),
+    ("font", Category::Fonts),
+    ("shaders", Category::Shaders),
+    ("particles", Category::Particles),
+    ("atlases", Category::Atlases),
+    ("texts", Category::Texts),
+    ("worldgen", Category::Worldgen),
+    ("recipes", Category::Recipes),
+    ("recipe", Category::Recipes),
+    ("loot_tables", Category::LootTables),
+    ("loot_table", Category::LootTables),
+    ("advancements", Category::Advancements),
+    ("advancement", Category::Advancements),
+    ("tags", Category::Tags),
+    ("structures", Category::Structures),
+    ("structure", Category::Structures),
+];
+
+impl Category {
+    /// Every category, in order.
+    pub const ALL: [Category; 17] = [
+        Self::Textures,
+        Self::Models,
+        Self::Blockstates,
+        Self::Sounds,
+        Self::Lang,
+        Self::Fonts,
+        Self::Shaders,
+        Self::Particles,
+        Self::Atlases,
+        Self::Texts,
+        Self::Worldgen,
+        Self::Recipes,
+        Self::LootTables,
+        Self::Advancements,
+        Self::Tags,
+        Self::Structures,
+        Self::Other,
+    ];
+
+    /// Returns the category of the file at `path`, relative to `assets` or
+    /// `data`.
+    pub fn of(path: &Path) -> Self {
+        let mut components = path.components();
+        let (Some(_), Some(Component::Normal(dir))) = (components.next(), components.next()) else {
+            return Self::Other;
+        };
+
+        PREFIXES
+            .iter()
+            .find(|(prefix, _)| dir == *prefix)
+            .map_or(Self::Other, |(_, category)| *category)
+    }
+
+    /// Returns the category named `name`, e.g. `loot_tables`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|category| category.name() == name)
+    }
+
+    /// The name of the category, e.g. `loot_tables`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Textures => "textures",
+            Self::Models => "models",
+            Self::Blockstates => "blockstates",
+            Self::Sounds => "sounds",
+            Self::Lang => "lang",
+            Self::Fonts => "fonts",
+            Self::Shaders => "shaders",
+            Self::Particles => "particles",
+            Self::Atlases => "atlases",
+            Self::Texts => "texts",
+            Self::Worldgen => "worldgen",
+            Self::Recipes => "recipes",
+            Self::LootTables => "loot_tables",
+            Self::Advancements => "advancements",
+            Self::Tags => "tags",
+            Self::Structures => "structures",
+            Self::Other => "other",
+        }
+    }
+}
+
+impl Display for Category {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Returns the namespace of the file at `path`, relative to `assets` or
+/// `data`, e.g. `minecraft` or `realms`.
+///
+/// Returns [`None`] for files directly within `assets` or `data`, such as
+/// `pack.mcmeta`.
+///
+/// # Examples
+/// ```
+/// use extract_minecraft_assets::category;
+/// use std::path::Path;
+///
+/// assert_eq!(category::namespace(Path::new("realms/lang/de_de.json")), Some("realms"));
+/// assert_eq!(category::namespace(Path::new("pack.mcmeta")), None);
+/// ```
+pub fn namespace(path: &Path) -> Option<&str> {
+    let mut components = path.components();
+    let Some(Component::Normal(namespace)) = components.next() else {
+        return None;
+    };
+
+    components.next()?;
+    namespace.to_str()
+}
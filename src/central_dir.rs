@@ -0,0 +1,291 @@
+//! Reads the central directory of a jar file directly.
+//!
+//! `zip` only keeps one entry for each name, using the last entry with the
+//! name in place of the first, so entries with the same name can't be found
+//! or chosen between through it. [`CentralDirectory`] lists every entry, and
+//! can leave out entries by presenting the archive with a rewritten central
+//! directory to `zip`.
+
+use std::collections::HashSet;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::sync::Arc;
+
+const EOCD_SIGNATURE: &[u8] = b"PK\x05\x06";
+const EOCD_SIZE: usize = 22;
+const ZIP64_LOCATOR_SIGNATURE: &[u8] = b"PK\x06\x07";
+const ZIP64_LOCATOR_SIZE: usize = 20;
+const ZIP64_EOCD_SIGNATURE: &[u8] = b"PK\x06\x06";
+const ZIP64_EOCD_SIZE: usize = 56;
+const HEADER_SIGNATURE: &[u8] = b"PK\x01\x02";
+const HEADER_SIZE: usize = 46;
+
+/// The central directory of a zip archive, with the records which end it.
+pub(crate) struct CentralDirectory {
+    /// Where the central directory starts within the file.
+    start: u64,
+    /// The header of each entry, in order.
+    headers: Vec<Header>,
+    /// Everything after the central directory: the end of central directory
+    /// record with the archive's comment, preceded by its zip64 versions if
+    /// any.
+    end: Vec<u8>,
+    /// Where the end of central directory record is within `end`.
+    eocd: usize,
+    /// Whether `end` starts with a zip64 end of central directory record.
+    zip64: bool,
+}
+
+/// The header of an entry in a [`CentralDirectory`].
+struct Header {
+    /// The raw bytes of the header, including the name, extra field and
+    /// comment.
+    bytes: Vec<u8>,
+    name_len: usize,
+}
+
+impl Header {
+    /// The name of the entry, as stored.
+    fn name(&self) -> &[u8] {
+        &self.bytes[HEADER_SIZE..HEADER_SIZE + self.name_len]
+    }
+}
+
+/// Returns an [`io::ErrorKind::InvalidData`] error with `message`.
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn read_u16(bytes: &[u8], at: usize) -> u16 {
+    u16::from_le_bytes([bytes[at], bytes[at + 1]])
+}
+
+fn read_u32(bytes: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+}
+
+fn read_u64(bytes: &[u8], at: usize) -> u64 {
+    u64::from_le_bytes(bytes[at..at + 8].try_into().unwrap())
+}
+
+/// Reads `len` bytes from `reader` starting at `position`.
+fn read_at(reader: &mut (impl Read + Seek), position: u64, len: usize) -> io::Result<Vec<u8>> {
+    let mut bytes = vec![0; len];
+    reader.seek(SeekFrom::Start(position))?;
+    reader.read_exact(&mut bytes)?;
+
+    Ok(bytes)
+}
+
+impl CentralDirectory {
+    /// Reads the central directory of the zip archive in `reader`.
+    pub(crate) fn read(reader: &mut (impl Read + Seek)) -> io::Result<Self> {
+        let len = reader.seek(SeekFrom::End(0))?;
+
+        // The record is followed by a comment of up to 65535 bytes.
+        let search_start = len.saturating_sub((EOCD_SIZE + usize::from(u16::MAX)) as u64);
+        let search = read_at(reader, search_start, (len - search_start) as usize)?;
+        let eocd_offset = (0..search.len().saturating_sub(EOCD_SIZE - 1))
+            .rev()
+            .find(|&at| {
+                &search[at..at + 4] == EOCD_SIGNATURE
+                    && at + EOCD_SIZE + usize::from(read_u16(&search, at + 20)) == search.len()
+            })
+            .ok_or_else(|| invalid("no end of central directory record"))?;
+        let eocd_position = search_start + eocd_offset as u64;
+        let eocd = &search[eocd_offset..];
+
+        let mut directory_end = eocd_position;
+        let mut directory_size = u64::from(read_u32(eocd, 12));
+        let mut entries = u64::from(read_u16(eocd, 10));
+        let mut zip64 = false;
+
+        if let Some(locator_position) = eocd_position.checked_sub(ZIP64_LOCATOR_SIZE as u64) {
+            let locator = read_at(reader, locator_position, ZIP64_LOCATOR_SIZE)?;
+
+            if &locator[..4] == ZIP64_LOCATOR_SIGNATURE {
+                let zip64_position = read_u64(&locator, 8);
+                let zip64_eocd = read_at(reader, zip64_position, ZIP64_EOCD_SIZE)?;
+                if &zip64_eocd[..4] != ZIP64_EOCD_SIGNATURE {
+                    return Err(invalid("no zip64 end of central directory record"));
+                }
+
+                directory_end = zip64_position;
+                directory_size = read_u64(&zip64_eocd, 40);
+                entries = read_u64(&zip64_eocd, 32);
+                zip64 = true;
+            }
+        }
+
+        let start = directory_end
+            .checked_sub(directory_size)
+            .ok_or_else(|| invalid("central directory larger than the archive"))?;
+        let directory = read_at(reader, start, directory_size as usize)?;
+        let end = read_at(reader, directory_end, (len - directory_end) as usize)?;
+
+        let mut headers = Vec::new();
+        let mut at = 0;
+        while at < directory.len() {
+            if directory.len() - at < HEADER_SIZE || &directory[at..at + 4] != HEADER_SIGNATURE {
+                return Err(invalid("invalid central directory header"));
+            }
+
+            let name_len = usize::from(read_u16(&directory, at + 28));
+            let header_len = HEADER_SIZE
+                + name_len
+                + usize::from(read_u16(&directory, at + 30))
+                + usize::from(read_u16(&directory, at + 32));
+            let bytes = directory
+                .get(at..at + header_len)
+                .ok_or_else(|| invalid("invalid central directory header"))?;
+
+            headers.push(Header {
+                bytes: bytes.to_owned(),
+                name_len,
+            });
+            at += header_len;
+        }
+
+        // The entry count is truncated for zip64 archives without a zip64
+        // record, so is only checked otherwise.
+        if (zip64 || entries != u64::from(u16::MAX)) && entries != headers.len() as u64 {
+            return Err(invalid("central directory entry count doesn't match"));
+        }
+
+        Ok(Self {
+            start,
+            headers,
+            eocd: (eocd_position - directory_end) as usize,
+            end,
+            zip64,
+        })
+    }
+
+    /// The names of the entries as stored, in order.
+    pub(crate) fn names(&self) -> impl Iterator<Item = &[u8]> {
+        self.headers.iter().map(Header::name)
+    }
+
+    /// Returns `reader` as an archive where only the first entry with each
+    /// name is listed.
+    ///
+    /// `reader` must be the archive this central directory was read from.
+    pub(crate) fn first_entries<R>(&self, reader: R) -> Rewritten<R> {
+        let mut seen = HashSet::new();
+        let headers: Vec<_> = self
+            .headers
+            .iter()
+            .filter(|header| seen.insert(header.name()))
+            .collect();
+
+        let mut tail: Vec<u8> = headers
+            .iter()
+            .flat_map(|header| header.bytes.iter().copied())
+            .collect();
+        let directory_size = tail.len() as u64;
+        let removed = self
+            .headers
+            .iter()
+            .map(|header| header.bytes.len())
+            .sum::<usize>() as u64
+            - directory_size;
+        let entries = headers.len() as u64;
+
+        let mut end = self.end.clone();
+        if self.zip64 {
+            end[24..32].copy_from_slice(&entries.to_le_bytes());
+            end[32..40].copy_from_slice(&entries.to_le_bytes());
+            end[40..48].copy_from_slice(&directory_size.to_le_bytes());
+
+            // The zip64 record moves back by as much as the directory shrinks.
+            let locator = self.eocd - ZIP64_LOCATOR_SIZE;
+            let zip64_position = read_u64(&end, locator + 8) - removed;
+            end[locator + 8..locator + 16].copy_from_slice(&zip64_position.to_le_bytes());
+        }
+
+        // Fields which are all ones defer to the zip64 record.
+        let eocd = self.eocd;
+        for at in [eocd + 8, eocd + 10] {
+            if read_u16(&end, at) != u16::MAX {
+                end[at..at + 2].copy_from_slice(&(entries as u16).to_le_bytes());
+            }
+        }
+        if read_u32(&end, eocd + 12) != u32::MAX {
+            end[eocd + 12..eocd + 16].copy_from_slice(&(directory_size as u32).to_le_bytes());
+        }
+
+        tail.extend_from_slice(&end);
+
+        Rewritten {
+            inner: reader,
+            replaced: Some((self.start, tail.into())),
+            position: 0,
+        }
+    }
+}
+
+/// A zip archive read from `R`, with its central directory possibly
+/// replaced by a [`CentralDirectory`].
+#[derive(Clone)]
+pub(crate) struct Rewritten<R> {
+    inner: R,
+    /// Where the central directory starts, and the bytes replacing everything
+    /// from there.
+    replaced: Option<(u64, Arc<[u8]>)>,
+    position: u64,
+}
+
+impl<R> Rewritten<R> {
+    /// Returns `reader` as it is.
+    pub(crate) fn unchanged(reader: R) -> Self {
+        Self {
+            inner: reader,
+            replaced: None,
+            position: 0,
+        }
+    }
+}
+
+impl<R: Read + Seek> Read for Rewritten<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let Some((start, tail)) = &self.replaced else {
+            return self.inner.read(buf);
+        };
+
+        let read = if self.position < *start {
+            let len = buf.len().min((*start - self.position) as usize);
+            self.inner.seek(SeekFrom::Start(self.position))?;
+            self.inner.read(&mut buf[..len])?
+        } else {
+            let offset = ((self.position - *start) as usize).min(tail.len());
+            let mut rest = &tail[offset..];
+            rest.read(buf)?
+        };
+        self.position += read as u64;
+
+        Ok(read)
+    }
+}
+
+impl<R: Read + Seek> Seek for Rewritten<R> {
+    fn seek(&mut self, position: SeekFrom) -> io::Result<u64> {
+        let Some((start, tail)) = &self.replaced else {
+            return self.inner.seek(position);
+        };
+
+        let len = *start + tail.len() as u64;
+        let position = match position {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => len.checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+        }
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "seek to a negative or overflowing position",
+            )
+        })?;
+        self.position = position;
+
+        Ok(position)
+    }
+}
@@ -0,0 +1,380 @@
+//! Undoing an extraction, by deleting the files which its
+//! [report](crate::report::ExtractionReport) records it wrote, and checking
+//! whether those files were changed since.
+
+use crate::diff;
+use crate::error::{ExtractError, IoContext};
+use crate::util;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs::{self, File};
+use std::io::{self, BufReader, Read};
+use std::num::NonZero;
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::{panic, thread};
+
+/// The files written by an extraction, as read back from its serialized
+/// [report](crate::report::ExtractionReport).
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct RecordedExtraction {
+    /// The directory the extraction wrote to.
+    pub output_dir: PathBuf,
+    /// The files the extraction wrote.
+    pub files: Vec<RecordedFile>,
+    /// The number of files the report records which the extraction didn't
+    /// write, as they were skipped or failed, or as their paths lead outside
+    /// of the output directory.
+    pub skipped: usize,
+    /// The paths of the files the report records which the extraction
+    /// didn't write, within the output directory.
+    unwritten: Vec<PathBuf>,
+    /// Whether files may have been converted while they were written, in
+    /// which case their hashes are those of the originals.
+    transformed: bool,
+}
+
+/// A file written by an extraction.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct RecordedFile {
+    /// The path of the file relative to the output directory.
+    pub path: PathBuf,
+    /// The number of bytes written.
+    pub bytes: u64,
+    /// The SHA-1 hash of the hashed asset the file was extracted from, if it
+    /// was extracted from an index file.
+    pub hash: Option<String>,
+}
+
+/// The parts of a serialized report needed to undo its extraction.
+#[derive(Deserialize)]
+struct ReportDocument {
+    files: Vec<ReportFile>,
+    options: ReportOptions,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReportFile {
+    path: PathBuf,
+    outcome: String,
+    bytes: Option<u64>,
+    hash: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReportOptions {
+    output_dir: PathBuf,
+    convert_lang: Option<serde_json::Value>,
+    convert_nbt: Option<serde_json::Value>,
+    format_json: Option<serde_json::Value>,
+    text_normalize: Option<serde_json::Value>,
+}
+
+impl RecordedExtraction {
+    /// Parses a report serialized as JSON, e.g. with `--report`, from
+    /// `reader`.
+    pub fn from_reader(reader: impl Read) -> Result<Self, serde_json::Error> {
+        let report: ReportDocument = serde_json::from_reader(BufReader::new(reader))?;
+        let options = report.options;
+
+        let mut extraction = Self {
+            output_dir: options.output_dir,
+            files: Vec::new(),
+            skipped: 0,
+            unwritten: Vec::new(),
+            transformed: options.convert_lang.is_some()
+                || options.convert_nbt.is_some()
+                || options.format_json.is_some()
+                || options.text_normalize.is_some(),
+        };
+        for file in report.files {
+            // A report which was edited could otherwise delete anything.
+            let within_output_dir = file
+                .path
+                .components()
+                .all(|component| matches!(component, Component::Normal(_)));
+
+            match file.bytes {
+                Some(bytes) if file.outcome == "written" && within_output_dir => {
+                    extraction.files.push(RecordedFile {
+                        path: file.path,
+                        bytes,
+                        hash: file.hash,
+                    });
+                }
+                _ => {
+                    extraction.skipped += 1;
+                    if within_output_dir {
+                        extraction.unwritten.push(file.path);
+                    }
+                }
+            }
+        }
+
+        Ok(extraction)
+    }
+
+    /// Reads and parses the report at `path`.
+    pub fn from_path(path: &Path) -> Result<Self, ExtractError> {
+        let file = File::open(path).reading_output(path)?;
+
+        Self::from_reader(file)
+            .map_err(io::Error::from)
+            .reading_output(path)
+    }
+}
+
+/// The files written by an extraction, sorted by whether they can be
+/// deleted to undo it, as found by [`plan`].
+#[derive(Clone, PartialEq, Eq, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CleanPlan {
+    /// The files which are as the extraction wrote them, relative to the
+    /// output directory.
+    pub unchanged: Vec<PathBuf>,
+    /// The files which were modified since they were written, as their sizes
+    /// or hashes no longer match the report, relative to the output
+    /// directory.
+    pub modified: Vec<PathBuf>,
+    /// The files which no longer exist, relative to the output directory.
+    pub missing: Vec<PathBuf>,
+    /// The number of files the report records which the extraction didn't
+    /// write.
+    pub skipped: usize,
+}
+
+/// Finds which of the files written by `extraction` are as it wrote them.
+///
+/// Every file's size is compared with the report, and the contents of
+/// hashed assets with their hashes too, unless files were converted while
+/// they were written. The files of jar files are only compared by size.
+pub fn plan(extraction: &RecordedExtraction) -> Result<CleanPlan, ExtractError> {
+    plan_with(extraction, true)
+}
+
+fn plan_with(
+    extraction: &RecordedExtraction,
+    compare_hashes: bool,
+) -> Result<CleanPlan, ExtractError> {
+    let mut plan = CleanPlan {
+        skipped: extraction.skipped,
+        ..CleanPlan::default()
+    };
+
+    for (file, state) in extraction
+        .files
+        .iter()
+        .zip(check_files(extraction, compare_hashes)?)
+    {
+        let paths = match state {
+            FileState::Unchanged => &mut plan.unchanged,
+            FileState::Modified => &mut plan.modified,
+            FileState::Missing => &mut plan.missing,
+        };
+        paths.push(file.path.clone());
+    }
+
+    plan.unchanged.sort();
+    plan.modified.sort();
+    plan.missing.sort();
+
+    Ok(plan)
+}
+
+/// Whether a file written by an extraction is as it wrote it.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum FileState {
+    Unchanged,
+    Modified,
+    Missing,
+}
+
+/// Compares each of the files written by `extraction` with the report, in
+/// order, comparing hashes too if `compare_hashes` is set.
+///
+/// Files are hashed on as many threads as there are CPUs, as hashing is what
+/// takes the longest. The first error reading any file is returned.
+fn check_files(
+    extraction: &RecordedExtraction,
+    compare_hashes: bool,
+) -> Result<Vec<FileState>, ExtractError> {
+    let files = &extraction.files;
+    let compare_hashes = compare_hashes && !extraction.transformed;
+    let threads = thread::available_parallelism()
+        .map_or(1, NonZero::get)
+        .min(files.len().max(1));
+    let next = AtomicUsize::new(0);
+
+    let mut checked: Vec<_> = thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut checked = Vec::new();
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(file) = files.get(index) else {
+                            break checked;
+                        };
+                        let full_path = extraction.output_dir.join(&file.path);
+                        checked.push((index, check_file(file, &full_path, compare_hashes)));
+                    }
+                })
+            })
+            .collect();
+
+        workers
+            .into_iter()
+            .flat_map(|worker| {
+                worker
+                    .join()
+                    .unwrap_or_else(|error| panic::resume_unwind(error))
+            })
+            .collect()
+    });
+    checked.sort_by_key(|(index, _)| *index);
+
+    checked.into_iter().map(|(_, state)| state).collect()
+}
+
+/// Compares the file at `full_path` with the report's `file`.
+fn check_file(
+    file: &RecordedFile,
+    full_path: &Path,
+    compare_hashes: bool,
+) -> Result<FileState, ExtractError> {
+    let metadata = match fs::symlink_metadata(full_path) {
+        Ok(metadata) => metadata,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(FileState::Missing),
+        Err(error) => return Err(ExtractError::read_output(full_path)(error)),
+    };
+
+    let unchanged = metadata.is_file()
+        && metadata.len() == file.bytes
+        && match &file.hash {
+            Some(hash) if compare_hashes => {
+                let contents = File::open(full_path).reading_output(full_path)?;
+                util::sha1_hex(contents).reading_output(full_path)? == *hash
+            }
+            _ => true,
+        };
+
+    Ok(if unchanged {
+        FileState::Unchanged
+    } else {
+        FileState::Modified
+    })
+}
+
+/// How the files of an extraction changed since it, as found by [`verify`].
+#[derive(Clone, PartialEq, Eq, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Verification {
+    /// The files which are as the extraction wrote them, relative to the
+    /// output directory.
+    pub unchanged: Vec<PathBuf>,
+    /// The files which were modified since they were written, relative to
+    /// the output directory.
+    pub modified: Vec<PathBuf>,
+    /// The files which no longer exist, relative to the output directory.
+    pub missing: Vec<PathBuf>,
+    /// The files the report doesn't record, within the directories of the
+    /// output directory it wrote to, relative to the output directory.
+    pub untracked: Vec<PathBuf>,
+    /// The number of files the report records which the extraction didn't
+    /// write.
+    pub skipped: usize,
+}
+
+impl Verification {
+    /// Whether nothing changed since the extraction.
+    pub fn is_clean(&self) -> bool {
+        self.modified.is_empty() && self.missing.is_empty() && self.untracked.is_empty()
+    }
+}
+
+/// Finds which of the files written by `extraction` were modified or
+/// deleted since, and which files were added beside them.
+///
+/// Files are compared as by [`plan`], but only by size if `size_only` is
+/// set. Files are untracked if they are within a directory of the output
+/// directory the extraction wrote to, e.g. `assets/`, and the report doesn't
+/// record them.
+pub fn verify(
+    extraction: &RecordedExtraction,
+    size_only: bool,
+) -> Result<Verification, ExtractError> {
+    let plan = plan_with(extraction, !size_only)?;
+
+    let recorded: BTreeSet<_> = extraction
+        .files
+        .iter()
+        .map(|file| file.path.as_path())
+        .chain(extraction.unwritten.iter().map(PathBuf::as_path))
+        .collect();
+    let roots: BTreeSet<_> = extraction
+        .files
+        .iter()
+        .filter_map(|file| file.path.components().next())
+        .collect();
+
+    let mut found = BTreeSet::new();
+    for root in roots {
+        let root = Path::new(&root);
+        if extraction.output_dir.join(root).is_dir() {
+            diff::find_files(&extraction.output_dir, root, &mut found)?;
+        }
+    }
+    let untracked = found
+        .into_iter()
+        .filter(|path| !recorded.contains(path.as_path()))
+        .collect();
+
+    Ok(Verification {
+        unchanged: plan.unchanged,
+        modified: plan.modified,
+        missing: plan.missing,
+        untracked,
+        skipped: plan.skipped,
+    })
+}
+
+impl CleanPlan {
+    /// Returns the files to delete, leaving out the
+    /// [modified](CleanPlan::modified) files unless `force` is set.
+    pub fn deleted(&self, force: bool) -> impl Iterator<Item = &Path> {
+        let modified = if force { &self.modified[..] } else { &[] };
+
+        self.unchanged.iter().chain(modified).map(PathBuf::as_path)
+    }
+
+    /// Deletes the [unchanged](CleanPlan::unchanged) files, and the
+    /// [modified](CleanPlan::modified) ones too if `force` is set, from
+    /// `output_dir`, along with the directories left empty within it.
+    /// Returns the number of files deleted.
+    pub fn apply(&self, output_dir: &Path, force: bool) -> Result<usize, ExtractError> {
+        let mut deleted = 0;
+
+        // The directories of missing files are pruned too, if left empty.
+        let missing = self.missing.iter().map(PathBuf::as_path);
+        for path in self.deleted(force).chain(missing) {
+            let full_path = output_dir.join(path);
+            match fs::remove_file(&full_path) {
+                Ok(()) => deleted += 1,
+                Err(error) if error.kind() == io::ErrorKind::NotFound => {}
+                Err(error) => return Err(ExtractError::write_file(full_path)(error)),
+            }
+
+            // Directories which still have something in them fail to be
+            // removed, stopping at the first. The output directory is kept.
+            for dir in path.ancestors().skip(1) {
+                if dir == Path::new("") || fs::remove_dir(output_dir.join(dir)).is_err() {
+                    break;
+                }
+            }
+        }
+
+        Ok(deleted)
+    }
+}
@@ -0,0 +1,308 @@
+use crate::cli::error::Error;
+use crate::cli::progress::{self, TerminalProgress};
+use crate::cli::{source, written};
+use crate::{ExtractCmd, Options};
+use clap::Args;
+use extract_minecraft_assets::hashed::IndexFile;
+use extract_minecraft_assets::jar::ExtractedContents;
+use extract_minecraft_assets::options::ExtractOptions;
+use extract_minecraft_assets::pool::{ContentPool, PoolSummary};
+use extract_minecraft_assets::report::ExtractionReport;
+use extract_minecraft_assets::version::Version;
+use extract_minecraft_assets::{hashed, jar, util};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The directory within the output directory of the contents every version's
+/// files are linked to.
+const POOL_DIR: &str = ".pool";
+/// The directory within the output directory which versions are extracted
+/// into, before being moved into place once finished.
+const STAGING_DIR: &str = ".staging";
+
+#[derive(Args)]
+pub struct AllSubcommand {
+    /// The path to the `.minecraft/versions/` directory to find the versions
+    /// in.
+    ///
+    /// Every directory within it with a `.jar` file and manifest is extracted.
+    /// Defaults to the default location on your OS.
+    #[arg(long = "versions", value_name = "DIRECTORY")]
+    versions_dir: Option<PathBuf>,
+    /// The path to the `.minecraft/assets/` directory to find hashed assets.
+    ///
+    /// Defaults to the default location on your OS.
+    #[arg(long = "hashed-assets", value_name = "DIRECTORY")]
+    hashed_assets_dir: Option<PathBuf>,
+
+    /// Which contents to extract.
+    #[command(flatten)]
+    contents: jar::ContentsArgs,
+}
+
+impl ExtractCmd for AllSubcommand {
+    fn source_dirs(&self) -> Vec<PathBuf> {
+        let versions_dir = self.versions_dir.clone().or_else(util::versions_dir);
+        let hashed_assets_dir = self
+            .hashed_assets_dir
+            .clone()
+            .or_else(util::hashed_assets_dir);
+
+        [versions_dir, hashed_assets_dir]
+            .into_iter()
+            .flatten()
+            .collect()
+    }
+
+    fn execute(self, options: &Options) -> Result<ExtractionReport, Error> {
+        let extracted_contents = ExtractedContents::from(self.contents);
+        let mut report =
+            ExtractionReport::new(options.extract.clone().contents(extracted_contents.clone()));
+
+        if extracted_contents.is_empty() {
+            return Ok(report);
+        }
+
+        let probed = match self.versions_dir {
+            Some(versions_dir) => vec![versions_dir],
+            None => util::minecraft_dir_candidates()
+                .into_iter()
+                .map(|dir| dir.join("versions"))
+                .collect(),
+        };
+        let versions = probed
+            .iter()
+            .find(|path| util::is_dir(path))
+            .map(|versions_dir| Version::installed(versions_dir))
+            .unwrap_or_default();
+        if versions.is_empty() {
+            return Err(Error::NoVersions { probed });
+        }
+
+        // Hashed assets are only needed to extract `assets`.
+        let hashed_assets_dir = if extracted_contents.contains("assets") {
+            Some(source::hashed_assets_dir(self.hashed_assets_dir)?)
+        } else {
+            None
+        };
+
+        // Versions which were moved into place were finished by an earlier
+        // run.
+        let output_dir = &options.extract.output_dir;
+        let (finished, pending): (Vec<_>, Vec<_>) = versions
+            .into_iter()
+            .partition(|version| output_dir.join(version.name()).exists());
+        if !finished.is_empty() {
+            let names: Vec<_> = finished.iter().map(Version::name).collect();
+            eprintln!(
+                "Skipping {} already extracted: {}",
+                versions_noun(names.len()),
+                names.join(", ")
+            );
+        }
+
+        // An index file which can't be read only fails its own version.
+        let pending: Vec<_> = pending
+            .into_iter()
+            .map(|version| {
+                let index = hashed_assets_dir
+                    .as_deref()
+                    .map(|hashed_assets_dir| source::version_index(&version, hashed_assets_dir))
+                    .transpose()
+                    .map(|index| {
+                        index.map(|index| options.extract.included_objects(&index).into_owned())
+                    });
+
+                (version, index)
+            })
+            .collect();
+
+        // Hashed assets are counted once however many versions use them, but
+        // jar files in full, as their entries are only hashed once extracted.
+        let mut hashes = HashSet::new();
+        let mut size = 0;
+        for (version, index) in &pending {
+            if let Ok(files) = jar::list_files(&version.jar_file(), &extracted_contents) {
+                size += files
+                    .iter()
+                    .filter(|(path, _)| options.extract.includes(path))
+                    .map(|(_, size)| size)
+                    .sum::<u64>();
+            }
+            for object in index
+                .iter()
+                .flatten()
+                .flat_map(|index| index.objects.values())
+            {
+                if hashes.insert(&object.hash) {
+                    size += object.size;
+                }
+            }
+        }
+        options.confirm_size(size)?;
+
+        let pool = ContentPool::new(output_dir.join(POOL_DIR));
+        let staging_dir = output_dir.join(STAGING_DIR);
+        let mut total = PoolSummary::default();
+        let mut extracted_versions = 0;
+        let mut failed = Vec::new();
+
+        for (version, index) in pending {
+            let name = version.name();
+            let staged = staging_dir.join(name);
+            let version_options = options
+                .extract
+                .clone()
+                .output(&staged)
+                .contents(extracted_contents.clone());
+
+            // A staged version was left by a run which didn't finish it.
+            if staged.exists() {
+                fs::remove_dir_all(&staged)?;
+            }
+            fs::create_dir_all(&staged)?;
+
+            progress::message(format!("Extracting {extracted_contents} from {name}..."));
+            let extracted = index.map_err(Error::from).and_then(|index| {
+                let mut version_report = extract_version(
+                    &version,
+                    hashed_assets_dir.as_deref().zip(index.as_ref()),
+                    &version_options,
+                    options.porcelain,
+                )?;
+
+                // Versions with failures are left staged to be redone.
+                let pooled =
+                    if version_report.summary.stopped() || version_report.summary.failures > 0 {
+                        None
+                    } else {
+                        let pooled = pool.add_extracted(&version_report)?;
+                        fs::rename(&staged, output_dir.join(name))?;
+                        // Only listed once moved into place, as they were
+                        // written while staged.
+                        for file in version_report.written() {
+                            written::record(&Path::new(name).join(&file.path));
+                        }
+                        Some(pooled)
+                    };
+
+                let within = if pooled.is_some() {
+                    PathBuf::from(name)
+                } else {
+                    Path::new(STAGING_DIR).join(name)
+                };
+                for file in &mut version_report.files {
+                    file.path = within.join(&file.path);
+                }
+                for warning in &mut version_report.warnings {
+                    warning.path = within.join(&warning.path);
+                }
+
+                Ok((version_report, pooled))
+            });
+
+            match extracted {
+                Ok((version_report, Some(pooled))) => {
+                    progress::message(format!("{name}: {}", pooled_message(&pooled)));
+                    total = total + pooled;
+                    extracted_versions += 1;
+                    report = report + version_report;
+                }
+                Ok((version_report, None)) => {
+                    let stopped = version_report.summary.stopped();
+                    if !stopped {
+                        failed.push(name.to_owned());
+                    }
+                    report = report + version_report;
+
+                    if stopped {
+                        break;
+                    }
+                }
+                Err(error) => {
+                    progress::message(format!("Failed to extract {name}: {error}"));
+                    failed.push(name.to_owned());
+                    report.summary.failures += 1;
+                }
+            }
+        }
+
+        // Nothing is left staged once every version is finished.
+        let _ = fs::remove_dir(&staging_dir);
+
+        eprintln!(
+            "{} extracted: {}",
+            versions_noun(extracted_versions),
+            pooled_message(&total)
+        );
+        if !failed.is_empty() {
+            eprintln!(
+                "{} failed, and will be extracted again by the next run: {}",
+                versions_noun(failed.len()),
+                failed.join(", ")
+            );
+        }
+
+        Ok(report)
+    }
+}
+
+/// Extracts the jar file of `version`, and its hashed assets from the
+/// directory and index file in `hashed`, if any.
+fn extract_version(
+    version: &Version,
+    hashed: Option<(&Path, &IndexFile)>,
+    options: &ExtractOptions,
+    porcelain: bool,
+) -> Result<ExtractionReport, Error> {
+    let jar = version.jar_file();
+    let jar_entries = jar::list_files(&jar, &options.contents)?
+        .iter()
+        .filter(|(path, _)| options.includes(path))
+        .count();
+
+    let mut progress = TerminalProgress::new(porcelain);
+    progress.add_phase(
+        format!("{} jar {}", version.name(), options.contents),
+        jar_entries,
+    );
+    if let Some((_, index)) = hashed {
+        progress.add_phase(format!("{} hashed assets", version.name()), index.len());
+    }
+
+    let mut report = jar::extract_jar(&jar, options, &mut progress)?;
+
+    // The hashed assets are left for later if the output volume is full.
+    if let Some((_, index)) = hashed
+        && report.summary.disk_full
+    {
+        report.summary.remaining_bytes += index.total_size();
+    } else if let Some((hashed_assets_dir, index)) = hashed {
+        report = report
+            + hashed::extract_hashed_assets(hashed_assets_dir, index, options, &mut progress)?;
+    }
+
+    progress.clear()?;
+
+    Ok(report)
+}
+
+/// Describes the sizes of `pooled` files before and after deduplication.
+fn pooled_message(pooled: &PoolSummary) -> String {
+    format!(
+        "{} in {} {}, {} after deduplication",
+        util::format_size(pooled.bytes),
+        pooled.files,
+        if pooled.files == 1 { "file" } else { "files" },
+        util::format_size(pooled.stored_bytes())
+    )
+}
+
+/// Returns e.g. `1 version` or `2 versions`.
+fn versions_noun(count: usize) -> String {
+    format!(
+        "{count} {}",
+        if count == 1 { "version" } else { "versions" }
+    )
+}
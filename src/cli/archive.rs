@@ -0,0 +1,169 @@
+//! Writing the extracted files into a zip archive rather than the output
+//! directory, with `--output-zip`.
+
+use crate::cli::error::Error;
+use crate::cli::json::Document;
+use clap::Args;
+use extract_minecraft_assets::archive::{self, Volume};
+use extract_minecraft_assets::report::ExtractionReport;
+use extract_minecraft_assets::util;
+use extract_minecraft_assets::vfs::{MemoryFs, Vfs};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Writing the extracted files into a zip archive, shared by the extraction
+/// subcommands.
+#[derive(Args)]
+pub struct ArchiveArgs {
+    /// Write the extracted files into this zip file rather than into the
+    /// output directory.
+    ///
+    /// Files are extracted into memory, and written to the zip file with
+    /// their paths relative to the output directory once the extraction
+    /// finishes. Nothing is written if the extraction stopped early.
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with_all = ["sync", "as_resource_pack", "catalog", "print0", "print", "written_list"],
+        global = true
+    )]
+    output_zip: Option<PathBuf>,
+    /// Split the zip file into volumes of at most this size each, e.g.
+    /// `95M`, which can each be extracted on their own.
+    ///
+    /// Files are never split across volumes: the largest files are placed
+    /// first, each in the first volume with room for it, and nothing is
+    /// written if a file doesn't fit in a volume of its own. Several volumes
+    /// are written beside the zip file's path, e.g. `pack.part1.zip` and
+    /// `pack.part2.zip` for `pack.zip`, along with `pack.volumes.json`
+    /// giving the volume each file is in.
+    #[arg(
+        long,
+        value_name = "SIZE",
+        value_parser = util::parse_size,
+        requires = "output_zip",
+        global = true
+    )]
+    split: Option<u64>,
+}
+
+/// A zip archive to write the extracted files into once they are extracted
+/// into memory.
+pub struct Archive {
+    fs: Arc<MemoryFs>,
+    path: PathBuf,
+    split: Option<u64>,
+}
+
+/// The document written beside the volumes of a split archive.
+#[derive(Serialize)]
+struct VolumesDocument<'a> {
+    volumes: Vec<VolumeDocument>,
+    /// The name of the volume each file is in, by the file's path relative
+    /// to the output directory.
+    files: BTreeMap<&'a Path, String>,
+}
+
+#[derive(Serialize)]
+struct VolumeDocument {
+    name: String,
+    size: u64,
+    files: usize,
+}
+
+impl ArchiveArgs {
+    /// Returns the archive to write, if --output-zip is given.
+    pub fn prepare(self) -> Option<Archive> {
+        Some(Archive {
+            fs: Arc::new(MemoryFs::new()),
+            path: self.output_zip?,
+            split: self.split,
+        })
+    }
+}
+
+impl Archive {
+    /// The filesystem to extract into.
+    pub fn vfs(&self) -> Arc<dyn Vfs> {
+        self.fs.clone()
+    }
+
+    /// Writes the files extracted in `report` into the archive, along with
+    /// the document listing its volumes if it is split.
+    ///
+    /// Does nothing if the extraction stopped early.
+    pub fn finish(&self, report: &ExtractionReport) -> Result<(), Error> {
+        if report.summary.stopped() {
+            return Ok(());
+        }
+
+        let volumes = archive::write_archive(&self.fs, report, &self.path, self.split)?;
+
+        if let Some(limit) = self.split {
+            let path = self.path.with_extension("volumes.json");
+            Document::new(volumes_document(&volumes)).write(&path)?;
+
+            let sizes: Vec<_> = volumes
+                .iter()
+                .map(|volume| {
+                    format!(
+                        "'{}' ({})",
+                        volume.path.display(),
+                        util::format_size(volume.size)
+                    )
+                })
+                .collect();
+            eprintln!(
+                "Wrote {} of at most {}: {}",
+                if volumes.len() == 1 {
+                    "1 volume".to_owned()
+                } else {
+                    format!("{} volumes", volumes.len())
+                },
+                util::format_size(limit),
+                sizes.join(", ")
+            );
+        } else if let Some(volume) = volumes.first() {
+            eprintln!(
+                "Wrote '{}' ({})",
+                volume.path.display(),
+                util::format_size(volume.size)
+            );
+        }
+
+        Ok(())
+    }
+}
+
+fn volumes_document(volumes: &[Volume]) -> VolumesDocument<'_> {
+    let name = |volume: &Volume| {
+        volume
+            .path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned()
+    };
+
+    VolumesDocument {
+        volumes: volumes
+            .iter()
+            .map(|volume| VolumeDocument {
+                name: name(volume),
+                size: volume.size,
+                files: volume.files.len(),
+            })
+            .collect(),
+        files: volumes
+            .iter()
+            .flat_map(|volume| {
+                volume
+                    .files
+                    .iter()
+                    .map(move |path| (path.as_path(), name(volume)))
+            })
+            .collect(),
+    }
+}
@@ -0,0 +1,611 @@
+use crate::cli::error::Error;
+#[cfg(feature = "git")]
+use crate::cli::git;
+use crate::cli::{source, written};
+use crate::{ExtractCmd, Options};
+use clap::Args;
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::{Attribute, Print, SetAttribute};
+use crossterm::terminal::{self, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{cursor, execute, queue};
+use extract_minecraft_assets::hashed::{self, IndexFile};
+use extract_minecraft_assets::jar::{self, ExtractedContents};
+use extract_minecraft_assets::options::ExtractOptions;
+use extract_minecraft_assets::progress::NoProgress;
+use extract_minecraft_assets::report::ExtractionReport;
+use extract_minecraft_assets::util;
+use extract_minecraft_assets::version::Version;
+use std::collections::{BTreeMap, HashSet};
+use std::io::{self, IsTerminal, Write};
+use std::panic;
+use std::path::{Path, PathBuf};
+
+#[derive(Args)]
+pub struct BrowseSubcommand {
+    /// The directory containing the version `.jar` file and manifest.
+    ///
+    /// Can be a path to the directory, or the name of the version to be found
+    /// within `.minecraft/versions/`.
+    ///
+    /// Example: `1.20.1` or `.minecraft/versions/1.20.1`
+    #[arg(value_name = "DIRECTORY or VERSION", value_parser = source::parse_version)]
+    version_dir: Version,
+    /// The path to the `.minecraft/assets/` directory to find hashed assets.
+    ///
+    /// Defaults to the default location on your OS.
+    #[arg(long = "hashed-assets", value_name = "DIRECTORY")]
+    hashed_assets_dir: Option<PathBuf>,
+}
+
+/// Where an [`Entry`] is extracted from.
+enum Source {
+    /// The version's jar file.
+    Jar,
+    /// The hashed assets, with the hashed name of the file.
+    Hashed(String),
+}
+
+/// A file which can be extracted.
+struct Entry {
+    /// The path of the file relative to the output directory, e.g.
+    /// `assets/minecraft/lang/en_us.json`.
+    path: PathBuf,
+    /// The size of the file in bytes.
+    size: u64,
+    source: Source,
+}
+
+/// A row displayed in the browser.
+enum Row {
+    /// A directory, with its name and full path.
+    Dir(String, PathBuf),
+    /// The index of an [`Entry`].
+    File(usize),
+}
+
+/// What to do after handling a key press.
+enum Action {
+    Continue,
+    Extract,
+    Quit,
+}
+
+/// The state of the browser.
+struct Browser {
+    entries: Vec<Entry>,
+    /// The indices of the marked entries.
+    marked: HashSet<usize>,
+    /// The directory whose contents are displayed.
+    dir: PathBuf,
+    /// Text which displayed entries' paths must contain.
+    ///
+    /// If this is not empty, every matching entry is displayed rather than the
+    /// contents of the current directory.
+    filter: String,
+    /// Whether key presses are editing the filter.
+    editing_filter: bool,
+    /// The index of the selected row.
+    selected: usize,
+    /// The index of the first row which is displayed.
+    scroll: usize,
+    /// A message displayed in place of the key bindings, e.g. after extraction.
+    status: Option<String>,
+}
+
+/// Restores the terminal written to through `out` when dropped, including
+/// when unwinding from a panic.
+struct TerminalGuard<W: Write> {
+    out: W,
+    /// Whether raw mode was enabled, and so is to be disabled again.
+    raw_mode: bool,
+}
+
+impl TerminalGuard<io::Stdout> {
+    /// Switches the terminal to raw mode and the alternate screen.
+    ///
+    /// A panic hook is installed so that the panic message is printed to the
+    /// restored terminal rather than the alternate screen.
+    fn enter() -> io::Result<Self> {
+        let hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            let _ = restore_terminal(&mut io::stdout(), true);
+            hook(info);
+        }));
+
+        terminal::enable_raw_mode()?;
+        // Created before anything else can fail, so that raw mode is disabled
+        // again if it does.
+        let mut guard = Self {
+            out: io::stdout(),
+            raw_mode: true,
+        };
+        execute!(guard.out, EnterAlternateScreen, cursor::Hide)?;
+
+        Ok(guard)
+    }
+}
+
+impl<W: Write> Drop for TerminalGuard<W> {
+    fn drop(&mut self) {
+        let _ = restore_terminal(&mut self.out, self.raw_mode);
+    }
+}
+
+/// Leaves the alternate screen and shows the cursor again by writing to
+/// `out`, after disabling raw mode if `raw_mode` is set.
+fn restore_terminal(out: &mut impl Write, raw_mode: bool) -> io::Result<()> {
+    if raw_mode {
+        terminal::disable_raw_mode()?;
+    }
+    execute!(out, LeaveAlternateScreen, cursor::Show)
+}
+
+impl Browser {
+    fn new(entries: Vec<Entry>) -> Self {
+        Self {
+            entries,
+            marked: HashSet::new(),
+            dir: PathBuf::new(),
+            filter: String::new(),
+            editing_filter: false,
+            selected: 0,
+            scroll: 0,
+            status: None,
+        }
+    }
+
+    /// Returns the rows to display: either the entries matching the filter, or
+    /// the contents of the current directory.
+    fn rows(&self) -> Vec<Row> {
+        if !self.filter.is_empty() {
+            let filter = self.filter.to_lowercase();
+
+            return self
+                .entries
+                .iter()
+                .enumerate()
+                .filter(|(_, entry)| {
+                    entry
+                        .path
+                        .to_string_lossy()
+                        .to_lowercase()
+                        .contains(&filter)
+                })
+                .map(|(i, _)| Row::File(i))
+                .collect();
+        }
+
+        let mut dirs = BTreeMap::new();
+        let mut files = Vec::new();
+
+        for (i, entry) in self.entries.iter().enumerate() {
+            let Ok(relative) = entry.path.strip_prefix(&self.dir) else {
+                continue;
+            };
+            let mut components = relative.components();
+            let Some(first) = components.next() else {
+                continue;
+            };
+
+            if components.next().is_some() {
+                let name = first.as_os_str().to_string_lossy().into_owned();
+                let path = self.dir.join(first);
+
+                dirs.entry(name.clone()).or_insert(Row::Dir(name, path));
+            } else {
+                files.push(i);
+            }
+        }
+
+        files.sort_by(|&a, &b| self.entries[a].path.cmp(&self.entries[b].path));
+
+        dirs.into_values()
+            .chain(files.into_iter().map(Row::File))
+            .collect()
+    }
+
+    /// Returns the indices of the entries represented by `row`.
+    fn entries_in(&self, row: &Row) -> Vec<usize> {
+        match row {
+            Row::Dir(_, path) => self
+                .entries
+                .iter()
+                .enumerate()
+                .filter(|(_, entry)| entry.path.starts_with(path))
+                .map(|(i, _)| i)
+                .collect(),
+            Row::File(i) => vec![*i],
+        }
+    }
+
+    /// Marks every entry represented by `row`, or unmarks them if they are
+    /// already all marked.
+    fn toggle_mark(&mut self, row: &Row) {
+        let entries = self.entries_in(row);
+
+        if entries.iter().all(|i| self.marked.contains(i)) {
+            for i in entries {
+                self.marked.remove(&i);
+            }
+        } else {
+            self.marked.extend(entries);
+        }
+    }
+
+    /// Handles a key press, given the rows currently displayed.
+    fn handle_key(&mut self, key: KeyEvent, rows: &[Row], page: usize) -> Action {
+        self.status = None;
+
+        if self.editing_filter {
+            match key.code {
+                KeyCode::Char(c) => self.filter.push(c),
+                KeyCode::Backspace => {
+                    self.filter.pop();
+                }
+                KeyCode::Esc => {
+                    self.filter.clear();
+                    self.editing_filter = false;
+                }
+                KeyCode::Enter => self.editing_filter = false,
+
+                _ => {}
+            }
+            self.selected = 0;
+
+            return Action::Continue;
+        }
+
+        let last = rows.len().saturating_sub(1);
+
+        match key.code {
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                return Action::Quit;
+            }
+            KeyCode::Char('q') | KeyCode::Esc => return Action::Quit,
+            KeyCode::Char('x') => return Action::Extract,
+
+            KeyCode::Up | KeyCode::Char('k') => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => self.selected = (self.selected + 1).min(last),
+            KeyCode::PageUp => self.selected = self.selected.saturating_sub(page),
+            KeyCode::PageDown => self.selected = (self.selected + page).min(last),
+            KeyCode::Home => self.selected = 0,
+            KeyCode::End => self.selected = last,
+
+            KeyCode::Right | KeyCode::Enter | KeyCode::Char('l') => {
+                if let Some(Row::Dir(_, path)) = rows.get(self.selected) {
+                    self.dir = path.clone();
+                    self.selected = 0;
+                }
+            }
+            KeyCode::Left | KeyCode::Backspace | KeyCode::Char('h') => {
+                if let Some(parent) = self.dir.parent() {
+                    let previous = self.dir.clone();
+                    self.dir = parent.to_owned();
+
+                    // Keep the directory we came from selected
+                    self.selected = self
+                        .rows()
+                        .iter()
+                        .position(|row| matches!(row, Row::Dir(_, path) if *path == previous))
+                        .unwrap_or(0);
+                }
+            }
+
+            KeyCode::Char(' ') => {
+                if let Some(row) = rows.get(self.selected) {
+                    self.toggle_mark(row);
+                    self.selected = (self.selected + 1).min(last);
+                }
+            }
+            KeyCode::Char('/') => self.editing_filter = true,
+
+            _ => {}
+        }
+
+        Action::Continue
+    }
+
+    /// Draws the browser to `out`, which is `columns` wide and `height` rows tall.
+    fn render(
+        &mut self,
+        out: &mut impl Write,
+        rows: &[Row],
+        columns: usize,
+        height: usize,
+    ) -> io::Result<()> {
+        // Keep the selected row in view
+        if self.selected < self.scroll {
+            self.scroll = self.selected;
+        } else if self.selected >= self.scroll + height {
+            self.scroll = self.selected + 1 - height;
+        }
+
+        queue!(out, terminal::Clear(ClearType::All), cursor::MoveTo(0, 0))?;
+
+        let header = if self.filter.is_empty() && !self.editing_filter {
+            format!("/{}", self.dir.display())
+        } else {
+            format!("Filter: {}", self.filter)
+        };
+        queue!(out, Print(truncate(&header, columns)))?;
+
+        for (line, (i, row)) in rows
+            .iter()
+            .enumerate()
+            .skip(self.scroll)
+            .take(height)
+            .enumerate()
+        {
+            let text = self.describe(row);
+
+            queue!(out, cursor::MoveTo(0, line as u16 + 1))?;
+            if i == self.selected {
+                queue!(out, SetAttribute(Attribute::Reverse))?;
+            }
+            queue!(
+                out,
+                Print(truncate(&text, columns)),
+                SetAttribute(Attribute::Reset)
+            )?;
+        }
+
+        let footer = match &self.status {
+            Some(status) => status.clone(),
+            None if self.editing_filter => "Type to filter, enter: done, esc: clear".to_owned(),
+            None => format!(
+                "{} marked | arrows: move/open, space: mark, /: filter, x: extract, q: quit",
+                self.marked.len()
+            ),
+        };
+        queue!(
+            out,
+            cursor::MoveTo(0, height as u16 + 1),
+            Print(truncate(&footer, columns))
+        )?;
+
+        out.flush()
+    }
+
+    /// Describes a row, e.g. `[x] en_us.json  12.3 KiB  jar`.
+    fn describe(&self, row: &Row) -> String {
+        let entries = self.entries_in(row);
+        let marked = entries.iter().filter(|i| self.marked.contains(i)).count();
+        let mark = match marked {
+            0 => "[ ]",
+            marked if marked == entries.len() => "[x]",
+            _ => "[~]",
+        };
+
+        match row {
+            Row::Dir(name, _) => format!("{mark} {name}/  ({} files)", entries.len()),
+            Row::File(i) => {
+                let entry = &self.entries[*i];
+                let name = if self.filter.is_empty() {
+                    entry.path.file_name().unwrap_or_default().to_string_lossy()
+                } else {
+                    entry.path.to_string_lossy()
+                };
+                let source = match &entry.source {
+                    Source::Jar => "jar".to_owned(),
+                    Source::Hashed(hash) => format!("hashed {hash}"),
+                };
+
+                format!("{mark} {name}  {}  {source}", util::format_size(entry.size))
+            }
+        }
+    }
+}
+
+/// Truncates `text` to at most `columns` characters.
+fn truncate(text: &str, columns: usize) -> String {
+    text.chars().take(columns).collect()
+}
+
+impl BrowseSubcommand {
+    /// Collects the files in the version's jar and hashed assets index.
+    fn entries(&self, index: Option<&IndexFile>) -> io::Result<Vec<Entry>> {
+        let extracted_contents = ExtractedContents::all();
+
+        let mut entries: Vec<Entry> =
+            jar::list_files(&self.version_dir.jar_file(), &extracted_contents)?
+                .into_iter()
+                .map(|(path, size)| Entry {
+                    path,
+                    size,
+                    source: Source::Jar,
+                })
+                .collect();
+
+        if let Some(index) = index {
+            entries.extend(index.entries().map(|entry| Entry {
+                path: Path::new("assets").join(entry.virtual_path),
+                size: entry.size,
+                source: Source::Hashed(entry.hash.to_owned()),
+            }));
+        }
+
+        Ok(entries)
+    }
+
+    /// Extracts the marked entries, returning the number of files extracted.
+    fn extract_marked(
+        &self,
+        browser: &Browser,
+        hashed_assets_dir: Option<&Path>,
+        index: Option<&IndexFile>,
+        options: &ExtractOptions,
+    ) -> io::Result<usize> {
+        let mut jar_paths = HashSet::new();
+        let mut hashed_paths = HashSet::new();
+
+        for entry in browser.marked.iter().map(|&i| &browser.entries[i]) {
+            match entry.source {
+                Source::Jar => {
+                    jar_paths.insert(entry.path.as_path());
+                }
+                Source::Hashed(_) => {
+                    hashed_paths.insert(entry.path.strip_prefix("assets").unwrap_or(&entry.path));
+                }
+            }
+        }
+
+        let mut report = ExtractionReport::new(options.clone());
+
+        if !jar_paths.is_empty() {
+            report = report
+                + jar::extract_jar_filtered(
+                    &self.version_dir.jar_file(),
+                    &options.clone().contents(ExtractedContents::all()),
+                    &mut NoProgress,
+                    |path| jar_paths.contains(path),
+                )?;
+        }
+
+        if let (Some(hashed_assets_dir), Some(index)) = (hashed_assets_dir, index)
+            && !hashed_paths.is_empty()
+            && !report.summary.disk_full
+        {
+            report = report
+                + hashed::extract_hashed_assets(
+                    hashed_assets_dir,
+                    &index.filtered(|path| hashed_paths.contains(path)),
+                    options,
+                    &mut NoProgress,
+                )?;
+        }
+
+        for file in report.written() {
+            written::record(&file.path);
+        }
+
+        Ok(report.summary.files)
+    }
+}
+
+impl ExtractCmd for BrowseSubcommand {
+    fn source_dirs(&self) -> Vec<PathBuf> {
+        let hashed_assets_dir = self
+            .hashed_assets_dir
+            .clone()
+            .or_else(util::hashed_assets_dir);
+
+        [Some(self.version_dir.path().to_owned()), hashed_assets_dir]
+            .into_iter()
+            .flatten()
+            .collect()
+    }
+
+    fn execute(self, options: &Options) -> Result<ExtractionReport, Error> {
+        if !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+            return Err(
+                io::Error::other("browse requires stdin and stdout to be a terminal").into(),
+            );
+        }
+
+        let hashed_assets_dir = self
+            .hashed_assets_dir
+            .clone()
+            .or_else(util::hashed_assets_dir)
+            .filter(|path| util::is_dir(path));
+        let index = match &hashed_assets_dir {
+            Some(dir) => {
+                let path = self.version_dir.index_file(dir)?;
+
+                path.is_file()
+                    .then(|| IndexFile::from_path(&path))
+                    .transpose()?
+            }
+            None => None,
+        };
+
+        let mut browser = Browser::new(self.entries(index.as_ref())?);
+
+        let _guard = TerminalGuard::enter()?;
+        let mut stdout = io::stdout();
+
+        loop {
+            let (columns, rows_len) = match terminal::size()? {
+                // Some terminals don't report their size
+                (0, _) | (_, 0) => (80, 24),
+                size => size,
+            };
+            // Leave room for the header and footer
+            let height = (rows_len as usize).saturating_sub(2).max(1);
+
+            let rows = browser.rows();
+            browser.selected = browser.selected.min(rows.len().saturating_sub(1));
+            browser.render(&mut stdout, &rows, columns as usize, height)?;
+
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+
+            match browser.handle_key(key, &rows, height) {
+                Action::Continue => {}
+                Action::Quit => break,
+
+                Action::Extract => {
+                    browser.status = Some(
+                        match self.extract_marked(
+                            &browser,
+                            hashed_assets_dir.as_deref(),
+                            index.as_ref(),
+                            &options.extract,
+                        ) {
+                            Ok(count) => format!(
+                                "Extracted {count} files to {}",
+                                options.extract.output_dir.display()
+                            ),
+                            Err(error) => format!("Extraction failed: {error}"),
+                        },
+                    );
+                }
+            }
+        }
+
+        Ok(ExtractionReport::new(options.extract.clone()))
+    }
+
+    #[cfg(feature = "git")]
+    fn git_source(&self) -> git::Source {
+        git::Source::version(&self.version_dir)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// What [`restore_terminal`] writes: leaving the alternate screen, then
+    /// showing the cursor.
+    const RESTORE: &[u8] = b"\x1b[?1049l\x1b[?25h";
+
+    #[test]
+    fn terminal_is_restored_when_dropped() {
+        let mut out = Vec::new();
+        drop(TerminalGuard {
+            out: &mut out,
+            raw_mode: false,
+        });
+
+        assert_eq!(out, RESTORE);
+    }
+
+    #[test]
+    fn terminal_is_restored_when_panicking() {
+        let mut out = Vec::new();
+        let result = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+            let _guard = TerminalGuard {
+                out: &mut out,
+                raw_mode: false,
+            };
+            panic!("while browsing");
+        }));
+
+        assert!(result.is_err());
+        assert_eq!(out, RESTORE);
+    }
+}
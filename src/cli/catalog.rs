@@ -0,0 +1,51 @@
+//! Writing a catalog of the extracted files into the output directory.
+
+use crate::cli::json::Document;
+use clap::ValueEnum;
+use extract_minecraft_assets::catalog::Catalog;
+use extract_minecraft_assets::error::{ExtractError, IoContext};
+use extract_minecraft_assets::report::ExtractionReport;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+
+/// The format in which to write the catalog of the extracted files.
+#[derive(Clone, Copy, ValueEnum)]
+pub enum CatalogFormat {
+    /// `catalog.json`, in the same format as --json.
+    Json,
+    /// `catalog.md`, a Markdown document with a table of files per category.
+    Md,
+}
+
+impl CatalogFormat {
+    /// The name of the catalog file within the output directory.
+    fn file_name(self) -> &'static str {
+        match self {
+            Self::Json => "catalog.json",
+            Self::Md => "catalog.md",
+        }
+    }
+}
+
+/// Writes the catalog of the files written by the extraction of `report`
+/// into its output directory as `format`, returning the catalog's path.
+pub fn write(format: CatalogFormat, report: &ExtractionReport) -> Result<PathBuf, ExtractError> {
+    let catalog = Catalog::from_report(report);
+    let path = report.options.output_dir.join(format.file_name());
+
+    match format {
+        CatalogFormat::Json => Document::new(catalog).write(&path)?,
+        CatalogFormat::Md => {
+            let write = || {
+                let mut file = BufWriter::new(File::create(&path)?);
+                catalog.write_markdown(&mut file)?;
+                file.flush()
+            };
+
+            write().writing(&path)?;
+        }
+    }
+
+    Ok(path)
+}
@@ -0,0 +1,225 @@
+use crate::cli::error::Error;
+#[cfg(feature = "git")]
+use crate::cli::git;
+use crate::cli::progress::TerminalProgress;
+use crate::cli::source;
+use crate::{ExtractCmd, Options};
+use clap::Args;
+use extract_minecraft_assets::diff::DiffItem;
+use extract_minecraft_assets::error::ExtractError;
+use extract_minecraft_assets::report::ExtractionReport;
+use extract_minecraft_assets::version::Version;
+use extract_minecraft_assets::{hashed, jar, util};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Args)]
+pub struct ChangedSubcommand {
+    /// The older version, whose files are left out if they are unchanged.
+    ///
+    /// Can be a path to the version directory, or the name of the version to
+    /// be found within `.minecraft/versions/`.
+    #[arg(value_name = "OLD", value_parser = source::parse_version)]
+    old_version: Version,
+    /// The newer version, whose added and modified files are extracted.
+    ///
+    /// Can be a path to the version directory, or the name of the version to
+    /// be found within `.minecraft/versions/`.
+    #[arg(value_name = "NEW", value_parser = source::parse_version)]
+    new_version: Version,
+    /// The path to the `.minecraft/assets/` directory to find hashed assets.
+    ///
+    /// Defaults to the default location on your OS.
+    #[arg(long = "hashed-assets", value_name = "DIRECTORY")]
+    hashed_assets_dir: Option<PathBuf>,
+    /// Write the paths of the files which the newer version removed to
+    /// `removed.txt` in the output directory.
+    #[arg(long)]
+    write_removed: bool,
+
+    /// Which contents to extract.
+    #[command(flatten)]
+    contents: jar::ContentsArgs,
+}
+
+impl ExtractCmd for ChangedSubcommand {
+    fn source_dirs(&self) -> Vec<PathBuf> {
+        let hashed_assets_dir = self
+            .hashed_assets_dir
+            .clone()
+            .or_else(util::hashed_assets_dir);
+
+        [
+            Some(self.old_version.path().to_owned()),
+            Some(self.new_version.path().to_owned()),
+            hashed_assets_dir,
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+
+    fn execute(self, options: &Options) -> Result<ExtractionReport, Error> {
+        let extracted_contents = jar::ExtractedContents::from(self.contents);
+        let ignore_top_level = options.extract.ignore_top_level;
+
+        if extracted_contents.is_empty() {
+            return Ok(ExtractionReport::new(options.extract.clone()));
+        }
+
+        let (old_jar, new_jar) = (self.old_version.jar_file(), self.new_version.jar_file());
+        let jar_diff = jar::diff_jars(&old_jar, &new_jar, &extracted_contents, false)?;
+        let included = |path: &Path| options.extract.includes(path);
+        let changed_jar_files: HashSet<_> = jar_diff
+            .only_in_b
+            .iter()
+            .map(|entry| entry.path.clone())
+            .chain(jar_diff.changed.iter().map(|entry| entry.path.clone()))
+            .filter(|path| included(path))
+            .collect();
+        let jar_size: u64 = jar_diff
+            .only_in_b
+            .iter()
+            .map(|entry| (&entry.path, entry.item.size()))
+            .chain(
+                jar_diff
+                    .changed
+                    .iter()
+                    .map(|entry| (&entry.path, entry.b.size())),
+            )
+            .filter(|(path, _)| included(path))
+            .map(|(_, size)| size)
+            .sum();
+        let mut unchanged = jar::list_files(&new_jar, &extracted_contents)?
+            .into_iter()
+            .filter(|(path, _)| included(path))
+            .count()
+            - changed_jar_files.len();
+
+        let mut removed: Vec<_> = jar_diff
+            .only_in_a
+            .iter()
+            .filter(|entry| included(&entry.path))
+            .filter_map(|entry| {
+                let root = extracted_contents.root_of(&entry.path)?;
+                let output_path = root.output_path(&entry.path, ignore_top_level);
+
+                Some(options.extract.relative_output_path(output_path, false))
+            })
+            .collect();
+
+        let hashed = if extracted_contents.contains("assets") {
+            let hashed_assets_dir = source::hashed_assets_dir(self.hashed_assets_dir)?;
+            let old_index = source::version_index(&self.old_version, &hashed_assets_dir)?;
+            let old_index = options.extract.included_objects(&old_index).into_owned();
+            let new_index = source::version_index(&self.new_version, &hashed_assets_dir)?;
+            let mut new_index = options.extract.included_objects(&new_index).into_owned();
+
+            let index_diff = old_index.diff(&new_index);
+            let changed_objects: HashSet<_> = index_diff
+                .only_in_b
+                .iter()
+                .map(|entry| entry.path.clone())
+                .chain(index_diff.changed.iter().map(|entry| entry.path.clone()))
+                .collect();
+
+            // Only the objects which were added or modified are kept in the newer index.
+            unchanged += new_index.len() - changed_objects.len();
+            new_index
+                .objects
+                .retain(|path, _| changed_objects.contains(path));
+
+            let assets_dir = if ignore_top_level {
+                PathBuf::new()
+            } else {
+                PathBuf::from("assets")
+            };
+            removed.extend(index_diff.only_in_a.iter().map(|entry| {
+                options
+                    .extract
+                    .relative_output_path(assets_dir.join(&entry.path), false)
+            }));
+
+            Some((hashed_assets_dir, new_index))
+        } else {
+            None
+        };
+
+        let hashed_size = hashed.as_ref().map_or(0, |(_, index)| index.total_size());
+        options.confirm_size(jar_size + hashed_size)?;
+
+        let mut progress = TerminalProgress::new(options.porcelain);
+        progress.add_phase(
+            format!("jar {}", extracted_contents),
+            changed_jar_files.len(),
+        );
+        if let Some((_, index)) = &hashed {
+            progress.add_phase("hashed assets", index.len());
+        }
+
+        eprintln!(
+            "Extracting {} changed between {} and {}...",
+            &extracted_contents,
+            self.old_version.name(),
+            self.new_version.name()
+        );
+        let dry_run = options.dry_run;
+        let options = options.extract.clone().contents(extracted_contents);
+
+        let mut report = jar::extract_jar_filtered(&new_jar, &options, &mut progress, |path| {
+            changed_jar_files.contains(path)
+        })?;
+
+        // The hashed assets are left for later if the output volume is full.
+        if let Some((_, index)) = &hashed
+            && report.summary.disk_full
+        {
+            report.summary.remaining_bytes += index.total_size();
+        } else if let Some((hashed_assets_dir, index)) = hashed {
+            report = report
+                + hashed::extract_hashed_assets(
+                    &hashed_assets_dir,
+                    &index,
+                    &options,
+                    &mut progress,
+                )?;
+        }
+        report.summary.unchanged = unchanged;
+
+        progress.clear()?;
+        eprintln!("{progress}");
+
+        if self.write_removed && !dry_run {
+            removed.sort();
+            write_removed(&options.output_dir.join("removed.txt"), &removed)?;
+        }
+
+        Ok(report)
+    }
+
+    #[cfg(feature = "git")]
+    fn git_source(&self) -> git::Source {
+        git::Source::version(&self.new_version)
+    }
+}
+
+/// Writes the `removed` paths to `path`, one per line with `/` separators.
+fn write_removed(path: &Path, removed: &[PathBuf]) -> Result<(), ExtractError> {
+    let contents: String = removed
+        .iter()
+        .map(|removed| {
+            let components: Vec<_> = removed
+                .iter()
+                .map(|component| component.to_string_lossy())
+                .collect();
+
+            components.join("/") + "\n"
+        })
+        .collect();
+
+    fs::write(path, contents).map_err(|source| ExtractError::WriteFile {
+        path: path.to_owned(),
+        source,
+    })
+}
@@ -0,0 +1,148 @@
+use crate::cli::error::Error;
+use crate::cli::json::Document;
+use crate::cli::lock::OutputLock;
+use clap::Args;
+use extract_minecraft_assets::clean::{self, CleanPlan, RecordedExtraction, Verification};
+use serde::Serialize;
+use std::path::Path;
+use std::process::ExitCode;
+
+#[derive(Args)]
+pub struct CleanRunSubcommand {
+    /// Also delete the files which were modified since they were extracted.
+    #[arg(long)]
+    force: bool,
+}
+
+#[derive(Args)]
+pub struct VerifyReportSubcommand {
+    /// Only compare the sizes of files with the report, without hashing
+    /// hashed assets.
+    #[arg(long)]
+    size_only: bool,
+}
+
+/// The document printed by `clean-run --json`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CleanRunDocument<'a> {
+    /// The files deleted, or which would be with --dry-run.
+    deleted: Vec<&'a Path>,
+    #[serde(flatten)]
+    plan: &'a CleanPlan,
+    dry_run: bool,
+}
+
+impl CleanRunSubcommand {
+    /// Deletes the files written by the extraction whose report is at
+    /// `report`, locking its output directory unless `no_lock` is set, or
+    /// only lists them if `dry_run`.
+    ///
+    /// Exits with 2 if any files were kept as they were modified.
+    pub fn execute(
+        self,
+        report: Option<&Path>,
+        no_lock: bool,
+        dry_run: bool,
+        json: bool,
+    ) -> Result<ExitCode, Error> {
+        let report = report.ok_or(Error::NoReport)?;
+        let extraction = RecordedExtraction::from_path(report)?;
+        let output_dir = &extraction.output_dir;
+
+        // Held until the files are deleted.
+        let _lock = (!no_lock && !dry_run && output_dir.is_dir())
+            .then(|| OutputLock::acquire(output_dir))
+            .transpose()?;
+        let plan = clean::plan(&extraction)?;
+
+        let deleted: Vec<_> = plan.deleted(self.force).collect();
+        if dry_run {
+            for path in &deleted {
+                eprintln!("Would delete '{}'", path.display());
+            }
+        } else {
+            plan.apply(output_dir, self.force)?;
+        }
+
+        if json {
+            Document::new(CleanRunDocument {
+                deleted: deleted.clone(),
+                plan: &plan,
+                dry_run,
+            })
+            .print()?;
+        } else {
+            for path in &plan.modified {
+                let action = if self.force { "Deleted" } else { "Kept" };
+                println!("{action} modified '{}'", output_dir.join(path).display());
+            }
+        }
+
+        let kept = if self.force { 0 } else { plan.modified.len() };
+        eprintln!(
+            "{} {}, kept {kept} modified, {} already gone, {} not written by the extraction",
+            if dry_run { "Would delete" } else { "Deleted" },
+            files_noun(deleted.len()),
+            plan.missing.len(),
+            plan.skipped
+        );
+        if kept > 0 {
+            eprintln!("Pass --force to delete the modified files too");
+
+            return Ok(ExitCode::from(2));
+        }
+
+        Ok(ExitCode::SUCCESS)
+    }
+}
+
+impl VerifyReportSubcommand {
+    /// Checks whether the files written by the extraction whose report is
+    /// at `report` changed since, and whether files were added beside them.
+    ///
+    /// Exits with 1 if anything changed.
+    pub fn execute(self, report: Option<&Path>, json: bool) -> Result<ExitCode, Error> {
+        let report = report.ok_or(Error::NoReport)?;
+        let extraction = RecordedExtraction::from_path(report)?;
+        let verification = clean::verify(&extraction, self.size_only)?;
+
+        if json {
+            Document::new(&verification).print()?;
+        } else {
+            print_verification(&verification, &extraction.output_dir);
+        }
+
+        Ok(if verification.is_clean() {
+            ExitCode::SUCCESS
+        } else {
+            ExitCode::FAILURE
+        })
+    }
+}
+
+fn print_verification(verification: &Verification, output_dir: &Path) {
+    let changes = [
+        ("modified", &verification.modified),
+        ("missing", &verification.missing),
+        ("untracked", &verification.untracked),
+    ];
+    for (change, paths) in changes {
+        for path in paths {
+            println!("{change}\t{}", output_dir.join(path).display());
+        }
+    }
+
+    eprintln!(
+        "{} unchanged, {} modified, {} missing, {} untracked",
+        files_noun(verification.unchanged.len()),
+        verification.modified.len(),
+        verification.missing.len(),
+        verification.untracked.len()
+    );
+}
+
+/// Returns e.g. `1 file` or `2 files`.
+fn files_noun(count: usize) -> String {
+    format!("{count} {}", if count == 1 { "file" } else { "files" })
+}
@@ -0,0 +1,117 @@
+use crate::cli::error::Error;
+use crate::cli::progress;
+use clap::{Args, ValueEnum};
+use extract_minecraft_assets::closure::{AssetSources, Closure, ClosureFile};
+use extract_minecraft_assets::search::FileSource;
+use extract_minecraft_assets::{fonts, models};
+use std::collections::HashSet;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Extracting only the files referenced by some starting files, shared by the
+/// extraction subcommands which extract from a jar file.
+#[derive(Args, Clone)]
+pub struct ClosureArgs {
+    /// Only extract this set of `assets`, found by following the references
+    /// between files.
+    ///
+    /// References to files which don't exist are reported.
+    #[arg(
+        long,
+        value_name = "SET",
+        requires = "assets",
+        conflicts_with = "closure"
+    )]
+    only: Option<OnlySet>,
+    /// Only extract this blockstate or model, along with every parent model,
+    /// texture and override model it references.
+    ///
+    /// Paths are within `assets`, e.g.
+    /// `assets/minecraft/blockstates/oak_stairs.json` or
+    /// `minecraft/models/item/compass.json`. May be given more than once.
+    /// References to files which don't exist are reported.
+    #[arg(long, value_name = "PATH", requires = "assets")]
+    closure: Vec<PathBuf>,
+}
+
+/// A set of `assets` which can be extracted on its own.
+#[derive(Clone, Copy, ValueEnum)]
+enum OnlySet {
+    /// Every font definition, and the textures, glyph data and other fonts
+    /// they reference.
+    FontsClosure,
+}
+
+impl ClosureArgs {
+    /// Whether only a closure is to be extracted.
+    pub fn is_enabled(&self) -> bool {
+        self.only.is_some() || !self.closure.is_empty()
+    }
+
+    /// Returns the closure to extract from `sources`, if only a closure is to
+    /// be extracted.
+    ///
+    /// Fails if a path given with --closure doesn't exist.
+    pub fn resolve(&self, sources: &mut AssetSources) -> Result<Option<Closure>, Error> {
+        if let Some(only) = self.only {
+            let closure = match only {
+                OnlySet::FontsClosure => fonts::fonts_closure(sources)?,
+            };
+
+            return Ok(Some(closure));
+        }
+        if self.closure.is_empty() {
+            return Ok(None);
+        }
+
+        let mut roots = Vec::new();
+        for path in &self.closure {
+            let path = path.strip_prefix("assets").unwrap_or(path);
+            if sources.find(path).is_none() {
+                return Err(Error::Io(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("no file found at '{}' within assets", path.display()),
+                )));
+            }
+
+            roots.push(path.to_owned());
+        }
+
+        Ok(Some(models::models_closure(sources, roots)?))
+    }
+}
+
+/// Returns the paths of the jar files of `closure`, relative to the jar's
+/// top-level directory, e.g. `assets/minecraft/font/default.json`.
+pub fn jar_files(closure: &Closure) -> HashSet<PathBuf> {
+    files_in(closure, FileSource::Jar)
+        .map(|file| Path::new("assets").join(&file.path))
+        .collect()
+}
+
+/// Returns the files of `closure` within `source`.
+pub fn files_in(closure: &Closure, source: FileSource) -> impl Iterator<Item = &ClosureFile> {
+    closure
+        .files
+        .iter()
+        .filter(move |file| file.source == source)
+}
+
+/// Prints the references of `closure` to files which don't exist, and the
+/// files whose references couldn't be followed.
+pub fn print_problems(closure: &Closure) {
+    for dangling in &closure.dangling {
+        progress::message(format_args!(
+            "Dangling reference from '{}' to '{}'",
+            dangling.from.display(),
+            dangling.path.display()
+        ));
+    }
+    for error in &closure.errors {
+        progress::message(format_args!(
+            "Couldn't follow the references of '{}': {}",
+            error.path.display(),
+            error.message
+        ));
+    }
+}
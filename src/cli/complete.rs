@@ -0,0 +1,338 @@
+//! Completing arguments in the shell, with values which depend on what is
+//! installed: the names of versions, index files and locales.
+//!
+//! The scripts printed by `completions` call the tool again as
+//! `__complete -- <words>`, with the words of the command line up to and
+//! including the one being completed, and offer each line it prints.
+//! Nothing is printed when nothing can be found, so that the shell falls
+//! back to completing paths, and completion never fails.
+
+use clap::{Arg, Args, Command, ValueEnum};
+use extract_minecraft_assets::util;
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// The hidden argument the completion scripts call the tool with.
+pub const COMPLETE: &str = "__complete";
+
+/// The shells whose completion scripts can be printed.
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+#[derive(Args)]
+pub struct CompletionsSubcommand {
+    /// The shell to print the completion script of.
+    #[arg(value_name = "SHELL")]
+    shell: Shell,
+}
+
+impl CompletionsSubcommand {
+    /// Prints the completion script for the shell, completing the tool by
+    /// the name `command` has.
+    pub fn execute(self, command: &Command) -> io::Result<()> {
+        let name = command.get_name();
+        let function = format!("_{}", name.replace('-', "_"));
+
+        let script = match self.shell {
+            Shell::Bash => format!(
+                r#"{function}() {{
+    local IFS=$'\n'
+    COMPREPLY=($({name} {COMPLETE} -- "${{COMP_WORDS[@]:1:COMP_CWORD}}" 2>/dev/null))
+}}
+complete -o default -F {function} {name}
+"#
+            ),
+            Shell::Zsh => format!(
+                r#"#compdef {name}
+{function}() {{
+    local -a candidates
+    candidates=("${{(@f)$({name} {COMPLETE} -- "${{(@)words[2,CURRENT]}}" 2>/dev/null)}}")
+    if [[ -n ${{candidates[1]}} ]]; then
+        compadd -a candidates
+    else
+        _files
+    fi
+}}
+compdef {function} {name}
+"#
+            ),
+            Shell::Fish => format!(
+                r#"function {function}
+    {name} {COMPLETE} -- (commandline -opc)[2..-1] (commandline -ct) 2>/dev/null
+end
+complete -c {name} -a '({function})'
+"#
+            ),
+        };
+
+        io::stdout().write_all(script.as_bytes())
+    }
+}
+
+/// The installed things an argument's values name.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Installed {
+    /// The directories within `.minecraft/versions/`.
+    Versions,
+    /// The index files within `.minecraft/assets/indexes/`.
+    Indexes,
+    /// The locales of the newest index file.
+    Locales,
+}
+
+impl Installed {
+    /// Returns what the values of the argument `arg` of the subcommand
+    /// `subcommand` name, if anything installed.
+    fn of(subcommand: &str, arg: &Arg) -> Option<Self> {
+        match (subcommand, arg.get_id().as_str()) {
+            (_, "version_dir" | "old" | "new" | "against" | "source" | "version") => {
+                Some(Self::Versions)
+            }
+            ("hashed", "index") | ("diff-index", "a" | "b") | (_, "against_index") => {
+                Some(Self::Indexes)
+            }
+            ("lang", "locales") | ("locales", "missing") => Some(Self::Locales),
+            _ => None,
+        }
+    }
+}
+
+/// Prints the completions of the last of the `words`, given the words before
+/// it, one per line.
+///
+/// `words` are the arguments following `__complete --`. Anything which
+/// can't be read is left out rather than reported.
+pub fn complete(mut command: Command, words: &[OsString]) {
+    command.build();
+    let words: Vec<_> = words
+        .iter()
+        .map(|word| word.to_string_lossy().into_owned())
+        .collect();
+    let Some((current, before)) = words.split_last() else {
+        return;
+    };
+    // Bash splits `--index=` into `--index` and `=`, the word completed.
+    let current = if current == "=" { "" } else { current };
+
+    let mut stdout = io::stdout().lock();
+    for candidate in candidates(&command, before, current) {
+        if candidate.starts_with(current) {
+            let _ = writeln!(stdout, "{candidate}");
+        }
+    }
+}
+
+/// Returns the completions of `current`, given the words `before` it, which
+/// may not start with it.
+fn candidates(root: &Command, before: &[String], current: &str) -> Vec<String> {
+    let mut command = root;
+    let mut positionals = 0;
+    // The option whose value the next word is, if any.
+    let mut pending: Option<&Arg> = None;
+    let mut only_positionals = false;
+
+    for word in before {
+        if let Some(arg) = pending.take() {
+            // Bash splits `--index=5` into `--index`, `=` and `5`.
+            if word == "=" {
+                pending = Some(arg);
+            }
+            continue;
+        }
+
+        if !only_positionals && word == "--" {
+            only_positionals = true;
+        } else if !only_positionals && word.starts_with('-') && word.len() > 1 {
+            pending = find_option(command, word)
+                .filter(|arg| arg.get_action().takes_values() && !word.contains('='));
+        } else if let Some(subcommand) = command.find_subcommand(word).filter(|_| positionals == 0)
+        {
+            command = subcommand;
+        } else {
+            positionals += 1;
+        }
+    }
+
+    let hashed_assets_dir = hashed_assets_dir(before);
+    if let Some(arg) = pending {
+        return values(command, arg, hashed_assets_dir);
+    }
+
+    if !only_positionals && current.starts_with("--") {
+        if let Some((option, _)) = current.split_once('=') {
+            return find_option(command, option)
+                .map(|arg| values(command, arg, hashed_assets_dir))
+                .unwrap_or_default()
+                .into_iter()
+                .map(|value| format!("{option}={value}"))
+                .collect();
+        }
+
+        return command
+            .get_arguments()
+            .filter(|arg| !arg.is_hide_set())
+            .filter_map(Arg::get_long)
+            .map(|long| format!("--{long}"))
+            .collect();
+    }
+
+    let mut candidates = Vec::new();
+    if positionals == 0 {
+        candidates.extend(
+            command
+                .get_subcommands()
+                .filter(|subcommand| !subcommand.is_hide_set())
+                .map(|subcommand| subcommand.get_name().to_owned()),
+        );
+    }
+    if let Some(arg) = command.get_positionals().nth(positionals) {
+        candidates.extend(values(command, arg, hashed_assets_dir));
+    }
+
+    candidates
+}
+
+/// Finds the option `word` names, e.g. `--index`, `--index=5` or `-i`.
+fn find_option<'c>(command: &'c Command, word: &str) -> Option<&'c Arg> {
+    if let Some(long) = word.strip_prefix("--") {
+        let long = long.split_once('=').map_or(long, |(long, _)| long);
+        return command.get_arguments().find(|arg| {
+            arg.get_long() == Some(long)
+                || arg
+                    .get_all_aliases()
+                    .is_some_and(|aliases| aliases.contains(&long))
+        });
+    }
+
+    // Only the last of several short options given together, e.g. `-yi`,
+    // can take the following word as its value, and none does if the last
+    // character is a value, e.g. `-i5`.
+    let short = word.chars().last()?;
+    command
+        .get_arguments()
+        .find(|arg| arg.get_short() == Some(short))
+}
+
+/// Returns the values of the argument `arg` of `command`: the names of what
+/// it names, if anything installed, or else its possible values.
+fn values(command: &Command, arg: &Arg, hashed_assets_dir: Option<PathBuf>) -> Vec<String> {
+    let hashed_assets_dir = || hashed_assets_dir.or_else(util::hashed_assets_dir);
+
+    match Installed::of(command.get_name(), arg) {
+        Some(Installed::Versions) => util::versions_dir()
+            .map(|versions_dir| dir_names(&versions_dir, |path| path.is_dir()))
+            .unwrap_or_default(),
+        Some(Installed::Indexes) => hashed_assets_dir()
+            .map(|dir| index_names(&dir.join("indexes")))
+            .unwrap_or_default(),
+        Some(Installed::Locales) => hashed_assets_dir()
+            .and_then(|dir| newest_index(&dir.join("indexes")))
+            .map(|index| locales(&index))
+            .unwrap_or_default(),
+        None => arg
+            .get_possible_values()
+            .iter()
+            .filter(|value| !value.is_hide_set())
+            .map(|value| value.get_name().to_owned())
+            .collect(),
+    }
+}
+
+/// Returns the directory given with `--hashed-assets` in `words`, if any.
+fn hashed_assets_dir(words: &[String]) -> Option<PathBuf> {
+    words.iter().enumerate().find_map(|(i, word)| {
+        if word == "--hashed-assets" {
+            words.get(i + 1).map(PathBuf::from)
+        } else {
+            word.strip_prefix("--hashed-assets=").map(PathBuf::from)
+        }
+    })
+}
+
+/// Returns the names of the entries of `dir` for which `filter` holds,
+/// sorted.
+fn dir_names(dir: &Path, filter: impl Fn(&Path) -> bool) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut names: Vec<_> = entries
+        .flatten()
+        .filter(|entry| filter(&entry.path()))
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .collect();
+    names.sort();
+
+    names
+}
+
+/// Returns the names of the index files in `indexes_dir`, without their
+/// `json` file extension.
+fn index_names(indexes_dir: &Path) -> Vec<String> {
+    dir_names(indexes_dir, is_index)
+        .into_iter()
+        .filter_map(|name| name.strip_suffix(".json").map(str::to_owned))
+        .collect()
+}
+
+fn is_index(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension == "json")
+        && path.is_file()
+}
+
+/// Returns the index file in `indexes_dir` modified last.
+fn newest_index(indexes_dir: &Path) -> Option<PathBuf> {
+    fs::read_dir(indexes_dir)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| is_index(path))
+        .max_by_key(|path| {
+            fs::metadata(path)
+                .and_then(|metadata| metadata.modified())
+                .unwrap_or(SystemTime::UNIX_EPOCH)
+        })
+}
+
+/// Returns the codes of the locales of the `minecraft` namespace listed in
+/// the index file at `path`, sorted, e.g. `de_de`.
+///
+/// The index file isn't parsed: each path of a language file is found in it
+/// as it is, as index files only escape paths which no language file has.
+fn locales(path: &Path) -> Vec<String> {
+    const PREFIX: &str = "\"minecraft/lang/";
+
+    let Ok(file) = File::open(path) else {
+        return Vec::new();
+    };
+
+    let mut locales = Vec::new();
+    for line in BufReader::new(file).split(b'\n').map_while(Result::ok) {
+        let line = String::from_utf8_lossy(&line);
+        for (start, _) in line.match_indices(PREFIX) {
+            let rest = &line[start + PREFIX.len()..];
+            let Some(end) = rest.find('"') else {
+                continue;
+            };
+            let name = &rest[..end];
+            if let Some(locale) = name
+                .strip_suffix(".json")
+                .or_else(|| name.strip_suffix(".lang"))
+            {
+                locales.push(locale.to_owned());
+            }
+        }
+    }
+    locales.sort();
+    locales.dedup();
+
+    locales
+}
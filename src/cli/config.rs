@@ -0,0 +1,283 @@
+use crate::cli::error::Error;
+use crate::cli::json::Document;
+use clap::parser::ValueSource;
+use clap::{ArgMatches, Args, Command};
+use extract_minecraft_assets::util;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::{fs, io};
+
+/// The default number of bytes above which extraction must be confirmed.
+const DEFAULT_CONFIRM_OVER: u64 = 1 << 30;
+
+/// Settings read from the config file.
+///
+/// The config file is found at `extract-minecraft-assets/config.toml` within
+/// the user's config directory (e.g. `~/.config/` on Linux).
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Config {
+    /// The size above which extraction must be confirmed, e.g. `1 GiB`.
+    confirm_over: Option<String>,
+    /// Named sets of option values, from `[profile.<name>]` sections, of
+    /// which one can be activated with `--profile`.
+    #[serde(default)]
+    profile: BTreeMap<String, toml::Table>,
+}
+
+impl Config {
+    /// The path to the config file, if the config directory is known.
+    pub fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("extract-minecraft-assets").join("config.toml"))
+    }
+
+    /// Loads the config file, or the default config if there is no config file.
+    pub fn load() -> io::Result<Self> {
+        let Some(path) = Self::path().filter(|path| path.is_file()) else {
+            return Ok(Self::default());
+        };
+
+        let contents = fs::read_to_string(&path).map_err(|error| {
+            io::Error::new(
+                error.kind(),
+                format!("failed to read config file '{}': {error}", path.display()),
+            )
+        })?;
+
+        toml::from_str(&contents).map_err(|error| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid config file '{}': {error}", path.display()),
+            )
+        })
+    }
+
+    /// The number of bytes above which extraction must be confirmed.
+    pub fn confirm_over(&self) -> io::Result<u64> {
+        match &self.confirm_over {
+            Some(size) => util::parse_size(size).map_err(|error| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("invalid `confirm-over` in config file: {error}"),
+                )
+            }),
+            None => Ok(DEFAULT_CONFIRM_OVER),
+        }
+    }
+
+    /// Returns the profile `name`, or an error listing the profiles the
+    /// config file defines.
+    fn profile(&self, name: &str) -> Result<&toml::Table, Error> {
+        self.profile.get(name).ok_or_else(|| Error::UnknownProfile {
+            name: name.to_owned(),
+            available: self.profile.keys().cloned().collect(),
+        })
+    }
+}
+
+/// An option set by a profile.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileOption {
+    /// The long name of the option, e.g. `format-json`.
+    name: String,
+    /// The arguments the option expands to, e.g. `--format-json=pretty`.
+    /// Empty for a flag set to `false`.
+    args: Vec<String>,
+    /// Whether the option was given on the command line, which overrides
+    /// the profile.
+    overridden: bool,
+}
+
+/// Returns the options set by the profile `name`, as they would be given
+/// on the command line.
+///
+/// A flag set to `true` expands to e.g. `--yes`, and to nothing if set to
+/// `false`. Strings and numbers expand to e.g. `--format-json=pretty`, and
+/// arrays to one such argument per element.
+fn expand(name: &str, profile: &toml::Table) -> Result<Vec<ProfileOption>, Error> {
+    let invalid = |option: &str, reason: &str| Error::InvalidProfile {
+        profile: name.to_owned(),
+        option: option.to_owned(),
+        reason: reason.to_owned(),
+    };
+    let arg = |option: &str, value: &toml::Value| match value {
+        toml::Value::String(value) => Ok(format!("--{option}={value}")),
+        toml::Value::Integer(value) => Ok(format!("--{option}={value}")),
+        toml::Value::Float(value) => Ok(format!("--{option}={value}")),
+        _ => Err(invalid(
+            option,
+            "values must be strings, numbers or booleans",
+        )),
+    };
+
+    profile
+        .iter()
+        .map(|(option, value)| {
+            if option == "profile" {
+                return Err(invalid(option, "profiles can't activate other profiles"));
+            }
+
+            let args = match value {
+                toml::Value::Boolean(true) => vec![format!("--{option}")],
+                toml::Value::Boolean(false) => Vec::new(),
+                toml::Value::Array(values) => values
+                    .iter()
+                    .map(|value| arg(option, value))
+                    .collect::<Result<_, _>>()?,
+                value => vec![arg(option, value)?],
+            };
+
+            Ok(ProfileOption {
+                name: option.clone(),
+                args,
+                overridden: false,
+            })
+        })
+        .collect()
+}
+
+/// Returns whether the option with the long name `option` was given on the
+/// command line, or [`None`] if neither `command` nor the subcommands
+/// `matches` were given for have that option.
+fn given(command: &Command, matches: &ArgMatches, option: &str) -> Option<bool> {
+    let arg = command.get_arguments().find(|arg| {
+        arg.get_long() == Some(option)
+            || arg
+                .get_all_aliases()
+                .is_some_and(|aliases| aliases.contains(&option))
+    });
+    if let Some(arg) = arg {
+        return Some(matches.value_source(arg.get_id().as_str()) == Some(ValueSource::CommandLine));
+    }
+
+    let (name, matches) = matches.subcommand()?;
+    given(command.find_subcommand(name)?, matches, option)
+}
+
+/// Adds the options set by the profile given with `--profile` to `args`,
+/// the command-line arguments `matches` were parsed from, leaving out those
+/// given on the command line so that they override the profile.
+///
+/// The options are added after the subcommand's own, so that both global
+/// and subcommand options can be set. `profiles` is left as it is, as it
+/// lists the profiles instead.
+pub fn apply_profile(
+    command: &Command,
+    matches: &ArgMatches,
+    mut args: Vec<OsString>,
+) -> Result<Vec<OsString>, Error> {
+    let Some(name) = matches.get_one::<String>("profile") else {
+        return Ok(args);
+    };
+    if matches.subcommand_name() == Some("profiles") {
+        return Ok(args);
+    }
+
+    let config = Config::load()?;
+    let mut added = Vec::new();
+    for option in expand(name, config.profile(name)?)? {
+        match given(command, matches, &option.name) {
+            Some(true) => {}
+            Some(false) => added.extend(option.args.into_iter().map(OsString::from)),
+            None => {
+                return Err(Error::InvalidProfile {
+                    profile: name.clone(),
+                    reason: format!(
+                        "'{}' has no --{} option",
+                        matches.subcommand_name().unwrap_or_default(),
+                        option.name
+                    ),
+                    option: option.name,
+                });
+            }
+        }
+    }
+
+    // Anything after `--` would be taken as positional arguments.
+    let end = args
+        .iter()
+        .position(|arg| arg == "--")
+        .unwrap_or(args.len());
+    args.splice(end..end, added);
+
+    Ok(args)
+}
+
+#[derive(Args)]
+pub struct ProfilesSubcommand {}
+
+/// The document printed by `profiles --json`.
+#[derive(Serialize)]
+struct ProfilesDocument {
+    profiles: Vec<ProfileDocument>,
+}
+
+#[derive(Serialize)]
+struct ProfileDocument {
+    name: String,
+    options: Vec<ProfileOption>,
+}
+
+impl ProfilesSubcommand {
+    /// Prints each profile defined in the config file, or only the one given
+    /// with `--profile`, with the arguments each of its options expands to.
+    ///
+    /// Options which were given on the command line, e.g. `--output`, are
+    /// marked as overriding the profile.
+    pub fn execute(self, command: &Command, matches: &ArgMatches) -> Result<ExitCode, Error> {
+        let config = Config::load()?;
+        let json = matches.get_flag("json");
+
+        let profiles = match matches.get_one::<String>("profile") {
+            Some(name) => vec![(name, config.profile(name)?)],
+            None => config.profile.iter().collect(),
+        };
+        let profiles = profiles
+            .into_iter()
+            .map(|(name, profile)| {
+                let mut options = expand(name, profile)?;
+                for option in &mut options {
+                    option.overridden = given(command, matches, &option.name) == Some(true);
+                }
+
+                Ok(ProfileDocument {
+                    name: name.clone(),
+                    options,
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        if json {
+            Document::new(ProfilesDocument { profiles }).print()?;
+
+            return Ok(ExitCode::SUCCESS);
+        }
+
+        if profiles.is_empty() {
+            let path = Config::path().unwrap_or_default();
+            eprintln!("The config file '{}' defines no profiles", path.display());
+        }
+        for profile in &profiles {
+            println!("{}", profile.name);
+            for option in &profile.options {
+                let args = if option.args.is_empty() {
+                    format!("(--{} left out)", option.name)
+                } else {
+                    option.args.join(" ")
+                };
+
+                if option.overridden {
+                    println!("    {args} (overridden on the command line)");
+                } else {
+                    println!("    {args}");
+                }
+            }
+        }
+
+        Ok(ExitCode::SUCCESS)
+    }
+}
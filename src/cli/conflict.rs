@@ -0,0 +1,78 @@
+//! What to do with files which already exist in the output directory, with
+//! `--on-conflict`.
+
+use crate::PROMPTING;
+use crate::cli::progress;
+use clap::ValueEnum;
+use extract_minecraft_assets::options::{Overwrite, OverwritePrompt};
+use std::io::{self, IsTerminal};
+use std::sync::Mutex;
+use std::sync::atomic::Ordering;
+
+/// What to do with a file which already exists where one is extracted.
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OnConflict {
+    /// Replace the existing file.
+    #[value(alias = "replace")]
+    Overwrite,
+    /// Keep the existing file, skipping the one extracted.
+    Skip,
+    /// Ask whether to replace each existing file, until answered with `all`
+    /// or `none`.
+    Prompt,
+}
+
+impl From<OnConflict> for Overwrite {
+    fn from(on_conflict: OnConflict) -> Self {
+        match on_conflict {
+            OnConflict::Overwrite => Self::Replace,
+            OnConflict::Skip => Self::Skip,
+            OnConflict::Prompt => Self::Ask,
+        }
+    }
+}
+
+/// Returns the prompt asking whether to replace each existing file.
+///
+/// Answering `all` or `none` replaces or keeps every file after without
+/// asking again, and anything but `y`, `yes` or `all` keeps the file. Fails
+/// if stdin or stderr is not a terminal, as nobody could answer.
+pub fn prompt() -> io::Result<OverwritePrompt> {
+    if !io::stdin().is_terminal() || !io::stderr().is_terminal() {
+        return Err(io::Error::other(
+            "--on-conflict prompt needs a terminal to ask on: pass --on-conflict skip or \
+             overwrite instead",
+        ));
+    }
+
+    // Files may be extracted on several threads, which ask one at a time.
+    let remembered: Mutex<Option<bool>> = Mutex::new(None);
+
+    Ok(OverwritePrompt::new(move |path| {
+        let mut remembered = remembered.lock().unwrap_or_else(|error| error.into_inner());
+        if let Some(replace) = *remembered {
+            return replace;
+        }
+
+        PROMPTING.store(true, Ordering::SeqCst);
+        let answer = progress::ask(format_args!(
+            "'{}' already exists. Replace it? [y/N/all/none] ",
+            path.display()
+        ));
+        PROMPTING.store(false, Ordering::SeqCst);
+
+        // A prompt which can't be answered keeps the file.
+        match answer.unwrap_or_default().trim().to_lowercase().as_str() {
+            "y" | "yes" => true,
+            "all" => {
+                *remembered = Some(true);
+                true
+            }
+            "none" => {
+                *remembered = Some(false);
+                false
+            }
+            _ => false,
+        }
+    }))
+}
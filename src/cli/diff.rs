@@ -0,0 +1,385 @@
+use crate::cli::error::Error;
+use crate::cli::json::Document;
+use clap::Args;
+use extract_minecraft_assets::diff::{Diff, DiffItem, DiffTotal, DiffTotals, OutputDiff};
+use extract_minecraft_assets::hashed::{IndexFile, IndexFileLocation};
+use extract_minecraft_assets::jar::{self, ContentsArgs, ExtractedContents};
+use extract_minecraft_assets::util;
+use serde::Serialize;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+#[derive(Args)]
+pub struct DiffIndexSubcommand {
+    /// The first index file to compare.
+    ///
+    /// Can either be a file path to the index file itself, or the name of
+    /// that version (e.g. `24` instead of `.minecraft/assets/indexes/24.json`).
+    /// Anything containing a path separator or ending in `.json` is a path.
+    #[arg(value_name = "A", value_parser = IndexFileLocation::parse)]
+    a: IndexFileLocation,
+    /// The second index file to compare, like the first.
+    #[arg(value_name = "B", value_parser = IndexFileLocation::parse)]
+    b: IndexFileLocation,
+    /// The path to the `.minecraft/assets/` directory to find index files by
+    /// name.
+    ///
+    /// Defaults to the default location on your OS.
+    #[arg(long = "hashed-assets", value_name = "DIRECTORY")]
+    hashed_assets_dir: Option<PathBuf>,
+    #[command(flatten)]
+    output: OutputArgs,
+}
+
+#[derive(Args)]
+pub struct DiffJarSubcommand {
+    /// The first jar file to compare.
+    #[arg(value_name = "A")]
+    a: PathBuf,
+    /// The second jar file to compare.
+    #[arg(value_name = "B")]
+    b: PathBuf,
+    #[command(flatten)]
+    contents: ContentsArgs,
+    /// Also compare the decompressed contents of files whose checksums and
+    /// sizes are the same.
+    ///
+    /// Slower, but detects changes which don't affect the checksum.
+    #[arg(long = "contents")]
+    compare_contents: bool,
+    #[command(flatten)]
+    output: OutputArgs,
+}
+
+#[derive(Args)]
+#[command(group = clap::ArgGroup::new("source").required(true))]
+pub struct DiffOutputSubcommand {
+    /// The directory assets were extracted into.
+    #[arg(value_name = "DIRECTORY")]
+    dir: PathBuf,
+    /// Compare against the jar or zip file the directory was extracted from.
+    #[arg(long, value_name = "FILE", group = "source")]
+    against_jar: Option<PathBuf>,
+    /// Compare against the index file the directory was extracted from.
+    ///
+    /// Can either be a file path to the index file itself, or the name of
+    /// that version (e.g. `24` instead of `.minecraft/assets/indexes/24.json`).
+    #[arg(
+        long,
+        value_name = "INDEX",
+        value_parser = IndexFileLocation::parse,
+        group = "source"
+    )]
+    against_index: Option<IndexFileLocation>,
+    /// The path to the `.minecraft/assets/` directory to find the index file
+    /// by name.
+    ///
+    /// Defaults to the default location on your OS.
+    #[arg(long = "hashed-assets", value_name = "DIRECTORY")]
+    hashed_assets_dir: Option<PathBuf>,
+    /// Compare the `assets` folder of the jar file.
+    ///
+    /// This is the default unless --data is given.
+    #[arg(short, long, requires = "against_jar")]
+    assets: bool,
+    /// Compare the `data` folder of the jar file.
+    ///
+    /// Can be combined with --assets.
+    #[arg(short, long, requires = "against_jar")]
+    data: bool,
+    /// Only print the paths which differ, each prefixed with `~` if modified,
+    /// `-` if missing, or `+` if extra.
+    #[arg(long)]
+    names_only: bool,
+}
+
+/// The output options shared by the `diff-*` subcommands.
+#[derive(Args)]
+struct OutputArgs {
+    /// Only print the paths which differ, each prefixed with `-` if only in A,
+    /// `+` if only in B, or `~` if changed.
+    #[arg(long, conflicts_with = "summary")]
+    names_only: bool,
+    /// Only print the number of files and bytes which differ.
+    #[arg(long)]
+    summary: bool,
+}
+
+/// The document printed by the `diff-*` subcommands with `--json`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DiffDocument<'a, T> {
+    totals: DiffTotals,
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    diff: Option<&'a Diff<T>>,
+}
+
+impl DiffIndexSubcommand {
+    /// Prints the differences between the two index files.
+    pub fn execute(self, json: bool, porcelain: bool) -> Result<(), Error> {
+        let hashed_assets_dir = self
+            .hashed_assets_dir
+            .or_else(util::hashed_assets_dir)
+            .unwrap_or_default();
+
+        let a = IndexFile::from_path(&self.a.resolve(&hashed_assets_dir)?)?;
+        let b = IndexFile::from_path(&self.b.resolve(&hashed_assets_dir)?)?;
+
+        self.output.print(&a.diff(&b), json, porcelain)
+    }
+}
+
+impl DiffJarSubcommand {
+    /// Prints the differences between the selected contents of the two jar
+    /// files.
+    pub fn execute(self, json: bool, porcelain: bool) -> Result<(), Error> {
+        let contents = ExtractedContents::from(self.contents);
+        let diff = jar::diff_jars(&self.a, &self.b, &contents, self.compare_contents)?;
+
+        self.output.print(&diff, json, porcelain)
+    }
+}
+
+impl DiffOutputSubcommand {
+    /// Prints the differences between the directory and its source, exiting
+    /// with 1 if there are any.
+    ///
+    /// With `porcelain`, each path is printed after its prefix and a tab, and
+    /// the number of each is printed to stderr unless --names-only is given.
+    pub fn execute(
+        self,
+        ignore_top_level: bool,
+        json: bool,
+        porcelain: bool,
+    ) -> Result<ExitCode, Error> {
+        let diff = match (self.against_jar, self.against_index) {
+            (Some(jar_file), _) => {
+                let contents = ExtractedContents::from(ContentsArgs {
+                    assets: self.assets || !self.data,
+                    data: self.data,
+                });
+
+                jar::diff_output(&jar_file, &self.dir, &contents, ignore_top_level)?
+            }
+            (None, Some(index)) => {
+                let hashed_assets_dir = self
+                    .hashed_assets_dir
+                    .or_else(util::hashed_assets_dir)
+                    .unwrap_or_default();
+                let index = IndexFile::from_path(&index.resolve(&hashed_assets_dir)?)?;
+
+                index.diff_output(&self.dir, ignore_top_level)?
+            }
+            (None, None) => unreachable!("a source is required"),
+        };
+
+        if json {
+            Document::new(&diff).print()?;
+        } else {
+            print_output_diff(&diff, self.names_only, porcelain);
+        }
+
+        Ok(if diff.is_empty() {
+            ExitCode::SUCCESS
+        } else {
+            ExitCode::FAILURE
+        })
+    }
+}
+
+impl OutputArgs {
+    /// Prints `diff` in the format selected by these options.
+    ///
+    /// With `porcelain`, fields are separated by tabs and sizes are in bytes:
+    /// each path which differs is printed after its prefix, or with its size
+    /// in A and B (`-` if it isn't in one) too unless --names-only is given.
+    /// The totals are then printed to stderr, as they summarize the paths,
+    /// unless --names-only is given. With --summary, only the totals are
+    /// printed.
+    fn print<T: DiffItem + Serialize>(
+        &self,
+        diff: &Diff<T>,
+        json: bool,
+        porcelain: bool,
+    ) -> Result<(), Error> {
+        if json {
+            Document::new(DiffDocument {
+                totals: diff.totals(),
+                diff: (!self.summary).then_some(diff),
+            })
+            .print()?;
+        } else if porcelain {
+            if self.summary {
+                print_porcelain_totals(diff);
+            } else {
+                print_porcelain_entries(diff, self.names_only);
+                if !self.names_only {
+                    print_totals(diff, &mut io::stderr())?;
+                }
+            }
+        } else if self.names_only {
+            print_names(diff);
+        } else {
+            if !self.summary {
+                print_entries(diff);
+            }
+            print_totals(diff, &mut io::stdout())?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Prints every path which differs, prefixed with its category.
+fn print_names<T>(diff: &Diff<T>) {
+    for entry in &diff.only_in_a {
+        println!("- {}", entry.path.display());
+    }
+    for entry in &diff.only_in_b {
+        println!("+ {}", entry.path.display());
+    }
+    for entry in &diff.changed {
+        println!("~ {}", entry.path.display());
+    }
+}
+
+/// Prints every path which differs along with its size, grouped by category.
+fn print_entries<T: DiffItem>(diff: &Diff<T>) {
+    for (heading, prefix, entries) in [
+        ("Only in A", '-', &diff.only_in_a),
+        ("Only in B", '+', &diff.only_in_b),
+    ] {
+        if !entries.is_empty() {
+            println!("{heading}:");
+        }
+        for entry in entries {
+            println!(
+                "  {prefix} {} ({})",
+                entry.path.display(),
+                util::format_size(entry.item.size())
+            );
+        }
+    }
+
+    if !diff.changed.is_empty() {
+        println!("Changed:");
+    }
+    for entry in &diff.changed {
+        println!(
+            "  ~ {} ({} -> {}, {})",
+            entry.path.display(),
+            util::format_size(entry.a.size()),
+            util::format_size(entry.b.size()),
+            format_delta(entry.size_delta())
+        );
+    }
+}
+
+/// Prints every path which differs, prefixed with its category, followed by
+/// its size in A and B unless `names_only` is set, separated by tabs.
+fn print_porcelain_entries<T: DiffItem>(diff: &Diff<T>, names_only: bool) {
+    let size =
+        |item: Option<&T>| item.map_or_else(|| String::from("-"), |item| item.size().to_string());
+    let print = |prefix: char, a: Option<&T>, b: Option<&T>, path: &Path| {
+        if names_only {
+            println!("{prefix}\t{}", path.display());
+        } else {
+            println!("{prefix}\t{}\t{}\t{}", size(a), size(b), path.display());
+        }
+    };
+
+    for entry in &diff.only_in_a {
+        print('-', Some(&entry.item), None, &entry.path);
+    }
+    for entry in &diff.only_in_b {
+        print('+', None, Some(&entry.item), &entry.path);
+    }
+    for entry in &diff.changed {
+        print('~', Some(&entry.a), Some(&entry.b), &entry.path);
+    }
+}
+
+/// Prints the number of files and bytes in each category to `out`.
+fn print_totals<T: DiffItem>(diff: &Diff<T>, out: &mut impl Write) -> io::Result<()> {
+    let totals = diff.totals();
+    let DiffTotal { files, bytes } = totals.only_in_a;
+    writeln!(
+        out,
+        "Only in A: {files} files ({})",
+        util::format_size(bytes as u64)
+    )?;
+    let DiffTotal { files, bytes } = totals.only_in_b;
+    writeln!(
+        out,
+        "Only in B: {files} files ({})",
+        util::format_size(bytes as u64)
+    )?;
+    let DiffTotal { files, bytes } = totals.changed;
+    writeln!(out, "Changed: {files} files ({})", format_delta(bytes))
+}
+
+/// Prints the number of files and bytes in each category, named
+/// `only-in-a`, `only-in-b` and `changed`, separated by tabs.
+///
+/// The bytes of changed files are how many more there are in B, so may be
+/// negative.
+fn print_porcelain_totals<T: DiffItem>(diff: &Diff<T>) {
+    let totals = diff.totals();
+
+    for (name, DiffTotal { files, bytes }) in [
+        ("only-in-a", totals.only_in_a),
+        ("only-in-b", totals.only_in_b),
+        ("changed", totals.changed),
+    ] {
+        println!("{name}\t{files}\t{bytes}");
+    }
+}
+
+/// Prints every path which differs from the source, grouped by category
+/// unless `names_only` or `porcelain` is set, followed by the number of each.
+fn print_output_diff(diff: &OutputDiff, names_only: bool, porcelain: bool) {
+    let categories = [
+        ("Modified", '~', &diff.modified),
+        ("Missing", '-', &diff.missing),
+        ("Extra", '+', &diff.extra),
+    ];
+
+    for (heading, prefix, paths) in categories {
+        if !names_only && !porcelain && !paths.is_empty() {
+            println!("{heading}:");
+        }
+        for path in paths {
+            if porcelain {
+                println!("{prefix}\t{}", path.display());
+            } else if names_only {
+                println!("{prefix} {}", path.display());
+            } else {
+                println!("  {prefix} {}", path.display());
+            }
+        }
+    }
+
+    if !names_only {
+        let counts = format!(
+            "Modified: {}, missing: {}, extra: {}",
+            diff.modified.len(),
+            diff.missing.len(),
+            diff.extra.len()
+        );
+
+        // Only the paths are the result with --porcelain.
+        if porcelain {
+            eprintln!("{counts}");
+        } else {
+            println!("{counts}");
+        }
+    }
+}
+
+/// Formats a change in size, e.g. `+1.5 KiB` or `-20 B`.
+fn format_delta(bytes: i64) -> String {
+    let sign = if bytes < 0 { '-' } else { '+' };
+
+    format!("{sign}{}", util::format_size(bytes.unsigned_abs()))
+}
@@ -0,0 +1,215 @@
+use crate::cli::json::Document;
+use clap::Args;
+use crossterm::terminal;
+use extract_minecraft_assets::util;
+use serde::Serialize;
+use std::fs;
+use std::io::{self, IsTerminal};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+#[derive(Args)]
+pub struct DoctorSubcommand {}
+
+/// A diagnosis of the environment the tool is running in.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Diagnosis {
+    /// Every `.minecraft` directory location which is probed, in order of preference.
+    installations: Vec<Installation>,
+    /// The output directory.
+    output_dir: PathBuf,
+    /// The number of bytes available on the output directory's volume, if known.
+    free_space: Option<u64>,
+    /// Whether the terminal supports rendering progress messages.
+    terminal: Terminal,
+}
+
+/// A probed `.minecraft` directory location.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Installation {
+    minecraft_dir: Probe,
+    hashed_assets_dir: Probe,
+    versions_dir: Probe,
+    /// The number of version directories containing a jar file.
+    versions: usize,
+    /// The number of index files within `assets/indexes/`.
+    indexes: usize,
+}
+
+/// A probed path and whether it exists.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Probe {
+    path: PathBuf,
+    exists: bool,
+}
+
+/// Information about the terminal which progress is rendered to.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Terminal {
+    /// Whether stderr, where progress is rendered, is a terminal.
+    is_terminal: bool,
+    /// The size of the terminal in columns and rows, if it could be determined.
+    size: Option<(u16, u16)>,
+}
+
+impl Probe {
+    fn new(path: PathBuf) -> Self {
+        let exists = util::is_dir(&path);
+
+        Self { path, exists }
+    }
+}
+
+impl Installation {
+    fn probe(minecraft_dir: PathBuf) -> Self {
+        let hashed_assets_dir = Probe::new(minecraft_dir.join("assets"));
+        let versions_dir = Probe::new(minecraft_dir.join("versions"));
+
+        let versions = count_entries(&versions_dir.path, |path| {
+            path.file_name().is_some_and(|name| {
+                let mut jar_name = name.to_owned();
+                jar_name.push(".jar");
+
+                path.join(jar_name).is_file()
+            })
+        });
+        let indexes = count_entries(&hashed_assets_dir.path.join("indexes"), |path| {
+            path.is_file()
+                && path
+                    .extension()
+                    .is_some_and(|extension| extension == "json")
+        });
+
+        Self {
+            minecraft_dir: Probe::new(minecraft_dir),
+            hashed_assets_dir,
+            versions_dir,
+            versions,
+            indexes,
+        }
+    }
+
+    /// Whether anything can be extracted from this installation.
+    fn is_usable(&self) -> bool {
+        self.minecraft_dir.exists && (self.versions > 0 || self.indexes > 0)
+    }
+}
+
+/// Counts the entries of `dir` for which `filter` returns true.
+///
+/// Returns 0 if `dir` cannot be read.
+fn count_entries(dir: &Path, filter: impl Fn(&Path) -> bool) -> usize {
+    fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .filter(|entry| filter(&entry.path()))
+                .count()
+        })
+        .unwrap_or(0)
+}
+
+/// Describes whether a path exists, e.g. `found` or `missing`.
+fn existence(exists: bool) -> &'static str {
+    if exists {
+        "found"
+    } else {
+        "missing"
+    }
+}
+
+impl DoctorSubcommand {
+    /// Prints a diagnosis of path discovery and the environment, as a JSON
+    /// document if `json` is set (e.g. for inclusion in bug reports).
+    ///
+    /// Fails if no usable Minecraft installation was found.
+    pub fn execute(self, output_dir: &Path, json: bool) -> io::Result<ExitCode> {
+        let diagnosis = Diagnosis {
+            installations: util::minecraft_dir_candidates()
+                .into_iter()
+                .map(Installation::probe)
+                .collect(),
+            output_dir: output_dir.to_owned(),
+            free_space: util::free_space(output_dir),
+            terminal: Terminal {
+                is_terminal: io::stderr().is_terminal(),
+                size: terminal::size().ok(),
+            },
+        };
+
+        if json {
+            Document::new(&diagnosis).print()?;
+        } else {
+            diagnosis.print();
+        }
+
+        Ok(
+            if diagnosis.installations.iter().any(Installation::is_usable) {
+                ExitCode::SUCCESS
+            } else {
+                eprintln!("No usable Minecraft installation found");
+
+                ExitCode::FAILURE
+            },
+        )
+    }
+}
+
+impl Diagnosis {
+    /// Prints the diagnosis in a human-readable format.
+    fn print(&self) {
+        if self.installations.is_empty() {
+            println!("No locations to probe for a Minecraft installation");
+        }
+
+        for installation in &self.installations {
+            let Installation {
+                minecraft_dir,
+                hashed_assets_dir,
+                versions_dir,
+                versions,
+                indexes,
+            } = installation;
+
+            println!(
+                "Minecraft directory: {} ({})",
+                minecraft_dir.path.display(),
+                existence(minecraft_dir.exists)
+            );
+            println!(
+                "  Hashed assets: {} ({}, {indexes} indexes)",
+                hashed_assets_dir.path.display(),
+                existence(hashed_assets_dir.exists)
+            );
+            println!(
+                "  Versions: {} ({}, {versions} versions)",
+                versions_dir.path.display(),
+                existence(versions_dir.exists)
+            );
+        }
+
+        match self.free_space {
+            Some(free_space) => println!(
+                "Output directory: {} ({} free)",
+                self.output_dir.display(),
+                util::format_size(free_space)
+            ),
+            None => println!(
+                "Output directory: {} (free space unknown)",
+                self.output_dir.display()
+            ),
+        }
+
+        match (self.terminal.is_terminal, self.terminal.size) {
+            (true, Some((columns, rows))) => {
+                println!("Terminal: progress supported ({columns}x{rows})")
+            }
+            (true, None) => println!("Terminal: progress supported (unknown size)"),
+            (false, _) => println!("Terminal: not a terminal, progress will not render properly"),
+        }
+    }
+}
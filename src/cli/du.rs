@@ -0,0 +1,138 @@
+use crate::cli::error::Error;
+use crate::cli::json::Document;
+use crate::cli::{hashed, jar, version};
+use clap::{Args, Subcommand};
+use extract_minecraft_assets::options::ExtractOptions;
+use extract_minecraft_assets::usage::{self, DiskUsage, Usage, UsageEstimate};
+use extract_minecraft_assets::util;
+use std::path::Path;
+
+#[derive(Args)]
+pub struct DuSubcommand {
+    #[command(subcommand)]
+    extraction: Extraction,
+    /// Also estimate the space the files would take up on disk, with each
+    /// file rounded up to a whole number of blocks.
+    ///
+    /// The block size is that of the output directory's volume, or 4 KiB if
+    /// it can't be determined.
+    #[arg(long, global = true)]
+    apparent: bool,
+}
+
+/// The extraction to estimate, given as it would be extracted.
+#[derive(Subcommand)]
+pub enum Extraction {
+    /// Estimates extracting hashed Minecraft assets.
+    Hashed(hashed::HashedSubcommand),
+    /// Estimates extracting non-hashed Minecraft `assets`, or `data`, from a
+    /// jar file.
+    Jar(jar::JarSubcommand),
+    /// Estimates extracting both hashed and non-hashed Minecraft `assets`, or
+    /// `data`.
+    Version(version::VersionSubcommand),
+}
+
+impl Extraction {
+    /// Collects the files the extraction would write with `options`.
+    pub fn estimate(&self, options: &ExtractOptions) -> Result<UsageEstimate, Error> {
+        match self {
+            Self::Hashed(subcommand) => subcommand.estimate(options),
+            Self::Jar(subcommand) => subcommand.estimate(options),
+            Self::Version(subcommand) => subcommand.estimate(options),
+        }
+    }
+}
+
+impl DuSubcommand {
+    /// Prints the estimated disk usage of extracting with `options`.
+    pub fn execute(self, options: &ExtractOptions, json: bool) -> Result<(), Error> {
+        let estimate = self.extraction.estimate(options)?;
+        let block_size = self
+            .apparent
+            .then(|| block_size(&options.output_dir).unwrap_or(usage::DEFAULT_BLOCK_SIZE));
+        let usage = estimate.usage(block_size);
+
+        if json {
+            Document::new(&usage).print()?;
+        } else {
+            print_table(&usage);
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the block size of the volume `output_dir` would be created on,
+/// which is that of its closest existing ancestor.
+fn block_size(output_dir: &Path) -> Option<u64> {
+    output_dir
+        .ancestors()
+        .find(|dir| dir.is_dir())
+        .and_then(util::block_size)
+}
+
+/// Prints the usage as aligned tables, by source and by top-level directory.
+fn print_table(usage: &DiskUsage) {
+    let sources = usage
+        .sources
+        .iter()
+        .map(|(source, usage)| (source.name().to_owned(), *usage));
+    print_group("Source", usage.block_size, sources);
+
+    let directories = usage.directories.iter().map(|(directory, usage)| {
+        let name = if directory.is_empty() {
+            "(none)"
+        } else {
+            directory
+        };
+        (name.to_owned(), *usage)
+    });
+    print_group("Directory", usage.block_size, directories);
+
+    print_row("Total", usage.total);
+    if let Some(block_size) = usage.block_size {
+        println!();
+        println!(
+            "On disk sizes are in blocks of {}",
+            util::format_size(block_size)
+        );
+    }
+}
+
+/// Prints a table of the `rows` of a group, under `heading`.
+fn print_group(
+    heading: &str,
+    block_size: Option<u64>,
+    rows: impl Iterator<Item = (String, Usage)>,
+) {
+    match block_size {
+        Some(_) => println!(
+            "{heading:<20} {:>8} {:>12} {:>12}",
+            "Files", "Size", "On disk"
+        ),
+        None => println!("{heading:<20} {:>8} {:>12}", "Files", "Size"),
+    }
+    for (name, usage) in rows {
+        print_row(&name, usage);
+    }
+    println!();
+}
+
+/// Prints a row of a table.
+fn print_row(name: &str, usage: Usage) {
+    let Usage {
+        files,
+        bytes,
+        allocated,
+    } = usage;
+
+    match allocated {
+        Some(allocated) => println!(
+            "{name:<20} {files:>8} {:>12} {:>12}",
+            util::format_size(bytes),
+            util::format_size(allocated)
+        ),
+        None => println!("{name:<20} {files:>8} {:>12}", util::format_size(bytes)),
+    }
+}
@@ -0,0 +1,72 @@
+use crate::cli::error::Error;
+use crate::cli::json::Document;
+use crate::cli::source::SourceArgs;
+use clap::Args;
+use extract_minecraft_assets::duplicates::{Cluster, DuplicateFinder};
+use extract_minecraft_assets::util;
+use serde::Serialize;
+
+#[derive(Args)]
+pub struct DuplicatesSubcommand {
+    #[command(flatten)]
+    source: SourceArgs,
+    /// Hide files smaller than this.
+    ///
+    /// Accepts sizes like `100`, `4 KiB` or `1M`.
+    #[arg(long, value_name = "SIZE", value_parser = util::parse_size, default_value = "0")]
+    min_size: u64,
+}
+
+/// The document printed by `duplicates --json`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DuplicatesDocument {
+    clusters: Vec<Cluster>,
+    /// The bytes wasted by every cluster.
+    wasted_bytes: u64,
+}
+
+impl DuplicatesSubcommand {
+    /// Prints the clusters of files with identical contents.
+    pub fn execute(self, json: bool) -> Result<(), Error> {
+        let mut finder = DuplicateFinder::new();
+        if let Some(jar_file) = self.source.jar_file() {
+            finder.add_jar(&jar_file, &self.source.contents())?;
+        }
+        if let Some(index) = self.source.index()? {
+            finder.add_index(&index);
+        }
+
+        let clusters = finder.clusters(self.min_size);
+        let wasted_bytes = clusters.iter().map(|cluster| cluster.wasted_bytes).sum();
+
+        if json {
+            Document::new(DuplicatesDocument {
+                clusters,
+                wasted_bytes,
+            })
+            .print()?;
+
+            return Ok(());
+        }
+
+        for cluster in &clusters {
+            println!(
+                "{} files of {} ({} wasted):",
+                cluster.paths.len(),
+                util::format_size(cluster.size),
+                util::format_size(cluster.wasted_bytes)
+            );
+            for path in &cluster.paths {
+                println!("  {}", path.display());
+            }
+        }
+        println!(
+            "{} clusters, {} wasted",
+            clusters.len(),
+            util::format_size(wasted_bytes)
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,252 @@
+use extract_minecraft_assets::error::ExtractError;
+use extract_minecraft_assets::filter::PatternError;
+use extract_minecraft_assets::pack::PackError;
+use extract_minecraft_assets::sounds;
+use extract_minecraft_assets::util;
+use std::io;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use thiserror::Error;
+
+/// An error which aborts a subcommand.
+#[derive(Error, Debug)]
+pub enum Error {
+    /// The extraction itself failed.
+    #[error(transparent)]
+    Extract(#[from] ExtractError),
+    /// A hash prefix given by the user matches more than one hash.
+    #[error("hash prefix '{prefix}' is ambiguous: it matches {}", .hashes.join(", "))]
+    AmbiguousHash { prefix: String, hashes: Vec<String> },
+    /// Hashed assets are needed, but no `.minecraft/assets/` directory was
+    /// found at any of the `probed` paths.
+    #[error(
+        "no hashed assets directory found{}; pass --hashed-assets to give its location",
+        probed_message(.probed)
+    )]
+    NoHashedAssets { probed: Vec<PathBuf> },
+    /// Every installed version is to be extracted, but no `.minecraft/versions/`
+    /// directory with any versions was found at the `probed` paths.
+    #[error(
+        "no installed versions found{}; pass --versions to give the location of the versions \
+         directory",
+        probed_message(.probed)
+    )]
+    NoVersions { probed: Vec<PathBuf> },
+    /// None of the files of some requested content roots (e.g. `data`) are
+    /// in the jar file.
+    #[error(
+        "'{}' has no {}; pass --allow-empty to extract anyway",
+        .jar_file.display(),
+        .roots.join(" or ")
+    )]
+    MissingContents {
+        jar_file: PathBuf,
+        /// The names of the roots without any files.
+        roots: Vec<String>,
+    },
+    /// Nothing is in the jar file at some paths given with `--entry`.
+    #[error(
+        "'{}' has nothing at {}",
+        .jar_file.display(),
+        .entries.iter().map(|entry| format!("'{}'", entry.display())).collect::<Vec<_>>().join(", ")
+    )]
+    MissingEntries {
+        jar_file: PathBuf,
+        entries: Vec<PathBuf>,
+    },
+    /// A jar file was given as a URL, but it can't be read as one.
+    #[error("can't read the jar file at '{url}': {reason}")]
+    RemoteJar { url: String, reason: String },
+    /// The output directory is inside a directory being extracted from, or
+    /// one being extracted from is inside a directory being written to, so
+    /// extracting could overwrite files while they are being read.
+    #[error(
+        "the output directory '{}' {} '{}'; pass --unsafe-allow-overlap to extract there anyway",
+        .output.display(),
+        if *.contains { "contains" } else { "is inside" },
+        .source_dir.display()
+    )]
+    OutputOverlaps {
+        output: PathBuf,
+        source_dir: PathBuf,
+        /// Whether the output directory contains `source_dir`, rather than
+        /// being inside it.
+        contains: bool,
+    },
+    /// Another process is extracting into the output directory, as it holds
+    /// the lock file at `path`.
+    #[error(
+        "the output directory is locked by {}, as '{}' exists; pass --no-lock to extract anyway, \
+         or remove it if no extraction is running",
+        match .pid {
+            Some(pid) => format!("process {pid}"),
+            None => "another process".to_owned(),
+        },
+        .path.display()
+    )]
+    OutputLocked {
+        path: PathBuf,
+        /// The ID of the process holding the lock, if known.
+        pid: Option<u32>,
+    },
+    /// `check-sounds` was given a version without a `sounds.json` in either
+    /// its index file or its jar file.
+    #[error(
+        "version '{version}' has no '{}' in its index file or jar file",
+        sounds::SOUNDS_FILE
+    )]
+    NoSoundEvents { version: String },
+    /// `clean-run` or `verify-report` was given without the report of an
+    /// extraction.
+    #[error("pass the report of the extraction with --report")]
+    NoReport,
+    /// `--profile` was given a profile which the config file doesn't define.
+    #[error(
+        "no profile '{name}' in the config file; {}",
+        if .available.is_empty() {
+            "it defines no profiles".to_owned()
+        } else {
+            format!("the available profiles are {}", .available.join(", "))
+        }
+    )]
+    UnknownProfile {
+        name: String,
+        available: Vec<String>,
+    },
+    /// An option of a profile in the config file can't be given to the
+    /// subcommand.
+    #[error("invalid option '{option}' in profile '{profile}': {reason}")]
+    InvalidProfile {
+        profile: String,
+        option: String,
+        reason: String,
+    },
+    /// `--sync` was given to a subcommand which doesn't extract the whole of
+    /// its source, so every file it leaves out would look stale.
+    #[error(
+        "--sync can't be used with '{command}', as it doesn't extract every file of its source"
+    )]
+    SyncUnsupported { command: String },
+    /// `--dry-run` was given to a subcommand which writes files itself, e.g.
+    /// moving versions into place, so it can't be kept from writing.
+    #[error("--dry-run can't be used with '{command}', as it writes files itself")]
+    DryRunUnsupported { command: String },
+    /// `--git-commit` was given, but the output directory isn't a git
+    /// repository.
+    #[cfg(feature = "git")]
+    #[error(
+        "the output directory '{}' isn't a git repository; pass --git-init to create one",
+        .path.display()
+    )]
+    NotARepository { path: PathBuf },
+    /// `--git-commit` was given, but the repository in the output directory
+    /// has staged changes, which would be committed along with the extracted
+    /// files.
+    #[cfg(feature = "git")]
+    #[error(
+        "the git repository in the output directory has staged changes to {}; commit or unstage \
+         them first",
+        .staged.iter().map(|path| format!("'{}'", path.display())).collect::<Vec<_>>().join(", ")
+    )]
+    StagedChanges { staged: Vec<PathBuf> },
+    /// `--git-tag` was given, but the subcommand doesn't extract from a
+    /// version.
+    #[cfg(feature = "git")]
+    #[error("there is no version to tag the commit with; leave out --git-tag")]
+    NoVersionToTag,
+    /// The git repository in the output directory couldn't be read or
+    /// written.
+    #[cfg(feature = "git")]
+    #[error("git failed: {0}")]
+    Git(#[from] git2::Error),
+    /// The output directory doesn't exist, or isn't a directory.
+    #[error("the output directory '{}' doesn't exist or isn't a directory", .path.display())]
+    OutputNotADirectory { path: PathBuf },
+    /// Files can't be created in the output directory.
+    #[error("the output directory '{}' isn't writable: {source}", .path.display())]
+    OutputNotWritable {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    /// The resource pack to write is invalid, e.g. its overlays overlap.
+    #[error(transparent)]
+    Pack(#[from] PackError),
+    /// A pattern given by the user isn't a valid regular expression.
+    #[error(transparent)]
+    Pattern(#[from] PatternError),
+    /// Anything else failed, e.g. the terminal or the confirmation prompt.
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+impl Error {
+    /// The exit code to exit with because of this error.
+    ///
+    /// - `3` if the Minecraft files being extracted are missing or invalid.
+    /// - `4` if the output couldn't be read or written.
+    /// - `5` if requested contents are absent from the jar file.
+    /// - `1` for anything else.
+    pub fn exit_code(&self) -> ExitCode {
+        match self {
+            Self::Extract(
+                ExtractError::ReadManifest { .. }
+                | ExtractError::ParseManifest { .. }
+                | ExtractError::NoAssetIndex { .. }
+                | ExtractError::MissingIndex { .. }
+                | ExtractError::Index { .. }
+                | ExtractError::MissingObject { .. }
+                | ExtractError::ReadObject { .. }
+                | ExtractError::CorruptObject { .. }
+                | ExtractError::ParseSounds { .. }
+                | ExtractError::Zip { .. }
+                | ExtractError::DuplicateEntries { .. },
+            )
+            | Self::NoHashedAssets { .. }
+            | Self::NoVersions { .. }
+            | Self::NoSoundEvents { .. } => ExitCode::from(3),
+            Self::Extract(
+                ExtractError::WriteFile { .. }
+                | ExtractError::DirectoryInTheWay { .. }
+                | ExtractError::ReadOutput { .. }
+                | ExtractError::ResolvePath { .. },
+            )
+            | Self::OutputNotADirectory { .. }
+            | Self::OutputNotWritable { .. } => ExitCode::from(4),
+            Self::MissingContents { .. } | Self::MissingEntries { .. } => ExitCode::from(5),
+            Self::Extract(_)
+            | Self::AmbiguousHash { .. }
+            | Self::OutputOverlaps { .. }
+            | Self::OutputLocked { .. }
+            | Self::NoReport
+            | Self::Pattern(_)
+            | Self::RemoteJar { .. }
+            | Self::UnknownProfile { .. }
+            | Self::InvalidProfile { .. }
+            | Self::SyncUnsupported { .. }
+            | Self::DryRunUnsupported { .. }
+            | Self::Pack(_)
+            | Self::Io(_) => ExitCode::FAILURE,
+            #[cfg(feature = "git")]
+            Self::NotARepository { .. }
+            | Self::StagedChanges { .. }
+            | Self::NoVersionToTag
+            | Self::Git(_) => ExitCode::FAILURE,
+        }
+    }
+}
+
+/// Lists the `probed` paths for the end of an error message, e.g.
+/// ` at '/home/steve/.minecraft/assets'`.
+fn probed_message(probed: &[PathBuf]) -> String {
+    if probed.is_empty() {
+        return ", as there is no default location on this OS".to_owned();
+    }
+
+    let paths: Vec<_> = probed
+        .iter()
+        .map(|path| util::display_resolved(path))
+        .collect();
+
+    format!(" at {}", paths.join(", "))
+}
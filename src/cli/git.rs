@@ -0,0 +1,221 @@
+//! Committing each extraction to a git repository in the output directory,
+//! with `--git-commit`.
+
+use crate::cli::error::Error;
+use clap::Args;
+use extract_minecraft_assets::report::ExtractionReport;
+use extract_minecraft_assets::version::Version;
+use git2::{ErrorCode, IndexAddOption, Oid, Repository, Signature};
+use std::path::{Path, PathBuf};
+
+/// Committing the extracted files, shared by the extraction subcommands.
+#[derive(Args)]
+pub struct GitArgs {
+    /// After extracting, commit the directories extracted into (e.g.
+    /// `assets` and `data`) to the git repository in the output directory.
+    ///
+    /// The commit message gives the version and index file extracted from,
+    /// and the version of this tool. Files deleted from those directories
+    /// are committed too, so that diffing commits diffs versions. Nothing is
+    /// extracted if the repository has staged changes, which would be
+    /// committed along with the extracted files, and nothing is committed if
+    /// the extraction stopped early or nothing changed.
+    #[arg(
+        long,
+        conflicts_with_all = ["ignore_top_level", "output_zip", "dry_run"],
+        global = true
+    )]
+    git_commit: bool,
+    /// Create a git repository in the output directory if there isn't one.
+    #[arg(long, requires = "git_commit", global = true)]
+    git_init: bool,
+    /// Tag the commit with the name of the version extracted from.
+    ///
+    /// If nothing changed, the last commit is tagged instead. Fails if the
+    /// tag already exists.
+    #[arg(long, requires = "git_commit", global = true)]
+    git_tag: bool,
+}
+
+/// What an extraction extracted from, to describe it in a commit.
+#[derive(Clone, Debug, Default)]
+pub struct Source {
+    /// The name of the version extracted from, e.g. `1.20.1`.
+    pub version: Option<String>,
+    /// The ID of the index file extracted from, e.g. `8`.
+    pub index: Option<String>,
+}
+
+impl Source {
+    /// Returns the source of an extraction from `version`, and from its index
+    /// file if its manifest can be read.
+    pub fn version(version: &Version) -> Self {
+        let manifest = version.manifest().ok();
+
+        Self {
+            version: Some(version.name().to_owned()),
+            index: manifest
+                .as_ref()
+                .and_then(|manifest| manifest.index_id())
+                .map(str::to_owned),
+        }
+    }
+}
+
+/// A repository to commit the extracted files to once extracted.
+pub struct GitCommit {
+    repository: Repository,
+    tag: bool,
+}
+
+impl GitArgs {
+    /// Returns the repository in `output_dir` to commit to, if --git-commit
+    /// is given, creating it with --git-init.
+    ///
+    /// This is done before extracting anything, so that a missing repository
+    /// or staged changes are reported up front. A `source` without a version
+    /// can't be tagged.
+    pub fn prepare(&self, output_dir: &Path, source: &Source) -> Result<Option<GitCommit>, Error> {
+        if !self.git_commit {
+            return Ok(None);
+        }
+
+        let repository = match Repository::open(output_dir) {
+            Ok(repository) => repository,
+            Err(error) if error.code() == ErrorCode::NotFound && self.git_init => {
+                let repository = Repository::init(output_dir)?;
+                eprintln!("Created a git repository in '{}'", output_dir.display());
+                repository
+            }
+            Err(error) if error.code() == ErrorCode::NotFound => {
+                return Err(Error::NotARepository {
+                    path: output_dir.to_owned(),
+                });
+            }
+            Err(error) => return Err(error.into()),
+        };
+
+        let staged = staged_changes(&repository)?;
+        if !staged.is_empty() {
+            return Err(Error::StagedChanges { staged });
+        }
+
+        if self.git_tag && source.version.is_none() {
+            return Err(Error::NoVersionToTag);
+        }
+
+        Ok(Some(GitCommit {
+            repository,
+            tag: self.git_tag,
+        }))
+    }
+}
+
+impl GitCommit {
+    /// Stages the directories which the extraction of `report` wrote to, and
+    /// commits them with a message describing `source`, tagging the commit
+    /// with the version with --git-tag.
+    ///
+    /// Does nothing if the extraction stopped early.
+    pub fn commit(&self, report: &ExtractionReport, source: &Source) -> Result<(), Error> {
+        if report.summary.stopped() {
+            return Ok(());
+        }
+
+        let repository = &self.repository;
+        let roots: Vec<PathBuf> = report
+            .options
+            .contents
+            .roots()
+            .iter()
+            .map(|root| root.output_dir.clone())
+            .collect();
+
+        // Deleted files are only staged by updating the index.
+        let mut index = repository.index()?;
+        index.add_all(&roots, IndexAddOption::DEFAULT, None)?;
+        index.update_all(&roots, None)?;
+        index.write()?;
+        let tree = repository.find_tree(index.write_tree()?)?;
+
+        let parent = match repository.head() {
+            Ok(head) => Some(head.peel_to_commit()?),
+            Err(error) if error.code() == ErrorCode::UnbornBranch => None,
+            Err(error) => return Err(error.into()),
+        };
+
+        let commit = match &parent {
+            Some(parent) if parent.tree_id() == tree.id() => {
+                eprintln!("Nothing changed since the last commit, so nothing was committed");
+                parent.id()
+            }
+            _ => {
+                // Commits are made by the tool if git has no user configured.
+                let signature = repository.signature().or_else(|_| {
+                    Signature::now(env!("CARGO_PKG_NAME"), "extract-minecraft-assets@localhost")
+                })?;
+                let message = commit_message(report, source);
+                let parents: Vec<_> = parent.iter().collect();
+                let commit = repository.commit(
+                    Some("HEAD"),
+                    &signature,
+                    &signature,
+                    &message,
+                    &tree,
+                    &parents,
+                )?;
+                eprintln!("Committed the extracted files as {}", short_id(commit));
+
+                commit
+            }
+        };
+
+        if self.tag
+            && let Some(version) = &source.version
+        {
+            let object = repository.find_object(commit, None)?;
+            repository.tag_lightweight(version, &object, false)?;
+            eprintln!("Tagged {} as '{version}'", short_id(commit));
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the paths of the files whose changes are staged in `repository`.
+fn staged_changes(repository: &Repository) -> Result<Vec<PathBuf>, git2::Error> {
+    let head_tree = match repository.head() {
+        Ok(head) => Some(head.peel_to_tree()?),
+        Err(error) if error.code() == ErrorCode::UnbornBranch => None,
+        Err(error) => return Err(error),
+    };
+    let diff = repository.diff_tree_to_index(head_tree.as_ref(), None, None)?;
+
+    Ok(diff
+        .deltas()
+        .filter_map(|delta| delta.new_file().path().map(Path::to_owned))
+        .collect())
+}
+
+/// Returns the message of the commit of the extraction of `report`, e.g.
+/// `Extract assets of 1.20.1 (index 8)`.
+fn commit_message(report: &ExtractionReport, source: &Source) -> String {
+    let mut subject = format!("Extract {}", report.options.contents);
+    match (&source.version, &source.index) {
+        (Some(version), Some(index)) => subject += &format!(" of {version} (index {index})"),
+        (Some(version), None) => subject += &format!(" of {version}"),
+        (None, Some(index)) => subject += &format!(" of index {index}"),
+        (None, None) => {}
+    }
+
+    format!(
+        "{subject}\n\nExtracted by {} {}\n",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION")
+    )
+}
+
+/// Returns the abbreviated form of the commit `id`.
+fn short_id(id: Oid) -> String {
+    id.to_string()[..7].to_owned()
+}
@@ -0,0 +1,117 @@
+use crate::cli::error::Error;
+use crate::cli::json::Document;
+use crate::cli::search::ContentType;
+use crate::cli::source::Source;
+use clap::Args;
+use extract_minecraft_assets::grep::{Grep, GrepStats, LineHit, TextPattern};
+use extract_minecraft_assets::util;
+use serde::Serialize;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+#[derive(Args)]
+pub struct GrepSubcommand {
+    /// Match lines containing this.
+    #[arg(value_name = "PATTERN")]
+    pattern: String,
+    /// Treat the pattern as a regular expression, matching lines containing
+    /// a match of it.
+    #[arg(long)]
+    regex: bool,
+    /// The version, jar file or index file to search.
+    ///
+    /// Paths ending in `.jar` or `.zip` are jar files, and paths ending in
+    /// `.json` are index files. Otherwise, this is the name or directory of a
+    /// version if there is one, or else the name of an index file. A version
+    /// includes both its jar file and its index file.
+    #[arg(long = "version", value_name = "VERSION, JAR or INDEX", value_parser = Source::parse)]
+    source: Source,
+    /// The path to the `.minecraft/assets/` directory to find index files.
+    ///
+    /// Defaults to the default location on your OS.
+    #[arg(long = "hashed-assets", value_name = "DIRECTORY")]
+    hashed_assets_dir: Option<PathBuf>,
+    /// Only search `assets` or `data`.
+    #[arg(long = "type", value_name = "TYPE")]
+    content_type: Option<ContentType>,
+    /// Only print the paths of the files with a matching line.
+    #[arg(short = 'l', long)]
+    files_with_matches: bool,
+}
+
+/// The document printed by `grep --json`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GrepDocument {
+    /// The matching lines, or only the first of each file with
+    /// `--files-with-matches`.
+    hits: Vec<LineHit>,
+    #[serde(flatten)]
+    stats: GrepStats,
+}
+
+impl GrepSubcommand {
+    /// Prints the lines of text files matching the pattern as they are found,
+    /// exiting with 1 if there are none.
+    pub fn execute(self, json: bool) -> Result<ExitCode, Error> {
+        let pattern = if self.regex {
+            TextPattern::regex(&self.pattern)?
+        } else {
+            TextPattern::Substring(self.pattern)
+        };
+        let grep = Grep::new(pattern).first_per_file(self.files_with_matches);
+        let contents = ContentType::contents(self.content_type);
+        let hashed_assets_dir = self
+            .hashed_assets_dir
+            .or_else(util::hashed_assets_dir)
+            .unwrap_or_default();
+
+        let mut found = false;
+        let mut hits = Vec::new();
+        let mut on_hit = |hit: LineHit| {
+            found = true;
+            if json {
+                hits.push(hit);
+            } else if self.files_with_matches {
+                println!("{}", hit.path.display());
+            } else {
+                println!("{}:{}: {}", hit.path.display(), hit.line, hit.text);
+            }
+        };
+
+        let mut stats = GrepStats::default();
+        if let Some(jar_file) = self.source.jar_file() {
+            stats = grep.search_jar(&jar_file, &contents, &mut on_hit)?;
+        }
+        if let Some(index) = self
+            .source
+            .index(&hashed_assets_dir, contents.contains("assets"))?
+        {
+            stats = stats + grep.search_index(&hashed_assets_dir, &index, &mut on_hit)?;
+        }
+
+        if stats.missing > 0 {
+            eprintln!(
+                "Warning: {} hashed {} missing from '{}'",
+                stats.missing,
+                if stats.missing == 1 {
+                    "file is"
+                } else {
+                    "files are"
+                },
+                hashed_assets_dir.join("objects").display()
+            );
+        }
+
+        let exit_code = if found {
+            ExitCode::SUCCESS
+        } else {
+            ExitCode::FAILURE
+        };
+        if json {
+            Document::new(GrepDocument { hits, stats }).print()?;
+        }
+
+        Ok(exit_code)
+    }
+}
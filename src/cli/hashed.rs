@@ -0,0 +1,177 @@
+use crate::cli::error::Error;
+#[cfg(feature = "git")]
+use crate::cli::git;
+use crate::cli::namespace::NamespaceArgs;
+use crate::cli::progress::TerminalProgress;
+use crate::cli::redistributable::{self, RedistributableArgs};
+use crate::cli::sounds::OrganizeSoundsArgs;
+use crate::cli::source;
+use crate::{ExtractCmd, Options};
+use clap::{Args, ValueEnum};
+use extract_minecraft_assets::backup::AssetsBackup;
+use extract_minecraft_assets::hashed::{self, IndexFile, IndexFileLocation};
+use extract_minecraft_assets::options::{self, ExtractOptions};
+use extract_minecraft_assets::report::ExtractionReport;
+use extract_minecraft_assets::sounds::SoundEvents;
+use extract_minecraft_assets::usage::UsageEstimate;
+use extract_minecraft_assets::util;
+use std::path::{Path, PathBuf};
+
+/// What to do with hashed files which don't match the index file.
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Verify {
+    /// Extract the file anyway, with a warning.
+    Warn,
+    /// Fail the file without writing it.
+    Strict,
+}
+
+impl From<Verify> for options::Verify {
+    fn from(verify: Verify) -> Self {
+        match verify {
+            Verify::Warn => Self::Warn,
+            Verify::Strict => Self::Strict,
+        }
+    }
+}
+
+#[derive(Args)]
+pub struct HashedSubcommand {
+    /// The path to the `.minecraft/assets/` directory to extract assets from.
+    ///
+    /// Can also be a backup of it in a zip or tar archive (optionally
+    /// gzipped), which is extracted from without restoring it. The `indexes`
+    /// and `objects` directories may be within a wrapper directory in the
+    /// archive. Defaults to the default `.minecraft/assets/` folder location
+    /// on your OS.
+    #[arg(value_name = "ASSETS DIRECTORY")]
+    hashed_assets_dir: Option<PathBuf>,
+    /// The index file to use.
+    ///
+    /// Can either be a file path to the index file itself, or the name of
+    /// that version (e.g. `24` instead of `.minecraft/assets/indexes/24.json`).
+    /// Anything containing a path separator or ending in `.json` is a path.
+    #[arg(short, long, value_name = "FILE or VERSION", value_parser = IndexFileLocation::parse)]
+    index: IndexFileLocation,
+
+    /// How to organize sound files.
+    #[command(flatten)]
+    sounds: OrganizeSoundsArgs,
+    /// Whether to leave out files which can't be redistributed.
+    #[command(flatten)]
+    redistributable: RedistributableArgs,
+    /// Which namespaces to extract.
+    #[command(flatten)]
+    namespaces: NamespaceArgs,
+}
+
+/// Where hashed assets are extracted from.
+enum HashedInput {
+    /// A `.minecraft/assets/` directory.
+    Dir(PathBuf),
+    /// A backup of one in an archive.
+    Backup(AssetsBackup),
+}
+
+impl HashedSubcommand {
+    /// Opens the hashed assets to extract from, along with the index file.
+    fn open(&self) -> Result<(HashedInput, IndexFile), Error> {
+        match &self.hashed_assets_dir {
+            Some(path) if path.is_file() => {
+                let backup = AssetsBackup::open(path)?;
+                let index = backup.index(&self.index)?;
+
+                Ok((HashedInput::Backup(backup), index))
+            }
+            given => {
+                let input_dir = source::hashed_assets_dir(given.clone())?;
+                let index = IndexFile::from_path(&self.index.resolve(&input_dir)?)?;
+
+                Ok((HashedInput::Dir(input_dir), index))
+            }
+        }
+    }
+
+    /// Estimates the files which extracting with `options` would write,
+    /// without extracting anything.
+    pub fn estimate(&self, options: &ExtractOptions) -> Result<UsageEstimate, Error> {
+        let (_, mut index) = self.open()?;
+        index = index.filtered(|path| self.namespaces.allows_index_path(path));
+
+        if let Some(redistributable) = self.redistributable.filter() {
+            index = index.filtered(|path| redistributable.allows(&Path::new("assets").join(path)));
+        }
+
+        let mut estimate = UsageEstimate::new();
+        estimate.add_index(&index, options);
+
+        Ok(estimate)
+    }
+}
+
+impl ExtractCmd for HashedSubcommand {
+    fn source_dirs(&self) -> Vec<PathBuf> {
+        self.hashed_assets_dir
+            .clone()
+            .or_else(util::hashed_assets_dir)
+            .into_iter()
+            .collect()
+    }
+
+    fn execute(self, options: &Options) -> Result<ExtractionReport, Error> {
+        let (input, mut index) = self.open()?;
+        let sound_events = match &input {
+            _ if !self.sounds.is_enabled() => None,
+            HashedInput::Dir(input_dir) => SoundEvents::from_index(input_dir, &index)?,
+            HashedInput::Backup(backup) => SoundEvents::from_backup(backup, &index)?,
+        };
+        let namespace_warnings = self.namespaces.unmatched_warnings(
+            Path::new("assets"),
+            [],
+            index.objects.keys().map(PathBuf::as_path),
+        );
+        index = index.filtered(|path| self.namespaces.allows_index_path(path));
+        index = options.extract.included_objects(&index).into_owned();
+
+        if let Some(redistributable) = self.redistributable.filter() {
+            let assets_path = |path: &Path| Path::new("assets").join(path);
+            let paths: Vec<_> = index.objects.keys().map(|path| assets_path(path)).collect();
+            redistributable::print_notice(&redistributable, paths.iter().map(PathBuf::as_path));
+
+            index = index.filtered(|path| redistributable.allows(&assets_path(path)));
+        }
+        options.confirm_size(index.total_size())?;
+
+        let mut progress = TerminalProgress::new(options.porcelain);
+        progress.add_phase("hashed assets", index.len());
+
+        let mut report = match &input {
+            HashedInput::Dir(input_dir) => {
+                hashed::extract_hashed_assets(input_dir, &index, &options.extract, &mut progress)?
+            }
+            HashedInput::Backup(backup) => {
+                backup.extract_hashed_assets(&index, &options.extract, &mut progress)?
+            }
+        };
+        report.warnings.extend(namespace_warnings);
+        self.sounds
+            .organize(sound_events, &report, &options.extract)?;
+
+        Ok(report)
+    }
+
+    #[cfg(feature = "git")]
+    fn git_source(&self) -> git::Source {
+        let index = match &self.index {
+            IndexFileLocation::File(path) => path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned()),
+            IndexFileLocation::Version(version) => Some(version.clone()),
+        };
+
+        git::Source {
+            version: None,
+            index,
+        }
+    }
+}
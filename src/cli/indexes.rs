@@ -0,0 +1,81 @@
+use crate::cli::error::Error;
+use crate::cli::json::Document;
+use clap::Args;
+use extract_minecraft_assets::lookup::{self, InstalledIndex};
+use extract_minecraft_assets::util;
+use serde::Serialize;
+use std::path::PathBuf;
+
+#[derive(Args)]
+pub struct IndexesSubcommand {
+    /// The path to the `.minecraft/assets/` directory to find index files.
+    ///
+    /// Versions are found in the `versions` directory next to it. Defaults to
+    /// the default location on your OS.
+    #[arg(long = "hashed-assets", value_name = "DIRECTORY")]
+    hashed_assets_dir: Option<PathBuf>,
+    /// Only list the index files which no installed version uses.
+    #[arg(long)]
+    unused: bool,
+}
+
+/// The document printed by `indexes --json`.
+#[derive(Serialize)]
+struct IndexesDocument {
+    indexes: Vec<InstalledIndex>,
+}
+
+impl IndexesSubcommand {
+    /// Prints every index file with its size, the number and total size of
+    /// the files it lists, and the installed versions using it.
+    pub fn execute(self, json: bool) -> Result<(), Error> {
+        let hashed_assets_dir = self
+            .hashed_assets_dir
+            .or_else(util::hashed_assets_dir)
+            .unwrap_or_default();
+        let versions_dir = hashed_assets_dir.with_file_name("versions");
+
+        let mut indexes = lookup::list_indexes(&hashed_assets_dir, &versions_dir);
+        if self.unused {
+            indexes.retain(|index| index.versions.is_empty());
+        }
+
+        if json {
+            Document::new(IndexesDocument { indexes }).print()?;
+
+            return Ok(());
+        }
+
+        for index in &indexes {
+            let versions = if index.versions.is_empty() {
+                "(no installed versions)".to_owned()
+            } else {
+                index.versions.join(", ")
+            };
+
+            match (index.objects, index.total_size) {
+                (Some(objects), Some(total_size)) => println!(
+                    "{:<12}  {:>10}  {:>7} {:<5}  {:>10}  {versions}",
+                    index.id,
+                    util::format_size(index.size),
+                    objects,
+                    if objects == 1 { "file" } else { "files" },
+                    util::format_size(total_size)
+                ),
+                _ => {
+                    println!(
+                        "{:<12}  {:>10}  {:<25}  {versions}",
+                        index.id,
+                        util::format_size(index.size),
+                        "(unreadable)"
+                    );
+                    if let Some(error) = &index.error {
+                        eprintln!("Couldn't read index {}: {error}", index.id);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,404 @@
+use crate::cli::closure::{self, ClosureArgs};
+use crate::cli::error::Error;
+#[cfg(feature = "git")]
+use crate::cli::git;
+use crate::cli::namespace::{self, NamespaceArgs};
+#[cfg(feature = "remote")]
+use crate::cli::progress;
+use crate::cli::progress::TerminalProgress;
+use crate::cli::redistributable::{self, RedistributableArgs};
+use crate::{ExtractCmd, Options};
+use clap::{Args, ValueEnum};
+use extract_minecraft_assets::closure::AssetSources;
+use extract_minecraft_assets::error::ExtractError;
+use extract_minecraft_assets::jar::{self, ContentsArgs, ExtractedContents};
+use extract_minecraft_assets::options::{self, ExtractOptions};
+#[cfg(feature = "remote")]
+use extract_minecraft_assets::remote::RangeReader;
+use extract_minecraft_assets::report::ExtractionReport;
+use extract_minecraft_assets::usage::UsageEstimate;
+use extract_minecraft_assets::util;
+use std::path::{Path, PathBuf};
+
+/// Which of several entries with the same name in a jar file to extract.
+#[derive(Clone, Copy, ValueEnum)]
+pub enum DuplicateEntries {
+    /// Extract the last entry with the name, as most zip tools do.
+    Last,
+    /// Extract the first entry with the name.
+    First,
+    /// Fail before extracting anything.
+    Error,
+}
+
+impl From<DuplicateEntries> for options::DuplicateEntries {
+    fn from(duplicate_entries: DuplicateEntries) -> Self {
+        match duplicate_entries {
+            DuplicateEntries::Last => Self::Last,
+            DuplicateEntries::First => Self::First,
+            DuplicateEntries::Error => Self::Error,
+        }
+    }
+}
+
+/// How to sort files into directories by type.
+#[derive(Clone, Copy, ValueEnum)]
+pub enum GroupBy {
+    /// Group files by their extensions, e.g. `png`.
+    #[value(alias = "extension")]
+    Ext,
+    /// Group files by their categories, e.g. `textures` or `lang`.
+    Category,
+}
+
+impl From<GroupBy> for options::GroupBy {
+    fn from(group_by: GroupBy) -> Self {
+        match group_by {
+            GroupBy::Ext => Self::Extension,
+            GroupBy::Category => Self::Category,
+        }
+    }
+}
+
+#[derive(Args)]
+pub struct JarSubcommand {
+    /// The jar or zip file to extract from.
+    ///
+    /// Minecraft version jar files can be found in `.minecraft/versions/`.
+    /// With the `remote` feature, this can also be an `http://` or `https://`
+    /// URL, from which only the parts of the file which are extracted are
+    /// fetched.
+    #[arg(value_name = "FILE")]
+    jar_file: PathBuf,
+    /// Only extract the file at this path within the jar file, or the files
+    /// within the directory at it, e.g. `assets/minecraft/lang/en_us.json`.
+    ///
+    /// Can be given more than once. Fails if nothing is found at a path.
+    #[arg(long = "entry", value_name = "PATH")]
+    entries: Vec<PathBuf>,
+    /// Which contents to extract.
+    #[command(flatten)]
+    contents: ContentsArgs,
+    /// Which references to follow within the jar file.
+    #[command(flatten)]
+    closure: ClosureArgs,
+    /// Whether to leave out files which can't be redistributed.
+    #[command(flatten)]
+    redistributable: RedistributableArgs,
+    /// Which namespaces to extract.
+    #[command(flatten)]
+    namespaces: NamespaceArgs,
+    /// Extract whatever is found even if the jar file contains none of the
+    /// `assets` or `data` requested, rather than failing.
+    ///
+    /// Either way, a warning lists the top-level directories the jar file
+    /// does contain.
+    #[arg(long)]
+    allow_empty: bool,
+}
+
+impl JarSubcommand {
+    /// Estimates the files which extracting with `options` would write,
+    /// without extracting anything.
+    pub fn estimate(&self, options: &ExtractOptions) -> Result<UsageEstimate, Error> {
+        let extracted_contents = ExtractedContents::from(self.contents.clone());
+
+        if let Some(url) = remote_url(&self.jar_file) {
+            return Err(Error::RemoteJar {
+                url: url.to_owned(),
+                reason: "only local jar files can be estimated".to_owned(),
+            });
+        }
+
+        let closure = if self.closure.is_enabled() {
+            let mut sources = AssetSources::new().jar(&self.jar_file)?;
+            self.closure.resolve(&mut sources)?
+        } else {
+            None
+        };
+        let closure_files = closure.as_ref().map(closure::jar_files);
+        let redistributable = self.redistributable.filter();
+
+        let mut estimate = UsageEstimate::new();
+        estimate.add_jar(
+            &self.jar_file,
+            &options.clone().contents(extracted_contents),
+            |path| {
+                closure_files
+                    .as_ref()
+                    .is_none_or(|files| files.contains(path))
+                    && is_entry(&self.entries, path)
+                    && self.namespaces.allows_jar_path(path)
+                    && redistributable
+                        .as_ref()
+                        .is_none_or(|redistributable| redistributable.allows(path))
+            },
+        )?;
+
+        Ok(estimate)
+    }
+}
+
+impl ExtractCmd for JarSubcommand {
+    fn source_dirs(&self) -> Vec<PathBuf> {
+        // The jar may be anywhere: only the `.minecraft` directory itself is
+        // off-limits.
+        Vec::new()
+    }
+
+    fn execute(self, options: &Options) -> Result<ExtractionReport, Error> {
+        let extracted_contents = ExtractedContents::from(self.contents);
+        let mut source = JarSource::open(&self.jar_file)?;
+
+        // With a closure, only its files are extracted.
+        let closure = if self.closure.is_enabled() {
+            if let Some(url) = remote_url(&self.jar_file) {
+                return Err(Error::RemoteJar {
+                    url: url.to_owned(),
+                    reason: "references can only be followed within local jar files".to_owned(),
+                });
+            }
+            let mut sources = AssetSources::new().jar(&self.jar_file)?;
+            self.closure.resolve(&mut sources)?
+        } else {
+            None
+        };
+
+        let closure_files = closure.as_ref().map(closure::jar_files);
+        let redistributable = self.redistributable.filter();
+        let selected = |path: &Path| {
+            closure_files
+                .as_ref()
+                .is_none_or(|files| files.contains(path))
+                && is_entry(&self.entries, path)
+                && self.namespaces.allows_jar_path(path)
+                && options.extract.includes(path)
+        };
+        let filter = |path: &Path| {
+            selected(path)
+                && redistributable
+                    .as_ref()
+                    .is_none_or(|redistributable| redistributable.allows(path))
+        };
+
+        let files = source.list_files(&self.jar_file, &extracted_contents)?;
+        check_contents(
+            &self.jar_file,
+            &extracted_contents,
+            &files,
+            self.allow_empty,
+            || source.top_level_dirs(&self.jar_file),
+        )?;
+        check_entries(&self.jar_file, &self.entries, &files)?;
+
+        let size = files
+            .iter()
+            .filter(|(path, _)| filter(path))
+            .map(|(_, size)| size)
+            .sum();
+
+        if let Some(redistributable) = &redistributable {
+            let paths = files.iter().map(|(path, _)| path.as_path());
+            redistributable::print_notice(redistributable, paths.filter(|path| selected(path)));
+        }
+        options.confirm_size(size)?;
+        let namespace_warnings = self.namespaces.unmatched_warnings(
+            namespace::warnings_dir(&extracted_contents),
+            files.iter().map(|(path, _)| path.as_path()),
+            [],
+        );
+
+        let mut progress = TerminalProgress::new(options.porcelain);
+        progress.add_phase(extracted_contents.to_string(), 0);
+
+        let options = options.extract.clone().contents(extracted_contents);
+        let mut report = source.extract(&self.jar_file, &options, &mut progress, filter)?;
+        report.warnings.extend(namespace_warnings);
+        source.print_stats();
+
+        if let Some(closure) = &closure {
+            progress.clear()?;
+            closure::print_problems(closure);
+        }
+
+        Ok(report)
+    }
+
+    #[cfg(feature = "git")]
+    fn git_source(&self) -> git::Source {
+        // Version jar files are named after their version.
+        git::Source {
+            version: self
+                .jar_file
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned()),
+            index: None,
+        }
+    }
+}
+
+/// Where a jar file is read from.
+enum JarSource {
+    File,
+    /// A remote jar file, read with HTTP range requests.
+    #[cfg(feature = "remote")]
+    Remote(RangeReader),
+}
+
+/// Returns `jar_file` as a URL, if it is one.
+fn remote_url(jar_file: &Path) -> Option<&str> {
+    jar_file.to_str().filter(|jar_file| util::is_url(jar_file))
+}
+
+impl JarSource {
+    /// Opens `jar_file`, fetching the end of it if it is a URL.
+    fn open(jar_file: &Path) -> Result<Self, Error> {
+        let Some(url) = remote_url(jar_file) else {
+            return Ok(Self::File);
+        };
+
+        #[cfg(feature = "remote")]
+        {
+            Ok(Self::Remote(RangeReader::open(url)?))
+        }
+        #[cfg(not(feature = "remote"))]
+        Err(Error::RemoteJar {
+            url: url.to_owned(),
+            reason: "reading jar files from URLs needs the `remote` feature".to_owned(),
+        })
+    }
+
+    fn list_files(
+        &mut self,
+        jar_file: &Path,
+        extracted_contents: &ExtractedContents,
+    ) -> Result<Vec<(PathBuf, u64)>, ExtractError> {
+        match self {
+            Self::File => jar::list_files(jar_file, extracted_contents),
+            #[cfg(feature = "remote")]
+            Self::Remote(reader) => jar::list_files_from(jar_file, reader, extracted_contents),
+        }
+    }
+
+    fn top_level_dirs(&mut self, jar_file: &Path) -> Result<Vec<String>, ExtractError> {
+        match self {
+            Self::File => jar::top_level_dirs(jar_file),
+            #[cfg(feature = "remote")]
+            Self::Remote(reader) => jar::top_level_dirs_from(jar_file, reader),
+        }
+    }
+
+    fn extract(
+        &mut self,
+        jar_file: &Path,
+        options: &ExtractOptions,
+        progress: &mut TerminalProgress,
+        filter: impl Fn(&Path) -> bool,
+    ) -> Result<ExtractionReport, ExtractError> {
+        match self {
+            Self::File => jar::extract_jar_filtered(jar_file, options, progress, filter),
+            #[cfg(feature = "remote")]
+            Self::Remote(reader) => {
+                jar::extract_jar_from(jar_file, reader, options, progress, filter)
+            }
+        }
+    }
+
+    /// Prints how much was fetched of a remote jar file.
+    fn print_stats(&self) {
+        #[cfg(feature = "remote")]
+        if let Self::Remote(reader) = self {
+            let stats = reader.stats();
+            progress::message(format_args!(
+                "Fetched {} of {} in {} requests",
+                util::format_size(stats.bytes),
+                util::format_size(reader.len()),
+                stats.requests
+            ));
+            if !stats.ranges {
+                progress::message(
+                    "The server doesn't support range requests, so the whole jar file was \
+                     downloaded",
+                );
+            }
+        }
+    }
+}
+
+/// Whether `path` is one of the `entries`, or within one, or if no entries
+/// were given.
+fn is_entry(entries: &[PathBuf], path: &Path) -> bool {
+    entries.is_empty() || entries.iter().any(|entry| path.starts_with(entry))
+}
+
+/// Fails if any of the `entries` matches none of the `files` listed in
+/// `jar_file`.
+fn check_entries(
+    jar_file: &Path,
+    entries: &[PathBuf],
+    files: &[(PathBuf, u64)],
+) -> Result<(), Error> {
+    let missing: Vec<_> = entries
+        .iter()
+        .filter(|entry| !files.iter().any(|(path, _)| path.starts_with(entry)))
+        .cloned()
+        .collect();
+
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::MissingEntries {
+            jar_file: jar_file.to_owned(),
+            entries: missing,
+        })
+    }
+}
+
+/// Warns about each root of the `extracted_contents` which none of the
+/// `files` listed in `jar_file` are part of, listing the top-level directories
+/// the jar file contains, as given by `top_level_dirs`, instead.
+///
+/// Fails if any root is missing, unless `allow_empty` is set.
+fn check_contents(
+    jar_file: &Path,
+    extracted_contents: &ExtractedContents,
+    files: &[(PathBuf, u64)],
+    allow_empty: bool,
+    top_level_dirs: impl FnOnce() -> Result<Vec<String>, ExtractError>,
+) -> Result<(), Error> {
+    let missing: Vec<_> = extracted_contents
+        .roots()
+        .iter()
+        .filter(|root| {
+            !files
+                .iter()
+                .any(|(path, _)| extracted_contents.root_of(path) == Some(*root))
+        })
+        .map(|root| root.name.clone())
+        .collect();
+    if missing.is_empty() {
+        return Ok(());
+    }
+
+    let dirs = top_level_dirs()?;
+    let found = if dirs.is_empty() {
+        "no directories".to_owned()
+    } else {
+        dirs.join(", ")
+    };
+    for root in &missing {
+        eprintln!(
+            "Warning: '{}' has no {root} files; its top-level directories are: {found}",
+            jar_file.display()
+        );
+    }
+
+    if allow_empty {
+        Ok(())
+    } else {
+        Err(Error::MissingContents {
+            jar_file: jar_file.to_owned(),
+            roots: missing,
+        })
+    }
+}
@@ -0,0 +1,80 @@
+//! The JSON documents written by `--json` and `--report`, and the format of
+//! extracted JSON files.
+
+use clap::ValueEnum;
+use extract_minecraft_assets::error::{ExtractError, IoContext};
+use extract_minecraft_assets::format::JsonFormat;
+use serde::Serialize;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+/// The version of the JSON documents' schema.
+///
+/// This is incremented whenever a field is removed or changes meaning, but not
+/// when a field is added.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// The format in which to write JSON files while extracting.
+#[derive(Clone, Copy, ValueEnum)]
+pub enum FormatJson {
+    /// Each value on its own line, indented by two spaces.
+    Pretty,
+    /// Without any whitespace between values.
+    Minify,
+}
+
+impl From<FormatJson> for JsonFormat {
+    fn from(format: FormatJson) -> Self {
+        match format {
+            FormatJson::Pretty => Self::Pretty,
+            FormatJson::Minify => Self::Minify,
+        }
+    }
+}
+
+/// A JSON document: `content` with a top-level `formatVersion` field.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Document<T> {
+    format_version: u32,
+    #[serde(flatten)]
+    content: T,
+}
+
+/// The document written by `--json` when a subcommand fails.
+#[derive(Serialize)]
+pub struct Failure {
+    /// A description of the error.
+    pub error: String,
+}
+
+impl<T: Serialize> Document<T> {
+    /// Returns a document of the current [format version](FORMAT_VERSION).
+    pub fn new(content: T) -> Self {
+        Self {
+            format_version: FORMAT_VERSION,
+            content,
+        }
+    }
+
+    /// Prints the document to stdout.
+    pub fn print(&self) -> io::Result<()> {
+        let mut stdout = io::stdout().lock();
+        serde_json::to_writer_pretty(&mut stdout, self)?;
+
+        writeln!(stdout)
+    }
+
+    /// Writes the document to the file at `path`.
+    pub fn write(&self, path: &Path) -> Result<(), ExtractError> {
+        let write = || {
+            let mut file = BufWriter::new(File::create(path)?);
+            serde_json::to_writer_pretty(&mut file, self)?;
+
+            file.flush()
+        };
+
+        write().writing(path)
+    }
+}
@@ -0,0 +1,173 @@
+use crate::cli::error::Error;
+use crate::cli::json::Document;
+use crate::cli::source;
+use clap::{Args, ValueEnum};
+use extract_minecraft_assets::error::ExtractError;
+use extract_minecraft_assets::lang::{Conflict, LangError, LangFormat, LangMerger};
+use extract_minecraft_assets::util;
+use extract_minecraft_assets::version::Version;
+use serde::Serialize;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+#[derive(Args)]
+pub struct LangSubcommand {
+    /// The directory containing the version `.jar` file and manifest.
+    ///
+    /// Can be a path to the directory, or the name of the version to be found
+    /// within `.minecraft/versions/`.
+    #[arg(value_name = "DIRECTORY or VERSION", value_parser = source::parse_version)]
+    version_dir: Version,
+    /// The path to the `.minecraft/assets/` directory to find hashed assets.
+    ///
+    /// Defaults to the default location on your OS.
+    #[arg(long = "hashed-assets", value_name = "DIRECTORY")]
+    hashed_assets_dir: Option<PathBuf>,
+    /// Only merge this locale, e.g. `de_de`.
+    ///
+    /// May be given more than once. Every locale is merged by default.
+    #[arg(long = "locale", value_name = "LOCALE")]
+    locales: Vec<String>,
+}
+
+/// The format to which to convert language files while extracting.
+#[derive(Clone, Copy, ValueEnum)]
+pub enum ConvertLang {
+    /// Convert `.lang` files to `.json` files.
+    Json,
+    /// Convert `.json` files to `.lang` files.
+    Lang,
+}
+
+impl From<ConvertLang> for LangFormat {
+    fn from(format: ConvertLang) -> Self {
+        match format {
+            ConvertLang::Json => Self::Json,
+            ConvertLang::Lang => Self::Lang,
+        }
+    }
+}
+
+/// The document printed by `lang --json`.
+#[derive(Serialize)]
+struct LangDocument {
+    locales: Vec<LocaleDocument>,
+    /// The language files left out because they couldn't be read or parsed.
+    errors: Vec<LangError>,
+}
+
+/// A merged locale in a [`LangDocument`].
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct LocaleDocument {
+    locale: String,
+    /// The path of the merged file, relative to the output directory.
+    path: PathBuf,
+    /// The number of keys in the merged file.
+    keys: usize,
+    conflicts: Vec<Conflict>,
+}
+
+impl LangSubcommand {
+    /// Writes the merged language file of each locale to `merged/<locale>.json`
+    /// within `output_dir`, exiting with 2 if any language file was left out.
+    pub fn execute(self, output_dir: &Path, json: bool) -> Result<ExitCode, Error> {
+        let hashed_assets_dir = self
+            .hashed_assets_dir
+            .or_else(util::hashed_assets_dir)
+            .unwrap_or_default();
+
+        let mut merger = LangMerger::new();
+        if !self.locales.is_empty() {
+            merger = merger.locales(self.locales);
+        }
+        merger.add_jar(&self.version_dir.jar_file())?;
+        merger.add_index(
+            &hashed_assets_dir,
+            &source::version_index(&self.version_dir, &hashed_assets_dir)?,
+        );
+
+        let merged_dir = output_dir.join("merged");
+        fs::create_dir_all(&merged_dir).map_err(|source| ExtractError::WriteFile {
+            path: merged_dir.clone(),
+            source,
+        })?;
+
+        let mut locales = Vec::new();
+        for merged in merger.merge() {
+            let path = Path::new("merged").join(format!("{}.json", merged.locale));
+            write_json(&output_dir.join(&path), &merged.entries)?;
+
+            locales.push(LocaleDocument {
+                locale: merged.locale,
+                path,
+                keys: merged.entries.len(),
+                conflicts: merged.conflicts,
+            });
+        }
+
+        let errors = merger.errors().to_vec();
+        let exit_code = if errors.is_empty() {
+            ExitCode::SUCCESS
+        } else {
+            ExitCode::from(2)
+        };
+
+        for error in &errors {
+            eprintln!(
+                "Skipped {} language file '{}': {}",
+                error.source.name(),
+                error.path.display(),
+                error.message
+            );
+        }
+
+        if json {
+            Document::new(LangDocument { locales, errors }).print()?;
+
+            return Ok(exit_code);
+        }
+
+        for locale in &locales {
+            for conflict in &locale.conflicts {
+                let values: Vec<_> = conflict
+                    .values
+                    .iter()
+                    .map(|value| format!("{} {:?}", value.namespace, value.value))
+                    .collect();
+                eprintln!(
+                    "Conflict in {} for '{}': {}",
+                    locale.locale,
+                    conflict.key,
+                    values.join(", ")
+                );
+            }
+            println!(
+                "{}: {} keys, {} conflicts",
+                locale.path.display(),
+                locale.keys,
+                locale.conflicts.len()
+            );
+        }
+
+        Ok(exit_code)
+    }
+}
+
+/// Writes `value` to the file at `path` as pretty-printed JSON.
+fn write_json(path: &Path, value: &impl Serialize) -> Result<(), ExtractError> {
+    let write = || {
+        let mut file = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(&mut file, value)?;
+        writeln!(file)?;
+
+        file.flush()
+    };
+
+    write().map_err(|source| ExtractError::WriteFile {
+        path: path.to_owned(),
+        source,
+    })
+}
@@ -0,0 +1,81 @@
+use crate::cli::error::Error;
+use crate::cli::json::Document;
+use crate::cli::source::SourceArgs;
+use clap::Args;
+use clap::builder::{PossibleValuesParser, TypedValueParser};
+use extract_minecraft_assets::category::Category;
+use extract_minecraft_assets::largest::{LargeFile, LargestFiles};
+use extract_minecraft_assets::util;
+use serde::Serialize;
+
+#[derive(Args)]
+pub struct LargestSubcommand {
+    #[command(flatten)]
+    source: SourceArgs,
+    /// The number of files to list.
+    #[arg(short = 'n', long, value_name = "N", default_value_t = 20)]
+    count: usize,
+    /// Only list files of this category.
+    ///
+    /// May be given more than once.
+    #[arg(long = "type", value_name = "CATEGORY", value_parser = category_parser())]
+    categories: Vec<Category>,
+    /// Only list files within this namespace, e.g. `minecraft` or `realms`.
+    ///
+    /// May be given more than once.
+    #[arg(long = "namespace", value_name = "NAMESPACE")]
+    namespaces: Vec<String>,
+}
+
+/// The document printed by `largest --json`.
+#[derive(Serialize)]
+struct LargestDocument {
+    files: Vec<LargeFile>,
+}
+
+/// Parses the name of a [`Category`], listing every name in `--help`.
+fn category_parser() -> impl TypedValueParser<Value = Category> {
+    PossibleValuesParser::new(Category::ALL.map(Category::name))
+        .map(|name| Category::from_name(&name).expect("only category names are possible"))
+}
+
+impl LargestSubcommand {
+    /// Prints the largest files of the source, largest first.
+    pub fn execute(self, json: bool) -> Result<(), Error> {
+        let mut files = LargestFiles::new();
+        if let Some(jar_file) = self.source.jar_file() {
+            files.add_jar(&jar_file, &self.source.contents())?;
+        }
+        if let Some(index) = self.source.index()? {
+            files.add_index(&index);
+        }
+
+        files.retain(|file| {
+            (self.categories.is_empty() || self.categories.contains(&file.category))
+                && (self.namespaces.is_empty()
+                    || file
+                        .namespace
+                        .as_ref()
+                        .is_some_and(|namespace| self.namespaces.contains(namespace)))
+        });
+        let files = files.largest(self.count);
+
+        if json {
+            Document::new(LargestDocument { files }).print()?;
+
+            return Ok(());
+        }
+
+        for file in &files {
+            println!(
+                "{:>10}  {:<6}  {:<12}  {}",
+                util::format_size(file.size),
+                file.source.name(),
+                file.category.name(),
+                file.path.display()
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,157 @@
+use crate::cli::error::Error;
+use crate::cli::json::Document;
+use crate::cli::source;
+use clap::Args;
+use extract_minecraft_assets::libraries::{self, LibraryStatus, Platform};
+use extract_minecraft_assets::util;
+use extract_minecraft_assets::version::Version;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+#[derive(Args)]
+pub struct LibrariesSubcommand {
+    /// The directory containing the version `.jar` file and manifest.
+    ///
+    /// Can be a path to the directory, or the name of the version to be found
+    /// within `.minecraft/versions/`.
+    #[arg(value_name = "DIRECTORY or VERSION", value_parser = source::parse_version)]
+    version_dir: Version,
+    /// The path to the `.minecraft/libraries/` directory to copy the
+    /// libraries from.
+    ///
+    /// Defaults to the `libraries` directory of the `.minecraft` directory
+    /// the version is in.
+    #[arg(long = "libraries", value_name = "DIRECTORY")]
+    libraries_dir: Option<PathBuf>,
+    /// Download the libraries which are missing from the libraries
+    /// directory, or whose SHA-1 hashes don't match, into the output
+    /// directory.
+    ///
+    /// The libraries directory itself is left as it is.
+    #[cfg(feature = "remote")]
+    #[arg(long)]
+    download_missing: bool,
+}
+
+/// The document printed by `libraries --json`.
+#[derive(Serialize)]
+struct LibrariesDocument {
+    /// Every library needed on this platform, sorted by path.
+    libraries: Vec<LibraryDocument>,
+}
+
+/// A library in a [`LibrariesDocument`].
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct LibraryDocument {
+    /// The path of the library within the libraries directory, and within
+    /// the output directory if it was written.
+    path: PathBuf,
+    size: u64,
+    url: String,
+    #[serde(flatten)]
+    status: LibraryStatus,
+}
+
+impl LibrariesSubcommand {
+    /// Copies the libraries the version needs on this platform into
+    /// `output_dir`, exiting with 2 if any is missing or doesn't match.
+    pub fn execute(self, output_dir: &Path, json: bool) -> Result<ExitCode, Error> {
+        let libraries_dir = self.libraries_dir.unwrap_or_else(|| {
+            self.version_dir
+                .path()
+                .ancestors()
+                .nth(2)
+                .map(|minecraft_dir| minecraft_dir.join("libraries"))
+                .unwrap_or_else(|| PathBuf::from("libraries"))
+        });
+
+        // A modded version needs the libraries of the version it inherits
+        // from too.
+        let mut manifests = vec![self.version_dir.manifest()?];
+        while let Some(parent) = manifests
+            .last()
+            .and_then(|manifest| manifest.inherits_from.clone())
+        {
+            if manifests.iter().any(|manifest| manifest.id == parent) {
+                break;
+            }
+            let parent = Version::from_dir(self.version_dir.path().with_file_name(parent));
+            manifests.push(parent.manifest()?);
+        }
+
+        let platform = Platform::current();
+        let mut artifacts: Vec<_> = manifests
+            .iter()
+            .flat_map(|manifest| manifest.library_artifacts(&platform))
+            .collect();
+        artifacts.sort_by(|a, b| a.path.cmp(&b.path));
+        artifacts.dedup_by(|a, b| a.path == b.path);
+
+        let mut documents = Vec::new();
+        for artifact in artifacts {
+            let status = libraries::copy_library(&libraries_dir, artifact, output_dir)?;
+
+            #[cfg(feature = "remote")]
+            let status = if self.download_missing && !status.is_written() {
+                libraries::download_library(artifact, output_dir)?
+            } else {
+                status
+            };
+
+            documents.push(LibraryDocument {
+                path: artifact.path.clone(),
+                size: artifact.size,
+                url: artifact.url.clone(),
+                status,
+            });
+        }
+
+        let exit_code = if documents.iter().all(|library| library.status.is_written()) {
+            ExitCode::SUCCESS
+        } else {
+            ExitCode::from(2)
+        };
+
+        if json {
+            Document::new(LibrariesDocument {
+                libraries: documents,
+            })
+            .print()?;
+
+            return Ok(exit_code);
+        }
+
+        for library in &documents {
+            match &library.status {
+                LibraryStatus::Copied | LibraryStatus::Downloaded => {}
+                LibraryStatus::Missing => {
+                    println!("missing\t{}\t{}", library.path.display(), library.url)
+                }
+                LibraryStatus::Mismatched { sha1 } => {
+                    eprintln!(
+                        "Library '{}' has SHA-1 hash {sha1}, not the one in the manifest",
+                        library.path.display()
+                    );
+                    println!("mismatched\t{}\t{}", library.path.display(), library.url);
+                }
+            }
+        }
+
+        let written: Vec<_> = documents
+            .iter()
+            .filter(|library| library.status.is_written())
+            .collect();
+        eprintln!(
+            "Copied {} of {} libraries ({}) from '{}' into '{}'",
+            written.len(),
+            documents.len(),
+            util::format_size(written.iter().map(|library| library.size).sum()),
+            libraries_dir.display(),
+            output_dir.display()
+        );
+
+        Ok(exit_code)
+    }
+}
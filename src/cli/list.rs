@@ -0,0 +1,105 @@
+use crate::cli::error::Error;
+use crate::cli::json::Document;
+use crate::cli::source::SourceArgs;
+use clap::Args;
+use extract_minecraft_assets::jar;
+use extract_minecraft_assets::search::FileSource;
+use extract_minecraft_assets::util;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+#[derive(Args)]
+pub struct ListSubcommand {
+    #[command(flatten)]
+    source: SourceArgs,
+    /// Only print the path of each file, one per line, e.g. to pipe into
+    /// `grep`.
+    #[arg(long, conflicts_with = "json")]
+    paths_only: bool,
+}
+
+/// A file listed by `list`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ListedFile {
+    path: PathBuf,
+    /// The size of the file in bytes.
+    size: u64,
+    source: FileSource,
+    /// The SHA-1 hash of the file, for files listed in an index file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hash: Option<String>,
+}
+
+/// The document printed by `list --json`.
+#[derive(Serialize)]
+struct ListDocument {
+    files: Vec<ListedFile>,
+}
+
+impl ListSubcommand {
+    /// Prints every file of the source, sorted by path.
+    ///
+    /// With `porcelain`, each file is printed as its source, size in bytes,
+    /// hash (or `-`) and path, separated by tabs.
+    pub fn execute(self, json: bool, porcelain: bool) -> Result<(), Error> {
+        // Hashed assets replace the files of the jar file at the same path,
+        // as they do when a version is extracted.
+        let mut files = BTreeMap::new();
+        if let Some(jar_file) = self.source.jar_file() {
+            for (path, size) in jar::list_files(&jar_file, &self.source.contents())? {
+                let file = ListedFile {
+                    path: path.clone(),
+                    size,
+                    source: FileSource::Jar,
+                    hash: None,
+                };
+                files.insert(path, file);
+            }
+        }
+        if let Some(index) = self.source.index()? {
+            for (path, object) in index.objects {
+                let path = Path::new("assets").join(path);
+                let file = ListedFile {
+                    path: path.clone(),
+                    size: object.size,
+                    source: FileSource::Hashed,
+                    hash: Some(object.hash),
+                };
+                files.insert(path, file);
+            }
+        }
+        let files: Vec<_> = files.into_values().collect();
+
+        if json {
+            Document::new(ListDocument { files }).print()?;
+
+            return Ok(());
+        }
+
+        for file in &files {
+            if self.paths_only {
+                println!("{}", file.path.display());
+            } else if porcelain {
+                println!(
+                    "{}\t{}\t{}\t{}",
+                    file.source.name(),
+                    file.size,
+                    file.hash.as_deref().unwrap_or("-"),
+                    file.path.display()
+                );
+            } else {
+                println!(
+                    "{:<6} {:>10}  {:<40}  {}",
+                    file.source.name(),
+                    util::format_size(file.size),
+                    file.hash.as_deref().unwrap_or("-"),
+                    file.path.display()
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
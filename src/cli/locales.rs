@@ -0,0 +1,95 @@
+use crate::cli::error::Error;
+use crate::cli::json::Document;
+use crate::cli::source;
+use clap::Args;
+use extract_minecraft_assets::lang::{Locale, LocaleList};
+use extract_minecraft_assets::util;
+use extract_minecraft_assets::version::Version;
+use serde::Serialize;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+#[derive(Args)]
+pub struct LocalesSubcommand {
+    /// The directory containing the version `.jar` file and manifest.
+    ///
+    /// Can be a path to the directory, or the name of the version to be found
+    /// within `.minecraft/versions/`.
+    #[arg(value_name = "DIRECTORY or VERSION", value_parser = source::parse_version)]
+    version_dir: Version,
+    /// The path to the `.minecraft/assets/` directory to find hashed assets.
+    ///
+    /// Defaults to the default location on your OS.
+    #[arg(long = "hashed-assets", value_name = "DIRECTORY")]
+    hashed_assets_dir: Option<PathBuf>,
+    /// Exit with 1 if this locale isn't available, e.g. `de_de`.
+    ///
+    /// Codes are matched regardless of case. May be given more than once.
+    #[arg(long, value_name = "CODE")]
+    missing: Vec<String>,
+}
+
+/// The document printed by `locales --json`.
+#[derive(Serialize)]
+struct LocalesDocument {
+    locales: Vec<Locale>,
+    /// The locales given with --missing which aren't available.
+    missing: Vec<String>,
+}
+
+impl LocalesSubcommand {
+    /// Prints the locales available in the version, exiting with 1 if any
+    /// locale given with --missing isn't available.
+    pub fn execute(self, json: bool) -> Result<ExitCode, Error> {
+        let hashed_assets_dir = self
+            .hashed_assets_dir
+            .or_else(util::hashed_assets_dir)
+            .unwrap_or_default();
+
+        let mut list = LocaleList::new();
+        list.add_jar(&self.version_dir.jar_file())?;
+        list.add_index(
+            &hashed_assets_dir,
+            &source::version_index(&self.version_dir, &hashed_assets_dir)?,
+        );
+
+        let locales = list.locales();
+        let missing: Vec<_> = self
+            .missing
+            .into_iter()
+            .filter(|code| !list.contains(code))
+            .collect();
+        let exit_code = if missing.is_empty() {
+            ExitCode::SUCCESS
+        } else {
+            ExitCode::FAILURE
+        };
+
+        for code in &missing {
+            eprintln!("Locale '{code}' isn't available");
+        }
+
+        if json {
+            Document::new(LocalesDocument { locales, missing }).print()?;
+
+            return Ok(exit_code);
+        }
+
+        for locale in &locales {
+            let name = match (&locale.name, &locale.region) {
+                (Some(name), Some(region)) => format!("{name} ({region})"),
+                (Some(name), None) => name.clone(),
+                (None, _) => "-".to_owned(),
+            };
+            println!(
+                "{:<8}  {:<32}  {:>10}  {}",
+                locale.code,
+                name,
+                util::format_size(locale.size),
+                locale.source.name()
+            );
+        }
+
+        Ok(exit_code)
+    }
+}
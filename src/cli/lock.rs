@@ -0,0 +1,96 @@
+//! Locking the output directory, so that concurrent extractions into it fail
+//! fast rather than corrupting each other's files.
+
+use crate::cli::error::Error;
+use extract_minecraft_assets::util;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// The name of the lock file within the output directory.
+pub const LOCK_FILE: &str = ".extract-lock";
+
+/// The path of the lock file held by this process, so that it can be
+/// released when exiting from the Ctrl-C handler.
+static HELD: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// A lock on an output directory, released when dropped.
+///
+/// The lock is a file containing the ID of the process holding it, which is
+/// created atomically. A lock left behind by a process which is no longer
+/// running is reclaimed.
+#[derive(Debug)]
+pub struct OutputLock {
+    path: PathBuf,
+}
+
+impl OutputLock {
+    /// Locks `output_dir`, failing if another process holds its lock.
+    pub fn acquire(output_dir: &Path) -> Result<Self, Error> {
+        let path = output_dir.join(LOCK_FILE);
+
+        loop {
+            match File::create_new(&path) {
+                Ok(mut file) => {
+                    let lock = Self { path };
+                    write!(file, "{}", std::process::id())?;
+                    *HELD.lock().unwrap_or_else(|error| error.into_inner()) =
+                        Some(lock.path.clone());
+
+                    return Ok(lock);
+                }
+                Err(error) if error.kind() == io::ErrorKind::AlreadyExists => {
+                    // The holder may not have written its ID yet, in which
+                    // case the lock is held by an unknown process.
+                    let pid = fs::read_to_string(&path)
+                        .ok()
+                        .and_then(|contents| contents.trim().parse().ok());
+
+                    match pid {
+                        Some(pid) if util::process_alive(pid) == Some(false) => {
+                            eprintln!(
+                                "Reclaiming the lock '{}' left behind by process {pid}, which \
+                                 is no longer running",
+                                path.display()
+                            );
+                            match fs::remove_file(&path) {
+                                Err(error) if error.kind() != io::ErrorKind::NotFound => {
+                                    return Err(error.into());
+                                }
+                                _ => {}
+                            }
+                        }
+                        _ => return Err(Error::OutputLocked { path, pid }),
+                    }
+                }
+                Err(source) => {
+                    return Err(Error::OutputNotWritable {
+                        path: output_dir.to_owned(),
+                        source,
+                    });
+                }
+            }
+        }
+    }
+}
+
+impl Drop for OutputLock {
+    fn drop(&mut self) {
+        *HELD.lock().unwrap_or_else(|error| error.into_inner()) = None;
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Releases the lock held by this process, if any, before exiting without
+/// dropping it.
+pub fn release() {
+    let held = HELD
+        .lock()
+        .unwrap_or_else(|error| error.into_inner())
+        .take();
+
+    if let Some(path) = held {
+        let _ = fs::remove_file(path);
+    }
+}
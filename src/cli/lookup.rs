@@ -0,0 +1,253 @@
+use crate::cli::error::Error;
+use crate::cli::json::Document;
+use clap::Args;
+use extract_minecraft_assets::hashed::Object;
+use extract_minecraft_assets::lookup::{HashLookup, HashUsage};
+use extract_minecraft_assets::util;
+use serde::Serialize;
+use std::fs::File;
+use std::io::{self, BufRead};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+#[derive(Args)]
+pub struct WhichIndexSubcommand {
+    /// The SHA-1 hash of a hashed file, or an unambiguous prefix of it.
+    #[arg(long = "hash", value_name = "SHA1", required = true)]
+    hashes: Vec<String>,
+    /// The path to the `.minecraft/assets/` directory to find index files.
+    ///
+    /// Versions are found in the `versions` directory next to it. Defaults to
+    /// the default location on your OS.
+    #[arg(long = "hashed-assets", value_name = "DIRECTORY")]
+    hashed_assets_dir: Option<PathBuf>,
+}
+
+/// The document printed by `which-index --json`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct WhichIndexDocument {
+    hashes: Vec<HashUsage>,
+    /// The hashes given which no index file uses.
+    not_found: Vec<String>,
+}
+
+impl WhichIndexSubcommand {
+    /// Prints the index files and versions using each hash, exiting with 1 if
+    /// any hash isn't used by any index file.
+    ///
+    /// With `porcelain`, a line is printed for each path the hash is used at,
+    /// with the hash, index file, versions (separated by commas, or `-` if
+    /// none are installed) and path separated by tabs.
+    pub fn execute(self, json: bool, porcelain: bool) -> Result<ExitCode, Error> {
+        let hashed_assets_dir = self
+            .hashed_assets_dir
+            .or_else(util::hashed_assets_dir)
+            .unwrap_or_default();
+        let versions_dir = hashed_assets_dir.with_file_name("versions");
+
+        let lookup = HashLookup::load(&hashed_assets_dir, &versions_dir);
+        for error in lookup.errors() {
+            eprintln!("Skipping index file: {error}");
+        }
+
+        let mut hashes = Vec::new();
+        let mut not_found = Vec::new();
+        for prefix in self.hashes {
+            let mut usages = lookup.find(&prefix);
+
+            match usages.len() {
+                0 => not_found.push(prefix),
+                1 => hashes.append(&mut usages),
+                _ => {
+                    return Err(Error::AmbiguousHash {
+                        prefix,
+                        hashes: usages.into_iter().map(|usage| usage.hash).collect(),
+                    });
+                }
+            }
+        }
+
+        let exit_code = if not_found.is_empty() {
+            ExitCode::SUCCESS
+        } else {
+            ExitCode::FAILURE
+        };
+
+        if json {
+            Document::new(WhichIndexDocument { hashes, not_found }).print()?;
+
+            return Ok(exit_code);
+        }
+
+        for usage in &hashes {
+            if porcelain {
+                for index in &usage.indexes {
+                    let versions = if index.versions.is_empty() {
+                        String::from("-")
+                    } else {
+                        index.versions.join(",")
+                    };
+                    for path in &index.paths {
+                        println!(
+                            "{}\t{}\t{versions}\t{}",
+                            usage.hash,
+                            index.id,
+                            path.display()
+                        );
+                    }
+                }
+
+                continue;
+            }
+
+            println!("{}", usage.hash);
+            for index in &usage.indexes {
+                if index.versions.is_empty() {
+                    println!("  index {} (no installed versions)", index.id);
+                } else {
+                    println!("  index {} ({})", index.id, index.versions.join(", "));
+                }
+                for path in &index.paths {
+                    println!("    {}", path.display());
+                }
+            }
+        }
+        for prefix in &not_found {
+            eprintln!("No index file uses '{prefix}'");
+        }
+
+        Ok(exit_code)
+    }
+}
+
+#[derive(Args)]
+pub struct HashPathSubcommand {
+    /// The files to hash, or `-` to read their paths from stdin, one per
+    /// line.
+    #[arg(value_name = "FILE", required = true)]
+    files: Vec<PathBuf>,
+    /// The path to a `.minecraft/assets/` directory to check whether each
+    /// file is already in its `objects` directory.
+    #[arg(
+        long = "hashed-assets",
+        visible_alias = "assets-dir",
+        value_name = "DIRECTORY"
+    )]
+    hashed_assets_dir: Option<PathBuf>,
+}
+
+/// The document printed by `hash-path --json`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct HashPathDocument {
+    files: Vec<HashedPath>,
+    /// The files which couldn't be read.
+    unreadable: Vec<PathBuf>,
+}
+
+/// Where a file would be within the `objects` directory.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct HashedPath {
+    path: PathBuf,
+    hash: String,
+    size: u64,
+    /// The path of the file within the hashed assets directory, e.g.
+    /// `objects/bf/bf21a9e8fbc5a3846fb05b4fa0859e0917b2202f`.
+    object_path: PathBuf,
+    /// Whether the object is already in the `objects` directory, if one was
+    /// given with --hashed-assets.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    exists: Option<bool>,
+}
+
+impl HashPathSubcommand {
+    /// Prints the SHA-1 hash, size and path within the `objects` directory of
+    /// each file, exiting with 1 if any file can't be read.
+    ///
+    /// With `porcelain`, the hash, size, path within the `objects` directory,
+    /// whether it is there (`exists`, `missing` or `-` if not checked) and
+    /// path of each file are separated by tabs.
+    pub fn execute(self, json: bool, porcelain: bool) -> Result<ExitCode, Error> {
+        let mut paths = Vec::new();
+        for path in self.files {
+            if path == Path::new("-") {
+                for line in io::stdin().lock().lines() {
+                    let line = line?;
+                    if !line.is_empty() {
+                        paths.push(PathBuf::from(line));
+                    }
+                }
+            } else {
+                paths.push(path);
+            }
+        }
+
+        let mut files = Vec::new();
+        let mut unreadable = Vec::new();
+        for path in paths {
+            let object = File::open(&path).and_then(Object::from_reader);
+            let object = match object {
+                Ok(object) => object,
+                Err(error) => {
+                    eprintln!("Couldn't read '{}': {error}", path.display());
+                    unreadable.push(path);
+                    continue;
+                }
+            };
+            let object_path = Path::new("objects").join(object.hashed_file_path());
+            let exists = self
+                .hashed_assets_dir
+                .as_ref()
+                .map(|hashed_assets_dir| hashed_assets_dir.join(&object_path).is_file());
+
+            if porcelain && !json {
+                let exists = match exists {
+                    Some(true) => "exists",
+                    Some(false) => "missing",
+                    None => "-",
+                };
+                println!(
+                    "{}\t{}\t{}\t{exists}\t{}",
+                    object.hash,
+                    object.size,
+                    object_path.display(),
+                    path.display()
+                );
+            } else if !json {
+                let exists = match exists {
+                    Some(true) => "  exists",
+                    Some(false) => "  missing",
+                    None => "",
+                };
+                println!(
+                    "{}  {:>10}  {}{exists}  {}",
+                    object.hash,
+                    object.size,
+                    object_path.display(),
+                    path.display()
+                );
+            }
+
+            files.push(HashedPath {
+                path,
+                hash: object.hash,
+                size: object.size,
+                object_path,
+                exists,
+            });
+        }
+
+        let exit_code = if unreadable.is_empty() {
+            ExitCode::SUCCESS
+        } else {
+            ExitCode::FAILURE
+        };
+        if json {
+            Document::new(HashPathDocument { files, unreadable }).print()?;
+        }
+
+        Ok(exit_code)
+    }
+}
@@ -0,0 +1,52 @@
+//! The subcommands of the command-line interface.
+
+pub mod all;
+pub mod archive;
+pub mod browse;
+pub mod catalog;
+pub mod changed;
+pub mod clean;
+pub mod closure;
+pub mod complete;
+pub mod config;
+pub mod conflict;
+pub mod diff;
+pub mod doctor;
+pub mod du;
+pub mod duplicates;
+pub mod error;
+#[cfg(feature = "git")]
+pub mod git;
+pub mod grep;
+pub mod hashed;
+pub mod indexes;
+pub mod jar;
+pub mod json;
+pub mod lang;
+pub mod largest;
+pub mod libraries;
+pub mod list;
+pub mod locales;
+pub mod lock;
+pub mod lookup;
+pub mod namespace;
+pub mod nbt;
+#[cfg(feature = "notify")]
+pub mod notify;
+pub mod pack;
+pub mod progress;
+pub mod redistributable;
+pub mod references;
+pub mod search;
+pub mod serve;
+pub mod sounds;
+pub mod source;
+pub mod stats;
+pub mod sync;
+pub mod text;
+pub mod tree;
+pub mod validate;
+pub mod version;
+#[cfg(feature = "watch")]
+pub mod watch;
+pub mod written;
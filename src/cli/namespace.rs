@@ -0,0 +1,98 @@
+//! Only extracting the files in some namespaces, with `--namespace`.
+
+use clap::Args;
+use extract_minecraft_assets::category;
+use extract_minecraft_assets::jar::ExtractedContents;
+use extract_minecraft_assets::report::FileWarning;
+use std::collections::HashSet;
+use std::path::{Component, Path};
+
+/// Only extracting the files in some namespaces, shared by the extraction
+/// subcommands.
+#[derive(Args, Clone)]
+pub struct NamespaceArgs {
+    /// Only extract the files in this namespace within `assets` and `data`,
+    /// e.g. `realms`.
+    ///
+    /// Files outside of any namespace, such as `pack.mcmeta`, are left out
+    /// too. May be given more than once, in which case the files in any of
+    /// them are extracted. A warning is given for each namespace which no
+    /// file is in.
+    #[arg(long = "namespace", value_name = "NAME")]
+    namespaces: Vec<String>,
+}
+
+impl NamespaceArgs {
+    /// Whether the entry at `path` within a jar file, e.g.
+    /// `assets/realms/lang/de_de.json`, is in one of the namespaces, or
+    /// whether every entry is, if --namespace isn't given.
+    pub fn allows_jar_path(&self, path: &Path) -> bool {
+        self.allows(jar_namespace(path))
+    }
+
+    /// Whether the asset at `path` in an index file, e.g.
+    /// `realms/lang/de_de.json`, is in one of the namespaces, or whether
+    /// every asset is, if --namespace isn't given.
+    pub fn allows_index_path(&self, path: &Path) -> bool {
+        self.allows(category::namespace(path))
+    }
+
+    fn allows(&self, namespace: Option<&str>) -> bool {
+        self.namespaces.is_empty()
+            || namespace.is_some_and(|namespace| self.namespaces.iter().any(|n| n == namespace))
+    }
+
+    /// Returns a warning for each namespace which none of the entries at
+    /// `jar_paths` nor the assets at `index_paths` is in, to be added to the
+    /// report once extracted.
+    ///
+    /// The warnings are given for the namespace's directory within `dir`,
+    /// e.g. `assets`.
+    pub fn unmatched_warnings<'p>(
+        &self,
+        dir: &Path,
+        jar_paths: impl IntoIterator<Item = &'p Path>,
+        index_paths: impl IntoIterator<Item = &'p Path>,
+    ) -> Vec<FileWarning> {
+        if self.namespaces.is_empty() {
+            return Vec::new();
+        }
+
+        let found: HashSet<_> = jar_paths
+            .into_iter()
+            .filter_map(jar_namespace)
+            .chain(index_paths.into_iter().filter_map(category::namespace))
+            .collect();
+
+        self.namespaces
+            .iter()
+            .filter(|namespace| !found.contains(namespace.as_str()))
+            .map(|namespace| FileWarning {
+                path: dir.join(namespace),
+                message: format!("namespace '{namespace}' matched 0 files"),
+            })
+            .collect()
+    }
+}
+
+/// Returns the directory within which namespaces matching no files are
+/// warned about: `assets`, unless only `data` is extracted.
+pub fn warnings_dir(extracted_contents: &ExtractedContents) -> &'static Path {
+    if extracted_contents.contains("data") && !extracted_contents.contains("assets") {
+        Path::new("data")
+    } else {
+        Path::new("assets")
+    }
+}
+
+/// The namespace of the entry at `path` within a jar file, e.g. `realms`
+/// for `assets/realms/lang/de_de.json`, if it is within `assets` or `data`.
+fn jar_namespace(path: &Path) -> Option<&str> {
+    let mut components = path.components();
+    match components.next()? {
+        Component::Normal(root) if root == "assets" || root == "data" => {}
+        _ => return None,
+    }
+
+    category::namespace(components.as_path())
+}
@@ -0,0 +1,19 @@
+//! The format to which NBT files are converted.
+
+use clap::ValueEnum;
+use extract_minecraft_assets::nbt::NbtFormat;
+
+/// The format to which to convert NBT files while extracting.
+#[derive(Clone, Copy, ValueEnum)]
+pub enum ConvertNbt {
+    /// Convert `.nbt` files to indented `.snbt` text files.
+    Snbt,
+}
+
+impl From<ConvertNbt> for NbtFormat {
+    fn from(format: ConvertNbt) -> Self {
+        match format {
+            ConvertNbt::Snbt => Self::Snbt,
+        }
+    }
+}
@@ -0,0 +1,42 @@
+use crate::cli::error::Error;
+use extract_minecraft_assets::report::ExtractionReport;
+use notify_rust::Notification;
+
+/// Shows a desktop notification describing the `result` of an extraction.
+///
+/// Failing to deliver the notification is reported on stderr, but otherwise
+/// ignored.
+pub fn notify(result: &Result<ExtractionReport, Error>) {
+    let mut notification = Notification::new();
+    notification.appname("extract-minecraft-assets");
+
+    let failed = match result {
+        Ok(report) => {
+            notification
+                .summary("Extraction complete")
+                .body(&report.to_string());
+
+            report.summary.failures > 0
+        }
+        Err(error) => {
+            notification
+                .summary("Extraction failed")
+                .body(&error.to_string());
+
+            true
+        }
+    };
+
+    if failed {
+        notification.icon("dialog-error");
+
+        #[cfg(all(unix, not(target_os = "macos")))]
+        notification.urgency(notify_rust::Urgency::Critical);
+    } else {
+        notification.icon("dialog-information");
+    }
+
+    if let Err(error) = notification.show() {
+        eprintln!("Failed to show notification: {error}");
+    }
+}
@@ -0,0 +1,117 @@
+//! Shaping the output directory as a resource pack after extracting.
+
+use crate::cli::error::Error;
+use clap::Args;
+use extract_minecraft_assets::error::{ExtractError, IoContext};
+use extract_minecraft_assets::pack::{self, Overlay, PACK_MCMETA};
+use extract_minecraft_assets::report::ExtractionReport;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+
+/// Writing the output directory as a resource pack, shared by the extraction
+/// subcommands.
+#[derive(Args)]
+pub struct ResourcePackArgs {
+    /// Shape the output directory as a resource pack, writing or updating
+    /// its `pack.mcmeta`.
+    ///
+    /// Assets are extracted into `assets/` as usual, so --ignore-top-level
+    /// and --group-by can't be used.
+    #[arg(long, conflicts_with_all = ["ignore_top_level", "group_by"], global = true)]
+    as_resource_pack: bool,
+    /// The pack format to write in `pack.mcmeta`.
+    ///
+    /// Defaults to the pack format of the existing `pack.mcmeta` in the
+    /// output directory, which is required if there isn't one.
+    #[arg(
+        long,
+        value_name = "FORMAT",
+        requires = "as_resource_pack",
+        global = true
+    )]
+    pack_format: Option<u32>,
+    /// Place the extracted assets in this overlay, applied by the game to
+    /// these pack formats, e.g. `new_ui:18-22` or `new_ui:18`.
+    ///
+    /// Overlays are supported since 1.20.2. The assets are placed in
+    /// `<NAME>/assets/`, and the overlay is listed in `overlays.entries` of
+    /// `pack.mcmeta`, keeping the other overlays listed there. May be given
+    /// more than once, but overlays may not apply to the same pack format.
+    #[arg(
+        long = "overlay",
+        value_name = "NAME:FORMATS",
+        value_parser = str::parse::<Overlay>,
+        requires = "as_resource_pack",
+        global = true
+    )]
+    overlays: Vec<Overlay>,
+    /// Also keep the assets placed in overlays in the pack's base `assets/`,
+    /// which is otherwise left empty.
+    #[arg(long, requires = "overlays", global = true)]
+    overlay_base: bool,
+}
+
+/// A resource pack to finish writing once the assets are extracted.
+pub struct ResourcePack {
+    pack_mcmeta: Vec<u8>,
+    overlays: Vec<Overlay>,
+    keep_base: bool,
+}
+
+impl ResourcePackArgs {
+    /// Returns the resource pack to write into `output_dir`, if
+    /// --as-resource-pack is given.
+    ///
+    /// This is done before extracting anything, so that an invalid
+    /// `pack.mcmeta` or overlapping overlays are reported up front.
+    pub fn prepare(self, output_dir: &Path) -> Result<Option<ResourcePack>, Error> {
+        if !self.as_resource_pack {
+            return Ok(None);
+        }
+
+        let path = output_dir.join(PACK_MCMETA);
+        let existing = match fs::read(&path) {
+            Err(error) if error.kind() == ErrorKind::NotFound => None,
+            result => Some(result.reading_output(&path)?),
+        };
+        let pack_mcmeta = pack::pack_mcmeta(existing.as_deref(), self.pack_format, &self.overlays)?;
+
+        Ok(Some(ResourcePack {
+            pack_mcmeta,
+            overlays: self.overlays,
+            keep_base: self.overlay_base,
+        }))
+    }
+}
+
+impl ResourcePack {
+    /// Places the assets extracted in `report` in the overlays, and writes
+    /// `pack.mcmeta`.
+    ///
+    /// Does nothing if the extraction stopped early.
+    pub fn finish(&self, report: &ExtractionReport) -> Result<(), ExtractError> {
+        if report.summary.stopped() {
+            return Ok(());
+        }
+
+        if !self.overlays.is_empty() {
+            let placed = pack::place_in_overlays(report, &self.overlays, self.keep_base)?;
+            let names: Vec<_> = self
+                .overlays
+                .iter()
+                .map(|overlay| format!("'{}' ({})", overlay.directory, overlay.formats))
+                .collect();
+            eprintln!("Placed {placed} files in overlays {}", names.join(", "));
+        }
+
+        let path = report.options.output_dir.join(PACK_MCMETA);
+        report
+            .options
+            .vfs
+            .write_file(&path, &mut self.pack_mcmeta.as_slice())
+            .map_err(|source| ExtractError::WriteFile { path, source })?;
+
+        Ok(())
+    }
+}
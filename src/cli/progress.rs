@@ -0,0 +1,500 @@
+use crate::cli::written;
+use crossterm::cursor::MoveUp;
+use crossterm::terminal::ClearType;
+use crossterm::{terminal, QueueableCommand};
+use extract_minecraft_assets::progress::{
+    FrameTarget, MultiProgress, ProgressSink, Summary, WorkerEvent,
+};
+use extract_minecraft_assets::util;
+use std::fmt::{Display, Formatter};
+use std::io::{IsTerminal, Write};
+use std::path::Path;
+use std::sync::mpsc::Sender;
+use std::sync::{Mutex, TryLockError};
+use std::time::Duration;
+use std::{fmt, io, panic};
+
+/// The status line currently displayed at the bottom of the terminal, if any.
+///
+/// The status line is the line the cursor is on: it is replaced by returning
+/// to the start of the line and clearing it, so nothing else may be written to
+/// stderr while it is displayed other than through [`message`]. A status may
+/// span several lines, in which case the cursor is on its last line.
+static STATUS: Mutex<Option<String>> = Mutex::new(None);
+
+/// Displays `status` on the status line, replacing what was there before.
+fn show_status(status: String) -> io::Result<()> {
+    let mut displayed = STATUS.lock().unwrap_or_else(|error| error.into_inner());
+    let mut stderr = io::stderr().lock();
+
+    erase(&mut stderr, displayed.as_deref())?;
+    write!(stderr, "{status}")?;
+    stderr.flush()?;
+
+    *displayed = Some(status);
+
+    Ok(())
+}
+
+/// Clears the status line, if one is displayed.
+///
+/// The cursor is left at the start of the now-empty line, so further output
+/// is written as normal.
+pub fn clear() -> io::Result<()> {
+    clear_from(&mut io::stderr())
+}
+
+/// Clears the status line, if one is displayed, by writing to `stderr`.
+fn clear_from(stderr: &mut impl Write) -> io::Result<()> {
+    let mut displayed = STATUS.lock().unwrap_or_else(|error| error.into_inner());
+
+    if let Some(status) = displayed.take() {
+        erase(stderr, Some(&status))?;
+    }
+
+    Ok(())
+}
+
+/// Returns the cursor to the start of the first line of `status`, which is
+/// displayed, and clears it along with every line below.
+fn erase(stderr: &mut impl Write, status: Option<&str>) -> io::Result<()> {
+    write!(stderr, "\r")?;
+
+    let rows = status.map_or(0, |status| status.lines().count());
+    if let Ok(rows) = u16::try_from(rows.saturating_sub(1))
+        && rows > 0
+    {
+        stderr.queue(MoveUp(rows))?;
+    }
+    stderr.queue(terminal::Clear(ClearType::FromCursorDown))?;
+    stderr.flush()
+}
+
+/// Makes panics clear the status line before their message is printed, so
+/// that the message isn't appended to the status line.
+pub fn clear_on_panic() {
+    let default_hook = panic::take_hook();
+
+    panic::set_hook(Box::new(move |info| {
+        let _ = clear_while_panicking(&mut io::stderr());
+
+        default_hook(info);
+    }));
+}
+
+/// Clears the status line, if one is displayed, by writing to `stderr` from
+/// a panic hook.
+///
+/// The panic may have happened while the status line was being displayed,
+/// in which case its last line is erased whether it was or not.
+fn clear_while_panicking(stderr: &mut impl Write) -> io::Result<()> {
+    let displayed = match STATUS.try_lock() {
+        Ok(mut displayed) => displayed.take().map(Some),
+        Err(TryLockError::Poisoned(error)) => error.into_inner().take().map(Some),
+        Err(TryLockError::WouldBlock) => Some(None),
+    };
+
+    match displayed {
+        Some(status) => erase(stderr, status.as_deref()),
+        None => Ok(()),
+    }
+}
+
+/// Prints `message` as a line on stderr above the status line, like
+/// [`eprintln!`], so that the status line stays at the bottom of the terminal.
+///
+/// When no status line is displayed, e.g. as stderr isn't a terminal, the
+/// message is printed as a plain line.
+pub fn message(message: impl Display) {
+    message_to(&mut io::stderr().lock(), message);
+}
+
+/// Prints `message` as a line above the status line by writing to `stderr`.
+fn message_to(stderr: &mut impl Write, message: impl Display) {
+    let displayed = STATUS.lock().unwrap_or_else(|error| error.into_inner());
+
+    // Failing to display the status line isn't worth failing over, but the
+    // message itself is written regardless.
+    if let Some(status) = &*displayed {
+        let _ = erase(stderr, Some(status));
+        let _ = writeln!(stderr, "{message}");
+        let _ = write!(stderr, "{status}");
+        let _ = stderr.flush();
+    } else {
+        let _ = writeln!(stderr, "{message}");
+    }
+}
+
+/// Asks `question` on stderr above the status line, and returns the line
+/// answered on stdin.
+///
+/// The status line isn't drawn again until the question is answered, so
+/// that nothing is written over it.
+pub fn ask(question: impl Display) -> io::Result<String> {
+    let displayed = STATUS.lock().unwrap_or_else(|error| error.into_inner());
+    let mut stderr = io::stderr().lock();
+
+    if let Some(status) = &*displayed {
+        erase(&mut stderr, Some(status))?;
+    }
+    write!(stderr, "{question}")?;
+    stderr.flush()?;
+
+    let mut answer = String::new();
+    let read = io::stdin().read_line(&mut answer);
+
+    if let Some(status) = &*displayed {
+        write!(stderr, "{status}")?;
+        stderr.flush()?;
+    }
+    read?;
+
+    Ok(answer)
+}
+
+/// How often the frames of a [`MultiProgress`] are drawn at most.
+///
+/// [`MultiProgress`]: extract_minecraft_assets::progress::MultiProgress
+pub const FRAME_INTERVAL: Duration = Duration::from_millis(50);
+
+/// The narrowest the path in a progress message is truncated to; below this,
+/// the whole message is truncated instead so the path remains recognizable.
+const MIN_PATH_WIDTH: usize = 16;
+
+/// Displays the progress of an extraction in the terminal, split into one or
+/// more phases.
+///
+/// Each phase corresponds to one call to an extraction function: the next
+/// phase starts whenever [`begin`](ProgressSink::begin) is called. The phases
+/// can be [added](TerminalProgress::add_phase) before extraction starts so
+/// that the overall progress can be displayed alongside the current phase's
+/// progress.
+///
+/// Progress is displayed on stderr, leaving stdout for the primary result.
+pub struct TerminalProgress {
+    phases: Vec<Phase>,
+    /// The index of the phase currently in progress, if any.
+    current: Option<usize>,
+    /// Whether progress messages are displayed at all.
+    visible: bool,
+    /// The display of the workers extracting the current phase, if it is
+    /// split across threads.
+    workers: Option<MultiProgress>,
+}
+
+/// A single phase of an extraction, such as extracting from a jar file.
+struct Phase {
+    /// The name of the phase, e.g. `hashed assets`.
+    name: String,
+    /// The number of files to be extracted in this phase.
+    total: usize,
+    /// The number of files which have been extracted so far in this phase.
+    completed: usize,
+    /// The total size of the files to be extracted in this phase, as given by
+    /// the index file or the sizes of the jar's entries.
+    total_bytes: u64,
+    /// The number of bytes which have been written so far in this phase.
+    bytes: u64,
+}
+
+impl TerminalProgress {
+    /// Returns a new [`TerminalProgress`] with no phases.
+    ///
+    /// Progress is only displayed when stderr is a terminal, and unless
+    /// `porcelain` is set: otherwise it is still tracked but never displayed,
+    /// so that redirected output isn't filled with status lines.
+    pub fn new(porcelain: bool) -> Self {
+        Self {
+            phases: Vec::new(),
+            current: None,
+            visible: !porcelain && io::stderr().is_terminal(),
+            workers: None,
+        }
+    }
+
+    /// Adds a phase which will extract `total` files.
+    pub fn add_phase(&mut self, name: impl Into<String>, total: usize) {
+        self.phases.push(Phase {
+            name: name.into(),
+            total,
+            completed: 0,
+            total_bytes: 0,
+            bytes: 0,
+        });
+    }
+
+    /// Displays the current progress, replacing the previous status line.
+    fn render(&self, path: &Path) -> io::Result<()> {
+        let Some(current) = self.current else {
+            return Ok(());
+        };
+        let phase = &self.phases[current];
+
+        // Every phase is shown on the same line, so which one it is comes first.
+        let prefix = if self.phases.len() > 1 {
+            format!("[{}/{}] Extracting ", current + 1, self.phases.len())
+        } else {
+            String::from("Extracting ")
+        };
+        let counter = format!(
+            "({}/{} {}, {})",
+            phase.completed,
+            phase.total,
+            phase.name,
+            phase.bytes_progress()
+        );
+        let path = path.display().to_string();
+
+        // The line must fit on one row, otherwise it wraps and clearing it
+        // only clears its last row. The width is queried every time so that
+        // resizing the terminal is taken into account.
+        let width = terminal::size().map_or(usize::MAX, |(columns, _)| usize::from(columns));
+        // Leave the last column free, as some terminals wrap when it is written.
+        let width = width.saturating_sub(1);
+        // The width left for the path once the prefix and counter are written.
+        let path_width =
+            width.saturating_sub(util::display_width(&prefix) + util::display_width(&counter) + 1);
+
+        if path_width > MIN_PATH_WIDTH {
+            let path = util::truncate_middle(&path, path_width);
+            show_status(format!("{prefix}{path} {counter}"))
+        } else {
+            let line = format!("{prefix}{path} {counter}");
+            show_status(util::truncate_middle(&line, width))
+        }
+    }
+
+    /// Clears the displayed progress message.
+    pub fn clear(&self) -> io::Result<()> {
+        clear()
+    }
+
+    /// The number of files extracted so far across every phase.
+    pub fn completed(&self) -> usize {
+        self.phases.iter().map(|phase| phase.completed).sum()
+    }
+
+    /// The number of files to be extracted across every phase.
+    pub fn total(&self) -> usize {
+        self.phases.iter().map(|phase| phase.total).sum()
+    }
+
+    /// The number of bytes written so far across every phase.
+    pub fn bytes(&self) -> u64 {
+        self.phases.iter().map(|phase| phase.bytes).sum()
+    }
+}
+
+impl Phase {
+    /// Describes the bytes written out of the total, e.g.
+    /// `12.3 MiB/190.5 MiB, 6%`.
+    ///
+    /// Files which are converted or skipped don't write as many bytes as the
+    /// total counted for them, so the percentage is capped at 100% and only
+    /// reaches it if every file was copied as it is.
+    fn bytes_progress(&self) -> String {
+        if self.total_bytes == 0 {
+            return util::format_size(self.bytes);
+        }
+
+        let percent = (self.bytes.saturating_mul(100) / self.total_bytes).min(100);
+        format!(
+            "{}/{}, {percent}%",
+            util::format_size(self.bytes),
+            util::format_size(self.total_bytes)
+        )
+    }
+}
+
+impl ProgressSink for TerminalProgress {
+    fn begin(&mut self, total_files: usize, total_bytes: u64) {
+        let next = self.current.map_or(0, |current| current + 1);
+
+        if next == self.phases.len() {
+            self.add_phase("files", total_files);
+        }
+        // The extraction function knows the total best
+        let phase = &mut self.phases[next];
+        phase.total = total_files;
+        phase.total_bytes = total_bytes;
+        self.current = Some(next);
+    }
+
+    fn file_started(&mut self, path: &Path) {
+        if let Some(phase) = self
+            .current
+            .and_then(|current| self.phases.get_mut(current))
+        {
+            phase.completed += 1;
+        }
+
+        // The workers' display replaces the status line while it is drawn.
+        if self.visible && self.workers.is_none() {
+            let _ = self.render(path);
+        }
+    }
+
+    fn file_done(&mut self, path: &Path, bytes: u64) {
+        if let Some(phase) = self
+            .current
+            .and_then(|current| self.phases.get_mut(current))
+        {
+            phase.bytes += bytes;
+        }
+
+        written::record(path);
+    }
+
+    fn file_skipped(&mut self, _path: &Path) {
+        if let Some(phase) = self
+            .current
+            .and_then(|current| self.phases.get_mut(current))
+        {
+            phase.completed += 1;
+        }
+    }
+
+    fn finish(&mut self, _summary: &Summary) {
+        if let Some(workers) = self.workers.take() {
+            let _ = workers.finish();
+        }
+    }
+
+    fn workers(&mut self, workers: usize) -> Option<Sender<WorkerEvent>> {
+        if !self.visible {
+            return None;
+        }
+
+        let total = self.phases.get(self.current?)?.total;
+        let display = MultiProgress::spawn(TerminalFrames, total, workers, FRAME_INTERVAL);
+        let sender = display.sender().clone();
+        self.workers = Some(display);
+
+        Some(sender)
+    }
+}
+
+/// Clears the status line, so that it isn't left behind when an extraction
+/// fails part way through.
+impl Drop for TerminalProgress {
+    fn drop(&mut self) {
+        if self.visible {
+            let _ = clear();
+        }
+    }
+}
+
+/// Summarizes the progress, e.g. `1200/1200 files extracted, 24.5 MiB written (jar assets: 800/800, hashed assets: 400/400)`.
+impl Display for TerminalProgress {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}/{} files extracted, {} written",
+            self.completed(),
+            self.total(),
+            util::format_size(self.bytes())
+        )?;
+
+        if self.phases.len() > 1 {
+            let phases: Vec<String> = self
+                .phases
+                .iter()
+                .map(|phase| format!("{}: {}/{}", phase.name, phase.completed, phase.total))
+                .collect();
+
+            write!(f, " ({})", phases.join(", "))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Draws the frames of a [`MultiProgress`] on the status line, which spans a
+/// line per worker.
+///
+/// [`MultiProgress`]: extract_minecraft_assets::progress::MultiProgress
+pub struct TerminalFrames;
+
+impl FrameTarget for TerminalFrames {
+    /// Redirected output only gets a single line, whatever the size of the
+    /// controlling terminal.
+    fn size(&self) -> Option<(usize, usize)> {
+        if !io::stderr().is_terminal() {
+            return None;
+        }
+
+        terminal::size()
+            .ok()
+            .map(|(columns, rows)| (usize::from(columns), usize::from(rows)))
+    }
+
+    fn draw(&mut self, lines: &[String]) -> io::Result<()> {
+        show_status(lines.join("\n"))
+    }
+
+    fn clear(&mut self) -> io::Result<()> {
+        clear()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Held by each test, as they share the status line.
+    static SERIAL: Mutex<()> = Mutex::new(());
+
+    /// Displays `status` without writing it anywhere.
+    fn display(status: &str) {
+        *STATUS.lock().unwrap_or_else(|error| error.into_inner()) = Some(status.to_owned());
+    }
+
+    #[test]
+    fn status_is_cleared() {
+        let _serial = SERIAL.lock().unwrap_or_else(|error| error.into_inner());
+        let mut stderr = Vec::new();
+
+        display("first line\nsecond line");
+        clear_from(&mut stderr).unwrap();
+        // Nothing is displayed any more, so there is nothing to clear.
+        clear_from(&mut stderr).unwrap();
+
+        assert_eq!(stderr, b"\r\x1b[1A\x1b[J");
+        assert_eq!(*STATUS.lock().unwrap(), None);
+    }
+
+    #[test]
+    fn messages_are_printed_above_the_status() {
+        let _serial = SERIAL.lock().unwrap_or_else(|error| error.into_inner());
+        let mut stderr = Vec::new();
+
+        display("Extracting en_us.json");
+        message_to(&mut stderr, "Warning");
+        clear_from(&mut stderr).unwrap();
+        // Nothing is displayed any more, so the message is a plain line.
+        message_to(&mut stderr, "Done");
+
+        assert_eq!(
+            String::from_utf8(stderr).unwrap(),
+            "\r\x1b[JWarning\nExtracting en_us.json\r\x1b[JDone\n"
+        );
+    }
+
+    #[test]
+    fn status_is_cleared_when_panicking() {
+        let _serial = SERIAL.lock().unwrap_or_else(|error| error.into_inner());
+        let mut stderr = Vec::new();
+
+        display("first line\nsecond line");
+        clear_while_panicking(&mut stderr).unwrap();
+        clear_while_panicking(&mut stderr).unwrap();
+        assert_eq!(stderr, b"\r\x1b[1A\x1b[J");
+
+        // Only the last line is erased while the status is being displayed.
+        stderr.clear();
+        let displayed = STATUS.lock().unwrap();
+        clear_while_panicking(&mut stderr).unwrap();
+        drop(displayed);
+        assert_eq!(stderr, b"\r\x1b[J");
+    }
+}
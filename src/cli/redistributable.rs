@@ -0,0 +1,75 @@
+use clap::Args;
+use extract_minecraft_assets::filter::{Pattern, PatternSet};
+use extract_minecraft_assets::redistributable::{EXCLUSIONS, Exclusion, Redistributable};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Leaving out files which can't be redistributed, shared by the extraction
+/// subcommands.
+#[derive(Args, Clone)]
+pub struct RedistributableArgs {
+    /// Leave out the files which can't be redistributed, e.g. to publish the
+    /// extracted files.
+    ///
+    /// Textures, sound files, font glyph bitmaps, and any other images and
+    /// sound or music files are left out, as they are Mojang's artwork and
+    /// recordings. Models, blockstates, language files, data and everything
+    /// else are kept. A notice lists how many files were left out and why.
+    #[arg(long)]
+    redistributable: bool,
+    /// Redistribute the files matching this glob, even if --redistributable
+    /// would leave them out.
+    ///
+    /// You take responsibility for redistributing them. Globs are matched
+    /// against paths like `assets/minecraft/textures/gui/title/minecraft.png`.
+    /// May be given more than once.
+    #[arg(
+        long,
+        value_name = "GLOB",
+        value_parser = Pattern::glob,
+        requires = "redistributable"
+    )]
+    redistribute: Vec<Pattern>,
+}
+
+impl RedistributableArgs {
+    /// Returns the filter leaving out the files which can't be
+    /// redistributed, if --redistributable is given.
+    pub fn filter(&self) -> Option<Redistributable> {
+        self.redistributable.then(|| {
+            Redistributable::new()
+                .include(self.redistribute.iter().cloned().collect::<PatternSet>())
+        })
+    }
+}
+
+/// Prints how many of the files at `paths` `filter` leaves out, and why.
+///
+/// Paths are relative to the jar's top-level directory, e.g.
+/// `assets/minecraft/textures/block/stone.png`.
+pub fn print_notice<'p>(filter: &Redistributable, paths: impl IntoIterator<Item = &'p Path>) {
+    let mut counts: HashMap<&Exclusion, usize> = HashMap::new();
+    for path in paths {
+        if let Some(exclusion) = filter.exclusion(path) {
+            *counts.entry(exclusion).or_default() += 1;
+        }
+    }
+
+    if counts.is_empty() {
+        eprintln!("No files needed to be left out to be redistributable");
+        return;
+    }
+
+    eprintln!("Leaving out files which can't be redistributed:");
+    for exclusion in EXCLUSIONS {
+        if let Some(count) = counts.get(exclusion) {
+            eprintln!(
+                "  {count} {} ({}), as {}",
+                exclusion.name, exclusion.glob, exclusion.reason
+            );
+        }
+    }
+    eprintln!(
+        "Pass --redistribute to keep any of them, taking responsibility for redistributing them"
+    );
+}
@@ -0,0 +1,131 @@
+use crate::cli::error::Error;
+use crate::cli::json::Document;
+use crate::cli::source;
+use clap::Args;
+use extract_minecraft_assets::closure::AssetSources;
+use extract_minecraft_assets::references::{self, ReferenceCheck};
+use extract_minecraft_assets::util;
+use extract_minecraft_assets::version::Version;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+#[derive(Args)]
+pub struct ValidatePackSubcommand {
+    /// The directory to check, with `assets` within it like a resource pack
+    /// or the output of an extraction.
+    ///
+    /// Defaults to the output directory. With --ignore-top-level, this is
+    /// the `assets` directory itself.
+    #[arg(value_name = "DIRECTORY")]
+    dir: Option<PathBuf>,
+    /// Take the assets of this version as a base which the directory
+    /// overrides, so that references to vanilla files aren't dangling.
+    ///
+    /// Can be a path to the directory, or the name of the version to be found
+    /// within `.minecraft/versions/`. Only the listings of its jar file and
+    /// index file are read.
+    #[arg(long, value_name = "VERSION", value_parser = source::parse_version)]
+    against: Option<Version>,
+    /// The path to the `.minecraft/assets/` directory to find the index file
+    /// of the version given with --against.
+    ///
+    /// Defaults to the default location on your OS.
+    #[arg(long = "hashed-assets", value_name = "DIRECTORY", requires = "against")]
+    hashed_assets_dir: Option<PathBuf>,
+}
+
+impl ValidatePackSubcommand {
+    /// Prints the references to missing files of the blockstates, models,
+    /// fonts and `sounds.json` files in the directory, grouped by the file
+    /// referencing them, exiting with 1 if there are any or any file couldn't
+    /// be read.
+    pub fn execute(
+        self,
+        output_dir: &Path,
+        ignore_top_level: bool,
+        json: bool,
+    ) -> Result<ExitCode, Error> {
+        let dir = self.dir.as_deref().unwrap_or(output_dir);
+        let assets_dir = if ignore_top_level {
+            dir.to_owned()
+        } else {
+            dir.join("assets")
+        };
+
+        if !assets_dir.is_dir() {
+            return Err(Error::Io(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no assets directory found at '{}'", assets_dir.display()),
+            )));
+        }
+
+        let base = match &self.against {
+            Some(version) => {
+                let hashed_assets_dir = self
+                    .hashed_assets_dir
+                    .or_else(util::hashed_assets_dir)
+                    .unwrap_or_default();
+                let index = source::version_index(version, &hashed_assets_dir)?;
+
+                Some(
+                    AssetSources::new()
+                        .jar(&version.jar_file())?
+                        .index(&hashed_assets_dir, index),
+                )
+            }
+            None => None,
+        };
+        let check = references::check(&assets_dir, base.as_ref())?;
+
+        let exit_code = if check.files.is_empty() && check.errors.is_empty() {
+            ExitCode::SUCCESS
+        } else {
+            ExitCode::FAILURE
+        };
+
+        if json {
+            Document::new(&check).print()?;
+        } else {
+            print_check(&check);
+        }
+
+        Ok(exit_code)
+    }
+}
+
+/// Prints the missing files under each file referencing them, followed by
+/// the files which couldn't be read and a summary.
+fn print_check(check: &ReferenceCheck) {
+    for file in &check.files {
+        println!("{}", file.path.display());
+        for dangling in &file.dangling {
+            println!("  {}", dangling.display());
+        }
+    }
+    for error in &check.errors {
+        eprintln!(
+            "Couldn't read the references of '{}': {}",
+            error.path.display(),
+            error.message
+        );
+    }
+
+    eprintln!(
+        "Checked {} {}: {} dangling {} in {} {}",
+        check.checked,
+        if check.checked == 1 { "file" } else { "files" },
+        check.dangling(),
+        if check.dangling() == 1 {
+            "reference"
+        } else {
+            "references"
+        },
+        check.files.len(),
+        if check.files.len() == 1 {
+            "file"
+        } else {
+            "files"
+        },
+    );
+}
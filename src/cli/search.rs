@@ -0,0 +1,139 @@
+use crate::cli::error::Error;
+use crate::cli::json::Document;
+use crate::cli::source::Source;
+use clap::{ArgGroup, Args, ValueEnum};
+use extract_minecraft_assets::filter::{Pattern, PatternSet};
+use extract_minecraft_assets::jar::ExtractedContents;
+use extract_minecraft_assets::search::{self, Hit};
+use extract_minecraft_assets::util;
+use serde::Serialize;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+#[derive(Args)]
+#[command(group(ArgGroup::new("patterns").required(true).multiple(true)))]
+pub struct SearchSubcommand {
+    /// Match paths containing this.
+    #[arg(value_name = "PATTERN", group = "patterns")]
+    substrings: Vec<String>,
+    /// Match paths matching this glob.
+    ///
+    /// `*` and `?` don't match `/`, while `**` matches any number of
+    /// directories. A glob without a `/` is matched against file names, so
+    /// `*.ogg` matches every `.ogg` file.
+    #[arg(long = "glob", value_name = "GLOB", value_parser = Pattern::glob, group = "patterns")]
+    globs: Vec<Pattern>,
+    /// Match paths containing a match of this regular expression.
+    #[arg(long = "regex", value_name = "REGEX", value_parser = Pattern::regex, group = "patterns")]
+    regexes: Vec<Pattern>,
+    /// The version, jar file or index file to search.
+    ///
+    /// Paths ending in `.jar` or `.zip` are jar files, and paths ending in
+    /// `.json` are index files. Otherwise, this is the name or directory of a
+    /// version if there is one, or else the name of an index file. A version
+    /// includes both its jar file and its index file.
+    #[arg(long = "version", value_name = "VERSION, JAR or INDEX", value_parser = Source::parse)]
+    source: Source,
+    /// The path to the `.minecraft/assets/` directory to find index files.
+    ///
+    /// Defaults to the default location on your OS.
+    #[arg(long = "hashed-assets", value_name = "DIRECTORY")]
+    hashed_assets_dir: Option<PathBuf>,
+    /// Only search `assets` or `data`.
+    #[arg(long = "type", value_name = "TYPE")]
+    content_type: Option<ContentType>,
+    /// Stop at the first hit.
+    #[arg(long)]
+    first: bool,
+}
+
+/// The folder of a jar file to search.
+#[derive(Clone, Copy, ValueEnum)]
+pub enum ContentType {
+    Assets,
+    Data,
+}
+
+impl ContentType {
+    /// The contents to search for `--type`, or everything without it.
+    pub fn contents(content_type: Option<Self>) -> ExtractedContents {
+        match content_type {
+            None => ExtractedContents::all(),
+            Some(Self::Assets) => ExtractedContents::assets(),
+            Some(Self::Data) => ExtractedContents::data(),
+        }
+    }
+}
+
+/// The document printed by `search --json`.
+#[derive(Serialize)]
+struct SearchDocument {
+    hits: Vec<Hit>,
+}
+
+impl SearchSubcommand {
+    /// Prints the files whose paths match any of the patterns, exiting with
+    /// 1 if there are none.
+    pub fn execute(self, json: bool) -> Result<ExitCode, Error> {
+        let patterns: PatternSet = self
+            .substrings
+            .iter()
+            .map(Pattern::substring)
+            .chain(self.globs)
+            .chain(self.regexes)
+            .collect();
+        let contents = ContentType::contents(self.content_type);
+        let hashed_assets_dir = self
+            .hashed_assets_dir
+            .or_else(util::hashed_assets_dir)
+            .unwrap_or_default();
+
+        let mut hits = Vec::new();
+        if let Some(jar_file) = self.source.jar_file() {
+            hits = search::search_jar(&jar_file, &contents, &patterns)?;
+        }
+        if (!self.first || hits.is_empty())
+            && let Some(index) = self
+                .source
+                .index(&hashed_assets_dir, contents.contains("assets"))?
+        {
+            hits.extend(search::search_index(&index, &patterns));
+        }
+
+        if self.first {
+            hits.truncate(1);
+        }
+        for hit in &mut hits {
+            if let Some(object_path) = &mut hit.object_path {
+                *object_path = hashed_assets_dir.join("objects").join(&*object_path);
+            }
+        }
+
+        let exit_code = if hits.is_empty() {
+            ExitCode::FAILURE
+        } else {
+            ExitCode::SUCCESS
+        };
+
+        if json {
+            Document::new(SearchDocument { hits }).print()?;
+
+            return Ok(exit_code);
+        }
+
+        for hit in &hits {
+            print!(
+                "{:<6} {:>10}  {}",
+                hit.source.name(),
+                util::format_size(hit.size),
+                hit.path.display()
+            );
+            match &hit.object_path {
+                Some(object_path) => println!(" ({})", object_path.display()),
+                None => println!(),
+            }
+        }
+
+        Ok(exit_code)
+    }
+}
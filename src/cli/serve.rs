@@ -0,0 +1,287 @@
+use crate::cli::error::Error;
+use crate::cli::source;
+use clap::Args;
+use extract_minecraft_assets::closure::AssetSources;
+use extract_minecraft_assets::options::CancellationToken;
+use extract_minecraft_assets::search::FileSource;
+use extract_minecraft_assets::util;
+use extract_minecraft_assets::version::Version;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::{Component, Path, PathBuf};
+use std::process::ExitCode;
+use std::thread;
+use std::time::Duration;
+
+/// How long to wait between checks for Ctrl-C while no request arrives.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// How long a connection may take to send its request, so that one which
+/// never does can't hold up every other.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The most bytes read of a request's line and headers.
+const MAX_REQUEST_SIZE: u64 = 16 * 1024;
+
+#[derive(Args)]
+pub struct ServeSubcommand {
+    /// The version whose assets to serve.
+    ///
+    /// Can be a path to the directory, or the name of the version to be found
+    /// within `.minecraft/versions/`.
+    #[arg(value_name = "VERSION", value_parser = source::parse_version)]
+    version: Version,
+    /// The path to the `.minecraft/assets/` directory to find the version's
+    /// index file and hashed assets.
+    ///
+    /// Defaults to the default location on your OS.
+    #[arg(long = "hashed-assets", value_name = "DIRECTORY")]
+    hashed_assets_dir: Option<PathBuf>,
+    /// The address and port to listen on.
+    ///
+    /// Only this machine can connect by default. Use port 0 to pick any free
+    /// port.
+    #[arg(long, value_name = "ADDRESS", default_value = "127.0.0.1:8080")]
+    listen: SocketAddr,
+}
+
+/// The JSON listing served at a directory's path.
+#[derive(Serialize)]
+struct Listing<'a> {
+    /// The path of the directory, e.g. `/assets/minecraft/`.
+    path: String,
+    entries: Vec<ListingEntry<'a>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+enum ListingEntry<'a> {
+    Directory {
+        name: &'a str,
+    },
+    File {
+        name: &'a str,
+        source: FileSource,
+        size: u64,
+    },
+}
+
+/// A response to a request.
+struct Response {
+    status: &'static str,
+    content_type: &'static str,
+    body: Vec<u8>,
+}
+
+impl ServeSubcommand {
+    /// Serves the version's `assets` over HTTP until Ctrl-C is pressed.
+    ///
+    /// Each connection is answered with a single response and then closed,
+    /// one connection at a time.
+    pub fn execute(self, cancellation: CancellationToken) -> Result<ExitCode, Error> {
+        let hashed_assets_dir = self
+            .hashed_assets_dir
+            .or_else(util::hashed_assets_dir)
+            .unwrap_or_default();
+        let index = source::version_index(&self.version, &hashed_assets_dir)?;
+        let mut sources = AssetSources::new()
+            .jar(&self.version.jar_file())?
+            .index(&hashed_assets_dir, index);
+
+        let listener = TcpListener::bind(self.listen)?;
+        // Accepting doesn't block, so that Ctrl-C is noticed between requests.
+        listener.set_nonblocking(true)?;
+        eprintln!(
+            "Serving the assets of {} at http://{}/assets/, until Ctrl-C is pressed",
+            self.version.name(),
+            listener.local_addr()?
+        );
+
+        while !cancellation.is_cancelled() {
+            match listener.accept() {
+                Ok((stream, _)) => {
+                    // A connection failing only fails its own request.
+                    if let Err(error) = respond(stream, &mut sources) {
+                        eprintln!("Failed to respond to a request: {error}");
+                    }
+                }
+                Err(error) if error.kind() == io::ErrorKind::WouldBlock => {
+                    thread::sleep(POLL_INTERVAL);
+                }
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+                Err(error) => return Err(error.into()),
+            }
+        }
+
+        eprintln!("Stopped serving");
+
+        Ok(ExitCode::SUCCESS)
+    }
+}
+
+/// Reads a request from `stream` and writes the response to it.
+fn respond(stream: TcpStream, sources: &mut AssetSources) -> io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+
+    let mut reader = BufReader::new(&stream).take(MAX_REQUEST_SIZE);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // The headers aren't needed, but are read so that the client doesn't
+    // see the connection reset before the response.
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 {
+        header.clear();
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let (method, target) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+    let response = match method {
+        "GET" | "HEAD" => handle(target, sources),
+        _ => Response::text("405 Method Not Allowed", "only GET and HEAD are supported"),
+    };
+
+    eprintln!("{method} {target} {}", response.status);
+
+    let mut stream = io::BufWriter::new(&stream);
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n",
+        response.status,
+        response.content_type,
+        response.body.len()
+    )?;
+    if response.status.starts_with("405") {
+        write!(stream, "Allow: GET, HEAD\r\n")?;
+    }
+    write!(stream, "\r\n")?;
+    if method != "HEAD" {
+        stream.write_all(&response.body)?;
+    }
+
+    stream.flush()
+}
+
+/// Returns the response to a request for `target`, e.g.
+/// `/assets/minecraft/textures/block/stone.png`.
+fn handle(target: &str, sources: &mut AssetSources) -> Response {
+    let path = target.split(['?', '#']).next().unwrap_or_default();
+    let Some(path) = path.strip_prefix('/') else {
+        return Response::text("400 Bad Request", "the path must start with '/'");
+    };
+    let path = util::decode_path(Path::new(path));
+
+    // Decoding never gives `..`, but it may have been given as it is.
+    if !path
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)))
+    {
+        return Response::text("400 Bad Request", "the path must not contain '.' or '..'");
+    }
+
+    let within_assets = path.strip_prefix("assets").ok();
+    if let Some(within_assets) = within_assets
+        && sources.find(within_assets).is_some()
+    {
+        return match sources.read(within_assets) {
+            Ok(Some(contents)) => Response {
+                status: "200 OK",
+                content_type: content_type(within_assets),
+                body: contents,
+            },
+            Ok(None) => Response::not_found(target),
+            Err(error) => Response::text("500 Internal Server Error", &error.to_string()),
+        };
+    }
+
+    match list(&path, within_assets, sources) {
+        Some(listing) => Response {
+            status: "200 OK",
+            content_type: "application/json",
+            body: serde_json::to_vec_pretty(&listing).unwrap_or_default(),
+        },
+        None => Response::not_found(target),
+    }
+}
+
+/// Lists the directory at `path`, or [`None`] if there is no such directory.
+///
+/// Only `assets` is served, so the root directory lists nothing else.
+fn list<'a>(
+    path: &Path,
+    within_assets: Option<&Path>,
+    sources: &'a AssetSources,
+) -> Option<Listing<'a>> {
+    let display = format!("/{}", path.display())
+        .trim_end_matches('/')
+        .to_owned()
+        + "/";
+
+    if path == Path::new("") {
+        return Some(Listing {
+            path: display,
+            entries: vec![ListingEntry::Directory { name: "assets" }],
+        });
+    }
+
+    let within_assets = within_assets?;
+    let mut entries = BTreeMap::new();
+    for file in sources.paths() {
+        let Ok(rest) = file.strip_prefix(within_assets) else {
+            continue;
+        };
+        let mut components = rest.components();
+        let Some(name) = components.next().and_then(|name| name.as_os_str().to_str()) else {
+            continue;
+        };
+
+        let entry = if components.next().is_some() {
+            ListingEntry::Directory { name }
+        } else if let Some((source, size)) = sources.find(file) {
+            ListingEntry::File { name, source, size }
+        } else {
+            continue;
+        };
+        entries.insert(name, entry);
+    }
+
+    // `assets` itself exists even if empty, but nothing else does.
+    if entries.is_empty() && within_assets != Path::new("") {
+        return None;
+    }
+
+    Some(Listing {
+        path: display,
+        entries: entries.into_values().collect(),
+    })
+}
+
+impl Response {
+    /// A response with a plain-text `message`.
+    fn text(status: &'static str, message: &str) -> Self {
+        Self {
+            status,
+            content_type: "text/plain; charset=utf-8",
+            body: format!("{message}\n").into_bytes(),
+        }
+    }
+
+    fn not_found(target: &str) -> Self {
+        Self::text("404 Not Found", &format!("nothing found at '{target}'"))
+    }
+}
+
+/// Returns the media type of the file at `path`, going by its extension.
+fn content_type(path: &Path) -> &'static str {
+    let extension = path.extension().and_then(|extension| extension.to_str());
+
+    match extension.map(str::to_ascii_lowercase).as_deref() {
+        Some("png") => "image/png",
+        Some("ogg") => "audio/ogg",
+        Some("json" | "mcmeta") => "application/json",
+        Some("txt" | "lang" | "properties" | "vsh" | "fsh" | "glsl") => "text/plain; charset=utf-8",
+        _ => "application/octet-stream",
+    }
+}
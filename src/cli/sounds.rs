@@ -0,0 +1,186 @@
+use crate::cli::error::Error;
+use crate::cli::json::Document;
+use crate::cli::{progress, source};
+use clap::{Args, ValueEnum};
+use extract_minecraft_assets::error::ExtractError;
+use extract_minecraft_assets::options::ExtractOptions;
+use extract_minecraft_assets::report::ExtractionReport;
+use extract_minecraft_assets::sounds::{self, BrokenSound, SoundEvents, SoundLinks};
+use extract_minecraft_assets::version::Version;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+/// Organizing extracted sound files by sound event, shared by the extraction
+/// subcommands which extract hashed assets.
+#[derive(Args, Clone)]
+pub struct OrganizeSoundsArgs {
+    /// Also write each sound file to `sounds-by-event/<EVENT>/<N>.ogg` in the
+    /// output directory, e.g. `sounds-by-event/entity.zombie.ambient/1.ogg`.
+    ///
+    /// The sound events are read from `minecraft/sounds.json`. With
+    /// `--organize-sounds=only`, sound files are moved there instead of also
+    /// being kept at their usual paths. Sound files which no sound event
+    /// plays are listed at the end.
+    #[arg(
+        long,
+        value_name = "MODE",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "also",
+        conflicts_with_all = ["strip_namespace", "group_by"]
+    )]
+    organize_sounds: Option<OrganizeSounds>,
+    /// Hard link sound files into `sounds-by-event` rather than copying them,
+    /// so that sound files played by several sound events take no extra
+    /// space.
+    #[arg(long, requires = "organize_sounds")]
+    hardlink_sounds: bool,
+}
+
+/// Whether organized sound files are also kept at their usual paths.
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OrganizeSounds {
+    /// Keep sound files at their usual paths too.
+    Also,
+    /// Only keep sound files within `sounds-by-event`.
+    Only,
+}
+
+impl OrganizeSoundsArgs {
+    /// Whether sound files are to be organized, so `sounds.json` is needed.
+    pub fn is_enabled(&self) -> bool {
+        self.organize_sounds.is_some()
+    }
+
+    /// Organizes the sound files extracted in `report` by the sound events of
+    /// `sound_events`, if enabled.
+    ///
+    /// Does nothing if the extraction stopped early, and only warns if no
+    /// `sounds.json` was found.
+    pub fn organize(
+        &self,
+        sound_events: Option<SoundEvents>,
+        report: &ExtractionReport,
+        options: &ExtractOptions,
+    ) -> Result<(), ExtractError> {
+        let Some(mode) = self.organize_sounds else {
+            return Ok(());
+        };
+        if report.summary.stopped() {
+            return Ok(());
+        }
+        let Some(sound_events) = sound_events else {
+            progress::message(format_args!(
+                "No '{}' found, so sound files weren't organized",
+                sounds::SOUNDS_FILE
+            ));
+
+            return Ok(());
+        };
+
+        let links = if self.hardlink_sounds {
+            SoundLinks::HardLink
+        } else {
+            SoundLinks::Copy
+        };
+        let organized =
+            sound_events.organize(report, options, links, mode == OrganizeSounds::Also)?;
+
+        progress::message(format_args!(
+            "Organized {} sound files by {} sound events into '{}'",
+            organized.files,
+            organized.events,
+            sounds::SOUNDS_BY_EVENT_DIR
+        ));
+        if !organized.missing.is_empty() {
+            progress::message(format_args!(
+                "{} sound files played by sound events weren't extracted",
+                organized.missing.len()
+            ));
+        }
+        for path in &organized.unreferenced {
+            progress::message(format_args!(
+                "Sound file '{}' isn't played by any sound event",
+                path.display()
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Args)]
+pub struct CheckSoundsSubcommand {
+    /// The directory containing the version `.jar` file and manifest.
+    ///
+    /// Can be a path to the directory, or the name of the version to be found
+    /// within `.minecraft/versions/`.
+    #[arg(value_name = "DIRECTORY or VERSION", value_parser = source::parse_version)]
+    version_dir: Version,
+    /// The path to the `.minecraft/assets/` directory to find hashed assets.
+    ///
+    /// Defaults to the default location on your OS.
+    #[arg(long = "hashed-assets", value_name = "DIRECTORY")]
+    hashed_assets_dir: Option<PathBuf>,
+}
+
+impl CheckSoundsSubcommand {
+    /// Prints the sounds of each sound event which can't be played, exiting
+    /// with 1 if there are any.
+    pub fn execute(self, json: bool) -> Result<ExitCode, Error> {
+        let hashed_assets_dir = source::hashed_assets_dir(self.hashed_assets_dir)?;
+        let index = source::version_index(&self.version_dir, &hashed_assets_dir)?;
+
+        // Older versions have `sounds.json` in their jar file instead.
+        let jar_file = self.version_dir.jar_file();
+        let sound_events = match SoundEvents::from_index(&hashed_assets_dir, &index)? {
+            Some(sound_events) => Some(sound_events),
+            None if jar_file.is_file() => SoundEvents::from_jar(&jar_file)?,
+            None => None,
+        };
+        let Some(sound_events) = sound_events else {
+            return Err(Error::NoSoundEvents {
+                version: self.version_dir.name().to_owned(),
+            });
+        };
+
+        let check = sound_events.check_index(&hashed_assets_dir, &index);
+        let exit_code = if check.is_ok() {
+            ExitCode::SUCCESS
+        } else {
+            ExitCode::FAILURE
+        };
+
+        eprintln!(
+            "Checked {} sound files of {} sound events: {} with broken sounds",
+            check.files,
+            check.events,
+            check.broken.len()
+        );
+
+        if json {
+            Document::new(&check).print()?;
+
+            return Ok(exit_code);
+        }
+
+        for (event, broken) in &check.broken {
+            println!("{event}");
+            for sound in broken {
+                match sound {
+                    BrokenSound::NotInIndex { path } => {
+                        println!("  not in index    {}", path.display());
+                    }
+                    BrokenSound::MissingObject { path, hash } => {
+                        println!("  missing object  {} ({hash})", path.display());
+                    }
+                    BrokenSound::UnknownEvent { event } => {
+                        println!("  unknown event   {event}");
+                    }
+                }
+            }
+        }
+
+        Ok(exit_code)
+    }
+}
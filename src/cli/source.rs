@@ -0,0 +1,187 @@
+use crate::cli::error::Error;
+use clap::Args;
+use extract_minecraft_assets::error::ExtractError;
+use extract_minecraft_assets::hashed::{self, IndexFile, IndexFileLocation};
+use extract_minecraft_assets::jar::{ContentsArgs, ExtractedContents};
+use extract_minecraft_assets::util;
+use extract_minecraft_assets::version::{InvalidVersion, Version};
+use std::fmt::{self, Display, Formatter};
+use std::path::{Path, PathBuf};
+
+/// A version, jar file or index file to analyse without extracting.
+#[derive(Clone)]
+pub enum Source {
+    Jar(PathBuf),
+    Version(Version),
+    Index(IndexFileLocation),
+}
+
+impl Source {
+    /// Parses `input` into a [`Source`]: a jar file if it ends in `.jar` or
+    /// `.zip`, an index file if it ends in `.json`, or otherwise a version if
+    /// there is one, or else the name of an index file.
+    pub fn parse(input: &str) -> Result<Self, ExtractError> {
+        let extension = Path::new(input)
+            .extension()
+            .and_then(|extension| extension.to_str());
+
+        if let Some("jar" | "zip") = extension {
+            return Ok(Self::Jar(PathBuf::from(input)));
+        }
+        if extension != Some("json")
+            && let Ok(version) = Version::resolve(input)
+        {
+            return Ok(Self::Version(version));
+        }
+
+        IndexFileLocation::parse(input).map(Self::Index)
+    }
+
+    /// The jar file of the source, if any.
+    pub fn jar_file(&self) -> Option<PathBuf> {
+        match self {
+            Self::Jar(jar_file) => Some(jar_file.clone()),
+            Self::Version(version) => Some(version.jar_file()),
+            Self::Index(_) => None,
+        }
+    }
+
+    /// Reads the index file of the source from `hashed_assets_dir`, if any.
+    ///
+    /// A version's index file is only included if `include_assets`.
+    pub fn index(
+        &self,
+        hashed_assets_dir: &Path,
+        include_assets: bool,
+    ) -> Result<Option<IndexFile>, ExtractError> {
+        match self {
+            Self::Jar(_) => Ok(None),
+            Self::Version(_) if !include_assets => Ok(None),
+            Self::Version(version) => version_index(version, hashed_assets_dir).map(Some),
+            Self::Index(location) => {
+                IndexFile::from_path(&location.resolve(hashed_assets_dir)?).map(Some)
+            }
+        }
+    }
+}
+
+/// Resolves a version given as an argument, like [`Version::resolve`].
+pub fn parse_version(input: &str) -> Result<Version, InvalidVersionArg> {
+    Version::resolve(input).map_err(InvalidVersionArg)
+}
+
+/// A version given as an argument which couldn't be resolved.
+///
+/// It only explains why the version is invalid and what to do about it, as
+/// clap already names the argument and its value.
+#[derive(Debug)]
+pub struct InvalidVersionArg(InvalidVersion);
+
+impl Display for InvalidVersionArg {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0.explanation())
+    }
+}
+
+impl std::error::Error for InvalidVersionArg {}
+
+/// Returns the `.minecraft/assets/` directory to find hashed assets in:
+/// `given` if it is given, or else the default location on this OS.
+///
+/// Fails with the paths probed if the directory doesn't exist, so that
+/// subcommands only look for it when they need hashed assets.
+pub fn hashed_assets_dir(given: Option<PathBuf>) -> Result<PathBuf, Error> {
+    let probed: Vec<_> = match given {
+        Some(given) => vec![given],
+        None => util::minecraft_dir_candidates()
+            .into_iter()
+            .map(|dir| dir.join("assets"))
+            .collect(),
+    };
+
+    match probed.iter().find(|path| util::is_dir(path)) {
+        Some(path) => Ok(path.clone()),
+        None => Err(Error::NoHashedAssets { probed }),
+    }
+}
+
+/// Reads the index file of `version` from `hashed_assets_dir`, suggesting
+/// similarly named index files if it doesn't exist.
+pub fn version_index(
+    version: &Version,
+    hashed_assets_dir: &Path,
+) -> Result<IndexFile, ExtractError> {
+    let index_file = version.index_file(hashed_assets_dir)?;
+    if !index_file.is_file() {
+        return Err(hashed::missing_index(hashed_assets_dir, index_file));
+    }
+
+    IndexFile::from_path(&index_file)
+}
+
+/// The arguments of the subcommands which analyse a [`Source`].
+#[derive(Args)]
+pub struct SourceArgs {
+    /// The version, jar file or index file.
+    ///
+    /// Paths ending in `.jar` or `.zip` are jar files, and paths ending in
+    /// `.json` are index files. Otherwise, this is the name or directory of a
+    /// version if there is one, or else the name of an index file. A version
+    /// includes both its jar file and its index file.
+    ///
+    /// Example: `1.20.1`, `client.jar` or `24`
+    #[arg(value_name = "VERSION, JAR or INDEX", value_parser = Source::parse)]
+    source: Source,
+    /// The path to the `.minecraft/assets/` directory to find index files.
+    ///
+    /// Defaults to the default location on your OS.
+    #[arg(long = "hashed-assets", value_name = "DIRECTORY")]
+    hashed_assets_dir: Option<PathBuf>,
+    /// Include the `assets` folder of the jar file.
+    ///
+    /// Both `assets` and `data` are included unless either is given.
+    #[arg(short, long)]
+    assets: bool,
+    /// Include the `data` folder of the jar file.
+    ///
+    /// Both `assets` and `data` are included unless either is given.
+    #[arg(short, long)]
+    data: bool,
+}
+
+impl SourceArgs {
+    /// The contents of the jar file to include.
+    pub fn contents(&self) -> ExtractedContents {
+        if self.assets || self.data {
+            ExtractedContents::from(ContentsArgs {
+                assets: self.assets,
+                data: self.data,
+            })
+        } else {
+            ExtractedContents::all()
+        }
+    }
+
+    /// The jar file of the source, if any.
+    pub fn jar_file(&self) -> Option<PathBuf> {
+        self.source.jar_file()
+    }
+
+    /// The `.minecraft/assets/` directory to find index files in.
+    pub fn hashed_assets_dir(&self) -> PathBuf {
+        self.hashed_assets_dir
+            .clone()
+            .or_else(util::hashed_assets_dir)
+            .unwrap_or_default()
+    }
+
+    /// Reads the index file of the source, if any.
+    ///
+    /// A version's index file is only included along with its `assets`.
+    pub fn index(&self) -> Result<Option<IndexFile>, ExtractError> {
+        self.source.index(
+            &self.hashed_assets_dir(),
+            self.contents().contains("assets"),
+        )
+    }
+}
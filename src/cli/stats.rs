@@ -0,0 +1,118 @@
+use crate::cli::error::Error;
+use crate::cli::json::Document;
+use crate::cli::source::SourceArgs;
+use clap::Args;
+use extract_minecraft_assets::stats::{Stats, Total};
+use extract_minecraft_assets::util;
+
+#[derive(Args)]
+pub struct StatsSubcommand {
+    #[command(flatten)]
+    source: SourceArgs,
+    /// Print comma-separated values with the columns `group`, `name`,
+    /// `files` and `bytes`, rather than a table.
+    #[arg(long)]
+    csv: bool,
+}
+
+impl StatsSubcommand {
+    /// Prints the stats of the source.
+    pub fn execute(self, json: bool) -> Result<(), Error> {
+        let mut stats = Stats::default();
+        if let Some(jar_file) = self.source.jar_file() {
+            stats = stats + Stats::of_jar(&jar_file, &self.source.contents())?;
+        }
+        if let Some(index) = self.source.index()? {
+            stats = stats + Stats::of_index(&index);
+        }
+
+        if json {
+            Document::new(&stats).print()?;
+        } else if self.csv {
+            print_csv(&stats);
+        } else {
+            print_table(&stats);
+        }
+
+        Ok(())
+    }
+}
+
+/// A group of rows of the stats, e.g. the namespaces.
+struct Group {
+    /// The name of the group in CSV, e.g. `namespace`.
+    name: &'static str,
+    /// The heading of the group in a table, e.g. `Namespace`.
+    heading: &'static str,
+    rows: Vec<(String, Total)>,
+}
+
+/// Returns the groups of the stats, with files without a namespace named
+/// `(none)`.
+fn groups(stats: &Stats) -> [Group; 2] {
+    let namespaces = stats
+        .namespaces
+        .iter()
+        .map(|(namespace, total)| {
+            let name = if namespace.is_empty() {
+                "(none)"
+            } else {
+                namespace
+            };
+            (name.to_owned(), *total)
+        })
+        .collect();
+    let categories = stats
+        .categories
+        .iter()
+        .map(|(category, total)| (category.to_string(), *total))
+        .collect();
+
+    [
+        Group {
+            name: "namespace",
+            heading: "Namespace",
+            rows: namespaces,
+        },
+        Group {
+            name: "category",
+            heading: "Category",
+            rows: categories,
+        },
+    ]
+}
+
+/// Prints the stats as aligned tables, the largest groups first.
+fn print_table(stats: &Stats) {
+    for mut group in groups(stats) {
+        group
+            .rows
+            .sort_by(|(a_name, a), (b_name, b)| b.bytes.cmp(&a.bytes).then(a_name.cmp(b_name)));
+
+        println!("{:<20} {:>8} {:>12}", group.heading, "Files", "Size");
+        for (name, total) in group.rows {
+            print_row(&name, total);
+        }
+        println!();
+    }
+
+    print_row("Total", stats.total);
+}
+
+/// Prints a row of the table.
+fn print_row(name: &str, Total { files, bytes }: Total) {
+    println!("{name:<20} {files:>8} {:>12}", util::format_size(bytes));
+}
+
+/// Prints the stats as comma-separated values, with a header row.
+fn print_csv(stats: &Stats) {
+    println!("group,name,files,bytes");
+    for group in groups(stats) {
+        for (name, Total { files, bytes }) in group.rows {
+            println!("{},{name},{files},{bytes}", group.name);
+        }
+    }
+
+    let Total { files, bytes } = stats.total;
+    println!("total,,{files},{bytes}");
+}
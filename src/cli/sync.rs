@@ -0,0 +1,88 @@
+//! Deleting the files which the source no longer has after extracting, with
+//! `--sync`.
+
+use crate::cli::error::Error;
+use clap::Args;
+use extract_minecraft_assets::report::ExtractionReport;
+use extract_minecraft_assets::search::FileSource;
+use extract_minecraft_assets::sync;
+
+/// Mirroring the output directory to the source, shared by the extraction
+/// subcommands.
+#[derive(Args)]
+pub struct SyncArgs {
+    /// After extracting, delete the files in the directories extracted into
+    /// (e.g. `assets` and `data`) which the source no longer has, along with
+    /// the directories left empty.
+    ///
+    /// The files each synced extraction writes are recorded in
+    /// `.extract-files` in the output directory, and only the files recorded
+    /// there are deleted, so files you added are kept. Nothing outside of the
+    /// directories extracted into is ever deleted, and nothing at all if the
+    /// extraction stopped early. Only `hashed`, `jar` and `version` without
+    /// --watch can be synced, as the other subcommands don't extract their
+    /// whole source, nor can extractions left out of by --include or
+    /// --exclude.
+    #[arg(long, conflicts_with_all = ["include", "exclude"], global = true)]
+    sync: bool,
+    /// Also delete the files which no synced extraction wrote, e.g. ones you
+    /// added.
+    #[arg(
+        long,
+        requires = "sync",
+        conflicts_with = "ignore_top_level",
+        global = true
+    )]
+    sync_delete_unknown: bool,
+    /// List the files --sync would delete, without deleting them.
+    #[arg(long, requires = "sync", global = true)]
+    sync_dry_run: bool,
+}
+
+impl SyncArgs {
+    /// Whether --sync is given.
+    pub fn is_enabled(&self) -> bool {
+        self.sync
+    }
+
+    /// Deletes the files in the output directory of `report` which the
+    /// `sources` it covers no longer have, counting them in its summary, or
+    /// only lists them with --sync-dry-run or if `dry_run`.
+    ///
+    /// Does nothing unless --sync is given.
+    pub fn finish(
+        &self,
+        report: &mut ExtractionReport,
+        sources: &[FileSource],
+        dry_run: bool,
+    ) -> Result<(), Error> {
+        if !self.sync {
+            return Ok(());
+        }
+
+        let plan = sync::plan(report, sources)?;
+        if !self.sync_delete_unknown && !plan.unknown.is_empty() {
+            eprintln!(
+                "Keeping {} {} which no synced extraction wrote; pass --sync-delete-unknown to \
+                 delete them",
+                plan.unknown.len(),
+                if plan.unknown.len() == 1 {
+                    "file"
+                } else {
+                    "files"
+                }
+            );
+        }
+
+        if self.sync_dry_run || dry_run {
+            for path in plan.deleted(self.sync_delete_unknown) {
+                eprintln!("Would delete '{}'", path.display());
+            }
+        } else {
+            report.summary.deleted =
+                plan.apply(&report.options.output_dir, self.sync_delete_unknown)?;
+        }
+
+        Ok(())
+    }
+}
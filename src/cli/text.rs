@@ -0,0 +1,22 @@
+//! The line endings to which text files are normalized.
+
+use clap::ValueEnum;
+use extract_minecraft_assets::text::LineEnding;
+
+/// The line endings to which to normalize text files while extracting.
+#[derive(Clone, Copy, ValueEnum)]
+pub enum TextNormalize {
+    /// End each line with `\n`, as on Linux and macOS.
+    Lf,
+    /// End each line with `\r\n`, as on Windows.
+    Crlf,
+}
+
+impl From<TextNormalize> for LineEnding {
+    fn from(line_ending: TextNormalize) -> Self {
+        match line_ending {
+            TextNormalize::Lf => Self::Lf,
+            TextNormalize::Crlf => Self::Crlf,
+        }
+    }
+}
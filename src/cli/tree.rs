@@ -0,0 +1,82 @@
+use crate::cli::du::Extraction;
+use crate::cli::error::Error;
+use crate::cli::json::Document;
+use clap::Args;
+use extract_minecraft_assets::options::ExtractOptions;
+use extract_minecraft_assets::usage::TreeNode;
+use extract_minecraft_assets::util;
+
+#[derive(Args)]
+pub struct TreeSubcommand {
+    #[command(subcommand)]
+    extraction: Extraction,
+    /// Only show files and directories at most this many levels deep within
+    /// the output directory.
+    ///
+    /// The directories at the last level are shown with the number of files
+    /// within them and their total size.
+    #[arg(long, value_name = "DEPTH", global = true)]
+    max_depth: Option<usize>,
+}
+
+impl TreeSubcommand {
+    /// Prints the layout the extraction with `options` would create.
+    pub fn execute(self, options: &ExtractOptions, json: bool) -> Result<(), Error> {
+        let tree = self.extraction.estimate(options)?.tree(self.max_depth);
+
+        if json {
+            Document::new(&tree).print()?;
+        } else {
+            println!("{}", options.output_dir.display());
+            let dirs = print_children(&tree, "");
+
+            println!();
+            println!(
+                "{dirs} {}, {} {}, {}",
+                if dirs == 1 {
+                    "directory"
+                } else {
+                    "directories"
+                },
+                tree.files,
+                if tree.files == 1 { "file" } else { "files" },
+                util::format_size(tree.bytes)
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// Prints the children of `node` as the branches of a tree, each line
+/// starting with `prefix`, and returns the number of directories printed.
+fn print_children(node: &TreeNode, prefix: &str) -> usize {
+    let mut dirs = 0;
+
+    for (i, child) in node.children.iter().enumerate() {
+        let last = i + 1 == node.children.len();
+        let (branch, indent) = if last {
+            ("└── ", "    ")
+        } else {
+            ("├── ", "│   ")
+        };
+
+        if child.is_dir && child.children.is_empty() {
+            println!(
+                "{prefix}{branch}{} ({} {}, {})",
+                child.name,
+                child.files,
+                if child.files == 1 { "file" } else { "files" },
+                util::format_size(child.bytes)
+            );
+        } else {
+            println!("{prefix}{branch}{}", child.name);
+        }
+
+        if child.is_dir {
+            dirs += 1 + print_children(child, &format!("{prefix}{indent}"));
+        }
+    }
+
+    dirs
+}
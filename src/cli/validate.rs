@@ -0,0 +1,28 @@
+//! The formats of the files checked while extracting.
+
+use clap::ValueEnum;
+use extract_minecraft_assets::validate::ContentFormat;
+
+/// A format of the files to check while extracting.
+#[derive(Clone, Copy, ValueEnum)]
+pub enum ValidateContent {
+    /// `.png` images: the PNG signature and the `IHDR` chunk.
+    Png,
+    /// `.ogg` sounds: the Ogg capture pattern.
+    Ogg,
+    /// `.json` and `.mcmeta` files: that they parse as JSON.
+    Json,
+    /// `.nbt` structures: the gzip magic number.
+    Nbt,
+}
+
+impl From<ValidateContent> for ContentFormat {
+    fn from(format: ValidateContent) -> Self {
+        match format {
+            ValidateContent::Png => Self::Png,
+            ValidateContent::Ogg => Self::Ogg,
+            ValidateContent::Json => Self::Json,
+            ValidateContent::Nbt => Self::Nbt,
+        }
+    }
+}
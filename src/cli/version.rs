@@ -0,0 +1,395 @@
+use crate::cli::closure::{self, ClosureArgs};
+use crate::cli::error::Error;
+#[cfg(feature = "git")]
+use crate::cli::git;
+use crate::cli::namespace::{self, NamespaceArgs};
+use crate::cli::progress::TerminalProgress;
+use crate::cli::redistributable::{self, RedistributableArgs};
+use crate::cli::sounds::OrganizeSoundsArgs;
+use crate::cli::source;
+use crate::{ExtractCmd, Options};
+use clap::Args;
+use extract_minecraft_assets::closure::{AssetSources, Closure};
+use extract_minecraft_assets::hashed::{IndexFile, Object};
+use extract_minecraft_assets::jar::EntryInfo;
+use extract_minecraft_assets::options::ExtractOptions;
+use extract_minecraft_assets::redistributable::Redistributable;
+use extract_minecraft_assets::report::{ExtractionReport, FileWarning};
+use extract_minecraft_assets::search::FileSource;
+use extract_minecraft_assets::sounds::SoundEvents;
+use extract_minecraft_assets::usage::UsageEstimate;
+use extract_minecraft_assets::version::Version;
+use extract_minecraft_assets::{hashed, jar, util};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+#[derive(Args, Clone)]
+pub struct VersionSubcommand {
+    /// The directory containing the version `.jar` file and manifest.
+    ///
+    /// Can be a path to the directory, or the name of the version to be found
+    /// within `.minecraft/versions/`.
+    ///
+    /// The name of the `.jar` and `.json` manifest file inside must match the
+    /// directory name.
+    ///
+    /// Example: `1.20.1` or `.minecraft/versions/1.20.1`
+    #[arg(value_name = "DIRECTORY or VERSION", value_parser = source::parse_version)]
+    version_dir: Version,
+    /// The path to the `.minecraft/assets/` directory to find hashed assets.
+    ///
+    /// Defaults to the default location on your OS.
+    #[arg(long = "hashed-assets", value_name = "DIRECTORY")]
+    hashed_assets_dir: Option<PathBuf>,
+
+    /// Which contents to extract.
+    #[command(flatten)]
+    contents: jar::ContentsArgs,
+    /// Which references to follow, across both the jar file and the hashed
+    /// assets.
+    #[command(flatten)]
+    closure: ClosureArgs,
+    /// How to organize sound files.
+    #[command(flatten)]
+    sounds: OrganizeSoundsArgs,
+    /// Whether to leave out files which can't be redistributed, from both the
+    /// jar file and the hashed assets.
+    #[command(flatten)]
+    redistributable: RedistributableArgs,
+    /// Which namespaces to extract, from both the jar file and the hashed
+    /// assets.
+    #[command(flatten)]
+    namespaces: NamespaceArgs,
+    /// After extracting, watch the version's jar file, manifest and index
+    /// file, and extract again whenever they change.
+    ///
+    /// Only the files which changed since the last extraction are extracted
+    /// again, and the rest are counted as unchanged. Runs until interrupted
+    /// with Ctrl-C.
+    #[cfg(feature = "watch")]
+    #[arg(long)]
+    pub watch: bool,
+
+    /// The files as of the last extraction which had no failures, shared
+    /// between the clones of a watched subcommand.
+    #[arg(skip)]
+    baseline: Arc<Mutex<Option<Baseline>>>,
+}
+
+/// The files of a version as of an extraction, so that only the files which
+/// changed since are extracted again.
+struct Baseline {
+    jar: BTreeMap<PathBuf, EntryInfo>,
+    objects: HashMap<PathBuf, Object>,
+}
+
+impl VersionSubcommand {
+    /// Returns the files which a watched extraction depends on: the version's
+    /// jar file and manifest, and its index file if `assets` are extracted and
+    /// it can be found.
+    #[cfg(feature = "watch")]
+    pub fn watched_files(&self) -> Vec<PathBuf> {
+        let mut files = vec![
+            self.version_dir.jar_file(),
+            self.version_dir.manifest_file(),
+        ];
+
+        if jar::ExtractedContents::from(self.contents.clone()).contains("assets")
+            && let Ok(hashed_assets_dir) = source::hashed_assets_dir(self.hashed_assets_dir.clone())
+            && let Ok(index_file) = self.version_dir.index_file(&hashed_assets_dir)
+        {
+            files.push(index_file);
+        }
+
+        files
+    }
+}
+
+/// The files which extracting a version writes, selected the same way
+/// whether they are extracted or only estimated.
+struct Selection {
+    /// The options to extract with, with the version's contents.
+    options: ExtractOptions,
+    hashed_assets_dir: PathBuf,
+    jar: PathBuf,
+    /// Every entry of the jar file within the contents.
+    jar_files: BTreeMap<PathBuf, EntryInfo>,
+    /// Every object listed in the index file, if `assets` are extracted.
+    objects: Option<HashMap<PathBuf, Object>>,
+    /// The entries of the jar file to extract.
+    jar_paths: HashSet<PathBuf>,
+    /// The hashed assets to extract, if `assets` are extracted.
+    index: Option<IndexFile>,
+    closure: Option<Closure>,
+    sound_events: Option<SoundEvents>,
+    namespace_warnings: Vec<FileWarning>,
+    redistributable: Option<Redistributable>,
+    /// The paths of the files left out as they can't be redistributed,
+    /// relative to the jar's top-level directory.
+    left_out: Vec<PathBuf>,
+}
+
+impl VersionSubcommand {
+    /// Selects the files which extracting with `options` would write, or
+    /// returns `None` if no contents are extracted.
+    ///
+    /// With a closure, only its files are selected, and then only those in
+    /// the namespaces, matching the patterns, and redistributable if asked.
+    fn select(&self, options: &ExtractOptions) -> Result<Option<Selection>, Error> {
+        let extracted_contents = jar::ExtractedContents::from(self.contents.clone());
+
+        if extracted_contents.is_empty() {
+            return Ok(None);
+        }
+
+        // Hashed assets are only needed to extract `assets`.
+        let (hashed_assets_dir, mut index) = if extracted_contents.contains("assets") {
+            let hashed_assets_dir = source::hashed_assets_dir(self.hashed_assets_dir.clone())?;
+            let index = source::version_index(&self.version_dir, &hashed_assets_dir)?;
+
+            (hashed_assets_dir, Some(index))
+        } else {
+            (PathBuf::new(), None)
+        };
+        let jar = self.version_dir.jar_file();
+        let jar_files = jar::list_entries(&jar, &extracted_contents)?;
+        let objects = index.as_ref().map(|index| index.objects.clone());
+
+        let closure = match &mut index {
+            Some(index) if self.closure.is_enabled() => {
+                let mut sources = AssetSources::new()
+                    .jar(&jar)?
+                    .index(&hashed_assets_dir, index.clone());
+                let closure = self.closure.resolve(&mut sources)?;
+
+                if let Some(closure) = &closure {
+                    let hashed_files: HashSet<_> = closure::files_in(closure, FileSource::Hashed)
+                        .map(|file| file.path.as_path())
+                        .collect();
+                    *index = index.filtered(|path| hashed_files.contains(path));
+                }
+
+                closure
+            }
+            _ => None,
+        };
+        let closure_jar_files = closure.as_ref().map(closure::jar_files);
+        let sound_events = match &index {
+            Some(index) if self.sounds.is_enabled() => {
+                match SoundEvents::from_index(&hashed_assets_dir, index)? {
+                    Some(sound_events) => Some(sound_events),
+                    None => SoundEvents::from_jar(&jar)?,
+                }
+            }
+            _ => None,
+        };
+        let namespace_warnings = self.namespaces.unmatched_warnings(
+            namespace::warnings_dir(&extracted_contents),
+            jar_files.keys().map(PathBuf::as_path),
+            objects
+                .iter()
+                .flat_map(|objects| objects.keys())
+                .map(PathBuf::as_path),
+        );
+        if let Some(index) = &mut index {
+            *index = index.filtered(|path| self.namespaces.allows_index_path(path));
+            *index = options.included_objects(index).into_owned();
+        }
+
+        let mut jar_paths: HashSet<_> = jar_files
+            .keys()
+            .filter(|path| {
+                closure_jar_files
+                    .as_ref()
+                    .is_none_or(|files| files.contains(*path))
+                    && self.namespaces.allows_jar_path(path)
+                    && options.includes(path)
+            })
+            .cloned()
+            .collect();
+
+        let redistributable = self.redistributable.filter();
+        let mut left_out = Vec::new();
+        if let Some(redistributable) = &redistributable {
+            let assets_path = |path: &Path| Path::new("assets").join(path);
+
+            jar_paths.retain(|path| {
+                let allowed = redistributable.allows(path);
+                if !allowed {
+                    left_out.push(path.clone());
+                }
+                allowed
+            });
+            if let Some(index) = &mut index {
+                left_out.extend(
+                    index
+                        .objects
+                        .keys()
+                        .map(|path| assets_path(path))
+                        .filter(|path| !redistributable.allows(path)),
+                );
+                *index = index.filtered(|path| redistributable.allows(&assets_path(path)));
+            }
+        }
+
+        Ok(Some(Selection {
+            options: options.clone().contents(extracted_contents),
+            hashed_assets_dir,
+            jar,
+            jar_files,
+            objects,
+            jar_paths,
+            index,
+            closure,
+            sound_events,
+            namespace_warnings,
+            redistributable,
+            left_out,
+        }))
+    }
+
+    /// Estimates the files which extracting with `options` would write,
+    /// without extracting anything.
+    pub fn estimate(&self, options: &ExtractOptions) -> Result<UsageEstimate, Error> {
+        let mut estimate = UsageEstimate::new();
+        let Some(selection) = self.select(options)? else {
+            return Ok(estimate);
+        };
+
+        estimate.add_jar(&selection.jar, &selection.options, |path| {
+            selection.jar_paths.contains(path)
+        })?;
+        if let Some(index) = &selection.index {
+            estimate.add_index(index, &selection.options);
+        }
+
+        Ok(estimate)
+    }
+}
+
+impl ExtractCmd for VersionSubcommand {
+    fn source_dirs(&self) -> Vec<PathBuf> {
+        let hashed_assets_dir = self
+            .hashed_assets_dir
+            .clone()
+            .or_else(util::hashed_assets_dir);
+
+        [Some(self.version_dir.path().to_owned()), hashed_assets_dir]
+            .into_iter()
+            .flatten()
+            .collect()
+    }
+
+    fn execute(self, options: &Options) -> Result<ExtractionReport, Error> {
+        let Some(selection) = self.select(&options.extract)? else {
+            return Ok(ExtractionReport::new(options.extract.clone()));
+        };
+        let Selection {
+            options: extract_options,
+            hashed_assets_dir,
+            jar,
+            jar_files,
+            objects,
+            jar_paths,
+            mut index,
+            closure,
+            sound_events,
+            namespace_warnings,
+            redistributable,
+            left_out,
+        } = selection;
+        let baseline = self.baseline.lock().unwrap().take();
+
+        let changed = |path: &Path| {
+            baseline
+                .as_ref()
+                .is_none_or(|baseline| baseline.jar.get(path) != jar_files.get(path))
+        };
+
+        if let Some(redistributable) = &redistributable {
+            redistributable::print_notice(redistributable, left_out.iter().map(PathBuf::as_path));
+        }
+
+        // Files which haven't changed since the last extraction are left out.
+        let mut unchanged = jar_paths.iter().filter(|path| !changed(path)).count();
+        if let Some((index, baseline)) = index.as_mut().zip(baseline.as_ref()) {
+            let len = index.len();
+            *index = index.filtered(|path| baseline.objects.get(path) != index.objects.get(path));
+            unchanged += len - index.len();
+        }
+
+        let (jar_size, jar_entries) = jar_files
+            .iter()
+            .filter(|(path, _)| jar_paths.contains(*path) && changed(path))
+            .fold((0, 0), |(size, count), (_, info)| {
+                (size + info.size, count + 1)
+            });
+        let hashed_size = index.as_ref().map_or(0, IndexFile::total_size);
+        options.confirm_size(jar_size + hashed_size)?;
+
+        // Compute the totals of every phase up front so the overall progress is known.
+        let mut progress = TerminalProgress::new(options.porcelain);
+        progress.add_phase(format!("jar {}", extract_options.contents), jar_entries);
+        if let Some(index) = &index {
+            progress.add_phase("hashed assets", index.len());
+        }
+
+        eprintln!(
+            "Extracting {} from {}...",
+            &extract_options.contents,
+            jar.display()
+        );
+
+        let mut report =
+            jar::extract_jar_filtered(&jar, &extract_options, &mut progress, |path| {
+                jar_paths.contains(path) && changed(path)
+            })?;
+
+        // The hashed assets are left for later if the output volume is full.
+        if let Some(index) = &index
+            && report.summary.disk_full
+        {
+            report.summary.remaining_bytes += index.total_size();
+        } else if let Some(index) = index {
+            report = report
+                + hashed::extract_hashed_assets(
+                    &hashed_assets_dir,
+                    &index,
+                    &extract_options,
+                    &mut progress,
+                )?;
+        }
+        report.summary.unchanged = unchanged;
+        report.warnings.extend(namespace_warnings);
+
+        // Files which failed are extracted again, along with everything
+        // else which changed since the last extraction without failures.
+        let finished = !report.summary.stopped() && report.summary.failures == 0;
+        *self.baseline.lock().unwrap() = if finished {
+            Some(Baseline {
+                jar: jar_files,
+                objects: objects.unwrap_or_default(),
+            })
+        } else {
+            baseline
+        };
+
+        progress.clear()?;
+        eprintln!("{progress}");
+
+        if let Some(closure) = &closure {
+            closure::print_problems(closure);
+        }
+
+        self.sounds
+            .organize(sound_events, &report, &extract_options)?;
+
+        Ok(report)
+    }
+
+    #[cfg(feature = "git")]
+    fn git_source(&self) -> git::Source {
+        git::Source::version(&self.version_dir)
+    }
+}
@@ -0,0 +1,160 @@
+//! Extracting again whenever the files being extracted from change, with
+//! `--watch`.
+
+use extract_minecraft_assets::options::CancellationToken;
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use std::collections::BTreeSet;
+use std::io;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// How long to wait after a change for more changes before extracting, as
+/// the launcher writes several files when it updates a version.
+const DEBOUNCE: Duration = Duration::from_millis(500);
+/// How often to check whether watching was interrupted.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Runs `extract`, then runs it again whenever any of the `files` it returns
+/// change, until interrupted through `cancellation`.
+///
+/// `files` is called again before each wait, as a change (e.g. of a version's
+/// manifest) can change which files are extracted from. Returns the exit code
+/// of the last extraction.
+pub fn watch(
+    files: impl Fn() -> Vec<PathBuf>,
+    cancellation: &CancellationToken,
+    mut extract: impl FnMut() -> io::Result<ExitCode>,
+) -> io::Result<ExitCode> {
+    let mut exit_code = extract()?;
+
+    while !cancellation.is_cancelled() {
+        let files = files();
+        let names: Vec<_> = files
+            .iter()
+            .map(|file| format!("'{}'", file.display()))
+            .collect();
+        eprintln!(
+            "[{}] Watching {} for changes, press Ctrl-C to stop",
+            timestamp(),
+            names.join(", ")
+        );
+
+        let Some(changed) = wait_for_change(&files, cancellation)? else {
+            break;
+        };
+
+        eprintln!(
+            "[{}] '{}' changed, extracting again",
+            timestamp(),
+            changed.display()
+        );
+        exit_code = extract()?;
+    }
+
+    Ok(exit_code)
+}
+
+/// Waits until any of the `files` change, and no more changes follow for a
+/// moment, returning the first file which changed, or [`None`] if
+/// interrupted.
+fn wait_for_change(
+    files: &[PathBuf],
+    cancellation: &CancellationToken,
+) -> io::Result<Option<PathBuf>> {
+    let (sender, receiver) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender).map_err(io::Error::other)?;
+
+    // The launcher replaces files by moving new ones over them, which isn't
+    // seen by watching the files themselves, so their directories are
+    // watched instead.
+    let dirs: BTreeSet<_> = files.iter().filter_map(|file| file.parent()).collect();
+    for dir in dirs {
+        watcher
+            .watch(dir, RecursiveMode::NonRecursive)
+            .map_err(io::Error::other)?;
+    }
+
+    let mut changed = None;
+    loop {
+        let timeout = if changed.is_some() {
+            DEBOUNCE
+        } else {
+            POLL_INTERVAL
+        };
+
+        match receiver.recv_timeout(timeout) {
+            Ok(Ok(event)) => {
+                if changed.is_none() {
+                    changed = changed_file(&event, files);
+                }
+            }
+            Ok(Err(error)) => return Err(io::Error::other(error)),
+            Err(RecvTimeoutError::Timeout) if changed.is_some() => return Ok(changed),
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => return Ok(None),
+        }
+
+        if cancellation.is_cancelled() {
+            return Ok(None);
+        }
+    }
+}
+
+/// Returns the file among `files` which `event` changed, if any.
+///
+/// Files are matched by name, as the paths of events within a watched
+/// directory may be given differently from the paths the directory was
+/// watched by.
+fn changed_file(event: &Event, files: &[PathBuf]) -> Option<PathBuf> {
+    if matches!(event.kind, EventKind::Access(_)) {
+        return None;
+    }
+
+    files
+        .iter()
+        .find(|file| {
+            event
+                .paths
+                .iter()
+                .any(|path| path.file_name() == file.file_name())
+        })
+        .cloned()
+}
+
+/// Returns the current local time as `HH:MM:SS`, or the time in UTC on
+/// platforms where the local time isn't known.
+fn timestamp() -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+
+    #[cfg(unix)]
+    {
+        use std::mem::MaybeUninit;
+
+        let time = now as libc::time_t;
+        let mut local = MaybeUninit::<libc::tm>::uninit();
+
+        // SAFETY: `time` is a valid `time_t` and `local` is a valid pointer to a `tm`.
+        if !unsafe { libc::localtime_r(&time, local.as_mut_ptr()) }.is_null() {
+            // SAFETY: `localtime_r` succeeded, so it has initialized `local`.
+            let local = unsafe { local.assume_init() };
+
+            return format!(
+                "{:02}:{:02}:{:02}",
+                local.tm_hour, local.tm_min, local.tm_sec
+            );
+        }
+    }
+
+    let seconds = now % (24 * 60 * 60);
+    format!(
+        "{:02}:{:02}:{:02} UTC",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
@@ -0,0 +1,93 @@
+//! Listing the files written by an extraction as it proceeds, with
+//! `--print0`, `--print` or `--written-list`.
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// The list being written, if any.
+///
+/// Files are recorded from wherever they are known to be written, e.g. a
+/// progress sink, so the list is global like the status line.
+static LIST: Mutex<Option<WrittenList>> = Mutex::new(None);
+
+/// Where the paths of written files are listed.
+struct WrittenList {
+    writer: Box<dyn Write + Send>,
+    /// The byte following each path: NUL, or a newline.
+    terminator: u8,
+    /// The absolute path of the output directory, which recorded paths are
+    /// relative to.
+    output_dir: PathBuf,
+    /// The first error writing the list, reported when it is
+    /// [flushed](flush).
+    error: Option<io::Error>,
+}
+
+/// Starts listing the files written within `output_dir`, each followed by
+/// `terminator`, into `file`, or stdout if not given.
+///
+/// Paths are listed absolute and as they are, so they may contain newlines:
+/// only NUL separates them unambiguously.
+pub fn start(output_dir: &Path, terminator: u8, file: Option<&Path>) -> io::Result<()> {
+    let writer: Box<dyn Write + Send> = match file {
+        Some(path) => Box::new(BufWriter::new(File::create(path).map_err(|error| {
+            io::Error::new(
+                error.kind(),
+                format!("failed to create '{}': {error}", path.display()),
+            )
+        })?)),
+        None => Box::new(io::stdout()),
+    };
+
+    *LIST.lock().unwrap_or_else(|error| error.into_inner()) = Some(WrittenList {
+        writer,
+        terminator,
+        output_dir: std::path::absolute(output_dir)?,
+        error: None,
+    });
+
+    Ok(())
+}
+
+/// Lists the file at `path`, relative to the output directory, if a list is
+/// being written.
+pub fn record(path: &Path) {
+    let mut list = LIST.lock().unwrap_or_else(|error| error.into_inner());
+    let Some(list) = list.as_mut().filter(|list| list.error.is_none()) else {
+        return;
+    };
+
+    // Each path is flushed as it is listed, so that whatever reads the list
+    // can start on files as soon as they are written.
+    let path = list.output_dir.join(path);
+    let result = list
+        .writer
+        .write_all(path.as_os_str().as_encoded_bytes())
+        .and_then(|()| list.writer.write_all(&[list.terminator]))
+        .and_then(|()| list.writer.flush());
+
+    // Nothing more is listed until the error is reported, as the list would
+    // be missing the path.
+    if let Err(error) = result {
+        list.error = Some(error);
+    }
+}
+
+/// Flushes the list, if one is being written, returning the first error
+/// writing it since it was last flushed.
+pub fn flush() -> io::Result<()> {
+    let mut list = LIST.lock().unwrap_or_else(|error| error.into_inner());
+    let Some(list) = list.as_mut() else {
+        return Ok(());
+    };
+
+    match list.error.take() {
+        Some(error) => Err(io::Error::new(
+            error.kind(),
+            format!("failed to list the written files: {error}"),
+        )),
+        None => list.writer.flush(),
+    }
+}
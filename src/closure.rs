@@ -0,0 +1,290 @@
+//! Finding the closure of the files referenced by some starting files, e.g.
+//! every texture and glyph file used by the fonts, across both a jar file and
+//! an index file.
+//!
+//! What a file references is decided by the caller, so the same resolution
+//! serves any kind of asset: see [`fonts`](crate::fonts) for an example.
+
+use crate::error::ExtractError;
+use crate::hashed::{self, IndexFile};
+use crate::jar::{self, ExtractedContents};
+use crate::locked::LockedRetry;
+use crate::search::FileSource;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use zip::ZipArchive;
+
+/// The assets of a jar file and an index file, found by their paths within
+/// `assets`, e.g. `minecraft/font/default.json`.
+///
+/// A file in both is taken from the index file, as hashed assets replace
+/// those of the jar file when a version is extracted.
+#[derive(Default)]
+pub struct AssetSources {
+    jar: Option<JarSource>,
+    hashed: Option<HashedSource>,
+}
+
+/// The `assets` of a jar file within [`AssetSources`].
+struct JarSource {
+    jar_file: PathBuf,
+    archive: ZipArchive<File>,
+    /// The index within the archive and the size of each file, by path.
+    files: BTreeMap<PathBuf, (usize, u64)>,
+}
+
+/// The objects of an index file within [`AssetSources`].
+struct HashedSource {
+    objects_dir: PathBuf,
+    index: IndexFile,
+}
+
+/// A file referenced by another file.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Reference {
+    /// A file which must exist, and is reported as
+    /// [dangling](Closure::dangling) otherwise.
+    Required(PathBuf),
+    /// A file which is only part of the closure if it exists, e.g. one of the
+    /// pages of a template.
+    Optional(PathBuf),
+}
+
+/// The files referenced, directly or indirectly, by some starting files.
+#[derive(Clone, PartialEq, Eq, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Closure {
+    /// Every file of the closure, including the starting files, sorted by
+    /// path.
+    pub files: Vec<ClosureFile>,
+    /// The required references to files which don't exist in either source.
+    pub dangling: Vec<DanglingReference>,
+    /// The files whose references couldn't be read, e.g. because they aren't
+    /// valid JSON.
+    pub errors: Vec<ClosureError>,
+}
+
+/// A file of a [`Closure`].
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClosureFile {
+    /// The path of the file within `assets`.
+    pub path: PathBuf,
+    pub source: FileSource,
+    /// The size of the file in bytes.
+    pub size: u64,
+}
+
+/// A reference to a file which doesn't exist.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DanglingReference {
+    /// The path of the referencing file within `assets`.
+    pub from: PathBuf,
+    /// The path of the missing file within `assets`.
+    pub path: PathBuf,
+}
+
+/// A file of a [`Closure`] whose references couldn't be read.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClosureError {
+    /// The path of the file within `assets`.
+    pub path: PathBuf,
+    /// A description of the error.
+    pub message: String,
+}
+
+impl AssetSources {
+    /// Returns sources without any files.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the `assets` of `jar_file`.
+    ///
+    /// Only the central directory is read until a file is
+    /// [read](AssetSources::read).
+    pub fn jar(mut self, jar_file: &Path) -> Result<Self, ExtractError> {
+        let (mut archive, top_level_dir) = jar::open_archive(jar_file)?;
+        let contents = ExtractedContents::assets();
+
+        let mut files = BTreeMap::new();
+        for i in 0..archive.len() {
+            let file = archive
+                .by_index_raw(i)
+                .map_err(ExtractError::zip(jar_file))?;
+
+            if file.is_dir() {
+                continue;
+            }
+
+            if let Some(path) = file.enclosed_name()
+                && let Some((path, root)) =
+                    jar::selected_path(&path, top_level_dir.as_deref(), &contents)
+                && let Ok(path) = path.strip_prefix(&root.prefix)
+            {
+                files.insert(path.to_owned(), (i, file.size()));
+            }
+        }
+
+        self.jar = Some(JarSource {
+            jar_file: jar_file.to_owned(),
+            archive,
+            files,
+        });
+
+        Ok(self)
+    }
+
+    /// Adds the objects listed in `index`, whose hashed files are read from
+    /// the `objects` directory within `hashed_assets_dir`.
+    pub fn index(mut self, hashed_assets_dir: &Path, index: IndexFile) -> Self {
+        self.hashed = Some(HashedSource {
+            objects_dir: hashed_assets_dir.join("objects"),
+            index,
+        });
+        self
+    }
+
+    /// Returns the paths of every file, sorted.
+    pub fn paths(&self) -> BTreeSet<&Path> {
+        let jar_paths = self
+            .jar
+            .iter()
+            .flat_map(|jar| jar.files.keys().map(PathBuf::as_path));
+        let hashed_paths = self
+            .hashed
+            .iter()
+            .flat_map(|hashed| hashed.index.objects.keys().map(PathBuf::as_path));
+
+        jar_paths.chain(hashed_paths).collect()
+    }
+
+    /// Returns the source and size of the file at `path`, if it exists.
+    pub fn find(&self, path: &Path) -> Option<(FileSource, u64)> {
+        let hashed = self.hashed.as_ref().and_then(|hashed| {
+            let object = hashed.index.objects.get(path)?;
+
+            Some((FileSource::Hashed, object.size))
+        });
+
+        hashed.or_else(|| {
+            let (_, size) = self.jar.as_ref()?.files.get(path)?;
+
+            Some((FileSource::Jar, *size))
+        })
+    }
+
+    /// Reads the contents of the file at `path`, if it exists.
+    pub fn read(&mut self, path: &Path) -> Result<Option<Vec<u8>>, ExtractError> {
+        if let Some(hashed) = &self.hashed
+            && let Some(object) = hashed.index.objects.get(path)
+        {
+            let object_path = hashed.objects_dir.join(object.hashed_file_path());
+
+            return hashed::read_object(path, &object_path, &LockedRetry::default()).map(Some);
+        }
+
+        let Some(jar) = &mut self.jar else {
+            return Ok(None);
+        };
+        let Some(&(index, _)) = jar.files.get(path) else {
+            return Ok(None);
+        };
+
+        let mut contents = Vec::new();
+        jar.archive
+            .by_index(index)
+            .map_err(ExtractError::zip(&jar.jar_file))?
+            .read_to_end(&mut contents)
+            .map_err(|error| ExtractError::Zip {
+                path: jar.jar_file.clone(),
+                source: error.into(),
+            })?;
+
+        Ok(Some(contents))
+    }
+}
+
+/// Returns the closure of the `roots`: the roots and every file they
+/// reference, directly or indirectly, within `sources`.
+///
+/// Only JSON files are read, and `references` is given the path and contents
+/// of each to return the files it references. Every other file is a leaf.
+/// Files which `references` fails to read are recorded in
+/// [`errors`](Closure::errors), and still part of the closure.
+pub fn resolve<E: ToString>(
+    sources: &mut AssetSources,
+    roots: impl IntoIterator<Item = PathBuf>,
+    mut references: impl FnMut(&Path, &[u8]) -> Result<Vec<Reference>, E>,
+) -> Result<Closure, ExtractError> {
+    let mut closure = Closure::default();
+    let mut visited = BTreeSet::new();
+    let mut queue: VecDeque<_> = roots.into_iter().collect();
+
+    while let Some(path) = queue.pop_front() {
+        if !visited.insert(path.clone()) {
+            continue;
+        }
+        let Some((source, size)) = sources.find(&path) else {
+            continue;
+        };
+
+        if path
+            .extension()
+            .is_some_and(|extension| extension == "json")
+        {
+            let contents = sources
+                .read(&path)?
+                .expect("files which were found can be read");
+
+            match references(&path, &contents) {
+                Ok(references) => {
+                    for reference in references {
+                        match reference {
+                            Reference::Required(referenced)
+                                if sources.find(&referenced).is_none() =>
+                            {
+                                closure.dangling.push(DanglingReference {
+                                    from: path.clone(),
+                                    path: referenced,
+                                });
+                            }
+                            Reference::Required(referenced) | Reference::Optional(referenced) => {
+                                queue.push_back(referenced);
+                            }
+                        }
+                    }
+                }
+                Err(error) => closure.errors.push(ClosureError {
+                    path: path.clone(),
+                    message: error.to_string(),
+                }),
+            }
+        }
+
+        closure.files.push(ClosureFile { path, source, size });
+    }
+
+    closure.files.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Ok(closure)
+}
+
+/// Returns the path within `assets` of the resource at `location`, within the
+/// directory `dir` of its namespace, e.g. `minecraft/font/include/space.json`
+/// for `minecraft:include/space` within `font` with the `.json` `extension`.
+///
+/// Locations without a namespace are within `minecraft`. An empty `dir` or
+/// `extension` is left out.
+pub fn resource_path(location: &str, dir: &str, extension: &str) -> PathBuf {
+    let (namespace, path) = location.split_once(':').unwrap_or(("minecraft", location));
+
+    Path::new(namespace)
+        .join(dir)
+        .join(format!("{path}{extension}"))
+}
@@ -0,0 +1,246 @@
+//! Differences between the files listed in two sources, such as two
+//! [index files](crate::hashed::IndexFile::diff) or two
+//! [jar files](crate::jar::diff_jars), and between an output directory and
+//! the source it was extracted from.
+
+use crate::error::ExtractError;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Something listed at a path in the sources of a [`Diff`], such as an
+/// [`Object`](crate::hashed::Object).
+pub trait DiffItem {
+    /// The size of the file in bytes.
+    fn size(&self) -> u64;
+}
+
+/// The differences between the files listed in two sources, `a` and `b`.
+///
+/// Paths are sorted.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Diff<T> {
+    /// The files only listed in `a`.
+    pub only_in_a: Vec<DiffEntry<T>>,
+    /// The files only listed in `b`.
+    pub only_in_b: Vec<DiffEntry<T>>,
+    /// The paths listed in both, but with different files.
+    pub changed: Vec<ChangedEntry<T>>,
+}
+
+/// A file listed in only one of the sources of a [`Diff`].
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+pub struct DiffEntry<T> {
+    /// The path of the file.
+    pub path: PathBuf,
+    #[serde(flatten)]
+    pub item: T,
+}
+
+/// A path listed in both sources of a [`Diff`] with different files.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+pub struct ChangedEntry<T> {
+    /// The path of the file.
+    pub path: PathBuf,
+    /// The file in `a`.
+    pub a: T,
+    /// The file in `b`.
+    pub b: T,
+}
+
+/// The number of files and bytes in each category of a [`Diff`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffTotals {
+    pub only_in_a: DiffTotal,
+    pub only_in_b: DiffTotal,
+    /// The change in size is from `a` to `b`.
+    pub changed: DiffTotal,
+}
+
+/// The number of files and bytes in a category of a [`Diff`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize)]
+pub struct DiffTotal {
+    pub files: usize,
+    /// The total size of the files, or the total change in size for
+    /// [changed](DiffTotals::changed) files.
+    pub bytes: i64,
+}
+
+impl<T: DiffItem + Clone> Diff<T> {
+    /// Compares the files listed in `a` and `b`, where `differs` decides
+    /// whether the files at the same path are different.
+    pub(crate) fn compare<'a>(
+        a: impl IntoIterator<Item = (&'a Path, &'a T)>,
+        b: impl IntoIterator<Item = (&'a Path, &'a T)>,
+        mut differs: impl FnMut(&Path, &T, &T) -> bool,
+    ) -> Self
+    where
+        T: 'a,
+    {
+        let a: BTreeMap<&Path, &T> = a.into_iter().collect();
+        let b: BTreeMap<&Path, &T> = b.into_iter().collect();
+        let entry = |path: &Path, item: &T| DiffEntry {
+            path: path.to_owned(),
+            item: item.clone(),
+        };
+
+        let mut diff = Self {
+            only_in_a: Vec::new(),
+            only_in_b: Vec::new(),
+            changed: Vec::new(),
+        };
+
+        for (&path, &a_item) in &a {
+            match b.get(path) {
+                None => diff.only_in_a.push(entry(path, a_item)),
+                Some(&b_item) if differs(path, a_item, b_item) => diff.changed.push(ChangedEntry {
+                    path: path.to_owned(),
+                    a: a_item.clone(),
+                    b: b_item.clone(),
+                }),
+                Some(_) => {}
+            }
+        }
+
+        for (&path, &b_item) in &b {
+            if !a.contains_key(path) {
+                diff.only_in_b.push(entry(path, b_item));
+            }
+        }
+
+        diff
+    }
+}
+
+impl<T: DiffItem> Diff<T> {
+    /// Whether both sources list the same files.
+    pub fn is_empty(&self) -> bool {
+        self.only_in_a.is_empty() && self.only_in_b.is_empty() && self.changed.is_empty()
+    }
+
+    /// Returns the number of files and bytes in each category.
+    pub fn totals(&self) -> DiffTotals {
+        let total = |entries: &[DiffEntry<T>]| DiffTotal {
+            files: entries.len(),
+            bytes: entries.iter().map(|entry| entry.item.size() as i64).sum(),
+        };
+
+        DiffTotals {
+            only_in_a: total(&self.only_in_a),
+            only_in_b: total(&self.only_in_b),
+            changed: DiffTotal {
+                files: self.changed.len(),
+                bytes: self.changed.iter().map(ChangedEntry::size_delta).sum(),
+            },
+        }
+    }
+}
+
+impl<T: DiffItem> ChangedEntry<T> {
+    /// The change in size from `a` to `b`, in bytes.
+    pub fn size_delta(&self) -> i64 {
+        self.b.size() as i64 - self.a.size() as i64
+    }
+}
+
+/// The differences between an output directory and the source it was
+/// extracted from, as returned by
+/// [`IndexFile::diff_output`](crate::hashed::IndexFile::diff_output) and
+/// [`jar::diff_output`](crate::jar::diff_output).
+///
+/// Paths are relative to the output directory, and sorted.
+#[derive(Clone, PartialEq, Eq, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputDiff {
+    /// The files whose contents differ from the source.
+    pub modified: Vec<PathBuf>,
+    /// The files in the source which are not in the output directory.
+    pub missing: Vec<PathBuf>,
+    /// The files in the output directory which are not in the source.
+    pub extra: Vec<PathBuf>,
+}
+
+impl OutputDiff {
+    /// Whether the output directory matches the source.
+    pub fn is_empty(&self) -> bool {
+        self.modified.is_empty() && self.missing.is_empty() && self.extra.is_empty()
+    }
+
+    /// Compares the files within `output_dir` with the `expected` files, by
+    /// path relative to `output_dir`, where `matches` decides whether a file
+    /// has the expected contents.
+    ///
+    /// Only files within the `searched` directories (relative to
+    /// `output_dir`) can be [extra](OutputDiff::extra).
+    pub(crate) fn compare<T>(
+        output_dir: &Path,
+        searched: &[PathBuf],
+        expected: &BTreeMap<PathBuf, T>,
+        mut matches: impl FnMut(File, &T) -> io::Result<bool>,
+    ) -> Result<Self, ExtractError> {
+        let mut files = BTreeSet::new();
+        for dir in searched {
+            find_files(output_dir, dir, &mut files)?;
+        }
+
+        let mut diff = Self::default();
+
+        for (path, item) in expected {
+            if !files.contains(path) {
+                diff.missing.push(path.clone());
+                continue;
+            }
+
+            let file_path = output_dir.join(path);
+            let file = File::open(&file_path).map_err(ExtractError::read_output(&file_path))?;
+
+            if !matches(file, item).map_err(ExtractError::read_output(&file_path))? {
+                diff.modified.push(path.clone());
+            }
+        }
+
+        diff.extra = files
+            .into_iter()
+            .filter(|path| !expected.contains_key(path))
+            .collect();
+
+        Ok(diff)
+    }
+}
+
+/// Adds the paths of the files within `dir` and its subdirectories, relative
+/// to `output_dir`, to `files`.
+///
+/// Does nothing if `dir` doesn't exist.
+pub(crate) fn find_files(
+    output_dir: &Path,
+    dir: &Path,
+    files: &mut BTreeSet<PathBuf>,
+) -> Result<(), ExtractError> {
+    let full_dir = output_dir.join(dir);
+    let entries = match fs::read_dir(&full_dir) {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(error) => return Err(ExtractError::read_output(full_dir)(error)),
+    };
+
+    for entry in entries {
+        let entry = entry.map_err(ExtractError::read_output(&full_dir))?;
+        let path = dir.join(entry.file_name());
+        let file_type = entry
+            .file_type()
+            .map_err(ExtractError::read_output(entry.path()))?;
+
+        if file_type.is_dir() {
+            find_files(output_dir, &path, files)?;
+        } else {
+            files.insert(path);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,134 @@
+//! Finding files with identical contents within jar files and index files,
+//! without extracting anything.
+
+use crate::error::ExtractError;
+use crate::hashed::IndexFile;
+use crate::jar::{ExtractedContents, JarAssets};
+use crate::util;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Groups files by their SHA-1 hashes to find [clusters](Cluster) of files
+/// with identical contents.
+///
+/// Paths are relative to the output directory, as if extracted without
+/// ignoring the top-level directory, e.g. `assets/minecraft/lang/en_us.json`.
+///
+/// # Examples
+/// ```
+/// use extract_minecraft_assets::duplicates::DuplicateFinder;
+///
+/// let mut finder = DuplicateFinder::new();
+/// finder.add("assets/minecraft/a.png".as_ref(), "aa", 300);
+/// finder.add("assets/minecraft/b.png".as_ref(), "aa", 300);
+/// finder.add("assets/minecraft/c.png".as_ref(), "cc", 300);
+///
+/// let clusters = finder.clusters(0);
+///
+/// assert_eq!(clusters.len(), 1);
+/// assert_eq!(clusters[0].paths.len(), 2);
+/// assert_eq!(clusters[0].wasted_bytes, 300);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct DuplicateFinder {
+    /// The size and paths of the files with each hash.
+    files: HashMap<String, (u64, Vec<PathBuf>)>,
+}
+
+/// Files with identical contents.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Cluster {
+    /// The SHA-1 hash of the contents.
+    pub hash: String,
+    /// The size of each file in bytes.
+    pub size: u64,
+    /// The paths of the files, sorted.
+    pub paths: Vec<PathBuf>,
+    /// The bytes taken up by every file but one.
+    pub wasted_bytes: u64,
+}
+
+impl DuplicateFinder {
+    /// Returns a finder without any files.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the file at `path` with the SHA-1 `hash` and `size` in bytes.
+    pub fn add(&mut self, path: &Path, hash: &str, size: u64) {
+        self.files
+            .entry(hash.to_owned())
+            .or_insert_with(|| (size, Vec::new()))
+            .1
+            .push(path.to_owned());
+    }
+
+    /// Adds every object listed in `index`, within `assets`.
+    pub fn add_index(&mut self, index: &IndexFile) {
+        let assets_dir = Path::new("assets");
+
+        for (path, object) in &index.objects {
+            self.add(&assets_dir.join(path), &object.hash, object.size);
+        }
+    }
+
+    /// Adds the files within `jar_file` which are part of the
+    /// `extracted_contents`.
+    ///
+    /// Every file is decompressed to hash it.
+    pub fn add_jar(
+        &mut self,
+        jar_file: &Path,
+        extracted_contents: &ExtractedContents,
+    ) -> Result<(), ExtractError> {
+        let mut assets = JarAssets::open(jar_file, extracted_contents.clone())?;
+
+        while let Some(mut entry) = assets.next_entry()? {
+            if entry.is_dir() {
+                continue;
+            }
+
+            let path = entry.output_path(false);
+            let size = entry.size();
+            let hash = util::sha1_hex(&mut entry).map_err(|error| ExtractError::Zip {
+                path: jar_file.to_owned(),
+                source: error.into(),
+            })?;
+
+            self.add(&path, &hash, size);
+        }
+
+        Ok(())
+    }
+
+    /// Returns the clusters of at least two files of at least `min_size`
+    /// bytes each, those wasting the most bytes first.
+    pub fn clusters(&self, min_size: u64) -> Vec<Cluster> {
+        let mut clusters: Vec<_> = self
+            .files
+            .iter()
+            .filter(|(_, (size, paths))| *size >= min_size && paths.len() > 1)
+            .map(|(hash, (size, paths))| {
+                let mut paths = paths.clone();
+                paths.sort();
+
+                Cluster {
+                    hash: hash.clone(),
+                    size: *size,
+                    wasted_bytes: size * (paths.len() as u64 - 1),
+                    paths,
+                }
+            })
+            .collect();
+
+        clusters.sort_by(|a, b| {
+            b.wasted_bytes
+                .cmp(&a.wasted_bytes)
+                .then_with(|| a.paths.cmp(&b.paths))
+        });
+
+        clusters
+    }
+}
@@ -0,0 +1,672 @@
+use crate::hashed::{IndexError, ObjectMismatch};
+use crate::util;
+use std::io;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use zip::result::ZipError;
+
+/// An error during an extraction.
+///
+/// Every variant carries the path it concerns, so that the error can be
+/// displayed on its own.
+#[derive(Error, Debug)]
+#[non_exhaustive]
+pub enum ExtractError {
+    /// A version's manifest file couldn't be read.
+    #[error("failed to read manifest file '{}': {source}", .path.display())]
+    ReadManifest {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    /// A version's manifest file is not valid JSON.
+    #[error("invalid manifest file '{}': {source}", .path.display())]
+    ParseManifest {
+        path: PathBuf,
+        #[source]
+        source: serde_json::Error,
+    },
+    /// A version's manifest file doesn't name an index file.
+    #[error("manifest file '{}' doesn't name an index file", .path.display())]
+    NoAssetIndex { path: PathBuf },
+    /// An index file doesn't exist.
+    #[error(
+        "no index file found at '{}'{}{}",
+        .path.display(),
+        suggestions_suffix(.suggestions),
+        available_indexes_suffix(.available)
+    )]
+    MissingIndex {
+        path: PathBuf,
+        /// Index files with similar names.
+        suggestions: Vec<String>,
+        /// The index files which do exist where it was looked for, in order.
+        available: Vec<String>,
+    },
+    /// An index file couldn't be read or parsed.
+    #[error("failed to load index file '{}': {source}", .path.display())]
+    Index {
+        path: PathBuf,
+        #[source]
+        source: IndexError,
+    },
+    /// A hashed file listed in an index file doesn't exist.
+    #[error("hashed file for '{}' not found at '{}'", .path.display(), .object_path.display())]
+    MissingObject {
+        /// The path of the asset within `assets`.
+        path: PathBuf,
+        /// The path to the hashed file which is missing.
+        object_path: PathBuf,
+    },
+    /// A hashed file doesn't have the size or SHA-1 hash listed in the index
+    /// file, as found when [verifying](crate::options::ExtractOptions::verify)
+    /// it.
+    #[error("hashed file for '{}' doesn't match the index file: {mismatch}", .path.display())]
+    CorruptObject {
+        /// The path of the asset within `assets`.
+        path: PathBuf,
+        mismatch: ObjectMismatch,
+    },
+    /// A `sounds.json` file is not valid JSON, or doesn't define sound
+    /// events.
+    #[error("invalid sounds file '{}': {source}", .path.display())]
+    ParseSounds {
+        /// The path of the file within `assets`.
+        path: PathBuf,
+        #[source]
+        source: serde_json::Error,
+    },
+    /// A hashed file couldn't be read.
+    #[error("failed to read hashed file '{}': {source}", .object_path.display())]
+    ReadObject {
+        object_path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    /// A backup of hashed assets couldn't be opened or read, or isn't an
+    /// archive of them.
+    #[error("failed to read assets backup '{}': {source}", .path.display())]
+    ReadBackup {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    /// A jar file couldn't be opened or read.
+    #[error("failed to read jar file '{}': {}", .path.display(), zip_message(.source))]
+    Zip {
+        path: PathBuf,
+        #[source]
+        source: ZipError,
+    },
+    /// An entry of a jar file couldn't be read, e.g. as it is corrupted.
+    #[error(
+        "failed to read '{}' from jar file '{}': {}",
+        .entry.display(),
+        .path.display(),
+        zip_message(.source)
+    )]
+    ReadEntry {
+        path: PathBuf,
+        /// The path of the entry relative to the jar's top-level directory
+        /// (if any).
+        entry: PathBuf,
+        #[source]
+        source: ZipError,
+    },
+    /// An extracted file or directory couldn't be written.
+    #[error("failed to write '{}': {source}", .path.display())]
+    WriteFile {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    /// A directory is where a file is to be written, and directories aren't
+    /// [replaced](crate::options::ExtractOptions::replace_dirs).
+    #[error("expected a file at '{}', but found a directory", .path.display())]
+    DirectoryInTheWay { path: PathBuf },
+    /// A previously extracted file or directory couldn't be read.
+    #[error("failed to read '{}': {source}", .path.display())]
+    ReadOutput {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    /// The files to be extracted without their
+    /// [namespace](crate::options::ExtractOptions::strip_namespace) are in
+    /// more than one namespace, so they would be mixed together.
+    #[error(
+        "can't strip the namespace of files in more than one namespace ({})",
+        .namespaces.join(", ")
+    )]
+    MultipleNamespaces { namespaces: Vec<String> },
+    /// More than one file would be extracted to the same path, e.g. without
+    /// their [namespace](crate::options::ExtractOptions::strip_namespace).
+    ///
+    /// Each collision is the path relative to the output directory, along
+    /// with the paths of the files which would be extracted to it.
+    #[error("files would be extracted to the same path: {}", collisions_message(.collisions))]
+    OutputCollisions {
+        collisions: Vec<(PathBuf, Vec<PathBuf>)>,
+    },
+    /// A jar file has several entries with the same name, and
+    /// [`DuplicateEntries::Error`](crate::options::DuplicateEntries::Error)
+    /// is set.
+    ///
+    /// Each entry is the path relative to the jar's top-level directory (if
+    /// any), along with how many entries have it.
+    #[error(
+        "jar file '{}' has several entries with the same name: {}",
+        .path.display(),
+        duplicates_message(.entries)
+    )]
+    DuplicateEntries {
+        path: PathBuf,
+        entries: Vec<(PathBuf, usize)>,
+    },
+    /// A file couldn't be downloaded from `url`.
+    #[cfg(feature = "remote")]
+    #[error("failed to download '{url}': {source}")]
+    Download {
+        url: String,
+        #[source]
+        source: io::Error,
+    },
+    /// A file is too large to fit in a volume of an
+    /// [archive](crate::archive::write_archive) of its own.
+    #[error(
+        "'{}' is {}, too large for volumes of at most {}",
+        .path.display(),
+        util::format_size(*.size),
+        util::format_size(*.limit)
+    )]
+    TooLargeForVolume {
+        path: PathBuf,
+        size: u64,
+        limit: u64,
+    },
+    /// A path couldn't be resolved, e.g. while canonicalizing it.
+    #[error("failed to resolve path '{}': {source}", .path.display())]
+    ResolvePath {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+}
+
+/// Formats `suggestions` for the end of an error message, e.g. ` (did you mean '17'?)`.
+fn suggestions_suffix(suggestions: &[String]) -> String {
+    util::did_you_mean(suggestions)
+        .map(|suggestions| format!(" ({suggestions})"))
+        .unwrap_or_default()
+}
+
+/// How many existing index files are listed when an index file isn't found.
+const LISTED_INDEXES: usize = 10;
+
+/// Lists the `available` index files, e.g. `; available index files are '5', '17'`.
+fn available_indexes_suffix(available: &[String]) -> String {
+    if available.is_empty() {
+        return String::new();
+    }
+
+    let listed: Vec<_> = available
+        .iter()
+        .take(LISTED_INDEXES)
+        .map(|index| format!("'{index}'"))
+        .collect();
+    let mut suffix = format!("; available index files are {}", listed.join(", "));
+    if available.len() > LISTED_INDEXES {
+        suffix.push_str(&format!(" and {} more", available.len() - LISTED_INDEXES));
+    }
+
+    suffix
+}
+
+/// Formats `collisions`, e.g. `'lang/en_us.json' from 'assets/minecraft/lang/en_us.json' and 'data/minecraft/lang/en_us.json'`.
+fn collisions_message(collisions: &[(PathBuf, Vec<PathBuf>)]) -> String {
+    collisions
+        .iter()
+        .map(|(path, sources)| {
+            let sources: Vec<_> = sources
+                .iter()
+                .map(|source| format!("'{}'", source.display()))
+                .collect();
+
+            format!("'{}' from {}", path.display(), sources.join(" and "))
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Formats `entries`, e.g. `'assets/minecraft/lang/en_us.json' (2 entries)`.
+fn duplicates_message(entries: &[(PathBuf, usize)]) -> String {
+    entries
+        .iter()
+        .map(|(path, count)| format!("'{}' ({count} entries)", path.display()))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Formats `error`, including the underlying error of [`ZipError::Io`] which
+/// would otherwise only be displayed as `i/o error`.
+fn zip_message(error: &ZipError) -> String {
+    match error {
+        ZipError::Io(error) => error.to_string(),
+        error => error.to_string(),
+    }
+}
+
+impl ExtractError {
+    /// Returns an [`ExtractError::Zip`] error for `path`.
+    pub(crate) fn zip(path: impl Into<PathBuf>) -> impl FnOnce(ZipError) -> Self {
+        let path = path.into();
+
+        move |source| Self::Zip { path, source }
+    }
+
+    /// Returns an [`ExtractError::WriteFile`] error for `path`.
+    pub(crate) fn write_file(path: impl Into<PathBuf>) -> impl FnOnce(io::Error) -> Self {
+        let path = path.into();
+
+        move |source| Self::WriteFile { path, source }
+    }
+
+    /// Returns an [`ExtractError::ReadBackup`] error for `path`.
+    pub(crate) fn read_backup(path: impl Into<PathBuf>) -> impl FnOnce(io::Error) -> Self {
+        let path = path.into();
+
+        move |source| Self::ReadBackup { path, source }
+    }
+
+    /// Returns an [`ExtractError::ReadOutput`] error for `path`.
+    pub(crate) fn read_output(path: impl Into<PathBuf>) -> impl FnOnce(io::Error) -> Self {
+        let path = path.into();
+
+        move |source| Self::ReadOutput { path, source }
+    }
+
+    /// Whether this is a failure to write because the output volume is full
+    /// (or the user's quota on it is), in which case no more files can be
+    /// written.
+    pub fn is_disk_full(&self) -> bool {
+        matches!(
+            self,
+            Self::WriteFile { source, .. }
+                if matches!(
+                    source.kind(),
+                    io::ErrorKind::StorageFull | io::ErrorKind::QuotaExceeded
+                )
+        )
+    }
+
+    /// The kind of [`io::Error`] which best describes this error.
+    pub fn kind(&self) -> io::ErrorKind {
+        match self {
+            Self::ReadManifest { source, .. }
+            | Self::ReadObject { source, .. }
+            | Self::ReadBackup { source, .. }
+            | Self::WriteFile { source, .. }
+            | Self::ReadOutput { source, .. }
+            | Self::ResolvePath { source, .. } => source.kind(),
+            #[cfg(feature = "remote")]
+            Self::Download { source, .. } => source.kind(),
+            Self::MissingIndex { .. } | Self::MissingObject { .. } => io::ErrorKind::NotFound,
+            Self::DirectoryInTheWay { .. } => io::ErrorKind::IsADirectory,
+            Self::Index {
+                source: IndexError::Io(source),
+                ..
+            }
+            | Self::Zip {
+                source: ZipError::Io(source),
+                ..
+            }
+            | Self::ReadEntry {
+                source: ZipError::Io(source),
+                ..
+            } => source.kind(),
+            Self::ParseManifest { .. }
+            | Self::NoAssetIndex { .. }
+            | Self::CorruptObject { .. }
+            | Self::ParseSounds { .. }
+            | Self::Index { .. }
+            | Self::Zip { .. }
+            | Self::ReadEntry { .. }
+            | Self::DuplicateEntries { .. } => io::ErrorKind::InvalidData,
+            Self::MultipleNamespaces { .. }
+            | Self::OutputCollisions { .. }
+            | Self::TooLargeForVolume { .. } => io::ErrorKind::InvalidInput,
+        }
+    }
+}
+
+/// Adds the path an IO operation was reading or writing to its error, so that
+/// the error says which file it concerns.
+///
+/// # Examples
+/// ```
+/// use extract_minecraft_assets::error::IoContext;
+/// use std::fs;
+/// use std::path::Path;
+///
+/// let path = Path::new("no/such/dir/report.json");
+/// let error = fs::write(path, "{}").writing(path).unwrap_err();
+///
+/// assert!(error.to_string().starts_with("failed to write 'no/such/dir/report.json': "));
+/// ```
+pub trait IoContext<T> {
+    /// Wraps the error as an [`ExtractError::WriteFile`] error for `path`.
+    fn writing(self, path: &Path) -> Result<T, ExtractError>;
+
+    /// Wraps the error as an [`ExtractError::ReadOutput`] error for `path`.
+    fn reading_output(self, path: &Path) -> Result<T, ExtractError>;
+}
+
+impl<T> IoContext<T> for io::Result<T> {
+    fn writing(self, path: &Path) -> Result<T, ExtractError> {
+        self.map_err(ExtractError::write_file(path))
+    }
+
+    fn reading_output(self, path: &Path) -> Result<T, ExtractError> {
+        self.map_err(ExtractError::read_output(path))
+    }
+}
+
+impl From<ExtractError> for io::Error {
+    fn from(error: ExtractError) -> Self {
+        io::Error::new(error.kind(), error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Asserts that `error` has `kind`, both itself and once converted into
+    /// an [`io::Error`], and displays as `message`.
+    #[track_caller]
+    fn assert_error(error: ExtractError, kind: io::ErrorKind, message: &str) {
+        assert_eq!(error.kind(), kind);
+        assert_eq!(error.to_string(), message);
+
+        let error = io::Error::from(error);
+        assert_eq!(error.kind(), kind);
+        assert_eq!(error.to_string(), message);
+    }
+
+    fn not_found() -> io::Error {
+        io::Error::new(io::ErrorKind::NotFound, "no such file")
+    }
+
+    fn invalid_json() -> serde_json::Error {
+        serde_json::from_str::<serde_json::Value>("{").unwrap_err()
+    }
+
+    #[test]
+    fn io_context() {
+        let path = Path::new("out/lang/en_us.json");
+
+        assert_error(
+            Err::<(), _>(io::Error::new(io::ErrorKind::StorageFull, "disk full"))
+                .writing(path)
+                .unwrap_err(),
+            io::ErrorKind::StorageFull,
+            "failed to write 'out/lang/en_us.json': disk full",
+        );
+        assert_error(
+            Err::<(), _>(not_found()).reading_output(path).unwrap_err(),
+            io::ErrorKind::NotFound,
+            "failed to read 'out/lang/en_us.json': no such file",
+        );
+    }
+
+    #[test]
+    fn disk_full() {
+        let write = |kind| ExtractError::write_file("out")(io::Error::from(kind));
+
+        assert!(write(io::ErrorKind::StorageFull).is_disk_full());
+        assert!(write(io::ErrorKind::QuotaExceeded).is_disk_full());
+        assert!(!write(io::ErrorKind::PermissionDenied).is_disk_full());
+        assert!(
+            !ExtractError::read_output("out")(io::ErrorKind::StorageFull.into()).is_disk_full()
+        );
+    }
+
+    #[test]
+    fn reading_sources() {
+        assert_error(
+            ExtractError::ReadManifest {
+                path: "1.21.json".into(),
+                source: not_found(),
+            },
+            io::ErrorKind::NotFound,
+            "failed to read manifest file '1.21.json': no such file",
+        );
+        assert_error(
+            ExtractError::ReadObject {
+                object_path: "objects/ab/abcd".into(),
+                source: not_found(),
+            },
+            io::ErrorKind::NotFound,
+            "failed to read hashed file 'objects/ab/abcd': no such file",
+        );
+        assert_error(
+            ExtractError::read_backup("assets.zip")(not_found()),
+            io::ErrorKind::NotFound,
+            "failed to read assets backup 'assets.zip': no such file",
+        );
+        assert_error(
+            ExtractError::ResolvePath {
+                path: "out".into(),
+                source: not_found(),
+            },
+            io::ErrorKind::NotFound,
+            "failed to resolve path 'out': no such file",
+        );
+        assert_error(
+            ExtractError::Index {
+                path: "17.json".into(),
+                source: not_found().into(),
+            },
+            io::ErrorKind::NotFound,
+            "failed to load index file '17.json': failed to read index file: no such file",
+        );
+    }
+
+    #[test]
+    fn missing_files() {
+        assert_error(
+            ExtractError::MissingIndex {
+                path: "indexes/18.json".into(),
+                suggestions: vec!["17".into(), "19".into()],
+                available: vec!["17".into(), "19".into(), "5".into()],
+            },
+            io::ErrorKind::NotFound,
+            "no index file found at 'indexes/18.json' (did you mean '17' or '19'?); available \
+             index files are '17', '19', '5'",
+        );
+        // Without a close match, the index files are still listed.
+        assert_error(
+            ExtractError::MissingIndex {
+                path: "indexes/foobarbaz.json".into(),
+                suggestions: Vec::new(),
+                available: vec!["17".into(), "5".into()],
+            },
+            io::ErrorKind::NotFound,
+            "no index file found at 'indexes/foobarbaz.json'; available index files are '17', '5'",
+        );
+        assert_error(
+            ExtractError::MissingIndex {
+                path: "indexes/18.json".into(),
+                suggestions: Vec::new(),
+                available: (1..=12).map(|id| id.to_string()).collect(),
+            },
+            io::ErrorKind::NotFound,
+            "no index file found at 'indexes/18.json'; available index files are '1', '2', '3', \
+             '4', '5', '6', '7', '8', '9', '10' and 2 more",
+        );
+        assert_error(
+            ExtractError::MissingIndex {
+                path: "indexes/18.json".into(),
+                suggestions: Vec::new(),
+                available: Vec::new(),
+            },
+            io::ErrorKind::NotFound,
+            "no index file found at 'indexes/18.json'",
+        );
+        assert_error(
+            ExtractError::MissingObject {
+                path: "minecraft/sounds/cave1.ogg".into(),
+                object_path: "objects/ab/abcd".into(),
+            },
+            io::ErrorKind::NotFound,
+            "hashed file for 'minecraft/sounds/cave1.ogg' not found at 'objects/ab/abcd'",
+        );
+    }
+
+    #[test]
+    fn invalid_sources() {
+        let json_message = invalid_json().to_string();
+
+        assert_error(
+            ExtractError::ParseManifest {
+                path: "1.21.json".into(),
+                source: invalid_json(),
+            },
+            io::ErrorKind::InvalidData,
+            &format!("invalid manifest file '1.21.json': {json_message}"),
+        );
+        assert_error(
+            ExtractError::NoAssetIndex {
+                path: "1.21.json".into(),
+            },
+            io::ErrorKind::InvalidData,
+            "manifest file '1.21.json' doesn't name an index file",
+        );
+        assert_error(
+            ExtractError::Index {
+                path: "17.json".into(),
+                source: invalid_json().into(),
+            },
+            io::ErrorKind::InvalidData,
+            &format!(
+                "failed to load index file '17.json': invalid index file at 1:1: {json_message}"
+            ),
+        );
+        assert_error(
+            ExtractError::CorruptObject {
+                path: "icons/icon_16x16.png".into(),
+                mismatch: ObjectMismatch::Size {
+                    expected: 3,
+                    actual: 2,
+                },
+            },
+            io::ErrorKind::InvalidData,
+            "hashed file for 'icons/icon_16x16.png' doesn't match the index file: it is 2 bytes \
+             rather than 3",
+        );
+        assert_error(
+            ExtractError::ParseSounds {
+                path: "minecraft/sounds.json".into(),
+                source: invalid_json(),
+            },
+            io::ErrorKind::InvalidData,
+            &format!("invalid sounds file 'minecraft/sounds.json': {json_message}"),
+        );
+    }
+
+    #[test]
+    fn jar_files() {
+        let not_zip = ZipError::InvalidArchive("Invalid zip header".into());
+        let not_zip_message = not_zip.to_string();
+
+        assert_error(
+            ExtractError::zip("client.jar")(not_zip),
+            io::ErrorKind::InvalidData,
+            &format!("failed to read jar file 'client.jar': {not_zip_message}"),
+        );
+        // The underlying error of an IO error is shown, rather than `i/o error`.
+        assert_error(
+            ExtractError::zip("client.jar")(ZipError::Io(not_found())),
+            io::ErrorKind::NotFound,
+            "failed to read jar file 'client.jar': no such file",
+        );
+        assert_error(
+            ExtractError::ReadEntry {
+                path: "client.jar".into(),
+                entry: "pack.png".into(),
+                source: ZipError::Io(io::ErrorKind::UnexpectedEof.into()),
+            },
+            io::ErrorKind::UnexpectedEof,
+            &format!(
+                "failed to read 'pack.png' from jar file 'client.jar': {}",
+                io::Error::from(io::ErrorKind::UnexpectedEof)
+            ),
+        );
+        assert_error(
+            ExtractError::DuplicateEntries {
+                path: "client.jar".into(),
+                entries: vec![("pack.png".into(), 2)],
+            },
+            io::ErrorKind::InvalidData,
+            "jar file 'client.jar' has several entries with the same name: 'pack.png' (2 entries)",
+        );
+    }
+
+    #[test]
+    fn output_paths() {
+        assert_error(
+            ExtractError::DirectoryInTheWay {
+                path: "out/pack.png".into(),
+            },
+            io::ErrorKind::IsADirectory,
+            "expected a file at 'out/pack.png', but found a directory",
+        );
+        assert_error(
+            ExtractError::MultipleNamespaces {
+                namespaces: vec!["minecraft".into(), "realms".into()],
+            },
+            io::ErrorKind::InvalidInput,
+            "can't strip the namespace of files in more than one namespace (minecraft, realms)",
+        );
+        assert_error(
+            ExtractError::OutputCollisions {
+                collisions: vec![(
+                    "lang/en_us.json".into(),
+                    vec![
+                        "assets/minecraft/lang/en_us.json".into(),
+                        "assets/realms/lang/en_us.json".into(),
+                    ],
+                )],
+            },
+            io::ErrorKind::InvalidInput,
+            "files would be extracted to the same path: 'lang/en_us.json' from \
+             'assets/minecraft/lang/en_us.json' and 'assets/realms/lang/en_us.json'",
+        );
+        assert_error(
+            ExtractError::TooLargeForVolume {
+                path: "music.ogg".into(),
+                size: 2048,
+                limit: 1024,
+            },
+            io::ErrorKind::InvalidInput,
+            &format!(
+                "'music.ogg' is {}, too large for volumes of at most {}",
+                util::format_size(2048),
+                util::format_size(1024)
+            ),
+        );
+    }
+
+    #[cfg(feature = "remote")]
+    #[test]
+    fn downloads() {
+        assert_error(
+            ExtractError::Download {
+                url: "https://example.com/17.json".into(),
+                source: io::Error::new(io::ErrorKind::TimedOut, "timed out"),
+            },
+            io::ErrorKind::TimedOut,
+            "failed to download 'https://example.com/17.json': timed out",
+        );
+    }
+}
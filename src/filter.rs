@@ -0,0 +1,218 @@
+//! Matching paths against [patterns](Pattern), to select which files to
+//! extract or list.
+//!
+//! Paths are matched as strings with `/` separators on every platform, e.g.
+//! `assets/minecraft/sounds/ambient/cave/cave1.ogg`. These are the paths
+//! given to the filter of [`extract_jar_filtered`], so a [`PatternSet`] can
+//! select what to extract with `|path| patterns.matches(path)`.
+//!
+//! [`extract_jar_filtered`]: crate::jar::extract_jar_filtered
+
+use globset::{Glob, GlobBuilder, GlobMatcher};
+use regex::Regex;
+use serde::{Serialize, Serializer};
+use std::hash::{Hash, Hasher};
+use std::mem;
+use std::path::{Component, Path};
+use thiserror::Error;
+
+/// A pattern matched against the paths of files.
+///
+/// # Examples
+/// ```
+/// use extract_minecraft_assets::filter::Pattern;
+/// use std::path::Path;
+///
+/// let path = Path::new("assets/minecraft/sounds/ambient/cave/cave1.ogg");
+///
+/// assert!(Pattern::substring("cave").matches(path));
+/// assert!(Pattern::glob("*.ogg")?.matches(path));
+/// assert!(Pattern::glob("assets/*/sounds/**")?.matches(path));
+/// assert!(!Pattern::glob("assets/*.ogg")?.matches(path));
+/// assert!(Pattern::regex(r"cave\d+\.ogg$")?.matches(path));
+/// # Ok::<(), extract_minecraft_assets::filter::PatternError>(())
+/// ```
+#[derive(Clone, Debug)]
+pub enum Pattern {
+    /// Matches paths containing the string.
+    Substring(String),
+    /// Matches paths matching the glob.
+    Glob(GlobPattern),
+    /// Matches paths containing a match of the regular expression.
+    Regex(Regex),
+}
+
+/// A [glob pattern](Pattern::Glob).
+///
+/// `*` and `?` never match `/`, while `**` matches any number of
+/// directories. A glob without a `/` is matched against file names alone,
+/// so `*.ogg` matches every `.ogg` file.
+#[derive(Clone, Debug)]
+pub struct GlobPattern {
+    matcher: GlobMatcher,
+    file_name_only: bool,
+}
+
+/// A pattern which isn't a valid glob or regular expression.
+#[derive(Error, Debug)]
+#[error("invalid pattern '{pattern}': {message}")]
+pub struct PatternError {
+    pub pattern: String,
+    /// A description of what's wrong with the pattern.
+    pub message: String,
+}
+
+impl Pattern {
+    /// Returns a pattern matching paths containing `substring`.
+    pub fn substring(substring: impl Into<String>) -> Self {
+        Self::Substring(substring.into())
+    }
+
+    /// Parses a [glob pattern](GlobPattern).
+    pub fn glob(glob: &str) -> Result<Self, PatternError> {
+        let matcher = GlobBuilder::new(glob)
+            .literal_separator(true)
+            .build()
+            .map(|glob: Glob| glob.compile_matcher())
+            .map_err(|error| PatternError {
+                pattern: glob.to_owned(),
+                message: error.kind().to_string(),
+            })?;
+
+        Ok(Self::Glob(GlobPattern {
+            matcher,
+            file_name_only: !glob.contains('/'),
+        }))
+    }
+
+    /// Parses a regular expression.
+    pub fn regex(regex: &str) -> Result<Self, PatternError> {
+        Regex::new(regex)
+            .map(Self::Regex)
+            .map_err(|error| PatternError {
+                pattern: regex.to_owned(),
+                message: error.to_string(),
+            })
+    }
+
+    /// The substring, glob or regular expression the pattern was made from.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Substring(substring) => substring,
+            Self::Glob(GlobPattern { matcher, .. }) => matcher.glob().glob(),
+            Self::Regex(regex) => regex.as_str(),
+        }
+    }
+
+    /// Whether the pattern matches `path`.
+    pub fn matches(&self, path: &Path) -> bool {
+        match self {
+            Self::Substring(substring) => slash_path(path).contains(substring.as_str()),
+            Self::Glob(GlobPattern {
+                matcher,
+                file_name_only: true,
+            }) => path.file_name().is_some_and(|name| matcher.is_match(name)),
+            Self::Glob(GlobPattern { matcher, .. }) => matcher.is_match(slash_path(path)),
+            Self::Regex(regex) => regex.is_match(&slash_path(path)),
+        }
+    }
+}
+
+/// Patterns are equal if they are of the same kind and made from the same
+/// string.
+impl PartialEq for Pattern {
+    fn eq(&self, other: &Self) -> bool {
+        mem::discriminant(self) == mem::discriminant(other) && self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for Pattern {}
+
+impl Hash for Pattern {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        mem::discriminant(self).hash(state);
+        self.as_str().hash(state);
+    }
+}
+
+/// Patterns are serialized as the string they were made from.
+impl Serialize for Pattern {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+/// Any number of [patterns](Pattern), matching paths which any of them
+/// match.
+///
+/// # Examples
+/// ```
+/// use extract_minecraft_assets::filter::{Pattern, PatternSet};
+/// use std::path::Path;
+///
+/// let patterns = PatternSet::new()
+///     .with(Pattern::substring("lang/"))
+///     .with(Pattern::glob("*.ogg")?);
+///
+/// assert!(patterns.matches(Path::new("assets/minecraft/lang/de_de.json")));
+/// assert!(patterns.matches(Path::new("assets/minecraft/sounds/cave1.ogg")));
+/// assert!(!patterns.matches(Path::new("assets/minecraft/textures/stone.png")));
+/// # Ok::<(), extract_minecraft_assets::filter::PatternError>(())
+/// ```
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Default, Serialize)]
+#[serde(transparent)]
+pub struct PatternSet {
+    patterns: Vec<Pattern>,
+}
+
+impl PatternSet {
+    /// Returns an empty set, which matches nothing.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `pattern` to the set.
+    pub fn with(mut self, pattern: Pattern) -> Self {
+        self.push(pattern);
+        self
+    }
+
+    /// Adds `pattern` to the set.
+    pub fn push(&mut self, pattern: Pattern) {
+        self.patterns.push(pattern);
+    }
+
+    /// Whether the set has no patterns.
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// Whether any pattern matches `path`.
+    pub fn matches(&self, path: &Path) -> bool {
+        self.patterns.iter().any(|pattern| pattern.matches(path))
+    }
+}
+
+impl FromIterator<Pattern> for PatternSet {
+    fn from_iter<I: IntoIterator<Item = Pattern>>(patterns: I) -> Self {
+        Self {
+            patterns: patterns.into_iter().collect(),
+        }
+    }
+}
+
+/// Returns `path` as a string with `/` separators.
+fn slash_path(path: &Path) -> String {
+    let mut string = String::new();
+
+    for component in path.components() {
+        if let Component::Normal(name) = component {
+            if !string.is_empty() {
+                string.push('/');
+            }
+            string.push_str(&name.to_string_lossy());
+        }
+    }
+
+    string
+}
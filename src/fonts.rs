@@ -0,0 +1,135 @@
+//! Finding every file used by the fonts, e.g. to extract a usable copy of
+//! them.
+//!
+//! Fonts are defined by `assets/<namespace>/font/<name>.json`, listing
+//! providers which point at textures (often in the jar file), glyph data
+//! (often hashed), and other fonts.
+
+use crate::closure::{self, AssetSources, Closure, Reference};
+use crate::error::ExtractError;
+use serde::Deserialize;
+use std::path::{Component, Path, PathBuf};
+
+/// A font definition.
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize)]
+struct Font {
+    #[serde(default)]
+    providers: Vec<Provider>,
+}
+
+/// A source of glyphs for a [`Font`].
+///
+/// Every resource location is within `minecraft` if it has no namespace.
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Provider {
+    /// Glyphs from a texture, e.g. `minecraft:font/ascii.png` within
+    /// `textures`.
+    Bitmap { file: String },
+    /// Glyphs from a TrueType font, e.g. `minecraft:uniform.ttf` within
+    /// `font`.
+    Ttf { file: String },
+    /// Glyphs from the pages of a template, e.g.
+    /// `minecraft:font/unicode_page_%s.png` within `textures`, with their
+    /// widths in `sizes`, e.g. `minecraft:font/glyph_sizes.bin`.
+    LegacyUnicode { sizes: String, template: String },
+    /// Glyphs from a zip file of `.hex` files, e.g.
+    /// `minecraft:font/unifont.zip`.
+    Unihex { hex_file: String },
+    /// Every glyph of another font, e.g. `minecraft:include/space` within
+    /// `font`.
+    Reference { id: String },
+    /// A provider which doesn't reference any files, e.g. `space`.
+    #[serde(other)]
+    Other,
+}
+
+/// Returns the files referenced by the font definition `contents`, within
+/// `assets`.
+///
+/// # Examples
+/// ```
+/// use extract_minecraft_assets::closure::Reference;
+/// use extract_minecraft_assets::fonts;
+/// use std::path::PathBuf;
+///
+/// let references = fonts::font_references(br#"{"providers": [
+///     {"type": "bitmap", "file": "minecraft:font/ascii.png", "chars": []},
+///     {"type": "reference", "id": "minecraft:include/space"}
+/// ]}"#)?;
+///
+/// assert_eq!(
+///     references,
+///     [
+///         Reference::Required(PathBuf::from("minecraft/textures/font/ascii.png")),
+///         Reference::Required(PathBuf::from("minecraft/font/include/space.json")),
+///     ]
+/// );
+/// # Ok::<(), serde_json::Error>(())
+/// ```
+pub fn font_references(contents: &[u8]) -> Result<Vec<Reference>, serde_json::Error> {
+    let font: Font = serde_json::from_slice(contents)?;
+    let mut references = Vec::new();
+
+    for provider in font.providers {
+        match provider {
+            Provider::Bitmap { file } => {
+                references.push(Reference::Required(closure::resource_path(
+                    &file, "textures", "",
+                )));
+            }
+            Provider::Ttf { file } => {
+                references.push(Reference::Required(closure::resource_path(
+                    &file, "font", "",
+                )));
+            }
+            Provider::LegacyUnicode { sizes, template } => {
+                references.push(Reference::Required(closure::resource_path(&sizes, "", "")));
+                // Pages without any glyphs don't exist.
+                references.extend((0..=0xff).map(|page| {
+                    let page = template.replace("%s", &format!("{page:02x}"));
+
+                    Reference::Optional(closure::resource_path(&page, "textures", ""))
+                }));
+            }
+            Provider::Unihex { hex_file } => {
+                references.push(Reference::Required(closure::resource_path(
+                    &hex_file, "", "",
+                )));
+            }
+            Provider::Reference { id } => {
+                references.push(Reference::Required(closure::resource_path(
+                    &id, "font", ".json",
+                )));
+            }
+            Provider::Other => {}
+        }
+    }
+
+    Ok(references)
+}
+
+/// Whether `path`, within `assets`, is a font definition, e.g.
+/// `minecraft/font/default.json` or `minecraft/font/include/space.json`.
+pub fn is_font(path: &Path) -> bool {
+    let mut components = path.components();
+
+    matches!(components.next(), Some(Component::Normal(_)))
+        && components.next() == Some(Component::Normal("font".as_ref()))
+        && path
+            .extension()
+            .is_some_and(|extension| extension == "json")
+}
+
+/// Returns the closure of every font definition within `sources`: the font
+/// definitions and every file they reference.
+pub fn fonts_closure(sources: &mut AssetSources) -> Result<Closure, ExtractError> {
+    let fonts: Vec<PathBuf> = sources
+        .paths()
+        .into_iter()
+        .filter(|path| is_font(path))
+        .map(Path::to_owned)
+        .collect();
+
+    closure::resolve(sources, fonts, |_, contents| font_references(contents))
+}
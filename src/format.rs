@@ -0,0 +1,114 @@
+//! Reformatting JSON files, e.g. to pretty-print the minified JSON files
+//! Minecraft ships while extracting them.
+
+use serde::Serialize;
+use serde::de::IgnoredAny;
+use std::path::Path;
+
+/// How to format JSON files.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JsonFormat {
+    /// Each value on its own line, indented by two spaces.
+    Pretty,
+    /// Without any whitespace between values.
+    Minify,
+}
+
+/// Whether the file at `path` is a JSON file which can be formatted, i.e. a
+/// `.json` or `.mcmeta` file.
+pub fn is_json(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension == "json" || extension == "mcmeta")
+}
+
+/// Formats the JSON `contents` as `format`.
+///
+/// Only whitespace between values is changed: keys stay in the order they
+/// appear, and strings and numbers are kept exactly as they are written.
+///
+/// Fails if `contents` isn't valid JSON, in which case it should be kept as
+/// it is.
+///
+/// # Examples
+/// ```
+/// use extract_minecraft_assets::format::{self, JsonFormat};
+///
+/// let pretty = format::format_json(br#"{"parent":"block/cube_all","textures":{"all":"block/stone"},"tags":[]}"#, JsonFormat::Pretty)?;
+///
+/// assert_eq!(
+///     String::from_utf8(pretty).unwrap(),
+///     r#"{
+///   "parent": "block/cube_all",
+///   "textures": {
+///     "all": "block/stone"
+///   },
+///   "tags": []
+/// }"#
+/// );
+/// # Ok::<(), serde_json::Error>(())
+/// ```
+pub fn format_json(contents: &[u8], format: JsonFormat) -> Result<Vec<u8>, serde_json::Error> {
+    // Validating first means the tokens below are well-formed.
+    serde_json::from_slice::<IgnoredAny>(contents)?;
+
+    let pretty = format == JsonFormat::Pretty;
+    let mut output = Vec::with_capacity(contents.len());
+    let mut depth = 0;
+    let mut bytes = contents.iter().copied().peekable();
+
+    let newline = |output: &mut Vec<u8>, depth: usize| {
+        if pretty {
+            output.push(b'\n');
+            output.resize(output.len() + depth * 2, b' ');
+        }
+    };
+
+    while let Some(byte) = bytes.next() {
+        match byte {
+            b' ' | b'\t' | b'\n' | b'\r' => {}
+            b'"' => {
+                output.push(byte);
+                while let Some(byte) = bytes.next() {
+                    output.push(byte);
+                    match byte {
+                        b'\\' => output.extend(bytes.next()),
+                        b'"' => break,
+                        _ => {}
+                    }
+                }
+            }
+            b'{' | b'[' => {
+                output.push(byte);
+                while bytes.next_if(u8::is_ascii_whitespace).is_some() {}
+
+                // Empty objects and arrays stay on one line.
+                if let Some(close @ (b'}' | b']')) = bytes.peek().copied() {
+                    output.push(close);
+                    bytes.next();
+                } else {
+                    depth += 1;
+                    newline(&mut output, depth);
+                }
+            }
+            b'}' | b']' => {
+                depth -= 1;
+                newline(&mut output, depth);
+                output.push(byte);
+            }
+            b',' => {
+                output.push(byte);
+                newline(&mut output, depth);
+            }
+            b':' => {
+                output.push(byte);
+                if pretty {
+                    output.push(b' ');
+                }
+            }
+            _ => output.push(byte),
+        }
+    }
+
+    Ok(output)
+}
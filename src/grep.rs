@@ -0,0 +1,268 @@
+//! Searching the contents of the text files of jar files and index files
+//! for a [pattern](TextPattern), without extracting anything.
+//!
+//! Only files with one of the [`TEXT_EXTENSIONS`](crate::text::TEXT_EXTENSIONS)
+//! are searched, and files which turn out to be binary are skipped. Files are
+//! read one line at a time, so that only the longest line is ever held in
+//! memory.
+
+use crate::error::ExtractError;
+use crate::filter::PatternError;
+use crate::hashed::IndexFile;
+use crate::jar::{ExtractedContents, JarAssets};
+use crate::search::FileSource;
+use crate::text;
+use regex::Regex;
+use serde::Serialize;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+
+/// What to search the lines of files for.
+#[derive(Clone, Debug)]
+pub enum TextPattern {
+    /// Matches lines containing the string.
+    Substring(String),
+    /// Matches lines containing a match of the regular expression.
+    Regex(Regex),
+}
+
+impl TextPattern {
+    /// Parses a regular expression.
+    pub fn regex(regex: &str) -> Result<Self, PatternError> {
+        Regex::new(regex)
+            .map(Self::Regex)
+            .map_err(|error| PatternError {
+                pattern: regex.to_owned(),
+                message: error.to_string(),
+            })
+    }
+
+    /// Whether the pattern matches `line`.
+    pub fn matches(&self, line: &str) -> bool {
+        match self {
+            Self::Substring(substring) => line.contains(substring.as_str()),
+            Self::Regex(regex) => regex.is_match(line),
+        }
+    }
+}
+
+/// A line matching the pattern searched for.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LineHit {
+    pub source: FileSource,
+    /// The path of the file relative to the output directory, as if
+    /// extracted without ignoring the top-level directory, e.g.
+    /// `data/minecraft/loot_table/chests/bastion_treasure.json`.
+    pub path: PathBuf,
+    /// The number of the line, starting at 1.
+    pub line: u64,
+    /// The line, without its line ending.
+    pub text: String,
+}
+
+/// How many files a search went through.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GrepStats {
+    /// The number of text files searched.
+    pub searched: usize,
+    /// The number of files skipped as their contents aren't text.
+    pub binary: usize,
+    /// The number of hashed files listed in the index file which don't
+    /// exist.
+    pub missing: usize,
+}
+
+/// A search for the lines of text files matching a pattern.
+///
+/// # Examples
+/// ```
+/// use extract_minecraft_assets::grep::{Grep, TextPattern};
+/// use extract_minecraft_assets::search::FileSource;
+/// use std::path::Path;
+///
+/// let grep = Grep::new(TextPattern::Substring("netherite".to_owned()));
+/// let contents = "{\n  \"name\": \"minecraft:netherite_ingot\"\n}\n";
+///
+/// let mut hits = Vec::new();
+/// let is_text = grep.search_reader(
+///     FileSource::Jar,
+///     Path::new("data/minecraft/loot_table/chest.json"),
+///     contents.as_bytes(),
+///     &mut |hit| hits.push(hit),
+/// )?;
+///
+/// assert!(is_text);
+/// assert_eq!(hits[0].line, 2);
+/// assert_eq!(hits[0].text, "  \"name\": \"minecraft:netherite_ingot\"");
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Clone, Debug)]
+pub struct Grep {
+    pattern: TextPattern,
+    first_per_file: bool,
+}
+
+impl Grep {
+    /// Returns a search for every line matching `pattern`.
+    pub fn new(pattern: TextPattern) -> Self {
+        Self {
+            pattern,
+            first_per_file: false,
+        }
+    }
+
+    /// Sets whether to stop searching each file at its first hit, e.g. to
+    /// only list the files which match.
+    pub fn first_per_file(mut self, first_per_file: bool) -> Self {
+        self.first_per_file = first_per_file;
+        self
+    }
+
+    /// Searches the lines read from `reader`, the contents of the file at
+    /// `path`, calling `on_hit` with each line which matches.
+    ///
+    /// Returns false without searching anything if the first bytes read
+    /// contain a null byte, as the file is then binary.
+    pub fn search_reader(
+        &self,
+        source: FileSource,
+        path: &Path,
+        reader: impl Read,
+        on_hit: &mut dyn FnMut(LineHit),
+    ) -> io::Result<bool> {
+        let mut reader = BufReader::new(reader);
+        if reader.fill_buf()?.contains(&0) {
+            return Ok(false);
+        }
+
+        let mut line = Vec::new();
+        let mut number = 0;
+        while reader.read_until(b'\n', &mut line)? > 0 {
+            number += 1;
+
+            let text = String::from_utf8_lossy(&line);
+            let text = text.trim_end_matches(['\n', '\r']);
+            if self.pattern.matches(text) {
+                on_hit(LineHit {
+                    source,
+                    path: path.to_owned(),
+                    line: number,
+                    text: text.to_owned(),
+                });
+                if self.first_per_file {
+                    break;
+                }
+            }
+            line.clear();
+        }
+
+        Ok(true)
+    }
+
+    /// Searches the text files within `jar_file` which are part of the
+    /// `extracted_contents`, in the order they are in the jar file.
+    ///
+    /// Each entry is decompressed as it is read, and never written anywhere.
+    pub fn search_jar(
+        &self,
+        jar_file: &Path,
+        extracted_contents: &ExtractedContents,
+        on_hit: &mut dyn FnMut(LineHit),
+    ) -> Result<GrepStats, ExtractError> {
+        let mut stats = GrepStats::default();
+        let mut assets =
+            JarAssets::open(jar_file, extracted_contents.clone())?.filter(text::is_text);
+
+        while let Some(entry) = assets.next_entry()? {
+            if entry.is_dir() {
+                continue;
+            }
+
+            let path = entry.output_path(false);
+            let entry_path = entry.path().to_owned();
+            let is_text = self
+                .search_reader(FileSource::Jar, &path, entry, on_hit)
+                .map_err(|source| ExtractError::ReadEntry {
+                    path: jar_file.to_owned(),
+                    entry: entry_path,
+                    source: source.into(),
+                })?;
+            stats.count(is_text);
+        }
+
+        Ok(stats)
+    }
+
+    /// Searches the text files listed in `index`, reading their hashed files
+    /// from the `objects` directory within `hashed_assets_dir`, sorted by
+    /// path.
+    ///
+    /// Hashed files which don't exist are counted rather than failing the
+    /// search.
+    pub fn search_index(
+        &self,
+        hashed_assets_dir: &Path,
+        index: &IndexFile,
+        on_hit: &mut dyn FnMut(LineHit),
+    ) -> Result<GrepStats, ExtractError> {
+        let mut stats = GrepStats::default();
+        let objects_dir = hashed_assets_dir.join("objects");
+
+        let mut objects: Vec<_> = index
+            .objects
+            .iter()
+            .filter(|(path, _)| text::is_text(path))
+            .collect();
+        objects.sort_by(|a, b| a.0.cmp(b.0));
+
+        for (path, object) in objects {
+            let object_path = objects_dir.join(object.hashed_file_path());
+            let read_error = |source| ExtractError::ReadObject {
+                object_path: object_path.clone(),
+                source,
+            };
+
+            let file = match File::open(&object_path) {
+                Ok(file) => file,
+                Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                    stats.missing += 1;
+                    continue;
+                }
+                Err(error) => return Err(read_error(error)),
+            };
+
+            let path = Path::new("assets").join(path);
+            let is_text = self
+                .search_reader(FileSource::Hashed, &path, file, on_hit)
+                .map_err(read_error)?;
+            stats.count(is_text);
+        }
+
+        Ok(stats)
+    }
+}
+
+impl GrepStats {
+    fn count(&mut self, is_text: bool) {
+        if is_text {
+            self.searched += 1;
+        } else {
+            self.binary += 1;
+        }
+    }
+}
+
+impl std::ops::Add for GrepStats {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            searched: self.searched + other.searched,
+            binary: self.binary + other.binary,
+            missing: self.missing + other.missing,
+        }
+    }
+}
@@ -1,28 +1,27 @@
-use crate::{util, ExtractCmd};
-use clap::Args;
-use crossterm::terminal::ClearType;
-use crossterm::{cursor, terminal, QueueableCommand};
-use serde::Deserialize;
+use crate::diff::{Diff, DiffItem, OutputDiff};
+use crate::error::ExtractError;
+use crate::locked::LockedRetry;
+use crate::options::{ExtractOptions, Verify};
+use crate::progress::{ProgressSink, Tally, WorkerEvent};
+use crate::report::ExtractionReport;
+use crate::search::FileSource;
+use crate::transform::Transform;
+use crate::util;
+use crate::validate;
+use crate::vfs::{self, Vfs};
+use serde::{Deserialize, Serialize, Serializer};
+use serde_json::ser::Formatter as JsonFormatter;
+use sha1::{Digest, Sha1};
 use std::collections::HashMap;
-use std::convert::Infallible;
-use std::io::Write;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
 use std::path::{Path, PathBuf};
-use std::{fs, io};
-
-#[derive(Args)]
-pub struct HashedSubcommand {
-    /// The path to the `.minecraft/assets/` directory to extract assets from.
-    ///
-    /// Defaults to the default `.minecraft/assets/` folder location on your OS.
-    #[arg(value_name = "ASSETS DIRECTORY")]
-    hashed_assets_dir: Option<PathBuf>,
-    /// The index file to use.
-    ///
-    /// Can either be a file path to the index file itself, or the name of
-    /// that version (e.g. `24` instead of `.minecraft/assets/indexes/24.json`).
-    #[arg(short, long, value_name = "FILE or VERSION", value_parser = IndexFileLocation::parse)]
-    index: IndexFileLocation,
-}
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Mutex, PoisonError};
+use std::thread;
+use std::{fmt, fs, io};
 
 /// The location of the index file to use.
 #[derive(Clone)]
@@ -34,137 +33,1196 @@ pub enum IndexFileLocation {
 }
 
 impl IndexFileLocation {
-    pub fn parse(input: &str) -> Result<Self, Infallible> {
+    /// Parses `input` into an [`IndexFileLocation`]: a [file](IndexFileLocation::File)
+    /// if a file exists at that path, or otherwise a [version](IndexFileLocation::Version).
+    ///
+    /// Inputs which look like paths, containing a path separator or ending in
+    /// `.json`, are never versions: if no file exists there, this fails with
+    /// [`ExtractError::MissingIndex`] giving the absolute path, so that typos
+    /// are caught straight away.
+    ///
+    /// # Examples
+    /// ```
+    /// use extract_minecraft_assets::hashed::IndexFileLocation;
+    ///
+    /// assert!(matches!(IndexFileLocation::parse("24"), Ok(IndexFileLocation::Version(_))));
+    /// assert!(IndexFileLocation::parse("indexes/24.jsn").is_err());
+    /// ```
+    pub fn parse(input: &str) -> Result<Self, ExtractError> {
         let path = Path::new(input);
 
-        Ok(if path.is_file() {
-            IndexFileLocation::File(path.to_owned())
-        } else {
-            IndexFileLocation::Version(input.to_owned())
+        if path.is_file() {
+            return Ok(IndexFileLocation::File(path.to_owned()));
+        }
+        if !input.contains(['/', std::path::MAIN_SEPARATOR]) && !input.ends_with(".json") {
+            return Ok(IndexFileLocation::Version(input.to_owned()));
+        }
+
+        let path = std::path::absolute(path).unwrap_or_else(|_| path.to_owned());
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy())
+            .unwrap_or_default();
+        let mut available = path
+            .parent()
+            .map(|dir| {
+                util::entry_names(dir, None, |path| {
+                    path.is_file()
+                        && path
+                            .extension()
+                            .is_some_and(|extension| extension == "json")
+                })
+            })
+            .unwrap_or_default();
+        available.sort();
+
+        Err(ExtractError::MissingIndex {
+            suggestions: util::suggest(&name, available.iter().cloned()),
+            available,
+            path,
         })
     }
+
+    /// Returns the path to the index file, looking [versions](IndexFileLocation::Version)
+    /// up within the `indexes` directory of `hashed_assets_dir`.
+    ///
+    /// Fails with [`ExtractError::MissingIndex`] if there is no index file of
+    /// that version.
+    pub fn resolve(&self, hashed_assets_dir: &Path) -> Result<PathBuf, ExtractError> {
+        match self {
+            Self::File(path) => Ok(path.clone()),
+            Self::Version(version) => {
+                let path = hashed_assets_dir
+                    .join("indexes")
+                    .join(format!("{version}.json"));
+
+                if path.is_file() {
+                    Ok(path)
+                } else {
+                    Err(missing_index(hashed_assets_dir, path))
+                }
+            }
+        }
+    }
 }
 
 /// Represents the contents of an index file in `.minecraft/assets/indexes`.
-#[derive(Deserialize)]
+///
+/// An index file can be modified and [written](IndexFile::to_writer) back out
+/// in the same format as the launcher's.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug, Default)]
 pub struct IndexFile {
+    /// Whether the assets are extracted to `.minecraft/assets/virtual` by the
+    /// launcher, as with the `legacy` index.
+    #[serde(rename = "virtual", default, skip_serializing_if = "is_false")]
+    pub is_virtual: bool,
+    /// Whether the assets are extracted to `.minecraft/resources` by the
+    /// launcher, as with the `pre-1.6` index.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub map_to_resources: bool,
     /// A map of file paths within `assets` and the associated [`Object`].
-    objects: HashMap<PathBuf, Object>,
+    #[serde(serialize_with = "serialize_sorted")]
+    pub objects: HashMap<PathBuf, Object>,
+}
+
+/// Whether `value` is false, so that unset flags are not serialized.
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+/// Serializes `objects` ordered by their file paths, as the launcher does.
+fn serialize_sorted<S: Serializer>(
+    objects: &HashMap<PathBuf, Object>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let mut objects: Vec<_> = objects.iter().collect();
+    objects.sort_by_key(|(path, _)| path.to_string_lossy());
+
+    serializer.collect_map(objects)
+}
+
+/// Formats JSON like the launcher's index files: on a single line, with a
+/// space after every `:` and `,`.
+struct IndexFormatter;
+
+impl JsonFormatter for IndexFormatter {
+    fn begin_array_value<W: ?Sized + Write>(
+        &mut self,
+        writer: &mut W,
+        first: bool,
+    ) -> io::Result<()> {
+        if first {
+            Ok(())
+        } else {
+            writer.write_all(b", ")
+        }
+    }
+
+    fn begin_object_key<W: ?Sized + Write>(
+        &mut self,
+        writer: &mut W,
+        first: bool,
+    ) -> io::Result<()> {
+        if first {
+            Ok(())
+        } else {
+            writer.write_all(b", ")
+        }
+    }
+
+    fn begin_object_value<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(b": ")
+    }
+}
+
+/// An asset listed in an [`IndexFile`].
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct AssetEntry<'a> {
+    /// The path of the asset within `assets`, e.g. `minecraft/sounds/ambient/cave/cave1.ogg`.
+    pub virtual_path: &'a Path,
+    /// The SHA-1 hash of the asset, which is also the name of its hashed file.
+    pub hash: &'a str,
+    /// The size of the asset in bytes.
+    pub size: u64,
+    /// The path to the asset's hashed file within the `objects` directory,
+    /// e.g. `b6/b62ca8ec10d07e6bf5ac8dae0c8c1d2e6a1e3356`.
+    pub object_path: PathBuf,
+}
+
+/// An error reading an [`IndexFile`].
+#[derive(Debug)]
+pub enum IndexError {
+    /// The index file couldn't be read.
+    Io(io::Error),
+    /// The index file is not valid JSON, or doesn't have the expected structure.
+    Parse {
+        /// The line at which the error was found, starting at 1.
+        line: usize,
+        /// The column at which the error was found, starting at 1.
+        column: usize,
+        /// A description of the error.
+        message: String,
+    },
+    /// An object's hash isn't 40 lowercase hexadecimal characters, so its
+    /// hashed file can't be found.
+    InvalidHash {
+        /// The path of the asset within `assets`.
+        path: PathBuf,
+        hash: String,
+    },
+}
+
+impl Display for IndexError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "failed to read index file: {error}"),
+            Self::Parse {
+                line,
+                column,
+                message,
+            } => write!(f, "invalid index file at {line}:{column}: {message}"),
+            Self::InvalidHash { path, hash } => write!(
+                f,
+                "invalid hash {hash:?} for '{}': expected 40 lowercase hexadecimal characters",
+                path.display()
+            ),
+        }
+    }
+}
+
+impl Error for IndexError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            Self::Parse { .. } | Self::InvalidHash { .. } => None,
+        }
+    }
 }
 
-/// Information about a hashed file.
-#[derive(Deserialize)]
-struct Object {
-    /// The hashed name of the file.
-    #[serde(rename = "hash")]
-    hashed_file_name: String,
+impl From<io::Error> for IndexError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for IndexError {
+    fn from(error: serde_json::Error) -> Self {
+        if error.is_io() {
+            Self::Io(error.into())
+        } else {
+            Self::Parse {
+                line: error.line(),
+                column: error.column(),
+                message: error.to_string(),
+            }
+        }
+    }
+}
+
+impl From<IndexError> for io::Error {
+    fn from(error: IndexError) -> Self {
+        match error {
+            IndexError::Io(error) => error,
+            error @ (IndexError::Parse { .. } | IndexError::InvalidHash { .. }) => {
+                io::Error::new(io::ErrorKind::InvalidData, error)
+            }
+        }
+    }
+}
+
+impl IndexFile {
+    /// Parses an index file from `reader`.
+    ///
+    /// Fails with [`IndexError::InvalidHash`] if an object's hash isn't a
+    /// [valid](Object::is_valid_hash) SHA-1 hash, naming the first such object
+    /// by path.
+    pub fn from_reader(reader: impl Read) -> Result<Self, IndexError> {
+        let index: Self = serde_json::from_reader(BufReader::new(reader))?;
+
+        if let Some((path, object)) = index
+            .objects
+            .iter()
+            .filter(|(_, object)| !Object::is_valid_hash(&object.hash))
+            .min_by_key(|(path, _)| *path)
+        {
+            return Err(IndexError::InvalidHash {
+                path: path.clone(),
+                hash: object.hash.clone(),
+            });
+        }
+
+        Ok(index)
+    }
+
+    /// Writes the index file to `writer` in the same format as the launcher.
+    ///
+    /// Objects are written in order of their file paths, so an index file
+    /// which is read and written back out is unchanged.
+    ///
+    /// # Examples
+    /// ```
+    /// use extract_minecraft_assets::hashed::{IndexFile, Object};
+    ///
+    /// let mut index = IndexFile::default();
+    /// index.insert(
+    ///     "icons/icon_16x16.png",
+    ///     Object {
+    ///         hash: "bdf48ef6b5d0d23bbb02e17d04865216179f510a".to_owned(),
+    ///         size: 3665,
+    ///     },
+    /// );
+    ///
+    /// let mut json = Vec::new();
+    /// index.to_writer(&mut json)?;
+    ///
+    /// assert_eq!(
+    ///     String::from_utf8(json).unwrap(),
+    ///     r#"{"objects": {"icons/icon_16x16.png": {"hash": "bdf48ef6b5d0d23bbb02e17d04865216179f510a", "size": 3665}}}"#,
+    /// );
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn to_writer(&self, writer: impl Write) -> io::Result<()> {
+        let mut writer = BufWriter::new(writer);
+        self.serialize(&mut serde_json::Serializer::with_formatter(
+            &mut writer,
+            IndexFormatter,
+        ))?;
+
+        writer.flush()
+    }
+
+    /// Reads and parses the index file at `path`.
+    ///
+    /// Fails with [`ExtractError::Index`] if the file can't be read or parsed.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use extract_minecraft_assets::hashed::IndexFile;
+    /// use std::path::Path;
+    ///
+    /// let index = IndexFile::from_path(Path::new(".minecraft/assets/indexes/17.json"))?;
+    ///
+    /// println!("{} objects ({} bytes)", index.len(), index.total_size());
+    /// # Ok::<(), extract_minecraft_assets::error::ExtractError>(())
+    /// ```
+    pub fn from_path(path: &Path) -> Result<Self, ExtractError> {
+        File::open(path)
+            .map_err(IndexError::from)
+            .and_then(Self::from_reader)
+            .map_err(|source| ExtractError::Index {
+                path: path.to_owned(),
+                source,
+            })
+    }
+
+    /// The number of objects listed in the index.
+    pub fn len(&self) -> usize {
+        self.objects.len()
+    }
+
+    /// Whether the index lists no objects.
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+
+    /// The total size in bytes of every object listed in the index.
+    pub fn total_size(&self) -> u64 {
+        self.objects.values().map(|object| object.size).sum()
+    }
+
+    /// Returns an iterator over the assets listed in the index, in no
+    /// particular order.
+    ///
+    /// # Examples
+    /// ```
+    /// use extract_minecraft_assets::hashed::IndexFile;
+    ///
+    /// let index = IndexFile::from_reader(
+    ///     r#"{"objects": {"icons/icon_16x16.png": {"hash": "bdf48ef6b5d0d23bbb02e17d04865216179f510a", "size": 3665}}}"#
+    ///         .as_bytes(),
+    /// )?;
+    ///
+    /// for entry in index.entries() {
+    ///     println!("{} is hashed as {}", entry.virtual_path.display(), entry.object_path.display());
+    /// }
+    /// # Ok::<(), extract_minecraft_assets::hashed::IndexError>(())
+    /// ```
+    pub fn entries(&self) -> impl Iterator<Item = AssetEntry<'_>> {
+        self.objects.iter().map(|(path, object)| object.entry(path))
+    }
+
+    /// Returns the asset at `path` within `assets`, if it is listed in the index.
+    pub fn get(&self, path: impl AsRef<Path>) -> Option<AssetEntry<'_>> {
+        self.objects
+            .get_key_value(path.as_ref())
+            .map(|(path, object)| object.entry(path))
+    }
+
+    /// Adds `object` at `path` within `assets`, returning the object which
+    /// was previously there, if any.
+    pub fn insert(&mut self, path: impl Into<PathBuf>, object: Object) -> Option<Object> {
+        self.objects.insert(path.into(), object)
+    }
+
+    /// Removes the object at `path` within `assets`, returning it if it was
+    /// listed in the index.
+    pub fn remove(&mut self, path: impl AsRef<Path>) -> Option<Object> {
+        self.objects.remove(path.as_ref())
+    }
+
+    /// Compares this index (`a`) with `other` (`b`), returning the paths only
+    /// listed in either one, and the paths listed in both with different
+    /// hashes.
+    pub fn diff(&self, other: &IndexFile) -> IndexDiff {
+        Diff::compare(
+            self.objects
+                .iter()
+                .map(|(path, object)| (path.as_path(), object)),
+            other
+                .objects
+                .iter()
+                .map(|(path, object)| (path.as_path(), object)),
+            |_, a, b| a.hash != b.hash,
+        )
+    }
+
+    /// Compares `output_dir` with the objects of the index, as extracted by
+    /// [`extract_hashed_assets`] with the given
+    /// [`ignore_top_level`](ExtractOptions::ignore_top_level).
+    ///
+    /// Files are compared by their SHA-1 hashes. Only files within the
+    /// `assets` directory can be extra, or any file if `ignore_top_level` is
+    /// set.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use extract_minecraft_assets::hashed::IndexFile;
+    /// use std::path::Path;
+    ///
+    /// let index = IndexFile::from_path(Path::new(".minecraft/assets/indexes/17.json"))?;
+    /// let diff = index.diff_output(Path::new("extracted"), false)?;
+    ///
+    /// for path in &diff.modified {
+    ///     println!("modified {}", path.display());
+    /// }
+    /// # Ok::<(), extract_minecraft_assets::error::ExtractError>(())
+    /// ```
+    pub fn diff_output(
+        &self,
+        output_dir: &Path,
+        ignore_top_level: bool,
+    ) -> Result<OutputDiff, ExtractError> {
+        let assets_dir = if ignore_top_level {
+            PathBuf::new()
+        } else {
+            PathBuf::from("assets")
+        };
+        let expected = self
+            .objects
+            .iter()
+            .map(|(path, object)| (assets_dir.join(path), object))
+            .collect();
+
+        OutputDiff::compare(output_dir, &[assets_dir], &expected, |file, object| {
+            Ok(util::sha1_hex(file)? == object.hash)
+        })
+    }
+
+    /// Returns a copy of the index containing only the objects whose file
+    /// paths satisfy `filter`.
+    pub fn filtered(&self, filter: impl Fn(&Path) -> bool) -> Self {
+        Self {
+            objects: self
+                .objects
+                .iter()
+                .filter(|(path, _)| filter(path))
+                .map(|(path, object)| (path.clone(), object.clone()))
+                .collect(),
+            ..*self
+        }
+    }
+}
+
+/// The differences between two index files, as returned by [`IndexFile::diff`].
+///
+/// # Examples
+/// ```
+/// use extract_minecraft_assets::hashed::{IndexFile, Object};
+///
+/// let object = |hash: &str, size| Object { hash: hash.repeat(40), size };
+///
+/// let mut old = IndexFile::default();
+/// old.insert("minecraft/sounds/old.ogg", object("a", 10));
+/// old.insert("minecraft/lang/de_de.json", object("b", 100));
+///
+/// let mut new = IndexFile::default();
+/// new.insert("minecraft/lang/de_de.json", object("c", 120));
+/// new.insert("minecraft/sounds/new.ogg", object("d", 30));
+///
+/// let diff = old.diff(&new);
+///
+/// assert_eq!(diff.only_in_a.len(), 1);
+/// assert_eq!(diff.only_in_b.len(), 1);
+/// assert_eq!(diff.changed[0].size_delta(), 20);
+/// assert_eq!(diff.totals().changed.bytes, 20);
+/// ```
+pub type IndexDiff = Diff<Object>;
+
+/// Information about a hashed file, as it appears in an [`IndexFile`].
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Object {
+    /// The SHA-1 hash of the file, which is also its hashed file name.
+    pub hash: String,
     /// The size of the file in bytes.
-    #[serde(rename = "size")]
-    _size: usize,
+    pub size: u64,
+}
+
+/// How the contents of a hashed file differ from its [`Object`], as found
+/// by [`Object::verify`].
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum ObjectMismatch {
+    /// The file has another size, so it wasn't hashed.
+    Size { expected: u64, actual: u64 },
+    /// The file has the right size, but another SHA-1 hash.
+    Hash { expected: String, actual: String },
+}
+
+impl Display for ObjectMismatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Size { expected, actual } => {
+                write!(f, "it is {actual} bytes rather than {expected}")
+            }
+            Self::Hash { expected, actual } => {
+                write!(f, "it has SHA-1 hash {actual} rather than {expected}")
+            }
+        }
+    }
+}
+
+impl DiffItem for Object {
+    fn size(&self) -> u64 {
+        self.size
+    }
 }
 
 impl Object {
+    /// Whether `hash` is a SHA-1 hash as written in index files: 40 lowercase
+    /// hexadecimal characters.
+    ///
+    /// # Examples
+    /// ```
+    /// use extract_minecraft_assets::hashed::Object;
+    ///
+    /// assert!(Object::is_valid_hash("bdf48ef6b5d0d23bbb02e17d04865216179f510a"));
+    /// assert!(!Object::is_valid_hash("BDF48EF6B5D0D23BBB02E17D04865216179F510A"));
+    /// assert!(!Object::is_valid_hash("bd"));
+    /// ```
+    pub fn is_valid_hash(hash: &str) -> bool {
+        hash.len() == 40
+            && hash
+                .bytes()
+                .all(|byte| byte.is_ascii_digit() || (b'a'..=b'f').contains(&byte))
+    }
+
+    /// Returns the object of everything read from `reader`, with its SHA-1
+    /// hash and size, as an index file would list it.
+    ///
+    /// # Examples
+    /// ```
+    /// use extract_minecraft_assets::hashed::Object;
+    /// use std::path::Path;
+    ///
+    /// let object = Object::from_reader("{}".as_bytes())?;
+    ///
+    /// assert_eq!(object.hash, "bf21a9e8fbc5a3846fb05b4fa0859e0917b2202f");
+    /// assert_eq!(object.size, 2);
+    /// assert_eq!(
+    ///     object.hashed_file_path(),
+    ///     Path::new("bf/bf21a9e8fbc5a3846fb05b4fa0859e0917b2202f")
+    /// );
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn from_reader(mut reader: impl Read) -> io::Result<Self> {
+        let mut hasher = Sha1::new();
+        let size = io::copy(&mut reader, &mut hasher)?;
+
+        Ok(Self {
+            hash: format!("{:x}", hasher.finalize()),
+            size,
+        })
+    }
+
+    /// Checks that `contents` are those of this object, comparing their size
+    /// before hashing them.
+    ///
+    /// # Examples
+    /// ```
+    /// use extract_minecraft_assets::hashed::{Object, ObjectMismatch};
+    ///
+    /// let object = Object::from_reader("{}".as_bytes())?;
+    ///
+    /// assert_eq!(object.verify(b"{}"), Ok(()));
+    /// assert!(matches!(object.verify(b"[]"), Err(ObjectMismatch::Hash { .. })));
+    /// assert_eq!(
+    ///     object.verify(b"{ }"),
+    ///     Err(ObjectMismatch::Size { expected: 2, actual: 3 })
+    /// );
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn verify(&self, contents: &[u8]) -> Result<(), ObjectMismatch> {
+        let size = contents.len() as u64;
+        if size != self.size {
+            return Err(ObjectMismatch::Size {
+                expected: self.size,
+                actual: size,
+            });
+        }
+
+        let hash = format!("{:x}", Sha1::digest(contents));
+        if hash != self.hash {
+            return Err(ObjectMismatch::Hash {
+                expected: self.hash.clone(),
+                actual: hash,
+            });
+        }
+
+        Ok(())
+    }
+
     /// Returns the name of the folder the hashed file is within inside the `objects` folder.
     ///
     /// The name of that folder will be the same as the first two characters of
-    /// [the hashed file's name](#field.hashed_file).
+    /// [the hashed file's name](Object::hash). Hashes of [parsed](IndexFile::from_reader)
+    /// index files are always valid, but the whole hash is used for objects
+    /// made with a hash which can't be split there.
     fn parent_dir(&self) -> &Path {
-        Path::new(&self.hashed_file_name[..2])
+        Path::new(self.hash.get(..2).unwrap_or(&self.hash))
     }
 
     /// Returns the path to the hashed file within the `objects` folder.
     pub fn hashed_file_path(&self) -> PathBuf {
-        [self.parent_dir(), self.hashed_file_name.as_ref()]
-            .iter()
-            .collect()
+        [self.parent_dir(), self.hash.as_ref()].iter().collect()
+    }
+
+    /// Returns the [`AssetEntry`] for this object, found at `virtual_path`.
+    fn entry<'a>(&'a self, virtual_path: &'a Path) -> AssetEntry<'a> {
+        AssetEntry {
+            virtual_path,
+            hash: &self.hash,
+            size: self.size,
+            object_path: self.hashed_file_path(),
+        }
+    }
+}
+
+/// Returns an [`ExtractError::MissingIndex`] error for the missing
+/// `index_file`, listing the index files within `hashed_assets_dir` and
+/// suggesting those with similar names.
+pub fn missing_index(hashed_assets_dir: &Path, index_file: PathBuf) -> ExtractError {
+    let name = index_file
+        .file_stem()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+    let mut available =
+        util::entry_names(&hashed_assets_dir.join("indexes"), Some(".json"), |path| {
+            path.is_file()
+        });
+    available.sort();
+
+    ExtractError::MissingIndex {
+        suggestions: util::suggest(&name, available.iter().cloned()),
+        available,
+        path: index_file,
     }
 }
 
+/// Extracts every object listed in `index` from the `objects` directory within
+/// `hashed_assets_dir` into the [output directory](ExtractOptions::output_dir),
+/// reporting progress to `progress`.
+///
+/// Files are placed in an `assets` directory within the output directory,
+/// unless [`ignore_top_level`](ExtractOptions::ignore_top_level) is set. Files
+/// which can't be read or written are [reported as failures](ProgressSink::file_failed),
+/// rather than aborting the extraction, unless the output volume is full: the
+/// extraction then stops, with [`disk_full`](crate::progress::Summary::disk_full)
+/// set.
+///
+/// Hashed assets are always `assets`, so [`contents`](ExtractOptions::contents)
+/// is not used.
+///
+/// # Examples
+/// ```no_run
+/// use extract_minecraft_assets::hashed::{self, IndexFile};
+/// use extract_minecraft_assets::options::ExtractOptions;
+/// use extract_minecraft_assets::progress::NoProgress;
+/// use std::path::Path;
+///
+/// let hashed_assets_dir = Path::new(".minecraft/assets");
+/// let index = IndexFile::from_path(&hashed_assets_dir.join("indexes/17.json"))?;
+///
+/// let report = hashed::extract_hashed_assets(
+///     hashed_assets_dir,
+///     &index,
+///     &ExtractOptions::new().output("extracted"),
+///     &mut NoProgress,
+/// )?;
+/// println!("Extracted {report}");
+/// # Ok::<(), extract_minecraft_assets::error::ExtractError>(())
+/// ```
 pub fn extract_hashed_assets(
     hashed_assets_dir: &Path,
-    mut output_dir: PathBuf,
-    index_path: PathBuf,
-    ignore_top_level: bool,
-) -> io::Result<()> {
-    let output_dir = {
-        if !ignore_top_level {
-            output_dir.push("assets");
+    index: &IndexFile,
+    options: &ExtractOptions,
+    progress: &mut dyn ProgressSink,
+) -> Result<ExtractionReport, ExtractError> {
+    let index = &*options.included_objects(index);
+    let objects_dir = hashed_assets_dir.join("objects");
+    let mut extraction = ObjectExtraction::begin(index, options, progress)?;
+
+    if options.threads > 1 {
+        return Ok(extract_in_parallel(
+            &objects_dir,
+            index,
+            options,
+            extraction,
+        ));
+    }
+
+    for (file_path, object) in &index.objects {
+        let object_path = objects_dir.join(object.hashed_file_path());
+
+        if let Err(stop) = extraction.extract(file_path, object, || {
+            read_object(file_path, &object_path, &options.locked_retry)
+        }) {
+            return Ok(extraction.stop(stop));
         }
-        output_dir
-    };
+    }
 
-    let objects_dir = hashed_assets_dir.join("objects");
+    Ok(extraction.finish())
+}
 
-    let index: IndexFile = serde_json::from_str(&fs::read_to_string(index_path)?)?;
-    let objects_len = index.objects.len();
+/// Extracts the objects of `index` from `objects_dir` on
+/// [`threads`](ExtractOptions::threads) worker threads.
+///
+/// Objects are started and recorded by `extraction` on this thread, which
+/// reports progress, while the workers read and write them, reporting only
+/// which object each of them is writing.
+fn extract_in_parallel(
+    objects_dir: &Path,
+    index: &IndexFile,
+    options: &ExtractOptions,
+    mut extraction: ObjectExtraction,
+) -> ExtractionReport {
+    /// Records `written`, keeping the first stop.
+    fn record(extraction: &mut ObjectExtraction, stop: &mut Option<Stop>, written: WrittenObject) {
+        if let Err(next) = extraction.record(written) {
+            match stop {
+                None => *stop = Some(next),
+                Some(_) => extraction.also_stop(next),
+            }
+        }
+    }
 
-    let mut stdout = io::stdout();
+    /// Sends `event` to where the workers report, if anywhere.
+    fn report(worker_events: &Option<Sender<WorkerEvent>>, event: WorkerEvent) {
+        if let Some(worker_events) = worker_events {
+            // Nobody may be listening any more, which doesn't matter.
+            let _ = worker_events.send(event);
+        }
+    }
 
-    for (i, (file_path, object)) in index.objects.iter().enumerate() {
-        let file_name = file_path.display();
+    let mut stop = None;
+    // Only a few objects are queued, so that progress follows what is being
+    // written.
+    let (pending_sender, pending_receiver) = mpsc::sync_channel::<PendingObject>(options.threads);
+    let pending_receiver = Mutex::new(pending_receiver);
+    let (written_sender, written_receiver) = mpsc::channel();
+    let worker_events = extraction.workers(options.threads);
 
-        // Print extraction progress (overwriting the previous progress message)
-        // The cursor position is saved and restored to ensure it doesn't move all over the place.
-        stdout.queue(cursor::SavePosition)?;
-        stdout.queue(terminal::Clear(ClearType::FromCursorDown))?;
-        stdout.write_all(
-            format!(
-                "Extracting {file_name} ({}/{objects_len} hashed assets)",
-                i + 1
-            )
-            .as_bytes(),
-        )?;
-        stdout.queue(cursor::RestorePosition)?;
+    thread::scope(|scope| {
+        for worker in 0..options.threads {
+            let (pending_receiver, written_sender) = (&pending_receiver, written_sender.clone());
+            let worker_events = worker_events.clone();
 
-        stdout.flush()?;
+            scope.spawn(move || {
+                // The lock is only held while waiting for the next object.
+                while let Ok(pending) = pending_receiver
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .recv()
+                {
+                    report(
+                        &worker_events,
+                        WorkerEvent::Started {
+                            worker,
+                            path: pending.path.clone(),
+                        },
+                    );
 
-        // Read the hashed file
-        match fs::read(objects_dir.join(object.hashed_file_path())) {
-            Ok(contents) => {
-                let output_file = output_dir.join(&file_path);
+                    let object_path = objects_dir.join(pending.object.hashed_file_path());
+                    let file_path = pending.file_path;
+                    let written = pending.write(options, || {
+                        read_object(file_path, &object_path, &options.locked_retry)
+                    });
 
-                // Fill in parent directories of the file, since Windows doesn't do that.
-                if let Some(Err(error)) = output_file.parent().map(fs::create_dir_all) {
-                    eprintln!("Failed to create parent directories for '{file_name}': {error}");
+                    report(&worker_events, WorkerEvent::Finished { worker });
+                    if written_sender.send(written).is_err() {
+                        break;
+                    }
                 }
 
-                // Copy the file contents
-                if let Err(error) = fs::write(output_file, contents) {
-                    eprintln!("Failed to write file '{file_name}': {error}");
+                report(&worker_events, WorkerEvent::Idle { worker });
+            });
+        }
+        drop(written_sender);
+
+        for (file_path, object) in &index.objects {
+            for written in written_receiver.try_iter() {
+                record(&mut extraction, &mut stop, written);
+            }
+            if stop.is_some() {
+                break;
+            }
+
+            match extraction.start(file_path, object) {
+                Ok(Some(pending)) => pending_sender
+                    .send(pending)
+                    .expect("the workers only stop once every object is sent"),
+                Ok(None) => report(&worker_events, WorkerEvent::Skipped),
+                Err(next) => {
+                    stop = Some(next);
+                    break;
                 }
             }
+        }
+
+        // The objects already started are finished, even once stopping.
+        drop(pending_sender);
+        for written in written_receiver {
+            record(&mut extraction, &mut stop, written);
+        }
+    });
+    // Every worker has stopped, so the display is finished with.
+    drop(worker_events);
+
+    match stop {
+        Some(stop) => extraction.stop(stop),
+        None => extraction.finish(),
+    }
+}
+
+/// An extraction of the objects listed in an index file, whose contents are
+/// read by the caller, in any order.
+pub(crate) struct ObjectExtraction<'a> {
+    options: &'a ExtractOptions,
+    tally: Tally<'a>,
+    /// Where assets are placed relative to the output directory.
+    assets_dir: PathBuf,
+    /// The size of the objects not yet extracted.
+    remaining_bytes: u64,
+}
+
+/// Why an [`ObjectExtraction`] stopped early.
+pub(crate) enum Stop {
+    Cancelled,
+    /// The output volume became full while writing to `path`, with
+    /// `remaining` bytes left to extract, including that file.
+    DiskFull {
+        path: PathBuf,
+        error: ExtractError,
+        remaining: u64,
+    },
+}
+
+impl<'a> ObjectExtraction<'a> {
+    /// Starts extracting the objects of `index`, failing if stripping their
+    /// namespaces would mix them together.
+    pub(crate) fn begin(
+        index: &IndexFile,
+        options: &'a ExtractOptions,
+        progress: &'a mut dyn ProgressSink,
+    ) -> Result<Self, ExtractError> {
+        // Paths are reported relative to the output directory
+        let assets_dir = if options.ignore_top_level {
+            PathBuf::new()
+        } else {
+            PathBuf::from("assets")
+        };
 
+        options.check_strip_namespace(index.objects.keys().map(|file_path| {
+            let file_path = options.index_path(file_path);
+
+            (
+                Path::new("assets").join(&file_path),
+                options.relative_output_path(assets_dir.join(&file_path), false),
+            )
+        }))?;
+
+        Ok(Self {
+            options,
+            tally: Tally::begin(
+                progress,
+                options,
+                FileSource::Hashed,
+                index.len(),
+                index.total_size(),
+            ),
+            assets_dir,
+            remaining_bytes: index.total_size(),
+        })
+    }
+
+    /// Returns where the `workers` extracting the objects report which object
+    /// each of them is writing, if anywhere.
+    fn workers(&mut self, workers: usize) -> Option<Sender<WorkerEvent>> {
+        self.tally.workers(workers)
+    }
+
+    /// Extracts the asset at `file_path`, with the contents returned by
+    /// `read`, unless it is skipped.
+    pub(crate) fn extract(
+        &mut self,
+        file_path: &Path,
+        object: &Object,
+        read: impl FnOnce() -> Result<Vec<u8>, ExtractError>,
+    ) -> Result<(), Stop> {
+        let options = self.options;
+
+        match self.start(file_path, object)? {
+            Some(pending) => self.record(pending.write(options, read)),
+            None => Ok(()),
+        }
+    }
+
+    /// Starts extracting the asset at `file_path`, clearing the way for its
+    /// file, and returns what is left to [write](PendingObject::write) unless
+    /// it is skipped or fails.
+    pub(crate) fn start<'o>(
+        &mut self,
+        file_path: &'o Path,
+        object: &'o Object,
+    ) -> Result<Option<PendingObject<'o>>, Stop> {
+        let options = self.options;
+        let tally = &mut self.tally;
+
+        if options.is_cancelled() {
+            return Err(Stop::Cancelled);
+        }
+
+        // Including this object, in case the output volume is full.
+        let remaining = self.remaining_bytes;
+        self.remaining_bytes = self.remaining_bytes.saturating_sub(object.size);
+
+        tally.object_started(&object.hash);
+
+        let decoded_path = options.index_path(file_path);
+        let original_path =
+            options.relative_output_path(self.assets_dir.join(&decoded_path), false);
+        let transform = Transform::of(
+            options,
+            &Path::new("assets").join(&decoded_path),
+            object.size,
+        );
+        // A transformed file is written, and so skipped, under its new name.
+        let path = match transform {
+            Some(transform) => transform.output_path(&original_path),
+            None => original_path.clone(),
+        };
+        let output_file = options.output_dir.join(&path);
+
+        match options.make_way(&output_file, false) {
+            Ok(true) => {}
+            Ok(false) => {
+                tally.file_skipped(&path);
+                return Ok(None);
+            }
             Err(error) => {
-                eprintln!("Skipping '{file_name}': failed to read hashed file: {error}")
+                tally.file_failed(&path, &error);
+                return Ok(None);
+            }
+        }
+
+        tally.file_started(&path);
+
+        Ok(Some(PendingObject {
+            file_path,
+            object,
+            path,
+            original_path,
+            transform,
+            remaining,
+        }))
+    }
+
+    /// Records what became of an object once [written](PendingObject::write).
+    pub(crate) fn record(&mut self, written: WrittenObject<'_>) -> Result<(), Stop> {
+        let tally = &mut self.tally;
+        tally.object_started(&written.object.hash);
+
+        for event in written.events {
+            match event {
+                ObjectEvent::Unverified => tally.object_unverified(),
+                ObjectEvent::Warning { path, message } => tally.file_warning(&path, message),
+                ObjectEvent::BytesSaved(bytes) => tally.bytes_saved(bytes),
+                ObjectEvent::Normalized(count) => tally.files_normalized(count),
+                ObjectEvent::Done { path, bytes } => tally.file_done(&path, bytes),
+                ObjectEvent::Invalid { path, reason } => tally.file_invalid(&path, reason),
+                ObjectEvent::Failed { path, error } if error.is_disk_full() => {
+                    return Err(Stop::DiskFull {
+                        path,
+                        error,
+                        remaining: written.remaining,
+                    });
+                }
+                ObjectEvent::Failed { path, error } => tally.file_failed(&path, &error),
             }
         }
+
+        Ok(())
+    }
+
+    /// Records the file of another [stop](Stop) than the first, of an
+    /// extraction already stopping, as failed.
+    fn also_stop(&mut self, stop: Stop) {
+        if let Stop::DiskFull { path, error, .. } = stop {
+            self.tally.file_failed(&path, &error);
+        }
     }
 
-    Ok(())
+    /// Finishes the extraction early, returning its partial report.
+    pub(crate) fn stop(self, stop: Stop) -> ExtractionReport {
+        match stop {
+            Stop::Cancelled => self.tally.cancel(),
+            Stop::DiskFull {
+                path,
+                error,
+                remaining,
+            } => self.tally.disk_full(&path, &error, remaining),
+        }
+    }
+
+    /// Finishes the extraction, returning its report.
+    pub(crate) fn finish(self) -> ExtractionReport {
+        self.tally.finish()
+    }
+}
+
+/// An object whose output path has been cleared by
+/// [`ObjectExtraction::start`], to be read and written on any thread.
+pub(crate) struct PendingObject<'o> {
+    file_path: &'o Path,
+    object: &'o Object,
+    /// The path of the file written, relative to the output directory.
+    path: PathBuf,
+    /// The path of the file before it is transformed.
+    original_path: PathBuf,
+    transform: Option<Transform>,
+    /// The size of the objects not yet extracted, including this one.
+    remaining: u64,
+}
+
+/// What became of a [`PendingObject`] once written, to be
+/// [recorded](ObjectExtraction::record).
+pub(crate) struct WrittenObject<'o> {
+    object: &'o Object,
+    remaining: u64,
+    /// What happened, in order.
+    events: Vec<ObjectEvent>,
+}
+
+/// Something which happened while writing a [`PendingObject`].
+enum ObjectEvent {
+    /// The object didn't match the index file when verified.
+    Unverified,
+    Warning {
+        path: PathBuf,
+        message: String,
+    },
+    BytesSaved(u64),
+    Normalized(usize),
+    Done {
+        path: PathBuf,
+        bytes: u64,
+    },
+    Invalid {
+        path: PathBuf,
+        reason: String,
+    },
+    Failed {
+        path: PathBuf,
+        error: ExtractError,
+    },
 }
 
-impl ExtractCmd for HashedSubcommand {
-    fn execute(self, output_dir: PathBuf, ignore_top_level: bool) -> io::Result<()> {
-        let input_dir = self
-            .hashed_assets_dir
-            .or_else(|| util::hashed_assets_dir())
-            .filter(|path| path.is_dir())
-            .expect("No input directory found");
-        let indexes_dir = input_dir.join("indexes");
-        let index_file = match self.index {
-            IndexFileLocation::File(file) => file,
+impl<'o> PendingObject<'o> {
+    /// Reads the object with `read`, then verifies, transforms and writes it
+    /// as `options` say.
+    ///
+    /// Once a file fails because the output volume is full, nothing more is
+    /// written.
+    pub(crate) fn write(
+        self,
+        options: &ExtractOptions,
+        read: impl FnOnce() -> Result<Vec<u8>, ExtractError>,
+    ) -> WrittenObject<'o> {
+        let mut events = Vec::new();
+        let Self {
+            file_path,
+            object,
+            path,
+            original_path,
+            transform,
+            remaining,
+        } = self;
+        let written = |events| WrittenObject {
+            object,
+            remaining,
+            events,
+        };
 
-            IndexFileLocation::Version(version) => {
-                let path = indexes_dir.join(format!("{version}.json"));
+        let contents = match read() {
+            Ok(contents) => contents,
+            Err(error) => return written(vec![ObjectEvent::Failed { path, error }]),
+        };
+        if let Some(verify) = options.verify
+            && let Err(mismatch) = object.verify(&contents)
+        {
+            let error = ExtractError::CorruptObject {
+                path: file_path.to_owned(),
+                mismatch,
+            };
+            events.push(ObjectEvent::Unverified);
+            match verify {
+                Verify::Warn => events.push(ObjectEvent::Warning {
+                    path: original_path.clone(),
+                    message: error.to_string(),
+                }),
+                Verify::Strict => {
+                    events.push(ObjectEvent::Failed { path, error });
+                    return written(events);
+                }
+            }
+        }
 
-                if !path.is_file() {
-                    panic!("No index file found at {}", path.display());
+        let files = match transform {
+            Some(transform) => {
+                let transformed = transform.apply(&original_path, contents, options.keep_original);
+                if let Some(message) = transformed.warning {
+                    events.push(ObjectEvent::Warning {
+                        path: original_path,
+                        message,
+                    });
                 }
+                events.push(ObjectEvent::BytesSaved(transformed.bytes_saved));
+                events.push(ObjectEvent::Normalized(transformed.normalized));
 
-                path
+                transformed.files
             }
+            None => vec![(path, contents)],
         };
 
-        extract_hashed_assets(&input_dir, output_dir, index_file, ignore_top_level)
+        for (path, contents) in files {
+            let output_file = options.output_dir.join(&path);
+
+            match write_object(&*options.vfs, &contents, &output_file) {
+                Ok(bytes) => {
+                    let reason = validate::check(options, &path, &contents);
+                    events.push(ObjectEvent::Done {
+                        path: path.clone(),
+                        bytes,
+                    });
+                    if let Some(reason) = reason {
+                        events.push(ObjectEvent::Invalid { path, reason });
+                    }
+                }
+                Err(error) if error.is_disk_full() => {
+                    vfs::remove_partial_file(&*options.vfs, &output_file);
+                    events.push(ObjectEvent::Failed { path, error });
+                    break;
+                }
+                Err(error) => events.push(ObjectEvent::Failed { path, error }),
+            }
+        }
+
+        written(events)
     }
 }
+
+/// Reads the hashed file at `object_path` for the asset at `path`, retrying
+/// as `retry` says if another process holds it locked.
+pub(crate) fn read_object(
+    path: &Path,
+    object_path: &Path,
+    retry: &LockedRetry,
+) -> Result<Vec<u8>, ExtractError> {
+    retry.run(|| fs::read(object_path)).map_err(|source| {
+        if source.kind() == io::ErrorKind::NotFound {
+            ExtractError::MissingObject {
+                path: path.to_owned(),
+                object_path: object_path.to_owned(),
+            }
+        } else {
+            ExtractError::ReadObject {
+                object_path: object_path.to_owned(),
+                source,
+            }
+        }
+    })
+}
+
+/// Writes the `contents` of a hashed file to `output_file` in `vfs`,
+/// returning the number of bytes written.
+fn write_object(vfs: &dyn Vfs, contents: &[u8], output_file: &Path) -> Result<u64, ExtractError> {
+    // Fill in parent directories of the file, since Windows doesn't do that.
+    if let Some(parent) = output_file.parent() {
+        vfs.create_dir_all(parent)
+            .map_err(ExtractError::write_file(parent))?;
+    }
+
+    // Copy the file contents
+    vfs.write_file(output_file, &mut &*contents)
+        .map_err(ExtractError::write_file(output_file))?;
+
+    Ok(contents.len() as u64)
+}
@@ -1,29 +1,182 @@
-use crate::ExtractCmd;
+use crate::central_dir::{CentralDirectory, Rewritten};
+use crate::diff::{Diff, DiffItem, OutputDiff};
+use crate::error::{ExtractError, IoContext};
+use crate::locked::LockedRetry;
+use crate::options::{DuplicateEntries, ExtractOptions};
+use crate::progress::{ProgressSink, Tally};
+use crate::report::ExtractionReport;
+use crate::search::FileSource;
+use crate::transform::Transform;
+use crate::util;
+use crate::validate::{self, CheckingReader};
+use crate::vfs::{self, Vfs};
+#[cfg(feature = "cli")]
 use clap::Args;
-use crossterm::terminal::ClearType;
-use crossterm::{cursor, terminal, QueueableCommand};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt::{Display, Formatter};
 use std::fs::File;
-use std::io::Write;
-use std::path::{Path, PathBuf};
-use std::{fmt, fs, io};
+use std::io::{Read, Seek};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::{fmt, io};
 use zip::ZipArchive;
+use zip::read::ZipFile;
 
-#[derive(Args)]
-pub struct JarSubcommand {
-    /// The jar or zip file to extract from.
+/// A top-level directory of a jar file which can be extracted, such as
+/// `assets`.
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContentRoot {
+    /// The name of the root, used to describe what is being extracted.
+    pub name: String,
+    /// The directory within the jar containing the root's files.
+    pub prefix: PathBuf,
+    /// The directory within the output directory into which the root's files
+    /// are extracted, unless the top-level directory is ignored.
+    pub output_dir: PathBuf,
+}
+
+impl ContentRoot {
+    /// Returns a root named `name` which extracts `prefix` into `output_dir`.
+    pub fn new(
+        name: impl Into<String>,
+        prefix: impl Into<PathBuf>,
+        output_dir: impl Into<PathBuf>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            prefix: prefix.into(),
+            output_dir: output_dir.into(),
+        }
+    }
+
+    /// Returns a root which extracts the directory `name` into a directory of
+    /// the same name, e.g. `assets`.
+    pub fn directory(name: &str) -> Self {
+        Self::new(name, name, name)
+    }
+
+    /// Returns the path relative to the output directory into which the file
+    /// at `path` within this root is extracted.
     ///
-    /// Minecraft version jar files can be found in `.minecraft/versions/`.
-    #[arg(value_name = "FILE")]
-    jar_file: PathBuf,
-    /// Which contents to extract.
-    #[command(flatten)]
-    extracted_contents: ExtractedContents,
+    /// If `ignore_top_level` is set, the file is placed directly in the
+    /// output directory rather than in the root's [`output_dir`](ContentRoot::output_dir).
+    pub fn output_path(&self, path: &Path, ignore_top_level: bool) -> PathBuf {
+        let path = path.strip_prefix(&self.prefix).unwrap_or(path);
+
+        if ignore_top_level {
+            path.to_owned()
+        } else {
+            self.output_dir.join(path)
+        }
+    }
 }
 
-#[derive(Args, Clone, PartialEq, Eq, Hash, Debug)]
-#[group(multiple = true, required = true)]
+/// Which top-level directories of a jar file to extract, as a set of
+/// [`ContentRoot`]s.
+///
+/// # Examples
+/// ```
+/// use extract_minecraft_assets::jar::{ContentRoot, ExtractedContents};
+///
+/// let contents = ExtractedContents::all().with(ContentRoot::directory("META-INF"));
+///
+/// assert!(contents.contains("data"));
+/// assert_eq!(contents.to_string(), "assets, data and META-INF");
+/// ```
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Serialize)]
+#[serde(transparent)]
 pub struct ExtractedContents {
+    roots: Vec<ContentRoot>,
+}
+
+impl ExtractedContents {
+    /// No top-level directories at all.
+    pub fn none() -> Self {
+        Self { roots: Vec::new() }
+    }
+
+    /// Only the `assets` folder.
+    pub fn assets() -> Self {
+        Self::none().with(ContentRoot::directory("assets"))
+    }
+
+    /// Only the `data` folder.
+    pub fn data() -> Self {
+        Self::none().with(ContentRoot::directory("data"))
+    }
+
+    /// Both the `assets` and `data` folders.
+    pub fn all() -> Self {
+        Self::assets().with(ContentRoot::directory("data"))
+    }
+
+    /// Returns these contents with `root` added.
+    pub fn with(mut self, root: ContentRoot) -> Self {
+        self.insert(root);
+        self
+    }
+
+    /// Adds `root`, replacing any root with the same name.
+    pub fn insert(&mut self, root: ContentRoot) {
+        match self.roots.iter_mut().find(|other| other.name == root.name) {
+            Some(other) => *other = root,
+            None => self.roots.push(root),
+        }
+    }
+
+    /// The roots to extract, in the order they were added.
+    pub fn roots(&self) -> &[ContentRoot] {
+        &self.roots
+    }
+
+    /// Whether a root named `name` is extracted.
+    pub fn contains(&self, name: &str) -> bool {
+        self.roots.iter().any(|root| root.name == name)
+    }
+
+    /// Whether nothing is extracted.
+    pub fn is_empty(&self) -> bool {
+        self.roots.is_empty()
+    }
+
+    /// Returns the root which the file at `path` within the jar is part of,
+    /// if any.
+    pub fn root_of(&self, path: &Path) -> Option<&ContentRoot> {
+        self.roots
+            .iter()
+            .find(|root| path.starts_with(&root.prefix))
+    }
+}
+
+/// Only the `assets` folder.
+impl Default for ExtractedContents {
+    fn default() -> Self {
+        Self::assets()
+    }
+}
+
+/// Lists the names of the roots, e.g. `assets and data`.
+impl Display for ExtractedContents {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.roots.as_slice() {
+            [] => write!(f, "nothing"),
+            [root] => write!(f, "{}", root.name),
+            [rest @ .., last] => {
+                let rest: Vec<&str> = rest.iter().map(|root| root.name.as_str()).collect();
+
+                write!(f, "{} and {}", rest.join(", "), last.name)
+            }
+        }
+    }
+}
+
+/// Which contents to extract, as given on the command line.
+#[cfg(feature = "cli")]
+#[derive(Args, Clone, Debug)]
+#[group(multiple = true, required = true)]
+pub struct ContentsArgs {
     /// Extract the `assets` folder.
     ///
     /// Can be combined with --data.
@@ -36,116 +189,1124 @@ pub struct ExtractedContents {
     pub data: bool,
 }
 
-impl Default for ExtractedContents {
-    fn default() -> Self {
-        Self {
-            assets: true,
-            data: false,
+#[cfg(feature = "cli")]
+impl From<ContentsArgs> for ExtractedContents {
+    fn from(args: ContentsArgs) -> Self {
+        let mut contents = Self::none();
+
+        if args.assets {
+            contents.insert(ContentRoot::directory("assets"));
         }
+        if args.data {
+            contents.insert(ContentRoot::directory("data"));
+        }
+
+        contents
     }
 }
 
-impl Display for ExtractedContents {
-    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        match (self.assets, self.data) {
-            (false, false) => write!(f, "nothing"),
-            (true, false) => write!(f, "assets"),
-            (false, true) => write!(f, "data"),
-            (true, true) => write!(f, "assets and data"),
-        }
-    }
+/// Returns the path of an entry within the jar relative to its top-level
+/// directory (if any), e.g. `assets/minecraft/lang/en_us.json`, along with
+/// the root it is part of.
+///
+/// Returns [`None`] if the entry is not part of the `extracted_contents`.
+pub(crate) fn selected_path<'a, 'c>(
+    path: &'a Path,
+    top_level_dir: Option<&Path>,
+    extracted_contents: &'c ExtractedContents,
+) -> Option<(&'a Path, &'c ContentRoot)> {
+    let path = match top_level_dir {
+        Some(top_level) => path.strip_prefix(top_level).unwrap_or(path),
+        None => path,
+    };
+
+    extracted_contents.root_of(path).map(|root| (path, root))
 }
 
-pub fn extract_jar(
+/// Opens `jar_file`, returning the archive along with its top-level directory
+/// (if any).
+pub(crate) fn open_archive(
     jar_file: &Path,
-    output_dir: &Path,
-    extracted_contents: ExtractedContents,
-    ignore_top_level: bool,
-) -> io::Result<()> {
-    let assets = extracted_contents.assets.then(|| Path::new("assets"));
-    let data = extracted_contents.data.then(|| Path::new("data"));
+) -> Result<(ZipArchive<File>, Option<PathBuf>), ExtractError> {
+    read_archive(jar_file, open_file(jar_file)?)
+}
 
-    if assets.is_none() && data.is_none() {
-        return Ok(());
+/// Opens `jar_file` for reading, retrying by default if another process
+/// holds it locked.
+fn open_file(jar_file: &Path) -> Result<File, ExtractError> {
+    open_file_retrying(jar_file, &LockedRetry::default())
+}
+
+/// Opens `jar_file` for reading, retrying as `retry` says if another process
+/// holds it locked.
+fn open_file_retrying(jar_file: &Path, retry: &LockedRetry) -> Result<File, ExtractError> {
+    retry
+        .run(|| File::open(jar_file))
+        .map_err(|error| ExtractError::Zip {
+            path: jar_file.to_owned(),
+            source: error.into(),
+        })
+}
+
+/// Reads the archive of `jar_file` from `reader`, returning it along with its
+/// top-level directory (if any).
+pub(crate) fn read_archive<R: Read + Seek>(
+    jar_file: &Path,
+    reader: R,
+) -> Result<(ZipArchive<R>, Option<PathBuf>), ExtractError> {
+    let zip_error = |source| ExtractError::Zip {
+        path: jar_file.to_owned(),
+        source,
+    };
+
+    let archive = ZipArchive::new(reader).map_err(zip_error)?;
+    let top_level_dir = archive
+        .root_dir(zip::read::root_dir_common_filter)
+        .map_err(zip_error)?;
+
+    Ok((archive, top_level_dir))
+}
+
+/// The entries of a jar file which are part of some [`ExtractedContents`],
+/// read one at a time.
+///
+/// Unlike [`extract_jar`], this doesn't write anything: each [`JarEntry`] can
+/// be read from directly, e.g. to process it in memory or upload it
+/// elsewhere. As each entry borrows the archive, entries are returned by
+/// [`next_entry`](JarAssets::next_entry) rather than by an [`Iterator`].
+///
+/// # Examples
+/// ```no_run
+/// use extract_minecraft_assets::jar::{ExtractedContents, JarAssets};
+/// use std::io::Read;
+/// use std::path::Path;
+///
+/// let mut assets = JarAssets::open(
+///     Path::new(".minecraft/versions/1.20.1/1.20.1.jar"),
+///     ExtractedContents::assets(),
+/// )?
+/// .filter(|path| path.extension().is_some_and(|extension| extension == "json"));
+///
+/// while let Some(mut entry) = assets.next_entry()? {
+///     if entry.is_dir() {
+///         continue;
+///     }
+///
+///     let mut contents = String::new();
+///     entry.read_to_string(&mut contents)?;
+///     println!("{}: {} bytes", entry.path().display(), contents.len());
+/// }
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub struct JarAssets<'f, R = File> {
+    jar_file: PathBuf,
+    archive: ZipArchive<R>,
+    top_level_dir: Option<PathBuf>,
+    contents: ExtractedContents,
+    filter: Box<dyn Fn(&Path) -> bool + 'f>,
+    /// The index of the next entry within the archive.
+    next: usize,
+}
+
+/// An entry of a jar file returned by [`JarAssets::next_entry`], which can be
+/// [read](Read) to get its decompressed contents.
+pub struct JarEntry<'a> {
+    file: ZipFile<'a>,
+    path: PathBuf,
+    root: &'a ContentRoot,
+}
+
+impl JarAssets<'_> {
+    /// Opens `jar_file`, selecting the entries which are part of `contents`.
+    pub fn open(jar_file: &Path, contents: ExtractedContents) -> Result<Self, ExtractError> {
+        Self::new(jar_file, open_file(jar_file)?, contents)
+    }
+}
+
+impl<'f, R: Read + Seek> JarAssets<'f, R> {
+    /// Reads the jar file `jar_file` from `reader`, selecting the entries
+    /// which are part of `contents`.
+    ///
+    /// `jar_file` is only used in errors.
+    pub fn new(
+        jar_file: &Path,
+        reader: R,
+        contents: ExtractedContents,
+    ) -> Result<Self, ExtractError> {
+        let (archive, top_level_dir) = read_archive(jar_file, reader)?;
+
+        Ok(Self {
+            jar_file: jar_file.to_owned(),
+            archive,
+            top_level_dir,
+            contents,
+            filter: Box::new(|_| true),
+            next: 0,
+        })
+    }
+
+    /// Only selects the entries for which `filter` returns true.
+    ///
+    /// `filter` is given the path of the entry relative to the jar's top-level
+    /// directory (if any), e.g. `assets/minecraft/lang/en_us.json`.
+    pub fn filter(mut self, filter: impl Fn(&Path) -> bool + 'f) -> Self {
+        self.filter = Box::new(filter);
+        self
+    }
+
+    /// Returns the next selected entry, or [`None`] once every entry has been
+    /// returned.
+    ///
+    /// Both files and directories are returned, in the order they appear in
+    /// the jar. Fails with [`ExtractError::ReadEntry`] if a selected entry
+    /// can't be read, after which the entries following it can still be
+    /// returned.
+    pub fn next_entry(&mut self) -> Result<Option<JarEntry<'_>>, ExtractError> {
+        // Only the central directory is read until a selected entry is found.
+        let (index, path) = loop {
+            if self.next >= self.archive.len() {
+                return Ok(None);
+            }
+
+            let index = self.next;
+            self.next += 1;
+
+            let file = self
+                .archive
+                .by_index_raw(index)
+                .map_err(ExtractError::zip(&self.jar_file))?;
+
+            if let Some(path) = file.enclosed_name()
+                && let Some((path, _)) =
+                    selected_path(&path, self.top_level_dir.as_deref(), &self.contents)
+                && (self.filter)(path)
+            {
+                break (index, path.to_owned());
+            }
+        };
+
+        let root = self
+            .contents
+            .root_of(&path)
+            .expect("selected entries are part of a root");
+        let file = match self.archive.by_index(index) {
+            Ok(file) => file,
+            Err(source) => {
+                return Err(ExtractError::ReadEntry {
+                    path: self.jar_file.clone(),
+                    entry: path,
+                    source,
+                });
+            }
+        };
+
+        Ok(Some(JarEntry { file, path, root }))
+    }
+}
+
+impl JarEntry<'_> {
+    /// The path of the entry relative to the jar's top-level directory (if
+    /// any), e.g. `assets/minecraft/lang/en_us.json`.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The root the entry is part of.
+    pub fn root(&self) -> &ContentRoot {
+        self.root
+    }
+
+    /// The path relative to the output directory into which the entry would
+    /// be extracted, as with [`ExtractOptions::ignore_top_level`].
+    pub fn output_path(&self, ignore_top_level: bool) -> PathBuf {
+        self.root.output_path(&self.path, ignore_top_level)
+    }
+
+    /// The uncompressed size of the entry in bytes.
+    pub fn size(&self) -> u64 {
+        self.file.size()
+    }
+
+    /// Whether the entry is a directory rather than a file.
+    pub fn is_dir(&self) -> bool {
+        self.file.is_dir()
+    }
+
+    /// The unix permissions of the entry, if known.
+    pub fn unix_mode(&self) -> Option<u32> {
+        self.file.unix_mode()
     }
+}
+
+/// Reads the decompressed contents of the entry.
+impl Read for JarEntry<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.file.read(buf)
+    }
+}
+
+/// Lists the files within `jar_file` which are part of the `extracted_contents`,
+/// along with their uncompressed sizes in bytes.
+///
+/// Paths are relative to the jar's top-level directory (if any), e.g.
+/// `assets/minecraft/lang/en_us.json`.
+pub fn list_files(
+    jar_file: &Path,
+    extracted_contents: &ExtractedContents,
+) -> Result<Vec<(PathBuf, u64)>, ExtractError> {
+    list_files_from(jar_file, open_file(jar_file)?, extracted_contents)
+}
 
-    let mut archive = ZipArchive::new(File::open(&jar_file)?)?;
-    let top_level_dir = archive.root_dir(zip::read::root_dir_common_filter)?;
+/// Lists the files within the jar file `jar_file` read from `reader`, like
+/// [`list_files`].
+///
+/// `jar_file` is only used in errors.
+pub fn list_files_from<R: Read + Seek>(
+    jar_file: &Path,
+    reader: R,
+    extracted_contents: &ExtractedContents,
+) -> Result<Vec<(PathBuf, u64)>, ExtractError> {
+    let (mut archive, top_level_dir) = read_archive(jar_file, reader)?;
 
-    let mut stdout = io::stdout();
+    let mut files = Vec::new();
 
-    // Why does ZipArchive not implement an iterator...?
     for i in 0..archive.len() {
-        let mut file = archive.by_index(i)?;
+        let file = archive
+            .by_index_raw(i)
+            .map_err(ExtractError::zip(jar_file))?;
+
+        if file.is_dir() {
+            continue;
+        }
+
+        if let Some(path) = file.enclosed_name()
+            && let Some((path, _)) =
+                selected_path(&path, top_level_dir.as_deref(), extracted_contents)
+        {
+            files.push((path.to_owned(), file.size()));
+        }
+    }
+
+    Ok(files)
+}
+
+/// Lists the names of the top-level directories within `jar_file`, relative
+/// to its top-level directory (if any), in order, e.g. `assets`, `data` and
+/// `net`.
+///
+/// This tells what a jar contains when none of the requested contents are in
+/// it, e.g. a server jar which only bundles the server itself.
+pub fn top_level_dirs(jar_file: &Path) -> Result<Vec<String>, ExtractError> {
+    top_level_dirs_from(jar_file, open_file(jar_file)?)
+}
+
+/// Lists the names of the top-level directories within the jar file
+/// `jar_file` read from `reader`, like [`top_level_dirs`].
+///
+/// `jar_file` is only used in errors.
+pub fn top_level_dirs_from<R: Read + Seek>(
+    jar_file: &Path,
+    reader: R,
+) -> Result<Vec<String>, ExtractError> {
+    let (mut archive, top_level_dir) = read_archive(jar_file, reader)?;
+
+    let mut dirs = BTreeSet::new();
 
-        let path = match file.enclosed_name() {
-            Some(path) => path,
-            None => continue,
+    for i in 0..archive.len() {
+        let file = archive
+            .by_index_raw(i)
+            .map_err(ExtractError::zip(jar_file))?;
+        let Some(path) = file.enclosed_name() else {
+            continue;
         };
         let path = match &top_level_dir {
             Some(top_level) => path.strip_prefix(top_level).unwrap_or(&path),
             None => &path,
         };
-        let path = match (assets, data) {
-            (Some(assets), _) if path.starts_with(assets) => {
-                if ignore_top_level {
-                    path.strip_prefix(assets).unwrap_or(path)
-                } else {
-                    path
-                }
+
+        let mut components = path.components();
+        // Files directly within the top level are not directories.
+        if let Some(first) = components.next()
+            && (file.is_dir() || components.next().is_some())
+        {
+            dirs.insert(first.as_os_str().to_string_lossy().into_owned());
+        }
+    }
+
+    Ok(dirs.into_iter().collect())
+}
+
+/// Lists the files within `jar_file` which are part of the
+/// `extracted_contents` and have more than one entry with their name, along
+/// with how many entries have it, in order of their paths.
+///
+/// Paths are relative to the jar's top-level directory (if any). Names are
+/// compared exactly, so entries whose names only differ in case aren't
+/// counted.
+pub fn duplicate_entries(
+    jar_file: &Path,
+    extracted_contents: &ExtractedContents,
+) -> Result<Vec<(PathBuf, usize)>, ExtractError> {
+    let (_, duplicates) = read_duplicates(jar_file, open_file(jar_file)?, extracted_contents)?;
+
+    Ok(duplicates)
+}
+
+/// Files with more than one entry with their name in a jar, along with how
+/// many entries have it, as listed by [`duplicate_entries`].
+type Duplicates = Vec<(PathBuf, usize)>;
+
+/// Reads the central directory of `jar_file` from `reader`, returning it
+/// along with the files which are part of the `extracted_contents` and have
+/// more than one entry with their name, as listed by [`duplicate_entries`].
+fn read_duplicates<R: Read + Seek>(
+    jar_file: &Path,
+    mut reader: R,
+    extracted_contents: &ExtractedContents,
+) -> Result<(CentralDirectory, Duplicates), ExtractError> {
+    let (_, top_level_dir) = read_archive(jar_file, &mut reader)?;
+    let directory = CentralDirectory::read(&mut reader).map_err(|error| ExtractError::Zip {
+        path: jar_file.to_owned(),
+        source: error.into(),
+    })?;
+
+    let mut counts = BTreeMap::<&[u8], usize>::new();
+    for name in directory.names() {
+        *counts.entry(name).or_default() += 1;
+    }
+
+    let mut duplicates = Vec::new();
+    for (name, count) in counts {
+        // Directories are only created, so their duplicates don't matter.
+        if count < 2 || name.ends_with(b"/") {
+            continue;
+        }
+
+        let path = PathBuf::from(String::from_utf8_lossy(name).into_owned());
+        let enclosed = path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+        if enclosed
+            && let Some((path, _)) =
+                selected_path(&path, top_level_dir.as_deref(), extracted_contents)
+        {
+            duplicates.push((path.to_owned(), count));
+        }
+    }
+    duplicates.sort();
+
+    Ok((directory, duplicates))
+}
+
+/// Applies the [`DuplicateEntries`] policy of the `options` to the jar file
+/// `jar_file` read from `reader`, returning the reader from which to read the
+/// archive along with the duplicated files for which `filter` returns true.
+///
+/// Fails with [`ExtractError::DuplicateEntries`] if there are any, and they
+/// are an error.
+pub(crate) fn resolve_duplicates<R: Read + Seek>(
+    jar_file: &Path,
+    mut reader: R,
+    options: &ExtractOptions,
+    filter: impl Fn(&Path) -> bool,
+) -> Result<(Rewritten<R>, Duplicates), ExtractError> {
+    if options.contents.is_empty() {
+        return Ok((Rewritten::unchanged(reader), Vec::new()));
+    }
+
+    let (directory, mut duplicates) = read_duplicates(jar_file, &mut reader, &options.contents)?;
+    duplicates.retain(|(path, _)| filter(path));
+
+    if duplicates.is_empty() {
+        return Ok((Rewritten::unchanged(reader), duplicates));
+    }
+
+    match options.duplicate_entries {
+        DuplicateEntries::Last => Ok((Rewritten::unchanged(reader), duplicates)),
+        DuplicateEntries::First => Ok((directory.first_entries(reader), duplicates)),
+        DuplicateEntries::Error => Err(ExtractError::DuplicateEntries {
+            path: jar_file.to_owned(),
+            entries: duplicates,
+        }),
+    }
+}
+
+/// Returns the warning given for each of the `duplicates` found by
+/// [`resolve_duplicates`], along with the path relative to the output
+/// directory it concerns.
+pub(crate) fn duplicate_warnings(
+    duplicates: Duplicates,
+    options: &ExtractOptions,
+) -> impl Iterator<Item = (PathBuf, String)> + '_ {
+    let extracted = match options.duplicate_entries {
+        DuplicateEntries::First => "first",
+        _ => "last",
+    };
+
+    duplicates.into_iter().filter_map(move |(path, count)| {
+        let root = options.contents.root_of(&path)?;
+        let output_path =
+            options.relative_output_path(root.output_path(&path, options.ignore_top_level), false);
+
+        Some((
+            output_path,
+            format!(
+                "the jar has {count} entries with this name, of which the {extracted} was extracted"
+            ),
+        ))
+    })
+}
+
+/// Returns the warning given for each entry of the jar file `jar_file` read
+/// from `reader` which isn't extracted at all as its name is unsafe, e.g.
+/// `../../options.txt`, along with its name.
+///
+/// `jar_file` is only used in errors.
+pub(crate) fn unsafe_name_warnings<R: Read + Seek>(
+    jar_file: &Path,
+    reader: R,
+) -> Result<Vec<(PathBuf, String)>, ExtractError> {
+    let (mut archive, _) = read_archive(jar_file, reader)?;
+
+    let mut warnings = Vec::new();
+    for i in 0..archive.len() {
+        let file = archive
+            .by_index_raw(i)
+            .map_err(ExtractError::zip(jar_file))?;
+
+        if file.enclosed_name().is_none() {
+            warnings.push((
+                PathBuf::from(file.name()),
+                String::from(
+                    "the jar has an entry with this name, which would be written outside the \
+                     output directory, so it was skipped",
+                ),
+            ));
+        }
+    }
+
+    Ok(warnings)
+}
+
+/// Counts the files within `jar_file` which would be extracted.
+///
+/// Directories are not counted. Only the central directory is read: no
+/// entries are decompressed.
+pub fn count_entries(
+    jar_file: &Path,
+    extracted_contents: &ExtractedContents,
+) -> Result<usize, ExtractError> {
+    if extracted_contents.is_empty() {
+        return Ok(0);
+    }
+
+    let (mut archive, top_level_dir) = open_archive(jar_file)?;
+
+    let mut count = 0;
+
+    for i in 0..archive.len() {
+        let file = archive
+            .by_index_raw(i)
+            .map_err(ExtractError::zip(jar_file))?;
+
+        let selected = file.is_file()
+            && file.enclosed_name().is_some_and(|path| {
+                selected_path(&path, top_level_dir.as_deref(), extracted_contents).is_some()
+            });
+
+        if selected {
+            count += 1;
+        }
+    }
+
+    Ok(count)
+}
+
+/// The total uncompressed size in bytes of the files within `jar_file` which
+/// are part of the `extracted_contents`.
+pub fn total_size(
+    jar_file: &Path,
+    extracted_contents: &ExtractedContents,
+) -> Result<u64, ExtractError> {
+    Ok(list_files(jar_file, extracted_contents)?
+        .iter()
+        .map(|(_, size)| size)
+        .sum())
+}
+
+/// The central directory information of a file within a jar, as compared by
+/// [`diff_jars`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize)]
+pub struct EntryInfo {
+    /// The CRC-32 checksum of the uncompressed contents.
+    pub crc32: u32,
+    /// The uncompressed size in bytes.
+    pub size: u64,
+}
+
+impl DiffItem for EntryInfo {
+    fn size(&self) -> u64 {
+        self.size
+    }
+}
+
+/// Lists the files within `jar_file` which are part of the
+/// `extracted_contents`, like [`list_files`], along with their [`EntryInfo`].
+///
+/// This tells whether a file changed since the jar was last read, without
+/// keeping a copy of the jar.
+pub fn list_entries(
+    jar_file: &Path,
+    extracted_contents: &ExtractedContents,
+) -> Result<BTreeMap<PathBuf, EntryInfo>, ExtractError> {
+    let (_, files) = entry_infos(jar_file, extracted_contents)?;
+
+    Ok(files
+        .into_iter()
+        .map(|(path, (_, info))| (path, info))
+        .collect())
+}
+
+/// The differences between two jar files, as returned by [`diff_jars`].
+pub type JarDiff = Diff<EntryInfo>;
+
+/// Compares the files within jar files `a` and `b` which are part of the
+/// `extracted_contents`.
+///
+/// Paths are relative to each jar's own top-level directory (if any), so jars
+/// which only differ in their top-level directory list the same paths.
+///
+/// Files are compared by their CRC-32 checksums and uncompressed sizes, which
+/// are read from the central directories without decompressing anything. If
+/// `compare_contents` is set, files whose checksums and sizes are the same
+/// are also decompressed and compared byte by byte.
+///
+/// # Examples
+/// ```no_run
+/// use extract_minecraft_assets::jar::{self, ExtractedContents};
+/// use std::path::Path;
+///
+/// let diff = jar::diff_jars(
+///     Path::new(".minecraft/versions/1.20.1/1.20.1.jar"),
+///     Path::new(".minecraft/versions/1.20.2/1.20.2.jar"),
+///     &ExtractedContents::all(),
+///     false,
+/// )?;
+///
+/// for entry in &diff.only_in_b {
+///     println!("added {}", entry.path.display());
+/// }
+/// # Ok::<(), extract_minecraft_assets::error::ExtractError>(())
+/// ```
+pub fn diff_jars(
+    a: &Path,
+    b: &Path,
+    extracted_contents: &ExtractedContents,
+    compare_contents: bool,
+) -> Result<JarDiff, ExtractError> {
+    let (mut a_archive, a_files) = entry_infos(a, extracted_contents)?;
+    let (mut b_archive, b_files) = entry_infos(b, extracted_contents)?;
+
+    let mut error = None;
+    let diff = Diff::compare(
+        a_files
+            .iter()
+            .map(|(path, (_, info))| (path.as_path(), info)),
+        b_files
+            .iter()
+            .map(|(path, (_, info))| (path.as_path(), info)),
+        |path, a_info, b_info| {
+            if a_info != b_info {
+                return true;
+            }
+            if !compare_contents || error.is_some() {
+                return false;
             }
-            (_, Some(data)) if path.starts_with(data) => {
-                if ignore_top_level {
-                    path.strip_prefix(data).unwrap_or(path)
-                } else {
-                    path
+
+            let (a_index, b_index) = (a_files[path].0, b_files[path].0);
+            match same_contents(a, &mut a_archive, a_index, b, &mut b_archive, b_index) {
+                Ok(same) => !same,
+                Err(err) => {
+                    error = Some(err);
+                    false
                 }
             }
+        },
+    );
 
-            (_, _) => continue,
-        };
-        let output_path = output_dir.join(path);
+    match error {
+        Some(error) => Err(error),
+        None => Ok(diff),
+    }
+}
+
+/// Compares `output_dir` with the files within `jar_file` which are part of
+/// the `extracted_contents`, as extracted by [`extract_jar`] with the given
+/// [`ignore_top_level`](ExtractOptions::ignore_top_level).
+///
+/// Files are compared by their CRC-32 checksums against the jar's central
+/// directory, so nothing is decompressed. Only files within the roots'
+/// [output directories](ContentRoot::output_dir) can be extra, or any file if
+/// `ignore_top_level` is set.
+///
+/// # Examples
+/// ```no_run
+/// use extract_minecraft_assets::jar::{self, ExtractedContents};
+/// use std::path::Path;
+///
+/// let diff = jar::diff_output(
+///     Path::new(".minecraft/versions/1.20.1/1.20.1.jar"),
+///     Path::new("extracted"),
+///     &ExtractedContents::assets(),
+///     false,
+/// )?;
+///
+/// for path in &diff.missing {
+///     println!("missing {}", path.display());
+/// }
+/// # Ok::<(), extract_minecraft_assets::error::ExtractError>(())
+/// ```
+pub fn diff_output(
+    jar_file: &Path,
+    output_dir: &Path,
+    extracted_contents: &ExtractedContents,
+    ignore_top_level: bool,
+) -> Result<OutputDiff, ExtractError> {
+    let (_, files) = entry_infos(jar_file, extracted_contents)?;
 
-        // Print file being extracted
-        stdout.queue(cursor::SavePosition)?;
-        stdout.queue(terminal::Clear(ClearType::FromCursorDown))?;
-        stdout.write_all(format!("Extracting {}", path.display()).as_bytes())?;
-        stdout.queue(cursor::RestorePosition)?;
+    // Files extracted to the same path replace each other in the order they
+    // appear in the jar.
+    let mut files: Vec<_> = files.into_iter().collect();
+    files.sort_by_key(|(_, (index, _))| *index);
 
-        stdout.flush()?;
+    let expected = files
+        .into_iter()
+        .map(|(path, (_, info))| {
+            let root = extracted_contents
+                .root_of(&path)
+                .expect("selected files are part of a root");
+
+            (root.output_path(&path, ignore_top_level), info)
+        })
+        .collect();
+    let searched = if ignore_top_level {
+        vec![PathBuf::new()]
+    } else {
+        extracted_contents
+            .roots()
+            .iter()
+            .map(|root| root.output_dir.clone())
+            .collect()
+    };
+
+    OutputDiff::compare(output_dir, &searched, &expected, |file, info| {
+        Ok(util::crc32(file)? == info.crc32)
+    })
+}
+
+/// The index and [`EntryInfo`] of each selected file within a jar, by path
+/// relative to the jar's top-level directory (if any).
+type EntryInfos = BTreeMap<PathBuf, (usize, EntryInfo)>;
+
+/// Opens `jar_file`, returning the archive along with the [`EntryInfos`] of
+/// the files which are part of the `extracted_contents`.
+fn entry_infos(
+    jar_file: &Path,
+    extracted_contents: &ExtractedContents,
+) -> Result<(ZipArchive<File>, EntryInfos), ExtractError> {
+    let (mut archive, top_level_dir) = open_archive(jar_file)?;
+
+    let mut files = BTreeMap::new();
+
+    for i in 0..archive.len() {
+        let file = archive
+            .by_index_raw(i)
+            .map_err(ExtractError::zip(jar_file))?;
 
         if file.is_dir() {
-            fs::create_dir_all(&output_path)?;
-        } else {
-            if let Some(parent) = output_path.parent() {
-                fs::create_dir_all(parent)?;
+            continue;
+        }
+
+        if let Some(path) = file.enclosed_name()
+            && let Some((path, _)) =
+                selected_path(&path, top_level_dir.as_deref(), extracted_contents)
+        {
+            let info = EntryInfo {
+                crc32: file.crc32(),
+                size: file.size(),
+            };
+            files.insert(path.to_owned(), (i, info));
+        }
+    }
+
+    Ok((archive, files))
+}
+
+/// Whether the entry at `a_index` within jar file `a` has the same
+/// decompressed contents as the entry at `b_index` within jar file `b`.
+///
+/// The entries must have the same size.
+fn same_contents(
+    a_jar: &Path,
+    a_archive: &mut ZipArchive<File>,
+    a_index: usize,
+    b_jar: &Path,
+    b_archive: &mut ZipArchive<File>,
+    b_index: usize,
+) -> Result<bool, ExtractError> {
+    let read_error = |jar_file: &Path| {
+        let path = jar_file.to_owned();
+        move |error: io::Error| ExtractError::Zip {
+            path,
+            source: error.into(),
+        }
+    };
+
+    let mut a_file = a_archive
+        .by_index(a_index)
+        .map_err(ExtractError::zip(a_jar))?;
+    let mut b_file = b_archive
+        .by_index(b_index)
+        .map_err(ExtractError::zip(b_jar))?;
+
+    let (mut a_buffer, mut b_buffer) = ([0; 8192], [0; 8192]);
+
+    loop {
+        let read = a_file.read(&mut a_buffer).map_err(read_error(a_jar))?;
+        if read == 0 {
+            // Reading to the end of `b` too checks its checksum.
+            return Ok(b_file.read(&mut b_buffer).map_err(read_error(b_jar))? == 0);
+        }
+
+        b_file
+            .read_exact(&mut b_buffer[..read])
+            .map_err(read_error(b_jar))?;
+
+        if a_buffer[..read] != b_buffer[..read] {
+            return Ok(false);
+        }
+    }
+}
+
+/// Extracts the entries of `jar_file` which are part of the
+/// [contents](ExtractOptions::contents) into the
+/// [output directory](ExtractOptions::output_dir), reporting progress to
+/// `progress`.
+///
+/// Entries are placed in `assets`/`data` directories within the output
+/// directory, unless [`ignore_top_level`](ExtractOptions::ignore_top_level) is
+/// set. Entries which can't be read or written are
+/// [reported as failures](ProgressSink::file_failed), rather than aborting the
+/// extraction, unless the output volume is full: the extraction then stops,
+/// with [`disk_full`](crate::progress::Summary::disk_full) set. Of several
+/// entries with the same name, one is extracted as set by
+/// [`duplicate_entries`](ExtractOptions::duplicate_entries), with a
+/// [warning](ProgressSink::file_warning).
+///
+/// # Examples
+/// ```no_run
+/// use extract_minecraft_assets::jar::{self, ExtractedContents};
+/// use extract_minecraft_assets::options::ExtractOptions;
+/// use extract_minecraft_assets::progress::NoProgress;
+/// use std::path::Path;
+///
+/// let options = ExtractOptions::new()
+///     .output("extracted")
+///     .contents(ExtractedContents::all());
+///
+/// let report = jar::extract_jar(
+///     Path::new(".minecraft/versions/1.20.1/1.20.1.jar"),
+///     &options,
+///     &mut NoProgress,
+/// )?;
+/// println!("Extracted {report}");
+/// # Ok::<(), extract_minecraft_assets::error::ExtractError>(())
+/// ```
+pub fn extract_jar(
+    jar_file: &Path,
+    options: &ExtractOptions,
+    progress: &mut dyn ProgressSink,
+) -> Result<ExtractionReport, ExtractError> {
+    extract_jar_filtered(jar_file, options, progress, |_| true)
+}
+
+/// Extracts the entries of `jar_file` which are part of the
+/// [contents](ExtractOptions::contents), [included](ExtractOptions::includes)
+/// and for which `filter` returns true.
+///
+/// `filter` is given the path of the entry relative to the jar's top-level
+/// directory (if any), e.g. `assets/minecraft/lang/en_us.json`.
+pub fn extract_jar_filtered(
+    jar_file: &Path,
+    options: &ExtractOptions,
+    progress: &mut dyn ProgressSink,
+    filter: impl Fn(&Path) -> bool,
+) -> Result<ExtractionReport, ExtractError> {
+    let file = open_file_retrying(jar_file, &options.locked_retry)?;
+
+    extract_jar_from(jar_file, file, options, progress, filter)
+}
+
+/// Extracts the entries of the jar file `jar_file` read from `reader`, like
+/// [`extract_jar_filtered`], e.g. from a
+/// [remote jar file](crate::remote::RangeReader).
+///
+/// `jar_file` is only used in errors. Only the central directory and the
+/// entries extracted are read from `reader`.
+pub fn extract_jar_from<R: Read + Seek>(
+    jar_file: &Path,
+    mut reader: R,
+    options: &ExtractOptions,
+    progress: &mut dyn ProgressSink,
+    filter: impl Fn(&Path) -> bool,
+) -> Result<ExtractionReport, ExtractError> {
+    let filter = |path: &Path| filter(path) && options.includes(path);
+    let extracted_contents = &options.contents;
+    let files: Vec<_> = list_files_from(jar_file, &mut reader, extracted_contents)?
+        .into_iter()
+        .filter(|(path, _)| filter(path))
+        .collect();
+    options.check_strip_namespace(files.iter().filter_map(|(path, _)| {
+        let output_path = extracted_contents
+            .root_of(path)?
+            .output_path(path, options.ignore_top_level);
+
+        Some((
+            path.clone(),
+            options.relative_output_path(output_path, false),
+        ))
+    }))?;
+
+    let unsafe_names = unsafe_name_warnings(jar_file, &mut reader)?;
+    let (reader, duplicates) = resolve_duplicates(jar_file, reader, options, filter)?;
+
+    let (total_files, total_bytes) = (files.len(), files.iter().map(|(_, size)| size).sum());
+    let mut tally = Tally::begin(progress, options, FileSource::Jar, total_files, total_bytes);
+
+    if extracted_contents.is_empty() {
+        return Ok(tally.finish());
+    }
+
+    for (path, message) in duplicate_warnings(duplicates, options).chain(unsafe_names) {
+        tally.file_warning(&path, message);
+    }
+
+    let mut assets = JarAssets::new(jar_file, reader, extracted_contents.clone())?.filter(filter);
+    let modes = UnixModes::default();
+    let mut remaining_bytes = total_bytes;
+
+    loop {
+        if options.is_cancelled() {
+            return Ok(tally.cancel());
+        }
+
+        let mut entry = match assets.next_entry() {
+            Ok(Some(entry)) => entry,
+            Ok(None) => break,
+            Err(ExtractError::ReadEntry {
+                path: jar,
+                entry,
+                source,
+            }) => {
+                let path = match extracted_contents.root_of(&entry) {
+                    Some(root) => options.relative_output_path(
+                        root.output_path(&entry, options.ignore_top_level),
+                        false,
+                    ),
+                    None => entry.clone(),
+                };
+                let error = ExtractError::ReadEntry {
+                    path: jar,
+                    entry,
+                    source,
+                };
+                tally.file_failed(&path, &error);
+
+                continue;
             }
+            Err(error) => return Err(error),
+        };
+        // Directories are only created to contain files when files are
+        // grouped, as they would be grouped apart from them.
+        if entry.is_dir() && options.group_by.is_some() {
+            continue;
+        }
+
+        // Including this entry, in case the output volume is full.
+        let remaining = remaining_bytes;
+        remaining_bytes = remaining_bytes.saturating_sub(entry.size());
+
+        let original_path = options
+            .relative_output_path(entry.output_path(options.ignore_top_level), entry.is_dir());
+        let transform = Transform::of(options, entry.path(), entry.size());
+        // A transformed file is written, and so skipped, under its new name.
+        let path = match transform {
+            Some(transform) => transform.output_path(&original_path),
+            None => original_path.clone(),
+        };
+        let output_path = options.output_dir.join(&path);
 
-            // Copy the file
-            let mut output_file = File::create(&output_path)?;
-            io::copy(&mut file, &mut output_file)?;
+        match options.make_way(&output_path, entry.is_dir()) {
+            Ok(true) => {}
+            Ok(false) => {
+                tally.file_skipped(&path);
+                continue;
+            }
+            Err(error) => {
+                tally.file_failed(&path, &error);
+                continue;
+            }
         }
 
-        // Set the file permissions on unix
-        #[cfg(unix)]
-        if let Some(mode) = file.unix_mode() {
-            use fs::Permissions;
-            use std::os::unix::fs::PermissionsExt;
+        if entry.is_dir() {
+            match options
+                .vfs
+                .create_dir_all(&output_path)
+                .map_err(ExtractError::write_file(&output_path))
+                .and_then(|()| modes.set(options, &output_path, entry.unix_mode()))
+            {
+                Ok(mode) => tally.mode_set(&path, mode),
+                Err(error) if error.is_disk_full() => {
+                    return Ok(tally.disk_full(&path, &error, remaining));
+                }
+                Err(error) => tally.file_failed(&path, &error),
+            }
+        } else if let Some(transform) = transform {
+            tally.file_started(&path);
+
+            let unix_mode = entry.unix_mode();
+            let mut contents = Vec::new();
+            if let Err(error) = entry.read_to_end(&mut contents) {
+                let error = ExtractError::ReadEntry {
+                    path: jar_file.to_owned(),
+                    entry: entry.path().to_owned(),
+                    source: error.into(),
+                };
+                tally.file_failed(&path, &error);
+
+                continue;
+            }
+
+            let transformed = transform.apply(&original_path, contents, options.keep_original);
+            if let Some(message) = transformed.warning {
+                tally.file_warning(&original_path, message);
+            }
+            tally.bytes_saved(transformed.bytes_saved);
+            tally.files_normalized(transformed.normalized);
+
+            for (path, contents) in transformed.files {
+                let output_path = options.output_dir.join(&path);
+
+                match write_file(&*options.vfs, &mut contents.as_slice(), &output_path)
+                    .and_then(|bytes| Ok((bytes, modes.set(options, &output_path, unix_mode)?)))
+                {
+                    Ok((bytes, mode)) => {
+                        tally.mode_set(&path, mode);
+                        tally.file_done(&path, bytes);
+                        if let Some(reason) = validate::check(options, &path, &contents) {
+                            tally.file_invalid(&path, reason);
+                        }
+                    }
+                    Err(error) if error.is_disk_full() => {
+                        vfs::remove_partial_file(&*options.vfs, &output_path);
+                        return Ok(tally.disk_full(&path, &error, remaining));
+                    }
+                    Err(error) => tally.file_failed(&path, &error),
+                }
+            }
+        } else {
+            tally.file_started(&path);
 
-            fs::set_permissions(&output_path, Permissions::from_mode(mode))?;
+            let unix_mode = entry.unix_mode();
+            let mut contents =
+                CheckingReader::new(&mut entry, validate::content_check(options, &path));
+            match write_file(&*options.vfs, &mut contents, &output_path)
+                .and_then(|bytes| Ok((bytes, modes.set(options, &output_path, unix_mode)?)))
+            {
+                Ok((bytes, mode)) => {
+                    tally.mode_set(&path, mode);
+                    tally.file_done(&path, bytes);
+                    if let Some(reason) = contents.finish() {
+                        tally.file_invalid(&path, reason);
+                    }
+                }
+                Err(error) if error.is_disk_full() => {
+                    vfs::remove_partial_file(&*options.vfs, &output_path);
+                    return Ok(tally.disk_full(&path, &error, remaining));
+                }
+                Err(error) => tally.file_failed(&path, &error),
+            }
         }
     }
 
-    Ok(())
+    Ok(tally.finish())
+}
+
+/// Copies `contents` to a new file at `output_path`, creating its parent
+/// directories, and returns the number of bytes written.
+fn write_file(
+    vfs: &dyn Vfs,
+    contents: &mut dyn Read,
+    output_path: &Path,
+) -> Result<u64, ExtractError> {
+    if let Some(parent) = output_path.parent() {
+        vfs.create_dir_all(parent).writing(parent)?;
+    }
+
+    // Copy the file
+    vfs.write_file(output_path, contents).writing(output_path)
 }
 
-impl ExtractCmd for JarSubcommand {
-    fn execute(self, output_dir: PathBuf, ignore_top_level: bool) -> io::Result<()> {
-        extract_jar(
-            &self.jar_file,
-            &output_dir,
-            self.extracted_contents,
-            ignore_top_level,
-        )
+/// Sets the unix permissions of the files and directories of an extraction.
+///
+/// After the first failure, the output is assumed not to support
+/// permissions, so none are set anymore.
+#[derive(Default, Debug)]
+pub(crate) struct UnixModes {
+    unsupported: AtomicBool,
+}
+
+/// The outcome of [setting the permissions](UnixModes::set) of a file or
+/// directory.
+#[derive(Debug)]
+pub(crate) enum ModeOutcome {
+    /// The permissions were set, or there were none to set.
+    Set,
+    /// The permissions weren't set, with a warning if they failed to be.
+    NotSet(Option<String>),
+}
+
+impl UnixModes {
+    /// Sets the permissions of the file or directory at `output_path` to
+    /// `unix_mode`, if it is known.
+    ///
+    /// Fails only if permissions are [strict](ExtractOptions::strict_permissions).
+    pub(crate) fn set(
+        &self,
+        options: &ExtractOptions,
+        output_path: &Path,
+        unix_mode: Option<u32>,
+    ) -> Result<ModeOutcome, ExtractError> {
+        let Some(mode) = unix_mode else {
+            return Ok(ModeOutcome::Set);
+        };
+        if self.unsupported.load(Ordering::Relaxed) {
+            return Ok(ModeOutcome::NotSet(None));
+        }
+
+        match options.vfs.set_unix_mode(output_path, mode) {
+            Ok(()) => Ok(ModeOutcome::Set),
+            Err(error) if options.strict_permissions => {
+                Err(ExtractError::write_file(output_path)(error))
+            }
+            Err(error) => {
+                // Only the first failure is warned about.
+                let first = !self.unsupported.swap(true, Ordering::Relaxed);
+
+                Ok(ModeOutcome::NotSet(first.then(|| {
+                    format!("failed to set permissions, so none are set from now on: {error}")
+                })))
+            }
+        }
     }
 }
@@ -0,0 +1,718 @@
+//! Merging the language files of every namespace, from both jar files and
+//! index files, into one file per locale, and converting language files
+//! between formats.
+//!
+//! Language files are found at `assets/<namespace>/lang/<locale>.json`, e.g.
+//! `assets/realms/lang/de_de.json`. `en_us` is usually only found in the jar
+//! file, and every other locale only in the index file. Versions before 1.13
+//! use `.lang` files of `key=value` lines instead, which can be
+//! [converted](convert) to and from JSON.
+
+use crate::error::ExtractError;
+use crate::hashed::{self, IndexFile};
+use crate::jar::{ContentRoot, ExtractedContents, JarAssets};
+use crate::locked::LockedRetry;
+use crate::search::FileSource;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Formatter};
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+use std::{fmt, str};
+use thiserror::Error;
+
+/// Collects language files to [merge](LangMerger::merge) them by locale.
+///
+/// Namespaces are layered in alphabetical order, so a key defined by both
+/// `minecraft` and `realms` takes the value from `realms`, and the two
+/// values are reported as a [`Conflict`] if they differ. Within a namespace,
+/// a file from an index file replaces one from a jar file, as when a version
+/// is extracted.
+///
+/// # Examples
+/// ```
+/// use extract_minecraft_assets::lang::LangMerger;
+/// use extract_minecraft_assets::search::FileSource;
+///
+/// let mut merger = LangMerger::new();
+/// merger.add(
+///     "minecraft/lang/de_de.json".as_ref(),
+///     FileSource::Hashed,
+///     br#"{"menu.quit": "Spiel beenden", "menu.options": "Optionen"}"#,
+/// );
+/// merger.add(
+///     "realms/lang/de_de.json".as_ref(),
+///     FileSource::Hashed,
+///     br#"{"menu.quit": "Beenden"}"#,
+/// );
+///
+/// let merged = merger.merge();
+///
+/// assert_eq!(merged[0].locale, "de_de");
+/// assert_eq!(merged[0].entries["menu.quit"], "Beenden");
+/// assert_eq!(merged[0].conflicts[0].key, "menu.quit");
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct LangMerger {
+    /// The locales to merge, or every locale if [`None`].
+    locales: Option<BTreeSet<String>>,
+    /// The entries of each language file, by locale and then by namespace.
+    files: BTreeMap<String, BTreeMap<String, BTreeMap<String, String>>>,
+    /// The language files which couldn't be read or parsed.
+    errors: Vec<LangError>,
+}
+
+/// The language files of a locale, merged.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MergedLocale {
+    /// The name of the locale, e.g. `en_us`.
+    pub locale: String,
+    /// The merged keys and values, sorted by key.
+    pub entries: BTreeMap<String, String>,
+    /// The keys given different values by different namespaces, sorted by
+    /// key.
+    pub conflicts: Vec<Conflict>,
+}
+
+/// A key given different values by different namespaces.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Conflict {
+    pub key: String,
+    /// The value given by each namespace, in the order they are layered: the
+    /// last value is the one merged.
+    pub values: Vec<NamespaceValue>,
+}
+
+/// The value a namespace gives a key.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NamespaceValue {
+    pub namespace: String,
+    pub value: String,
+}
+
+/// A language file which couldn't be read or parsed, and was left out of the
+/// merge.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LangError {
+    /// The path of the file within `assets`.
+    pub path: PathBuf,
+    pub source: FileSource,
+    /// A description of the error.
+    pub message: String,
+}
+
+impl LangMerger {
+    /// Returns a merger of every locale, without any files.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only merges the given `locales`, e.g. `de_de`.
+    pub fn locales(mut self, locales: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.locales = Some(locales.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Returns the namespace and locale of the language file at `path`,
+    /// relative to `assets`, if it is one of the locales to merge.
+    fn lang_file<'p>(&self, path: &'p Path) -> Option<(&'p str, &'p str)> {
+        let components: Vec<_> = path.components().collect();
+        let [
+            Component::Normal(namespace),
+            Component::Normal(lang),
+            Component::Normal(file_name),
+        ] = components.as_slice()
+        else {
+            return None;
+        };
+
+        let locale = file_name.to_str()?.strip_suffix(".json")?;
+        if *lang != "lang"
+            || self
+                .locales
+                .as_ref()
+                .is_some_and(|locales| !locales.contains(locale))
+        {
+            return None;
+        }
+
+        Some((namespace.to_str()?, locale))
+    }
+
+    /// Adds the language file at `path`, relative to `assets`, containing
+    /// `contents`.
+    ///
+    /// Files which aren't language files of the locales to merge are ignored,
+    /// and files which aren't JSON objects of strings are recorded in
+    /// [`errors`](LangMerger::errors).
+    pub fn add(&mut self, path: &Path, source: FileSource, contents: &[u8]) {
+        let Some((namespace, locale)) = self.lang_file(path) else {
+            return;
+        };
+
+        match serde_json::from_slice(contents) {
+            Ok(entries) => {
+                self.files
+                    .entry(locale.to_owned())
+                    .or_default()
+                    .insert(namespace.to_owned(), entries);
+            }
+            Err(error) => self.errors.push(LangError {
+                path: path.to_owned(),
+                source,
+                message: error.to_string(),
+            }),
+        }
+    }
+
+    /// Adds the language files within the `assets` of `jar_file`.
+    pub fn add_jar(&mut self, jar_file: &Path) -> Result<(), ExtractError> {
+        let mut assets = JarAssets::open(jar_file, ExtractedContents::assets())?.filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "json")
+        });
+
+        while let Some(mut entry) = assets.next_entry()? {
+            let path = entry.output_path(true);
+            if entry.is_dir() || self.lang_file(&path).is_none() {
+                continue;
+            }
+
+            let mut contents = Vec::new();
+            match entry.read_to_end(&mut contents) {
+                Ok(_) => self.add(&path, FileSource::Jar, &contents),
+                Err(error) => self.errors.push(LangError {
+                    path,
+                    source: FileSource::Jar,
+                    message: error.to_string(),
+                }),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Adds the language files listed in `index`, reading them from the
+    /// `objects` directory within `hashed_assets_dir`.
+    pub fn add_index(&mut self, hashed_assets_dir: &Path, index: &IndexFile) {
+        let objects_dir = hashed_assets_dir.join("objects");
+
+        let mut objects: Vec<_> = index
+            .objects
+            .iter()
+            .filter(|(path, _)| self.lang_file(path).is_some())
+            .collect();
+        objects.sort_by_key(|(path, _)| *path);
+
+        for (path, object) in objects {
+            match hashed::read_object(
+                path,
+                &objects_dir.join(object.hashed_file_path()),
+                &LockedRetry::default(),
+            ) {
+                Ok(contents) => self.add(path, FileSource::Hashed, &contents),
+                Err(error) => self.errors.push(LangError {
+                    path: path.clone(),
+                    source: FileSource::Hashed,
+                    message: error.to_string(),
+                }),
+            }
+        }
+    }
+
+    /// The language files which couldn't be read or parsed.
+    pub fn errors(&self) -> &[LangError] {
+        &self.errors
+    }
+
+    /// Merges the language files of each locale, sorted by locale.
+    pub fn merge(&self) -> Vec<MergedLocale> {
+        self.files
+            .iter()
+            .map(|(locale, namespaces)| {
+                let mut values: BTreeMap<&str, Vec<NamespaceValue>> = BTreeMap::new();
+                for (namespace, entries) in namespaces {
+                    for (key, value) in entries {
+                        values.entry(key).or_default().push(NamespaceValue {
+                            namespace: namespace.clone(),
+                            value: value.clone(),
+                        });
+                    }
+                }
+
+                let mut merged = MergedLocale {
+                    locale: locale.clone(),
+                    entries: BTreeMap::new(),
+                    conflicts: Vec::new(),
+                };
+                for (key, values) in values {
+                    let last = values.last().expect("keys have at least one value");
+                    merged.entries.insert(key.to_owned(), last.value.clone());
+
+                    if values.iter().any(|value| value.value != last.value) {
+                        merged.conflicts.push(Conflict {
+                            key: key.to_owned(),
+                            values,
+                        });
+                    }
+                }
+
+                merged
+            })
+            .collect()
+    }
+}
+
+/// The path of the list of locales within the `assets` of a jar file, as
+/// `<code>=<name>` lines.
+pub const LOCALE_LIST_FILE: &str = "minecraft/lang/_list";
+
+/// A locale available in a version, as listed by [`LocaleList`].
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Locale {
+    /// The code of the locale, as in the name of its language file, e.g.
+    /// `de_de`, or `de_DE` in older versions.
+    pub code: String,
+    /// The name of the language, e.g. `Deutsch`, if it is listed anywhere.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// The region of the language, e.g. `Deutschland`, if it is listed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+    /// The path of the language file within `assets`, e.g.
+    /// `minecraft/lang/de_de.json`.
+    pub path: PathBuf,
+    /// The size of the language file in bytes.
+    pub size: u64,
+    pub source: FileSource,
+}
+
+/// Collects the locales available in a version, i.e. the language files of
+/// the `minecraft` namespace, along with the names of their languages.
+///
+/// Names are read from the `language` section of `pack.mcmeta`, in the jar
+/// file or the index file, or else from the jar's [`LOCALE_LIST_FILE`]. As
+/// when a version is extracted, a language file from an index file replaces
+/// one from a jar file. Codes are matched regardless of case, as older
+/// versions use codes like `en_US`.
+///
+/// # Examples
+/// ```
+/// use extract_minecraft_assets::lang::LocaleList;
+/// use extract_minecraft_assets::search::FileSource;
+///
+/// let mut list = LocaleList::new();
+/// list.add_file("minecraft/lang/de_de.json".as_ref(), 1234, FileSource::Hashed);
+/// list.add_pack_mcmeta(br#"{"language": {"de_de": {"name": "Deutsch", "region": "Deutschland"}}}"#);
+///
+/// let locales = list.locales();
+///
+/// assert_eq!(locales[0].name.as_deref(), Some("Deutsch"));
+/// assert!(list.contains("DE_DE"));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct LocaleList {
+    /// The language file of each locale, by lowercase code.
+    files: BTreeMap<String, Locale>,
+    /// The name and region of each listed locale, by lowercase code.
+    names: BTreeMap<String, (String, Option<String>)>,
+}
+
+/// An entry of the `language` section of `pack.mcmeta`.
+#[derive(Deserialize)]
+struct LanguageInfo {
+    name: String,
+    region: Option<String>,
+}
+
+/// The parts of `pack.mcmeta` listing the locales.
+#[derive(Deserialize)]
+struct LanguagePack {
+    #[serde(default)]
+    language: BTreeMap<String, LanguageInfo>,
+}
+
+impl LocaleList {
+    /// Returns a list without any locales.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the file at `path`, relative to `assets`, if it is a language
+    /// file of the `minecraft` namespace.
+    pub fn add_file(&mut self, path: &Path, size: u64, source: FileSource) {
+        let Ok(within_lang) = path.strip_prefix("minecraft/lang") else {
+            return;
+        };
+        if within_lang.components().count() != 1 {
+            return;
+        }
+        let Some(code) = within_lang.to_str().and_then(|name| {
+            name.strip_suffix(".json")
+                .or_else(|| name.strip_suffix(".lang"))
+        }) else {
+            return;
+        };
+
+        self.files.insert(
+            code.to_lowercase(),
+            Locale {
+                code: code.to_owned(),
+                name: None,
+                region: None,
+                path: path.to_owned(),
+                size,
+                source,
+            },
+        );
+    }
+
+    /// Adds the names of the locales listed in the `language` section of
+    /// `contents`, a `pack.mcmeta` file.
+    ///
+    /// Files which aren't valid are ignored, as the names are only a
+    /// convenience.
+    pub fn add_pack_mcmeta(&mut self, contents: &[u8]) {
+        let Ok(pack) = serde_json::from_slice::<LanguagePack>(contents) else {
+            return;
+        };
+
+        for (code, info) in pack.language {
+            self.names
+                .insert(code.to_lowercase(), (info.name, info.region));
+        }
+    }
+
+    /// Adds the names of the locales listed in `contents`, a
+    /// [`LOCALE_LIST_FILE`] of `<code>=<name>` lines.
+    ///
+    /// Blank lines, lines starting with `#` and lines without `=` are
+    /// ignored.
+    pub fn add_list(&mut self, contents: &str) {
+        for line in contents.lines().map(str::trim) {
+            if line.starts_with('#') {
+                continue;
+            }
+            if let Some((code, name)) = line.split_once('=') {
+                self.names
+                    .insert(code.trim().to_lowercase(), (name.trim().to_owned(), None));
+            }
+        }
+    }
+
+    /// Adds the language files within the `assets` of `jar_file`, along with
+    /// the names listed in its `pack.mcmeta` and [`LOCALE_LIST_FILE`].
+    pub fn add_jar(&mut self, jar_file: &Path) -> Result<(), ExtractError> {
+        let contents =
+            ExtractedContents::assets().with(ContentRoot::new("pack.mcmeta", "pack.mcmeta", "."));
+        let mut entries = JarAssets::open(jar_file, contents)?;
+
+        while let Some(mut entry) = entries.next_entry()? {
+            if entry.is_dir() {
+                continue;
+            }
+
+            let path = entry.output_path(true);
+            if path == Path::new("pack.mcmeta") || path == Path::new(LOCALE_LIST_FILE) {
+                let mut contents = Vec::new();
+                entry
+                    .read_to_end(&mut contents)
+                    .map_err(|error| ExtractError::Zip {
+                        path: jar_file.to_owned(),
+                        source: error.into(),
+                    })?;
+
+                if path == Path::new(LOCALE_LIST_FILE) {
+                    self.add_list(&String::from_utf8_lossy(&contents));
+                } else {
+                    self.add_pack_mcmeta(&contents);
+                }
+            } else {
+                self.add_file(&path, entry.size(), FileSource::Jar);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Adds the language files listed in `index`, along with the names listed
+    /// in its `pack.mcmeta`, reading it from the `objects` directory within
+    /// `hashed_assets_dir`.
+    ///
+    /// A `pack.mcmeta` which can't be read is ignored, as with one which
+    /// isn't valid.
+    pub fn add_index(&mut self, hashed_assets_dir: &Path, index: &IndexFile) {
+        for (path, object) in &index.objects {
+            self.add_file(path, object.size, FileSource::Hashed);
+        }
+
+        if let Some(object) = index.objects.get(Path::new("pack.mcmeta")) {
+            let object_path = hashed_assets_dir
+                .join("objects")
+                .join(object.hashed_file_path());
+
+            if let Ok(contents) = hashed::read_object(
+                Path::new("pack.mcmeta"),
+                &object_path,
+                &LockedRetry::default(),
+            ) {
+                self.add_pack_mcmeta(&contents);
+            }
+        }
+    }
+
+    /// Whether the locale `code` is available, regardless of case.
+    pub fn contains(&self, code: &str) -> bool {
+        self.files.contains_key(&code.to_lowercase())
+    }
+
+    /// The available locales with the names of their languages, sorted by
+    /// code.
+    pub fn locales(&self) -> Vec<Locale> {
+        self.files
+            .iter()
+            .map(|(code, locale)| {
+                let (name, region) = self.names.get(code).cloned().unzip();
+
+                Locale {
+                    name,
+                    region: region.flatten(),
+                    ..locale.clone()
+                }
+            })
+            .collect()
+    }
+}
+
+/// The format of a language file.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LangFormat {
+    /// A JSON object of strings, used since 1.13.
+    Json,
+    /// `key=value` lines, used before 1.13.
+    Lang,
+}
+
+impl LangFormat {
+    /// The extension of language files of this format, e.g. `json`.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Lang => "lang",
+        }
+    }
+
+    /// Returns the format of the language file at `path`, relative to
+    /// `assets`, if it is one: a file in a namespace's `lang` directory, e.g.
+    /// `minecraft/lang/en_us.lang`.
+    pub fn of(path: &Path) -> Option<Self> {
+        let components: Vec<_> = path.components().collect();
+        let [
+            Component::Normal(_),
+            Component::Normal(lang),
+            Component::Normal(_),
+        ] = components.as_slice()
+        else {
+            return None;
+        };
+        if *lang != "lang" {
+            return None;
+        }
+
+        match path.extension()?.to_str()? {
+            "json" => Some(Self::Json),
+            "lang" => Some(Self::Lang),
+            _ => None,
+        }
+    }
+}
+
+/// Displays the extension of the format, e.g. `json`.
+impl Display for LangFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.extension())
+    }
+}
+
+/// An error while converting a language file, in which case the file should
+/// be kept as it is.
+#[derive(Error, Debug)]
+#[non_exhaustive]
+pub enum ConvertLangError {
+    /// The file isn't valid UTF-8.
+    #[error("invalid UTF-8: {0}")]
+    Utf8(#[from] str::Utf8Error),
+    /// A line of a `.lang` file is neither blank, a comment, nor a
+    /// `key=value` pair.
+    #[error("line {line} has no '=' separating its key and value")]
+    MissingSeparator { line: usize },
+    /// A line of a `.lang` file has a `\u` escape which isn't four
+    /// hexadecimal digits of a character.
+    #[error("line {line} has an invalid unicode escape")]
+    InvalidEscape { line: usize },
+    /// A JSON language file isn't a JSON object of strings.
+    #[error("invalid JSON: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// Parses the `key=value` lines of a `.lang` file.
+///
+/// Blank lines and lines starting with `#` are ignored, and a line is split at
+/// its first `=` which isn't escaped. Keys and values may escape characters
+/// with a backslash: `\n`, `\r` and `\t` are a line feed, carriage return
+/// and tab, `\uXXXX` is the character with that code, and any other escaped
+/// character (e.g. `\=` or `\\`) is itself. A key given more than once takes
+/// its last value.
+///
+/// # Examples
+/// ```
+/// use extract_minecraft_assets::lang;
+///
+/// let entries = lang::parse_lang(
+///     "# Menus\n\nmenu.quit=Quit Game\ndeath.attack.anvil=%1$s was squashed\\nby an anvil\n",
+/// )?;
+///
+/// assert_eq!(entries["menu.quit"], "Quit Game");
+/// assert_eq!(entries["death.attack.anvil"], "%1$s was squashed\nby an anvil");
+/// # Ok::<(), extract_minecraft_assets::lang::ConvertLangError>(())
+/// ```
+pub fn parse_lang(contents: &str) -> Result<BTreeMap<String, String>, ConvertLangError> {
+    let contents = contents.strip_prefix('\u{feff}').unwrap_or(contents);
+    let mut entries = BTreeMap::new();
+
+    for (index, line) in contents.lines().enumerate() {
+        let line_number = index + 1;
+        if line.trim().is_empty() || line.trim_start().starts_with('#') {
+            continue;
+        }
+
+        let (key, rest) = unescape_until(line, Some('='), line_number)?;
+        let Some(value) = rest else {
+            return Err(ConvertLangError::MissingSeparator { line: line_number });
+        };
+        let (value, _) = unescape_until(value, None, line_number)?;
+
+        entries.insert(key, value);
+    }
+
+    Ok(entries)
+}
+
+/// Unescapes `input` up to the first unescaped `separator` (if any),
+/// returning the unescaped text and what follows the separator, if it was
+/// found.
+fn unescape_until(
+    input: &str,
+    separator: Option<char>,
+    line: usize,
+) -> Result<(String, Option<&str>), ConvertLangError> {
+    let mut output = String::new();
+    let mut chars = input.char_indices();
+
+    while let Some((index, c)) = chars.next() {
+        if Some(c) == separator {
+            return Ok((output, Some(&input[index + c.len_utf8()..])));
+        }
+        if c != '\\' {
+            output.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some((_, 'n')) => output.push('\n'),
+            Some((_, 'r')) => output.push('\r'),
+            Some((_, 't')) => output.push('\t'),
+            Some((start, 'u')) => {
+                let digits = input
+                    .get(start + 1..start + 5)
+                    .ok_or(ConvertLangError::InvalidEscape { line })?;
+                let c = u32::from_str_radix(digits, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .ok_or(ConvertLangError::InvalidEscape { line })?;
+
+                output.push(c);
+                chars.nth(3);
+            }
+            Some((_, escaped)) => output.push(escaped),
+            // A trailing backslash is kept as it is.
+            None => output.push('\\'),
+        }
+    }
+
+    Ok((output, None))
+}
+
+/// Writes `entries` as the `key=value` lines of a `.lang` file, sorted by
+/// key, escaping characters so that [`parse_lang`] reads them back.
+///
+/// # Examples
+/// ```
+/// use extract_minecraft_assets::lang;
+/// use std::collections::BTreeMap;
+///
+/// let entries = BTreeMap::from([
+///     ("menu.quit".to_owned(), "Quit Game".to_owned()),
+///     ("tip".to_owned(), "Line one\nLine two".to_owned()),
+/// ]);
+///
+/// assert_eq!(lang::write_lang(&entries), "menu.quit=Quit Game\ntip=Line one\\nLine two\n");
+/// ```
+pub fn write_lang(entries: &BTreeMap<String, String>) -> String {
+    let mut output = String::new();
+
+    for (key, value) in entries {
+        for (index, c) in key.chars().enumerate() {
+            match c {
+                '=' => output.push_str("\\="),
+                '#' if index == 0 => output.push_str("\\#"),
+                c => push_escaped(&mut output, c),
+            }
+        }
+        output.push('=');
+        for c in value.chars() {
+            push_escaped(&mut output, c);
+        }
+        output.push('\n');
+    }
+
+    output
+}
+
+/// Pushes `c` to `output`, escaping backslashes and whitespace which would
+/// end or hide part of a line.
+fn push_escaped(output: &mut String, c: char) {
+    match c {
+        '\\' => output.push_str("\\\\"),
+        '\n' => output.push_str("\\n"),
+        '\r' => output.push_str("\\r"),
+        '\t' => output.push_str("\\t"),
+        c => output.push(c),
+    }
+}
+
+/// Converts the `contents` of a language file in the other format to
+/// `format`.
+///
+/// JSON files are written pretty-printed with sorted keys, and `.lang` files
+/// as by [`write_lang`].
+pub fn convert(contents: &[u8], format: LangFormat) -> Result<Vec<u8>, ConvertLangError> {
+    match format {
+        LangFormat::Json => {
+            let entries = parse_lang(str::from_utf8(contents)?)?;
+
+            Ok(serde_json::to_vec_pretty(&entries)?)
+        }
+        LangFormat::Lang => {
+            let entries: BTreeMap<String, String> = serde_json::from_slice(contents)?;
+
+            Ok(write_lang(&entries).into_bytes())
+        }
+    }
+}
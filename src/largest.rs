@@ -0,0 +1,140 @@
+//! Finding the largest files of jar files and index files, without
+//! extracting anything.
+
+use crate::category::{self, Category};
+use crate::error::ExtractError;
+use crate::hashed::IndexFile;
+use crate::jar::{self, ExtractedContents};
+use crate::search::FileSource;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Collects the sizes of files to list the [largest](LargestFiles::largest).
+///
+/// Paths are relative to the output directory, as if extracted without
+/// ignoring the top-level directory, e.g. `assets/minecraft/lang/en_us.json`.
+/// A file added at the same path as an earlier one replaces it, as hashed
+/// assets replace those of the jar file when a version is extracted.
+///
+/// Sizes are read from index files and the central directories of jar files,
+/// so nothing is decompressed.
+///
+/// # Examples
+/// ```
+/// use extract_minecraft_assets::largest::{LargeFile, LargestFiles};
+/// use extract_minecraft_assets::search::FileSource;
+///
+/// let mut files = LargestFiles::new();
+/// files.add(LargeFile::new("assets/minecraft/a.png", 100, FileSource::Jar));
+/// files.add(LargeFile::new("assets/minecraft/b.ogg", 300, FileSource::Jar));
+/// files.add(LargeFile::new("assets/minecraft/a.png", 200, FileSource::Hashed));
+///
+/// let largest = files.largest(1);
+///
+/// assert_eq!(largest.len(), 1);
+/// assert_eq!(largest[0].size, 300);
+/// assert_eq!(files.largest(2)[1].source, FileSource::Hashed);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct LargestFiles {
+    files: BTreeMap<PathBuf, LargeFile>,
+}
+
+/// A file listed by [`LargestFiles`].
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LargeFile {
+    pub path: PathBuf,
+    /// The size of the file in bytes.
+    pub size: u64,
+    pub source: FileSource,
+    /// The namespace of the file, e.g. `minecraft`, if it has one.
+    pub namespace: Option<String>,
+    pub category: Category,
+}
+
+impl LargeFile {
+    /// Returns the file at `path`, of `size` bytes, classifying it by the
+    /// part of `path` after its first component, e.g. `assets` or `data`.
+    pub fn new(path: impl Into<PathBuf>, size: u64, source: FileSource) -> Self {
+        let path = path.into();
+        let within_root: PathBuf = path.components().skip(1).collect();
+
+        Self::classified(path, &within_root, size, source)
+    }
+
+    /// Returns the file at `path`, classifying it by `within_root`, its path
+    /// relative to `assets` or `data`.
+    fn classified(path: PathBuf, within_root: &Path, size: u64, source: FileSource) -> Self {
+        Self {
+            namespace: category::namespace(within_root).map(str::to_owned),
+            category: Category::of(within_root),
+            path,
+            size,
+            source,
+        }
+    }
+}
+
+impl LargestFiles {
+    /// Returns a collection without any files.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `file`, replacing any file at the same path.
+    pub fn add(&mut self, file: LargeFile) {
+        self.files.insert(file.path.clone(), file);
+    }
+
+    /// Adds every object listed in `index`, within `assets`.
+    pub fn add_index(&mut self, index: &IndexFile) {
+        let assets_dir = Path::new("assets");
+
+        for (path, object) in &index.objects {
+            self.add(LargeFile::new(
+                assets_dir.join(path),
+                object.size,
+                FileSource::Hashed,
+            ));
+        }
+    }
+
+    /// Adds the files within `jar_file` which are part of the
+    /// `extracted_contents`.
+    pub fn add_jar(
+        &mut self,
+        jar_file: &Path,
+        extracted_contents: &ExtractedContents,
+    ) -> Result<(), ExtractError> {
+        for (path, size) in jar::list_files(jar_file, extracted_contents)? {
+            let root = extracted_contents
+                .root_of(&path)
+                .expect("listed files are part of a root");
+            let within_root = path.strip_prefix(&root.prefix).unwrap_or(&path).to_owned();
+
+            self.add(LargeFile::classified(
+                path,
+                &within_root,
+                size,
+                FileSource::Jar,
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Keeps only the files for which `keep` returns true.
+    pub fn retain(&mut self, mut keep: impl FnMut(&LargeFile) -> bool) {
+        self.files.retain(|_, file| keep(file));
+    }
+
+    /// Returns the `count` largest files, largest first.
+    pub fn largest(&self, count: usize) -> Vec<LargeFile> {
+        let mut files: Vec<_> = self.files.values().collect();
+        files.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.path.cmp(&b.path)));
+
+        files.into_iter().take(count).cloned().collect()
+    }
+}
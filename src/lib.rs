@@ -0,0 +1,153 @@
+//! Extracts Minecraft `assets` or `data`.
+//!
+//! Most Minecraft assets are located within a version's jar file, while some
+//! (like sounds or non-US-English languages) are found hashed in
+//! `.minecraft/assets/`. This crate can extract `assets` or `data` from either
+//! location:
+//!
+//! - [`jar::extract_jar`] extracts from a version's jar file.
+//! - [`hashed::extract_hashed_assets`] extracts hashed assets using an
+//!   [index file](hashed::IndexFile).
+//! - [`backup::AssetsBackup`] extracts hashed assets straight out of a
+//!   backup of `.minecraft/assets` in a zip or tar archive.
+//! - With the `async` feature, `asynchronous` provides async versions of both
+//!   of the above which report progress through a channel.
+//! - With the `remote` feature, `remote::RangeReader` reads a jar file from
+//!   a URL, fetching only the parts which are extracted.
+//! - [`diff::Diff`] compares the files listed in two index files or jar
+//!   files, and [`diff::OutputDiff`] compares an output directory with its
+//!   source.
+//! - [`stats::Stats`] counts the files of a jar file or index file by
+//!   namespace and by [category](category::Category),
+//!   [`duplicates::DuplicateFinder`] finds the files with identical contents,
+//!   and [`largest::LargestFiles`] lists the largest files.
+//! - [`usage::UsageEstimate`] estimates how many files and bytes an
+//!   extraction would write, and how much space they would take up on disk.
+//! - [`catalog::Catalog`] lists the files written by an extraction by
+//!   namespace and category, from its [report](report::ExtractionReport).
+//! - [`search`] finds the files whose paths match
+//!   [patterns](filter::PatternSet), which can also select the files to
+//!   extract.
+//! - [`lang::LangMerger`] merges the language files of every namespace into
+//!   one file per locale, [`lang::LocaleList`] lists the locales available
+//!   in a version, and [`lang::convert`] converts language files between
+//!   `.lang` and JSON.
+//! - [`libraries::copy_library`] copies the libraries a version needs to
+//!   run, as listed in its [manifest](version::VersionManifest), out of
+//!   `.minecraft/libraries/`, checking their SHA-1 hashes.
+//! - [`format::format_json`] pretty-prints or minifies JSON files, which
+//!   extraction can do as files are written, along with converting language
+//!   files, as set by the [options](options::ExtractOptions::format_json).
+//! - [`text::normalize`] normalizes the line endings of text files, which
+//!   extraction can do too, as set by the
+//!   [options](options::ExtractOptions::text_normalize).
+//! - [`validate::validate`] checks that files look like the formats their
+//!   extensions claim, which extraction can do as files are written, as set
+//!   by the [options](options::ExtractOptions::validate_content).
+//! - [`closure::resolve`] finds the files referenced by other files across a
+//!   jar file and an index file, e.g. every file used by the
+//!   [fonts](fonts::fonts_closure) or by [models](models::models_closure).
+//! - [`references::check`] finds the references between the assets of a
+//!   directory shaped like a resource pack which point at missing files.
+//! - [`sounds::SoundEvents`] organizes extracted sound files by the sound
+//!   events which play them.
+//! - [`pack::pack_mcmeta`] and [`pack::place_in_overlays`] shape an output
+//!   directory as a resource pack, with assets in
+//!   [overlays](pack::Overlay) for ranges of pack formats.
+//! - [`redistributable::Redistributable`] leaves out the files which can't
+//!   be redistributed, such as textures and sounds.
+//! - [`pool::ContentPool`] deduplicates identical files across output
+//!   directories, e.g. of every version, by hard linking them against a
+//!   content-addressed pool.
+//! - [`sync::plan`] finds the files in an output directory which the source
+//!   it was extracted from no longer has, to mirror the output to it.
+//! - [`clean::plan`] finds the files written by an extraction, as recorded
+//!   in its report, which haven't been modified since, to undo it.
+//! - [`lookup::HashLookup`] finds the index files and versions which use a
+//!   hashed file, and [`lookup::list_indexes`] the versions which use each
+//!   index file.
+//! - [`options::ExtractOptions`] configures an extraction for both of the
+//!   above.
+//! - [`version::Version`] locates a version's jar file, manifest and index file.
+//! - [`vfs::Vfs`] abstracts the filesystem extracted files are written to,
+//!   e.g. to extract into memory with [`vfs::MemoryFs`].
+//! - [`report::ExtractionReport`] describes what happened during an
+//!   extraction.
+//! - [`error::ExtractError`] describes what went wrong during an extraction.
+//! - [`util`] finds the default locations of the `.minecraft` directory and
+//!   its contents, with the `discovery` feature.
+//!
+//! The `cli` feature (enabled by default) builds the command-line tool. Without
+//! it, the library doesn't depend on `clap` or `crossterm`, and only depends
+//! on `dirs` with the `discovery` feature.
+//!
+//! # Examples
+//! ```no_run
+//! use extract_minecraft_assets::jar::{self, ExtractedContents};
+//! use extract_minecraft_assets::hashed::{self, IndexFile};
+//! use extract_minecraft_assets::options::ExtractOptions;
+//! use extract_minecraft_assets::progress::NoProgress;
+//! use extract_minecraft_assets::version::Version;
+//! use std::path::Path;
+//!
+//! let version = Version::resolve(".minecraft/versions/1.20.1")?;
+//! let hashed_assets_dir = Path::new(".minecraft/assets");
+//! let options = ExtractOptions::new()
+//!     .output("extracted")
+//!     .contents(ExtractedContents::assets());
+//!
+//! let jar_report = jar::extract_jar(&version.jar_file(), &options, &mut NoProgress)?;
+//!
+//! let index = IndexFile::from_path(&version.index_file(hashed_assets_dir)?)?;
+//! let hashed_report =
+//!     hashed::extract_hashed_assets(hashed_assets_dir, &index, &options, &mut NoProgress)?;
+//!
+//! println!("Extracted {}", jar_report + hashed_report);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+pub mod archive;
+#[cfg(feature = "async")]
+pub mod asynchronous;
+pub mod backup;
+pub mod catalog;
+pub mod category;
+pub mod clean;
+mod central_dir;
+pub mod closure;
+pub mod diff;
+pub mod duplicates;
+pub mod error;
+pub mod filter;
+pub mod fonts;
+pub mod format;
+pub mod grep;
+pub mod hashed;
+pub mod jar;
+pub mod lang;
+pub mod largest;
+pub mod libraries;
+pub mod locked;
+pub mod lookup;
+pub mod models;
+pub mod nbt;
+pub mod options;
+pub mod pack;
+pub mod pool;
+pub mod progress;
+pub mod redistributable;
+pub mod references;
+#[cfg(feature = "remote")]
+pub mod remote;
+pub mod report;
+pub mod search;
+pub mod sounds;
+pub mod stats;
+pub mod sync;
+pub mod text;
+pub mod usage;
+mod transform;
+pub mod util;
+pub mod validate;
+pub mod version;
+pub mod vfs;
@@ -0,0 +1,329 @@
+//! Finding the libraries a version needs to run, as listed in its
+//! [manifest](VersionManifest), and copying them out of
+//! `.minecraft/libraries/`.
+//!
+//! Each library is a jar file at a path following the Maven layout, e.g.
+//! `com/mojang/brigadier/1.1.8/brigadier-1.1.8.jar`. Some libraries are only
+//! needed on some platforms, as given by the `rules` of their entries, and
+//! those with native code have a jar file per platform.
+
+use crate::error::{ExtractError, IoContext};
+use crate::util;
+use crate::version::VersionManifest;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{self, File};
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+/// A library listed in a [`VersionManifest`].
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize)]
+#[non_exhaustive]
+pub struct Library {
+    /// The Maven coordinates of the library, e.g.
+    /// `com.mojang:brigadier:1.1.8`.
+    pub name: String,
+    /// The jar files of the library.
+    #[serde(default)]
+    pub downloads: LibraryDownloads,
+    /// The rules deciding which platforms the library is needed on. It is
+    /// needed on every platform if there are none.
+    #[serde(default)]
+    pub rules: Vec<Rule>,
+    /// The classifier of the jar file with the native code for each OS, e.g.
+    /// `natives-linux` for `linux`, found in the
+    /// [`classifiers`](LibraryDownloads::classifiers).
+    ///
+    /// `${arch}` in a classifier stands for `32` or `64`.
+    #[serde(default)]
+    pub natives: BTreeMap<String, String>,
+}
+
+/// The jar files of a [`Library`].
+#[derive(Clone, PartialEq, Eq, Debug, Default, Deserialize)]
+#[non_exhaustive]
+pub struct LibraryDownloads {
+    /// The main jar file, if any: libraries with only native code have none.
+    pub artifact: Option<Artifact>,
+    /// The other jar files, by classifier, e.g. `natives-windows`.
+    #[serde(default)]
+    pub classifiers: BTreeMap<String, Artifact>,
+}
+
+/// A jar file of a [`Library`].
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize)]
+#[non_exhaustive]
+pub struct Artifact {
+    /// The path of the jar file within `.minecraft/libraries/`.
+    pub path: PathBuf,
+    /// The SHA-1 hash of the jar file.
+    pub sha1: String,
+    /// The size of the jar file in bytes.
+    pub size: u64,
+    /// Where the jar file can be downloaded from.
+    pub url: String,
+}
+
+/// A rule of a [`Library`], allowing or disallowing it where its conditions
+/// hold.
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize)]
+#[non_exhaustive]
+pub struct Rule {
+    pub action: RuleAction,
+    /// The OS the rule applies to, or [`None`] for every OS.
+    pub os: Option<OsCondition>,
+    /// The launcher features the rule applies to, e.g. `is_demo_user`. None
+    /// are enabled when copying libraries.
+    #[serde(default)]
+    pub features: BTreeMap<String, bool>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleAction {
+    Allow,
+    Disallow,
+}
+
+/// The OS a [`Rule`] applies to.
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize)]
+#[non_exhaustive]
+pub struct OsCondition {
+    /// The name of the OS: `windows`, `osx` or `linux`.
+    pub name: Option<String>,
+    /// The architecture, e.g. `x86`.
+    pub arch: Option<String>,
+}
+
+/// A platform libraries are needed on.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Platform {
+    /// The name of the OS as in rules: `windows`, `osx` or `linux`.
+    pub os: &'static str,
+    /// The architecture as in rules, e.g. `x86_64`.
+    pub arch: &'static str,
+    /// Whether the architecture is 64-bit.
+    pub is_64_bit: bool,
+}
+
+impl Platform {
+    /// The platform this is running on.
+    pub fn current() -> Self {
+        let os = match std::env::consts::OS {
+            "macos" => "osx",
+            os => os,
+        };
+        let arch = match std::env::consts::ARCH {
+            "aarch64" => "arm64",
+            arch => arch,
+        };
+
+        Self {
+            os,
+            arch,
+            is_64_bit: cfg!(target_pointer_width = "64"),
+        }
+    }
+}
+
+impl Rule {
+    /// Whether the conditions of the rule hold on `platform`.
+    fn applies(&self, platform: &Platform) -> bool {
+        let os_applies = self.os.as_ref().is_none_or(|os| {
+            os.name.as_deref().is_none_or(|name| name == platform.os)
+                && os.arch.as_deref().is_none_or(|arch| arch == platform.arch)
+        });
+
+        os_applies && self.features.values().all(|enabled| !enabled)
+    }
+}
+
+impl Library {
+    /// Whether the library is needed on `platform`.
+    ///
+    /// The last of its rules which applies decides, and the library isn't
+    /// needed if none do.
+    ///
+    /// # Examples
+    /// ```
+    /// use extract_minecraft_assets::libraries::{Library, Platform};
+    ///
+    /// let library: Library = serde_json::from_str(
+    ///     r#"{
+    ///         "name": "ca.weblite:java-objc-bridge:1.1",
+    ///         "rules": [{"action": "allow", "os": {"name": "osx"}}]
+    ///     }"#,
+    /// )?;
+    /// let linux = Platform { os: "linux", arch: "x86_64", is_64_bit: true };
+    /// let macos = Platform { os: "osx", arch: "arm64", is_64_bit: true };
+    ///
+    /// assert!(!library.is_needed_on(&linux));
+    /// assert!(library.is_needed_on(&macos));
+    /// # Ok::<(), serde_json::Error>(())
+    /// ```
+    pub fn is_needed_on(&self, platform: &Platform) -> bool {
+        if self.rules.is_empty() {
+            return true;
+        }
+
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| rule.applies(platform))
+            .is_some_and(|rule| rule.action == RuleAction::Allow)
+    }
+
+    /// The jar files of the library needed on `platform`: its main jar file
+    /// and the one with its native code for the OS, if any.
+    pub fn artifacts_on(&self, platform: &Platform) -> Vec<&Artifact> {
+        if !self.is_needed_on(platform) {
+            return Vec::new();
+        }
+
+        let arch = if platform.is_64_bit { "64" } else { "32" };
+        let natives = self
+            .natives
+            .get(platform.os)
+            .map(|classifier| classifier.replace("${arch}", arch))
+            .and_then(|classifier| self.downloads.classifiers.get(&classifier));
+
+        self.downloads.artifact.iter().chain(natives).collect()
+    }
+}
+
+impl VersionManifest {
+    /// The jar files of every library the version needs on `platform`,
+    /// sorted by path.
+    pub fn library_artifacts(&self, platform: &Platform) -> Vec<&Artifact> {
+        let mut paths = BTreeSet::new();
+        let mut artifacts: Vec<_> = self
+            .libraries
+            .iter()
+            .flat_map(|library| library.artifacts_on(platform))
+            .filter(|artifact| paths.insert(&artifact.path))
+            .collect();
+        artifacts.sort_by(|a, b| a.path.cmp(&b.path));
+
+        artifacts
+    }
+}
+
+/// What [copying](copy_library) a library's jar file did.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+#[serde(rename_all = "camelCase", tag = "status")]
+pub enum LibraryStatus {
+    /// The jar file was copied.
+    Copied,
+    /// The jar file was downloaded, as it was missing or didn't match.
+    Downloaded,
+    /// The jar file isn't in the libraries directory.
+    Missing,
+    /// The jar file's SHA-1 hash isn't the one listed in the manifest, so it
+    /// wasn't copied.
+    Mismatched {
+        /// The SHA-1 hash of the jar file.
+        sha1: String,
+    },
+}
+
+impl LibraryStatus {
+    /// Whether the jar file is in the output directory.
+    pub fn is_written(&self) -> bool {
+        matches!(self, Self::Copied | Self::Downloaded)
+    }
+}
+
+/// Fails if the path of `artifact` would leave the directory it is within,
+/// e.g. with `..`.
+fn check_path(artifact: &Artifact, output_dir: &Path) -> Result<(), ExtractError> {
+    if artifact
+        .path
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)))
+    {
+        return Ok(());
+    }
+
+    Err(ExtractError::write_file(output_dir.join(&artifact.path))(
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "the library's path leaves the libraries directory",
+        ),
+    ))
+}
+
+/// Copies the jar file of `artifact` from `libraries_dir` to the same path
+/// within `output_dir`, if its SHA-1 hash matches.
+///
+/// Fails if the jar file can't be read or written, but not if it is missing
+/// or doesn't match, which the [`LibraryStatus`] tells.
+pub fn copy_library(
+    libraries_dir: &Path,
+    artifact: &Artifact,
+    output_dir: &Path,
+) -> Result<LibraryStatus, ExtractError> {
+    check_path(artifact, output_dir)?;
+
+    let source = libraries_dir.join(&artifact.path);
+    let file = match File::open(&source) {
+        Ok(file) => file,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => {
+            return Ok(LibraryStatus::Missing);
+        }
+        Err(error) => return Err(ExtractError::read_output(&source)(error)),
+    };
+
+    let sha1 = util::sha1_hex(file).reading_output(&source)?;
+    if !sha1.eq_ignore_ascii_case(&artifact.sha1) {
+        return Ok(LibraryStatus::Mismatched { sha1 });
+    }
+
+    let destination = output_dir.join(&artifact.path);
+    if let Some(parent) = destination.parent() {
+        fs::create_dir_all(parent).writing(parent)?;
+    }
+    fs::copy(&source, &destination).writing(&destination)?;
+
+    Ok(LibraryStatus::Copied)
+}
+
+/// Downloads the jar file of `artifact` from its URL to its path within
+/// `output_dir`, keeping it only if its SHA-1 hash matches.
+///
+/// Fails if it can't be downloaded or written.
+#[cfg(feature = "remote")]
+pub fn download_library(
+    artifact: &Artifact,
+    output_dir: &Path,
+) -> Result<LibraryStatus, ExtractError> {
+    use std::io::Read;
+
+    check_path(artifact, output_dir)?;
+
+    let download_error = |source| ExtractError::Download {
+        url: artifact.url.clone(),
+        source,
+    };
+
+    let response = ureq::get(&artifact.url)
+        .call()
+        .map_err(|error| download_error(io::Error::other(error)))?;
+    let mut contents = Vec::new();
+    response
+        .into_reader()
+        .read_to_end(&mut contents)
+        .map_err(download_error)?;
+
+    let sha1 = util::sha1_hex(contents.as_slice()).map_err(download_error)?;
+    if !sha1.eq_ignore_ascii_case(&artifact.sha1) {
+        return Ok(LibraryStatus::Mismatched { sha1 });
+    }
+
+    let destination = output_dir.join(&artifact.path);
+    if let Some(parent) = destination.parent() {
+        fs::create_dir_all(parent).writing(parent)?;
+    }
+    fs::write(&destination, contents).writing(&destination)?;
+
+    Ok(LibraryStatus::Downloaded)
+}
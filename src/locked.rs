@@ -0,0 +1,129 @@
+//! Retrying the reads of files which another process holds locked.
+//!
+//! On Windows, a file can't be opened while another process holds it open
+//! without sharing it, which the game and the launcher do with the jar file
+//! and hashed files they are using. Such failures are only temporary, so the
+//! file is opened or read again after a short delay, a few times, before the
+//! failure is given up on.
+
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::io;
+use std::thread;
+use std::time::Duration;
+
+/// How many times to retry reading a file which another process holds
+/// locked, and how long to wait in between.
+///
+/// # Examples
+/// ```
+/// use extract_minecraft_assets::locked::LockedRetry;
+/// use std::io;
+///
+/// let mut attempts = 0;
+/// let result: io::Result<()> = LockedRetry::default().run(|| {
+///     attempts += 1;
+///     Err(io::ErrorKind::NotFound.into())
+/// });
+///
+/// // Only failures because the file is locked are retried.
+/// assert!(result.is_err());
+/// assert_eq!(attempts, 1);
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct LockedRetry {
+    /// The number of times to try again after the first attempt.
+    pub retries: u32,
+    /// How long to wait before the first retry, which doubles before each
+    /// retry after it.
+    pub delay: Duration,
+}
+
+/// The error of a file which was still locked by another process after every
+/// [retry](LockedRetry).
+#[derive(Debug)]
+pub struct LockedError {
+    source: io::Error,
+    attempts: u32,
+}
+
+impl Default for LockedRetry {
+    /// Retries 3 times, after 100, 200 and 400 milliseconds.
+    fn default() -> Self {
+        Self {
+            retries: 3,
+            delay: Duration::from_millis(100),
+        }
+    }
+}
+
+impl LockedRetry {
+    /// Doesn't retry at all.
+    pub const NEVER: Self = Self {
+        retries: 0,
+        delay: Duration::ZERO,
+    };
+
+    /// Calls `read` until it succeeds, fails for another reason than the file
+    /// being [locked](is_locked), or has been retried as many times as
+    /// allowed.
+    ///
+    /// If the file is still locked, the error is replaced by a [`LockedError`]
+    /// of the same kind, whose message notes that another process holds the
+    /// file.
+    pub fn run<T>(&self, mut read: impl FnMut() -> io::Result<T>) -> io::Result<T> {
+        let mut delay = self.delay;
+
+        for _ in 0..self.retries {
+            match read() {
+                Err(error) if is_locked(&error) => {
+                    thread::sleep(delay);
+                    delay = delay.saturating_mul(2);
+                }
+                result => return result,
+            }
+        }
+
+        read().map_err(|error| {
+            if is_locked(&error) {
+                io::Error::new(
+                    error.kind(),
+                    LockedError {
+                        source: error,
+                        attempts: self.retries + 1,
+                    },
+                )
+            } else {
+                error
+            }
+        })
+    }
+}
+
+impl Display for LockedError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} (another process, such as the game or the launcher, holds the file; tried {} {})",
+            self.source,
+            self.attempts,
+            if self.attempts == 1 { "time" } else { "times" }
+        )
+    }
+}
+
+impl Error for LockedError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+/// Whether `error` is because another process holds the file locked, i.e. a
+/// sharing or lock violation on Windows.
+///
+/// This is never the case on other platforms, where files are only locked
+/// advisorily.
+pub fn is_locked(error: &io::Error) -> bool {
+    // ERROR_SHARING_VIOLATION and ERROR_LOCK_VIOLATION.
+    cfg!(windows) && matches!(error.raw_os_error(), Some(32 | 33))
+}
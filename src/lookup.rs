@@ -0,0 +1,261 @@
+//! Finding which index files, and which versions, use a hashed file, and
+//! which versions use each index file.
+
+use crate::error::ExtractError;
+use crate::hashed::IndexFile;
+use crate::version::Version;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The most versions followed through
+/// [`inherits_from`](crate::version::VersionManifest::inherits_from) to find
+/// a version's index file, in case of a cycle.
+const MAX_INHERITANCE_DEPTH: usize = 8;
+
+/// Every index file within `.minecraft/assets/indexes/` and every version
+/// within `.minecraft/versions/`, read once to [find](HashLookup::find) the
+/// users of any number of hashes.
+///
+/// # Examples
+/// ```no_run
+/// use extract_minecraft_assets::lookup::HashLookup;
+/// use std::path::Path;
+///
+/// let lookup = HashLookup::load(Path::new(".minecraft/assets"), Path::new(".minecraft/versions"));
+///
+/// for usage in lookup.find("8014d8") {
+///     for index in &usage.indexes {
+///         println!("{} is used by index {} ({:?})", usage.hash, index.id, index.versions);
+///     }
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct HashLookup {
+    /// The paths of each hash within each index file, by hash and then by
+    /// index id.
+    hashes: BTreeMap<String, BTreeMap<String, Vec<PathBuf>>>,
+    /// The names of the versions using each index file, by index id.
+    versions: BTreeMap<String, Vec<String>>,
+    /// The index files which couldn't be read.
+    errors: Vec<ExtractError>,
+}
+
+/// The index files which use a hash.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HashUsage {
+    /// The full SHA-1 hash.
+    pub hash: String,
+    /// The index files which use the hash, sorted by id.
+    pub indexes: Vec<IndexUsage>,
+}
+
+/// An index file which uses a hash.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexUsage {
+    /// The name of the index file, without the `json` file extension.
+    pub id: String,
+    /// The paths within `assets` which the index file gives the hash, sorted.
+    pub paths: Vec<PathBuf>,
+    /// The names of the installed versions using the index file, sorted.
+    pub versions: Vec<String>,
+}
+
+/// An index file within `.minecraft/assets/indexes/`, as listed by
+/// [`list_indexes`].
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstalledIndex {
+    /// The name of the index file, without the `json` file extension.
+    pub id: String,
+    /// The path to the index file.
+    pub path: PathBuf,
+    /// The size of the index file itself, in bytes.
+    pub size: u64,
+    /// The number of hashed files the index file lists, if it could be read.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub objects: Option<usize>,
+    /// The total size of the hashed files the index file lists, going by the
+    /// sizes it gives, if it could be read.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_size: Option<u64>,
+    /// The names of the installed versions using the index file, sorted.
+    pub versions: Vec<String>,
+    /// A description of why the index file couldn't be read, if it
+    /// couldn't.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Lists every index file within `hashed_assets_dir`, sorted by id, along
+/// with the versions within `versions_dir` which use it.
+///
+/// Missing directories are treated as empty. Index files which can't be read
+/// are listed with their [`error`](InstalledIndex::error), while versions
+/// whose manifests can't be read are skipped.
+///
+/// # Examples
+/// ```no_run
+/// use extract_minecraft_assets::lookup;
+/// use std::path::Path;
+///
+/// let indexes = lookup::list_indexes(Path::new(".minecraft/assets"), Path::new(".minecraft/versions"));
+///
+/// for index in indexes.iter().filter(|index| index.versions.is_empty()) {
+///     println!("No installed version uses index {}", index.id);
+/// }
+/// ```
+pub fn list_indexes(hashed_assets_dir: &Path, versions_dir: &Path) -> Vec<InstalledIndex> {
+    let mut versions = index_versions(versions_dir);
+
+    dir_entries(&hashed_assets_dir.join("indexes"))
+        .into_iter()
+        .filter_map(|(id, path)| {
+            let id = id.strip_suffix(".json")?.to_owned();
+            let metadata = fs::metadata(&path)
+                .ok()
+                .filter(|metadata| metadata.is_file())?;
+            let index = IndexFile::from_path(&path);
+
+            Some(InstalledIndex {
+                size: metadata.len(),
+                objects: index.as_ref().ok().map(IndexFile::len),
+                total_size: index.as_ref().ok().map(IndexFile::total_size),
+                versions: versions.remove(&id).unwrap_or_default(),
+                error: index.err().map(|error| error.to_string()),
+                id,
+                path,
+            })
+        })
+        .collect()
+}
+
+impl HashLookup {
+    /// Reads every index file within `hashed_assets_dir` and the manifest of
+    /// every version within `versions_dir`.
+    ///
+    /// Missing directories are treated as empty. Index files which can't be
+    /// read are listed in [`errors`](HashLookup::errors), while versions
+    /// whose manifests can't be read are skipped.
+    pub fn load(hashed_assets_dir: &Path, versions_dir: &Path) -> Self {
+        let mut lookup = Self {
+            versions: index_versions(versions_dir),
+            ..Self::default()
+        };
+
+        for (id, path) in dir_entries(&hashed_assets_dir.join("indexes")) {
+            let Some(id) = id.strip_suffix(".json") else {
+                continue;
+            };
+
+            match IndexFile::from_path(&path) {
+                Ok(index) => lookup.add_index(id, &index),
+                Err(error) => lookup.errors.push(error),
+            }
+        }
+
+        lookup
+    }
+
+    /// Adds the hashes used by `index`, the index file named `id`.
+    fn add_index(&mut self, id: &str, index: &IndexFile) {
+        for (path, object) in &index.objects {
+            self.hashes
+                .entry(object.hash.clone())
+                .or_default()
+                .entry(id.to_owned())
+                .or_default()
+                .push(path.clone());
+        }
+    }
+
+    /// The errors reading index files, if any.
+    pub fn errors(&self) -> &[ExtractError] {
+        &self.errors
+    }
+
+    /// Returns the usages of every hash starting with `prefix`, sorted by
+    /// hash.
+    ///
+    /// A full hash matches at most one hash, while a shorter prefix may be
+    /// ambiguous.
+    pub fn find(&self, prefix: &str) -> Vec<HashUsage> {
+        let prefix = prefix.to_ascii_lowercase();
+
+        self.hashes
+            .range(prefix.clone()..)
+            .take_while(|(hash, _)| hash.starts_with(&prefix))
+            .map(|(hash, indexes)| HashUsage {
+                hash: hash.clone(),
+                indexes: indexes
+                    .iter()
+                    .map(|(id, paths)| {
+                        let mut paths = paths.clone();
+                        paths.sort();
+
+                        IndexUsage {
+                            id: id.clone(),
+                            paths,
+                            versions: self.versions.get(id).cloned().unwrap_or_default(),
+                        }
+                    })
+                    .collect(),
+            })
+            .collect()
+    }
+}
+
+/// Returns the names of the versions within `versions_dir` using each index
+/// file, by index id.
+///
+/// A version whose manifest doesn't name an index file uses the index file of
+/// the version it inherits from.
+fn index_versions(versions_dir: &Path) -> BTreeMap<String, Vec<String>> {
+    let manifests: BTreeMap<_, _> = dir_entries(versions_dir)
+        .into_iter()
+        .filter_map(|(name, dir)| Some((name, Version::from_dir(dir).manifest().ok()?)))
+        .collect();
+
+    let mut versions: BTreeMap<String, Vec<String>> = BTreeMap::new();
+
+    for name in manifests.keys() {
+        let mut manifest = &manifests[name];
+        for _ in 0..MAX_INHERITANCE_DEPTH {
+            match (&manifest.inherits_from, manifest.index_id()) {
+                (Some(parent), None) if manifests.contains_key(parent) => {
+                    manifest = &manifests[parent];
+                }
+                _ => break,
+            }
+        }
+
+        if let Some(index_id) = manifest.index_id() {
+            versions
+                .entry(index_id.to_owned())
+                .or_default()
+                .push(name.clone());
+        }
+    }
+
+    versions
+}
+
+/// Returns the names and paths of the entries within `dir`, sorted by name.
+///
+/// Returns nothing if `dir` cannot be read.
+fn dir_entries(dir: &Path) -> Vec<(String, PathBuf)> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut entries: Vec<_> = entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| Some((entry.file_name().into_string().ok()?, entry.path())))
+        .collect();
+    entries.sort();
+
+    entries
+}
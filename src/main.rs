@@ -1,13 +1,37 @@
-mod hashed;
-mod jar;
-mod util;
-mod version;
+mod cli;
 
-use clap::{Parser, Subcommand};
-use crossterm::terminal::ClearType;
-use crossterm::{terminal, ExecutableCommand};
-use std::path::PathBuf;
-use std::{env, io};
+use crate::cli::error::Error;
+use crate::cli::json::{self, Document, Failure};
+use crate::cli::lock::OutputLock;
+use crate::cli::source::InvalidVersionArg;
+use crate::cli::{
+    all, archive, browse, catalog, changed, clean, complete, config, conflict, diff, doctor, du,
+    duplicates, grep, hashed, indexes, jar, lang, largest, libraries, list, locales, lookup, nbt,
+    pack, references, search, serve, sounds, stats, sync, text, tree, validate, version, written,
+};
+use clap::{ArgGroup, CommandFactory, Parser, Subcommand};
+use extract_minecraft_assets::error::ExtractError;
+use extract_minecraft_assets::filter::Pattern;
+use extract_minecraft_assets::jar::ExtractedContents;
+use extract_minecraft_assets::locked::LockedRetry;
+use extract_minecraft_assets::options::{CancellationToken, ExtractOptions};
+use extract_minecraft_assets::report::{ExtractionReport, FileOutcome};
+use extract_minecraft_assets::search::FileSource;
+use extract_minecraft_assets::util;
+use extract_minecraft_assets::vfs::DryRunFs;
+use std::ffi::OsString;
+use std::io::{IsTerminal, Write};
+use std::num::NonZero;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use std::{env, io, process, thread};
+
+/// Whether the user is being asked for confirmation, in which case Ctrl-C
+/// exits immediately.
+static PROMPTING: AtomicBool = AtomicBool::new(false);
 
 /// Extracts Minecraft `assets` or `data`.
 ///
@@ -15,10 +39,17 @@ use std::{env, io};
 /// (like sounds or non-US-English languages) are found hashed in
 /// `.minecraft/assets/`. This tool can extract `assets` or `data` from either
 /// location, or both at the same time.
+///
+/// Exits with 2 if some files failed to be extracted, 3 if the Minecraft
+/// files being extracted are missing or invalid, 4 if the output couldn't be
+/// written (including when its volume fills up), 5 if requested contents are
+/// absent from the jar file, 130 if the extraction was cancelled with Ctrl-C,
+/// and 1 for any other failure.
 #[derive(Parser)]
+#[command(group(ArgGroup::new("conversion").multiple(true)))]
 struct ExtractCommand {
     #[command(subcommand)]
-    subcommand: ExtractSubcommand,
+    subcommand: Command,
 
     /// The path to the directory into which to extract assets.
     ///
@@ -37,6 +68,535 @@ struct ExtractCommand {
     /// and `data` at the same time, as their contents would get mixed up.
     #[arg(long, global = true)]
     ignore_top_level: bool,
+    /// Leave out the namespace directory of each file, e.g. extracting
+    /// `assets/minecraft/lang/en_us.json` to `assets/lang/en_us.json`.
+    ///
+    /// Only valid if every file extracted is in the same namespace, as files
+    /// of different namespaces would get mixed up: nothing is extracted
+    /// otherwise, nor if two files would still be extracted to the same path.
+    #[arg(long, global = true, conflicts_with = "as_resource_pack")]
+    strip_namespace: bool,
+    /// Only extract the files matching this glob, e.g.
+    /// `assets/minecraft/textures/**`.
+    ///
+    /// Globs are matched against paths within the jar file before anything
+    /// is left out of them, with hashed assets matched as if under `assets`,
+    /// and a glob without a `/` matches file names, e.g. `*.png`. Files are
+    /// left out before being read, and aren't counted in the progress. May
+    /// be given more than once, in which case files matching any of them
+    /// are extracted.
+    #[arg(long, value_name = "GLOB", value_parser = Pattern::glob, global = true)]
+    include: Vec<Pattern>,
+    /// Leave out the files matching this glob, even if --include matches
+    /// them.
+    ///
+    /// Matched like --include. May be given more than once.
+    #[arg(long, value_name = "GLOB", value_parser = Pattern::glob, global = true)]
+    exclude: Vec<Pattern>,
+    /// Sort the files extracted into directories by type, each keeping its
+    /// usual path within its directory.
+    ///
+    /// With `ext`, `assets/minecraft/textures/block/stone.png` is extracted
+    /// to `png/assets/minecraft/textures/block/stone.png`, and with
+    /// `category` to `textures/assets/minecraft/textures/block/stone.png`.
+    /// Files without an extension, or in no category, go in `other`.
+    /// Converted language files are grouped by their original extension.
+    #[arg(long, value_name = "GROUP", global = true)]
+    group_by: Option<jar::GroupBy>,
+    /// Convert language files to this format while extracting.
+    ///
+    /// Versions before 1.13 use `key=value` `.lang` files rather than JSON.
+    /// With `json`, every `assets/<namespace>/lang/<locale>.lang` file is
+    /// extracted as a `.json` file with the same name, and with `lang` the
+    /// other way around. Files which can't be converted are extracted as they
+    /// are, with a warning.
+    #[arg(long, value_name = "FORMAT", group = "conversion", global = true)]
+    convert_lang: Option<lang::ConvertLang>,
+    /// Convert the NBT files of data to this format while extracting.
+    ///
+    /// Structures and other `data/**/*.nbt` files are binary. With `snbt`,
+    /// each is decompressed and extracted as an indented `.snbt` text file
+    /// with the same name, which can be read and diffed but isn't meant to be
+    /// converted back. Files which can't be parsed are extracted as they are,
+    /// with a warning.
+    #[arg(long, value_name = "FORMAT", group = "conversion", global = true)]
+    convert_nbt: Option<nbt::ConvertNbt>,
+    /// Extract the original of each converted language or NBT file too.
+    #[arg(long, requires = "conversion", global = true)]
+    keep_original: bool,
+    /// Reformat `.json` and `.mcmeta` files while extracting.
+    ///
+    /// Most JSON files are minified onto one line. With `pretty`, they are
+    /// written with two-space indentation, keeping keys in the same order,
+    /// and with `minify` without any whitespace, reporting the bytes saved.
+    /// Files which aren't valid JSON are extracted as they are, with a
+    /// warning.
+    #[arg(long, value_name = "FORMAT", global = true)]
+    format_json: Option<json::FormatJson>,
+    /// Extract JSON files larger than this as they are, rather than
+    /// formatting them with --format-json.
+    ///
+    /// Accepts sizes like `500 KiB`, `16M` or `1048576`. Each formatted file
+    /// is held in memory while it is formatted.
+    #[arg(
+        long,
+        value_name = "SIZE",
+        value_parser = util::parse_size,
+        default_value = "16 MiB",
+        requires = "format_json",
+        global = true
+    )]
+    format_json_max_size: u64,
+    /// Normalize the line endings of text files to this while extracting.
+    ///
+    /// Text files are recognized by their extension: `.txt`, `.lang`,
+    /// `.json`, `.mcmeta`, `.properties`, `.vsh` and `.fsh`. A UTF-8 byte
+    /// order mark is stripped too, and the summary counts the files which
+    /// changed. Files which don't look like text are extracted as they are,
+    /// with a warning, and every other file is left untouched.
+    #[arg(long, value_name = "LINE_ENDING", global = true)]
+    text_normalize: Option<text::TextNormalize>,
+    /// Check that files look like their format as they are written, e.g. to
+    /// catch hashed files corrupted on disk.
+    ///
+    /// PNG files are checked for their signature and `IHDR` chunk, OGG files
+    /// for the capture pattern, JSON and `.mcmeta` files by parsing them
+    /// (unless larger than 16 MiB), and NBT files for the gzip magic number.
+    /// Files are checked as they are written, so none is read again.
+    /// Invalid files are still written, with a warning giving the reason, and
+    /// counted in the summary. Every format is checked unless given as e.g.
+    /// `--validate-content=png,json`.
+    #[arg(
+        long,
+        value_name = "FORMATS",
+        num_args = 0..=1,
+        require_equals = true,
+        value_delimiter = ',',
+        default_missing_values = ["png", "ogg", "json", "nbt"],
+        global = true
+    )]
+    validate_content: Option<Vec<validate::ValidateContent>>,
+    /// Whether files and directories whose permissions can't be set fail to
+    /// be extracted.
+    ///
+    /// By default, files are extracted without their permissions if the
+    /// output directory's filesystem doesn't support them (e.g. exFAT), with
+    /// a warning for the first and the rest counted in the summary.
+    #[arg(long, global = true)]
+    strict_permissions: bool,
+    /// Check that each hashed file has the size and SHA-1 hash listed in the
+    /// index file as it is read, e.g. to catch a corrupted download.
+    ///
+    /// The size is checked first, so that only files of the right size are
+    /// hashed. Files which don't match are extracted anyway with a warning,
+    /// or fail without being written with `--verify=strict`, and the summary
+    /// counts them. Files of the jar file aren't verified.
+    #[arg(
+        long,
+        value_name = "MODE",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "warn",
+        global = true
+    )]
+    verify: Option<hashed::Verify>,
+    /// How many threads read and write hashed files at once.
+    ///
+    /// Defaults to the number of logical CPUs, as extracting many small
+    /// files is bound by waiting on the disk. Progress and failures are
+    /// still reported from a single thread, and with `--threads 1` files are
+    /// extracted one after the other.
+    #[arg(long, value_name = "N", global = true)]
+    threads: Option<NonZero<usize>>,
+    /// How many times to try opening the jar file or reading a hashed file
+    /// again while another process holds it locked.
+    ///
+    /// On Windows, the game and the launcher can hold the files they are
+    /// using open without sharing them, which fails to read them for a
+    /// moment. Files still locked after every retry fail, noting that
+    /// another process holds them.
+    #[arg(long, value_name = "N", default_value_t = LockedRetry::default().retries, global = true)]
+    locked_retries: u32,
+    /// How many milliseconds to wait before the first retry of a locked
+    /// file, which doubles before each retry after it.
+    #[arg(
+        long,
+        value_name = "MILLISECONDS",
+        default_value_t = LockedRetry::default().delay.as_millis() as u64,
+        global = true
+    )]
+    locked_retry_delay: u64,
+    /// Extract hashed assets to the paths in the index file as they are,
+    /// rather than decoding percent-encoded sequences such as `%20`.
+    #[arg(long, global = true)]
+    no_decode_paths: bool,
+    /// Which entry to extract when a jar file has several entries with the
+    /// same name.
+    ///
+    /// Each such file is reported with a warning giving how many entries
+    /// have its name. Names are compared exactly, so entries whose names only
+    /// differ in case are separate files.
+    #[arg(long, value_name = "ENTRY", default_value = "last", global = true)]
+    duplicate_entries: jar::DuplicateEntries,
+    /// What to do with files which already exist where files are extracted,
+    /// e.g. ones you edited.
+    ///
+    /// Skipped files are counted in the summary. Files where directories are
+    /// to be extracted are skipped or replaced the same way.
+    #[arg(
+        long,
+        value_name = "ACTION",
+        default_value = "overwrite",
+        global = true
+    )]
+    on_conflict: conflict::OnConflict,
+    /// Remove directories (and everything in them) which are where files are
+    /// to be extracted.
+    ///
+    /// By default, a directory in the way of a file fails to extract that
+    /// file.
+    #[arg(long, global = true)]
+    force_replace_dirs: bool,
+    /// Whether to allow extracting into the Minecraft installation, or over
+    /// the files being extracted from.
+    ///
+    /// By default, extraction is refused if the output directory is inside
+    /// the `.minecraft` directory, the hashed assets directory, or the version
+    /// directory being extracted from, or if either of the latter is inside
+    /// a directory being extracted into (e.g. `assets` in the output
+    /// directory), as files would be overwritten while being read.
+    #[arg(long, global = true, alias = "allow-inside-minecraft")]
+    unsafe_allow_overlap: bool,
+    /// List the files which would be extracted, without writing anything.
+    ///
+    /// The files are listed on stdout, as bare paths with --porcelain, each
+    /// followed by a NUL byte with --print0, or in the document with --json.
+    /// Every hashed file and jar entry is still read, so that missing or
+    /// unreadable files and entries with unsafe names fail as they would,
+    /// and the exit code is the one the extraction would have. No directory
+    /// is created and the output directory isn't locked. With `clean-run`,
+    /// lists the files which would be deleted instead.
+    #[arg(
+        long,
+        conflicts_with_all = ["output_zip", "as_resource_pack", "catalog"],
+        global = true
+    )]
+    dry_run: bool,
+    /// Extract without locking the output directory.
+    ///
+    /// By default, a `.extract-lock` file holding the process ID is created
+    /// in the output directory while extracting, and extraction fails if
+    /// another running process holds it, as concurrent extractions could
+    /// corrupt each other's files. A lock left behind by a process which is
+    /// no longer running is reclaimed.
+    #[arg(long, global = true)]
+    no_lock: bool,
+    /// Whether to use a stable, line-oriented, unstyled format for stdout.
+    ///
+    /// Only the primary result of a command (e.g. a listing) is ever written
+    /// to stdout: progress, warnings and summaries are written to stderr.
+    /// Progress is only displayed when stderr is a terminal. With this set,
+    /// progress is not displayed at all, and `list`, the `diff-*`
+    /// subcommands, `which-index` and `hash-path` print their results with
+    /// tab-separated fields, sizes in bytes and without headings, moving any
+    /// totals to stderr.
+    #[arg(long, global = true)]
+    porcelain: bool,
+    /// Print the result of the command as a single JSON document on stdout.
+    ///
+    /// Extraction subcommands print their report, `doctor` prints its
+    /// diagnosis, the `diff-*` subcommands print the differences, `stats`,
+    /// `du`, `duplicates` and `largest` print their analysis, `tree` prints
+    /// the nested directories and files, `list` prints every file, `search`,
+    /// `grep` and `which-index` print what they found, `hash-path` prints the
+    /// hashes, `indexes` prints the index files, `lang` prints the locales it
+    /// merged, `libraries` prints the libraries it copied, `clean-run` prints
+    /// the files it deleted, `verify-report` prints the files which changed,
+    /// `validate-pack` prints the dangling references, `check-sounds` prints
+    /// the broken sounds, and `profiles` prints the profiles. Every document
+    /// has a `formatVersion` field, which changes whenever the schema changes
+    /// incompatibly.
+    #[arg(long, global = true)]
+    json: bool,
+    /// Print the absolute path of every file extracted to stdout as soon as
+    /// it is written, each followed by a NUL byte, e.g. for `xargs -0`.
+    ///
+    /// Nothing else is written to stdout. Paths are printed as they are, so
+    /// they may contain newlines, which NUL bytes still separate. Skipped and
+    /// failed files aren't printed.
+    #[arg(long, global = true, conflicts_with_all = ["json", "print"])]
+    print0: bool,
+    /// Like --print0, but with each path followed by a newline instead.
+    #[arg(long, global = true, conflicts_with = "json")]
+    print: bool,
+    /// Write the paths printed by --print0 to this file rather than stdout.
+    ///
+    /// Paths are followed by NUL bytes, or by newlines with --print.
+    #[arg(long, value_name = "FILE", global = true)]
+    written_list: Option<PathBuf>,
+    /// Ask for confirmation before extracting more than this many bytes.
+    ///
+    /// Accepts sizes like `500 MiB`, `2G` or `1048576`. Confirmation is also
+    /// asked for when extracting more than the free space of the output
+    /// directory's volume. When not running interactively, extraction is
+    /// aborted instead unless --yes is given.
+    ///
+    /// Defaults to `confirm-over` in the config file, or 1 GiB.
+    #[arg(long, value_name = "SIZE", value_parser = util::parse_size, global = true)]
+    confirm_over: Option<u64>,
+    /// Extract without asking for confirmation.
+    #[arg(short, long, global = true)]
+    yes: bool,
+    /// Use the option values of this profile from the config file.
+    ///
+    /// Profiles are `[profile.<name>]` sections of the config file, setting
+    /// options by their long names, e.g. `format-json = "pretty"` or
+    /// `yes = true`. Options given on the command line override the
+    /// profile's. Use `profiles` to list the profiles and what they expand
+    /// to.
+    #[arg(long, value_name = "NAME", global = true)]
+    profile: Option<String>,
+    /// Write a JSON report of the extraction to this file.
+    ///
+    /// The report lists what happened to every file, along with the totals
+    /// and the options used, in the same format as --json. With `clean-run`
+    /// and `verify-report`, this is the report of an earlier extraction
+    /// instead.
+    #[arg(long, value_name = "FILE", global = true)]
+    report: Option<PathBuf>,
+    /// Write a catalog of the extracted files into the output directory.
+    ///
+    /// The catalog lists every file written, grouped by namespace and
+    /// category, with its size, where it was extracted from, and its hash if
+    /// it is a hashed asset. With `json` it is written to `catalog.json`, and
+    /// with `md` to `catalog.md`, sorted so that it diffs cleanly between
+    /// versions.
+    #[arg(long, value_name = "FORMAT", global = true)]
+    catalog: Option<catalog::CatalogFormat>,
+    /// Whether to write the extracted files into a zip archive.
+    #[command(flatten)]
+    archive: archive::ArchiveArgs,
+    /// Whether to shape the output directory as a resource pack.
+    #[command(flatten)]
+    resource_pack: pack::ResourcePackArgs,
+    /// Whether to delete the files which the source no longer has.
+    #[command(flatten)]
+    sync: sync::SyncArgs,
+    /// Whether to commit the extracted files to a git repository.
+    #[cfg(feature = "git")]
+    #[command(flatten)]
+    git: cli::git::GitArgs,
+    /// Whether to show a desktop notification when extraction finishes.
+    #[cfg(feature = "notify")]
+    #[arg(long, global = true)]
+    notify: bool,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    #[command(flatten)]
+    Extract(ExtractSubcommand),
+    /// Diagnoses where the tool looks for Minecraft installations.
+    ///
+    /// Prints every location probed for the `.minecraft` directory, what was
+    /// found there, the free space in the output directory, and whether the
+    /// terminal supports progress messages.
+    Doctor(doctor::DoctorSubcommand),
+    /// Compares two index files.
+    ///
+    /// Lists the paths only in A, only in B, and in both but with different
+    /// hashes, along with their sizes and the totals of each.
+    DiffIndex(diff::DiffIndexSubcommand),
+    /// Compares the selected contents of two jar files.
+    ///
+    /// Lists the paths only in A, only in B, and in both but with different
+    /// checksums or sizes, along with their sizes and the totals of each. Only
+    /// the central directories are read unless --contents is given.
+    DiffJar(diff::DiffJarSubcommand),
+    /// Compares an extracted directory with the source it was extracted from.
+    ///
+    /// Lists the files which were modified, are missing, or are extra, and
+    /// exits with 1 if there are any. Use the same --ignore-top-level as the
+    /// extraction.
+    DiffOutput(diff::DiffOutputSubcommand),
+    /// Counts the files of a version, jar file or index file without
+    /// extracting anything.
+    ///
+    /// Prints the number of files and bytes in each namespace (e.g.
+    /// `minecraft` or `realms`) and each category (e.g. `textures` or
+    /// `recipes`). A version counts both its jar file and its index file.
+    Stats(stats::StatsSubcommand),
+    /// Estimates how many files and bytes an extraction would write, without
+    /// extracting anything.
+    ///
+    /// Give the extraction as it would be extracted, e.g. `du version 1.20.1
+    /// --assets`: its options are taken into account, including which files
+    /// it leaves out and where they would be written. Prints the number of
+    /// files and bytes from the jar file and the hashed assets, and in each
+    /// directory directly within the output directory. Sizes are read without
+    /// decompressing anything.
+    Du(du::DuSubcommand),
+    /// Prints the directories and files an extraction would create, without
+    /// extracting anything.
+    ///
+    /// Give the extraction as it would be extracted, e.g. `tree version
+    /// 1.20.1 --assets --max-depth 3`: the files it leaves out and where the
+    /// rest would be written are taken into account, as with `du`.
+    /// Directories cut off by --max-depth are shown with the number of files
+    /// within them and their total size.
+    Tree(tree::TreeSubcommand),
+    /// Lists every file of a version, jar file or index file, without
+    /// extracting anything.
+    ///
+    /// Prints the source (jar or hashed), size, SHA-1 hash (for hashed
+    /// files) and path of each file, sorted by path so that the listings of
+    /// versions can be diffed. Paths are like
+    /// `assets/minecraft/lang/en_us.json` for both jar and hashed files. A
+    /// version includes both its jar file and its index file, and files in
+    /// both are listed once, from the index file, as they would be extracted.
+    List(list::ListSubcommand),
+    /// Lists the files of a version, jar file or index file with identical
+    /// contents.
+    ///
+    /// Files are grouped by their SHA-1 hashes, and the groups which waste
+    /// the most bytes are listed first. A version includes both its jar file
+    /// and its index file, so duplicates across the two are found too.
+    Duplicates(duplicates::DuplicatesSubcommand),
+    /// Lists the largest files of a version, jar file or index file.
+    ///
+    /// Prints the size, source (jar or hashed), category and path of each
+    /// file, largest first, to help decide what to leave out of an
+    /// extraction. Sizes are read without decompressing anything. A version
+    /// includes both its jar file and its index file, and files in both are
+    /// listed once, from the index file, as they would be extracted.
+    Largest(largest::LargestSubcommand),
+    /// Finds the files of a version, jar file or index file whose paths match
+    /// any of the patterns.
+    ///
+    /// Patterns are substrings unless given with --glob or --regex, and are
+    /// matched against paths like `assets/minecraft/lang/en_us.json`, in the
+    /// same way as when choosing files to extract. Prints whether each hit is
+    /// in the jar file or hashed, its size, and the path to its hashed file.
+    /// Exits with 1 if nothing matches.
+    Search(search::SearchSubcommand),
+    /// Finds the lines of the text files of a version, jar file or index file
+    /// which match the pattern.
+    ///
+    /// Only files with text extensions, like `.json`, `.mcmeta` and `.txt`,
+    /// are searched, and files which turn out to be binary are skipped.
+    /// Files are decompressed or read one line at a time, without extracting
+    /// anything. Prints each hit as `path:line: text`, with paths like
+    /// `assets/minecraft/lang/en_us.json`. Exits with 1 if nothing matches.
+    Grep(grep::GrepSubcommand),
+    /// Finds the index files, and the versions using them, which list a
+    /// hashed file.
+    ///
+    /// Every index file in `assets/indexes/` is read once, along with the
+    /// manifest of every version in `versions/`. Prints the paths each index
+    /// file gives the hash. Exits with 1 if any hash isn't listed anywhere.
+    WhichIndex(lookup::WhichIndexSubcommand),
+    /// Prints where files would be within the `objects` directory of hashed
+    /// assets.
+    ///
+    /// Prints the SHA-1 hash, size and path within `objects/` of each file,
+    /// and with --hashed-assets whether that directory already has it. Give
+    /// `-` to read the paths of the files from stdin, e.g. from `find`.
+    /// Exits with 1 if any file can't be read.
+    HashPath(lookup::HashPathSubcommand),
+    /// Lists the index files, and the versions using each of them.
+    ///
+    /// Every index file in `assets/indexes/` is listed with its size, the
+    /// number of hashed files it lists and their total size, and the
+    /// installed versions in `versions/` whose manifests use it. Index files
+    /// which can't be read are listed with the reason.
+    Indexes(indexes::IndexesSubcommand),
+    /// Merges a version's language files into one file per locale.
+    ///
+    /// Collects `assets/<namespace>/lang/<locale>.json` from both the jar
+    /// file and the hashed assets, and writes `merged/<locale>.json` with
+    /// sorted keys into the output directory. Namespaces are layered in
+    /// alphabetical order, and keys they give different values are reported
+    /// as conflicts. Language files which can't be read are reported and left
+    /// out, exiting with 2.
+    Lang(lang::LangSubcommand),
+    /// Lists the locales available in a version.
+    ///
+    /// Combines the language files of the `minecraft` namespace in the jar
+    /// file (usually just `en_us`) with those in the hashed assets. Prints the
+    /// code, the name of the language where `pack.mcmeta` or the jar's
+    /// `lang/_list` gives it, the size and the source of each locale. Exits
+    /// with 1 if any locale given with --missing isn't available.
+    Locales(locales::LocalesSubcommand),
+    /// Copies the libraries a version needs to run into the output
+    /// directory.
+    ///
+    /// Reads the `libraries` of the version's manifest, along with those of
+    /// the version it inherits from, if any, and copies the jar files needed
+    /// on this platform out of `.minecraft/libraries/`, keeping their paths,
+    /// e.g. `com/mojang/brigadier/1.1.8/brigadier-1.1.8.jar`. Each jar file's
+    /// SHA-1 hash is checked against the manifest first. Lists the libraries
+    /// which are missing or don't match, along with their URLs, and exits
+    /// with 2 if there are any.
+    Libraries(libraries::LibrariesSubcommand),
+    /// Undoes an extraction, deleting the files it wrote.
+    ///
+    /// Reads the report written by the extraction with --report, given with
+    /// --report again, and deletes the files it records as written from the
+    /// output directory it records, along with the directories left empty.
+    /// Files whose sizes, or the hashes of hashed assets, no longer match the
+    /// report were modified since, and are kept unless --force is given,
+    /// exiting with 2. Files the extraction skipped are never deleted.
+    CleanRun(clean::CleanRunSubcommand),
+    /// Checks whether the files an extraction wrote changed since.
+    ///
+    /// Reads the report written by the extraction with --report, given with
+    /// --report again, and lists the files it records as written which were
+    /// modified or deleted since, as for `clean-run`, along with the files
+    /// it doesn't record within the directories it wrote to, e.g. `assets/`.
+    /// Hashed assets are hashed on every CPU, unless --size-only is given.
+    /// Exits with 1 if anything changed.
+    VerifyReport(clean::VerifyReportSubcommand),
+    /// Checks that the references between the assets of a directory resolve.
+    ///
+    /// Reads every blockstate, model, font and `sounds.json` file within
+    /// `assets`, and lists the files they reference which don't exist,
+    /// grouped by the file referencing them. With --against, files of that
+    /// version exist too, as the directory is taken to override it like a
+    /// resource pack. Exits with 1 if any reference is dangling or any file
+    /// couldn't be read.
+    ValidatePack(references::ValidatePackSubcommand),
+    /// Checks that every sound of a version's sound events can be played.
+    ///
+    /// Reads `minecraft/sounds.json` from the version's index file, or from
+    /// its jar file for older versions, and lists the sound files each sound
+    /// event plays which aren't in the index file or whose hashed files are
+    /// missing, as after a broken download. Sounds which play another sound
+    /// event only need that event to be defined. Exits with 1 if any sound is
+    /// broken.
+    CheckSounds(sounds::CheckSoundsSubcommand),
+    /// Serves a version's assets over HTTP, without extracting anything.
+    ///
+    /// Paths like `/assets/minecraft/textures/block/stone.png` are served
+    /// from the jar file or the hashed assets, as they would be extracted,
+    /// and directory paths like `/assets/minecraft/` with a JSON listing of
+    /// their files and subdirectories. Only GET and HEAD requests are
+    /// answered, one at a time and each on its own connection. Press Ctrl-C
+    /// to stop.
+    Serve(serve::ServeSubcommand),
+    /// Lists the profiles defined in the config file.
+    ///
+    /// Prints the arguments each option of each profile expands to, or only
+    /// those of the profile given with --profile, marking the options given
+    /// on the command line, which override the profile's.
+    Profiles(config::ProfilesSubcommand),
+    /// Prints a script completing the arguments of this tool in a shell.
+    ///
+    /// Besides subcommands and options, the script completes the names of
+    /// the installed versions, of the index files in `assets/indexes/`, and
+    /// of the locales of the newest index file, found in the same places as
+    /// when extracting. Load it in the shell's startup file, e.g. with
+    /// `source <(extract-minecraft-assets completions bash)` in `~/.bashrc`.
+    Completions(complete::CompletionsSubcommand),
 }
 
 #[derive(Subcommand)]
@@ -52,48 +612,721 @@ enum ExtractSubcommand {
     Jar(jar::JarSubcommand),
     /// Extracts both hashed and non-hashed Minecraft `assets`, or `data`.
     Version(version::VersionSubcommand),
+    /// Extracts only the files which were added or modified between two
+    /// versions.
+    ///
+    /// The jar files and index files of both versions are compared, and
+    /// only the newer version's added and modified files are extracted. The
+    /// summary counts the files left out because they were unchanged.
+    Changed(changed::ChangedSubcommand),
+    /// Interactively browses a version's jar and hashed assets.
+    ///
+    /// Files and directories can be marked and then extracted on demand.
+    Browse(browse::BrowseSubcommand),
+    /// Extracts every installed version, each into a directory named after
+    /// it.
+    ///
+    /// Every directory in `.minecraft/versions/` with a `.jar` file and
+    /// manifest is extracted into `<output>/<version>/`. Identical files are
+    /// stored once: each file is hard linked to its contents in
+    /// `<output>/.pool/`, named by its hash, so don't extract anything else
+    /// into the version directories afterwards. Versions are extracted into
+    /// `<output>/.staging/` and only moved into place once finished, so
+    /// running again skips the versions already extracted, and redoes any
+    /// which failed or were interrupted.
+    All(all::AllSubcommand),
+}
+
+/// Options which apply to every extraction subcommand.
+pub struct Options {
+    /// The options shared by every extraction, which subcommands complete
+    /// with their own arguments (e.g. which contents to extract).
+    pub extract: ExtractOptions,
+    /// Whether to use a stable, line-oriented, unstyled format for stdout.
+    pub porcelain: bool,
+    /// The number of bytes above which extraction must be confirmed.
+    pub confirm_over: u64,
+    /// Whether to extract without asking for confirmation.
+    pub yes: bool,
+    /// Whether nothing is written, only listing the files which would be.
+    pub dry_run: bool,
+}
+
+impl Options {
+    /// Asks for confirmation to extract `bytes` bytes into the output
+    /// directory, if that exceeds [`confirm_over`](Options::confirm_over) or
+    /// the free space of its volume.
+    ///
+    /// Fails if the extraction is declined, or if confirmation is needed but
+    /// stdin or stderr is not a terminal. Dry runs are never confirmed, as
+    /// nothing is written.
+    pub fn confirm_size(&self, bytes: u64) -> io::Result<()> {
+        let output_dir = &self.extract.output_dir;
+        // Existing files may be skipped or replaced, so there may be enough
+        // space after all.
+        let free_space = util::free_space(output_dir).filter(|free_space| *free_space < bytes);
+        if self.yes || self.dry_run || (bytes <= self.confirm_over && free_space.is_none()) {
+            return Ok(());
+        }
+
+        let size = util::format_size(bytes);
+        let short_of_space = free_space
+            .map(|free_space| format!(", with only {} free", util::format_size(free_space)))
+            .unwrap_or_default();
+
+        if !io::stdin().is_terminal() || !io::stderr().is_terminal() {
+            return Err(io::Error::other(format!(
+                "extracting {size} into '{}'{short_of_space} requires confirmation: pass --yes \
+                 to proceed",
+                output_dir.display()
+            )));
+        }
+
+        eprint!(
+            "Extract {size} into '{}'{short_of_space}? [y/N] ",
+            output_dir.display()
+        );
+        io::stderr().flush()?;
+
+        let mut answer = String::new();
+        PROMPTING.store(true, Ordering::SeqCst);
+        let read = io::stdin().read_line(&mut answer);
+        PROMPTING.store(false, Ordering::SeqCst);
+        read?;
+
+        if matches!(answer.trim().to_lowercase().as_str(), "y" | "yes") {
+            Ok(())
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::Interrupted,
+                "extraction cancelled",
+            ))
+        }
+    }
 }
 
 trait ExtractCmd {
+    /// Returns the directories which the subcommand reads from.
+    ///
+    /// These are used to refuse extracting into the source of the extraction.
+    fn source_dirs(&self) -> Vec<PathBuf>;
+
     /// Executes and consumes the subcommand.
-    fn execute(self, output_dir: PathBuf, ignore_top_level: bool) -> io::Result<()>;
+    ///
+    /// Only the primary result of the subcommand may be written to stdout;
+    /// everything else is written to stderr.
+    fn execute(self, options: &Options) -> Result<ExtractionReport, Error>;
+
+    /// Returns what the subcommand extracts from, to describe it in a git
+    /// commit.
+    #[cfg(feature = "git")]
+    fn git_source(&self) -> cli::git::Source {
+        cli::git::Source::default()
+    }
+}
+
+impl ExtractSubcommand {
+    /// Returns the sources which the subcommand extracts in full, and so can
+    /// be synced with, or the name of the subcommand if it can't be synced.
+    fn synced_sources(&self) -> Result<Vec<FileSource>, &'static str> {
+        match self {
+            Self::Hashed(_) => Ok(vec![FileSource::Hashed]),
+            Self::Jar(_) => Ok(vec![FileSource::Jar]),
+            #[cfg(feature = "watch")]
+            Self::Version(subcommand) if subcommand.watch => Err("version --watch"),
+            Self::Version(_) => Ok(vec![FileSource::Jar, FileSource::Hashed]),
+            Self::Changed(_) => Err("changed"),
+            Self::Browse(_) => Err("browse"),
+            Self::All(_) => Err("all"),
+        }
+    }
+
+    /// Returns the name of the subcommand if it writes files other than
+    /// through the [`vfs`](ExtractOptions::vfs) of its options, and so can't
+    /// be dry run.
+    fn dry_runnable(&self) -> Result<(), &'static str> {
+        match self {
+            Self::Browse(_) => Err("browse"),
+            Self::All(_) => Err("all"),
+            _ => Ok(()),
+        }
+    }
 }
 
 impl ExtractCmd for ExtractSubcommand {
-    fn execute(self, output_dir: PathBuf, ignore_top_level: bool) -> io::Result<()> {
+    fn source_dirs(&self) -> Vec<PathBuf> {
         match self {
-            Self::Hashed(subcommand) => subcommand.execute(output_dir, ignore_top_level),
-            Self::Jar(subcommand) => subcommand.execute(output_dir, ignore_top_level),
-            Self::Version(subcommand) => subcommand.execute(output_dir, ignore_top_level),
+            Self::Hashed(subcommand) => subcommand.source_dirs(),
+            Self::Jar(subcommand) => subcommand.source_dirs(),
+            Self::Version(subcommand) => subcommand.source_dirs(),
+            Self::Changed(subcommand) => subcommand.source_dirs(),
+            Self::Browse(subcommand) => subcommand.source_dirs(),
+            Self::All(subcommand) => subcommand.source_dirs(),
+        }
+    }
+
+    fn execute(self, options: &Options) -> Result<ExtractionReport, Error> {
+        match self {
+            Self::Hashed(subcommand) => subcommand.execute(options),
+            Self::Jar(subcommand) => subcommand.execute(options),
+            Self::Version(subcommand) => subcommand.execute(options),
+            Self::Changed(subcommand) => subcommand.execute(options),
+            Self::Browse(subcommand) => subcommand.execute(options),
+            Self::All(subcommand) => subcommand.execute(options),
+        }
+    }
+
+    #[cfg(feature = "git")]
+    fn git_source(&self) -> cli::git::Source {
+        match self {
+            Self::Hashed(subcommand) => subcommand.git_source(),
+            Self::Jar(subcommand) => subcommand.git_source(),
+            Self::Version(subcommand) => subcommand.git_source(),
+            Self::Changed(subcommand) => subcommand.git_source(),
+            Self::Browse(subcommand) => subcommand.git_source(),
+            Self::All(subcommand) => subcommand.git_source(),
+        }
+    }
+}
+
+/// Checks that extracting into `output_dir` is safe before anything is
+/// extracted: that it is an existing directory in which files can be
+/// created, and that it doesn't overlap
+/// with the Minecraft installation or the `source_dirs` being extracted from,
+/// unless `allow_overlap` is set.
+///
+/// Paths are canonicalized, so that symlinks can't hide an overlap.
+fn check_output_dir(
+    output_dir: &Path,
+    source_dirs: Vec<PathBuf>,
+    ignore_top_level: bool,
+    allow_overlap: bool,
+) -> Result<(), Error> {
+    if !allow_overlap {
+        let enclosing = util::minecraft_dir().into_iter().chain(source_dirs.clone());
+
+        if let Some((output, source_dir)) = util::find_enclosing_dir(output_dir, enclosing)? {
+            return Err(Error::OutputOverlaps {
+                output,
+                source_dir,
+                contains: false,
+            });
+        }
+
+        // Only the directories files are extracted into are written to, so the
+        // output directory may contain the Minecraft installation elsewhere.
+        let written: Vec<_> = if ignore_top_level {
+            vec![output_dir.to_owned()]
+        } else {
+            ExtractedContents::all()
+                .roots()
+                .iter()
+                .map(|root| output_dir.join(&root.output_dir))
+                .collect()
+        };
+        for source_dir in source_dirs {
+            if let Some((source_dir, _)) = util::find_enclosing_dir(&source_dir, written.clone())? {
+                return Err(Error::OutputOverlaps {
+                    output: util::canonicalize_lenient(output_dir)?,
+                    source_dir,
+                    contains: true,
+                });
+            }
         }
     }
+
+    if !output_dir.is_dir() {
+        return Err(Error::OutputNotADirectory {
+            path: output_dir.to_owned(),
+        });
+    }
+
+    util::probe_writable(output_dir).map_err(|source| Error::OutputNotWritable {
+        path: output_dir.to_owned(),
+        source,
+    })?;
+
+    Ok(())
+}
+
+/// Returns a token which is cancelled when the user presses Ctrl-C.
+///
+/// The extraction then stops after the file it is extracting. Pressing Ctrl-C
+/// again, or while being asked for confirmation, exits immediately.
+fn handle_interrupts() -> io::Result<CancellationToken> {
+    let token = CancellationToken::new();
+    let handler_token = token.clone();
+
+    ctrlc::set_handler(move || {
+        if handler_token.is_cancelled() || PROMPTING.load(Ordering::SeqCst) {
+            let _ = cli::progress::clear();
+            cli::lock::release();
+            process::exit(130);
+        }
+
+        handler_token.cancel();
+    })
+    .map_err(io::Error::other)?;
+
+    Ok(token)
 }
 
-fn main() -> io::Result<()> {
+/// Returns the exit code of a subcommand which prints its own output, first
+/// printing its error (if any).
+fn finish(result: Result<ExitCode, Error>, json: bool) -> io::Result<ExitCode> {
+    match result {
+        Ok(exit_code) => Ok(exit_code),
+        Err(error) => {
+            eprintln!("{error}");
+            if json {
+                Document::new(Failure {
+                    error: error.to_string(),
+                })
+                .print()?;
+            }
+
+            Ok(error.exit_code())
+        }
+    }
+}
+
+/// Prints `error` from parsing the arguments and exits, like
+/// [`clap::Error::exit`], but with 3 rather than 2 for versions and index
+/// files which can't be found, as they are Minecraft files which are missing.
+fn exit_parsing(error: clap::Error) -> ! {
+    let missing = std::error::Error::source(&error)
+        .is_some_and(|source| source.is::<InvalidVersionArg>() || source.is::<ExtractError>());
+
+    if missing {
+        let _ = error.print();
+        process::exit(3);
+    }
+    error.exit()
+}
+
+fn main() -> io::Result<ExitCode> {
+    let command = ExtractCommand::command();
+    let args: Vec<OsString> = env::args_os().collect();
+    // Completing is done before anything else, e.g. loading the config
+    // file, as it must be fast and never fail.
+    if args.get(1).is_some_and(|arg| arg == complete::COMPLETE) {
+        let words = args.get(2..).unwrap_or_default();
+        complete::complete(command, words.strip_prefix(&["--".into()]).unwrap_or(words));
+
+        return Ok(ExitCode::SUCCESS);
+    }
+    // Arguments which the profile may give, e.g. `--assets`, aren't required
+    // yet, so the arguments are only validated once it is applied.
+    let matches = command
+        .clone()
+        .ignore_errors(true)
+        .try_get_matches_from(&args)
+        .unwrap_or_else(|error| error.exit());
+    let args = match config::apply_profile(&command, &matches, args) {
+        Ok(args) => args,
+        Err(error) => return finish(Err(error), matches.get_flag("json")),
+    };
+
     let ExtractCommand {
         subcommand,
         output_dir,
         ignore_top_level,
-    } = ExtractCommand::parse();
+        strip_namespace,
+        include,
+        exclude,
+        group_by,
+        convert_lang,
+        convert_nbt,
+        keep_original,
+        format_json,
+        format_json_max_size,
+        text_normalize,
+        validate_content,
+        strict_permissions,
+        verify,
+        threads,
+        locked_retries,
+        locked_retry_delay,
+        no_decode_paths,
+        duplicate_entries,
+        on_conflict,
+        force_replace_dirs,
+        unsafe_allow_overlap,
+        dry_run,
+        no_lock,
+        porcelain,
+        json,
+        print0,
+        print,
+        written_list,
+        confirm_over,
+        yes,
+        profile: _,
+        report: report_file,
+        catalog: catalog_format,
+        archive,
+        resource_pack,
+        sync,
+        #[cfg(feature = "git")]
+        git,
+        #[cfg(feature = "notify")]
+        notify,
+    } = ExtractCommand::try_parse_from(args).unwrap_or_else(|error| exit_parsing(error));
 
-    let output_dir = output_dir.map(Ok).unwrap_or_else(|| env::current_dir())?;
+    cli::progress::clear_on_panic();
 
-    if output_dir.is_dir() {
-        let result = subcommand.execute(output_dir, ignore_top_level);
+    let output_dir = output_dir.map(Ok).unwrap_or_else(env::current_dir)?;
+    // The options which decide where files would be written, for the
+    // subcommands which only estimate an extraction.
+    let estimate_options = || {
+        ExtractOptions::new()
+            .output(&output_dir)
+            .ignore_top_level(ignore_top_level)
+            .strip_namespace(strip_namespace)
+            .include(include.iter().cloned().collect())
+            .exclude(exclude.iter().cloned().collect())
+            .group_by(group_by.map(Into::into))
+            .decode_paths(!no_decode_paths)
+    };
 
-        let mut stdout = io::stdout();
-        stdout.execute(terminal::Clear(ClearType::FromCursorDown))?;
+    let subcommand = match subcommand {
+        Command::Extract(subcommand) => subcommand,
+        Command::Doctor(subcommand) => return subcommand.execute(&output_dir, json),
+        Command::DiffIndex(subcommand) => {
+            return finish(
+                subcommand
+                    .execute(json, porcelain)
+                    .map(|()| ExitCode::SUCCESS),
+                json,
+            );
+        }
+        Command::DiffJar(subcommand) => {
+            return finish(
+                subcommand
+                    .execute(json, porcelain)
+                    .map(|()| ExitCode::SUCCESS),
+                json,
+            );
+        }
+        Command::DiffOutput(subcommand) => {
+            return finish(subcommand.execute(ignore_top_level, json, porcelain), json);
+        }
+        Command::Stats(subcommand) => {
+            return finish(subcommand.execute(json).map(|()| ExitCode::SUCCESS), json);
+        }
+        Command::Du(subcommand) => {
+            return finish(
+                subcommand
+                    .execute(&estimate_options(), json)
+                    .map(|()| ExitCode::SUCCESS),
+                json,
+            );
+        }
+        Command::Tree(subcommand) => {
+            return finish(
+                subcommand
+                    .execute(&estimate_options(), json)
+                    .map(|()| ExitCode::SUCCESS),
+                json,
+            );
+        }
+        Command::Duplicates(subcommand) => {
+            return finish(subcommand.execute(json).map(|()| ExitCode::SUCCESS), json);
+        }
+        Command::List(subcommand) => {
+            return finish(
+                subcommand
+                    .execute(json, porcelain)
+                    .map(|()| ExitCode::SUCCESS),
+                json,
+            );
+        }
+        Command::Largest(subcommand) => {
+            return finish(subcommand.execute(json).map(|()| ExitCode::SUCCESS), json);
+        }
+        Command::Search(subcommand) => return finish(subcommand.execute(json), json),
+        Command::Grep(subcommand) => return finish(subcommand.execute(json), json),
+        Command::WhichIndex(subcommand) => {
+            return finish(subcommand.execute(json, porcelain), json);
+        }
+        Command::HashPath(subcommand) => {
+            return finish(subcommand.execute(json, porcelain), json);
+        }
+        Command::Indexes(subcommand) => {
+            return finish(subcommand.execute(json).map(|()| ExitCode::SUCCESS), json);
+        }
+        Command::Lang(subcommand) => return finish(subcommand.execute(&output_dir, json), json),
+        Command::Locales(subcommand) => return finish(subcommand.execute(json), json),
+        Command::Libraries(subcommand) => {
+            return finish(subcommand.execute(&output_dir, json), json);
+        }
+        Command::ValidatePack(subcommand) => {
+            return finish(
+                subcommand.execute(&output_dir, ignore_top_level, json),
+                json,
+            );
+        }
+        Command::CheckSounds(subcommand) => return finish(subcommand.execute(json), json),
+        Command::Serve(subcommand) => {
+            return finish(subcommand.execute(handle_interrupts()?), json);
+        }
+        Command::Profiles(subcommand) => {
+            return finish(subcommand.execute(&command, &matches), json);
+        }
+        Command::Completions(subcommand) => {
+            return finish(
+                subcommand
+                    .execute(&command)
+                    .map(|()| ExitCode::SUCCESS)
+                    .map_err(Error::from),
+                json,
+            );
+        }
+        Command::CleanRun(subcommand) => {
+            return finish(
+                subcommand.execute(report_file.as_deref(), no_lock, dry_run, json),
+                json,
+            );
+        }
+        Command::VerifyReport(subcommand) => {
+            return finish(subcommand.execute(report_file.as_deref(), json), json);
+        }
+    };
+
+    if let Err(error) = check_output_dir(
+        &output_dir,
+        subcommand.source_dirs(),
+        // Grouped files are written anywhere within the output directory.
+        ignore_top_level || group_by.is_some(),
+        unsafe_allow_overlap,
+    ) {
+        return finish(Err(error), json);
+    }
+
+    if dry_run && let Err(command) = subcommand.dry_runnable() {
+        return finish(
+            Err(Error::DryRunUnsupported {
+                command: command.to_owned(),
+            }),
+            json,
+        );
+    }
+
+    let synced_sources = match subcommand.synced_sources() {
+        Ok(sources) => sources,
+        Err(command) if sync.is_enabled() => {
+            return finish(
+                Err(Error::SyncUnsupported {
+                    command: command.to_owned(),
+                }),
+                json,
+            );
+        }
+        Err(_) => Vec::new(),
+    };
+
+    // Held until the extraction finishes, or released on Ctrl-C.
+    let _lock = match (!no_lock && !dry_run).then(|| OutputLock::acquire(&output_dir)) {
+        Some(Ok(lock)) => Some(lock),
+        Some(Err(error)) => return finish(Err(error), json),
+        None => None,
+    };
+    let config = config::Config::load()?;
+    if print0 || print || written_list.is_some() {
+        let terminator = if print { b'\n' } else { b'\0' };
+        if let Err(error) = written::start(&output_dir, terminator, written_list.as_deref()) {
+            return finish(Err(error.into()), json);
+        }
+    }
+    let resource_pack = match resource_pack.prepare(&output_dir) {
+        Ok(resource_pack) => resource_pack,
+        Err(error) => return finish(Err(error), json),
+    };
+    #[cfg(feature = "git")]
+    let git_source = subcommand.git_source();
+    #[cfg(feature = "git")]
+    let git_commit = match git.prepare(&output_dir, &git_source) {
+        Ok(git_commit) => git_commit,
+        Err(error) => return finish(Err(error), json),
+    };
+    let archive = archive.prepare();
+    let mut extract_options = ExtractOptions::new()
+        .output(output_dir)
+        .ignore_top_level(ignore_top_level)
+        .strip_namespace(strip_namespace)
+        .include(include.into_iter().collect())
+        .exclude(exclude.into_iter().collect())
+        .group_by(group_by.map(Into::into))
+        .convert_lang(convert_lang.map(Into::into))
+        .convert_nbt(convert_nbt.map(Into::into))
+        .keep_original(keep_original)
+        .format_json(format_json.map(Into::into))
+        .format_json_max_size(format_json_max_size)
+        .text_normalize(text_normalize.map(Into::into))
+        .validate_content(validate_content.into_iter().flatten().map(Into::into))
+        .strict_permissions(strict_permissions)
+        .verify(verify.map(Into::into))
+        .threads(
+            threads
+                .or_else(|| thread::available_parallelism().ok())
+                .map_or(1, NonZero::get),
+        )
+        .locked_retry(LockedRetry {
+            retries: locked_retries,
+            delay: Duration::from_millis(locked_retry_delay),
+        })
+        .decode_paths(!no_decode_paths)
+        .duplicate_entries(duplicate_entries.into())
+        .overwrite(on_conflict.into())
+        .replace_dirs(force_replace_dirs)
+        .cancellation(handle_interrupts()?);
+    if on_conflict == conflict::OnConflict::Prompt {
+        match conflict::prompt() {
+            Ok(prompt) => extract_options = extract_options.overwrite_prompt(prompt),
+            Err(error) => return finish(Err(error.into()), json),
+        }
+    }
+    if let Some(archive) = &archive {
+        extract_options = extract_options.vfs(archive.vfs());
+    } else if dry_run {
+        extract_options = extract_options.vfs(Arc::new(DryRunFs));
+    }
+    let options = Options {
+        extract: extract_options,
+        porcelain,
+        confirm_over: match confirm_over {
+            Some(confirm_over) => confirm_over,
+            None => config.confirm_over()?,
+        },
+        yes,
+        dry_run,
+    };
+
+    // Prints the outcome of the extraction, returning the exit code.
+    let extract = |subcommand: ExtractSubcommand| -> io::Result<ExitCode> {
+        let result = subcommand.execute(&options);
+
+        cli::progress::clear()?;
+
+        let result = result.and_then(|mut report| {
+            sync.finish(&mut report, &synced_sources, dry_run)?;
+            if let Some(resource_pack) = &resource_pack {
+                resource_pack.finish(&report)?;
+            }
+            if let Some(archive) = &archive {
+                archive.finish(&report)?;
+            }
+            #[cfg(feature = "git")]
+            if let Some(git_commit) = &git_commit {
+                git_commit.commit(&report, &git_source)?;
+            }
 
+            Ok(report)
+        });
+
+        // Failing to write the report, catalog or list of written files fails
+        // the extraction, as they may be relied on by whatever runs this.
+        let result = result.and_then(|extraction| {
+            // The files which would be extracted are the result of a dry
+            // run, so they are listed on stdout, unless the JSON document
+            // or the --print0 list already has them there.
+            if dry_run && !json && !print0 && !print {
+                let mut stdout = io::stdout().lock();
+                for file in &extraction.files {
+                    if let FileOutcome::Written { .. } = file.outcome {
+                        let path = extraction.options.output_dir.join(&file.path);
+                        if porcelain {
+                            writeln!(stdout, "{}", path.display())?;
+                        } else {
+                            writeln!(stdout, "Would extract '{}'", path.display())?;
+                        }
+                    }
+                }
+            }
+            for file in extraction.failed() {
+                if let FileOutcome::Failed { error } = &file.outcome {
+                    eprintln!("Failed to extract '{}': {error}", file.path.display());
+                }
+            }
+            for warning in &extraction.warnings {
+                eprintln!(
+                    "Warning for '{}': {}",
+                    warning.path.display(),
+                    warning.message
+                );
+            }
+
+            written::flush()?;
+            if let Some(path) = &report_file {
+                Document::new(&extraction).write(path)?;
+            }
+            if let Some(format) = catalog_format {
+                let path = catalog::write(format, &extraction)?;
+                eprintln!("Wrote the catalog to '{}'", path.display());
+            }
+
+            Ok(extraction)
+        });
+
+        if json {
+            match &result {
+                Ok(report) => Document::new(report).print()?,
+                Err(error) => Document::new(Failure {
+                    error: error.to_string(),
+                })
+                .print()?,
+            }
+        }
+
+        let extraction = if dry_run { "Dry run" } else { "Extraction" };
         match &result {
-            Ok(_) => println!("Extraction complete"),
-            Err(error) => eprintln!("Extraction failed: {error}"),
+            Ok(report) if report.summary.cancelled => {
+                eprintln!("{extraction} cancelled: {report}")
+            }
+            Ok(report) if report.summary.disk_full => eprintln!(
+                "Extraction stopped as the output volume is full, having written {} into '{}' \
+                 with {} left to extract: {report}",
+                util::format_size(report.summary.bytes),
+                report.options.output_dir.display(),
+                util::format_size(report.summary.remaining_bytes)
+            ),
+            Ok(report) if report.summary.failures > 0 => eprintln!(
+                "{extraction} finished with {} {}: {report}",
+                report.summary.failures,
+                if report.summary.failures == 1 {
+                    "error"
+                } else {
+                    "errors"
+                }
+            ),
+            Ok(report) => eprintln!("{extraction} complete: {report}"),
+            Err(error) => eprintln!("{extraction} failed: {error}"),
         }
 
-        result
-    } else {
-        panic!(
-            "'{}' does not exist or is not a directory",
-            output_dir.display()
+        #[cfg(feature = "notify")]
+        if notify {
+            cli::notify::notify(&result);
+        }
+
+        Ok(match result {
+            Ok(report) if report.summary.cancelled => ExitCode::from(130),
+            Ok(report) if report.summary.disk_full => ExitCode::from(4),
+            Ok(report) if report.summary.failures > 0 => ExitCode::from(2),
+            Ok(_) => ExitCode::SUCCESS,
+            Err(error) => error.exit_code(),
+        })
+    };
+
+    #[cfg(feature = "watch")]
+    if let ExtractSubcommand::Version(version) = &subcommand
+        && version.watch
+    {
+        return cli::watch::watch(
+            || version.watched_files(),
+            &options.extract.cancellation.clone().unwrap_or_default(),
+            || extract(ExtractSubcommand::Version(version.clone())),
         );
     }
+
+    extract(subcommand)
 }
@@ -0,0 +1,186 @@
+//! Finding every file used by blockstates and models, e.g. to extract a block
+//! along with its models and textures.
+//!
+//! Blockstates (`assets/<namespace>/blockstates/<name>.json`) pick models for
+//! each state of a block, and models
+//! (`assets/<namespace>/models/<name>.json`) inherit from parent models,
+//! name their textures, and (for item models) override the model in some
+//! situations.
+
+use crate::closure::{self, AssetSources, Closure, Reference};
+use crate::error::ExtractError;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// A blockstate definition, with either `variants` or `multipart`.
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize)]
+struct BlockState {
+    #[serde(default)]
+    variants: BTreeMap<String, Variants>,
+    #[serde(default)]
+    multipart: Vec<MultipartCase>,
+}
+
+/// The models of a variant, one of which is picked at random if there are
+/// several.
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize)]
+#[serde(untagged)]
+enum Variants {
+    One(Variant),
+    Many(Vec<Variant>),
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize)]
+struct Variant {
+    /// The model, e.g. `minecraft:block/oak_stairs` within `models`.
+    model: String,
+}
+
+/// A case of a `multipart` blockstate, applying its models when its
+/// condition holds.
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize)]
+struct MultipartCase {
+    apply: Variants,
+}
+
+/// A block or item model.
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize)]
+struct Model {
+    /// The model inherited from, e.g. `minecraft:block/stairs` within
+    /// `models`.
+    parent: Option<String>,
+    /// The textures of the model by variable name.
+    #[serde(default)]
+    textures: BTreeMap<String, Texture>,
+    /// The models replacing an item model in some situations.
+    #[serde(default)]
+    overrides: Vec<Override>,
+}
+
+/// The value of a texture variable.
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize)]
+#[serde(untagged)]
+enum Texture {
+    /// A texture, e.g. `minecraft:block/oak_planks` within `textures`, or
+    /// another variable, e.g. `#side`.
+    Name(String),
+    Sprite {
+        sprite: String,
+    },
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize)]
+struct Override {
+    model: String,
+}
+
+/// Returns the files referenced by the blockstate or model at `path`, within
+/// `assets`, containing `contents`.
+///
+/// Texture variables like `#side` are resolved by the textures of the model
+/// or its parents, which are referenced themselves, so only textures are
+/// references. Built-in parents like `builtin/generated` aren't files, and
+/// files which are neither blockstates nor models reference nothing.
+///
+/// # Examples
+/// ```
+/// use extract_minecraft_assets::closure::Reference;
+/// use extract_minecraft_assets::models;
+/// use std::path::{Path, PathBuf};
+///
+/// let references = models::model_references(
+///     Path::new("minecraft/models/block/oak_stairs.json"),
+///     br##"{
+///         "parent": "minecraft:block/stairs",
+///         "textures": {"side": "minecraft:block/oak_planks", "particle": "#side"}
+///     }"##,
+/// )?;
+///
+/// assert_eq!(
+///     references,
+///     [
+///         Reference::Required(PathBuf::from("minecraft/models/block/stairs.json")),
+///         Reference::Required(PathBuf::from("minecraft/textures/block/oak_planks.png")),
+///         Reference::Optional(PathBuf::from("minecraft/textures/block/oak_planks.png.mcmeta")),
+///     ]
+/// );
+/// # Ok::<(), serde_json::Error>(())
+/// ```
+pub fn model_references(path: &Path, contents: &[u8]) -> Result<Vec<Reference>, serde_json::Error> {
+    let kind = path.components().nth(1).map(|kind| kind.as_os_str());
+    let mut references = Vec::new();
+
+    if kind == Some("blockstates".as_ref()) {
+        let block_state: BlockState = serde_json::from_slice(contents)?;
+        let variants = block_state
+            .variants
+            .values()
+            .chain(block_state.multipart.iter().map(|case| &case.apply));
+
+        for variants in variants {
+            let variants = match variants {
+                Variants::One(variant) => std::slice::from_ref(variant),
+                Variants::Many(variants) => variants,
+            };
+            references.extend(
+                variants
+                    .iter()
+                    .map(|variant| Reference::Required(model_path(&variant.model))),
+            );
+        }
+    } else if kind == Some("models".as_ref()) {
+        let model: Model = serde_json::from_slice(contents)?;
+
+        if let Some(parent) = &model.parent
+            && !is_builtin(parent)
+        {
+            references.push(Reference::Required(model_path(parent)));
+        }
+        for texture in model.textures.values() {
+            let (Texture::Name(name) | Texture::Sprite { sprite: name }) = texture;
+            if name.starts_with('#') {
+                continue;
+            }
+
+            let texture = closure::resource_path(name, "textures", ".png");
+            // Animated textures have their animation alongside.
+            let mut animation = texture.clone().into_os_string();
+            animation.push(".mcmeta");
+
+            references.push(Reference::Required(texture));
+            references.push(Reference::Optional(animation.into()));
+        }
+        references.extend(
+            model
+                .overrides
+                .iter()
+                .map(|model_override| Reference::Required(model_path(&model_override.model))),
+        );
+    }
+
+    Ok(references)
+}
+
+/// Returns the path of the model `id` within `assets`, e.g.
+/// `minecraft/models/block/stairs.json` for `minecraft:block/stairs`.
+fn model_path(id: &str) -> PathBuf {
+    closure::resource_path(id, "models", ".json")
+}
+
+/// Whether the model `id` is built into the game rather than a file, e.g.
+/// `builtin/generated`.
+fn is_builtin(id: &str) -> bool {
+    let path = id.strip_prefix("minecraft:").unwrap_or(id);
+
+    path.starts_with("builtin/")
+}
+
+/// Returns the closure of the blockstates and models at `roots`, within
+/// `assets`: the roots and every model and texture they reference.
+pub fn models_closure(
+    sources: &mut AssetSources,
+    roots: impl IntoIterator<Item = PathBuf>,
+) -> Result<Closure, ExtractError> {
+    closure::resolve(sources, roots, model_references)
+}
@@ -0,0 +1,350 @@
+//! Reading the binary NBT files of data packs, e.g. the structures in
+//! `data/minecraft/structure/`, and converting them to SNBT, the text format
+//! of NBT, so that they can be read and diffed.
+//!
+//! Only reading is supported. The SNBT written is meant to be read by people
+//! rather than parsed back into the same binary file.
+
+use flate2::read::{GzDecoder, ZlibDecoder};
+use serde::Serialize;
+use std::fmt::{self, Display, Formatter, Write};
+use std::io::{self, Read};
+use std::path::Path;
+use thiserror::Error;
+
+/// The deepest compounds and lists can be nested, as in the game.
+const MAX_DEPTH: usize = 512;
+
+/// A format to which to convert NBT files.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NbtFormat {
+    /// Indented SNBT, e.g. `{DataVersion: 3953, size: [1, 2, 1]}`.
+    Snbt,
+}
+
+/// A value of an NBT file.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Tag {
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    ByteArray(Vec<i8>),
+    String(String),
+    List(Vec<Tag>),
+    /// The named values of a compound, in the order they are in the file.
+    Compound(Vec<(String, Tag)>),
+    IntArray(Vec<i32>),
+    LongArray(Vec<i64>),
+}
+
+/// An error while reading an NBT file, in which case it should be kept as
+/// it is.
+#[derive(Error, Debug)]
+#[non_exhaustive]
+pub enum NbtError {
+    /// The file ended early, or couldn't be decompressed.
+    #[error("{0}")]
+    Io(#[from] io::Error),
+    /// A tag has an ID which isn't one of NBT's.
+    #[error("unknown tag type {0}")]
+    UnknownTag(u8),
+    /// The root of the file isn't a compound.
+    #[error("the root tag isn't a compound")]
+    RootNotCompound,
+    /// Compounds or lists are nested too deeply.
+    #[error("tags are nested more than {MAX_DEPTH} deep")]
+    TooDeep,
+    /// An array or list has a negative length.
+    #[error("negative length {0}")]
+    NegativeLength(i32),
+}
+
+impl NbtFormat {
+    /// The extension of files of this format, e.g. `snbt`.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Snbt => "snbt",
+        }
+    }
+}
+
+impl Display for NbtFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.extension())
+    }
+}
+
+/// Whether the file at `path` is an NBT file, i.e. has the `nbt` extension.
+pub fn is_nbt(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| extension == "nbt")
+}
+
+/// Converts the `contents` of an NBT file, gzipped, zlib-compressed or not
+/// compressed at all, to `format`.
+///
+/// # Examples
+/// ```
+/// use extract_minecraft_assets::nbt::{self, NbtFormat};
+///
+/// // An uncompressed compound named "" holding `DataVersion: 3953`.
+/// let mut contents = vec![10, 0, 0, 3, 0, 11];
+/// contents.extend(b"DataVersion");
+/// contents.extend(3953_i32.to_be_bytes());
+/// contents.push(0);
+///
+/// let snbt = nbt::convert(&contents, NbtFormat::Snbt)?;
+///
+/// assert_eq!(snbt, b"{\n    DataVersion: 3953\n}\n");
+/// # Ok::<(), extract_minecraft_assets::nbt::NbtError>(())
+/// ```
+pub fn convert(contents: &[u8], format: NbtFormat) -> Result<Vec<u8>, NbtError> {
+    let root = read(contents)?;
+
+    match format {
+        NbtFormat::Snbt => {
+            let mut snbt = root.to_snbt();
+            snbt.push('\n');
+
+            Ok(snbt.into_bytes())
+        }
+    }
+}
+
+/// Reads the root compound of an NBT file from its `contents`, decompressing
+/// them first if they are gzipped or zlib-compressed.
+pub fn read(contents: &[u8]) -> Result<Tag, NbtError> {
+    match contents {
+        [0x1f, 0x8b, ..] => read_root(&mut GzDecoder::new(contents)),
+        // The two bytes of a zlib header are a multiple of 31.
+        [0x78, second, ..] if u16::from_be_bytes([0x78, *second]).is_multiple_of(31) => {
+            read_root(&mut ZlibDecoder::new(contents))
+        }
+        _ => read_root(&mut &*contents),
+    }
+}
+
+/// Reads the root tag from `reader`, which must be a compound. Its name is
+/// ignored, as it is empty in practice.
+fn read_root(reader: &mut impl Read) -> Result<Tag, NbtError> {
+    let id = read_u8(reader)?;
+    if id != 10 {
+        return Err(NbtError::RootNotCompound);
+    }
+    read_string(reader)?;
+
+    read_payload(reader, id, 0)
+}
+
+/// Reads the payload of a tag of type `id` from `reader`, nested `depth`
+/// deep.
+fn read_payload(reader: &mut impl Read, id: u8, depth: usize) -> Result<Tag, NbtError> {
+    if depth > MAX_DEPTH {
+        return Err(NbtError::TooDeep);
+    }
+
+    Ok(match id {
+        1 => Tag::Byte(i8::from_be_bytes(read_array(reader)?)),
+        2 => Tag::Short(i16::from_be_bytes(read_array(reader)?)),
+        3 => Tag::Int(i32::from_be_bytes(read_array(reader)?)),
+        4 => Tag::Long(i64::from_be_bytes(read_array(reader)?)),
+        5 => Tag::Float(f32::from_be_bytes(read_array(reader)?)),
+        6 => Tag::Double(f64::from_be_bytes(read_array(reader)?)),
+        7 => Tag::ByteArray(read_values(reader, |reader| {
+            Ok(i8::from_be_bytes(read_array(reader)?))
+        })?),
+        8 => Tag::String(read_string(reader)?),
+        9 => {
+            let element_id = read_u8(reader)?;
+            Tag::List(read_values(reader, |reader| {
+                read_payload(reader, element_id, depth + 1)
+            })?)
+        }
+        10 => {
+            let mut entries = Vec::new();
+            loop {
+                let id = read_u8(reader)?;
+                if id == 0 {
+                    break;
+                }
+                let name = read_string(reader)?;
+                entries.push((name, read_payload(reader, id, depth + 1)?));
+            }
+            Tag::Compound(entries)
+        }
+        11 => Tag::IntArray(read_values(reader, |reader| {
+            Ok(i32::from_be_bytes(read_array(reader)?))
+        })?),
+        12 => Tag::LongArray(read_values(reader, |reader| {
+            Ok(i64::from_be_bytes(read_array(reader)?))
+        })?),
+        id => return Err(NbtError::UnknownTag(id)),
+    })
+}
+
+/// Reads an `i32` length from `reader` followed by that many values read by
+/// `read_value`.
+fn read_values<R: Read, T>(
+    reader: &mut R,
+    mut read_value: impl FnMut(&mut R) -> Result<T, NbtError>,
+) -> Result<Vec<T>, NbtError> {
+    let length = i32::from_be_bytes(read_array(reader)?);
+    let length = usize::try_from(length).map_err(|_| NbtError::NegativeLength(length))?;
+
+    // The length isn't trusted to reserve space, as the file may be corrupt.
+    let mut values = Vec::new();
+    for _ in 0..length {
+        values.push(read_value(reader)?);
+    }
+
+    Ok(values)
+}
+
+fn read_u8(reader: &mut impl Read) -> Result<u8, NbtError> {
+    Ok(read_array::<1>(reader)?[0])
+}
+
+fn read_array<const N: usize>(reader: &mut impl Read) -> Result<[u8; N], NbtError> {
+    let mut bytes = [0; N];
+    reader.read_exact(&mut bytes)?;
+
+    Ok(bytes)
+}
+
+/// Reads a string prefixed by its `u16` length in bytes.
+///
+/// NBT strings are in Java's modified UTF-8, which only differs from UTF-8
+/// for null characters and characters outside the basic multilingual plane.
+/// Those are replaced, as the conversion is only meant to be read.
+fn read_string(reader: &mut impl Read) -> Result<String, NbtError> {
+    let length = u16::from_be_bytes(read_array(reader)?);
+    let mut bytes = vec![0; usize::from(length)];
+    reader.read_exact(&mut bytes)?;
+
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+impl Tag {
+    /// Formats the tag as SNBT, with the entries of compounds and lists of
+    /// compounds or lists on separate lines indented by four spaces.
+    pub fn to_snbt(&self) -> String {
+        let mut snbt = String::new();
+        self.write_snbt(&mut snbt, 0)
+            .expect("writing to a string can't fail");
+
+        snbt
+    }
+
+    fn write_snbt(&self, out: &mut String, indent: usize) -> fmt::Result {
+        match self {
+            Self::Byte(value) => write!(out, "{value}b"),
+            Self::Short(value) => write!(out, "{value}s"),
+            Self::Int(value) => write!(out, "{value}"),
+            Self::Long(value) => write!(out, "{value}L"),
+            Self::Float(value) => write!(out, "{value:?}f"),
+            Self::Double(value) => write!(out, "{value:?}d"),
+            Self::ByteArray(values) => {
+                write_array(out, "B", values.iter().map(|v| format!("{v}b")))
+            }
+            Self::IntArray(values) => write_array(out, "I", values.iter().map(i32::to_string)),
+            Self::LongArray(values) => {
+                write_array(out, "L", values.iter().map(|v| format!("{v}L")))
+            }
+            Self::String(value) => write_quoted(out, value),
+            Self::List(values) if values.is_empty() => write!(out, "[]"),
+            Self::List(values)
+                if values
+                    .iter()
+                    .all(|value| !matches!(value, Self::List(_) | Self::Compound(_))) =>
+            {
+                out.push('[');
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        out.push_str(", ");
+                    }
+                    value.write_snbt(out, indent)?;
+                }
+                write!(out, "]")
+            }
+            Self::List(values) => {
+                out.push('[');
+                for (i, value) in values.iter().enumerate() {
+                    out.push_str(if i > 0 { ",\n" } else { "\n" });
+                    push_indent(out, indent + 1);
+                    value.write_snbt(out, indent + 1)?;
+                }
+                out.push('\n');
+                push_indent(out, indent);
+                write!(out, "]")
+            }
+            Self::Compound(entries) if entries.is_empty() => write!(out, "{{}}"),
+            Self::Compound(entries) => {
+                out.push('{');
+                for (i, (name, value)) in entries.iter().enumerate() {
+                    out.push_str(if i > 0 { ",\n" } else { "\n" });
+                    push_indent(out, indent + 1);
+                    write_key(out, name)?;
+                    out.push_str(": ");
+                    value.write_snbt(out, indent + 1)?;
+                }
+                out.push('\n');
+                push_indent(out, indent);
+                write!(out, "}}")
+            }
+        }
+    }
+}
+
+fn push_indent(out: &mut String, indent: usize) {
+    for _ in 0..indent {
+        out.push_str("    ");
+    }
+}
+
+/// Writes a typed array, e.g. `[I; 1, 2, 3]`.
+fn write_array(
+    out: &mut String,
+    prefix: &str,
+    values: impl Iterator<Item = String>,
+) -> fmt::Result {
+    let values: Vec<_> = values.collect();
+
+    write!(out, "[{prefix};")?;
+    if !values.is_empty() {
+        write!(out, " {}", values.join(", "))?;
+    }
+    write!(out, "]")
+}
+
+/// Writes the name of a compound's entry, quoted unless it only has
+/// characters which don't need quoting.
+fn write_key(out: &mut String, name: &str) -> fmt::Result {
+    let unquoted = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '+'));
+
+    if unquoted {
+        out.push_str(name);
+        Ok(())
+    } else {
+        write_quoted(out, name)
+    }
+}
+
+/// Writes `value` in double quotes, escaping backslashes and double quotes.
+fn write_quoted(out: &mut String, value: &str) -> fmt::Result {
+    out.push('"');
+    for c in value.chars() {
+        if matches!(c, '"' | '\\') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out.push('"');
+
+    Ok(())
+}
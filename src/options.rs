@@ -0,0 +1,890 @@
+use crate::category::{self, Category};
+use crate::error::{ExtractError, IoContext};
+use crate::filter::PatternSet;
+use crate::format::JsonFormat;
+use crate::hashed::IndexFile;
+use crate::jar::ExtractedContents;
+use crate::lang::LangFormat;
+use crate::locked::LockedRetry;
+use crate::nbt::NbtFormat;
+use crate::text::LineEnding;
+use crate::util;
+use crate::validate::ContentFormat;
+use crate::vfs::{SharedVfs, Vfs};
+use serde::Serialize;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{self, Debug, Formatter};
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// What to do when a file being extracted already exists in the output
+/// directory.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Overwrite {
+    /// Replace the existing file.
+    #[default]
+    Replace,
+    /// Keep the existing file, and [skip](crate::progress::ProgressSink::file_skipped)
+    /// extracting it.
+    Skip,
+    /// Ask the [prompt](ExtractOptions::overwrite_prompt) whether to replace
+    /// each existing file, keeping them all if there is none.
+    Ask,
+}
+
+/// Which of several entries with the same name in a jar file to extract.
+///
+/// Zip files can contain more than one entry with a name, of which most tools
+/// only see the last. Names are compared exactly: entries whose names only
+/// differ in case are separate files.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DuplicateEntries {
+    /// Extract the last entry with the name.
+    #[default]
+    Last,
+    /// Extract the first entry with the name.
+    First,
+    /// Fail with [`ExtractError::DuplicateEntries`] before anything is
+    /// extracted.
+    Error,
+}
+
+/// What to do with hashed files whose contents don't match the
+/// [object](crate::hashed::Object) listed in the index file, e.g. because
+/// they were corrupted while being downloaded.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Verify {
+    /// Extract the file anyway, with a
+    /// [warning](crate::progress::ProgressSink::file_warning).
+    Warn,
+    /// Fail the file with [`ExtractError::CorruptObject`], without writing it.
+    Strict,
+}
+
+/// How files are sorted into directories by type within the output directory,
+/// each keeping its usual path within its directory.
+///
+/// For example, `assets/minecraft/textures/block/stone.png` is extracted to
+/// `png/assets/minecraft/textures/block/stone.png` when grouping by
+/// [extension](GroupBy::Extension), and to
+/// `textures/assets/minecraft/textures/block/stone.png` when grouping by
+/// [category](GroupBy::Category).
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GroupBy {
+    /// Group files by their extensions, in lowercase, and files without one
+    /// in `other`.
+    ///
+    /// Converted files are grouped by the extension they had in the jar file
+    /// or index file, e.g. `.lang` files converted to `.json` files in `lang`.
+    Extension,
+    /// Group files by their [categories](Category), e.g. `textures` or
+    /// `loot_tables`.
+    Category,
+}
+
+impl GroupBy {
+    /// Returns the directory the file at `path` is grouped into, where
+    /// `path` is relative to `assets` or `data`.
+    fn dir_of(self, path: &Path) -> Cow<'static, str> {
+        match self {
+            Self::Extension => path
+                .extension()
+                .map(|extension| Cow::Owned(extension.to_string_lossy().to_lowercase()))
+                .unwrap_or(Cow::Borrowed("other")),
+            Self::Category => Cow::Borrowed(Category::of(path).name()),
+        }
+    }
+}
+
+/// Cancels an extraction from another thread, e.g. when the user presses
+/// Ctrl-C.
+///
+/// Clones share the same state: a clone is kept to [cancel](CancellationToken::cancel)
+/// an extraction while another is [given to its options](ExtractOptions::cancellation).
+/// The token is checked between files, so the file being extracted is always
+/// finished first, and the extraction then returns its partial [summary](crate::progress::Summary)
+/// with [`cancelled`](crate::progress::Summary::cancelled) set.
+///
+/// # Examples
+/// ```
+/// use extract_minecraft_assets::options::{CancellationToken, ExtractOptions};
+///
+/// let token = CancellationToken::new();
+/// let options = ExtractOptions::new().cancellation(token.clone());
+///
+/// token.cancel();
+/// assert!(options.is_cancelled());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// Returns a new token which has not been cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancels every extraction using this token.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    /// Whether this token has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// Tokens are equal if they are clones of each other.
+impl PartialEq for CancellationToken {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for CancellationToken {}
+
+impl Hash for CancellationToken {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Arc::as_ptr(&self.0).hash(state);
+    }
+}
+
+/// Decides whether to replace each file which already exists, with
+/// [`Overwrite::Ask`], e.g. by asking the user.
+///
+/// It is given the path of the existing file, and returns whether to replace
+/// it. Files may be extracted on several threads, so it may be called from
+/// any of them.
+///
+/// # Examples
+/// ```
+/// use extract_minecraft_assets::options::{ExtractOptions, Overwrite, OverwritePrompt};
+///
+/// // Keep the language files, which may have been edited.
+/// let prompt = OverwritePrompt::new(|path| !path.starts_with("out/assets/minecraft/lang"));
+/// let options = ExtractOptions::new()
+///     .output("out")
+///     .overwrite(Overwrite::Ask)
+///     .overwrite_prompt(prompt);
+/// ```
+#[derive(Clone)]
+pub struct OverwritePrompt(Arc<dyn Fn(&Path) -> bool + Send + Sync>);
+
+impl OverwritePrompt {
+    /// Returns a prompt replacing the existing files for which `replace`
+    /// returns true.
+    pub fn new(replace: impl Fn(&Path) -> bool + Send + Sync + 'static) -> Self {
+        Self(Arc::new(replace))
+    }
+
+    /// Whether to replace the existing file at `path`.
+    pub fn replaces(&self, path: &Path) -> bool {
+        (self.0)(path)
+    }
+}
+
+impl Debug for OverwritePrompt {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("OverwritePrompt")
+    }
+}
+
+/// Prompts are equal if they are clones of each other.
+impl PartialEq for OverwritePrompt {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for OverwritePrompt {}
+
+impl Hash for OverwritePrompt {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Arc::as_ptr(&self.0).cast::<()>().hash(state);
+    }
+}
+
+/// Options for an extraction, used by both [`jar::extract_jar`](crate::jar::extract_jar)
+/// and [`hashed::extract_hashed_assets`](crate::hashed::extract_hashed_assets).
+///
+/// Options are set with builder methods, starting from [`ExtractOptions::new`]:
+/// anything which isn't set keeps its default.
+///
+/// # Examples
+/// ```
+/// use extract_minecraft_assets::jar::ExtractedContents;
+/// use extract_minecraft_assets::options::{ExtractOptions, Overwrite};
+///
+/// let options = ExtractOptions::new()
+///     .output("extracted")
+///     .contents(ExtractedContents::all())
+///     .ignore_top_level(false)
+///     .overwrite(Overwrite::Skip);
+///
+/// assert_eq!(options.output_dir.to_str(), Some("extracted"));
+/// ```
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Serialize)]
+#[non_exhaustive]
+#[serde(rename_all = "camelCase")]
+pub struct ExtractOptions {
+    /// The directory into which to extract.
+    ///
+    /// Defaults to the current directory.
+    pub output_dir: PathBuf,
+    /// Which top-level directories of a jar file to extract.
+    ///
+    /// Defaults to [`ExtractedContents::default`], i.e. only `assets`.
+    pub contents: ExtractedContents,
+    /// The files to extract, by patterns matched against their paths within
+    /// the jar file, e.g. `assets/minecraft/textures/**`, with hashed assets
+    /// matched as if within `assets`.
+    ///
+    /// Files are left out before they are read, and aren't counted in the
+    /// totals given to the [progress](crate::progress::ProgressSink::begin).
+    /// Defaults to none, extracting every file.
+    #[serde(skip_serializing_if = "PatternSet::is_empty")]
+    pub include: PatternSet,
+    /// The files to leave out, by patterns matched like those to
+    /// [`include`](ExtractOptions::include), even if they are included.
+    ///
+    /// Defaults to none.
+    #[serde(skip_serializing_if = "PatternSet::is_empty")]
+    pub exclude: PatternSet,
+    /// Whether to extract the contents of `assets`/`data` directly into the
+    /// output directory, rather than creating `assets`/`data` directories.
+    ///
+    /// Defaults to false.
+    pub ignore_top_level: bool,
+    /// Whether to leave out the namespace directory of each file, e.g.
+    /// extracting `assets/minecraft/lang/en_us.json` to `assets/lang/en_us.json`.
+    ///
+    /// Files directly within `assets` or `data` are extracted as they are.
+    /// Extraction fails before anything is written if the files extracted are
+    /// in more than one namespace, or if two of them would still be extracted
+    /// to the same path, e.g. `assets/minecraft/shared.json` and
+    /// `data/minecraft/shared.json` with [`ignore_top_level`](ExtractOptions::ignore_top_level).
+    /// Defaults to false.
+    #[serde(skip_serializing_if = "is_false")]
+    pub strip_namespace: bool,
+    /// How to sort files into directories by type, if at all.
+    ///
+    /// Directories in a jar file aren't extracted when files are grouped,
+    /// other than those containing files. Defaults to [`None`], extracting
+    /// files to their usual paths.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group_by: Option<GroupBy>,
+    /// What to do with files which already exist.
+    ///
+    /// This also applies to a file where a directory is to be created, which
+    /// is removed (or skipped) the same way. Defaults to [`Overwrite::Replace`].
+    pub overwrite: Overwrite,
+    /// What decides whether to replace each existing file, with
+    /// [`Overwrite::Ask`].
+    ///
+    /// Defaults to [`None`].
+    #[serde(skip)]
+    pub overwrite_prompt: Option<OverwritePrompt>,
+    /// Whether a directory where a file is to be written is removed, along
+    /// with everything in it, when [replacing](Overwrite::Replace) files.
+    ///
+    /// Otherwise, the file fails with [`ExtractError::DirectoryInTheWay`].
+    /// Defaults to false.
+    #[serde(skip_serializing_if = "is_false")]
+    pub replace_dirs: bool,
+    /// The number of threads on which
+    /// [`extract_hashed_assets`](crate::hashed::extract_hashed_assets) reads
+    /// and writes hashed files at once.
+    ///
+    /// Progress is still reported from the calling thread, but files finish,
+    /// and are listed in the report, in no particular order. Defaults to 1,
+    /// extracting files one after the other.
+    #[serde(skip)]
+    pub threads: usize,
+    /// The maximum number of files extracted at once by the async functions,
+    /// available with the `async` feature.
+    ///
+    /// Defaults to 16.
+    pub concurrency: usize,
+    /// The format to which to convert language files in the other format,
+    /// e.g. `assets/minecraft/lang/en_us.lang` to `en_us.json` for
+    /// [`LangFormat::Json`].
+    ///
+    /// Files which can't be converted are extracted as they are, with a
+    /// [warning](crate::progress::ProgressSink::file_warning). Defaults to
+    /// [`None`], extracting language files as they are.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub convert_lang: Option<LangFormat>,
+    /// The format to which to convert the NBT files of `data`, e.g.
+    /// `data/minecraft/structure/igloo/top.nbt` to `top.snbt` for
+    /// [`NbtFormat::Snbt`].
+    ///
+    /// Files which can't be read are extracted as they are, with a
+    /// [warning](crate::progress::ProgressSink::file_warning). Defaults to
+    /// [`None`], extracting NBT files as they are.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub convert_nbt: Option<NbtFormat>,
+    /// Whether to also extract the original of each converted language or
+    /// NBT file.
+    ///
+    /// Defaults to false.
+    #[serde(skip_serializing_if = "is_false")]
+    pub keep_original: bool,
+    /// The format in which to write `.json` and `.mcmeta` files.
+    ///
+    /// Files which aren't valid JSON are extracted as they are, with a
+    /// [warning](crate::progress::ProgressSink::file_warning). Language files
+    /// being [converted](ExtractOptions::convert_lang) aren't formatted.
+    /// Defaults to [`None`], extracting JSON files as they are.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format_json: Option<JsonFormat>,
+    /// The size in bytes above which JSON files are extracted as they are
+    /// rather than [formatted](ExtractOptions::format_json), as each file is
+    /// read into memory to format it.
+    ///
+    /// Defaults to 16 MiB.
+    pub format_json_max_size: u64,
+    /// The line endings to which to normalize text files, e.g. `.txt`,
+    /// `.lang` and `.json` files, as listed in
+    /// [`TEXT_EXTENSIONS`](crate::text::TEXT_EXTENSIONS).
+    ///
+    /// A UTF-8 byte order mark is stripped too. Files which don't look like
+    /// text are extracted as they are, with a
+    /// [warning](crate::progress::ProgressSink::file_warning). Defaults to
+    /// [`None`], extracting text files as they are.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text_normalize: Option<LineEnding>,
+    /// The formats of the files to [check](crate::validate) as they are
+    /// written, e.g. that `.png` files start with a PNG signature.
+    ///
+    /// Files which are invalid are still written, with a
+    /// [warning](crate::progress::ProgressSink::file_warning), and counted in
+    /// [`Summary::invalid`](crate::progress::Summary::invalid). Defaults to
+    /// none.
+    #[serde(skip_serializing_if = "BTreeSet::is_empty")]
+    pub validate_content: BTreeSet<ContentFormat>,
+    /// Whether percent-encoded sequences such as `%20` in the paths of index
+    /// files are [decoded](crate::util::decode_path) to get the paths hashed
+    /// assets are extracted to, as the game expects.
+    ///
+    /// Defaults to true.
+    pub decode_paths: bool,
+    /// Which entry to extract when a jar file has several entries with the
+    /// same name.
+    ///
+    /// Each such name is reported with a
+    /// [warning](crate::progress::ProgressSink::file_warning) giving how many
+    /// entries have it. Defaults to [`DuplicateEntries::Last`].
+    pub duplicate_entries: DuplicateEntries,
+    /// Whether files and directories whose unix permissions can't be set
+    /// fail to be extracted.
+    ///
+    /// Otherwise, files are extracted without them, with a
+    /// [warning](crate::progress::ProgressSink::file_warning) the first time,
+    /// and counted in [`Summary::permissions_not_set`](crate::progress::Summary::permissions_not_set).
+    /// As filesystems which don't support permissions (e.g. exFAT) fail for
+    /// every file, none are set after the first failure. Defaults to false.
+    #[serde(skip_serializing_if = "is_false")]
+    pub strict_permissions: bool,
+    /// Whether to check that each hashed file has the size and SHA-1 hash
+    /// listed in the index file as it is read, and what to do with those
+    /// which don't.
+    ///
+    /// The size is checked first, so that only files of the right size are
+    /// hashed. Mismatches are counted in
+    /// [`Summary::unverified`](crate::progress::Summary::unverified). Files of
+    /// the jar file are never verified. Defaults to [`None`], extracting
+    /// hashed files without checking them.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verify: Option<Verify>,
+    /// How to retry opening the jar file and reading hashed files while
+    /// another process holds them locked, as the game or the launcher can on
+    /// Windows.
+    ///
+    /// Defaults to [`LockedRetry::default`].
+    #[serde(skip)]
+    pub locked_retry: LockedRetry,
+    /// The token with which the extraction can be cancelled, if any.
+    ///
+    /// Defaults to [`None`].
+    #[serde(skip)]
+    pub cancellation: Option<CancellationToken>,
+    /// The filesystem to which extracted files are written.
+    ///
+    /// Defaults to the real filesystem.
+    #[serde(skip)]
+    pub vfs: SharedVfs,
+}
+
+impl ExtractOptions {
+    /// Returns the default options.
+    pub fn new() -> Self {
+        Self {
+            output_dir: PathBuf::from("."),
+            contents: ExtractedContents::default(),
+            include: PatternSet::new(),
+            exclude: PatternSet::new(),
+            ignore_top_level: false,
+            strip_namespace: false,
+            group_by: None,
+            overwrite: Overwrite::default(),
+            overwrite_prompt: None,
+            replace_dirs: false,
+            threads: 1,
+            concurrency: 16,
+            convert_lang: None,
+            convert_nbt: None,
+            keep_original: false,
+            format_json: None,
+            format_json_max_size: 16 * 1024 * 1024,
+            text_normalize: None,
+            validate_content: BTreeSet::new(),
+            decode_paths: true,
+            duplicate_entries: DuplicateEntries::default(),
+            strict_permissions: false,
+            verify: None,
+            locked_retry: LockedRetry::default(),
+            cancellation: None,
+            vfs: SharedVfs::default(),
+        }
+    }
+
+    /// Sets the [output directory](ExtractOptions::output_dir).
+    pub fn output(mut self, output_dir: impl Into<PathBuf>) -> Self {
+        self.output_dir = output_dir.into();
+        self
+    }
+
+    /// Sets which [contents](ExtractOptions::contents) to extract.
+    pub fn contents(mut self, contents: ExtractedContents) -> Self {
+        self.contents = contents;
+        self
+    }
+
+    /// Sets the patterns of the files to [include](ExtractOptions::include).
+    pub fn include(mut self, include: PatternSet) -> Self {
+        self.include = include;
+        self
+    }
+
+    /// Sets the patterns of the files to [exclude](ExtractOptions::exclude).
+    pub fn exclude(mut self, exclude: PatternSet) -> Self {
+        self.exclude = exclude;
+        self
+    }
+
+    /// Sets whether to [ignore the top-level directory](ExtractOptions::ignore_top_level).
+    pub fn ignore_top_level(mut self, ignore_top_level: bool) -> Self {
+        self.ignore_top_level = ignore_top_level;
+        self
+    }
+
+    /// Sets whether to [strip the namespace](ExtractOptions::strip_namespace)
+    /// of each file.
+    pub fn strip_namespace(mut self, strip_namespace: bool) -> Self {
+        self.strip_namespace = strip_namespace;
+        self
+    }
+
+    /// Sets how to [group files](ExtractOptions::group_by) by type.
+    pub fn group_by(mut self, group_by: Option<GroupBy>) -> Self {
+        self.group_by = group_by;
+        self
+    }
+
+    /// Sets what to do with files which [already exist](ExtractOptions::overwrite).
+    pub fn overwrite(mut self, overwrite: Overwrite) -> Self {
+        self.overwrite = overwrite;
+        self
+    }
+
+    /// Sets what decides whether to replace existing files, with
+    /// [`Overwrite::Ask`](ExtractOptions::overwrite_prompt).
+    pub fn overwrite_prompt(mut self, prompt: OverwritePrompt) -> Self {
+        self.overwrite_prompt = Some(prompt);
+        self
+    }
+
+    /// Sets whether directories in the way of files are [replaced](ExtractOptions::replace_dirs).
+    pub fn replace_dirs(mut self, replace_dirs: bool) -> Self {
+        self.replace_dirs = replace_dirs;
+        self
+    }
+
+    /// Sets the number of [threads](ExtractOptions::threads) extracting
+    /// hashed files.
+    ///
+    /// A number of 0 is treated as 1.
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Sets how many files may be [extracted at once](ExtractOptions::concurrency).
+    ///
+    /// A `concurrency` of 0 is treated as 1.
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Sets the format to which to [convert language files](ExtractOptions::convert_lang).
+    pub fn convert_lang(mut self, format: Option<LangFormat>) -> Self {
+        self.convert_lang = format;
+        self
+    }
+
+    /// Sets the format to which to [convert NBT files](ExtractOptions::convert_nbt).
+    pub fn convert_nbt(mut self, format: Option<NbtFormat>) -> Self {
+        self.convert_nbt = format;
+        self
+    }
+
+    /// Sets whether to [keep the original](ExtractOptions::keep_original) of
+    /// each converted language or NBT file.
+    pub fn keep_original(mut self, keep_original: bool) -> Self {
+        self.keep_original = keep_original;
+        self
+    }
+
+    /// Sets the [format of JSON files](ExtractOptions::format_json).
+    pub fn format_json(mut self, format: Option<JsonFormat>) -> Self {
+        self.format_json = format;
+        self
+    }
+
+    /// Sets the [size above which JSON files aren't formatted](ExtractOptions::format_json_max_size).
+    pub fn format_json_max_size(mut self, bytes: u64) -> Self {
+        self.format_json_max_size = bytes;
+        self
+    }
+
+    /// Sets the [line endings to which to normalize text files](ExtractOptions::text_normalize).
+    pub fn text_normalize(mut self, line_ending: Option<LineEnding>) -> Self {
+        self.text_normalize = line_ending;
+        self
+    }
+
+    /// Sets the formats of the files to [check](ExtractOptions::validate_content)
+    /// as they are written.
+    pub fn validate_content(mut self, formats: impl IntoIterator<Item = ContentFormat>) -> Self {
+        self.validate_content = formats.into_iter().collect();
+        self
+    }
+
+    /// Sets whether the paths of index files are [decoded](ExtractOptions::decode_paths).
+    pub fn decode_paths(mut self, decode_paths: bool) -> Self {
+        self.decode_paths = decode_paths;
+        self
+    }
+
+    /// Sets which of several [entries with the same name](ExtractOptions::duplicate_entries)
+    /// in a jar file to extract.
+    pub fn duplicate_entries(mut self, duplicate_entries: DuplicateEntries) -> Self {
+        self.duplicate_entries = duplicate_entries;
+        self
+    }
+
+    /// Sets whether files fail if [permissions can't be set](ExtractOptions::strict_permissions).
+    pub fn strict_permissions(mut self, strict_permissions: bool) -> Self {
+        self.strict_permissions = strict_permissions;
+        self
+    }
+
+    /// Sets whether to [verify hashed files](ExtractOptions::verify) against
+    /// the index file.
+    pub fn verify(mut self, verify: Option<Verify>) -> Self {
+        self.verify = verify;
+        self
+    }
+
+    /// Sets how to [retry reading locked files](ExtractOptions::locked_retry).
+    pub fn locked_retry(mut self, locked_retry: LockedRetry) -> Self {
+        self.locked_retry = locked_retry;
+        self
+    }
+
+    /// Sets the token with which the extraction can be [cancelled](ExtractOptions::cancellation).
+    pub fn cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    /// Sets the [filesystem](ExtractOptions::vfs) to which extracted files are
+    /// written.
+    ///
+    /// # Examples
+    /// ```
+    /// use extract_minecraft_assets::options::ExtractOptions;
+    /// use extract_minecraft_assets::vfs::MemoryFs;
+    /// use std::sync::Arc;
+    ///
+    /// let fs = Arc::new(MemoryFs::new());
+    /// let options = ExtractOptions::new().vfs(fs.clone());
+    /// ```
+    pub fn vfs(mut self, vfs: Arc<dyn Vfs>) -> Self {
+        self.vfs = SharedVfs(vfs);
+        self
+    }
+
+    /// Whether the extraction has been cancelled by its [token](ExtractOptions::cancellation).
+    pub fn is_cancelled(&self) -> bool {
+        self.cancellation
+            .as_ref()
+            .is_some_and(CancellationToken::is_cancelled)
+    }
+
+    /// Whether the file at `path` within the jar file, e.g.
+    /// `assets/minecraft/lang/en_us.json`, is [included](ExtractOptions::include)
+    /// and not [excluded](ExtractOptions::exclude).
+    ///
+    /// # Examples
+    /// ```
+    /// use extract_minecraft_assets::filter::{Pattern, PatternSet};
+    /// use extract_minecraft_assets::options::ExtractOptions;
+    /// use std::path::Path;
+    ///
+    /// let options = ExtractOptions::new()
+    ///     .include(PatternSet::new().with(Pattern::glob("assets/*/sounds/**")?))
+    ///     .exclude(PatternSet::new().with(Pattern::glob("*.ogg")?));
+    ///
+    /// assert!(options.includes(Path::new("assets/minecraft/sounds/sounds.json")));
+    /// assert!(!options.includes(Path::new("assets/minecraft/sounds/cave1.ogg")));
+    /// assert!(!options.includes(Path::new("assets/minecraft/lang/de_de.json")));
+    /// # Ok::<(), extract_minecraft_assets::filter::PatternError>(())
+    /// ```
+    pub fn includes(&self, path: &Path) -> bool {
+        (self.include.is_empty() || self.include.matches(path)) && !self.exclude.matches(path)
+    }
+
+    /// Returns the objects of `index` which are [included](ExtractOptions::includes),
+    /// matching them by the path they are extracted to within `assets`.
+    pub fn included_objects<'i>(&self, index: &'i IndexFile) -> Cow<'i, IndexFile> {
+        if self.include.is_empty() && self.exclude.is_empty() {
+            return Cow::Borrowed(index);
+        }
+
+        Cow::Owned(
+            index.filtered(|path| self.includes(&Path::new("assets").join(self.index_path(path)))),
+        )
+    }
+
+    /// Returns the path to which the asset at `virtual_path` in an index file
+    /// is extracted within `assets`, [decoded](ExtractOptions::decode_paths)
+    /// if asked to be.
+    pub(crate) fn index_path<'a>(&self, virtual_path: &'a Path) -> Cow<'a, Path> {
+        if self.decode_paths {
+            util::decode_path(virtual_path)
+        } else {
+            Cow::Borrowed(virtual_path)
+        }
+    }
+
+    /// Returns `path`, relative to the output directory, without its namespace
+    /// directory if [`strip_namespace`](ExtractOptions::strip_namespace) is
+    /// set.
+    ///
+    /// The namespace directory itself, if `path` is a directory, becomes the
+    /// directory containing it.
+    ///
+    /// # Examples
+    /// ```
+    /// use extract_minecraft_assets::options::ExtractOptions;
+    /// use std::path::Path;
+    ///
+    /// let options = ExtractOptions::new().strip_namespace(true);
+    ///
+    /// assert_eq!(
+    ///     options.without_namespace("assets/minecraft/lang/en_us.json".into(), false),
+    ///     Path::new("assets/lang/en_us.json")
+    /// );
+    /// assert_eq!(
+    ///     options.without_namespace("assets/pack.mcmeta".into(), false),
+    ///     Path::new("assets/pack.mcmeta")
+    /// );
+    /// ```
+    pub fn without_namespace(&self, path: PathBuf, is_dir: bool) -> PathBuf {
+        // The namespace follows the top-level directory, unless it's ignored.
+        let index = usize::from(!self.ignore_top_level);
+        let components = path.components().count();
+
+        // Files directly within the top-level directory have no namespace.
+        if !self.strip_namespace || components <= index + usize::from(!is_dir) {
+            return path;
+        }
+
+        path.components()
+            .enumerate()
+            .filter(|(i, _)| *i != index)
+            .map(|(_, component)| component)
+            .collect()
+    }
+
+    /// Returns the path to which the file (or directory, if `is_dir`) at
+    /// `path` is extracted, relative to the output directory: `path`
+    /// [without its namespace](ExtractOptions::without_namespace), within the
+    /// directory it is [grouped](ExtractOptions::group_by) into, if any.
+    ///
+    /// # Examples
+    /// ```
+    /// use extract_minecraft_assets::options::{ExtractOptions, GroupBy};
+    /// use std::path::Path;
+    ///
+    /// let options = ExtractOptions::new().group_by(Some(GroupBy::Extension));
+    ///
+    /// assert_eq!(
+    ///     options.relative_output_path("assets/minecraft/lang/en_us.json".into(), false),
+    ///     Path::new("json/assets/minecraft/lang/en_us.json")
+    /// );
+    /// ```
+    pub fn relative_output_path(&self, path: PathBuf, is_dir: bool) -> PathBuf {
+        let Some(group_by) = self.group_by else {
+            return self.without_namespace(path, is_dir);
+        };
+
+        // Files are grouped by their paths within `assets` or `data`, which
+        // still have their namespaces.
+        let within_root: PathBuf = path
+            .components()
+            .skip(usize::from(!self.ignore_top_level))
+            .collect();
+        let dir = group_by.dir_of(&within_root);
+
+        Path::new(dir.as_ref()).join(self.without_namespace(path, is_dir))
+    }
+
+    /// Clears the way for a file (or directory, if `is_dir`) to be written
+    /// at `output_path`, according to [`overwrite`](ExtractOptions::overwrite),
+    /// returning whether it should be written rather than skipped.
+    ///
+    /// Whatever is in the way is removed: an existing file or directory at
+    /// `output_path` of the other kind, or a file where one of its parent
+    /// directories within the output directory should be. Directories are
+    /// only removed if [`replace_dirs`](ExtractOptions::replace_dirs) is set.
+    pub(crate) fn make_way(&self, output_path: &Path, is_dir: bool) -> Result<bool, ExtractError> {
+        let vfs = &*self.vfs;
+
+        match vfs.metadata(output_path) {
+            Ok(metadata) if metadata.is_dir == is_dir => {
+                return Ok(is_dir || !self.keeps_existing(output_path));
+            }
+            Ok(_) if self.keeps_existing(output_path) => return Ok(false),
+            Ok(metadata) if metadata.is_dir => {
+                if !self.replace_dirs {
+                    return Err(ExtractError::DirectoryInTheWay {
+                        path: output_path.to_owned(),
+                    });
+                }
+                vfs.remove_dir_all(output_path).writing(output_path)?;
+
+                return Ok(true);
+            }
+            Ok(_) => {
+                vfs.remove_file(output_path).writing(output_path)?;
+
+                return Ok(true);
+            }
+            Err(_) => {}
+        }
+
+        let Some(parent) = output_path.parent() else {
+            return Ok(true);
+        };
+        // Most of the time the parent directory already exists.
+        if vfs.metadata(parent).is_ok_and(|metadata| metadata.is_dir) {
+            return Ok(true);
+        }
+
+        // Nothing outside the output directory is touched.
+        let file = parent
+            .ancestors()
+            .take_while(|dir| dir.starts_with(&self.output_dir) && *dir != self.output_dir)
+            .find(|dir| vfs.metadata(dir).is_ok_and(|metadata| !metadata.is_dir));
+        match file {
+            Some(file) if self.keeps_existing(file) => Ok(false),
+            Some(file) => {
+                vfs.remove_file(file).writing(file)?;
+
+                Ok(true)
+            }
+            None => Ok(true),
+        }
+    }
+
+    /// Whether the existing file or directory at `output_path` is kept
+    /// rather than replaced, according to [`overwrite`](ExtractOptions::overwrite),
+    /// asking the [prompt](ExtractOptions::overwrite_prompt) if need be.
+    pub(crate) fn keeps_existing(&self, output_path: &Path) -> bool {
+        match self.overwrite {
+            Overwrite::Replace => false,
+            Overwrite::Skip => true,
+            Overwrite::Ask => self
+                .overwrite_prompt
+                .as_ref()
+                .is_none_or(|prompt| !prompt.replaces(output_path)),
+        }
+    }
+
+    /// Checks that the files at `paths` can be extracted without their
+    /// namespace, if [`strip_namespace`](ExtractOptions::strip_namespace) is
+    /// set: that they are all in one namespace, and that no two of them would
+    /// be extracted to the same path.
+    ///
+    /// `paths` are the paths of the files relative to the jar's top-level
+    /// directory, e.g. `assets/minecraft/lang/en_us.json`, along with the
+    /// paths relative to the output directory to which they would be
+    /// extracted.
+    pub(crate) fn check_strip_namespace(
+        &self,
+        paths: impl IntoIterator<Item = (PathBuf, PathBuf)>,
+    ) -> Result<(), ExtractError> {
+        if !self.strip_namespace {
+            return Ok(());
+        }
+
+        let mut namespaces = BTreeSet::new();
+        let mut sources: BTreeMap<PathBuf, Vec<PathBuf>> = BTreeMap::new();
+        for (path, output_path) in paths {
+            let within_root: PathBuf = path.components().skip(1).collect();
+            if let Some(namespace) = category::namespace(&within_root) {
+                namespaces.insert(namespace.to_owned());
+            }
+
+            sources.entry(output_path).or_default().push(path);
+        }
+
+        if namespaces.len() > 1 {
+            return Err(ExtractError::MultipleNamespaces {
+                namespaces: namespaces.into_iter().collect(),
+            });
+        }
+
+        let collisions: Vec<_> = sources
+            .into_iter()
+            .filter(|(_, sources)| sources.len() > 1)
+            .collect();
+        if !collisions.is_empty() {
+            return Err(ExtractError::OutputCollisions { collisions });
+        }
+
+        Ok(())
+    }
+}
+
+/// Whether `value` is false, so that unset flags are not serialized.
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+impl Default for ExtractOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
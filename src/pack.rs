@@ -0,0 +1,367 @@
+//! Shaping an output directory as a resource pack: writing its
+//! [`pack.mcmeta`](PACK_MCMETA), and placing extracted assets in overlays,
+//! which the game applies to a range of pack formats since 1.20.2.
+
+use crate::error::ExtractError;
+use crate::report::{ExtractionReport, FileOutcome};
+use serde::Serialize;
+use serde_json::{Map, Value, json};
+use std::fmt::{self, Display, Formatter};
+use std::path::{Component, Path, PathBuf};
+use std::str::FromStr;
+use thiserror::Error;
+
+/// The file at the root of a resource pack describing it.
+pub const PACK_MCMETA: &str = "pack.mcmeta";
+
+/// The description written to a new [`PACK_MCMETA`].
+const DEFAULT_DESCRIPTION: &str = "Extracted Minecraft assets";
+
+/// An inclusive range of pack formats, e.g. `18-22`, or a single format.
+///
+/// # Examples
+/// ```
+/// use extract_minecraft_assets::pack::FormatRange;
+///
+/// let range: FormatRange = "18-22".parse()?;
+///
+/// assert_eq!(range, FormatRange { min_inclusive: 18, max_inclusive: 22 });
+/// assert!(range.overlaps("22".parse()?));
+/// assert!(!range.overlaps("23-30".parse()?));
+/// assert!("22-18".parse::<FormatRange>().is_err());
+/// # Ok::<(), extract_minecraft_assets::pack::PackError>(())
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize)]
+pub struct FormatRange {
+    pub min_inclusive: u32,
+    pub max_inclusive: u32,
+}
+
+/// A directory of assets which the game applies on top of the base `assets`
+/// for the pack formats in a [`FormatRange`], written as `NAME:FORMATS`, e.g.
+/// `new_ui:18-22`.
+///
+/// The game only looks for overlays at the root of the pack, so the assets
+/// of the overlay `new_ui` are placed in `new_ui/assets/`.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Overlay {
+    /// The name of the overlay's directory.
+    pub directory: String,
+    pub formats: FormatRange,
+}
+
+/// An error in the resource pack to write.
+#[derive(Error, Debug)]
+pub enum PackError {
+    /// An overlay isn't written as `NAME:FORMATS`.
+    #[error("overlay '{0}' should be written as NAME:FORMATS, e.g. 'new_ui:18-22'")]
+    MalformedOverlay(String),
+    /// An overlay's directory name has characters the game doesn't allow.
+    #[error(
+        "invalid overlay directory '{0}': only lowercase letters, digits, '_', '-' and '.' are allowed"
+    )]
+    InvalidDirectory(String),
+    /// Pack formats aren't a number or a range of numbers.
+    #[error("invalid pack formats '{0}': expected a format like '18' or a range like '18-22'")]
+    InvalidFormats(String),
+    /// A range of pack formats has its minimum above its maximum.
+    #[error("invalid pack formats '{min}-{max}': the minimum is above the maximum")]
+    ReversedFormats { min: u32, max: u32 },
+    /// The same overlay is given more than once.
+    #[error("overlay '{0}' is given more than once")]
+    DuplicateOverlay(String),
+    /// Two overlays apply to some of the same pack formats.
+    #[error("overlays '{first}' ({first_formats}) and '{second}' ({second_formats}) overlap")]
+    OverlappingOverlays {
+        first: String,
+        first_formats: FormatRange,
+        second: String,
+        second_formats: FormatRange,
+    },
+    /// No pack format is given, and the existing `pack.mcmeta` has none.
+    #[error("no pack format given, and no existing {PACK_MCMETA} has one")]
+    MissingPackFormat,
+    /// The existing `pack.mcmeta` isn't a JSON object.
+    #[error("invalid existing {PACK_MCMETA}: {0}")]
+    InvalidPackMcmeta(String),
+}
+
+impl FormatRange {
+    /// Whether any pack format is in both ranges.
+    pub fn overlaps(self, other: Self) -> bool {
+        self.min_inclusive <= other.max_inclusive && other.min_inclusive <= self.max_inclusive
+    }
+
+    /// Returns the range written in a `pack.mcmeta` as `value`: a single
+    /// format, a `[min, max]` array, or a `min_inclusive`/`max_inclusive`
+    /// object.
+    fn from_json(value: &Value) -> Option<Self> {
+        let format = |value: &Value| value.as_u64().and_then(|format| u32::try_from(format).ok());
+        let (min, max) = match value {
+            Value::Number(_) => (format(value)?, format(value)?),
+            Value::Array(range) => match range.as_slice() {
+                [min, max] => (format(min)?, format(max)?),
+                _ => return None,
+            },
+            Value::Object(range) => (
+                format(range.get("min_inclusive")?)?,
+                format(range.get("max_inclusive")?)?,
+            ),
+            _ => return None,
+        };
+
+        Some(Self {
+            min_inclusive: min,
+            max_inclusive: max,
+        })
+    }
+}
+
+impl FromStr for FormatRange {
+    type Err = PackError;
+
+    fn from_str(formats: &str) -> Result<Self, Self::Err> {
+        let invalid = || PackError::InvalidFormats(formats.to_owned());
+        let (min, max) = formats.split_once('-').unwrap_or((formats, formats));
+        let min: u32 = min.trim().parse().map_err(|_| invalid())?;
+        let max: u32 = max.trim().parse().map_err(|_| invalid())?;
+
+        if min > max {
+            return Err(PackError::ReversedFormats { min, max });
+        }
+
+        Ok(Self {
+            min_inclusive: min,
+            max_inclusive: max,
+        })
+    }
+}
+
+/// Displays the range as it is parsed, e.g. `18-22` or `18`.
+impl Display for FormatRange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.min_inclusive == self.max_inclusive {
+            write!(f, "{}", self.min_inclusive)
+        } else {
+            write!(f, "{}-{}", self.min_inclusive, self.max_inclusive)
+        }
+    }
+}
+
+impl FromStr for Overlay {
+    type Err = PackError;
+
+    fn from_str(overlay: &str) -> Result<Self, Self::Err> {
+        let Some((directory, formats)) = overlay.split_once(':') else {
+            return Err(PackError::MalformedOverlay(overlay.to_owned()));
+        };
+        if directory.is_empty() || formats.is_empty() {
+            return Err(PackError::MalformedOverlay(overlay.to_owned()));
+        }
+
+        let is_allowed = |char: char| {
+            char.is_ascii_lowercase() || char.is_ascii_digit() || matches!(char, '_' | '-' | '.')
+        };
+        if !directory.chars().all(is_allowed) || directory.chars().all(|char| char == '.') {
+            return Err(PackError::InvalidDirectory(directory.to_owned()));
+        }
+
+        Ok(Self {
+            directory: directory.to_owned(),
+            formats: formats.parse()?,
+        })
+    }
+}
+
+/// Returns the contents of the `pack.mcmeta` to write, updating the
+/// `existing` one if there is one.
+///
+/// The pack format is set to `pack_format` if given, and is otherwise kept
+/// from the existing file. Each of the `overlays` is listed in
+/// `overlays.entries`, replacing any entry with the same directory and
+/// keeping the others.
+///
+/// Fails if no pack format is known, if an overlay is given twice, or if any
+/// of the overlays listed would apply to the same pack format.
+///
+/// # Examples
+/// ```
+/// use extract_minecraft_assets::pack::{self, Overlay};
+/// use serde_json::{Value, json};
+///
+/// let overlays: Vec<Overlay> = vec!["new_ui:18-22".parse()?];
+/// let pack_mcmeta = pack::pack_mcmeta(None, Some(15), &overlays)?;
+/// let pack_mcmeta: Value = serde_json::from_slice(&pack_mcmeta).unwrap();
+///
+/// assert_eq!(pack_mcmeta["pack"]["pack_format"], 15);
+/// assert_eq!(
+///     pack_mcmeta["overlays"]["entries"],
+///     json!([{"directory": "new_ui", "formats": {"min_inclusive": 18, "max_inclusive": 22}}])
+/// );
+/// # Ok::<(), extract_minecraft_assets::pack::PackError>(())
+/// ```
+pub fn pack_mcmeta(
+    existing: Option<&[u8]>,
+    pack_format: Option<u32>,
+    overlays: &[Overlay],
+) -> Result<Vec<u8>, PackError> {
+    let invalid = |message: &str| PackError::InvalidPackMcmeta(message.to_owned());
+    let mut root = match existing {
+        Some(existing) => match serde_json::from_slice(existing) {
+            Ok(Value::Object(root)) => root,
+            Ok(_) => return Err(invalid("expected an object")),
+            Err(error) => return Err(invalid(&error.to_string())),
+        },
+        None => Map::new(),
+    };
+
+    let pack = root
+        .entry("pack")
+        .or_insert_with(|| json!({}))
+        .as_object_mut()
+        .ok_or_else(|| invalid("'pack' isn't an object"))?;
+    match pack_format {
+        Some(pack_format) => {
+            pack.insert("pack_format".to_owned(), pack_format.into());
+        }
+        None if pack.get("pack_format").is_some_and(Value::is_u64) => {}
+        None => return Err(PackError::MissingPackFormat),
+    }
+    pack.entry("description")
+        .or_insert_with(|| DEFAULT_DESCRIPTION.into());
+
+    if !overlays.is_empty() {
+        let entries = root
+            .entry("overlays")
+            .or_insert_with(|| json!({}))
+            .as_object_mut()
+            .ok_or_else(|| invalid("'overlays' isn't an object"))?
+            .entry("entries")
+            .or_insert_with(|| json!([]))
+            .as_array_mut()
+            .ok_or_else(|| invalid("'overlays.entries' isn't an array"))?;
+
+        for (index, overlay) in overlays.iter().enumerate() {
+            if overlays[..index]
+                .iter()
+                .any(|other| other.directory == overlay.directory)
+            {
+                return Err(PackError::DuplicateOverlay(overlay.directory.clone()));
+            }
+        }
+
+        entries.retain(|entry| {
+            let directory = entry.get("directory").and_then(Value::as_str);
+            !overlays
+                .iter()
+                .any(|overlay| directory == Some(&overlay.directory))
+        });
+        entries.extend(overlays.iter().map(|overlay| {
+            json!({
+                "directory": overlay.directory,
+                "formats": overlay.formats,
+            })
+        }));
+
+        check_overlaps(entries)?;
+    }
+
+    let mut contents = serde_json::to_vec_pretty(&root).expect("JSON values serialize");
+    contents.push(b'\n');
+
+    Ok(contents)
+}
+
+/// Fails if any two of the overlay `entries` of a `pack.mcmeta` apply to the
+/// same pack format. Entries which can't be read are left alone.
+fn check_overlaps(entries: &[Value]) -> Result<(), PackError> {
+    let ranges: Vec<(&str, FormatRange)> = entries
+        .iter()
+        .filter_map(|entry| {
+            let directory = entry.get("directory")?.as_str()?;
+            let formats = FormatRange::from_json(entry.get("formats")?)?;
+
+            Some((directory, formats))
+        })
+        .collect();
+
+    for (index, (first, first_formats)) in ranges.iter().enumerate() {
+        for (second, second_formats) in &ranges[index + 1..] {
+            if first_formats.overlaps(*second_formats) {
+                return Err(PackError::OverlappingOverlays {
+                    first: first.to_string(),
+                    first_formats: *first_formats,
+                    second: second.to_string(),
+                    second_formats: *second_formats,
+                });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Places the assets extracted in `report` in each of the `overlays`, e.g.
+/// `assets/minecraft/lang/en_us.json` in
+/// `new_ui/assets/minecraft/lang/en_us.json`, returning how many files were
+/// placed.
+///
+/// Unless `keep_base` is set, each file is moved to the first overlay,
+/// leaving the base `assets` empty. Files outside of `assets`, e.g. `data`,
+/// are left where they are, as are all files if the top-level directory was
+/// ignored.
+pub fn place_in_overlays(
+    report: &ExtractionReport,
+    overlays: &[Overlay],
+    keep_base: bool,
+) -> Result<usize, ExtractError> {
+    let options = &report.options;
+    let vfs = &*options.vfs;
+    let mut placed = 0;
+
+    if options.ignore_top_level {
+        return Ok(placed);
+    }
+
+    let extracted = report
+        .files
+        .iter()
+        .filter(|file| !matches!(file.outcome, FileOutcome::Failed { .. }))
+        .filter(|file| file.path.components().next() == Some(Component::Normal("assets".as_ref())));
+
+    for file in extracted {
+        let mut original = options.output_dir.join(&file.path);
+
+        for (index, overlay) in overlays.iter().enumerate() {
+            let target: PathBuf = options
+                .output_dir
+                .join(Path::new(&overlay.directory))
+                .join(&file.path);
+            if vfs.exists(&target) {
+                if options.keeps_existing(&target) {
+                    continue;
+                }
+                vfs.remove_file(&target)
+                    .map_err(ExtractError::write_file(&target))?;
+            }
+
+            if let Some(parent) = target.parent() {
+                vfs.create_dir_all(parent)
+                    .map_err(ExtractError::write_file(parent))?;
+            }
+
+            if index == 0 && !keep_base {
+                vfs.rename(&original, &target)
+                    .map_err(ExtractError::write_file(&target))?;
+                original = target;
+            } else {
+                vfs.copy(&original, &target)
+                    .map_err(ExtractError::write_file(&target))?;
+            }
+
+            placed += 1;
+        }
+    }
+
+    Ok(placed)
+}
@@ -0,0 +1,190 @@
+//! Deduplicating identical files across output directories, by hard linking
+//! them against a content-addressed pool.
+
+use crate::error::{ExtractError, IoContext};
+use crate::report::{ExtractionReport, FileOutcome};
+use crate::util;
+use serde::Serialize;
+use std::fs::{self, File};
+use std::io;
+use std::ops;
+use std::path::{Path, PathBuf};
+
+/// A directory of files named by the SHA-1 hashes of their contents, which
+/// identical extracted files are hard linked to.
+///
+/// Files are stored like hashed assets, e.g. `bf/bf21a9e8…` within the pool,
+/// so that each contents is only stored once however many output directories
+/// it is extracted into. The pool works on the real filesystem, and must be on
+/// the same volume as the files added to it.
+///
+/// As pooled files share their contents, a file which has been added must
+/// not be written to in place, or every file linked to it changes too.
+/// Replacing it with a new file is fine.
+///
+/// # Examples
+/// ```no_run
+/// use extract_minecraft_assets::jar;
+/// use extract_minecraft_assets::options::ExtractOptions;
+/// use extract_minecraft_assets::pool::ContentPool;
+/// use extract_minecraft_assets::progress::NoProgress;
+/// use std::path::Path;
+///
+/// let pool = ContentPool::new("archive/.pool");
+/// for version in ["1.20.1", "1.20.2"] {
+///     let report = jar::extract_jar(
+///         &Path::new(".minecraft/versions").join(version).join(format!("{version}.jar")),
+///         &ExtractOptions::new().output(Path::new("archive").join(version)),
+///         &mut NoProgress,
+///     )?;
+///     let pooled = pool.add_extracted(&report)?;
+///
+///     println!("{version}: {} after deduplication", pooled.stored_bytes());
+/// }
+/// # Ok::<(), extract_minecraft_assets::error::ExtractError>(())
+/// ```
+#[derive(Clone, Debug)]
+pub struct ContentPool {
+    dir: PathBuf,
+}
+
+/// What happened to a file [added](ContentPool::add) to a [`ContentPool`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Pooled {
+    /// The pool didn't have the contents, so the file was added to it.
+    Added,
+    /// The pool already had the contents, so the file was replaced with a
+    /// link to them.
+    Linked,
+}
+
+/// The totals of the files [added](ContentPool::add_extracted) to a
+/// [`ContentPool`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PoolSummary {
+    /// The number of files added.
+    pub files: usize,
+    /// The number of bytes in the files added.
+    pub bytes: u64,
+    /// The number of files whose contents the pool already had.
+    pub linked: usize,
+    /// The number of bytes in the files whose contents the pool already had,
+    /// which no longer take up any space.
+    pub linked_bytes: u64,
+}
+
+impl PoolSummary {
+    /// The number of bytes the files take up after deduplication.
+    pub fn stored_bytes(&self) -> u64 {
+        self.bytes - self.linked_bytes
+    }
+}
+
+/// Combines the totals of two sets of files.
+impl ops::Add for PoolSummary {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            files: self.files + other.files,
+            bytes: self.bytes + other.bytes,
+            linked: self.linked + other.linked,
+            linked_bytes: self.linked_bytes + other.linked_bytes,
+        }
+    }
+}
+
+impl ContentPool {
+    /// Returns the pool in `dir`, which is created as files are added.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// The directory of the pool.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The path within the pool of the contents with the SHA-1 `hash`.
+    pub fn path(&self, hash: &str) -> PathBuf {
+        self.dir.join(&hash[..2]).join(hash)
+    }
+
+    /// Adds the file at `path`, whose contents have the SHA-1 `hash` if it is
+    /// known, to the pool.
+    ///
+    /// If the pool already has the contents, the file is replaced with a hard
+    /// link to them, and otherwise the file is linked into the pool. Without a
+    /// `hash`, or one which isn't a SHA-1 hash, the file is read to hash it.
+    pub fn add(&self, path: &Path, hash: Option<&str>) -> Result<Pooled, ExtractError> {
+        let hash = match hash.filter(|hash| is_sha1(hash)) {
+            Some(hash) => hash.to_owned(),
+            None => File::open(path)
+                .and_then(util::sha1_hex)
+                .reading_output(path)?,
+        };
+        let pooled = self.path(&hash);
+
+        if !pooled.is_file() {
+            let parent = pooled.parent().unwrap_or(&self.dir);
+            fs::create_dir_all(parent).writing(parent)?;
+
+            match fs::hard_link(path, &pooled) {
+                Ok(()) => return Ok(Pooled::Added),
+                // Another file with the contents was added meanwhile.
+                Err(error) if error.kind() == io::ErrorKind::AlreadyExists => {}
+                Err(error) => return Err(ExtractError::write_file(&pooled)(error)),
+            }
+        }
+
+        // The link is made beside the file and moved over it, so that the file
+        // is never missing.
+        let mut link = path.as_os_str().to_owned();
+        link.push(".pooled");
+        let link = PathBuf::from(link);
+        let _ = fs::remove_file(&link);
+        fs::hard_link(&pooled, &link).writing(&link)?;
+        fs::rename(&link, path).writing(path)?;
+
+        Ok(Pooled::Linked)
+    }
+
+    /// Adds every file written by the extraction of `report` to the pool.
+    ///
+    /// The hashes of hashed assets are taken from the report, unless their
+    /// contents may have been changed as they were written, e.g. by
+    /// [formatting JSON](crate::options::ExtractOptions::format_json). Every
+    /// other file is read to hash it.
+    pub fn add_extracted(&self, report: &ExtractionReport) -> Result<PoolSummary, ExtractError> {
+        let options = &report.options;
+        let transformed = options.convert_lang.is_some()
+            || options.convert_nbt.is_some()
+            || options.format_json.is_some()
+            || options.text_normalize.is_some();
+        let mut summary = PoolSummary::default();
+
+        for file in &report.files {
+            let FileOutcome::Written { bytes } = file.outcome else {
+                continue;
+            };
+            let hash = file.hash.as_deref().filter(|_| !transformed);
+
+            let pooled = self.add(&options.output_dir.join(&file.path), hash)?;
+            summary.files += 1;
+            summary.bytes += bytes;
+            if pooled == Pooled::Linked {
+                summary.linked += 1;
+                summary.linked_bytes += bytes;
+            }
+        }
+
+        Ok(summary)
+    }
+}
+
+/// Whether `hash` looks like a SHA-1 hash in hexadecimal, so that it can name
+/// a file in the pool.
+fn is_sha1(hash: &str) -> bool {
+    hash.len() == 40 && hash.bytes().all(|byte| byte.is_ascii_hexdigit())
+}
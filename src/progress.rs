@@ -0,0 +1,701 @@
+use crate::error::ExtractError;
+use crate::jar::ModeOutcome;
+use crate::options::ExtractOptions;
+use crate::report::{ExtractionReport, FileOutcome, FileRecord, FileWarning};
+use crate::search::FileSource;
+use crate::util;
+use serde::Serialize;
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+use std::{fmt, io, ops};
+
+/// Receives progress events from an extraction.
+///
+/// Every method does nothing by default, so implementors only need to handle
+/// the events they're interested in. [`NoProgress`] ignores every event.
+///
+/// # Examples
+/// ```
+/// use extract_minecraft_assets::progress::ProgressSink;
+/// use std::path::Path;
+///
+/// /// Prints the path of every extracted file.
+/// struct PrintFiles;
+///
+/// impl ProgressSink for PrintFiles {
+///     fn file_done(&mut self, path: &Path, _bytes: u64) {
+///         println!("{}", path.display());
+///     }
+/// }
+/// ```
+pub trait ProgressSink {
+    /// Called once before any files are extracted, with the number of files
+    /// and bytes which are going to be extracted.
+    fn begin(&mut self, _total_files: usize, _total_bytes: u64) {}
+
+    /// Called when a file starts being extracted.
+    ///
+    /// `path` is relative to the output directory.
+    fn file_started(&mut self, _path: &Path) {}
+
+    /// Called when a file has been extracted, with the number of bytes written.
+    fn file_done(&mut self, _path: &Path, _bytes: u64) {}
+
+    /// Called when a file is skipped because it already exists, as set by
+    /// [`Overwrite::Skip`](crate::options::Overwrite::Skip) or decided with
+    /// [`Overwrite::Ask`](crate::options::Overwrite::Ask).
+    fn file_skipped(&mut self, _path: &Path) {}
+
+    /// Called when a file failed to be extracted, but the extraction continues.
+    fn file_failed(&mut self, _path: &Path, _error: &ExtractError) {}
+
+    /// Called when a file was extracted, but not as asked, e.g. when a
+    /// language file couldn't be converted and was extracted as it is.
+    fn file_warning(&mut self, _path: &Path, _message: &str) {}
+
+    /// Called once after every file has been extracted.
+    fn finish(&mut self, _summary: &Summary) {}
+
+    /// Called after [`begin`](ProgressSink::begin) when the files are
+    /// extracted by `workers` worker threads, as set by
+    /// [`threads`](ExtractOptions::threads).
+    ///
+    /// Returns where the workers report the file each of them is writing,
+    /// such as the [sender](MultiProgress::sender) of a [`MultiProgress`], if
+    /// anywhere. The other events are still sent to this sink, from the
+    /// thread the extraction was started on.
+    fn workers(&mut self, _workers: usize) -> Option<Sender<WorkerEvent>> {
+        None
+    }
+}
+
+/// A [`ProgressSink`] which ignores every event.
+#[derive(Clone, Copy, Default, Debug)]
+pub struct NoProgress;
+
+impl ProgressSink for NoProgress {}
+
+/// A summary of a finished extraction.
+#[derive(Clone, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Summary {
+    /// The number of files extracted.
+    pub files: usize,
+    /// The number of bytes written.
+    pub bytes: u64,
+    /// The number of files which were skipped because they already existed.
+    pub skipped: usize,
+    /// The number of files which were left out because they were unchanged
+    /// from an earlier version.
+    pub unchanged: usize,
+    /// The number of files deleted from the output directory because the
+    /// source no longer has them, when [syncing](crate::sync).
+    pub deleted: usize,
+    /// The number of files which failed to be extracted.
+    pub failures: usize,
+    /// The number of bytes fewer than the originals which JSON files were
+    /// written with, by [minifying](crate::format::JsonFormat::Minify) them.
+    pub bytes_saved: u64,
+    /// The number of text files whose line endings were
+    /// [normalized](crate::text::normalize).
+    pub normalized: usize,
+    /// The number of files written whose contents failed to
+    /// [validate](crate::options::ExtractOptions::validate_content).
+    pub invalid: usize,
+    /// The number of hashed files which didn't match the index file when
+    /// [verified](crate::options::ExtractOptions::verify), whether they
+    /// were written anyway or failed.
+    pub unverified: usize,
+    /// The number of files and directories whose unix permissions couldn't
+    /// be [set](crate::options::ExtractOptions::strict_permissions).
+    pub permissions_not_set: usize,
+    /// Whether the extraction was [cancelled](crate::options::CancellationToken)
+    /// before every file was extracted.
+    pub cancelled: bool,
+    /// Whether the extraction stopped before every file was extracted
+    /// because the output volume is full.
+    pub disk_full: bool,
+    /// The number of bytes which were left to extract when the output volume
+    /// became full, going by the sizes in the jar file or index file.
+    pub remaining_bytes: u64,
+    /// How long the extraction took.
+    pub duration: Duration,
+}
+
+impl Summary {
+    /// An empty summary, for commands which don't extract anything.
+    pub fn empty() -> Self {
+        Self::default()
+    }
+
+    /// Whether the extraction stopped before every file was extracted,
+    /// because it was cancelled or the output volume is full.
+    pub fn stopped(&self) -> bool {
+        self.cancelled || self.disk_full
+    }
+}
+
+/// Combines the summaries of two extractions.
+impl ops::Add for Summary {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            files: self.files + other.files,
+            bytes: self.bytes + other.bytes,
+            skipped: self.skipped + other.skipped,
+            unchanged: self.unchanged + other.unchanged,
+            deleted: self.deleted + other.deleted,
+            failures: self.failures + other.failures,
+            bytes_saved: self.bytes_saved + other.bytes_saved,
+            normalized: self.normalized + other.normalized,
+            invalid: self.invalid + other.invalid,
+            unverified: self.unverified + other.unverified,
+            permissions_not_set: self.permissions_not_set + other.permissions_not_set,
+            cancelled: self.cancelled || other.cancelled,
+            disk_full: self.disk_full || other.disk_full,
+            remaining_bytes: self.remaining_bytes + other.remaining_bytes,
+            duration: self.duration + other.duration,
+        }
+    }
+}
+
+/// Summarizes the extraction, e.g. `1200 files (24.5 MiB) in 3.2s, 0 failures`.
+///
+/// Skipped, unchanged and deleted files, bytes saved, normalized, invalid
+/// and unverified files, and those without permissions are only mentioned if
+/// there were any, and `(cancelled)` or `(disk full, 1.2 GiB remaining)` is
+/// added if the extraction stopped early.
+impl Display for Summary {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} files ({}) in {:.1}s, ",
+            self.files,
+            util::format_size(self.bytes),
+            self.duration.as_secs_f64(),
+        )?;
+
+        if self.skipped > 0 {
+            write!(f, "{} skipped, ", self.skipped)?;
+        }
+        if self.unchanged > 0 {
+            write!(f, "{} unchanged, ", self.unchanged)?;
+        }
+        if self.deleted > 0 {
+            write!(f, "{} deleted, ", self.deleted)?;
+        }
+        if self.bytes_saved > 0 {
+            write!(f, "{} saved, ", util::format_size(self.bytes_saved))?;
+        }
+        if self.normalized > 0 {
+            write!(f, "{} normalized, ", self.normalized)?;
+        }
+        if self.invalid > 0 {
+            write!(f, "{} invalid, ", self.invalid)?;
+        }
+        if self.unverified > 0 {
+            write!(f, "{} failed verification, ", self.unverified)?;
+        }
+        if self.permissions_not_set > 0 {
+            write!(f, "{} without permissions, ", self.permissions_not_set)?;
+        }
+
+        write!(f, "{} failures", self.failures)?;
+
+        if self.cancelled {
+            write!(f, " (cancelled)")?;
+        }
+        if self.disk_full {
+            write!(
+                f,
+                " (disk full, {} remaining)",
+                util::format_size(self.remaining_bytes)
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Tallies the events of an extraction into an [`ExtractionReport`] while
+/// forwarding them to a [`ProgressSink`].
+pub(crate) struct Tally<'a> {
+    sink: &'a mut dyn ProgressSink,
+    started: Instant,
+    report: ExtractionReport,
+    /// Where the files are extracted from.
+    source: FileSource,
+    /// The hash of the hashed asset whose files are being recorded, if any.
+    hash: Option<String>,
+}
+
+impl<'a> Tally<'a> {
+    /// Starts tallying an extraction with `options` of `total_files` files and
+    /// `total_bytes` bytes from `source`.
+    pub(crate) fn begin(
+        sink: &'a mut dyn ProgressSink,
+        options: &ExtractOptions,
+        source: FileSource,
+        total_files: usize,
+        total_bytes: u64,
+    ) -> Self {
+        sink.begin(total_files, total_bytes);
+
+        Self {
+            sink,
+            started: Instant::now(),
+            report: ExtractionReport::new(options.clone()),
+            source,
+            hash: None,
+        }
+    }
+
+    fn record(&mut self, path: &Path, outcome: FileOutcome) {
+        self.report.files.push(FileRecord {
+            path: path.to_owned(),
+            source: self.source,
+            hash: self.hash.clone(),
+            outcome,
+        });
+    }
+
+    /// Records the files of the hashed asset with `hash` from now on.
+    pub(crate) fn object_started(&mut self, hash: &str) {
+        self.hash = Some(hash.to_owned());
+    }
+
+    pub(crate) fn workers(&mut self, workers: usize) -> Option<Sender<WorkerEvent>> {
+        self.sink.workers(workers)
+    }
+
+    pub(crate) fn file_started(&mut self, path: &Path) {
+        self.sink.file_started(path);
+    }
+
+    pub(crate) fn file_done(&mut self, path: &Path, bytes: u64) {
+        self.report.summary.files += 1;
+        self.report.summary.bytes += bytes;
+        self.record(path, FileOutcome::Written { bytes });
+        self.sink.file_done(path, bytes);
+    }
+
+    pub(crate) fn file_skipped(&mut self, path: &Path) {
+        self.report.summary.skipped += 1;
+        self.record(path, FileOutcome::Skipped);
+        self.sink.file_skipped(path);
+    }
+
+    pub(crate) fn file_failed(&mut self, path: &Path, error: &ExtractError) {
+        self.report.summary.failures += 1;
+        self.record(
+            path,
+            FileOutcome::Failed {
+                error: error.to_string(),
+            },
+        );
+        self.sink.file_failed(path, error);
+    }
+
+    pub(crate) fn bytes_saved(&mut self, bytes: u64) {
+        self.report.summary.bytes_saved += bytes;
+    }
+
+    pub(crate) fn files_normalized(&mut self, count: usize) {
+        self.report.summary.normalized += count;
+    }
+
+    pub(crate) fn file_warning(&mut self, path: &Path, message: String) {
+        self.sink.file_warning(path, &message);
+        self.report.warnings.push(FileWarning {
+            path: path.to_owned(),
+            message,
+        });
+    }
+
+    /// Records that the contents of the file written to `path` are invalid,
+    /// as a warning giving the `reason`.
+    pub(crate) fn file_invalid(&mut self, path: &Path, reason: String) {
+        self.report.summary.invalid += 1;
+        self.file_warning(path, reason);
+    }
+
+    /// Records that the hashed file being extracted doesn't match the index
+    /// file.
+    pub(crate) fn object_unverified(&mut self) {
+        self.report.summary.unverified += 1;
+    }
+
+    /// Records the outcome of setting the permissions of the file or
+    /// directory written to `path`.
+    pub(crate) fn mode_set(&mut self, path: &Path, outcome: ModeOutcome) {
+        if let ModeOutcome::NotSet(warning) = outcome {
+            self.report.summary.permissions_not_set += 1;
+            if let Some(message) = warning {
+                self.file_warning(path, message);
+            }
+        }
+    }
+
+    /// Finishes the extraction early because it was cancelled, returning its
+    /// partial report.
+    pub(crate) fn cancel(mut self) -> ExtractionReport {
+        self.report.summary.cancelled = true;
+
+        self.finish()
+    }
+
+    /// Finishes the extraction early because the output volume is full,
+    /// recording the file being written to `path` as failed with `error`, and
+    /// returning its partial report.
+    ///
+    /// `remaining_bytes` is the size of the files not yet extracted, including
+    /// that one.
+    pub(crate) fn disk_full(
+        mut self,
+        path: &Path,
+        error: &ExtractError,
+        remaining_bytes: u64,
+    ) -> ExtractionReport {
+        self.file_failed(path, error);
+        self.report.summary.disk_full = true;
+        self.report.summary.remaining_bytes = remaining_bytes;
+
+        self.finish()
+    }
+
+    /// Finishes the extraction, returning its report.
+    pub(crate) fn finish(mut self) -> ExtractionReport {
+        self.report.summary.duration = self.started.elapsed();
+        self.sink.finish(&self.report.summary);
+
+        self.report
+    }
+}
+
+/// An event sent by a [`WorkerSink`] to a [`MultiProgress`].
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum WorkerEvent {
+    /// The worker started extracting the file at `path`, relative to the
+    /// output directory.
+    Started { worker: usize, path: PathBuf },
+    /// The worker finished a file, whether it was extracted, skipped or
+    /// failed.
+    Finished { worker: usize },
+    /// The worker has nothing more to extract for now.
+    Idle { worker: usize },
+    /// A file was finished without being given to any worker, e.g. because
+    /// it already exists and was skipped.
+    Skipped,
+}
+
+/// A [`ProgressSink`] for one of the workers of an extraction split across
+/// threads, which sends its events to a [`MultiProgress`].
+#[derive(Clone, Debug)]
+pub struct WorkerSink {
+    worker: usize,
+    sender: Sender<WorkerEvent>,
+}
+
+impl WorkerSink {
+    fn send(&self, event: WorkerEvent) {
+        // The display is only gone once the extraction is over.
+        let _ = self.sender.send(event);
+    }
+}
+
+impl ProgressSink for WorkerSink {
+    fn file_started(&mut self, path: &Path) {
+        self.send(WorkerEvent::Started {
+            worker: self.worker,
+            path: path.to_owned(),
+        });
+    }
+
+    fn file_done(&mut self, _path: &Path, _bytes: u64) {
+        self.send(WorkerEvent::Finished {
+            worker: self.worker,
+        });
+    }
+
+    fn file_skipped(&mut self, _path: &Path) {
+        self.send(WorkerEvent::Finished {
+            worker: self.worker,
+        });
+    }
+
+    fn file_failed(&mut self, _path: &Path, _error: &ExtractError) {
+        self.send(WorkerEvent::Finished {
+            worker: self.worker,
+        });
+    }
+
+    fn finish(&mut self, _summary: &Summary) {
+        self.send(WorkerEvent::Idle {
+            worker: self.worker,
+        });
+    }
+}
+
+/// Where a [`MultiProgress`] draws its frames, e.g. a terminal.
+pub trait FrameTarget: Send {
+    /// The number of columns and rows available, or [`None`] if the target
+    /// isn't a terminal, in which case frames are a single line.
+    fn size(&self) -> Option<(usize, usize)>;
+
+    /// Replaces the frame drawn before, if any, with `lines`.
+    fn draw(&mut self, lines: &[String]) -> io::Result<()>;
+
+    /// Removes the frame drawn last, if any.
+    fn clear(&mut self) -> io::Result<()>;
+}
+
+/// The narrowest target on which a [`MultiProgress`] draws a line per
+/// worker, rather than a single line.
+const MIN_MULTI_LINE_WIDTH: usize = 40;
+/// The widest the overall progress bar of a [`MultiProgress`] is drawn.
+const MAX_BAR_WIDTH: usize = 40;
+
+/// Displays the progress of an extraction split across worker threads: an
+/// overall progress bar, and a line per worker giving the file it is
+/// extracting.
+///
+/// Frames are drawn by a dedicated thread which owns the [`FrameTarget`],
+/// and which the workers report to through their [sinks](MultiProgress::sink),
+/// so that workers never wait on the display. When the target is too narrow
+/// or short for a line per worker, or isn't a terminal, the overall progress
+/// and the latest file are drawn on a single line instead. The frame is
+/// cleared once [finished](MultiProgress::finish), or dropped.
+///
+/// # Examples
+/// ```
+/// use extract_minecraft_assets::progress::{FrameTarget, MultiProgress, ProgressSink};
+/// use std::io;
+/// use std::path::Path;
+/// use std::time::Duration;
+///
+/// /// Prints every frame as it is drawn.
+/// struct PrintFrames;
+///
+/// impl FrameTarget for PrintFrames {
+///     fn size(&self) -> Option<(usize, usize)> {
+///         Some((80, 24))
+///     }
+///
+///     fn draw(&mut self, lines: &[String]) -> io::Result<()> {
+///         println!("{}", lines.join("\n"));
+///         Ok(())
+///     }
+///
+///     fn clear(&mut self) -> io::Result<()> {
+///         Ok(())
+///     }
+/// }
+///
+/// let progress = MultiProgress::spawn(PrintFrames, 2, 2, Duration::from_millis(100));
+/// let mut sinks = [progress.sink(0), progress.sink(1)];
+/// sinks[0].file_started(Path::new("assets/minecraft/lang/en_us.json"));
+/// sinks[1].file_started(Path::new("assets/minecraft/texts/splashes.txt"));
+///
+/// drop(sinks);
+/// progress.finish()?;
+/// # Ok::<(), io::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct MultiProgress {
+    /// Taken once finished, so that the drawing thread stops.
+    sender: Option<Sender<WorkerEvent>>,
+    thread: Option<JoinHandle<io::Result<()>>>,
+}
+
+/// The state of an extraction split across workers, as drawn by a
+/// [`MultiProgress`].
+struct ProgressArea {
+    total: usize,
+    completed: usize,
+    /// The file each worker is extracting, if any.
+    workers: Vec<Option<PathBuf>>,
+    /// The file started last by any worker.
+    latest: Option<PathBuf>,
+}
+
+impl MultiProgress {
+    /// Starts drawing the progress of `workers` workers extracting
+    /// `total_files` files between them onto `target`, at most once per
+    /// `interval`.
+    ///
+    /// With a zero `interval`, a frame is drawn for every event.
+    pub fn spawn(
+        mut target: impl FrameTarget + 'static,
+        total_files: usize,
+        workers: usize,
+        interval: Duration,
+    ) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let mut area = ProgressArea {
+            total: total_files,
+            completed: 0,
+            workers: vec![None; workers],
+            latest: None,
+        };
+
+        let thread = thread::spawn(move || {
+            let mut drawn: Option<Instant> = None;
+            let mut pending = false;
+
+            loop {
+                let event = if pending {
+                    let waited = drawn.map_or(interval, |drawn| drawn.elapsed());
+                    receiver.recv_timeout(interval.saturating_sub(waited))
+                } else {
+                    receiver.recv().map_err(RecvTimeoutError::from)
+                };
+
+                match event {
+                    Ok(event) => {
+                        area.apply(event);
+                        pending = true;
+                    }
+                    Err(RecvTimeoutError::Timeout) => {}
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
+
+                if pending && drawn.is_none_or(|drawn| drawn.elapsed() >= interval) {
+                    target.draw(&area.frame(target.size()))?;
+                    drawn = Some(Instant::now());
+                    pending = false;
+                }
+            }
+
+            target.clear()
+        });
+
+        Self {
+            sender: Some(sender),
+            thread: Some(thread),
+        }
+    }
+
+    /// Returns the sink for the worker numbered `worker`, counting from 0.
+    pub fn sink(&self, worker: usize) -> WorkerSink {
+        WorkerSink {
+            worker,
+            sender: self.sender().clone(),
+        }
+    }
+
+    /// Returns a sender of events, e.g. to report the progress of workers
+    /// without a [`WorkerSink`].
+    pub fn sender(&self) -> &Sender<WorkerEvent> {
+        self.sender
+            .as_ref()
+            .expect("the sender is only taken once finished")
+    }
+
+    /// Waits for the events sent so far to be drawn, and clears the frame.
+    ///
+    /// The sinks of every worker must have been dropped first, as the frame
+    /// is drawn until every sender is gone.
+    pub fn finish(mut self) -> io::Result<()> {
+        self.stop()
+    }
+
+    fn stop(&mut self) -> io::Result<()> {
+        self.sender = None;
+
+        match self.thread.take().map(JoinHandle::join) {
+            Some(Ok(result)) => result,
+            Some(Err(panic)) => std::panic::resume_unwind(panic),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Clears the frame, so that it isn't left behind when an extraction fails
+/// part way through.
+impl Drop for MultiProgress {
+    fn drop(&mut self) {
+        if !thread::panicking() {
+            let _ = self.stop();
+        }
+    }
+}
+
+impl ProgressArea {
+    fn apply(&mut self, event: WorkerEvent) {
+        match event {
+            WorkerEvent::Started { worker, path } => {
+                if let Some(current) = self.workers.get_mut(worker) {
+                    *current = Some(path.clone());
+                }
+                self.latest = Some(path);
+            }
+            WorkerEvent::Finished { .. } | WorkerEvent::Skipped => self.completed += 1,
+            WorkerEvent::Idle { worker } => {
+                if let Some(current) = self.workers.get_mut(worker) {
+                    *current = None;
+                }
+            }
+        }
+    }
+
+    /// Returns the lines of the frame to draw on a target of `size`.
+    fn frame(&self, size: Option<(usize, usize)>) -> Vec<String> {
+        let counter = format!("{}/{} files", self.completed.min(self.total), self.total);
+
+        // The last column and row are left free, as some terminals wrap or
+        // scroll when they are written.
+        let multi_line = size.filter(|&(columns, rows)| {
+            columns > MIN_MULTI_LINE_WIDTH && rows > self.workers.len() + 1
+        });
+        let Some((columns, _)) = multi_line else {
+            let width = size.map_or(usize::MAX, |(columns, _)| columns.saturating_sub(1));
+            let counter = format!("({counter})");
+            // The counter is kept whole for as long as the line fits.
+            let line = match &self.latest {
+                Some(path) => {
+                    let path_width =
+                        width.saturating_sub(util::display_width(&counter) + "Extracting  ".len());
+                    let path = util::truncate_middle(&path.display().to_string(), path_width);
+                    format!("Extracting {path} {counter}")
+                }
+                None => format!("Extracting {counter}"),
+            };
+
+            return vec![util::truncate_middle(&line, width)];
+        };
+        let width = columns - 1;
+
+        let bar_width = width
+            .saturating_sub(util::display_width(&counter) + 3)
+            .min(MAX_BAR_WIDTH);
+        // Nothing to extract is as good as everything extracted.
+        let filled = (bar_width * self.completed.min(self.total))
+            .checked_div(self.total)
+            .unwrap_or(bar_width);
+        let mut lines = vec![format!(
+            "[{}{}] {counter}",
+            "#".repeat(filled),
+            ".".repeat(bar_width - filled)
+        )];
+
+        for (worker, current) in self.workers.iter().enumerate() {
+            let prefix = format!("  #{} ", worker + 1);
+            let status = match current {
+                Some(path) => path.display().to_string(),
+                None => "idle".to_owned(),
+            };
+            let status_width = width.saturating_sub(util::display_width(&prefix));
+
+            lines.push(format!(
+                "{prefix}{}",
+                util::truncate_middle(&status, status_width)
+            ));
+        }
+
+        lines
+    }
+}
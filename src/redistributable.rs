@@ -0,0 +1,131 @@
+//! Leaving out the files which can't be redistributed, e.g. to publish
+//! extracted models, blockstates, language files and data without Mojang's
+//! artwork and recordings.
+//!
+//! Every file left out is matched by one of the [`EXCLUSIONS`], against paths
+//! like `assets/minecraft/textures/block/stone.png` as with the
+//! [filters](crate::filter) of an extraction.
+
+use crate::filter::{Pattern, PatternSet};
+use serde::Serialize;
+use std::path::Path;
+
+/// A kind of file left out of redistributable extractions, and why.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Exclusion {
+    /// What the files are, e.g. `textures`.
+    pub name: &'static str,
+    /// The [glob](Pattern::glob) matching the files.
+    pub glob: &'static str,
+    /// Why the files can't be redistributed.
+    pub reason: &'static str,
+}
+
+/// Every kind of file left out of redistributable extractions.
+pub const EXCLUSIONS: &[Exclusion] = &[
+    Exclusion {
+        name: "textures",
+        glob: "assets/*/textures/**",
+        reason: "they are Mojang's artwork",
+    },
+    Exclusion {
+        name: "sound files",
+        glob: "assets/*/sounds/**",
+        reason: "they are Mojang's recordings",
+    },
+    Exclusion {
+        name: "font glyph bitmaps",
+        glob: "assets/*/font/**/*.{zip,hex}",
+        reason: "they are Mojang's artwork",
+    },
+    Exclusion {
+        name: "other images, e.g. pack.png and icons",
+        glob: "{*.png,*.icns}",
+        reason: "they are Mojang's artwork",
+    },
+    Exclusion {
+        name: "other sound and music files, e.g. of older versions",
+        glob: "*.ogg",
+        reason: "they are Mojang's recordings and music",
+    },
+];
+
+/// Decides which files are left out by the [`EXCLUSIONS`], unless they are
+/// explicitly [included](Redistributable::include).
+///
+/// # Examples
+/// ```
+/// use extract_minecraft_assets::filter::{Pattern, PatternSet};
+/// use extract_minecraft_assets::redistributable::Redistributable;
+/// use std::path::Path;
+///
+/// let redistributable = Redistributable::new()
+///     .include(PatternSet::new().with(Pattern::glob("assets/minecraft/textures/gui/**")?));
+///
+/// assert!(redistributable.allows(Path::new("assets/minecraft/models/block/stone.json")));
+/// assert!(redistributable.allows(Path::new("assets/minecraft/textures/gui/title/minecraft.png")));
+/// assert_eq!(
+///     redistributable
+///         .exclusion(Path::new("assets/minecraft/sounds/ambient/cave/cave1.ogg"))
+///         .map(|exclusion| exclusion.name),
+///     Some("sound files")
+/// );
+/// # Ok::<(), extract_minecraft_assets::filter::PatternError>(())
+/// ```
+#[derive(Clone, Debug)]
+pub struct Redistributable {
+    exclusions: Vec<(&'static Exclusion, Pattern)>,
+    /// The files kept even if an exclusion matches them.
+    include: PatternSet,
+}
+
+impl Redistributable {
+    /// Returns a filter leaving out every file matched by the
+    /// [`EXCLUSIONS`].
+    pub fn new() -> Self {
+        let exclusions = EXCLUSIONS
+            .iter()
+            .map(|exclusion| {
+                let pattern = Pattern::glob(exclusion.glob).expect("exclusions are valid globs");
+
+                (exclusion, pattern)
+            })
+            .collect();
+
+        Self {
+            exclusions,
+            include: PatternSet::new(),
+        }
+    }
+
+    /// Keeps the files matching `include`, even if an exclusion matches them.
+    pub fn include(mut self, include: PatternSet) -> Self {
+        self.include = include;
+        self
+    }
+
+    /// Returns the exclusion leaving out the file at `path`, if it is left
+    /// out.
+    pub fn exclusion(&self, path: &Path) -> Option<&'static Exclusion> {
+        if self.include.matches(path) {
+            return None;
+        }
+
+        self.exclusions
+            .iter()
+            .find(|(_, pattern)| pattern.matches(path))
+            .map(|(exclusion, _)| *exclusion)
+    }
+
+    /// Whether the file at `path` is kept.
+    pub fn allows(&self, path: &Path) -> bool {
+        self.exclusion(path).is_none()
+    }
+}
+
+impl Default for Redistributable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,180 @@
+//! Checking that the references between the assets of a directory shaped
+//! like a resource pack, e.g. the output of an extraction, resolve.
+//!
+//! Blockstates, models, fonts and `sounds.json` files are read, and the
+//! files they require are looked up within the directory, or within a
+//! vanilla version's [sources](AssetSources) taken as the base which the
+//! directory overrides.
+
+use crate::closure::{AssetSources, ClosureError, Reference};
+use crate::diff;
+use crate::error::{ExtractError, IoContext};
+use crate::sounds::SoundEvents;
+use crate::{fonts, models};
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// The outcome of [checking](check) the references of a directory.
+#[derive(Clone, PartialEq, Eq, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReferenceCheck {
+    /// The number of files whose references were checked.
+    pub checked: usize,
+    /// The files with references to files which don't exist, sorted by path.
+    pub files: Vec<ReferencingFile>,
+    /// The files whose references couldn't be read, e.g. because they aren't
+    /// valid JSON.
+    pub errors: Vec<ClosureError>,
+}
+
+/// A file with references to files which don't exist.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReferencingFile {
+    /// The path of the file within `assets`.
+    pub path: PathBuf,
+    /// The paths of the missing files within `assets`, sorted.
+    pub dangling: Vec<PathBuf>,
+}
+
+impl ReferenceCheck {
+    /// The number of references to files which don't exist.
+    pub fn dangling(&self) -> usize {
+        self.files.iter().map(|file| file.dangling.len()).sum()
+    }
+}
+
+/// Returns the files required by the file at `path`, within `assets`,
+/// containing `contents`, or [`None`] if it isn't a blockstate, model, font
+/// or `sounds.json` file.
+///
+/// # Examples
+/// ```
+/// use extract_minecraft_assets::references;
+/// use std::path::{Path, PathBuf};
+///
+/// let sounds = references::required_files(
+///     Path::new("minecraft/sounds.json"),
+///     br#"{"block.bell.use": {"sounds": ["block/bell/bell_use01"]}}"#,
+/// );
+///
+/// assert_eq!(
+///     sounds.unwrap()?,
+///     [PathBuf::from("minecraft/sounds/block/bell/bell_use01.ogg")]
+/// );
+/// assert!(references::required_files(Path::new("minecraft/lang/en_us.json"), b"{}").is_none());
+/// # Ok::<(), serde_json::Error>(())
+/// ```
+pub fn required_files(
+    path: &Path,
+    contents: &[u8],
+) -> Option<Result<Vec<PathBuf>, serde_json::Error>> {
+    let references = match Kind::of(path)? {
+        Kind::Sounds => {
+            return Some(
+                SoundEvents::from_slice(contents)
+                    .map(|events| events.all_files().into_iter().collect()),
+            );
+        }
+        Kind::Font => fonts::font_references(contents),
+        Kind::Model => models::model_references(path, contents),
+    };
+
+    Some(references.map(|references| {
+        references
+            .into_iter()
+            .filter_map(|reference| match reference {
+                Reference::Required(path) => Some(path),
+                Reference::Optional(_) => None,
+            })
+            .collect()
+    }))
+}
+
+/// The kinds of files with references.
+enum Kind {
+    /// A `sounds.json` file, e.g. `minecraft/sounds.json`.
+    Sounds,
+    Font,
+    /// A blockstate or model.
+    Model,
+}
+
+impl Kind {
+    /// Returns the kind of the file at `path` within `assets`, if it has
+    /// references.
+    fn of(path: &Path) -> Option<Self> {
+        let mut components = path.components().map(Component::as_os_str);
+        let (Some(_), Some(dir)) = (components.next(), components.next()) else {
+            return None;
+        };
+        let is_json = path
+            .extension()
+            .is_some_and(|extension| extension == "json");
+
+        if dir == "sounds.json" && components.next().is_none() {
+            Some(Self::Sounds)
+        } else if fonts::is_font(path) {
+            Some(Self::Font)
+        } else if is_json && (dir == "blockstates" || dir == "models") {
+            Some(Self::Model)
+        } else {
+            None
+        }
+    }
+}
+
+/// Checks that the files required by every blockstate, model, font and
+/// `sounds.json` file within `assets_dir` exist, either within `assets_dir`
+/// or within the `base` sources, if given.
+///
+/// Built-in models like `builtin/generated` aren't files, so are never
+/// missing.
+pub fn check(
+    assets_dir: &Path,
+    base: Option<&AssetSources>,
+) -> Result<ReferenceCheck, ExtractError> {
+    let mut files = BTreeSet::new();
+    diff::find_files(assets_dir, Path::new(""), &mut files)?;
+
+    let mut check = ReferenceCheck::default();
+    for path in &files {
+        if Kind::of(path).is_none() {
+            continue;
+        }
+        let full_path = assets_dir.join(path);
+        let contents = fs::read(&full_path).reading_output(&full_path)?;
+        let Some(required) = required_files(path, &contents) else {
+            continue;
+        };
+        check.checked += 1;
+
+        let required = match required {
+            Ok(required) => required,
+            Err(error) => {
+                check.errors.push(ClosureError {
+                    path: path.clone(),
+                    message: error.to_string(),
+                });
+                continue;
+            }
+        };
+        let dangling: BTreeSet<_> = required
+            .into_iter()
+            .filter(|required| {
+                !files.contains(required) && base.is_none_or(|base| base.find(required).is_none())
+            })
+            .collect();
+
+        if !dangling.is_empty() {
+            check.files.push(ReferencingFile {
+                path: path.clone(),
+                dangling: dangling.into_iter().collect(),
+            });
+        }
+    }
+
+    Ok(check)
+}
@@ -0,0 +1,291 @@
+//! Reading a remote jar file over HTTP(S), fetching only the parts which are
+//! read.
+//!
+//! A zip archive is read from its end, where the central directory lists
+//! its entries, and then only the entries which are extracted, so reading a
+//! few files out of a jar file doesn't download the whole of it.
+//! [`RangeReader`] implements [`Read`] and [`Seek`] with HTTP range
+//! requests, so it can be given to e.g.
+//! [`jar::extract_jar_from`](crate::jar::extract_jar_from).
+
+use std::collections::BTreeMap;
+use std::io::{self, Read, Seek, SeekFrom};
+
+/// The size of the blocks a [`RangeReader`] fetches and keeps.
+const BLOCK_SIZE: u64 = 64 * 1024;
+
+/// The number of bytes fetched from the end of the file by the first
+/// request, which is usually enough for the whole central directory.
+const TAIL_SIZE: u64 = 4 * BLOCK_SIZE;
+
+/// The most blocks fetched by one request while reading sequentially.
+const MAX_READAHEAD: u64 = 64;
+
+/// What a [`RangeReader`] fetched.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct TransferStats {
+    /// The number of requests sent.
+    pub requests: usize,
+    /// The number of bytes received, not counting headers.
+    pub bytes: u64,
+    /// Whether the server supports range requests. If it doesn't, the whole
+    /// file was downloaded by the first request.
+    pub ranges: bool,
+}
+
+/// A remote file read with HTTP range requests.
+///
+/// The file is fetched in blocks of 64 KiB as it is read, and every block
+/// fetched is kept, so no block is fetched twice. Reading sequentially fetches
+/// more blocks at once, up to 4 MiB, so that large entries don't take a
+/// request per block. If the server doesn't support range requests, the
+/// whole file is downloaded by the first request instead.
+///
+/// # Examples
+/// ```no_run
+/// use extract_minecraft_assets::jar::{self, ExtractedContents};
+/// use extract_minecraft_assets::options::ExtractOptions;
+/// use extract_minecraft_assets::progress::NoProgress;
+/// use extract_minecraft_assets::remote::RangeReader;
+/// use std::path::Path;
+///
+/// let url = "https://example.com/client.jar";
+/// let mut reader = RangeReader::open(url)?;
+/// let options = ExtractOptions::new()
+///     .output("extracted")
+///     .contents(ExtractedContents::assets());
+///
+/// let report = jar::extract_jar_from(
+///     Path::new(url),
+///     &mut reader,
+///     &options,
+///     &mut NoProgress,
+///     |path| path.starts_with("assets/minecraft/lang"),
+/// )?;
+/// let stats = reader.stats();
+/// println!("Extracted {report} in {} requests", stats.requests);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub struct RangeReader {
+    agent: ureq::Agent,
+    url: String,
+    len: u64,
+    pos: u64,
+    /// The blocks fetched so far, by index.
+    blocks: BTreeMap<u64, Vec<u8>>,
+    /// The index of the block after the last one fetched.
+    fetched_until: u64,
+    /// The number of blocks fetched by the last request.
+    readahead: u64,
+    stats: TransferStats,
+}
+
+impl RangeReader {
+    /// Starts reading the file at `url`, fetching its end.
+    ///
+    /// Fails if the server can't be reached, or responds with an error.
+    pub fn open(url: &str) -> io::Result<Self> {
+        let mut reader = Self {
+            agent: ureq::agent(),
+            url: url.to_owned(),
+            len: 0,
+            pos: 0,
+            blocks: BTreeMap::new(),
+            fetched_until: 0,
+            readahead: 1,
+            stats: TransferStats::default(),
+        };
+
+        let response = reader.get(&format!("bytes=-{TAIL_SIZE}"))?;
+        match response.status() {
+            206 => {
+                let (start, end, len) = content_range(&response)?;
+                reader.len = len;
+                reader.stats.ranges = true;
+
+                // A partial block at the start of the tail is left out, as
+                // blocks are only kept whole.
+                let first_block = start.div_ceil(BLOCK_SIZE);
+                let mut body = reader.body(response, end - start)?;
+                let partial = ((first_block * BLOCK_SIZE - start) as usize).min(body.len());
+                body.drain(..partial);
+                reader.store(first_block, body);
+            }
+            _ => reader.store_whole(response)?,
+        }
+
+        Ok(reader)
+    }
+
+    /// The size of the file in bytes.
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Whether the file is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// What has been fetched so far.
+    pub fn stats(&self) -> TransferStats {
+        self.stats
+    }
+
+    /// Sends a request for the `range` of the file, e.g. `bytes=0-99`.
+    fn get(&mut self, range: &str) -> io::Result<ureq::Response> {
+        self.stats.requests += 1;
+
+        self.agent
+            .get(&self.url)
+            .set("Range", range)
+            .call()
+            .map_err(|error| io::Error::other(format!("failed to fetch '{}': {error}", self.url)))
+    }
+
+    /// Reads the `len` bytes of the body of `response`.
+    fn body(&mut self, response: ureq::Response, len: u64) -> io::Result<Vec<u8>> {
+        let mut body = Vec::new();
+        response.into_reader().take(len).read_to_end(&mut body)?;
+        self.stats.bytes += body.len() as u64;
+
+        if (body.len() as u64) < len {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!("the response from '{}' ended early", self.url),
+            ));
+        }
+
+        Ok(body)
+    }
+
+    /// Keeps the whole file, from a response which ignored the range asked
+    /// for.
+    fn store_whole(&mut self, response: ureq::Response) -> io::Result<()> {
+        let mut body = Vec::new();
+        response.into_reader().read_to_end(&mut body)?;
+        self.stats.bytes += body.len() as u64;
+        self.stats.ranges = false;
+
+        self.len = body.len() as u64;
+        self.store(0, body);
+
+        Ok(())
+    }
+
+    /// Keeps the blocks of `contents`, starting with the block `first`.
+    fn store(&mut self, first: u64, contents: Vec<u8>) {
+        for (i, block) in (first..).zip(contents.chunks(BLOCK_SIZE as usize)) {
+            self.blocks.entry(i).or_insert_with(|| block.to_vec());
+        }
+    }
+
+    /// Fetches the block `first`, and the blocks following it which haven't
+    /// been fetched yet if reading sequentially.
+    fn fetch(&mut self, first: u64) -> io::Result<()> {
+        let blocks = self.len.div_ceil(BLOCK_SIZE);
+        self.readahead = if first == self.fetched_until {
+            (self.readahead * 2).min(MAX_READAHEAD)
+        } else {
+            1
+        };
+        let count = (first..blocks)
+            .take(self.readahead as usize)
+            .take_while(|block| *block == first || !self.blocks.contains_key(block))
+            .count() as u64;
+
+        let start = first * BLOCK_SIZE;
+        let end = ((first + count) * BLOCK_SIZE).min(self.len);
+        let response = self.get(&format!("bytes={start}-{}", end - 1))?;
+
+        if response.status() != 206 {
+            return self.store_whole(response);
+        }
+        let (range_start, _, _) = content_range(&response)?;
+        if range_start != start {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("'{}' responded with another range than asked", self.url),
+            ));
+        }
+
+        let body = self.body(response, end - start)?;
+        self.store(first, body);
+        self.fetched_until = first + count;
+
+        Ok(())
+    }
+}
+
+/// Returns the first and last byte positions, plus one, and the size of the
+/// file, given by the `Content-Range` header of `response`, e.g.
+/// `bytes 100-199/1000`.
+fn content_range(response: &ureq::Response) -> io::Result<(u64, u64, u64)> {
+    let invalid = || {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "'{}' responded with an invalid Content-Range",
+                response.get_url()
+            ),
+        )
+    };
+
+    let range = response
+        .header("Content-Range")
+        .and_then(|range| range.strip_prefix("bytes "))
+        .ok_or_else(invalid)?;
+    let (range, len) = range.split_once('/').ok_or_else(invalid)?;
+    let (start, end) = range.split_once('-').ok_or_else(invalid)?;
+
+    match (start.parse(), end.parse::<u64>(), len.parse()) {
+        (Ok(start), Ok(end), Ok(len)) if start <= end && end < len => Ok((start, end + 1, len)),
+        _ => Err(invalid()),
+    }
+}
+
+impl Read for RangeReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pos >= self.len || buf.is_empty() {
+            return Ok(0);
+        }
+
+        let index = self.pos / BLOCK_SIZE;
+        if !self.blocks.contains_key(&index) {
+            self.fetch(index)?;
+        }
+        let Some(block) = self.blocks.get(&index) else {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!("'{}' is shorter than it was", self.url),
+            ));
+        };
+
+        let offset = (self.pos % BLOCK_SIZE) as usize;
+        let available = block.get(offset..).unwrap_or_default();
+        let read = available.len().min(buf.len());
+        buf[..read].copy_from_slice(&available[..read]);
+        self.pos += read as u64;
+
+        Ok(read)
+    }
+}
+
+impl Seek for RangeReader {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let pos = match pos {
+            SeekFrom::Start(pos) => Some(pos),
+            SeekFrom::End(offset) => self.len.checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.pos.checked_add_signed(offset),
+        };
+
+        self.pos = pos.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )
+        })?;
+
+        Ok(self.pos)
+    }
+}
@@ -0,0 +1,140 @@
+use crate::options::ExtractOptions;
+use crate::progress::Summary;
+use crate::search::FileSource;
+use serde::Serialize;
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+use std::{fmt, ops};
+
+/// A report of a finished extraction, returned by the extraction functions.
+///
+/// Along with the [summary](ExtractionReport::summary), the report records
+/// what happened to every file and the options which were used, and can be
+/// serialized (e.g. to JSON) to keep a record of the extraction.
+///
+/// # Examples
+/// ```no_run
+/// use extract_minecraft_assets::jar;
+/// use extract_minecraft_assets::options::ExtractOptions;
+/// use extract_minecraft_assets::progress::NoProgress;
+/// use extract_minecraft_assets::report::FileOutcome;
+/// use std::path::Path;
+///
+/// let report = jar::extract_jar(
+///     Path::new(".minecraft/versions/1.20.1/1.20.1.jar"),
+///     &ExtractOptions::new().output("extracted"),
+///     &mut NoProgress,
+/// )?;
+///
+/// for file in report.failed() {
+///     if let FileOutcome::Failed { error } = &file.outcome {
+///         eprintln!("{} failed: {error}", file.path.display());
+///     }
+/// }
+/// println!("Extracted {report}");
+/// # Ok::<(), extract_minecraft_assets::error::ExtractError>(())
+/// ```
+#[derive(Clone, Debug, Default, Serialize)]
+#[non_exhaustive]
+#[serde(rename_all = "camelCase")]
+pub struct ExtractionReport {
+    /// The totals of the extraction.
+    pub summary: Summary,
+    /// Every file which was extracted, skipped or failed, in the order they
+    /// finished.
+    pub files: Vec<FileRecord>,
+    /// The files which were extracted, but not as asked, in the order they
+    /// finished.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<FileWarning>,
+    /// The options the extraction was performed with.
+    pub options: ExtractOptions,
+}
+
+/// What happened to a single file during an extraction.
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileRecord {
+    /// The path of the file relative to the output directory.
+    pub path: PathBuf,
+    /// Where the file was extracted from.
+    pub source: FileSource,
+    /// The SHA-1 hash of the hashed asset the file was extracted from, if it
+    /// was extracted from an index file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hash: Option<String>,
+    #[serde(flatten)]
+    pub outcome: FileOutcome,
+}
+
+/// Whether a file was written, skipped or failed.
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Serialize)]
+#[serde(tag = "outcome", rename_all = "lowercase")]
+pub enum FileOutcome {
+    /// The file was written, with this many bytes.
+    Written { bytes: u64 },
+    /// The file was skipped because it already existed.
+    Skipped,
+    /// The file failed to be extracted.
+    Failed {
+        /// A description of the error.
+        error: String,
+    },
+}
+
+/// A file which was extracted, but not as asked, e.g. a language file which
+/// couldn't be converted and was extracted as it is.
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileWarning {
+    /// The path of the file relative to the output directory.
+    pub path: PathBuf,
+    /// A description of what went wrong.
+    pub message: String,
+}
+
+impl ExtractionReport {
+    /// Returns an empty report of an extraction with `options`.
+    pub fn new(options: ExtractOptions) -> Self {
+        Self {
+            summary: Summary::empty(),
+            files: Vec::new(),
+            warnings: Vec::new(),
+            options,
+        }
+    }
+
+    /// Returns the files which failed to be extracted.
+    pub fn failed(&self) -> impl Iterator<Item = &FileRecord> {
+        self.files
+            .iter()
+            .filter(|file| matches!(file.outcome, FileOutcome::Failed { .. }))
+    }
+
+    /// Returns the files which were written.
+    pub fn written(&self) -> impl Iterator<Item = &FileRecord> {
+        self.files
+            .iter()
+            .filter(|file| matches!(file.outcome, FileOutcome::Written { .. }))
+    }
+}
+
+/// Combines the reports of two extractions, keeping the options of the first.
+impl ops::Add for ExtractionReport {
+    type Output = Self;
+
+    fn add(mut self, other: Self) -> Self {
+        self.summary = self.summary + other.summary;
+        self.files.extend(other.files);
+        self.warnings.extend(other.warnings);
+
+        self
+    }
+}
+
+/// Displays the [summary](ExtractionReport::summary) of the extraction.
+impl Display for ExtractionReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.summary.fmt(f)
+    }
+}
@@ -0,0 +1,116 @@
+//! Searching the paths of jar files and index files for [patterns], without
+//! extracting anything.
+//!
+//! [patterns]: crate::filter::PatternSet
+
+use crate::error::ExtractError;
+use crate::filter::PatternSet;
+use crate::hashed::IndexFile;
+use crate::jar::{self, ExtractedContents};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Where a file is found: a [`Hit`] or a
+/// [large file](crate::largest::LargeFile).
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileSource {
+    /// Within a jar file.
+    Jar,
+    /// Within an index file.
+    Hashed,
+}
+
+impl FileSource {
+    /// The name of the source, `jar` or `hashed`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Jar => "jar",
+            Self::Hashed => "hashed",
+        }
+    }
+}
+
+/// A file whose path matched the patterns searched for.
+///
+/// Paths are relative to the output directory, as if extracted without
+/// ignoring the top-level directory, e.g. `assets/minecraft/lang/en_us.json`.
+/// This is also the path which is matched.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Hit {
+    pub source: FileSource,
+    pub path: PathBuf,
+    /// The size of the file in bytes.
+    pub size: u64,
+    /// The path to the hashed file within the `objects` folder, for hits
+    /// within an index file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub object_path: Option<PathBuf>,
+}
+
+/// Returns the files within `jar_file` which are part of the
+/// `extracted_contents` and match the `patterns`, sorted by path.
+///
+/// Only the central directory of the jar file is read.
+pub fn search_jar(
+    jar_file: &Path,
+    extracted_contents: &ExtractedContents,
+    patterns: &PatternSet,
+) -> Result<Vec<Hit>, ExtractError> {
+    let mut hits: Vec<_> = jar::list_files(jar_file, extracted_contents)?
+        .into_iter()
+        .filter(|(path, _)| patterns.matches(path))
+        .map(|(path, size)| Hit {
+            source: FileSource::Jar,
+            path,
+            size,
+            object_path: None,
+        })
+        .collect();
+    hits.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Ok(hits)
+}
+
+/// Returns the objects listed in `index` which match the `patterns`, sorted
+/// by path, within `assets`.
+///
+/// # Examples
+/// ```
+/// use extract_minecraft_assets::filter::{Pattern, PatternSet};
+/// use extract_minecraft_assets::hashed::IndexFile;
+/// use extract_minecraft_assets::search;
+///
+/// let index: IndexFile = serde_json::from_str(r#"{"objects": {
+///     "minecraft/sounds/ambient/cave/cave1.ogg": {"hash": "ab01", "size": 3},
+///     "minecraft/lang/de_de.json": {"hash": "cd02", "size": 5}
+/// }}"#)?;
+/// let patterns = PatternSet::new().with(Pattern::substring("cave"));
+///
+/// let hits = search::search_index(&index, &patterns);
+///
+/// assert_eq!(hits.len(), 1);
+/// assert_eq!(hits[0].path.to_str(), Some("assets/minecraft/sounds/ambient/cave/cave1.ogg"));
+/// assert_eq!(hits[0].object_path.as_deref().and_then(|path| path.to_str()), Some("ab/ab01"));
+/// # Ok::<(), serde_json::Error>(())
+/// ```
+pub fn search_index(index: &IndexFile, patterns: &PatternSet) -> Vec<Hit> {
+    let assets_dir = Path::new("assets");
+
+    let mut hits: Vec<_> = index
+        .objects
+        .iter()
+        .map(|(path, object)| (assets_dir.join(path), object))
+        .filter(|(path, _)| patterns.matches(path))
+        .map(|(path, object)| Hit {
+            source: FileSource::Hashed,
+            path,
+            size: object.size,
+            object_path: Some(object.hashed_file_path()),
+        })
+        .collect();
+    hits.sort_by(|a, b| a.path.cmp(&b.path));
+
+    hits
+}
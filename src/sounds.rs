@@ -0,0 +1,511 @@
+//! Organizing extracted sound files by the sound events which play them, as
+//! defined by `assets/minecraft/sounds.json`.
+//!
+//! Sound files have paths like `minecraft/sounds/mob/zombie/say1.ogg`, while
+//! the game (e.g. the `/playsound` command) refers to sound events like
+//! `entity.zombie.ambient`, each of which plays one of several sound files.
+
+use crate::backup::AssetsBackup;
+use crate::error::ExtractError;
+use crate::hashed::{self, IndexFile};
+use crate::jar::{ExtractedContents, JarAssets};
+use crate::locked::LockedRetry;
+use crate::options::ExtractOptions;
+use crate::report::{ExtractionReport, FileOutcome};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// The path of the file defining the sound events within `assets`.
+pub const SOUNDS_FILE: &str = "minecraft/sounds.json";
+
+/// The directory within the output directory into which sound files are
+/// organized.
+pub const SOUNDS_BY_EVENT_DIR: &str = "sounds-by-event";
+
+/// The sound events defined by a `sounds.json` file.
+///
+/// # Examples
+/// ```
+/// use extract_minecraft_assets::sounds::SoundEvents;
+/// use std::path::PathBuf;
+///
+/// let events = SoundEvents::from_slice(br#"{
+///     "entity.zombie.ambient": {"sounds": ["mob/zombie/say1", {"name": "mob/zombie/say2", "weight": 2}]},
+///     "entity.zombie_villager.ambient": {"sounds": [{"name": "entity.zombie.ambient", "type": "event"}]}
+/// }"#)?;
+///
+/// assert_eq!(
+///     events.files("entity.zombie_villager.ambient"),
+///     [
+///         PathBuf::from("minecraft/sounds/mob/zombie/say1.ogg"),
+///         PathBuf::from("minecraft/sounds/mob/zombie/say2.ogg"),
+///     ]
+/// );
+/// # Ok::<(), serde_json::Error>(())
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug, Default, Deserialize)]
+#[serde(transparent)]
+pub struct SoundEvents {
+    events: BTreeMap<String, SoundEvent>,
+}
+
+/// A sound event, playing one of its sounds at random.
+#[derive(Clone, PartialEq, Eq, Debug, Default, Deserialize)]
+struct SoundEvent {
+    #[serde(default)]
+    sounds: Vec<Sound>,
+}
+
+/// A sound which a [`SoundEvent`] can play.
+///
+/// Other fields, like the `weight` making a sound more likely to be played,
+/// don't change which files are played, so they are ignored.
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize)]
+#[serde(untagged)]
+enum Sound {
+    /// The name of a sound file, e.g. `mob/zombie/say1`.
+    File(String),
+    Detailed {
+        /// The name of a sound file, or of a sound event if `kind` is
+        /// [`SoundKind::Event`].
+        name: String,
+        #[serde(default, rename = "type")]
+        kind: SoundKind,
+    },
+}
+
+/// What the name of a [`Sound`] refers to.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum SoundKind {
+    #[default]
+    File,
+    /// Another sound event, playing any of its sounds.
+    Event,
+}
+
+/// How sound files are written into [`SOUNDS_BY_EVENT_DIR`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum SoundLinks {
+    /// Each sound file is copied.
+    #[default]
+    Copy,
+    /// Each sound file is hard linked, so sound files played by several
+    /// events take no extra space.
+    HardLink,
+}
+
+/// What happened when [organizing](SoundEvents::organize) sound files.
+#[derive(Clone, PartialEq, Eq, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrganizedSounds {
+    /// The number of sound events with at least one sound file.
+    pub events: usize,
+    /// The number of files written into [`SOUNDS_BY_EVENT_DIR`].
+    pub files: usize,
+    /// The sound files played by sound events which weren't extracted, within
+    /// `assets`.
+    pub missing: Vec<PathBuf>,
+    /// The extracted sound files which no sound event plays, relative to the
+    /// output directory.
+    pub unreferenced: Vec<PathBuf>,
+}
+
+/// A sound of a sound event which can't be played, as found by
+/// [`SoundEvents::check_index`].
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+#[serde(rename_all = "camelCase", tag = "problem")]
+pub enum BrokenSound {
+    /// The sound file isn't listed in the index file.
+    NotInIndex {
+        /// The path of the sound file within `assets`.
+        path: PathBuf,
+    },
+    /// The sound file is listed in the index file, but its hashed file
+    /// doesn't exist.
+    MissingObject {
+        /// The path of the sound file within `assets`.
+        path: PathBuf,
+        /// The SHA-1 hash of the sound file, which is also the name of its
+        /// hashed file.
+        hash: String,
+    },
+    /// A sound of `"type": "event"` names a sound event which isn't defined.
+    UnknownEvent { event: String },
+}
+
+/// The result of [checking](SoundEvents::check_index) the sounds of every
+/// sound event against an index file.
+#[derive(Clone, PartialEq, Eq, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SoundCheck {
+    /// The number of sound events checked.
+    pub events: usize,
+    /// The number of distinct sound files checked.
+    pub files: usize,
+    /// The broken sounds of each sound event with any, by the event's name,
+    /// in the order they are listed.
+    pub broken: BTreeMap<String, Vec<BrokenSound>>,
+}
+
+impl SoundCheck {
+    /// Whether every sound of every sound event can be played.
+    pub fn is_ok(&self) -> bool {
+        self.broken.is_empty()
+    }
+}
+
+impl SoundEvents {
+    /// Parses the contents of a `sounds.json` file.
+    pub fn from_slice(contents: &[u8]) -> Result<Self, serde_json::Error> {
+        serde_json::from_slice(contents)
+    }
+
+    /// Reads the `sounds.json` file listed in `index`, if there is one, from
+    /// the `objects` directory within `hashed_assets_dir`.
+    pub fn from_index(
+        hashed_assets_dir: &Path,
+        index: &IndexFile,
+    ) -> Result<Option<Self>, ExtractError> {
+        let Some(entry) = index.get(SOUNDS_FILE) else {
+            return Ok(None);
+        };
+
+        let object_path = hashed_assets_dir.join("objects").join(entry.object_path);
+        let contents =
+            hashed::read_object(entry.virtual_path, &object_path, &LockedRetry::default())?;
+
+        Self::parse(&contents).map(Some)
+    }
+
+    /// Reads the `sounds.json` file listed in `index`, if there is one, from
+    /// a `backup` of hashed assets.
+    pub fn from_backup(
+        backup: &AssetsBackup,
+        index: &IndexFile,
+    ) -> Result<Option<Self>, ExtractError> {
+        let Some((path, object)) = index.objects.get_key_value(Path::new(SOUNDS_FILE)) else {
+            return Ok(None);
+        };
+
+        Self::parse(&backup.read_object(path, object)?).map(Some)
+    }
+
+    /// Reads the `sounds.json` file within the `assets` of `jar_file`, if
+    /// there is one.
+    pub fn from_jar(jar_file: &Path) -> Result<Option<Self>, ExtractError> {
+        let sounds_file = Path::new("assets").join(SOUNDS_FILE);
+        let mut assets = JarAssets::open(jar_file, ExtractedContents::assets())?
+            .filter(|path| path == sounds_file);
+
+        let Some(mut entry) = assets.next_entry()? else {
+            return Ok(None);
+        };
+
+        let mut contents = Vec::new();
+        entry
+            .read_to_end(&mut contents)
+            .map_err(|source| ExtractError::Zip {
+                path: jar_file.to_owned(),
+                source: source.into(),
+            })?;
+
+        Self::parse(&contents).map(Some)
+    }
+
+    fn parse(contents: &[u8]) -> Result<Self, ExtractError> {
+        Self::from_slice(contents).map_err(|source| ExtractError::ParseSounds {
+            path: PathBuf::from(SOUNDS_FILE),
+            source,
+        })
+    }
+
+    /// The number of sound events.
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Whether no sound events are defined.
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// The names of the sound events, sorted.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.events.keys().map(String::as_str)
+    }
+
+    /// Returns the sound files which `event` can play, within `assets`, in the
+    /// order they are listed.
+    ///
+    /// Sounds of `"type": "event"` are replaced by the files of the event they
+    /// name, and each file is only returned once.
+    pub fn files(&self, event: &str) -> Vec<PathBuf> {
+        let mut files = Vec::new();
+        self.collect_files(event, &mut HashSet::new(), &mut files);
+
+        files
+    }
+
+    /// Adds the files which `event` can play to `files`, skipping the events
+    /// which were already `visited` so that cycles end.
+    fn collect_files(&self, event: &str, visited: &mut HashSet<String>, files: &mut Vec<PathBuf>) {
+        let event = event.strip_prefix("minecraft:").unwrap_or(event);
+        let Some(sound_event) = self.events.get(event) else {
+            return;
+        };
+        if !visited.insert(event.to_owned()) {
+            return;
+        }
+
+        for sound in &sound_event.sounds {
+            match sound {
+                Sound::Detailed {
+                    name,
+                    kind: SoundKind::Event,
+                } => self.collect_files(name, visited, files),
+                Sound::File(name) | Sound::Detailed { name, .. } => {
+                    let file = sound_file(name);
+                    if !files.contains(&file) {
+                        files.push(file);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the sound files played by any sound event, within `assets`,
+    /// sorted.
+    pub fn all_files(&self) -> BTreeSet<PathBuf> {
+        self.events
+            .values()
+            .flat_map(|event| &event.sounds)
+            .filter_map(|sound| match sound {
+                Sound::File(name)
+                | Sound::Detailed {
+                    name,
+                    kind: SoundKind::File,
+                } => Some(sound_file(name)),
+                Sound::Detailed {
+                    kind: SoundKind::Event,
+                    ..
+                } => None,
+            })
+            .collect()
+    }
+
+    /// Checks that the sound files played by every sound event are listed in
+    /// `index`, and that their hashed files exist in the `objects` directory
+    /// within `hashed_assets_dir`.
+    ///
+    /// Each event is only checked for the sounds it lists itself: sounds of
+    /// `"type": "event"` only need the event they name to be defined, as its
+    /// own sounds are checked with it.
+    ///
+    /// # Examples
+    /// ```
+    /// use extract_minecraft_assets::hashed::IndexFile;
+    /// use extract_minecraft_assets::sounds::{BrokenSound, SoundEvents};
+    /// use std::path::{Path, PathBuf};
+    ///
+    /// let events = SoundEvents::from_slice(br#"{
+    ///     "entity.zombie.ambient": {"sounds": ["mob/zombie/say1"]},
+    ///     "entity.zombie_villager.ambient": {"sounds": [{"name": "entity.zombie.ambient", "type": "event"}]}
+    /// }"#)?;
+    ///
+    /// let check = events.check_index(Path::new("assets"), &IndexFile::default());
+    ///
+    /// assert_eq!(check.broken.len(), 1);
+    /// assert_eq!(
+    ///     check.broken["entity.zombie.ambient"],
+    ///     [BrokenSound::NotInIndex { path: PathBuf::from("minecraft/sounds/mob/zombie/say1.ogg") }]
+    /// );
+    /// # Ok::<(), serde_json::Error>(())
+    /// ```
+    pub fn check_index(&self, hashed_assets_dir: &Path, index: &IndexFile) -> SoundCheck {
+        let objects_dir = hashed_assets_dir.join("objects");
+        // Whether each sound file is fine, so that files played by several
+        // events are only looked for once.
+        let mut checked: BTreeMap<PathBuf, Option<BrokenSound>> = BTreeMap::new();
+        let mut check = SoundCheck {
+            events: self.events.len(),
+            ..SoundCheck::default()
+        };
+
+        for (name, event) in &self.events {
+            let mut broken = Vec::new();
+
+            for sound in &event.sounds {
+                let problem = match sound {
+                    Sound::Detailed {
+                        name,
+                        kind: SoundKind::Event,
+                    } => {
+                        let event = name.strip_prefix("minecraft:").unwrap_or(name);
+                        (!self.events.contains_key(event)).then(|| BrokenSound::UnknownEvent {
+                            event: name.clone(),
+                        })
+                    }
+                    Sound::File(name) | Sound::Detailed { name, .. } => {
+                        let file = sound_file(name);
+                        checked
+                            .entry(file)
+                            .or_insert_with_key(|file| match index.get(file) {
+                                None => Some(BrokenSound::NotInIndex { path: file.clone() }),
+                                Some(entry) if !objects_dir.join(&entry.object_path).is_file() => {
+                                    Some(BrokenSound::MissingObject {
+                                        path: file.clone(),
+                                        hash: entry.hash.to_owned(),
+                                    })
+                                }
+                                Some(_) => None,
+                            })
+                            .clone()
+                    }
+                };
+
+                if let Some(problem) = problem
+                    && !broken.contains(&problem)
+                {
+                    broken.push(problem);
+                }
+            }
+
+            if !broken.is_empty() {
+                check.broken.insert(name.clone(), broken);
+            }
+        }
+        check.files = checked.len();
+
+        check
+    }
+
+    /// Writes the sound files extracted in `report` into
+    /// [`SOUNDS_BY_EVENT_DIR`] within the output directory of `options`, at
+    /// `<event>/<n>.ogg` for the `n`th file each sound event plays, e.g.
+    /// `sounds-by-event/entity.zombie.ambient/1.ogg`.
+    ///
+    /// Sound files played by several events are written once for each. Unless
+    /// `keep_originals` is set, each sound file is moved to the first of them,
+    /// and sound files which no event plays are left where they are.
+    pub fn organize(
+        &self,
+        report: &ExtractionReport,
+        options: &ExtractOptions,
+        links: SoundLinks,
+        keep_originals: bool,
+    ) -> Result<OrganizedSounds, ExtractError> {
+        let extracted = extracted_sound_files(report, options.ignore_top_level);
+        let mut organized = OrganizedSounds::default();
+        let mut missing = BTreeSet::new();
+
+        // The files to write for each extracted sound file, in order.
+        let mut targets: BTreeMap<&Path, Vec<PathBuf>> = BTreeMap::new();
+        for event in self.names() {
+            let event_dir = Path::new(SOUNDS_BY_EVENT_DIR).join(event);
+            let mut count = 0;
+
+            for file in self.files(event) {
+                let Some((file, _)) = extracted.get_key_value(&file) else {
+                    missing.insert(file);
+                    continue;
+                };
+
+                count += 1;
+                targets
+                    .entry(file)
+                    .or_default()
+                    .push(event_dir.join(format!("{count}.ogg")));
+            }
+
+            if count > 0 {
+                organized.events += 1;
+            }
+        }
+
+        let vfs = &*options.vfs;
+        for (file, targets) in &targets {
+            let mut original = options.output_dir.join(&extracted[*file]);
+
+            for (index, target) in targets.iter().enumerate() {
+                let target = options.output_dir.join(target);
+                if vfs.exists(&target) {
+                    if options.keeps_existing(&target) {
+                        continue;
+                    }
+                    vfs.remove_file(&target)
+                        .map_err(ExtractError::write_file(&target))?;
+                }
+
+                if let Some(parent) = target.parent() {
+                    vfs.create_dir_all(parent)
+                        .map_err(ExtractError::write_file(parent))?;
+                }
+
+                if index == 0 && !keep_originals {
+                    vfs.rename(&original, &target)
+                        .map_err(ExtractError::write_file(&target))?;
+                    original = target;
+                } else {
+                    match links {
+                        SoundLinks::Copy => vfs.copy(&original, &target).map(drop),
+                        SoundLinks::HardLink => vfs.hard_link(&original, &target),
+                    }
+                    .map_err(ExtractError::write_file(&target))?;
+                }
+
+                organized.files += 1;
+            }
+        }
+
+        organized.missing = missing.into_iter().collect();
+        organized.unreferenced = extracted
+            .iter()
+            .filter(|(file, _)| !targets.contains_key(file.as_path()))
+            .map(|(_, path)| path.clone())
+            .collect();
+
+        Ok(organized)
+    }
+}
+
+/// Returns the path within `assets` of the sound file named `name`, e.g.
+/// `minecraft/sounds/mob/zombie/say1.ogg` for `mob/zombie/say1`.
+fn sound_file(name: &str) -> PathBuf {
+    let (namespace, name) = name.split_once(':').unwrap_or(("minecraft", name));
+
+    Path::new(namespace)
+        .join("sounds")
+        .join(format!("{name}.ogg"))
+}
+
+/// Returns the sound files which were written or skipped in `report`, mapping
+/// their paths within `assets` to their paths relative to the output
+/// directory.
+fn extracted_sound_files(
+    report: &ExtractionReport,
+    ignore_top_level: bool,
+) -> BTreeMap<PathBuf, PathBuf> {
+    report
+        .files
+        .iter()
+        .filter(|file| !matches!(file.outcome, FileOutcome::Failed { .. }))
+        .filter_map(|file| {
+            let within_assets = if ignore_top_level {
+                &file.path
+            } else {
+                file.path.strip_prefix("assets").ok()?
+            };
+            let mut components = within_assets.components().skip(1);
+
+            let is_sound = components.next()?.as_os_str() == "sounds"
+                && within_assets
+                    .extension()
+                    .is_some_and(|extension| extension == "ogg");
+
+            is_sound.then(|| (within_assets.to_owned(), file.path.clone()))
+        })
+        .collect()
+}
@@ -0,0 +1,132 @@
+//! File counts and byte totals of a jar file or index file, grouped by
+//! namespace and by [`Category`], without extracting anything.
+
+use crate::category::{self, Category};
+use crate::error::ExtractError;
+use crate::hashed::IndexFile;
+use crate::jar::{self, ExtractedContents};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::ops;
+use std::path::Path;
+
+/// The number of files in a group, and their total size in bytes.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default, Serialize)]
+pub struct Total {
+    pub files: usize,
+    pub bytes: u64,
+}
+
+/// File counts and byte totals grouped by namespace and by [`Category`].
+///
+/// Files without a namespace, such as `pack.mcmeta`, are counted under an
+/// empty namespace.
+///
+/// # Examples
+/// ```
+/// use extract_minecraft_assets::category::Category;
+/// use extract_minecraft_assets::stats::{Stats, Total};
+///
+/// let mut stats = Stats::default();
+/// stats.add("minecraft/textures/block/stone.png".as_ref(), 300);
+/// stats.add("minecraft/lang/de_de.json".as_ref(), 100);
+/// stats.add("realms/textures/invite.png".as_ref(), 50);
+///
+/// assert_eq!(stats.total, Total { files: 3, bytes: 450 });
+/// assert_eq!(stats.namespaces["minecraft"], Total { files: 2, bytes: 400 });
+/// assert_eq!(stats.categories[&Category::Textures], Total { files: 2, bytes: 350 });
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Stats {
+    pub total: Total,
+    pub namespaces: BTreeMap<String, Total>,
+    pub categories: BTreeMap<Category, Total>,
+}
+
+impl Total {
+    pub(crate) fn add(&mut self, bytes: u64) {
+        self.files += 1;
+        self.bytes += bytes;
+    }
+}
+
+impl Stats {
+    /// Returns the stats of every object listed in `index`.
+    pub fn of_index(index: &IndexFile) -> Self {
+        let mut stats = Self::default();
+        for (path, object) in &index.objects {
+            stats.add(path, object.size);
+        }
+
+        stats
+    }
+
+    /// Returns the stats of the files within `jar_file` which are part of the
+    /// `extracted_contents`.
+    ///
+    /// Only the central directory is read: no entries are decompressed.
+    pub fn of_jar(
+        jar_file: &Path,
+        extracted_contents: &ExtractedContents,
+    ) -> Result<Self, ExtractError> {
+        let mut stats = Self::default();
+
+        for (path, size) in jar::list_files(jar_file, extracted_contents)? {
+            let root = extracted_contents
+                .root_of(&path)
+                .expect("listed files are part of a root");
+            stats.add(path.strip_prefix(&root.prefix).unwrap_or(&path), size);
+        }
+
+        Ok(stats)
+    }
+
+    /// Counts the file at `path`, relative to `assets` or `data`, of `bytes`
+    /// bytes.
+    pub fn add(&mut self, path: &Path, bytes: u64) {
+        let namespace = category::namespace(path).unwrap_or_default();
+
+        self.total.add(bytes);
+        self.namespaces
+            .entry(namespace.to_owned())
+            .or_default()
+            .add(bytes);
+        self.categories
+            .entry(Category::of(path))
+            .or_default()
+            .add(bytes);
+    }
+}
+
+impl ops::Add for Total {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            files: self.files + other.files,
+            bytes: self.bytes + other.bytes,
+        }
+    }
+}
+
+/// Combines the stats of two sources, e.g. a version's jar file and index
+/// file.
+impl ops::Add for Stats {
+    type Output = Self;
+
+    fn add(mut self, other: Self) -> Self {
+        self.total = self.total + other.total;
+
+        for (namespace, total) in other.namespaces {
+            let entry = self.namespaces.entry(namespace).or_default();
+            *entry = *entry + total;
+        }
+        for (category, total) in other.categories {
+            let entry = self.categories.entry(category).or_default();
+            *entry = *entry + total;
+        }
+
+        self
+    }
+}
@@ -0,0 +1,178 @@
+//! Mirroring an output directory to the source it was extracted from, by
+//! deleting the files which the source no longer has.
+
+use crate::diff;
+use crate::error::{ExtractError, IoContext};
+use crate::report::ExtractionReport;
+use crate::search::FileSource;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// The name of the file within the output directory recording which files
+/// synced extractions wrote, and where from.
+pub const RECORD_FILE: &str = ".extract-files";
+
+/// The files which an output directory has, but the source it was last
+/// extracted from doesn't, as found by [`plan`].
+///
+/// Only files within the directories the extraction wrote to (e.g. `assets`
+/// and `data`) are considered, so nothing else in the output directory is
+/// ever touched.
+#[derive(Clone, PartialEq, Eq, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncPlan {
+    /// The files which an earlier synced extraction from the same sources
+    /// wrote, but which the source no longer has, relative to the output
+    /// directory.
+    pub stale: Vec<PathBuf>,
+    /// The files which no synced extraction wrote, e.g. ones added by the
+    /// user, relative to the output directory.
+    pub unknown: Vec<PathBuf>,
+    /// The directories the extraction wrote to, which are kept even once
+    /// empty.
+    #[serde(skip)]
+    roots: Vec<PathBuf>,
+    /// The record to write once the plan is carried out.
+    #[serde(skip)]
+    record: BTreeMap<PathBuf, FileSource>,
+}
+
+/// Finds the files in the output directory of `report` which the sources
+/// extracted from no longer have.
+///
+/// `sources` are the kinds of sources the extraction covered in full, e.g.
+/// only [`FileSource::Hashed`] for an extraction of hashed assets alone, so
+/// that the files of a jar file extracted into the same directory aren't
+/// mistaken for stale ones. The output directory is read from the real
+/// filesystem.
+///
+/// Nothing is found if the extraction [stopped](crate::progress::Summary::stopped)
+/// early, as the files it didn't get to would look stale.
+pub fn plan(report: &ExtractionReport, sources: &[FileSource]) -> Result<SyncPlan, ExtractError> {
+    let output_dir = &report.options.output_dir;
+    let record_path = output_dir.join(RECORD_FILE);
+    let mut record: BTreeMap<PathBuf, FileSource> = match fs::read(&record_path) {
+        Ok(contents) => serde_json::from_slice(&contents)
+            .map_err(io::Error::from)
+            .reading_output(&record_path)?,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => BTreeMap::new(),
+        Err(error) => return Err(ExtractError::read_output(record_path)(error)),
+    };
+
+    if report.summary.stopped() {
+        return Ok(SyncPlan {
+            record,
+            ..SyncPlan::default()
+        });
+    }
+
+    let roots = managed_roots(report);
+
+    let mut files = BTreeSet::new();
+    for root in &roots {
+        diff::find_files(output_dir, root, &mut files)?;
+    }
+    files.remove(Path::new(RECORD_FILE));
+
+    let extracted: BTreeMap<_, _> = report
+        .files
+        .iter()
+        .map(|file| (file.path.clone(), file.source))
+        .collect();
+
+    let mut plan = SyncPlan {
+        roots,
+        ..SyncPlan::default()
+    };
+    for path in files
+        .into_iter()
+        .filter(|path| !extracted.contains_key(path))
+    {
+        match record.get(&path) {
+            Some(source) if sources.contains(source) => plan.stale.push(path),
+            Some(_) => {}
+            None => plan.unknown.push(path),
+        }
+    }
+
+    // Only the files of the covered sources in the directories written to
+    // are replaced by this extraction's files.
+    record.retain(|path, source| {
+        !(sources.contains(source) && plan.roots.iter().any(|root| path.starts_with(root)))
+    });
+    record.extend(extracted);
+    plan.record = record;
+
+    Ok(plan)
+}
+
+impl SyncPlan {
+    /// Returns the files to delete, leaving out the
+    /// [unknown](SyncPlan::unknown) files unless `delete_unknown` is set.
+    pub fn deleted(&self, delete_unknown: bool) -> impl Iterator<Item = &Path> {
+        let unknown = if delete_unknown {
+            &self.unknown[..]
+        } else {
+            &[]
+        };
+
+        self.stale.iter().chain(unknown).map(PathBuf::as_path)
+    }
+
+    /// Deletes the [stale](SyncPlan::stale) files, and the
+    /// [unknown](SyncPlan::unknown) ones too if `delete_unknown` is set, from
+    /// `output_dir`, along with the directories left empty. Returns the
+    /// number of files deleted.
+    ///
+    /// The files extracted are then recorded in [`RECORD_FILE`], so that the
+    /// next synced extraction knows them.
+    pub fn apply(&self, output_dir: &Path, delete_unknown: bool) -> Result<usize, ExtractError> {
+        let mut deleted = 0;
+
+        for path in self.deleted(delete_unknown) {
+            let full_path = output_dir.join(path);
+            match fs::remove_file(&full_path) {
+                Ok(()) => deleted += 1,
+                Err(error) if error.kind() == io::ErrorKind::NotFound => {}
+                Err(error) => return Err(ExtractError::write_file(full_path)(error)),
+            }
+
+            // Directories which still have something in them fail to be
+            // removed, stopping at the first.
+            for dir in path.ancestors().skip(1) {
+                if self.roots.iter().any(|root| root == dir)
+                    || fs::remove_dir(output_dir.join(dir)).is_err()
+                {
+                    break;
+                }
+            }
+        }
+
+        let record_path = output_dir.join(RECORD_FILE);
+        let contents = serde_json::to_vec_pretty(&self.record).map_err(io::Error::from);
+        contents
+            .and_then(|contents| fs::write(&record_path, contents))
+            .writing(&record_path)?;
+
+        Ok(deleted)
+    }
+}
+
+/// Returns the directories within the output directory which the extraction
+/// of `report` wrote its files into, e.g. `assets` and `data`.
+fn managed_roots(report: &ExtractionReport) -> Vec<PathBuf> {
+    let options = &report.options;
+    if options.ignore_top_level || options.group_by.is_some() {
+        return vec![PathBuf::new()];
+    }
+
+    options
+        .contents
+        .roots()
+        .iter()
+        .map(|root| root.output_dir.clone())
+        .collect()
+}
@@ -0,0 +1,80 @@
+//! Normalizing the line endings of text files, e.g. so that extracted
+//! splashes, credits and `.lang` files don't churn diffs on checkouts with
+//! other line endings.
+
+use serde::Serialize;
+use std::path::Path;
+
+/// The extensions of the files which are normalized as text.
+///
+/// Only files with these extensions are normalized, so that binary files are
+/// never changed.
+pub const TEXT_EXTENSIONS: &[&str] = &["txt", "lang", "json", "mcmeta", "properties", "vsh", "fsh"];
+
+/// The line endings to which to normalize text files.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LineEnding {
+    /// `\n`, as used on Linux and macOS.
+    Lf,
+    /// `\r\n`, as used on Windows.
+    Crlf,
+}
+
+impl LineEnding {
+    /// The bytes ending each line.
+    pub fn as_bytes(self) -> &'static [u8] {
+        match self {
+            Self::Lf => b"\n",
+            Self::Crlf => b"\r\n",
+        }
+    }
+}
+
+/// Whether the file at `path` is a text file which can be normalized, i.e.
+/// has one of the [`TEXT_EXTENSIONS`].
+pub fn is_text(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| TEXT_EXTENSIONS.contains(&extension))
+}
+
+/// Normalizes the `contents` of a text file to end each line with
+/// `line_ending`, stripping the UTF-8 byte order mark if there is one.
+///
+/// `\r\n`, `\n` and lone `\r` are each treated as the end of a line.
+///
+/// Returns [`None`] if `contents` doesn't look like text, i.e. isn't UTF-8 or
+/// contains a null byte, in which case it should be kept as it is.
+///
+/// # Examples
+/// ```
+/// use extract_minecraft_assets::text::{self, LineEnding};
+///
+/// let normalized = text::normalize(b"\xEF\xBB\xBFFirst\r\nSecond\n", LineEnding::Lf);
+///
+/// assert_eq!(normalized.as_deref(), Some(&b"First\nSecond\n"[..]));
+/// assert_eq!(text::normalize(b"PNG\0\r\n", LineEnding::Lf), None);
+/// ```
+pub fn normalize(contents: &[u8], line_ending: LineEnding) -> Option<Vec<u8>> {
+    let contents = contents.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(contents);
+    if contents.contains(&0) || str::from_utf8(contents).is_err() {
+        return None;
+    }
+
+    let mut output = Vec::with_capacity(contents.len());
+    let mut bytes = contents.iter().copied().peekable();
+
+    while let Some(byte) = bytes.next() {
+        match byte {
+            b'\r' => {
+                bytes.next_if_eq(&b'\n');
+                output.extend_from_slice(line_ending.as_bytes());
+            }
+            b'\n' => output.extend_from_slice(line_ending.as_bytes()),
+            _ => output.push(byte),
+        }
+    }
+
+    Some(output)
+}
@@ -0,0 +1,185 @@
+//! Transforming files while extracting them, as set by [`ExtractOptions`]:
+//! converting language and NBT files, formatting JSON files and normalizing
+//! the line endings of text files.
+
+use crate::format::{self, JsonFormat};
+use crate::lang::{self, LangFormat};
+use crate::nbt::{self, NbtFormat};
+use crate::options::ExtractOptions;
+use crate::text::{self, LineEnding};
+use std::path::{Path, PathBuf};
+
+/// How a file is transformed while extracting it.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) struct Transform {
+    /// How the file's contents are converted, if they are.
+    conversion: Option<Conversion>,
+    /// The line endings to which the file is then normalized, as set by
+    /// [`text_normalize`](ExtractOptions::text_normalize), if it is a text
+    /// file.
+    line_ending: Option<LineEnding>,
+}
+
+/// How a file's contents are converted while extracting it.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Conversion {
+    /// Converting a language file to this format, as set by
+    /// [`convert_lang`](ExtractOptions::convert_lang).
+    ConvertLang(LangFormat),
+    /// Converting an NBT file of `data` to this format, as set by
+    /// [`convert_nbt`](ExtractOptions::convert_nbt).
+    ConvertNbt(NbtFormat),
+    /// Formatting a JSON file as this format, as set by
+    /// [`format_json`](ExtractOptions::format_json).
+    FormatJson(JsonFormat),
+}
+
+/// The files to write in place of a transformed file, as returned by
+/// [`Transform::apply`].
+pub(crate) struct Transformed {
+    /// The paths and contents of the files, relative to the output directory.
+    pub files: Vec<(PathBuf, Vec<u8>)>,
+    /// Why the file couldn't be transformed, if it couldn't.
+    pub warning: Option<String>,
+    /// The number of bytes fewer than the original the file was written with,
+    /// if it was [minified](JsonFormat::Minify).
+    pub bytes_saved: u64,
+    /// The number of files whose line endings or byte order mark were
+    /// changed by [normalizing](text::normalize) them.
+    pub normalized: usize,
+}
+
+impl Transform {
+    /// Returns how the file at `path`, relative to the jar's top-level
+    /// directory (e.g. `assets/minecraft/lang/en_us.lang`), of `size` bytes is
+    /// transformed with `options`, if it is.
+    ///
+    /// Language files in the other format and NBT files in `data` are
+    /// converted, and other JSON files are formatted unless they are larger
+    /// than the [maximum size](ExtractOptions::format_json_max_size). Text
+    /// files are then normalized.
+    pub(crate) fn of(options: &ExtractOptions, path: &Path, size: u64) -> Option<Self> {
+        let conversion = options.convert_lang.filter(|&format| {
+            path.strip_prefix("assets")
+                .ok()
+                .and_then(LangFormat::of)
+                .is_some_and(|of| of != format)
+        });
+        let conversion = match conversion {
+            Some(format) => Some(Conversion::ConvertLang(format)),
+            None if path.starts_with("data") && nbt::is_nbt(path) => {
+                options.convert_nbt.map(Conversion::ConvertNbt)
+            }
+            None => options
+                .format_json
+                .filter(|_| format::is_json(path) && size <= options.format_json_max_size)
+                .map(Conversion::FormatJson),
+        };
+        let line_ending = options.text_normalize.filter(|_| text::is_text(path));
+
+        (conversion.is_some() || line_ending.is_some()).then_some(Self {
+            conversion,
+            line_ending,
+        })
+    }
+
+    /// Returns the path to which a file which would be extracted to `path` is
+    /// written once transformed, e.g. with a new extension.
+    pub(crate) fn output_path(self, path: &Path) -> PathBuf {
+        match self.conversion {
+            Some(conversion) => conversion.output_path(path),
+            None => path.to_owned(),
+        }
+    }
+
+    /// Transforms the `contents` of the file being extracted to `path`,
+    /// relative to the output directory.
+    ///
+    /// A converted language or NBT file replaces the original, unless
+    /// `keep_original` is set. Each of the files is then normalized. If the file can't be
+    /// converted or normalized, it is written as it is, with a warning.
+    pub(crate) fn apply(self, path: &Path, contents: Vec<u8>, keep_original: bool) -> Transformed {
+        let mut transformed = match self.conversion {
+            Some(conversion) => conversion.apply(path, contents, keep_original),
+            None => Transformed {
+                files: vec![(path.to_owned(), contents)],
+                warning: None,
+                bytes_saved: 0,
+                normalized: 0,
+            },
+        };
+
+        if let Some(line_ending) = self.line_ending {
+            for (_, contents) in &mut transformed.files {
+                match text::normalize(contents, line_ending) {
+                    Some(normalized) if normalized != *contents => {
+                        *contents = normalized;
+                        transformed.normalized += 1;
+                    }
+                    Some(_) => {}
+                    None => {
+                        transformed.warning.get_or_insert_with(|| {
+                            "couldn't normalize line endings, as it doesn't look like text"
+                                .to_owned()
+                        });
+                    }
+                }
+            }
+        }
+
+        transformed
+    }
+}
+
+impl Conversion {
+    /// Returns the path to which a file which would be extracted to `path` is
+    /// written once converted.
+    fn output_path(self, path: &Path) -> PathBuf {
+        match self {
+            Self::ConvertLang(format) => path.with_extension(format.extension()),
+            Self::ConvertNbt(format) => path.with_extension(format.extension()),
+            Self::FormatJson(_) => path.to_owned(),
+        }
+    }
+
+    /// Converts the `contents` of the file being extracted to `path`, as by
+    /// [`Transform::apply`].
+    fn apply(self, path: &Path, contents: Vec<u8>, keep_original: bool) -> Transformed {
+        let result = match self {
+            Self::ConvertLang(format) => lang::convert(&contents, format)
+                .map_err(|error| format!("couldn't convert to {format}: {error}")),
+            Self::ConvertNbt(format) => nbt::convert(&contents, format)
+                .map_err(|error| format!("couldn't convert to {format}: {error}")),
+            Self::FormatJson(format) => format::format_json(&contents, format)
+                .map_err(|error| format!("couldn't format invalid JSON: {error}")),
+        };
+
+        match result {
+            Ok(converted) => {
+                let bytes_saved = match self {
+                    Self::FormatJson(JsonFormat::Minify) => {
+                        contents.len().saturating_sub(converted.len()) as u64
+                    }
+                    _ => 0,
+                };
+                let mut files = vec![(self.output_path(path), converted)];
+                if keep_original && matches!(self, Self::ConvertLang(_) | Self::ConvertNbt(_)) {
+                    files.push((path.to_owned(), contents));
+                }
+
+                Transformed {
+                    files,
+                    warning: None,
+                    bytes_saved,
+                    normalized: 0,
+                }
+            }
+            Err(warning) => Transformed {
+                files: vec![(path.to_owned(), contents)],
+                warning: Some(warning),
+                bytes_saved: 0,
+                normalized: 0,
+            },
+        }
+    }
+}
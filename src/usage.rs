@@ -0,0 +1,288 @@
+//! Estimating how many files and bytes an extraction would write, without
+//! extracting anything.
+
+use crate::error::ExtractError;
+use crate::hashed::IndexFile;
+use crate::jar;
+use crate::options::ExtractOptions;
+use crate::search::FileSource;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::ops;
+use std::path::{Path, PathBuf};
+
+/// The block size assumed when that of the output volume can't be
+/// determined, which is the block size of most filesystems.
+pub const DEFAULT_BLOCK_SIZE: u64 = 4096;
+
+/// Collects the files an extraction would write, to estimate its
+/// [disk usage](DiskUsage).
+///
+/// Files are keyed by the paths they would be written to relative to the
+/// output directory, taking the top-level directory, namespaces and decoded
+/// paths of the [options](ExtractOptions) into account. A file added at the
+/// same path as an earlier one replaces it, as hashed assets replace those of
+/// the jar file when a version is extracted.
+///
+/// Sizes are read from index files and the central directories of jar files,
+/// so nothing is decompressed. Files converted while extracting, e.g. with
+/// [`format_json`](ExtractOptions::format_json), are counted at their
+/// original size.
+///
+/// # Examples
+/// ```
+/// use extract_minecraft_assets::search::FileSource;
+/// use extract_minecraft_assets::usage::UsageEstimate;
+///
+/// let mut estimate = UsageEstimate::new();
+/// estimate.add("assets/minecraft/lang/de_de.json".into(), 5000, FileSource::Jar);
+/// estimate.add("assets/minecraft/lang/de_de.json".into(), 6000, FileSource::Hashed);
+/// estimate.add("data/minecraft/recipe/stick.json".into(), 100, FileSource::Jar);
+///
+/// let usage = estimate.usage(Some(4096));
+///
+/// assert_eq!(usage.total.files, 2);
+/// assert_eq!(usage.total.bytes, 6100);
+/// assert_eq!(usage.total.allocated, Some(12288));
+/// assert_eq!(usage.directories["data"].bytes, 100);
+/// assert_eq!(usage.sources[&FileSource::Hashed].files, 1);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct UsageEstimate {
+    files: BTreeMap<PathBuf, (u64, FileSource)>,
+}
+
+/// The estimated disk usage of an extraction, in total, by source and by
+/// top-level directory.
+#[derive(Clone, PartialEq, Eq, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiskUsage {
+    pub total: Usage,
+    pub sources: BTreeMap<FileSource, Usage>,
+    /// The usage of each directory directly within the output directory,
+    /// e.g. `assets`, with files directly within the output directory under
+    /// an empty name.
+    pub directories: BTreeMap<String, Usage>,
+    /// The block size which the [allocated](Usage::allocated) sizes are
+    /// rounded to, if estimated.
+    pub block_size: Option<u64>,
+}
+
+/// A file or directory of the layout an extraction would create within the
+/// output directory, as returned by [`UsageEstimate::tree`].
+#[derive(Clone, PartialEq, Eq, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TreeNode {
+    /// The name of the file or directory, which is empty for the output
+    /// directory itself.
+    pub name: String,
+    pub is_dir: bool,
+    /// The number of files within the directory and its subdirectories, or 1
+    /// for a file.
+    pub files: usize,
+    /// The total size of the files in bytes.
+    pub bytes: u64,
+    /// The files and directories directly within the directory, sorted by
+    /// name. Empty for files, and for directories at the maximum depth.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<TreeNode>,
+}
+
+/// The number of files in a group, and their total size in bytes.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default, Serialize)]
+pub struct Usage {
+    pub files: usize,
+    pub bytes: u64,
+    /// The space the files would take up on disk, with each file rounded up
+    /// to a whole number of blocks, if estimated.
+    pub allocated: Option<u64>,
+}
+
+impl UsageEstimate {
+    /// Returns an estimate without any files.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the file extracted to `path`, relative to the output directory,
+    /// of `size` bytes, replacing any file at the same path.
+    pub fn add(&mut self, path: PathBuf, size: u64, source: FileSource) {
+        self.files.insert(path, (size, source));
+    }
+
+    /// Adds the files within `jar_file` which are part of the
+    /// [contents](ExtractOptions::contents) of `options` and for which
+    /// `filter` returns true, like [`jar::extract_jar_filtered`].
+    pub fn add_jar(
+        &mut self,
+        jar_file: &Path,
+        options: &ExtractOptions,
+        filter: impl Fn(&Path) -> bool,
+    ) -> Result<(), ExtractError> {
+        let contents = &options.contents;
+
+        for (path, size) in jar::list_files(jar_file, contents)? {
+            let Some(root) = contents
+                .root_of(&path)
+                .filter(|_| filter(&path) && options.includes(&path))
+            else {
+                continue;
+            };
+            let output_path = root.output_path(&path, options.ignore_top_level);
+
+            self.add(
+                options.relative_output_path(output_path, false),
+                size,
+                FileSource::Jar,
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Adds every object listed in `index`, like
+    /// [`hashed::extract_hashed_assets`](crate::hashed::extract_hashed_assets).
+    pub fn add_index(&mut self, index: &IndexFile, options: &ExtractOptions) {
+        let assets_dir = if options.ignore_top_level {
+            PathBuf::new()
+        } else {
+            PathBuf::from("assets")
+        };
+
+        for (path, object) in &options.included_objects(index).objects {
+            let output_path = assets_dir.join(options.index_path(path));
+
+            self.add(
+                options.relative_output_path(output_path, false),
+                object.size,
+                FileSource::Hashed,
+            );
+        }
+    }
+
+    /// Returns the disk usage of the files, along with the space they would
+    /// take up on disk in blocks of `block_size` bytes, if given.
+    pub fn usage(&self, block_size: Option<u64>) -> DiskUsage {
+        let mut usage = DiskUsage {
+            block_size,
+            ..DiskUsage::default()
+        };
+
+        for (path, &(size, source)) in &self.files {
+            let file = Usage {
+                files: 1,
+                bytes: size,
+                allocated: block_size.map(|block_size| size.next_multiple_of(block_size.max(1))),
+            };
+            // Files directly within the output directory have no directory.
+            let directory = match path.parent() {
+                Some(parent) if parent != Path::new("") => path
+                    .components()
+                    .next()
+                    .map(|component| component.as_os_str().to_string_lossy().into_owned())
+                    .unwrap_or_default(),
+                _ => String::new(),
+            };
+
+            usage.total = usage.total + file;
+            let entry = usage.sources.entry(source).or_default();
+            *entry = *entry + file;
+            let entry = usage.directories.entry(directory).or_default();
+            *entry = *entry + file;
+        }
+
+        usage
+    }
+
+    /// Returns the layout of the files within the output directory, with the
+    /// files and directories nested at most `max_depth` levels deep.
+    ///
+    /// Directories at the maximum depth have no children, but still count the
+    /// files within them.
+    ///
+    /// # Examples
+    /// ```
+    /// use extract_minecraft_assets::search::FileSource;
+    /// use extract_minecraft_assets::usage::UsageEstimate;
+    ///
+    /// let mut estimate = UsageEstimate::new();
+    /// estimate.add("assets/minecraft/lang/de_de.json".into(), 5000, FileSource::Hashed);
+    /// estimate.add("assets/minecraft/lang/fr_fr.json".into(), 6000, FileSource::Hashed);
+    /// estimate.add("pack.mcmeta".into(), 100, FileSource::Jar);
+    ///
+    /// let tree = estimate.tree(Some(2));
+    ///
+    /// assert_eq!((tree.files, tree.bytes), (3, 11100));
+    /// let minecraft = &tree.children[0].children[0];
+    /// assert_eq!((minecraft.name.as_str(), minecraft.files), ("minecraft", 2));
+    /// assert!(minecraft.children.is_empty());
+    /// assert!(!tree.children[1].is_dir);
+    /// ```
+    pub fn tree(&self, max_depth: Option<usize>) -> TreeNode {
+        let mut root = TreeNode {
+            is_dir: true,
+            ..TreeNode::default()
+        };
+
+        for (path, &(size, _)) in &self.files {
+            let names: Vec<_> = path
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy().into_owned())
+                .collect();
+            root.add(&names, size, max_depth);
+        }
+
+        root
+    }
+}
+
+impl TreeNode {
+    /// Counts the file of `size` bytes at the path made of `names` within the
+    /// directory, adding the files and directories along the path if they are
+    /// less than `depth_left` levels deep.
+    fn add(&mut self, names: &[String], size: u64, depth_left: Option<usize>) {
+        self.files += 1;
+        self.bytes += size;
+
+        let Some((name, rest)) = names.split_first() else {
+            return;
+        };
+        if depth_left == Some(0) {
+            return;
+        }
+
+        let index = match self
+            .children
+            .binary_search_by(|child| child.name.as_str().cmp(name))
+        {
+            Ok(index) => index,
+            Err(index) => {
+                self.children.insert(
+                    index,
+                    TreeNode {
+                        name: name.clone(),
+                        is_dir: !rest.is_empty(),
+                        ..TreeNode::default()
+                    },
+                );
+                index
+            }
+        };
+        self.children[index].add(rest, size, depth_left.map(|depth| depth - 1));
+    }
+}
+
+impl ops::Add for Usage {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            files: self.files + other.files,
+            bytes: self.bytes + other.bytes,
+            allocated: match (self.allocated, other.allocated) {
+                (Some(a), Some(b)) => Some(a + b),
+                (allocated, None) | (None, allocated) => allocated,
+            },
+        }
+    }
+}
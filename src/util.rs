@@ -1,4 +1,10 @@
-use std::path::PathBuf;
+use crate::error::ExtractError;
+use sha1::{Digest, Sha1};
+use std::borrow::Cow;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+use std::time::Duration;
+use std::{env, fs, io, thread};
 
 pub trait OptionExt<T> {
     /// Calls a function with a mutable reference to the contained value if [`Some`].
@@ -9,11 +15,12 @@ pub trait OptionExt<T> {
     ///
     /// # Examples
     /// ```
-    /// let list = vec![1, 2, 3];
+    /// use extract_minecraft_assets::util::OptionExt;
+    /// use std::path::PathBuf;
     ///
-    /// list.get(2).inspect_mut(|element| element += 1);
+    /// let path = Some(PathBuf::from(".minecraft")).inspect_mut(|path| path.push("assets"));
     ///
-    /// assert_eq!(list.get(2), Some(4));
+    /// assert_eq!(path, Some(PathBuf::from(".minecraft/assets")));
     /// ```
     fn inspect_mut(self, f: impl FnOnce(&mut T)) -> Self;
 }
@@ -29,38 +36,718 @@ impl<T> OptionExt<T> for Option<T> {
 }
 
 // Windows
-/// Returns the default location of the `.minecraft` directory.
-#[cfg(target_os = "windows")]
-pub fn minecraft_dir() -> Option<PathBuf> {
+/// Returns every location probed for the `.minecraft` directory, in order of
+/// preference.
+#[cfg(all(feature = "discovery", target_os = "windows"))]
+pub fn minecraft_dir_candidates() -> Vec<PathBuf> {
     dirs::data_dir()
         .inspect_mut(|path| path.push(".minecraft"))
-        .filter(|path| path.is_dir())
+        .into_iter()
+        .collect()
 }
 
 // Mac
-/// Returns the default location of the `minecraft` directory.
-#[cfg(target_os = "macos")]
-pub fn minecraft_dir() -> Option<PathBuf> {
+/// Returns every location probed for the `minecraft` directory, in order of
+/// preference.
+#[cfg(all(feature = "discovery", target_os = "macos"))]
+pub fn minecraft_dir_candidates() -> Vec<PathBuf> {
     dirs::data_dir()
         .inspect_mut(|path| path.push("minecraft"))
-        .filter(|path| path.is_dir())
+        .into_iter()
+        .collect()
 }
 
 // Linux
-/// Returns the default location of the `.minecraft` directory.
-#[cfg(not(any(target_os = "windows", target_os = "macos")))]
-pub fn minecraft_dir() -> Option<PathBuf> {
+/// Returns every location probed for the `.minecraft` directory, in order of
+/// preference.
+#[cfg(all(feature = "discovery", not(any(target_os = "windows", target_os = "macos"))))]
+pub fn minecraft_dir_candidates() -> Vec<PathBuf> {
     dirs::home_dir()
         .inspect_mut(|path| path.push(".minecraft"))
-        .filter(|path| path.is_dir())
+        .into_iter()
+        .collect()
+}
+
+/// Returns the default location of the `.minecraft` directory.
+///
+/// This is the first of the [candidates](minecraft_dir_candidates) which exists.
+///
+/// # Examples
+/// ```
+/// use extract_minecraft_assets::util;
+///
+/// match util::minecraft_dir() {
+///     Some(dir) => println!("Found Minecraft at {}", dir.display()),
+///     None => println!("Minecraft is not installed"),
+/// }
+/// ```
+///
+/// The directory is canonicalized, so that a `.minecraft` which is a symlink
+/// (e.g. onto a bigger drive) is only resolved once.
+#[cfg(feature = "discovery")]
+pub fn minecraft_dir() -> Option<PathBuf> {
+    minecraft_dir_candidates()
+        .into_iter()
+        .find(|path| is_dir(path))
+        .map(|path| fs::canonicalize(&path).unwrap_or(path))
 }
 
 /// Returns the default location of the `.minecraft/assets/` directory.
+///
+/// This is [`None`] if the [`.minecraft` directory](minecraft_dir) was not found.
+#[cfg(feature = "discovery")]
 pub fn hashed_assets_dir() -> Option<PathBuf> {
     minecraft_dir().inspect_mut(|path| path.push("assets"))
 }
 
 /// Returns the default location of the `.minecraft/versions/` directory.
+///
+/// This is [`None`] if the [`.minecraft` directory](minecraft_dir) was not found.
+#[cfg(feature = "discovery")]
 pub fn versions_dir() -> Option<PathBuf> {
     minecraft_dir().inspect_mut(|path| path.push("versions"))
 }
+
+/// How long to wait before looking through a symlink which couldn't be
+/// followed again.
+const SYMLINK_RETRY_DELAY: Duration = Duration::from_millis(200);
+
+/// Whether `path` is a directory, following symlinks in any of its
+/// components.
+///
+/// If `path` isn't a directory but goes through a symlink, it is looked at
+/// again after a short delay, as a symlink onto a network filesystem can be
+/// broken while the filesystem is automounted.
+pub fn is_dir(path: &Path) -> bool {
+    if path.is_dir() {
+        return true;
+    }
+
+    let through_symlink = path.ancestors().any(|ancestor| {
+        fs::symlink_metadata(ancestor).is_ok_and(|metadata| metadata.file_type().is_symlink())
+    });
+    if !through_symlink {
+        return false;
+    }
+
+    thread::sleep(SYMLINK_RETRY_DELAY);
+    path.is_dir()
+}
+
+/// Formats `path` for a message, along with what it resolves to if that is
+/// elsewhere because of a symlink, e.g.
+/// `'/home/me/.minecraft/assets' (resolved to '/mnt/games/minecraft/assets')`.
+///
+/// A broken symlink along the way is named along with its target instead.
+///
+/// # Examples
+/// ```
+/// use extract_minecraft_assets::util;
+/// use std::path::Path;
+///
+/// assert_eq!(util::display_resolved(Path::new("/")), "'/'");
+/// ```
+pub fn display_resolved(path: &Path) -> String {
+    let absolute = match env::current_dir() {
+        Ok(current_dir) => current_dir.join(path),
+        Err(_) => path.to_owned(),
+    };
+
+    let broken = absolute.ancestors().find_map(|ancestor| {
+        let metadata = fs::symlink_metadata(ancestor).ok()?;
+        if !metadata.file_type().is_symlink() || ancestor.exists() {
+            return None;
+        }
+
+        Some((ancestor, fs::read_link(ancestor).ok()?))
+    });
+    if let Some((link, target)) = broken {
+        return format!(
+            "'{}' ('{}' is a broken symlink to '{}')",
+            path.display(),
+            link.display(),
+            target.display()
+        );
+    }
+
+    match canonicalize_lenient(&absolute) {
+        Ok(resolved) if resolved != absolute => {
+            format!(
+                "'{}' (resolved to '{}')",
+                path.display(),
+                resolved.display()
+            )
+        }
+        _ => format!("'{}'", path.display()),
+    }
+}
+
+/// Canonicalizes `path`, tolerating components which don't exist yet.
+///
+/// The nearest existing ancestor of `path` is canonicalized, and the remaining
+/// components are appended to it unchanged.
+pub fn canonicalize_lenient(path: &Path) -> io::Result<PathBuf> {
+    let path = if path.is_absolute() {
+        path.to_owned()
+    } else {
+        env::current_dir()?.join(path)
+    };
+
+    for ancestor in path.ancestors() {
+        if let Ok(canonical) = ancestor.canonicalize() {
+            return Ok(match path.strip_prefix(ancestor) {
+                Ok(remainder) if !remainder.as_os_str().is_empty() => canonical.join(remainder),
+                _ => canonical,
+            });
+        }
+    }
+
+    Ok(path)
+}
+
+/// Returns the first of `dirs` which `path` is inside of (or equal to), along
+/// with the canonicalized forms of both.
+///
+/// Both `path` and `dirs` are [canonicalized leniently](canonicalize_lenient).
+/// Fails with [`ExtractError::ResolvePath`] if any of them can't be.
+pub fn find_enclosing_dir(
+    path: &Path,
+    dirs: impl IntoIterator<Item = PathBuf>,
+) -> Result<Option<(PathBuf, PathBuf)>, ExtractError> {
+    let resolve = |path: &Path| {
+        canonicalize_lenient(path).map_err(|source| ExtractError::ResolvePath {
+            path: path.to_owned(),
+            source,
+        })
+    };
+    let path = resolve(path)?;
+
+    for dir in dirs {
+        let dir = resolve(&dir)?;
+
+        if path.starts_with(&dir) {
+            return Ok(Some((path, dir)));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Whether `source` is the URL of a remote file rather than a path, i.e.
+/// starts with `http://` or `https://`.
+///
+/// # Examples
+/// ```
+/// use extract_minecraft_assets::util;
+///
+/// assert!(util::is_url("https://example.com/client.jar"));
+/// assert!(!util::is_url("versions/1.20.1/1.20.1.jar"));
+/// ```
+pub fn is_url(source: &str) -> bool {
+    source.starts_with("http://") || source.starts_with("https://")
+}
+
+/// Decodes percent-encoded sequences such as `%20` in each component of
+/// `path`, as found in the virtual paths of some index files.
+///
+/// A component is kept as it is if decoding it wouldn't give valid UTF-8, or
+/// would give a path separator or a `.` or `..` component, so that a decoded
+/// path never leaves the directory it is extracted into. Invalid sequences
+/// such as `100%` are kept as they are too.
+///
+/// # Examples
+/// ```
+/// use extract_minecraft_assets::util::decode_path;
+/// use std::path::Path;
+///
+/// assert_eq!(
+///     decode_path(Path::new("minecraft/sounds/music/calm%201.ogg")),
+///     Path::new("minecraft/sounds/music/calm 1.ogg")
+/// );
+/// assert_eq!(decode_path(Path::new("minecraft/%2E%2E/x")), Path::new("minecraft/%2E%2E/x"));
+/// ```
+pub fn decode_path(path: &Path) -> Cow<'_, Path> {
+    if !path.as_os_str().as_encoded_bytes().contains(&b'%') {
+        return Cow::Borrowed(path);
+    }
+
+    let decoded = path
+        .components()
+        .map(|component| match component {
+            Component::Normal(name) => match name.to_str().and_then(decode_component) {
+                Some(decoded) => PathBuf::from(decoded),
+                None => PathBuf::from(name),
+            },
+            component => PathBuf::from(component.as_os_str()),
+        })
+        .collect();
+
+    Cow::Owned(decoded)
+}
+
+/// Decodes the percent-encoded sequences in `name`, a single path component,
+/// unless the result wouldn't be a single normal component.
+fn decode_component(name: &str) -> Option<String> {
+    let bytes = name.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let byte = bytes
+            .get(i + 1..i + 3)
+            .filter(|hex| bytes[i] == b'%' && hex.iter().all(u8::is_ascii_hexdigit))
+            .and_then(|hex| u8::from_str_radix(str::from_utf8(hex).ok()?, 16).ok());
+
+        match byte {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+
+    let decoded = String::from_utf8(decoded).ok()?;
+    let is_normal = !matches!(decoded.as_str(), "" | "." | "..")
+        && !decoded.contains(['/', '\\', '\0'])
+        && !decoded.contains(std::path::MAIN_SEPARATOR);
+
+    is_normal.then_some(decoded)
+}
+
+/// Checks that files can be created in `dir`, by creating an empty probe file
+/// in it and removing it again.
+///
+/// This catches read-only mounts and directories without write permission
+/// before anything is extracted, rather than failing on every file.
+pub fn probe_writable(dir: &Path) -> io::Result<()> {
+    let probe = dir.join(format!(
+        ".extract-minecraft-assets-probe-{}",
+        std::process::id()
+    ));
+
+    fs::File::create_new(&probe)?;
+    fs::remove_file(&probe)
+}
+
+/// Returns the number of bytes available to unprivileged users on the volume
+/// containing `path`, if it can be determined.
+#[cfg(unix)]
+pub fn free_space(path: &Path) -> Option<u64> {
+    use std::ffi::CString;
+    use std::mem::MaybeUninit;
+    use std::os::unix::ffi::OsStrExt;
+
+    let path = CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut stat = MaybeUninit::<libc::statvfs>::uninit();
+
+    // SAFETY: `path` is a valid C string and `stat` is a valid pointer to a `statvfs`.
+    if unsafe { libc::statvfs(path.as_ptr(), stat.as_mut_ptr()) } != 0 {
+        return None;
+    }
+    // SAFETY: `statvfs` succeeded, so it has initialized `stat`.
+    let stat = unsafe { stat.assume_init() };
+
+    #[allow(clippy::unnecessary_cast)]
+    Some(stat.f_bavail as u64 * stat.f_frsize as u64)
+}
+
+/// Returns the number of bytes available to unprivileged users on the volume
+/// containing `path`, if it can be determined.
+#[cfg(not(unix))]
+pub fn free_space(_path: &Path) -> Option<u64> {
+    None
+}
+
+/// Returns the size of the blocks files are allocated in on the volume
+/// containing `path`, if it can be determined.
+#[cfg(unix)]
+pub fn block_size(path: &Path) -> Option<u64> {
+    use std::ffi::CString;
+    use std::mem::MaybeUninit;
+    use std::os::unix::ffi::OsStrExt;
+
+    let path = CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut stat = MaybeUninit::<libc::statvfs>::uninit();
+
+    // SAFETY: `path` is a valid C string and `stat` is a valid pointer to a `statvfs`.
+    if unsafe { libc::statvfs(path.as_ptr(), stat.as_mut_ptr()) } != 0 {
+        return None;
+    }
+    // SAFETY: `statvfs` succeeded, so it has initialized `stat`.
+    let stat = unsafe { stat.assume_init() };
+
+    #[allow(clippy::unnecessary_cast)]
+    Some(stat.f_frsize as u64).filter(|&block_size| block_size > 0)
+}
+
+/// Returns the size of the blocks files are allocated in on the volume
+/// containing `path`, if it can be determined.
+#[cfg(not(unix))]
+pub fn block_size(_path: &Path) -> Option<u64> {
+    None
+}
+
+/// Returns whether the process with the ID `pid` is running, if it can be
+/// determined.
+#[cfg(unix)]
+pub fn process_alive(pid: u32) -> Option<bool> {
+    // 0 and negative IDs stand for groups of processes.
+    let pid = libc::pid_t::try_from(pid).ok().filter(|pid| *pid > 0)?;
+
+    // SAFETY: sending no signal only checks that the process exists.
+    if unsafe { libc::kill(pid, 0) } == 0 {
+        return Some(true);
+    }
+
+    // The process exists, but belongs to another user.
+    Some(io::Error::last_os_error().raw_os_error() == Some(libc::EPERM))
+}
+
+/// Returns whether the process with the ID `pid` is running, if it can be
+/// determined.
+#[cfg(not(unix))]
+pub fn process_alive(_pid: u32) -> Option<bool> {
+    None
+}
+
+/// Returns the SHA-1 hash of everything read from `reader`, in lowercase
+/// hexadecimal as in an index file.
+///
+/// # Examples
+/// ```
+/// use extract_minecraft_assets::util::sha1_hex;
+///
+/// assert_eq!(
+///     sha1_hex("{}".as_bytes())?,
+///     "bf21a9e8fbc5a3846fb05b4fa0859e0917b2202f"
+/// );
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub fn sha1_hex(mut reader: impl Read) -> io::Result<String> {
+    let mut hasher = Sha1::new();
+    io::copy(&mut reader, &mut hasher)?;
+
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Returns the CRC-32 checksum of everything read from `reader`, as stored
+/// in a jar file.
+pub fn crc32(mut reader: impl Read) -> io::Result<u32> {
+    let mut hasher = crc32fast::Hasher::new();
+    let mut buffer = [0; 8192];
+
+    loop {
+        match reader.read(&mut buffer)? {
+            0 => return Ok(hasher.finalize()),
+            read => hasher.update(&buffer[..read]),
+        }
+    }
+}
+
+/// Formats a number of bytes in human-readable binary units, e.g. `12.3 MiB`.
+///
+/// # Examples
+/// ```
+/// use extract_minecraft_assets::util::format_size;
+///
+/// assert_eq!(format_size(512), "512 B");
+/// assert_eq!(format_size(1536), "1.5 KiB");
+/// ```
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["KiB", "MiB", "GiB", "TiB", "PiB"];
+
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = UNITS[0];
+
+    for next_unit in &UNITS[1..] {
+        if size < 1024.0 {
+            break;
+        }
+
+        size /= 1024.0;
+        unit = next_unit;
+    }
+
+    format!("{size:.1} {unit}")
+}
+
+/// Parses a human-readable size into a number of bytes, e.g. `1 GiB`, `500M`
+/// or `1048576`.
+///
+/// Units are case-insensitive and always binary (`1 KB` is 1024 bytes).
+///
+/// # Examples
+/// ```
+/// use extract_minecraft_assets::util::parse_size;
+///
+/// assert_eq!(parse_size("1 GiB"), Ok(1 << 30));
+/// assert_eq!(parse_size("500m"), Ok(500 << 20));
+/// assert!(parse_size("ten").is_err());
+/// ```
+pub fn parse_size(input: &str) -> Result<u64, String> {
+    let input = input.trim();
+    let split = input
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(input.len());
+    let (number, unit) = input.split_at(split);
+
+    let number: f64 = number
+        .parse()
+        .map_err(|_| format!("invalid size '{input}': expected a number"))?;
+    let multiplier: u64 = match unit.trim().to_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" | "kib" => 1 << 10,
+        "m" | "mb" | "mib" => 1 << 20,
+        "g" | "gb" | "gib" => 1 << 30,
+        "t" | "tb" | "tib" => 1 << 40,
+
+        unit => return Err(format!("invalid size '{input}': unknown unit '{unit}'")),
+    };
+
+    Ok((number * multiplier as f64) as u64)
+}
+
+/// Returns the number of terminal columns `c` takes up: 2 for wide
+/// characters such as CJK ideographs, 0 for control characters and combining
+/// marks, and 1 otherwise.
+///
+/// This follows the East Asian Width property for the most common scripts,
+/// rather than every range of the Unicode tables.
+pub fn char_width(c: char) -> usize {
+    match u32::from(c) {
+        0x00..=0x1f | 0x7f..=0x9f => 0,
+        // Combining marks, and zero-width spaces and joiners
+        0x0300..=0x036f
+        | 0x1ab0..=0x1aff
+        | 0x1dc0..=0x1dff
+        | 0x200b..=0x200f
+        | 0x20d0..=0x20ff
+        | 0xfe00..=0xfe0f
+        | 0xfe20..=0xfe2f => 0,
+        // Hangul Jamo, CJK, Hangul syllables, fullwidth forms and emoji
+        0x1100..=0x115f
+        | 0x2e80..=0x303e
+        | 0x3041..=0x33ff
+        | 0x3400..=0x4dbf
+        | 0x4e00..=0x9fff
+        | 0xa000..=0xa4cf
+        | 0xac00..=0xd7a3
+        | 0xf900..=0xfaff
+        | 0xfe30..=0xfe4f
+        | 0xff00..=0xff60
+        | 0xffe0..=0xffe6
+        | 0x1f300..=0x1f64f
+        | 0x1f900..=0x1f9ff
+        | 0x20000..=0x2fffd
+        | 0x30000..=0x3fffd => 2,
+        _ => 1,
+    }
+}
+
+/// Returns the number of terminal columns `text` takes up, as the sum of the
+/// [widths of its characters](char_width).
+///
+/// # Examples
+/// ```
+/// use extract_minecraft_assets::util::display_width;
+///
+/// assert_eq!(display_width("lang/en_us.json"), 15);
+/// assert_eq!(display_width("森林"), 4);
+/// ```
+pub fn display_width(text: &str) -> usize {
+    text.chars().map(char_width).sum()
+}
+
+/// Shortens `text` to take up at most `max_width` terminal columns, replacing
+/// its middle with `…` so that both its start and end are kept.
+///
+/// Text which already fits is returned unchanged.
+///
+/// # Examples
+/// ```
+/// use extract_minecraft_assets::util::truncate_middle;
+///
+/// assert_eq!(
+///     truncate_middle("data/minecraft/worldgen/structure/village.json", 24),
+///     "data/minecra…illage.json"
+/// );
+/// assert_eq!(truncate_middle("lang/en_us.json", 24), "lang/en_us.json");
+/// ```
+pub fn truncate_middle(text: &str, max_width: usize) -> String {
+    if display_width(text) <= max_width {
+        return text.to_owned();
+    }
+    let Some(available) = max_width.checked_sub(1) else {
+        return String::new();
+    };
+
+    // The start gets the extra column if there is one.
+    let end_width = available / 2;
+    let start_width = available - end_width;
+
+    let mut start = String::new();
+    let mut width = 0;
+    for c in text.chars() {
+        width += char_width(c);
+        if width > start_width {
+            break;
+        }
+        start.push(c);
+    }
+
+    let mut end = Vec::new();
+    let mut width = 0;
+    for c in text.chars().rev() {
+        width += char_width(c);
+        if width > end_width {
+            break;
+        }
+        end.push(c);
+    }
+
+    start.push('…');
+    start.extend(end.into_iter().rev());
+
+    start
+}
+
+/// The maximum number of candidates considered by [`suggest`], so that huge
+/// directories don't slow down reporting an error.
+const MAX_SUGGESTION_CANDIDATES: usize = 1000;
+
+/// Returns up to three of `candidates` which are similar to `input`, most
+/// similar first.
+///
+/// Comparison is case-insensitive, and at most 1000 candidates are considered.
+///
+/// # Examples
+/// ```
+/// use extract_minecraft_assets::util::suggest;
+///
+/// let installed = ["1.21.4", "24w14a", "1.8.9"].map(String::from);
+///
+/// assert_eq!(suggest("1.21.5", installed), ["1.21.4"]);
+/// ```
+pub fn suggest(input: &str, candidates: impl IntoIterator<Item = String>) -> Vec<String> {
+    let input_lower = input.to_lowercase();
+    // Allow more edits for longer inputs, but not so many that everything matches.
+    let max_distance = (input.chars().count() / 3).max(2);
+
+    let mut suggestions: Vec<(usize, String)> = candidates
+        .into_iter()
+        .take(MAX_SUGGESTION_CANDIDATES)
+        .filter_map(|candidate| {
+            let distance = strsim::levenshtein(&input_lower, &candidate.to_lowercase());
+
+            (distance <= max_distance).then_some((distance, candidate))
+        })
+        .collect();
+    suggestions.sort();
+
+    suggestions
+        .into_iter()
+        .map(|(_, candidate)| candidate)
+        .take(3)
+        .collect()
+}
+
+/// Formats `suggestions` as a question, e.g. `did you mean '1.21.4' or '24w14a'?`.
+///
+/// Returns [`None`] if there are no suggestions.
+pub fn did_you_mean(suggestions: &[String]) -> Option<String> {
+    let quoted: Vec<String> = suggestions
+        .iter()
+        .map(|suggestion| format!("'{suggestion}'"))
+        .collect();
+
+    match quoted.as_slice() {
+        [] => None,
+        [only] => Some(format!("did you mean {only}?")),
+        [rest @ .., last] => Some(format!("did you mean {} or {last}?", rest.join(", "))),
+    }
+}
+
+/// Returns the names of the entries within `dir` for which `filter` returns
+/// true, with `extension` removed from the end of file names.
+///
+/// Returns nothing if `dir` cannot be read.
+pub fn entry_names(
+    dir: &Path,
+    extension: Option<&str>,
+    filter: impl Fn(&Path) -> bool,
+) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    entries
+        .filter_map(Result::ok)
+        .take(MAX_SUGGESTION_CANDIDATES)
+        .map(|entry| entry.path())
+        .filter(|path| filter(path))
+        .filter_map(|path| {
+            let name = path.file_name()?.to_str()?;
+
+            Some(match extension {
+                Some(extension) => name.strip_suffix(extension)?.to_owned(),
+                None => name.to_owned(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn suggestions_ignore_case() {
+        let candidates = ["Minecraft", "realms", "MINECRAFT_LEGACY"].map(String::from);
+
+        assert_eq!(suggest("minecraft", candidates.clone()), ["Minecraft"]);
+        assert_eq!(suggest("REALM", candidates), ["realms"]);
+    }
+
+    #[test]
+    fn suggestions_only_consider_the_first_candidates() {
+        let unrelated = (0..MAX_SUGGESTION_CANDIDATES).map(|i| format!("unrelated-{i}"));
+
+        assert_eq!(
+            suggest("1.21.4", unrelated.clone().chain(["1.21.4".into()])),
+            [] as [String; 0]
+        );
+        assert_eq!(
+            suggest("1.21.4", unrelated.take(999).chain(["1.21.4".into()])),
+            ["1.21.4"]
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        for i in 0..=MAX_SUGGESTION_CANDIDATES {
+            fs::write(dir.path().join(format!("{i}.json")), "{}").unwrap();
+        }
+        let names = entry_names(dir.path(), Some(".json"), |_| true);
+        assert_eq!(names.len(), MAX_SUGGESTION_CANDIDATES);
+        assert!(names.iter().all(|name| name.parse::<usize>().is_ok()));
+    }
+
+    #[test]
+    fn suggestions_are_asked_about() {
+        assert_eq!(did_you_mean(&[]), None);
+        assert_eq!(
+            did_you_mean(&["17".into()]).as_deref(),
+            Some("did you mean '17'?")
+        );
+        assert_eq!(
+            did_you_mean(&["17".into(), "18".into(), "19".into()]).as_deref(),
+            Some("did you mean '17', '18' or '19'?")
+        );
+    }
+}
@@ -0,0 +1,255 @@
+//! Checking that extracted files look like the formats their extensions
+//! claim, e.g. to catch hashed files corrupted on disk after they were
+//! downloaded with the right hash.
+//!
+//! The checks are cheap sanity checks rather than full decoders: only the
+//! first bytes of PNG, OGG and NBT files are looked at, and JSON files are
+//! parsed unless they are larger than [`MAX_JSON_SIZE`]. Files are checked as
+//! they are written, so they are never read again.
+
+use crate::options::ExtractOptions;
+use serde::Serialize;
+use serde::de::IgnoredAny;
+use std::fmt::{self, Display, Formatter};
+use std::io::{self, Read};
+use std::path::Path;
+
+/// The size in bytes above which JSON files aren't parsed, as they are held
+/// in memory to parse them.
+pub const MAX_JSON_SIZE: usize = 16 * 1024 * 1024;
+
+/// The PNG file signature.
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// A format whose files can be checked.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ContentFormat {
+    /// `.png` images, checked for the PNG signature and a valid `IHDR`
+    /// chunk.
+    Png,
+    /// `.ogg` sounds, checked for the Ogg capture pattern.
+    Ogg,
+    /// `.json` and `.mcmeta` files, checked by parsing them.
+    Json,
+    /// `.nbt` structures, checked for the gzip magic number.
+    Nbt,
+}
+
+impl ContentFormat {
+    /// Every format, in the order they are listed.
+    pub const ALL: [Self; 4] = [Self::Png, Self::Ogg, Self::Json, Self::Nbt];
+
+    /// The name of the format, e.g. `png`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Png => "png",
+            Self::Ogg => "ogg",
+            Self::Json => "json",
+            Self::Nbt => "nbt",
+        }
+    }
+
+    /// Returns the format of the file at `path` by its extension, if it is
+    /// one which can be checked.
+    pub fn of(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "png" => Some(Self::Png),
+            "ogg" => Some(Self::Ogg),
+            "json" | "mcmeta" => Some(Self::Json),
+            "nbt" => Some(Self::Nbt),
+            _ => None,
+        }
+    }
+
+    /// The number of bytes at the start of a file needed to check it.
+    fn bytes_needed(self) -> usize {
+        match self {
+            // The signature, then the length, type and data of `IHDR`.
+            Self::Png => PNG_SIGNATURE.len() + 8 + 13,
+            // The capture pattern and the stream structure version.
+            Self::Ogg => 5,
+            // The magic number and the compression method.
+            Self::Nbt => 3,
+            Self::Json => MAX_JSON_SIZE,
+        }
+    }
+}
+
+impl Display for ContentFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Checks the contents of a file of a [`ContentFormat`] as they are
+/// [fed](ContentCheck::update) to it, only keeping the bytes needed to check
+/// it.
+///
+/// # Examples
+/// ```
+/// use extract_minecraft_assets::validate::{ContentCheck, ContentFormat};
+///
+/// let mut check = ContentCheck::new(ContentFormat::Ogg);
+/// check.update(b"OggS\0");
+/// check.update(b"...rest of the stream");
+///
+/// assert_eq!(check.finish(), Ok(()));
+/// ```
+#[derive(Clone, Debug)]
+pub struct ContentCheck {
+    format: ContentFormat,
+    /// The first bytes of the file, up to those needed to check it.
+    start: Vec<u8>,
+    /// The number of bytes fed so far.
+    len: u64,
+}
+
+impl ContentCheck {
+    /// Returns a check of a file of `format`, without any contents yet.
+    pub fn new(format: ContentFormat) -> Self {
+        Self {
+            format,
+            start: Vec::new(),
+            len: 0,
+        }
+    }
+
+    /// Feeds the next `bytes` of the file to the check.
+    pub fn update(&mut self, bytes: &[u8]) {
+        let needed = self.format.bytes_needed().saturating_sub(self.start.len());
+        self.start
+            .extend_from_slice(&bytes[..needed.min(bytes.len())]);
+        self.len += bytes.len() as u64;
+    }
+
+    /// Finishes the check, returning why the file is invalid if it is.
+    ///
+    /// JSON files larger than [`MAX_JSON_SIZE`] are assumed to be valid.
+    pub fn finish(self) -> Result<(), String> {
+        let start = self.start.as_slice();
+
+        match self.format {
+            ContentFormat::Png => check_png(start),
+            ContentFormat::Ogg if !start.starts_with(b"OggS") => {
+                Err("invalid OGG: missing the capture pattern 'OggS'".to_owned())
+            }
+            ContentFormat::Ogg if start.get(4) != Some(&0) => {
+                Err("invalid OGG: unsupported stream structure version".to_owned())
+            }
+            ContentFormat::Ogg => Ok(()),
+            ContentFormat::Nbt if !start.starts_with(&[0x1f, 0x8b]) => {
+                Err("invalid NBT: not gzip-compressed".to_owned())
+            }
+            ContentFormat::Nbt if start.get(2) != Some(&8) => {
+                Err("invalid NBT: unsupported gzip compression method".to_owned())
+            }
+            ContentFormat::Nbt => Ok(()),
+            ContentFormat::Json if self.len > MAX_JSON_SIZE as u64 => Ok(()),
+            ContentFormat::Json => serde_json::from_slice::<IgnoredAny>(start)
+                .map(|_| ())
+                .map_err(|error| format!("invalid JSON: {error}")),
+        }
+    }
+}
+
+/// Checks the PNG signature and `IHDR` chunk at the `start` of a PNG file.
+fn check_png(start: &[u8]) -> Result<(), String> {
+    let Some(ihdr) = start.strip_prefix(PNG_SIGNATURE) else {
+        return Err("invalid PNG: missing the PNG signature".to_owned());
+    };
+    if ihdr.len() < 8 + 13 || ihdr[..8] != *b"\0\0\0\x0dIHDR" {
+        return Err("invalid PNG: missing the IHDR chunk".to_owned());
+    }
+
+    let data = &ihdr[8..];
+    let width = u32::from_be_bytes([data[0], data[1], data[2], data[3]]);
+    let height = u32::from_be_bytes([data[4], data[5], data[6], data[7]]);
+    let (bit_depth, color_type) = (data[8], data[9]);
+
+    if width == 0 || height == 0 {
+        return Err(format!("invalid PNG: the image is {width}x{height}"));
+    }
+
+    let bit_depths: &[u8] = match color_type {
+        0 => &[1, 2, 4, 8, 16],
+        3 => &[1, 2, 4, 8],
+        2 | 4 | 6 => &[8, 16],
+        _ => return Err(format!("invalid PNG: unknown color type {color_type}")),
+    };
+    if !bit_depths.contains(&bit_depth) {
+        return Err(format!(
+            "invalid PNG: bit depth {bit_depth} isn't allowed with color type {color_type}"
+        ));
+    }
+    if data[10] != 0 || data[11] != 0 || data[12] > 1 {
+        return Err("invalid PNG: unknown compression, filter or interlace method".to_owned());
+    }
+
+    Ok(())
+}
+
+/// Checks the `contents` of a file of `format`, returning why it is invalid
+/// if it is.
+///
+/// # Examples
+/// ```
+/// use extract_minecraft_assets::validate::{self, ContentFormat};
+///
+/// assert_eq!(validate::validate(ContentFormat::Json, br#"{"a": 1}"#), Ok(()));
+/// assert!(validate::validate(ContentFormat::Png, b"GIF89a").is_err());
+/// ```
+pub fn validate(format: ContentFormat, contents: &[u8]) -> Result<(), String> {
+    let mut check = ContentCheck::new(format);
+    check.update(contents);
+
+    check.finish()
+}
+
+/// Returns the check of the file being written to `path` if its format is
+/// [validated](ExtractOptions::validate_content) with `options`.
+pub(crate) fn content_check(options: &ExtractOptions, path: &Path) -> Option<ContentCheck> {
+    ContentFormat::of(path)
+        .filter(|format| options.validate_content.contains(format))
+        .map(ContentCheck::new)
+}
+
+/// Checks the `contents` of the file written to `path` if its format is
+/// [validated](ExtractOptions::validate_content) with `options`, returning why
+/// it is invalid if it is.
+pub(crate) fn check(options: &ExtractOptions, path: &Path, contents: &[u8]) -> Option<String> {
+    let mut check = content_check(options, path)?;
+    check.update(contents);
+
+    check.finish().err()
+}
+
+/// Feeds the contents read through it to a [`ContentCheck`], if there is one,
+/// so that a file can be checked while it is written.
+pub(crate) struct CheckingReader<'r> {
+    inner: &'r mut dyn Read,
+    check: Option<ContentCheck>,
+}
+
+impl<'r> CheckingReader<'r> {
+    pub(crate) fn new(inner: &'r mut dyn Read, check: Option<ContentCheck>) -> Self {
+        Self { inner, check }
+    }
+
+    /// Finishes the check, returning why the contents read are invalid if
+    /// they are.
+    pub(crate) fn finish(self) -> Option<String> {
+        self.check?.finish().err()
+    }
+}
+
+impl Read for CheckingReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        if let Some(check) = &mut self.check {
+            check.update(&buf[..read]);
+        }
+
+        Ok(read)
+    }
+}
@@ -1,57 +1,70 @@
-use crate::util::OptionExt;
-use crate::{hashed, jar, util, ExtractCmd};
-use clap::Args;
+use crate::error::ExtractError;
+use crate::libraries::Library;
+use crate::util;
 use serde::Deserialize;
+use std::collections::BTreeMap;
 use std::error::Error;
 use std::ffi::OsStr;
 use std::fmt::{Display, Formatter};
-use std::path::{Path, PathBuf};
-use std::{fs, io};
-
-#[derive(Args)]
-pub struct VersionSubcommand {
-    /// The directory containing the version `.jar` file and manifest.
-    ///
-    /// Can be a path to the directory, or the name of the version to be found
-    /// within `.minecraft/versions/`.
-    ///
-    /// The name of the `.jar` and `.json` manifest file inside must match the
-    /// directory name.
-    ///
-    /// Example: `1.20.1` or `.minecraft/versions/1.20.1`
-    #[arg(value_name = "DIRECTORY or VERSION", value_parser = Version::parse)]
-    version_dir: Version,
-    /// The path to the `.minecraft/assets/` directory to find hashed assets.
-    ///
-    /// Defaults to the default location on your OS.
-    #[arg(long = "hashed-assets", value_name = "DIRECTORY")]
-    hashed_assets_dir: Option<PathBuf>,
-
-    /// Which contents to extract.
-    #[command(flatten)]
-    extracted_contents: jar::ExtractedContents,
-}
+use std::fs::{self, File};
+use std::io::{BufReader, Read};
+use std::path::{Component, Path, PathBuf};
 
 /// Represents a directory containing the version `.jar` file and manifest.
+///
+/// A version is either [resolved](Version::resolve) from a name or path given
+/// by the user, or created [from its directory](Version::from_dir) directly.
+///
+/// # Examples
+/// ```no_run
+/// use extract_minecraft_assets::version::Version;
+/// use std::path::Path;
+///
+/// let version = Version::resolve_in("1.20.1", Path::new("/opt/minecraft"))?;
+///
+/// assert_eq!(version.jar_file(), Path::new("/opt/minecraft/versions/1.20.1/1.20.1.jar"));
+/// println!("Asset index: {:?}", version.manifest()?.index_id());
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
-struct Version {
+pub struct Version {
     dir: PathBuf,
 }
 
 impl Version {
-    /// Returns a new [`Version`] wrapping `dir`.
-    fn new(dir: PathBuf) -> Self {
+    /// Returns the [`Version`] in `dir`, without checking that it exists.
+    ///
+    /// `dir` is normalized so that its last component is the version's
+    /// [name](Version::name): trailing separators and `.` components are
+    /// removed, and paths ending in `..` are made canonical if they exist.
+    pub fn from_dir(dir: impl Into<PathBuf>) -> Self {
+        let dir = dir.into();
+        let normalized: PathBuf = dir
+            .components()
+            .filter(|component| *component != Component::CurDir)
+            .collect();
+
+        let dir = match normalized.components().next_back() {
+            Some(Component::Normal(_)) => normalized,
+            _ => fs::canonicalize(&dir).unwrap_or(dir),
+        };
+
         Self { dir }
     }
 
     /// The path to the version's directory.
-    fn path(&self) -> &Path {
+    pub fn path(&self) -> &Path {
         &self.dir
     }
 
     /// The name of the version directory, [jar file](Version::jar_file), and
     /// [manifest file](Version::manifest_file).
-    fn name(&self) -> &str {
+    ///
+    /// # Panics
+    /// Panics if the directory has no name, or it isn't valid UTF-8. This can
+    /// only happen to versions created [from a directory](Version::from_dir)
+    /// such as `/`: [resolution](Version::resolve) rejects them.
+    pub fn name(&self) -> &str {
         self.path()
             .file_name()
             .and_then(OsStr::to_str)
@@ -59,127 +72,406 @@ impl Version {
     }
 
     /// The path to the version's jar file.
-    fn jar_file(&self) -> PathBuf {
+    pub fn jar_file(&self) -> PathBuf {
         self.path().join(format!("{}.jar", self.name()))
     }
 
     /// The path to the version's manifest file.
-    fn manifest_file(&self) -> PathBuf {
+    pub fn manifest_file(&self) -> PathBuf {
         self.path().join(format!("{}.json", self.name()))
     }
 
-    /// Parses `input` into a [`Version`].
+    /// Reads and parses the version's [manifest file](Version::manifest_file).
+    pub fn manifest(&self) -> Result<VersionManifest, ExtractError> {
+        VersionManifest::from_path(&self.manifest_file())
+    }
+
+    /// The path to the version's hashed assets index file within
+    /// `hashed_assets_dir`, as named by the [manifest](Version::manifest).
+    ///
+    /// The index file itself isn't required to exist.
+    pub fn index_file(&self, hashed_assets_dir: &Path) -> Result<PathBuf, ExtractError> {
+        let manifest = self.manifest()?;
+        let index_id = manifest
+            .index_id()
+            .ok_or_else(|| ExtractError::NoAssetIndex {
+                path: self.manifest_file(),
+            })?;
+
+        Ok(hashed_assets_dir
+            .join("indexes")
+            .join(format!("{index_id}.json")))
+    }
+
+    /// Returns every version installed in `versions_dir`, sorted by name:
+    /// the directories with both a [jar file](Version::jar_file) and a
+    /// [manifest file](Version::manifest_file).
+    ///
+    /// Returns nothing if `versions_dir` cannot be read.
+    pub fn installed(versions_dir: &Path) -> Vec<Self> {
+        let Ok(entries) = fs::read_dir(versions_dir) else {
+            return Vec::new();
+        };
+
+        let mut versions: Vec<_> = entries
+            .filter_map(Result::ok)
+            .filter(|entry| entry.file_name().to_str().is_some())
+            .map(|entry| Self::from_dir(entry.path()))
+            .filter(|version| version.jar_file().is_file() && version.manifest_file().is_file())
+            .collect();
+        versions.sort();
+
+        versions
+    }
+
+    /// Resolves `input`, the path to a version directory or the name of a
+    /// version, into a [`Version`].
+    ///
+    /// Names are looked up in the `versions` directory of the default
+    /// [`.minecraft` directory](util::minecraft_dir) with the `discovery`
+    /// feature: without it, `input` must be a path. Use [`Version::resolve_in`]
+    /// to look names up in a different `.minecraft` directory.
+    ///
+    /// If no version directory is found, or it has no name to find the jar and
+    /// manifest files by, an [`InvalidVersion`] error is returned, whose
+    /// [reason](InvalidVersionReason) tells e.g. a missing `.minecraft`
+    /// directory apart from a version which isn't installed.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use extract_minecraft_assets::version::Version;
+    ///
+    /// let version = Version::resolve("1.20.1")?;
     ///
-    /// If there is neither a directory at the path specified by `input`, nor as
-    /// a child of the [default `versions` directory location](util::versions_dir),
-    /// an [`InvalidVersion`] error is returned.
-    fn parse(input: &str) -> Result<Self, InvalidVersion> {
+    /// assert_eq!(version.name(), "1.20.1");
+    /// println!("Jar file: {}", version.jar_file().display());
+    /// # Ok::<(), extract_minecraft_assets::version::InvalidVersion>(())
+    /// ```
+    pub fn resolve(input: &str) -> Result<Self, InvalidVersion> {
+        #[cfg(feature = "discovery")]
+        let minecraft_dir = util::minecraft_dir().ok_or_else(util::minecraft_dir_candidates);
+        #[cfg(not(feature = "discovery"))]
+        let minecraft_dir = Err(Vec::new());
+
+        Self::resolve_with(input, minecraft_dir)
+    }
+
+    /// Resolves `input` like [`Version::resolve`], but looks names up in the
+    /// `versions` directory of `minecraft_dir`.
+    pub fn resolve_in(input: &str, minecraft_dir: &Path) -> Result<Self, InvalidVersion> {
+        let minecraft_dir = if util::is_dir(minecraft_dir) {
+            Ok(minecraft_dir.to_owned())
+        } else {
+            Err(vec![minecraft_dir.to_owned()])
+        };
+
+        Self::resolve_with(input, minecraft_dir)
+    }
+
+    /// Resolves `input` as a path, or as a name within the `versions`
+    /// directory of `minecraft_dir`, which is otherwise the locations probed
+    /// for it.
+    fn resolve_with(
+        input: &str,
+        minecraft_dir: Result<PathBuf, Vec<PathBuf>>,
+    ) -> Result<Self, InvalidVersion> {
         let path = Path::new(input);
+        let invalid = |reason| InvalidVersion {
+            version: input.to_owned(),
+            reason,
+            suggestions: Vec::new(),
+        };
 
-        if path.is_dir() {
-            Ok(Self::new(path.to_owned()))
+        let dir = if util::is_dir(path) {
+            path.to_owned()
+        } else if path.components().count() > 1 {
+            // Names are a single component, so this can only be a path.
+            return Err(invalid(InvalidVersionReason::NoSuchDirectory));
         } else {
-            if let Some(path) = util::versions_dir()
-                .inspect_mut(|dir| dir.push(path))
-                .filter(|path| path.is_dir())
-            {
-                Ok(Self::new(path))
-            } else {
-                Err(InvalidVersion::new(input.to_owned()))
+            let minecraft_dir = minecraft_dir
+                .map_err(|probed| invalid(InvalidVersionReason::NoMinecraftDir { probed }))?;
+            let versions_dir = minecraft_dir.join("versions");
+            if !util::is_dir(&versions_dir) {
+                return Err(invalid(InvalidVersionReason::NoVersionsDir {
+                    path: versions_dir,
+                }));
+            }
+
+            let dir = versions_dir.join(path);
+            if !util::is_dir(&dir) {
+                return Err(InvalidVersion::not_found(input.to_owned(), versions_dir));
             }
+
+            dir
+        };
+
+        let version = Self::from_dir(dir);
+        if version.path().file_name().and_then(OsStr::to_str).is_none() {
+            return Err(invalid(InvalidVersionReason::Unnamed));
         }
+
+        Ok(version)
     }
 }
 
 /// Represents an error locating a [version directory](Version) during
-/// [parsing](Version::parse).
+/// [resolution](Version::resolve).
 #[derive(Debug)]
 pub struct InvalidVersion {
     pub version: String,
+    pub reason: InvalidVersionReason,
+    /// Installed versions with similar names.
+    pub suggestions: Vec<String>,
 }
 
+/// How many installed versions are listed when a version isn't found.
+const LISTED_VERSIONS: usize = 10;
+
 impl InvalidVersion {
-    fn new(version: String) -> Self {
-        Self { version }
+    /// Returns an [`InvalidVersion`] for `version` not being in
+    /// `versions_dir`, listing the versions which are and suggesting
+    /// similarly named ones.
+    fn not_found(version: String, versions_dir: PathBuf) -> Self {
+        let mut available = util::entry_names(&versions_dir, None, util::is_dir);
+        available.sort();
+        let suggestions = util::suggest(&version, available.iter().cloned());
+
+        Self {
+            version,
+            reason: InvalidVersionReason::NotFound {
+                versions_dir,
+                available,
+            },
+            suggestions,
+        }
+    }
+
+    /// Explains why the version is invalid and what to do about it, without
+    /// naming the version, e.g. for a command-line argument which is already
+    /// named.
+    pub fn explanation(&self) -> String {
+        let suggestions = util::did_you_mean(&self.suggestions)
+            .map(|suggestions| format!(" ({suggestions})"))
+            .unwrap_or_default();
+
+        match &self.reason {
+            // Suggestions come before the list of installed versions.
+            InvalidVersionReason::NotFound {
+                versions_dir,
+                available,
+            } => not_found_message(versions_dir, available, &suggestions),
+            reason => format!("{reason}{suggestions}"),
+        }
+    }
+}
+
+/// Explains that a version isn't in `versions_dir`, followed by `suggestions`
+/// and a list of the `available` versions.
+fn not_found_message(versions_dir: &Path, available: &[String], suggestions: &str) -> String {
+    let mut message = format!(
+        "no directory exists at that path nor with that name in '{}'{suggestions}",
+        versions_dir.display()
+    );
+
+    if available.is_empty() {
+        message.push_str(", which has no versions installed");
+    } else {
+        let listed: Vec<_> = available
+            .iter()
+            .take(LISTED_VERSIONS)
+            .map(|version| format!("'{version}'"))
+            .collect();
+        message.push_str(&format!("; installed versions are {}", listed.join(", ")));
+
+        if available.len() > LISTED_VERSIONS {
+            message.push_str(&format!(" and {} more", available.len() - LISTED_VERSIONS));
+        }
     }
+
+    message
 }
 
 impl Display for InvalidVersion {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "invalid version '{}': no directory exists of that path nor name within `minecraft/versions`",
-            self.version
+            "invalid version '{}': {}",
+            self.version,
+            self.explanation()
         )
     }
 }
 
 impl Error for InvalidVersion {}
 
-/// Represents the manifest file for a version.
+/// Why a [version](Version) couldn't be [resolved](Version::resolve).
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum InvalidVersionReason {
+    /// No directory exists at the path, which has more than one component so
+    /// can't be the name of a version.
+    NoSuchDirectory,
+    /// No directory exists at the path, and there is no `.minecraft`
+    /// directory to look it up in by name: none exists at any of the
+    /// locations `probed`, which is empty if there is no default location.
+    NoMinecraftDir { probed: Vec<PathBuf> },
+    /// No directory exists at the path, and the `.minecraft` directory has
+    /// no `versions` directory at `path` to look it up in by name, e.g. as
+    /// no version has been installed yet.
+    NoVersionsDir { path: PathBuf },
+    /// No directory exists of that path nor name within `versions_dir`,
+    /// which contains the `available` versions, in order.
+    NotFound {
+        versions_dir: PathBuf,
+        available: Vec<String>,
+    },
+    /// The directory has no name, e.g. `/`, or it isn't valid UTF-8.
+    Unnamed,
+}
+
+/// Explains the reason along with what to do about it, e.g. `no directory
+/// named '1.20.2' in '.minecraft/versions'; installed versions are '1.20.1'`.
+impl Display for InvalidVersionReason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoSuchDirectory => write!(f, "no directory exists at that path"),
+            Self::NoMinecraftDir { probed } if probed.is_empty() => write!(
+                f,
+                "no directory exists at that path, and there is no default \
+                 `.minecraft` directory to look it up in; pass the path to the version directory"
+            ),
+            Self::NoMinecraftDir { probed } => {
+                let probed: Vec<_> = probed
+                    .iter()
+                    .map(|path| util::display_resolved(path))
+                    .collect();
+
+                write!(
+                    f,
+                    "no directory exists at that path, and no `.minecraft` directory was \
+                     found at {} to look it up in; check that Minecraft is installed for \
+                     this user, or pass the path to the version directory",
+                    probed.join(", ")
+                )
+            }
+            Self::NoVersionsDir { path } => write!(
+                f,
+                "no directory exists at that path, and {} doesn't exist to look it up \
+                 in; launch the version once with the Minecraft launcher to install it",
+                util::display_resolved(path)
+            ),
+            Self::NotFound {
+                versions_dir,
+                available,
+            } => f.write_str(&not_found_message(versions_dir, available, "")),
+            Self::Unnamed => write!(
+                f,
+                "the directory has no UTF-8 name to find its jar and manifest files by"
+            ),
+        }
+    }
+}
+
+/// The manifest file of a [`Version`], e.g. `versions/1.20.1/1.20.1.json`.
 ///
 /// The manifest file has a lot of information: this representation only
-/// includes what is necessary for identifying the hashed assets index.
-#[derive(Deserialize)]
+/// includes what is needed to locate and verify the version's files. Fields
+/// which a manifest may leave out, such as those of modded versions which
+/// inherit from another version, are optional.
+///
+/// # Examples
+/// ```
+/// use extract_minecraft_assets::version::VersionManifest;
+///
+/// let manifest = VersionManifest::from_reader(
+///     r#"{"id": "1.20.1", "assets": "5", "downloads": {}}"#.as_bytes(),
+/// )?;
+///
+/// assert_eq!(manifest.index_id(), Some("5"));
+/// # Ok::<(), serde_json::Error>(())
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
 #[non_exhaustive]
-struct ManifestFile {
+pub struct VersionManifest {
+    /// The name of the version, e.g. `1.20.1`.
+    pub id: String,
     /// The name of the index file to be found within `.minecraft/assets/indexes/`,
     /// without the `json` file extension.
-    #[serde(rename = "assets")]
-    index_version: String,
+    pub assets: Option<String>,
+    /// The index file to download, if any.
+    pub asset_index: Option<AssetIndex>,
+    /// The files which can be downloaded for the version, such as `client`
+    /// (the jar file) or `server`, by name.
+    #[serde(default)]
+    pub downloads: BTreeMap<String, Download>,
+    /// The name of the version this version is based on, e.g. for modded
+    /// versions.
+    pub inherits_from: Option<String>,
+    /// The libraries the version needs to run.
+    #[serde(default)]
+    pub libraries: Vec<Library>,
 }
 
-impl ExtractCmd for VersionSubcommand {
-    fn execute(self, output_dir: PathBuf, ignore_top_level: bool) -> io::Result<()> {
-        if !self.extracted_contents.assets && !self.extracted_contents.data {
-            return Ok(());
-        }
+/// The index file named by a [`VersionManifest`].
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct AssetIndex {
+    /// The name of the index file, without the `json` file extension.
+    ///
+    /// This is all that is needed to find the index file: the other fields
+    /// may be missing, e.g. from hand-written manifests.
+    pub id: String,
+    /// The SHA-1 hash of the index file.
+    pub sha1: Option<String>,
+    /// The size of the index file in bytes.
+    pub size: Option<u64>,
+    /// The total size in bytes of every hashed file listed in the index.
+    pub total_size: Option<u64>,
+    /// Where the index file can be downloaded from.
+    pub url: Option<String>,
+}
 
-        let manifest: Option<ManifestFile> = self
-            .extracted_contents
-            .assets
-            .then(|| {
-                serde_json::from_str(&fs::read_to_string(self.version_dir.manifest_file())?)
-                    .map_err(Into::<io::Error>::into)
-            })
-            .transpose()?;
-        let hashed_assets_dir = self
-            .hashed_assets_dir
-            .or_else(|| util::hashed_assets_dir())
-            .filter(|path| path.is_dir())
-            .expect("No hashed assets directory found");
-        let index = manifest.map(|ManifestFile { index_version }| {
-            let mut path: PathBuf = [
-                &hashed_assets_dir,
-                Path::new("indexes"),
-                index_version.as_ref(),
-            ]
-            .iter()
-            .collect();
-            path.set_extension("json");
-
-            assert!(
-                path.is_file(),
-                "No index file for hashed assets found at '{}'",
-                path.display()
-            );
-
-            path
-        });
-
-        let jar = self.version_dir.jar_file();
-        println!(
-            "Extracting {} from {}...",
-            &self.extracted_contents,
-            jar.display()
-        );
-        jar::extract_jar(&jar, &output_dir, self.extracted_contents, ignore_top_level)?;
-
-        if let Some(path) = index {
-            println!("Extracting hashed assets using index {}...", path.display());
-            hashed::extract_hashed_assets(&hashed_assets_dir, output_dir, path, ignore_top_level)?;
-        }
+/// A file which can be downloaded for a version, listed in its
+/// [`VersionManifest`].
+#[derive(Clone, PartialEq, Eq, Debug, Deserialize)]
+#[non_exhaustive]
+pub struct Download {
+    /// The SHA-1 hash of the file.
+    pub sha1: Option<String>,
+    /// The size of the file in bytes.
+    pub size: Option<u64>,
+    /// Where the file can be downloaded from.
+    pub url: Option<String>,
+}
 
-        Ok(())
+impl VersionManifest {
+    /// Parses a manifest file from `reader`.
+    pub fn from_reader(reader: impl Read) -> Result<Self, serde_json::Error> {
+        serde_json::from_reader(BufReader::new(reader))
+    }
+
+    /// Reads and parses the manifest file at `path`.
+    pub fn from_path(path: &Path) -> Result<Self, ExtractError> {
+        let file = File::open(path).map_err(|source| ExtractError::ReadManifest {
+            path: path.to_owned(),
+            source,
+        })?;
+
+        Self::from_reader(file).map_err(|source| ExtractError::ParseManifest {
+            path: path.to_owned(),
+            source,
+        })
+    }
+
+    /// The name of the version's index file, without the `json` file
+    /// extension.
+    ///
+    /// This is [`assets`](VersionManifest::assets), or else the
+    /// [`asset_index`](VersionManifest::asset_index)'s id.
+    pub fn index_id(&self) -> Option<&str> {
+        self.assets
+            .as_deref()
+            .or_else(|| self.asset_index.as_ref().map(|index| index.id.as_str()))
     }
 }
@@ -0,0 +1,496 @@
+use std::collections::BTreeMap;
+use std::fmt::{self, Debug, Formatter};
+use std::fs::{self, File};
+use std::hash::{Hash, Hasher};
+use std::io::{self, Read};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// The filesystem operations used to write the output of an extraction.
+///
+/// Extractions write through the [`vfs`](crate::options::ExtractOptions::vfs)
+/// of their options, which is the real filesystem ([`RealFs`]) by default.
+/// [`MemoryFs`] keeps everything in memory instead, e.g. for tests.
+///
+/// Only the output is written through a [`Vfs`]: jar files, index files and
+/// hashed files are always read from the real filesystem.
+pub trait Vfs: Send + Sync {
+    /// Creates the directory at `path` along with any missing parents.
+    fn create_dir_all(&self, path: &Path) -> io::Result<()>;
+
+    /// Creates or truncates the file at `path` and copies `contents` into it,
+    /// returning the number of bytes written.
+    ///
+    /// The parent directory of `path` must already exist.
+    fn write_file(&self, path: &Path, contents: &mut dyn Read) -> io::Result<u64>;
+
+    /// Sets the unix permissions of the file or directory at `path`.
+    ///
+    /// Does nothing on platforms without unix permissions.
+    fn set_unix_mode(&self, path: &Path, mode: u32) -> io::Result<()>;
+
+    /// Returns the metadata of the file or directory at `path`.
+    fn metadata(&self, path: &Path) -> io::Result<Metadata>;
+
+    /// Whether a file or directory exists at `path`.
+    fn exists(&self, path: &Path) -> bool {
+        self.metadata(path).is_ok()
+    }
+
+    /// Moves the file at `from` to `to`, replacing any file at `to`.
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()>;
+
+    /// Removes the file at `path`.
+    fn remove_file(&self, path: &Path) -> io::Result<()>;
+
+    /// Removes the directory at `path` along with everything in it.
+    fn remove_dir_all(&self, path: &Path) -> io::Result<()>;
+
+    /// Copies the file at `from` to `to`, replacing any file at `to`, and
+    /// returns the number of bytes copied.
+    fn copy(&self, from: &Path, to: &Path) -> io::Result<u64>;
+
+    /// Creates a hard link at `link` to the file at `original`.
+    ///
+    /// There must not already be a file at `link`. Filesystems without hard
+    /// links copy the file instead, which is the default.
+    fn hard_link(&self, original: &Path, link: &Path) -> io::Result<()> {
+        self.copy(original, link).map(drop)
+    }
+}
+
+/// Removes the partially written file at `output_path` after the output volume
+/// became full, so that it isn't mistaken for an extracted file.
+pub(crate) fn remove_partial_file(vfs: &dyn Vfs, output_path: &Path) {
+    // The file may not have been created at all.
+    let _ = vfs.remove_file(output_path);
+}
+
+/// The metadata of a file or directory in a [`Vfs`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Metadata {
+    /// Whether this is a directory rather than a file.
+    pub is_dir: bool,
+    /// The size of the file in bytes, or 0 for a directory.
+    pub len: u64,
+}
+
+/// A [`Vfs`] writing to the real filesystem with [`std::fs`].
+#[derive(Clone, Copy, Default, Debug)]
+pub struct RealFs;
+
+impl Vfs for RealFs {
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        fs::create_dir_all(path)
+    }
+
+    fn write_file(&self, path: &Path, contents: &mut dyn Read) -> io::Result<u64> {
+        io::copy(contents, &mut File::create(path)?)
+    }
+
+    fn set_unix_mode(&self, path: &Path, mode: u32) -> io::Result<()> {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            fs::set_permissions(path, fs::Permissions::from_mode(mode))?;
+        }
+
+        #[cfg(not(unix))]
+        let _ = (path, mode);
+
+        Ok(())
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<Metadata> {
+        let metadata = fs::metadata(path)?;
+
+        Ok(Metadata {
+            is_dir: metadata.is_dir(),
+            len: if metadata.is_dir() { 0 } else { metadata.len() },
+        })
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        fs::rename(from, to)
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        fs::remove_file(path)
+    }
+
+    fn remove_dir_all(&self, path: &Path) -> io::Result<()> {
+        fs::remove_dir_all(path)
+    }
+
+    fn copy(&self, from: &Path, to: &Path) -> io::Result<u64> {
+        fs::copy(from, to)
+    }
+
+    fn hard_link(&self, original: &Path, link: &Path) -> io::Result<()> {
+        fs::hard_link(original, link)
+    }
+}
+
+/// A [`Vfs`] which writes nothing, for dry runs.
+///
+/// The contents of every file are still read in full, so that files which
+/// can't be read fail as they would, but are then discarded. Metadata is
+/// read from the real filesystem, so that existing files are skipped or
+/// replaced as they would be, while creating directories, removing, moving,
+/// copying and linking files do nothing.
+///
+/// # Examples
+/// ```
+/// use extract_minecraft_assets::vfs::{DryRunFs, Vfs};
+/// use std::path::Path;
+///
+/// let path = Path::new("never-written.json");
+/// let bytes = DryRunFs.write_file(path, &mut &b"{}"[..])?;
+///
+/// assert_eq!(bytes, 2);
+/// assert!(!DryRunFs.exists(path));
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Clone, Copy, Default, Debug)]
+pub struct DryRunFs;
+
+impl Vfs for DryRunFs {
+    fn create_dir_all(&self, _path: &Path) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn write_file(&self, _path: &Path, contents: &mut dyn Read) -> io::Result<u64> {
+        io::copy(contents, &mut io::sink())
+    }
+
+    fn set_unix_mode(&self, _path: &Path, _mode: u32) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<Metadata> {
+        RealFs.metadata(path)
+    }
+
+    fn rename(&self, _from: &Path, _to: &Path) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn remove_file(&self, _path: &Path) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn remove_dir_all(&self, _path: &Path) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn copy(&self, from: &Path, _to: &Path) -> io::Result<u64> {
+        // The file may only have been written in the dry run.
+        Ok(RealFs.metadata(from).map_or(0, |metadata| metadata.len))
+    }
+
+    fn hard_link(&self, _original: &Path, _link: &Path) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// A [`Vfs`] which keeps every file and directory in memory.
+///
+/// Relative paths are treated as relative to the root, and `.` components
+/// are ignored.
+///
+/// # Examples
+/// ```
+/// use extract_minecraft_assets::vfs::{MemoryFs, Vfs};
+/// use std::path::Path;
+///
+/// let fs = MemoryFs::new();
+/// fs.create_dir_all(Path::new("extracted/assets"))?;
+/// fs.write_file(Path::new("extracted/assets/pack.mcmeta"), &mut "{}".as_bytes())?;
+///
+/// assert_eq!(fs.read(Path::new("extracted/assets/pack.mcmeta")), Some(b"{}".to_vec()));
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Default, Debug)]
+pub struct MemoryFs {
+    entries: Mutex<BTreeMap<PathBuf, Entry>>,
+}
+
+/// A file or directory in a [`MemoryFs`].
+#[derive(Clone, Debug)]
+enum Entry {
+    Dir {
+        mode: Option<u32>,
+    },
+    File {
+        contents: Vec<u8>,
+        mode: Option<u32>,
+    },
+}
+
+impl MemoryFs {
+    /// Returns an empty [`MemoryFs`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn entries(&self) -> MutexGuard<'_, BTreeMap<PathBuf, Entry>> {
+        // An entry is only ever replaced whole, so a poisoned map is still consistent
+        self.entries
+            .lock()
+            .unwrap_or_else(|error| error.into_inner())
+    }
+
+    /// Returns the contents of the file at `path`, if there is one.
+    pub fn read(&self, path: &Path) -> Option<Vec<u8>> {
+        match self.entries().get(&normalize(path)) {
+            Some(Entry::File { contents, .. }) => Some(contents.clone()),
+            _ => None,
+        }
+    }
+
+    /// Returns the unix permissions of the file or directory at `path`, if
+    /// they were set.
+    pub fn unix_mode(&self, path: &Path) -> Option<u32> {
+        match self.entries().get(&normalize(path))? {
+            Entry::Dir { mode } | Entry::File { mode, .. } => *mode,
+        }
+    }
+
+    /// Returns the paths of every file, in order.
+    pub fn files(&self) -> Vec<PathBuf> {
+        self.entries()
+            .iter()
+            .filter(|(_, entry)| matches!(entry, Entry::File { .. }))
+            .map(|(path, _)| path.clone())
+            .collect()
+    }
+}
+
+/// Removes `.` components from `path`, and makes it relative to the root.
+fn normalize(path: &Path) -> PathBuf {
+    path.components()
+        .filter(|component| matches!(component, Component::Normal(_) | Component::ParentDir))
+        .collect()
+}
+
+fn not_found(path: &Path) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("'{}' does not exist", path.display()),
+    )
+}
+
+/// Fails unless the parent directory of `path` exists in `entries`.
+fn check_parent(entries: &BTreeMap<PathBuf, Entry>, path: &Path) -> io::Result<()> {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => match entries.get(parent) {
+            Some(Entry::Dir { .. }) => Ok(()),
+            Some(Entry::File { .. }) => Err(io::Error::other(format!(
+                "'{}' is not a directory",
+                parent.display()
+            ))),
+            None => Err(not_found(parent)),
+        },
+        _ => Ok(()),
+    }
+}
+
+impl Vfs for MemoryFs {
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        let path = normalize(path);
+        let mut entries = self.entries();
+
+        for dir in path.ancestors().filter(|dir| !dir.as_os_str().is_empty()) {
+            match entries.get(dir) {
+                Some(Entry::Dir { .. }) => {}
+                Some(Entry::File { .. }) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::AlreadyExists,
+                        format!("'{}' is a file", dir.display()),
+                    ));
+                }
+                None => {
+                    entries.insert(dir.to_owned(), Entry::Dir { mode: None });
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn write_file(&self, path: &Path, contents: &mut dyn Read) -> io::Result<u64> {
+        let path = normalize(path);
+        let mut buffer = Vec::new();
+        let bytes = contents.read_to_end(&mut buffer)? as u64;
+
+        let mut entries = self.entries();
+        check_parent(&entries, &path)?;
+
+        if let Some(Entry::Dir { .. }) = entries.get(&path) {
+            return Err(io::Error::other(format!(
+                "'{}' is a directory",
+                path.display()
+            )));
+        }
+
+        entries.insert(
+            path,
+            Entry::File {
+                contents: buffer,
+                mode: None,
+            },
+        );
+
+        Ok(bytes)
+    }
+
+    fn set_unix_mode(&self, path: &Path, new_mode: u32) -> io::Result<()> {
+        match self.entries().get_mut(&normalize(path)) {
+            Some(Entry::Dir { mode } | Entry::File { mode, .. }) => {
+                *mode = Some(new_mode);
+
+                Ok(())
+            }
+            None => Err(not_found(path)),
+        }
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<Metadata> {
+        match self.entries().get(&normalize(path)) {
+            Some(Entry::Dir { .. }) => Ok(Metadata {
+                is_dir: true,
+                len: 0,
+            }),
+            Some(Entry::File { contents, .. }) => Ok(Metadata {
+                is_dir: false,
+                len: contents.len() as u64,
+            }),
+            None => Err(not_found(path)),
+        }
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        let (from, to) = (normalize(from), normalize(to));
+        let mut entries = self.entries();
+        check_parent(&entries, &to)?;
+
+        match entries.remove(&from) {
+            Some(entry @ Entry::File { .. }) => {
+                entries.insert(to, entry);
+
+                Ok(())
+            }
+            Some(entry) => {
+                entries.insert(from.clone(), entry);
+
+                Err(io::Error::other(format!(
+                    "'{}' is a directory",
+                    from.display()
+                )))
+            }
+            None => Err(not_found(&from)),
+        }
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        let path = normalize(path);
+        let mut entries = self.entries();
+
+        match entries.get(&path) {
+            Some(Entry::File { .. }) => {
+                entries.remove(&path);
+
+                Ok(())
+            }
+            Some(Entry::Dir { .. }) => Err(io::Error::other(format!(
+                "'{}' is a directory",
+                path.display()
+            ))),
+            None => Err(not_found(&path)),
+        }
+    }
+
+    fn remove_dir_all(&self, path: &Path) -> io::Result<()> {
+        let path = normalize(path);
+        let mut entries = self.entries();
+
+        match entries.get(&path) {
+            Some(Entry::Dir { .. }) => {
+                entries.retain(|entry, _| !entry.starts_with(&path));
+
+                Ok(())
+            }
+            Some(Entry::File { .. }) => Err(io::Error::other(format!(
+                "'{}' is not a directory",
+                path.display()
+            ))),
+            None => Err(not_found(&path)),
+        }
+    }
+
+    fn copy(&self, from: &Path, to: &Path) -> io::Result<u64> {
+        let (from, to) = (normalize(from), normalize(to));
+        let mut entries = self.entries();
+        check_parent(&entries, &to)?;
+
+        match entries.get(&from) {
+            Some(Entry::File { contents, mode }) => {
+                let bytes = contents.len() as u64;
+                let file = Entry::File {
+                    contents: contents.clone(),
+                    mode: *mode,
+                };
+                entries.insert(to, file);
+
+                Ok(bytes)
+            }
+            Some(Entry::Dir { .. }) => Err(io::Error::other(format!(
+                "'{}' is a directory",
+                from.display()
+            ))),
+            None => Err(not_found(&from)),
+        }
+    }
+}
+
+/// A shared [`Vfs`], as held by [`ExtractOptions`](crate::options::ExtractOptions).
+///
+/// Handles are equal if they share the same [`Vfs`].
+#[derive(Clone)]
+pub struct SharedVfs(pub Arc<dyn Vfs>);
+
+/// The real filesystem.
+impl Default for SharedVfs {
+    fn default() -> Self {
+        Self(Arc::new(RealFs))
+    }
+}
+
+impl PartialEq for SharedVfs {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for SharedVfs {}
+
+impl Hash for SharedVfs {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Arc::as_ptr(&self.0).cast::<()>().hash(state);
+    }
+}
+
+impl Debug for SharedVfs {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("SharedVfs").finish_non_exhaustive()
+    }
+}
+
+impl std::ops::Deref for SharedVfs {
+    type Target = dyn Vfs;
+
+    fn deref(&self) -> &Self::Target {
+        &*self.0
+    }
+}
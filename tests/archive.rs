@@ -0,0 +1,112 @@
+mod common;
+
+use common::JarBuilder;
+use extract_minecraft_assets::archive;
+use extract_minecraft_assets::error::ExtractError;
+use extract_minecraft_assets::jar::{self, ExtractedContents};
+use extract_minecraft_assets::options::ExtractOptions;
+use extract_minecraft_assets::progress::NoProgress;
+use extract_minecraft_assets::vfs::MemoryFs;
+use std::collections::BTreeSet;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Returns `len` bytes which don't compress.
+fn noise(len: usize) -> Vec<u8> {
+    let mut state = 0x2545_f491_u32;
+
+    (0..len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state as u8
+        })
+        .collect()
+}
+
+#[test]
+fn volumes_are_standalone_and_within_the_limit() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client()
+        .file("assets/minecraft/textures/block/stone.png", noise(3000))
+        .file("assets/minecraft/textures/block/dirt.png", noise(2000))
+        .file("assets/minecraft/sounds/step.ogg", noise(1500))
+        .write(&dir.path().join("client.jar"));
+
+    let fs = Arc::new(MemoryFs::new());
+    let options = ExtractOptions::new()
+        .output("out")
+        .vfs(fs.clone())
+        .contents(ExtractedContents::all());
+    let report = jar::extract_jar(&jar, &options, &mut NoProgress).unwrap();
+
+    let path = dir.path().join("pack.zip");
+    let volumes = archive::write_archive(&fs, &report, &path, Some(4000)).unwrap();
+
+    assert!(volumes.len() > 1);
+    assert!(!path.exists());
+    assert_eq!(volumes[0].path, dir.path().join("pack.part1.zip"));
+
+    let mut archived = BTreeSet::new();
+    for volume in &volumes {
+        assert!(volume.size <= 4000, "{volume:?}");
+        assert_eq!(fs::metadata(&volume.path).unwrap().len(), volume.size);
+
+        let mut zip = zip::ZipArchive::new(File::open(&volume.path).unwrap()).unwrap();
+        for file in &volume.files {
+            let name = file.to_str().unwrap().replace('\\', "/");
+            let mut contents = Vec::new();
+            zip.by_name(&name)
+                .unwrap()
+                .read_to_end(&mut contents)
+                .unwrap();
+
+            assert_eq!(Some(contents), fs.read(&PathBuf::from("out").join(file)));
+            assert!(archived.insert(file.clone()));
+        }
+        assert_eq!(zip.len(), volume.files.len());
+    }
+    assert_eq!(archived.len(), report.summary.files);
+}
+
+#[test]
+fn a_file_larger_than_a_volume_is_refused() {
+    let files = [
+        (PathBuf::from("small.json"), 10),
+        (PathBuf::from("large.ogg"), 5000),
+    ];
+
+    let error = archive::plan_volumes(&files, Some(4000)).unwrap_err();
+
+    let ExtractError::TooLargeForVolume { path, size, limit } = error else {
+        panic!("unexpected error: {error}");
+    };
+    assert_eq!(
+        (path, size, limit),
+        (PathBuf::from("large.ogg"), 5000, 4000)
+    );
+}
+
+#[test]
+fn an_unsplit_archive_is_written_to_its_path() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client().write(&dir.path().join("client.jar"));
+
+    let fs = Arc::new(MemoryFs::new());
+    let options = ExtractOptions::new().output("out").vfs(fs.clone());
+    let report = jar::extract_jar(&jar, &options, &mut NoProgress).unwrap();
+
+    let path = dir.path().join("pack.zip");
+    let volumes = archive::write_archive(&fs, &report, &path, None).unwrap();
+
+    assert_eq!(volumes.len(), 1);
+    assert_eq!(volumes[0].path, path);
+    let zip = zip::ZipArchive::new(File::open(&path).unwrap()).unwrap();
+    assert!(
+        zip.file_names()
+            .any(|name| name == "assets/minecraft/lang/en_us.json")
+    );
+}
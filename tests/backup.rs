@@ -0,0 +1,199 @@
+mod common;
+
+use common::{AssetStore, AssetStoreBuilder};
+use extract_minecraft_assets::backup::{AssetsBackup, BackupFormat};
+use extract_minecraft_assets::error::ExtractError;
+use extract_minecraft_assets::hashed::{self, IndexFileLocation};
+use extract_minecraft_assets::options::ExtractOptions;
+use extract_minecraft_assets::progress::NoProgress;
+use extract_minecraft_assets::report::{FileOutcome, FileRecord};
+use extract_minecraft_assets::vfs::MemoryFs;
+use flate2::write::GzEncoder;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use zip::ZipWriter;
+use zip::write::SimpleFileOptions;
+
+/// Returns the paths of the files within `dir`, relative to it, sorted.
+fn files_within(dir: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        let name = PathBuf::from(path.file_name().unwrap());
+
+        if path.is_dir() {
+            files.extend(files_within(&path).into_iter().map(|file| name.join(file)));
+        } else {
+            files.push(name);
+        }
+    }
+    files.sort();
+
+    files
+}
+
+/// Archives the files of `store` into `archive` in `format`, within the
+/// `wrapper` directory.
+fn back_up(store: &AssetStore, archive: &Path, format: BackupFormat, wrapper: &str) -> PathBuf {
+    let files = files_within(&store.dir);
+    let name = |file: &Path| {
+        let file = file.to_str().unwrap().replace('\\', "/");
+        format!("{wrapper}{file}")
+    };
+
+    match format {
+        BackupFormat::Zip => {
+            let mut zip = ZipWriter::new(File::create(archive).unwrap());
+            for file in &files {
+                zip.start_file(name(file), SimpleFileOptions::default())
+                    .unwrap();
+                zip.write_all(&fs::read(store.dir.join(file)).unwrap())
+                    .unwrap();
+            }
+            zip.finish().unwrap();
+        }
+        BackupFormat::Tar | BackupFormat::TarGz => {
+            let file = File::create(archive).unwrap();
+            let writer: Box<dyn Write> = match format {
+                BackupFormat::TarGz => Box::new(GzEncoder::new(file, Default::default())),
+                _ => Box::new(file),
+            };
+            let mut tar = tar::Builder::new(writer);
+            for file in &files {
+                tar.append_path_with_name(store.dir.join(file), name(file))
+                    .unwrap();
+            }
+            tar.into_inner().unwrap().flush().unwrap();
+        }
+    }
+
+    archive.to_owned()
+}
+
+/// Extracts from `backup` and from `store` itself, asserting that both
+/// write the same files and report the same outcomes.
+fn assert_extracts_like_the_directory(backup: &AssetsBackup, store: &AssetStore) {
+    let index = backup
+        .index(&IndexFileLocation::Version("5".to_owned()))
+        .unwrap();
+    assert_eq!(index, store.index());
+
+    let backup_fs = Arc::new(MemoryFs::new());
+    let options = ExtractOptions::new().output("out").vfs(backup_fs.clone());
+    let mut report = backup
+        .extract_hashed_assets(&index, &options, &mut NoProgress)
+        .unwrap();
+
+    let dir_fs = Arc::new(MemoryFs::new());
+    let options = ExtractOptions::new().output("out").vfs(dir_fs.clone());
+    let mut expected =
+        hashed::extract_hashed_assets(&store.dir, &index, &options, &mut NoProgress).unwrap();
+
+    assert_eq!(backup_fs.files(), dir_fs.files());
+    for file in dir_fs.files() {
+        assert_eq!(backup_fs.read(&file), dir_fs.read(&file));
+    }
+
+    report.files.sort_by(|a, b| a.path.cmp(&b.path));
+    expected.files.sort_by(|a, b| a.path.cmp(&b.path));
+    let outcomes = |files: &[FileRecord]| -> Vec<_> {
+        files
+            .iter()
+            .map(|file| {
+                (
+                    file.path.clone(),
+                    matches!(file.outcome, FileOutcome::Written { .. }),
+                )
+            })
+            .collect()
+    };
+    assert_eq!(outcomes(&report.files), outcomes(&expected.files));
+    assert_eq!(report.summary.failures, 1);
+}
+
+#[test]
+fn zip_backups_extract_like_the_directory() {
+    let dir = common::temp_dir();
+    let store = AssetStoreBuilder::typical().write(&dir.path().join("assets"));
+    let archive = back_up(
+        &store,
+        &dir.path().join("backup.zip"),
+        BackupFormat::Zip,
+        "backup/.minecraft/assets/",
+    );
+
+    let backup = AssetsBackup::open(&archive).unwrap();
+    assert_eq!(backup.format(), BackupFormat::Zip);
+    assert_eq!(backup.index_ids().collect::<Vec<_>>(), ["5"]);
+
+    assert_extracts_like_the_directory(&backup, &store);
+}
+
+#[test]
+fn tar_backups_extract_like_the_directory() {
+    let dir = common::temp_dir();
+    let store = AssetStoreBuilder::typical().write(&dir.path().join("assets"));
+
+    // Named misleadingly, so that only the magic bytes give the format away.
+    for (format, name, wrapper) in [
+        (BackupFormat::Tar, "backup.zip", ""),
+        (BackupFormat::TarGz, "backup.tar.gz", "./assets/"),
+    ] {
+        let archive = back_up(&store, &dir.path().join(name), format, wrapper);
+
+        let backup = AssetsBackup::open(&archive).unwrap();
+        assert_eq!(backup.format(), format);
+
+        assert_extracts_like_the_directory(&backup, &store);
+    }
+}
+
+#[test]
+fn missing_objects_and_indexes_are_reported() {
+    let dir = common::temp_dir();
+    let store = AssetStoreBuilder::typical().write(&dir.path().join("assets"));
+    let archive = back_up(
+        &store,
+        &dir.path().join("backup.tgz"),
+        BackupFormat::TarGz,
+        "assets/",
+    );
+    let backup = AssetsBackup::open(&archive).unwrap();
+
+    let error = backup
+        .index(&IndexFileLocation::Version("6".to_owned()))
+        .unwrap_err();
+    assert!(
+        matches!(&error, ExtractError::MissingIndex { suggestions, .. } if suggestions == &["5"]),
+        "{error}"
+    );
+
+    let index = store.index();
+    let memory = Arc::new(MemoryFs::new());
+    let options = ExtractOptions::new().output("out").vfs(memory.clone());
+    let report = backup
+        .extract_hashed_assets(&index, &options, &mut NoProgress)
+        .unwrap();
+
+    let missing = report
+        .files
+        .iter()
+        .find(|file| file.path.ends_with("missing.ogg"))
+        .unwrap();
+    let FileOutcome::Failed { error } = &missing.outcome else {
+        panic!("{:?}", missing.outcome);
+    };
+    assert!(error.contains("backup.tgz"), "{error}");
+    assert!(error.contains("not found"), "{error}");
+    assert_eq!(memory.files().len(), 2);
+
+    let not_a_backup = dir.path().join("notes.txt");
+    fs::write(&not_a_backup, "just some notes").unwrap();
+    assert!(matches!(
+        AssetsBackup::open(&not_a_backup),
+        Err(ExtractError::ReadBackup { .. })
+    ));
+}
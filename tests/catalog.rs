@@ -0,0 +1,111 @@
+mod common;
+
+use common::{AssetStoreBuilder, JarBuilder};
+use extract_minecraft_assets::catalog::Catalog;
+use extract_minecraft_assets::category::Category;
+use extract_minecraft_assets::options::ExtractOptions;
+use extract_minecraft_assets::progress::NoProgress;
+use extract_minecraft_assets::search::FileSource;
+use extract_minecraft_assets::stats::Total;
+use extract_minecraft_assets::vfs::MemoryFs;
+use extract_minecraft_assets::{hashed, jar};
+use std::path::Path;
+use std::sync::Arc;
+
+#[test]
+fn extracted_files_are_grouped_by_namespace_and_category() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client()
+        .file("assets/minecraft/textures/block/stone.png", "PNG stone")
+        .file("assets/realms/textures/invite.png", "PNG")
+        .write(&dir.path().join("client.jar"));
+    let store = AssetStoreBuilder::new()
+        .file("minecraft/lang/en_us.json", "{\"menu.quit\": \"Quit\"}")
+        .file("minecraft/sounds/ambient/cave/cave1.ogg", "OGG cave")
+        .write(&dir.path().join("assets"));
+
+    let fs = Arc::new(MemoryFs::new());
+    let options = ExtractOptions::new().output("out").vfs(fs.clone());
+    let report = jar::extract_jar(&jar, &options, &mut NoProgress).unwrap()
+        + hashed::extract_hashed_assets(&store.dir, &store.index(), &options, &mut NoProgress)
+            .unwrap();
+    let catalog = Catalog::from_report(&report);
+
+    let minecraft = &catalog.namespaces["minecraft"];
+    assert_eq!(
+        minecraft.keys().copied().collect::<Vec<_>>(),
+        [
+            Category::Textures,
+            Category::Sounds,
+            Category::Lang,
+            Category::Texts,
+            Category::Other
+        ]
+    );
+    assert_eq!(
+        catalog.namespaces["realms"][&Category::Textures].total,
+        Total { files: 1, bytes: 3 }
+    );
+
+    // The hashed language file replaces the jar's, and is listed once.
+    let lang = &minecraft[&Category::Lang].files;
+    assert_eq!(lang.len(), 1);
+    assert_eq!(lang[0].source, FileSource::Hashed);
+    assert_eq!(
+        lang[0].hash,
+        Some(
+            store.index().objects[Path::new("minecraft/lang/en_us.json")]
+                .hash
+                .clone()
+        )
+    );
+
+    let textures = &minecraft[&Category::Textures].files;
+    assert_eq!(
+        textures[0].path,
+        Path::new("assets/minecraft/textures/block/stone.png")
+    );
+    assert_eq!(textures[0].source, FileSource::Jar);
+    assert_eq!(textures[0].hash, None);
+
+    // Every file written is listed once, including the language file written
+    // from both the jar file and the index.
+    assert_eq!(catalog.total.files, report.summary.files - 1);
+}
+
+#[test]
+fn markdown_is_sorted() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client()
+        .file("assets/minecraft/textures/block/b.png", "PNG b")
+        .file("assets/minecraft/textures/block/a.png", "PNG a")
+        .file("assets/minecraft/models/block/a.json", "{}")
+        .write(&dir.path().join("client.jar"));
+
+    let fs = Arc::new(MemoryFs::new());
+    let options = ExtractOptions::new()
+        .output("out")
+        .vfs(fs.clone())
+        .ignore_top_level(true)
+        .contents(jar::ExtractedContents::assets());
+    let report = jar::extract_jar(&jar, &options, &mut NoProgress).unwrap();
+
+    let mut markdown = Vec::new();
+    Catalog::from_report(&report)
+        .write_markdown(&mut markdown)
+        .unwrap();
+    let markdown = String::from_utf8(markdown).unwrap();
+
+    assert!(markdown.starts_with("# Catalog\n\n6 files ("), "{markdown}");
+    let position = |text: &str| {
+        markdown
+            .find(text)
+            .unwrap_or_else(|| panic!("{text} not in {markdown}"))
+    };
+    assert!(position("## minecraft") < position("### textures"));
+    assert!(position("### textures") < position("### models"));
+    assert!(
+        position("`minecraft/textures/block/a.png`") < position("`minecraft/textures/block/b.png`")
+    );
+    assert!(markdown.contains("| `minecraft/models/block/a.json` | 2 B | jar |  |\n"));
+}
@@ -0,0 +1,162 @@
+mod common;
+
+use common::{AssetStoreBuilder, JarBuilder};
+use extract_minecraft_assets::clean::{self, RecordedExtraction};
+use extract_minecraft_assets::jar::ExtractedContents;
+use extract_minecraft_assets::options::ExtractOptions;
+use extract_minecraft_assets::progress::NoProgress;
+use extract_minecraft_assets::report::ExtractionReport;
+use extract_minecraft_assets::{hashed, jar};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Serializes `report` and reads it back, as `clean-run` does.
+fn recorded(report: &ExtractionReport) -> RecordedExtraction {
+    let json = serde_json::to_vec(report).unwrap();
+
+    RecordedExtraction::from_reader(json.as_slice()).unwrap()
+}
+
+#[test]
+fn only_the_files_written_are_deleted() {
+    let dir = common::temp_dir();
+    let output_dir = dir.path().join("out");
+    let jar = JarBuilder::client().write(&dir.path().join("client.jar"));
+    let options = ExtractOptions::new()
+        .output(&output_dir)
+        .contents(ExtractedContents::all());
+
+    fs::create_dir_all(output_dir.join("assets/minecraft/lang")).unwrap();
+    fs::write(output_dir.join("assets/minecraft/lang/notes.txt"), "mine\n").unwrap();
+    let report = jar::extract_jar(&jar, &options, &mut NoProgress).unwrap();
+
+    let plan = clean::plan(&recorded(&report)).unwrap();
+    assert_eq!(plan.unchanged.len(), report.summary.files);
+    assert!(plan.modified.is_empty() && plan.missing.is_empty());
+    assert_eq!(plan.apply(&output_dir, false).unwrap(), 5);
+
+    // Directories are only removed once empty, and the output directory is
+    // kept.
+    assert!(!output_dir.join("data").exists());
+    assert!(!output_dir.join("assets/minecraft/texts").exists());
+    assert!(output_dir.join("assets/minecraft/lang/notes.txt").is_file());
+    assert!(output_dir.is_dir());
+}
+
+#[test]
+fn modified_files_are_kept_unless_forced() {
+    let dir = common::temp_dir();
+    let output_dir = dir.path().join("out");
+    let store = AssetStoreBuilder::new()
+        .file(
+            "minecraft/lang/de_de.json",
+            "{\"menu.quit\": \"Spiel beenden\"}\n",
+        )
+        .file(
+            "minecraft/lang/fr_fr.json",
+            "{\"menu.quit\": \"Quitter le jeu\"}\n",
+        )
+        .file("minecraft/sounds/ambient/cave/cave1.ogg", "OggS")
+        .write(&dir.path().join("assets"));
+    let options = ExtractOptions::new().output(&output_dir);
+    let report =
+        hashed::extract_hashed_assets(&store.dir, &store.index(), &options, &mut NoProgress)
+            .unwrap();
+
+    // Same size, different contents, so only the hash gives it away.
+    let same_size = Path::new("assets/minecraft/lang/de_de.json");
+    fs::write(
+        output_dir.join(same_size),
+        "{\"menu.quit\": \"Spiel_beenden\"}\n",
+    )
+    .unwrap();
+    let resized = Path::new("assets/minecraft/lang/fr_fr.json");
+    fs::write(output_dir.join(resized), "{}\n").unwrap();
+    let removed = Path::new("assets/minecraft/sounds/ambient/cave/cave1.ogg");
+    fs::remove_file(output_dir.join(removed)).unwrap();
+
+    let plan = clean::plan(&recorded(&report)).unwrap();
+    assert_eq!(plan.modified, [same_size, resized]);
+    assert_eq!(plan.missing, [removed]);
+    assert!(plan.unchanged.is_empty());
+
+    assert_eq!(plan.apply(&output_dir, false).unwrap(), 0);
+    assert!(output_dir.join(same_size).is_file());
+    assert!(output_dir.join(resized).is_file());
+
+    assert_eq!(plan.deleted(true).collect::<Vec<_>>(), [same_size, resized]);
+    assert_eq!(plan.apply(&output_dir, true).unwrap(), 2);
+    assert!(!output_dir.join("assets").exists());
+}
+
+#[test]
+fn drift_since_the_extraction_is_found() {
+    let dir = common::temp_dir();
+    let output_dir = dir.path().join("out");
+    let store = AssetStoreBuilder::new()
+        .file(
+            "minecraft/lang/de_de.json",
+            "{\"menu.quit\": \"Spiel beenden\"}\n",
+        )
+        .file("minecraft/sounds/ambient/cave/cave1.ogg", "OggS")
+        .file("minecraft/texts/splashes.txt", "Hello!\n")
+        .write(&dir.path().join("assets"));
+    let options = ExtractOptions::new().output(&output_dir);
+    let report =
+        hashed::extract_hashed_assets(&store.dir, &store.index(), &options, &mut NoProgress)
+            .unwrap();
+    fs::write(output_dir.join("unrelated.txt"), "mine\n").unwrap();
+
+    let extraction = recorded(&report);
+    let verification = clean::verify(&extraction, false).unwrap();
+    assert!(verification.is_clean(), "{verification:?}");
+    assert_eq!(verification.unchanged.len(), 3);
+
+    let modified = Path::new("assets/minecraft/lang/de_de.json");
+    fs::write(
+        output_dir.join(modified),
+        "{\"menu.quit\": \"Spiel_beenden\"}\n",
+    )
+    .unwrap();
+    let removed = Path::new("assets/minecraft/sounds/ambient/cave/cave1.ogg");
+    fs::remove_file(output_dir.join(removed)).unwrap();
+    let added = Path::new("assets/minecraft/texts/end.txt");
+    fs::write(output_dir.join(added), "The End\n").unwrap();
+
+    let verification = clean::verify(&extraction, false).unwrap();
+    assert!(!verification.is_clean());
+    assert_eq!(verification.modified, [modified]);
+    assert_eq!(verification.missing, [removed]);
+    // Files outside of the directories the extraction wrote to aren't
+    // untracked.
+    assert_eq!(verification.untracked, [added]);
+
+    // The modified file has the same size.
+    let verification = clean::verify(&extraction, true).unwrap();
+    assert!(verification.modified.is_empty());
+    assert_eq!(verification.unchanged.len(), 2);
+}
+
+#[test]
+fn paths_outside_the_output_directory_are_never_deleted() {
+    let report = r#"{
+        "files": [
+            {"path": "../escape.txt", "outcome": "written", "bytes": 3, "source": "jar"},
+            {"path": "/etc/hostname", "outcome": "written", "bytes": 3, "source": "jar"},
+            {"path": "assets/skipped.txt", "outcome": "skipped", "source": "jar"},
+            {"path": "assets/kept.txt", "outcome": "written", "bytes": 3, "source": "jar"}
+        ],
+        "options": {"outputDir": "out"}
+    }"#;
+    let extraction = RecordedExtraction::from_reader(report.as_bytes()).unwrap();
+
+    assert_eq!(extraction.skipped, 3);
+    assert_eq!(
+        extraction
+            .files
+            .iter()
+            .map(|file| &file.path)
+            .collect::<Vec<_>>(),
+        [&PathBuf::from("assets/kept.txt")]
+    );
+}
@@ -0,0 +1,2484 @@
+//! Snapshot tests of the documents printed by `--json`, so that accidental
+//! schema changes are caught.
+//!
+//! Run with `UPDATE_SNAPSHOTS=1` to rewrite the snapshots after an
+//! intentional change.
+
+#![cfg(feature = "cli")]
+
+mod common;
+
+use common::{AssetStoreBuilder, JarBuilder};
+use extract_minecraft_assets::util;
+use serde_json::Value;
+use std::env;
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use tempfile::TempDir;
+
+/// Runs the command-line tool with `--json`, extracting into `out` within
+/// `dir`, and returns its exit code and the document it printed.
+///
+/// The home and config directories are set within `dir`, so that the
+/// `.minecraft` directory and config of the machine running the tests
+/// aren't found.
+fn run_json(dir: &TempDir, args: &[&str]) -> (Option<i32>, Value) {
+    let output_dir = dir.path().join("out");
+    fs::create_dir_all(&output_dir).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_extract-minecraft-assets"))
+        .args(args)
+        .arg("--json")
+        .arg("--yes")
+        .arg("--porcelain")
+        .arg("--output")
+        .arg(&output_dir)
+        .env("HOME", dir.path())
+        .env("XDG_CONFIG_HOME", dir.path().join(".config"))
+        .output()
+        .unwrap();
+    let document = serde_json::from_slice(&output.stdout).unwrap_or_else(|error| {
+        panic!(
+            "stdout is not a JSON document ({error}): {}",
+            String::from_utf8_lossy(&output.stdout)
+        )
+    });
+    // No progress is displayed with --porcelain, so stderr only has whole
+    // lines of plain text.
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        !stderr.contains(['\r', '\x1b']),
+        "progress was displayed on stderr: {stderr:?}"
+    );
+
+    (output.status.code(), normalize(document, dir))
+}
+
+/// Runs the command-line tool with `--porcelain` like [`run_json`], but
+/// without `--json`, and returns its exit code, stdout and stderr with the
+/// path of `dir` replaced by `<temp>`.
+fn run_porcelain(dir: &TempDir, args: &[&str]) -> (Option<i32>, String, String) {
+    let output_dir = dir.path().join("out");
+    fs::create_dir_all(&output_dir).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_extract-minecraft-assets"))
+        .args(args)
+        .arg("--yes")
+        .arg("--porcelain")
+        .arg("--output")
+        .arg(&output_dir)
+        .env("HOME", dir.path())
+        .env("XDG_CONFIG_HOME", dir.path().join(".config"))
+        .output()
+        .unwrap();
+    let normalize = |bytes: Vec<u8>| {
+        String::from_utf8(bytes)
+            .unwrap()
+            .replace(dir.path().to_str().unwrap(), "<temp>")
+    };
+
+    (
+        output.status.code(),
+        normalize(output.stdout),
+        normalize(output.stderr),
+    )
+}
+
+/// Replaces the parts of `document` which vary between runs: paths, the
+/// duration, and the order of hashed assets.
+fn normalize(document: Value, dir: &TempDir) -> Value {
+    let json = serde_json::to_string(&document)
+        .unwrap()
+        .replace(dir.path().to_str().unwrap(), "<temp>");
+    let mut document: Value = serde_json::from_str(&json).unwrap();
+
+    if let Some(duration) = document.pointer_mut("/summary/duration") {
+        *duration = Value::from("<duration>");
+    }
+    if let Some(Value::Array(files)) = document.get_mut("files") {
+        files.sort_by_key(|file| file["path"].as_str().map(str::to_owned));
+    }
+
+    document
+}
+
+/// Compares `document` to the snapshot `name`, or rewrites the snapshot if
+/// `UPDATE_SNAPSHOTS` is set.
+fn assert_snapshot(name: &str, document: &Value) {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/snapshots")
+        .join(name)
+        .with_extension("json");
+    let actual = serde_json::to_string_pretty(document).unwrap() + "\n";
+
+    if env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        fs::write(&path, actual).unwrap();
+    } else {
+        let expected = fs::read_to_string(&path).unwrap_or_default();
+
+        assert!(
+            expected == actual,
+            "{name} doesn't match its snapshot, rerun with UPDATE_SNAPSHOTS=1 if this is intended\n\
+             expected:\n{expected}\nactual:\n{actual}"
+        );
+    }
+}
+
+#[test]
+fn jar_report() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client().write(&dir.path().join("client.jar"));
+    let (code, document) = run_json(&dir, &["jar", jar.to_str().unwrap(), "--assets", "--data"]);
+
+    assert_eq!(code, Some(0));
+    assert_snapshot("jar_report", &document);
+}
+
+#[test]
+fn hashed_report_with_failures() {
+    let dir = common::temp_dir();
+    let store = AssetStoreBuilder::typical().write(&dir.path().join("assets"));
+    let (code, document) = run_json(
+        &dir,
+        &[
+            "hashed",
+            store.dir.to_str().unwrap(),
+            "--index",
+            store.index_file.to_str().unwrap(),
+        ],
+    );
+
+    assert_eq!(code, Some(2));
+    assert_snapshot("hashed_report_with_failures", &document);
+}
+
+#[test]
+fn version_with_incomplete_hashed_assets_fails() {
+    let dir = common::temp_dir();
+    let version = common::write_manifest(&dir.path().join("versions"), "1.0", "\"assets\": \"5\"");
+    JarBuilder::client().write(&version.join("1.0.jar"));
+    let store = AssetStoreBuilder::new()
+        .file("minecraft/lang/de_de.json", "{}\n")
+        .missing("minecraft/sounds/missing.ogg", "missing")
+        .missing("minecraft/sounds/lost.ogg", "lost")
+        .write(&dir.path().join("assets"));
+    fs::create_dir_all(dir.path().join("out")).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_extract-minecraft-assets"))
+        .args(["version", version.to_str().unwrap(), "--hashed-assets"])
+        .arg(&store.dir)
+        .args(["--assets", "--yes", "--porcelain", "--output"])
+        .arg(dir.path().join("out"))
+        .env("HOME", dir.path())
+        .env("XDG_CONFIG_HOME", dir.path().join(".config"))
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert_eq!(output.status.code(), Some(2), "{stderr}");
+    assert!(
+        stderr.contains("Extraction finished with 2 errors: "),
+        "{stderr}"
+    );
+    assert!(!stderr.contains("Extraction complete"), "{stderr}");
+    // The files which could be extracted still are.
+    assert!(
+        dir.path()
+            .join("out/assets/minecraft/lang/de_de.json")
+            .is_file()
+    );
+}
+
+#[test]
+fn doctor_counts_what_it_finds() {
+    let dir = common::temp_dir();
+    let minecraft_dir = dir.path().join(".minecraft");
+
+    let (code, document) = run_json(&dir, &["doctor"]);
+    assert_eq!(code, Some(1));
+    assert_eq!(
+        document["installations"][0]["minecraftDir"],
+        serde_json::json!({"path": "<temp>/.minecraft", "exists": false})
+    );
+
+    AssetStoreBuilder::typical().write(&minecraft_dir.join("assets"));
+    let version =
+        common::write_manifest(&minecraft_dir.join("versions"), "1.0", "\"assets\": \"5\"");
+    JarBuilder::client().write(&version.join("1.0.jar"));
+    // Version directories without a jar file can't be extracted from.
+    fs::create_dir(minecraft_dir.join("versions/broken")).unwrap();
+
+    let (code, document) = run_json(&dir, &["doctor"]);
+    assert_eq!(code, Some(0), "{document}");
+    let installation = &document["installations"][0];
+    assert_eq!(installation["minecraftDir"]["exists"], true);
+    assert_eq!(installation["hashedAssetsDir"]["exists"], true);
+    assert_eq!(installation["versions"], 1);
+    assert_eq!(installation["indexes"], 1);
+    assert_eq!(document["outputDir"], "<temp>/out");
+    assert_eq!(document["terminal"]["isTerminal"], false);
+}
+
+#[test]
+fn failure() {
+    let dir = common::temp_dir();
+    let jar = dir.path().join("missing.jar");
+    let (code, document) = run_json(&dir, &["jar", jar.to_str().unwrap(), "--assets"]);
+
+    assert_eq!(code, Some(3));
+    assert_snapshot("failure", &document);
+}
+
+#[test]
+fn missing_contents_fail_unless_allowed() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::new()
+        .dir("assets/")
+        .file("assets/minecraft/lang/en_us.json", "{}\n")
+        .file("net/minecraft/server/Main.class", [0xca, 0xfe, 0xba, 0xbe])
+        .file("version.json", "{}\n")
+        .write(&dir.path().join("server.jar"));
+    let args = ["jar", jar.to_str().unwrap(), "--assets", "--data"];
+
+    let (code, document) = run_json(&dir, &args);
+    assert_eq!(code, Some(5));
+    assert_eq!(
+        document["error"],
+        "'<temp>/server.jar' has no data; pass --allow-empty to extract anyway"
+    );
+    assert!(!dir.path().join("out/assets").exists());
+
+    let (code, document) = run_json(&dir, &[&args[..], &["--allow-empty"]].concat());
+    assert_eq!(code, Some(0), "{document}");
+    assert_eq!(document["summary"]["files"], 1);
+}
+
+#[test]
+fn only_the_given_entries_are_extracted() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client()
+        .file("assets/minecraft/textures/block/stone.png", "PNG stone")
+        .file("assets/minecraft/textures/block/dirt.png", "PNG dirt")
+        .write(&dir.path().join("client.jar"));
+    let args = [
+        "jar",
+        jar.to_str().unwrap(),
+        "--assets",
+        "--entry",
+        "assets/minecraft/lang/en_us.json",
+        "--entry",
+        "assets/minecraft/textures",
+    ];
+
+    let (code, document) = run_json(&dir, &args);
+    assert_eq!(code, Some(0), "{document}");
+    assert_eq!(document["summary"]["files"], 3);
+    assert!(!dir.path().join("out/assets/minecraft/shared.json").exists());
+
+    let (code, document) = run_json(
+        &dir,
+        &[&args[..], &["--entry", "assets/minecraft/missing.json"]].concat(),
+    );
+    assert_eq!(code, Some(5));
+    assert_eq!(
+        document["error"],
+        "'<temp>/client.jar' has nothing at 'assets/minecraft/missing.json'"
+    );
+}
+
+/// The lock of a running process is respected, while that of a process which
+/// has exited is reclaimed.
+#[cfg(unix)]
+#[test]
+fn locked_output_dirs_are_refused() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client().write(&dir.path().join("client.jar"));
+    let lock = dir.path().join("out/.extract-lock");
+    fs::create_dir_all(dir.path().join("out")).unwrap();
+    let args = ["jar", jar.to_str().unwrap(), "--data"];
+
+    fs::write(&lock, std::process::id().to_string()).unwrap();
+    let (code, document) = run_json(&dir, &args);
+    assert_eq!(code, Some(1));
+    assert_eq!(
+        document["error"],
+        format!(
+            "the output directory is locked by process {}, as '<temp>/out/.extract-lock' \
+             exists; pass --no-lock to extract anyway, or remove it if no extraction is running",
+            std::process::id()
+        )
+    );
+
+    let (code, document) = run_json(&dir, &[&args[..], &["--no-lock"]].concat());
+    assert_eq!(code, Some(0), "{document}");
+    assert!(lock.exists());
+
+    let mut exited = Command::new("true").spawn().unwrap();
+    exited.wait().unwrap();
+    fs::write(&lock, exited.id().to_string()).unwrap();
+    let (code, document) = run_json(&dir, &args);
+    assert_eq!(code, Some(0), "{document}");
+    assert!(!lock.exists());
+}
+
+#[test]
+fn sources_inside_the_written_directories_are_refused() {
+    let dir = common::temp_dir();
+    let store = AssetStoreBuilder::typical().write(&dir.path().join("out/assets/store"));
+    let args = [
+        "hashed",
+        store.dir.to_str().unwrap(),
+        "--index",
+        store.index_file.to_str().unwrap(),
+    ];
+
+    let (code, document) = run_json(&dir, &args);
+    assert_eq!(code, Some(1));
+    assert_eq!(
+        document["error"],
+        "the output directory '<temp>/out' contains '<temp>/out/assets/store'; pass \
+         --unsafe-allow-overlap to extract there anyway"
+    );
+
+    let (code, document) = run_json(&dir, &[&args[..], &["--unsafe-allow-overlap"]].concat());
+    assert_eq!(code, Some(2), "{document}");
+}
+
+/// A symlink to the version directory is still recognized as being inside it.
+#[cfg(unix)]
+#[test]
+fn output_inside_the_version_dir_is_refused() {
+    let dir = common::temp_dir();
+    let version = common::write_manifest(&dir.path().join("versions"), "1.0", "\"assets\": \"5\"");
+    JarBuilder::client().write(&version.join("1.0.jar"));
+    std::os::unix::fs::symlink(&version, dir.path().join("out")).unwrap();
+
+    let (code, document) = run_json(&dir, &["version", version.to_str().unwrap(), "--data"]);
+
+    assert_eq!(code, Some(1));
+    assert_eq!(
+        document["error"],
+        "the output directory '<temp>/versions/1.0' is inside '<temp>/versions/1.0'; pass \
+         --unsafe-allow-overlap to extract there anyway"
+    );
+    assert!(!version.join("data").exists());
+}
+
+/// Output directories which don't exist yet are compared by their nearest
+/// existing ancestor.
+#[test]
+fn output_inside_the_minecraft_dir_is_refused() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client().write(&dir.path().join("client.jar"));
+    fs::create_dir_all(dir.path().join(".minecraft/saves")).unwrap();
+    let output_dir = dir.path().join(".minecraft/saves/../extracted/assets");
+    let extract = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_extract-minecraft-assets"))
+            .args([
+                "jar",
+                jar.to_str().unwrap(),
+                "--data",
+                "--json",
+                "--yes",
+                "--output",
+            ])
+            .arg(&output_dir)
+            .args(args)
+            .env("HOME", dir.path())
+            .env("XDG_CONFIG_HOME", dir.path().join(".config"))
+            .output()
+            .unwrap();
+        let document = serde_json::from_slice(&output.stdout).unwrap();
+
+        (output.status.code(), normalize(document, &dir))
+    };
+
+    let (code, document) = extract(&[]);
+    assert_eq!(code, Some(1));
+    assert_eq!(
+        document["error"],
+        "the output directory '<temp>/.minecraft/extracted/assets' is inside '<temp>/.minecraft'; \
+         pass --unsafe-allow-overlap to extract there anyway"
+    );
+    assert!(!dir.path().join(".minecraft/extracted").exists());
+
+    fs::create_dir_all(&output_dir).unwrap();
+    let (code, document) = extract(&["--unsafe-allow-overlap"]);
+    assert_eq!(code, Some(0), "{document}");
+    assert!(output_dir.join("data/minecraft/shared.json").is_file());
+}
+
+#[test]
+fn output_must_be_an_existing_directory() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client().write(&dir.path().join("client.jar"));
+    let extract = |output_dir: &Path| {
+        let output = Command::new(env!("CARGO_BIN_EXE_extract-minecraft-assets"))
+            .args([
+                "jar",
+                jar.to_str().unwrap(),
+                "--assets",
+                "--yes",
+                "--output",
+            ])
+            .arg(output_dir)
+            .env("HOME", dir.path())
+            .env("XDG_CONFIG_HOME", dir.path().join(".config"))
+            .output()
+            .unwrap();
+        let stderr = String::from_utf8(output.stderr)
+            .unwrap()
+            .replace(dir.path().to_str().unwrap(), "<temp>");
+
+        (output.status.code(), stderr)
+    };
+
+    let (code, stderr) = extract(&dir.path().join("missing/deeper"));
+    assert_eq!(code, Some(4), "{stderr}");
+    assert_eq!(
+        stderr,
+        "the output directory '<temp>/missing/deeper' doesn't exist or isn't a directory\n"
+    );
+
+    let (code, stderr) = extract(&jar);
+    assert_eq!(code, Some(4), "{stderr}");
+    assert_eq!(
+        stderr,
+        "the output directory '<temp>/client.jar' doesn't exist or isn't a directory\n"
+    );
+}
+
+#[test]
+fn browsing_needs_a_terminal() {
+    let dir = common::temp_dir();
+    let version = common::write_manifest(&dir.path().join("versions"), "1.0", "\"assets\": \"5\"");
+    JarBuilder::client().write(&version.join("1.0.jar"));
+
+    let (code, document) = run_json(&dir, &["browse", version.to_str().unwrap()]);
+
+    assert_eq!(code, Some(1));
+    assert_eq!(
+        document["error"],
+        "browse requires stdin and stdout to be a terminal"
+    );
+    assert_eq!(fs::read_dir(dir.path().join("out")).unwrap().count(), 0);
+}
+
+#[test]
+fn version_only_needs_hashed_assets_for_assets() {
+    let dir = common::temp_dir();
+    let version = common::write_manifest(&dir.path().join("versions"), "1.0", "\"assets\": \"5\"");
+    JarBuilder::client().write(&version.join("1.0.jar"));
+    let store = AssetStoreBuilder::new()
+        .file("minecraft/lang/de_de.json", "{}\n")
+        .write(&dir.path().join("assets"));
+    let missing = dir.path().join("missing");
+
+    for (contents, hashed_assets_dir, expected) in [
+        (&["--data"][..], &missing, Ok(2)),
+        (&["--data"], &store.dir, Ok(2)),
+        (&["--assets"], &missing, Err(3)),
+        (&["--assets"], &store.dir, Ok(4)),
+        (&["--assets", "--data"], &missing, Err(3)),
+        (&["--assets", "--data"], &store.dir, Ok(6)),
+    ] {
+        let mut args = vec![
+            "version",
+            version.to_str().unwrap(),
+            "--hashed-assets",
+            hashed_assets_dir.to_str().unwrap(),
+        ];
+        args.extend(contents);
+        let (code, document) = run_json(&dir, &args);
+
+        match expected {
+            Ok(files) => {
+                assert_eq!(code, Some(0), "{args:?}: {document}");
+                assert_eq!(document["summary"]["files"], files, "{args:?}");
+            }
+            Err(expected_code) => {
+                assert_eq!(code, Some(expected_code), "{args:?}");
+                assert_eq!(
+                    document["error"],
+                    "no hashed assets directory found at '<temp>/missing'; pass \
+                     --hashed-assets to give its location"
+                );
+            }
+        }
+    }
+}
+
+/// Without --hashed-assets, the default location is probed, which is in the
+/// home directory on Unix.
+#[cfg(unix)]
+#[test]
+fn version_names_probed_hashed_assets_dirs() {
+    let dir = common::temp_dir();
+    let version = common::write_manifest(&dir.path().join("versions"), "1.0", "\"assets\": \"5\"");
+    JarBuilder::client().write(&version.join("1.0.jar"));
+
+    let (code, _) = run_json(&dir, &["version", version.to_str().unwrap(), "--data"]);
+    assert_eq!(code, Some(0));
+
+    let (code, document) = run_json(&dir, &["version", version.to_str().unwrap(), "--assets"]);
+    assert_eq!(code, Some(3));
+    assert!(
+        document["error"]
+            .as_str()
+            .unwrap()
+            .contains("<temp>/.minecraft/assets"),
+        "{document}"
+    );
+}
+
+/// A `.minecraft` directory which is a symlink, with an `assets` directory
+/// which is a symlink too, is found by the default location.
+#[cfg(unix)]
+#[test]
+fn symlinked_minecraft_and_hashed_assets_dirs_are_found() {
+    use std::os::unix::fs::symlink;
+
+    let dir = common::temp_dir();
+    let drive = dir.path().join("drive");
+    let version = common::write_manifest(&drive.join("versions"), "1.0", "\"assets\": \"5\"");
+    JarBuilder::client().write(&version.join("1.0.jar"));
+    let store = AssetStoreBuilder::new()
+        .file("minecraft/lang/de_de.json", "{}\n")
+        .write(&dir.path().join("shared-assets"));
+    symlink(&drive, dir.path().join(".minecraft")).unwrap();
+    symlink(&store.dir, drive.join("assets")).unwrap();
+
+    let (code, document) = run_json(&dir, &["version", "1.0", "--assets"]);
+    assert_eq!(code, Some(0), "{document}");
+    assert_eq!(document["summary"]["files"], 4);
+
+    // A broken symlink is named in the error, along with its target.
+    fs::remove_file(drive.join("assets")).unwrap();
+    symlink(dir.path().join("unmounted"), drive.join("assets")).unwrap();
+
+    let (code, document) = run_json(&dir, &["version", "1.0", "--assets"]);
+    assert_eq!(code, Some(3));
+    assert_eq!(
+        document["error"],
+        "no hashed assets directory found at '<temp>/.minecraft/assets' \
+         ('<temp>/.minecraft/assets' is a broken symlink to '<temp>/unmounted'); \
+         pass --hashed-assets to give its location"
+    );
+}
+
+/// Without a `.minecraft` directory, a version name is reported as such
+/// rather than as a version which isn't installed.
+#[cfg(unix)]
+#[test]
+fn version_names_without_minecraft_dir_are_explained() {
+    let dir = common::temp_dir();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_extract-minecraft-assets"))
+        .args(["version", "1.20.1"])
+        .env("HOME", dir.path())
+        .env("XDG_CONFIG_HOME", dir.path().join(".config"))
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert_eq!(output.status.code(), Some(3), "{stderr}");
+    assert!(
+        stderr.contains(
+            "invalid value '1.20.1' for '<DIRECTORY or VERSION>': no directory exists at that \
+             path, and no `.minecraft` directory was found at"
+        ),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn versions_which_are_not_installed_are_explained() {
+    let dir = common::temp_dir();
+    fs::create_dir_all(dir.path().join(".minecraft/versions/1.0")).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_extract-minecraft-assets"))
+        .args(["version", "9.9"])
+        .env("HOME", dir.path())
+        .env("XDG_CONFIG_HOME", dir.path().join(".config"))
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    assert_eq!(output.status.code(), Some(3), "{stderr}");
+    assert!(stderr.contains("installed versions are '1.0'"), "{stderr}");
+}
+
+#[test]
+fn report_file_matches_json() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client().write(&dir.path().join("client.jar"));
+    let report = dir.path().join("report.json");
+    let (_, document) = run_json(
+        &dir,
+        &[
+            "jar",
+            jar.to_str().unwrap(),
+            "--assets",
+            "--report",
+            report.to_str().unwrap(),
+        ],
+    );
+
+    let report = serde_json::from_slice(&fs::read(&report).unwrap()).unwrap();
+    assert_eq!(normalize(report, &dir), document);
+}
+
+#[test]
+fn report_file_errors_name_the_report_file() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client().write(&dir.path().join("client.jar"));
+    let report = dir.path().join("missing/report.json");
+    let (code, document) = run_json(
+        &dir,
+        &[
+            "jar",
+            jar.to_str().unwrap(),
+            "--assets",
+            "--report",
+            report.to_str().unwrap(),
+        ],
+    );
+
+    assert_eq!(code, Some(4));
+    let error = document["error"].as_str().unwrap();
+    assert!(
+        error.starts_with("failed to write '<temp>/missing/report.json': "),
+        "{error}"
+    );
+}
+
+#[test]
+fn unreadable_manifest_errors_name_the_manifest() {
+    let dir = common::temp_dir();
+    let version = common::write_manifest(&dir.path().join("versions"), "1.0", "\"assets\": ");
+    JarBuilder::client().write(&version.join("1.0.jar"));
+    let (code, document) = run_json(
+        &dir,
+        &[
+            "version",
+            version.to_str().unwrap(),
+            "--hashed-assets",
+            ".",
+            "--assets",
+        ],
+    );
+
+    assert_eq!(code, Some(3));
+    let error = document["error"].as_str().unwrap();
+    assert!(
+        error.starts_with("invalid manifest file '<temp>/versions/1.0/1.0.json': "),
+        "{error}"
+    );
+}
+
+#[test]
+fn diff_index() {
+    let dir = common::temp_dir();
+    let a = AssetStoreBuilder::typical()
+        .index_id("a")
+        .write(&dir.path().join("assets"));
+    let b = AssetStoreBuilder::new()
+        .index_id("b")
+        .file("minecraft/lang/de_de.json", "{}\n")
+        .file("minecraft/sounds/new.ogg", "OggS new sound\n")
+        .write(&dir.path().join("assets"));
+    let (code, document) = run_json(
+        &dir,
+        &[
+            "diff-index",
+            a.index_file.to_str().unwrap(),
+            "b",
+            "--hashed-assets",
+            b.dir.to_str().unwrap(),
+        ],
+    );
+
+    assert_eq!(code, Some(0));
+    assert_snapshot("diff_index", &document);
+}
+
+#[test]
+fn diff_jar() {
+    let dir = common::temp_dir();
+    let a = JarBuilder::client().write(&dir.path().join("a.jar"));
+    let b = JarBuilder::new()
+        .dir("client/")
+        .file(
+            "client/assets/minecraft/lang/en_us.json",
+            "{\"menu.quit\": \"Quit Game\"}\n",
+        )
+        .file(
+            "client/assets/minecraft/texts/splashes.txt",
+            "Also try Terraria and Stardew Valley!\n",
+        )
+        .file("client/assets/minecraft/lang/de_de.json", "{}\n")
+        .write(&dir.path().join("b.jar"));
+    let (code, document) = run_json(
+        &dir,
+        &[
+            "diff-jar",
+            a.to_str().unwrap(),
+            b.to_str().unwrap(),
+            "--assets",
+        ],
+    );
+
+    assert_eq!(code, Some(0));
+    assert_snapshot("diff_jar", &document);
+}
+
+#[test]
+fn diff_output() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client().write(&dir.path().join("client.jar"));
+    let out = dir.path().join("out");
+    let jar = jar.to_str().unwrap();
+
+    let (code, _) = run_json(&dir, &["jar", jar, "--assets"]);
+    assert_eq!(code, Some(0));
+    fs::write(out.join("assets/minecraft/shared.json"), "{}").unwrap();
+    fs::remove_file(out.join("assets/minecraft/texts/splashes.txt")).unwrap();
+
+    let (code, document) = run_json(
+        &dir,
+        &["diff-output", out.to_str().unwrap(), "--against-jar", jar],
+    );
+
+    assert_eq!(code, Some(1));
+    assert_snapshot("diff_output", &document);
+}
+
+#[test]
+fn diff_porcelain() {
+    let dir = common::temp_dir();
+    let a = JarBuilder::client().write(&dir.path().join("a.jar"));
+    let b = JarBuilder::new()
+        .dir("assets/")
+        .file(
+            "assets/minecraft/lang/en_us.json",
+            "{\"menu.quit\": \"Quit Game\"}\n",
+        )
+        .file(
+            "assets/minecraft/texts/splashes.txt",
+            "Also try Terraria and Stardew Valley!\n",
+        )
+        .file("assets/minecraft/lang/de_de.json", "{}\n")
+        .file("pack.mcmeta", "{}\n")
+        .write(&dir.path().join("b.jar"));
+    let (a, b) = (a.to_str().unwrap(), b.to_str().unwrap());
+
+    let (code, stdout, stderr) = run_porcelain(&dir, &["diff-jar", a, b, "--assets"]);
+    assert_eq!(code, Some(0));
+    assert_eq!(
+        stdout,
+        "-\t19\t-\tassets/minecraft/shared.json\n\
+         +\t-\t3\tassets/minecraft/lang/de_de.json\n\
+         ~\t19\t38\tassets/minecraft/texts/splashes.txt\n"
+    );
+    // The totals summarize the paths, so they are on stderr.
+    assert!(stderr.contains("Only in A: 1 files"), "{stderr}");
+
+    let (_, stdout, stderr) = run_porcelain(&dir, &["diff-jar", a, b, "--assets", "--names-only"]);
+    assert_eq!(
+        stdout,
+        "-\tassets/minecraft/shared.json\n\
+         +\tassets/minecraft/lang/de_de.json\n\
+         ~\tassets/minecraft/texts/splashes.txt\n"
+    );
+    assert_eq!(stderr, "");
+
+    let (_, stdout, stderr) = run_porcelain(&dir, &["diff-jar", a, b, "--assets", "--summary"]);
+    assert_eq!(
+        stdout,
+        "only-in-a\t1\t19\nonly-in-b\t1\t3\nchanged\t1\t19\n"
+    );
+    assert_eq!(stderr, "");
+}
+
+#[test]
+fn diff_output_porcelain() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client().write(&dir.path().join("client.jar"));
+    let out = dir.path().join("out");
+    let jar = jar.to_str().unwrap();
+
+    let (code, stdout, stderr) = run_porcelain(&dir, &["jar", jar, "--assets"]);
+    assert_eq!(code, Some(0));
+    // Nothing is listed by an extraction, and it is summarized on stderr.
+    assert_eq!(stdout, "");
+    assert!(stderr.starts_with("Extraction complete: "), "{stderr}");
+    fs::write(out.join("assets/minecraft/shared.json"), "{}").unwrap();
+    fs::remove_file(out.join("assets/minecraft/texts/splashes.txt")).unwrap();
+
+    let (code, stdout, stderr) = run_porcelain(
+        &dir,
+        &["diff-output", out.to_str().unwrap(), "--against-jar", jar],
+    );
+    assert_eq!(code, Some(1));
+    assert_eq!(
+        stdout,
+        "~\tassets/minecraft/shared.json\n-\tassets/minecraft/texts/splashes.txt\n"
+    );
+    assert_eq!(stderr, "Modified: 1, missing: 1, extra: 0\n");
+}
+
+#[test]
+fn stats() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client().write(&dir.path().join("client.jar"));
+    let (code, document) = run_json(&dir, &["stats", jar.to_str().unwrap()]);
+
+    assert_eq!(code, Some(0));
+    assert_snapshot("stats", &document);
+}
+
+#[test]
+fn duplicates() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client()
+        .file("data/minecraft/tags/items/logs.json", "{\"values\": []}\n")
+        .write(&dir.path().join("client.jar"));
+    let (code, document) = run_json(&dir, &["duplicates", jar.to_str().unwrap()]);
+
+    assert_eq!(code, Some(0));
+    assert_snapshot("duplicates", &document);
+}
+
+#[test]
+fn search() {
+    let dir = common::temp_dir();
+    let store = AssetStoreBuilder::typical().write(&dir.path().join("assets"));
+    let (code, document) = run_json(
+        &dir,
+        &[
+            "search",
+            "cave",
+            "--glob",
+            "*.json",
+            "--version",
+            store.index_file.to_str().unwrap(),
+            "--hashed-assets",
+            store.dir.to_str().unwrap(),
+        ],
+    );
+
+    assert_eq!(code, Some(0));
+    assert_snapshot("search", &document);
+}
+
+#[test]
+fn grep_exits_with_1_without_hits() {
+    let dir = common::temp_dir();
+    let store = AssetStoreBuilder::typical().write(&dir.path().join("assets"));
+    let grep = |pattern: &str| {
+        run_json(
+            &dir,
+            &[
+                "grep",
+                pattern,
+                "--version",
+                store.index_file.to_str().unwrap(),
+                "--hashed-assets",
+                store.dir.to_str().unwrap(),
+                "--files-with-matches",
+            ],
+        )
+    };
+
+    let (code, document) = grep("Spiel");
+    assert_eq!(code, Some(0));
+    assert_eq!(
+        document["hits"][0]["path"],
+        "assets/minecraft/lang/de_de.json"
+    );
+    assert_eq!(document["missing"], 0);
+
+    let (code, document) = grep("Creeper");
+    assert_eq!(code, Some(1));
+    assert_eq!(document["hits"], serde_json::json!([]));
+}
+
+#[test]
+fn which_index() {
+    let dir = common::temp_dir();
+    let store = AssetStoreBuilder::typical().write(&dir.path().join("assets"));
+    let hash = common::object(b"OggS fake cave sound\n").hash;
+    let (code, document) = run_json(
+        &dir,
+        &[
+            "which-index",
+            "--hash",
+            &hash[..10].to_uppercase(),
+            "--hash",
+            "0000000",
+            "--hashed-assets",
+            store.dir.to_str().unwrap(),
+        ],
+    );
+
+    assert_eq!(code, Some(1));
+    assert_snapshot("which_index", &document);
+}
+
+#[test]
+fn which_index_porcelain() {
+    let dir = common::temp_dir();
+    let store = AssetStoreBuilder::typical().write(&dir.path().join("assets"));
+    let hash = common::object(b"OggS fake cave sound\n").hash;
+    let (code, stdout, stderr) = run_porcelain(
+        &dir,
+        &[
+            "which-index",
+            "--hash",
+            &hash[..10],
+            "--hash",
+            "0000000",
+            "--hashed-assets",
+            store.dir.to_str().unwrap(),
+        ],
+    );
+
+    assert_eq!(code, Some(1));
+    assert_eq!(
+        stdout,
+        format!("{hash}\t5\t-\tminecraft/sounds/ambient/cave/cave1.ogg\n")
+    );
+    assert_eq!(stderr, "No index file uses '0000000'\n");
+}
+
+#[test]
+fn unused_indexes() {
+    let dir = common::temp_dir();
+    let assets_dir = dir.path().join("assets");
+    AssetStoreBuilder::typical()
+        .index_id("5")
+        .write(&assets_dir);
+    AssetStoreBuilder::typical()
+        .index_id("17")
+        .write(&assets_dir);
+    common::write_manifest(&dir.path().join("versions"), "1.20.1", "\"assets\": \"5\"");
+    let (code, document) = run_json(
+        &dir,
+        &[
+            "indexes",
+            "--unused",
+            "--hashed-assets",
+            assets_dir.to_str().unwrap(),
+        ],
+    );
+
+    assert_eq!(code, Some(0));
+    assert_snapshot("unused_indexes", &document);
+}
+
+#[test]
+fn largest() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client().write(&dir.path().join("client.jar"));
+    let (code, document) = run_json(
+        &dir,
+        &[
+            "largest",
+            jar.to_str().unwrap(),
+            "-n",
+            "3",
+            "--namespace",
+            "minecraft",
+        ],
+    );
+
+    assert_eq!(code, Some(0));
+    assert_snapshot("largest", &document);
+}
+
+#[test]
+fn list() {
+    let dir = common::temp_dir();
+    let store = AssetStoreBuilder::typical().write(&dir.path().join("assets"));
+    let (code, document) = run_json(
+        &dir,
+        &[
+            "list",
+            store.index_file.to_str().unwrap(),
+            "--hashed-assets",
+            store.dir.to_str().unwrap(),
+        ],
+    );
+
+    assert_eq!(code, Some(0));
+    assert_snapshot("list", &document);
+}
+
+#[test]
+fn list_paths_only() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client().write(&dir.path().join("client.jar"));
+
+    let output = Command::new(env!("CARGO_BIN_EXE_extract-minecraft-assets"))
+        .args(["list", jar.to_str().unwrap(), "--data", "--paths-only"])
+        .env("HOME", dir.path())
+        .env("XDG_CONFIG_HOME", dir.path().join(".config"))
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let paths: Vec<_> = stdout.lines().collect();
+
+    assert_eq!(output.status.code(), Some(0));
+    assert!(!paths.is_empty());
+    assert!(
+        paths.iter().all(|path| path.starts_with("data/")),
+        "{stdout}"
+    );
+    assert!(paths.is_sorted(), "{stdout}");
+}
+
+#[test]
+fn list_porcelain() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client().write(&dir.path().join("client.jar"));
+    let (code, stdout, stderr) = run_porcelain(&dir, &["list", jar.to_str().unwrap(), "--assets"]);
+
+    assert_eq!(code, Some(0));
+    assert_eq!(
+        stdout,
+        "jar\t27\t-\tassets/minecraft/lang/en_us.json\n\
+         jar\t19\t-\tassets/minecraft/shared.json\n\
+         jar\t19\t-\tassets/minecraft/texts/splashes.txt\n"
+    );
+    assert_eq!(stderr, "");
+}
+
+#[test]
+fn changed() {
+    let dir = common::temp_dir();
+    let versions_dir = dir.path().join("versions");
+    let old = common::write_manifest(&versions_dir, "1.0", "\"assets\": \"1\"");
+    let new = common::write_manifest(&versions_dir, "1.1", "\"assets\": \"2\"");
+    JarBuilder::client().write(&old.join("1.0.jar"));
+    JarBuilder::new()
+        .file(
+            "assets/minecraft/lang/en_us.json",
+            "{\"menu.quit\": \"Quit\"}\n",
+        )
+        .file("assets/minecraft/shared.json", "{\"from\": \"assets\"}\n")
+        .file("assets/minecraft/texts/credits.json", "[]\n")
+        .file("pack.mcmeta", "{\"pack\": {\"pack_format\": 15}}\n")
+        .write(&new.join("1.1.jar"));
+    let store = AssetStoreBuilder::new()
+        .index_id("1")
+        .file("minecraft/lang/de_de.json", "{}\n")
+        .file("minecraft/sounds/old.ogg", "OggS old\n")
+        .write(&dir.path().join("assets"));
+    AssetStoreBuilder::new()
+        .index_id("2")
+        .file("minecraft/lang/de_de.json", "{}\n")
+        .file("minecraft/sounds/new.ogg", "OggS new\n")
+        .write(&store.dir);
+
+    let (code, document) = run_json(
+        &dir,
+        &[
+            "changed",
+            old.to_str().unwrap(),
+            new.to_str().unwrap(),
+            "--assets",
+            "--hashed-assets",
+            store.dir.to_str().unwrap(),
+            "--write-removed",
+        ],
+    );
+    let removed = fs::read_to_string(dir.path().join("out/removed.txt")).unwrap();
+
+    assert_eq!(code, Some(0));
+    assert_snapshot("changed", &document);
+    assert_eq!(
+        removed,
+        "assets/minecraft/sounds/old.ogg\nassets/minecraft/texts/splashes.txt\n"
+    );
+}
+
+#[test]
+fn all_versions_are_deduplicated_and_resumed() {
+    let dir = common::temp_dir();
+    let versions_dir = dir.path().join("versions");
+    for name in ["1.0", "1.1", "2.0"] {
+        let version = common::write_manifest(&versions_dir, name, "\"assets\": \"5\"");
+        JarBuilder::client().write(&version.join(format!("{name}.jar")));
+    }
+    fs::write(versions_dir.join("2.0/2.0.jar"), "not a jar").unwrap();
+    let store = AssetStoreBuilder::new()
+        .file("minecraft/lang/de_de.json", "{}\n")
+        .write(&dir.path().join("assets"));
+    let args = [
+        "all",
+        "--versions",
+        versions_dir.to_str().unwrap(),
+        "--hashed-assets",
+        store.dir.to_str().unwrap(),
+        "--assets",
+    ];
+    let out = dir.path().join("out");
+
+    // A version which fails doesn't stop the others.
+    let (code, document) = run_json(&dir, &args);
+    assert_eq!(code, Some(2), "{document}");
+    assert_eq!(document["summary"]["failures"], 1);
+    assert!(out.join("1.0/assets/minecraft/lang/de_de.json").is_file());
+    assert!(out.join("1.1/assets/minecraft/lang/de_de.json").is_file());
+    assert!(!out.join("2.0").exists());
+    let files = document["summary"]["files"].as_u64().unwrap();
+    assert_eq!(files % 2, 0);
+    assert_eq!(
+        document["files"][0]["path"],
+        "1.0/assets/minecraft/lang/de_de.json"
+    );
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+
+        let inode = |path: &str| fs::metadata(out.join(path)).unwrap().ino();
+        assert_eq!(
+            inode("1.0/assets/minecraft/texts/splashes.txt"),
+            inode("1.1/assets/minecraft/texts/splashes.txt")
+        );
+    }
+
+    // Only the failed version is extracted again.
+    JarBuilder::client().write(&versions_dir.join("2.0/2.0.jar"));
+    let (code, document) = run_json(&dir, &args);
+    assert_eq!(code, Some(0), "{document}");
+    assert_eq!(document["summary"]["files"].as_u64().unwrap(), files / 2);
+    assert!(out.join("2.0/assets/minecraft/lang/de_de.json").is_file());
+    assert!(!out.join(".staging").exists());
+}
+
+/// The jar file is replaced by moving a new one over it, as the launcher
+/// does, and only the file which was added is extracted.
+#[cfg(all(unix, feature = "watch"))]
+#[test]
+fn watched_versions_are_extracted_again_when_changed() {
+    use std::io::{BufRead, BufReader};
+    use std::process::Stdio;
+
+    let dir = common::temp_dir();
+    let version = common::write_manifest(&dir.path().join("versions"), "1.0", "\"assets\": \"5\"");
+    let jar = version.join("1.0.jar");
+    JarBuilder::client().write(&jar);
+    let output_dir = dir.path().join("out");
+    fs::create_dir_all(&output_dir).unwrap();
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_extract-minecraft-assets"))
+        .args(["version", version.to_str().unwrap(), "--data", "--watch"])
+        .args(["--json", "--yes", "--porcelain", "--output"])
+        .arg(&output_dir)
+        .env("HOME", dir.path())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stderr = BufReader::new(child.stderr.take().unwrap()).lines();
+    let mut wait_for = |text: &str| {
+        stderr
+            .by_ref()
+            .map(Result::unwrap)
+            .find(|line| line.contains(text))
+            .unwrap_or_else(|| panic!("exited before printing {text:?}"))
+    };
+
+    wait_for("Watching");
+    let replacement = version.join("1.0.jar.tmp");
+    JarBuilder::client()
+        .file(
+            "data/minecraft/tags/blocks/planks.json",
+            "{\"values\": []}\n",
+        )
+        .write(&replacement);
+    fs::rename(&replacement, &jar).unwrap();
+    let changed = wait_for("extracting again");
+    wait_for("Watching");
+
+    // SAFETY: sending SIGINT to the child only interrupts it.
+    unsafe { libc::kill(child.id() as libc::pid_t, libc::SIGINT) };
+    let output = child.wait_with_output().unwrap();
+    let documents: Vec<Value> = serde_json::Deserializer::from_slice(&output.stdout)
+        .into_iter()
+        .map(Result::unwrap)
+        .collect();
+
+    assert_eq!(output.status.code(), Some(0));
+    assert!(
+        changed.ends_with("1.0.jar' changed, extracting again"),
+        "{changed}"
+    );
+    assert_eq!(documents.len(), 2);
+    assert_eq!(documents[0]["summary"]["files"], 2);
+    assert_eq!(documents[1]["summary"]["files"], 1);
+    assert_eq!(documents[1]["summary"]["unchanged"], 2);
+    assert_eq!(
+        documents[1]["files"][0]["path"],
+        "data/minecraft/tags/blocks/planks.json"
+    );
+}
+
+#[test]
+fn lang() {
+    let dir = common::temp_dir();
+    let version = common::write_manifest(&dir.path().join("versions"), "1.0", "\"assets\": \"5\"");
+    JarBuilder::client().write(&version.join("1.0.jar"));
+    let store = AssetStoreBuilder::typical()
+        .file("realms/lang/de_de.json", "{\"menu.quit\": \"Verlassen\"}\n")
+        .file("realms/lang/fr_fr.json", "{\"menu.quit\": \n")
+        .write(&dir.path().join("assets"));
+    let (code, document) = run_json(
+        &dir,
+        &[
+            "lang",
+            version.to_str().unwrap(),
+            "--hashed-assets",
+            store.dir.to_str().unwrap(),
+        ],
+    );
+    let de_de = fs::read_to_string(dir.path().join("out/merged/de_de.json")).unwrap();
+
+    assert_eq!(code, Some(2));
+    assert_snapshot("lang", &document);
+    assert_eq!(de_de, "{\n  \"menu.quit\": \"Verlassen\"\n}\n");
+}
+
+#[test]
+fn libraries() {
+    let dir = common::temp_dir();
+    let libraries_dir = dir.path().join("libraries");
+    let library = |path: &str, contents: &str| {
+        let file = libraries_dir.join(path);
+        fs::create_dir_all(file.parent().unwrap()).unwrap();
+        fs::write(file, contents).unwrap();
+
+        format!(
+            r#"{{"name": "{path}", "downloads": {{"artifact": {{"path": "{path}", "sha1": "{}", "size": {}, "url": "https://libraries.minecraft.net/{path}"}}}}}}"#,
+            util::sha1_hex(contents.as_bytes()).unwrap(),
+            contents.len()
+        )
+    };
+    let brigadier = library("com/mojang/brigadier/1.0/brigadier-1.0.jar", "brigadier");
+    let guava = library("com/google/guava/guava/31.1/guava-31.1.jar", "guava");
+    let loader = library("net/fabricmc/loader/0.14/loader-0.14.jar", "fabric");
+    fs::write(
+        libraries_dir.join("com/google/guava/guava/31.1/guava-31.1.jar"),
+        "modified guava",
+    )
+    .unwrap();
+    let missing = library("org/lwjgl/lwjgl/3.3/lwjgl-3.3.jar", "lwjgl");
+    fs::remove_file(libraries_dir.join("org/lwjgl/lwjgl/3.3/lwjgl-3.3.jar")).unwrap();
+
+    let versions_dir = dir.path().join("versions");
+    common::write_manifest(
+        &versions_dir,
+        "1.0",
+        &format!(r#""libraries": [{brigadier}, {guava}, {missing}]"#),
+    );
+    let version = common::write_manifest(
+        &versions_dir,
+        "1.0-fabric",
+        &format!(r#""inheritsFrom": "1.0", "libraries": [{loader}, {brigadier}]"#),
+    );
+
+    let (code, document) = run_json(&dir, &["libraries", version.to_str().unwrap()]);
+
+    assert_eq!(code, Some(2));
+    assert_snapshot("libraries", &document);
+    assert_eq!(
+        fs::read_to_string(
+            dir.path()
+                .join("out/net/fabricmc/loader/0.14/loader-0.14.jar")
+        )
+        .unwrap(),
+        "fabric"
+    );
+    assert!(!dir.path().join("out/com/google").exists());
+}
+
+#[test]
+fn locales() {
+    let dir = common::temp_dir();
+    let version = common::write_manifest(&dir.path().join("versions"), "1.0", "\"assets\": \"5\"");
+    JarBuilder::client().write(&version.join("1.0.jar"));
+    let store = AssetStoreBuilder::typical()
+        .file(
+            "pack.mcmeta",
+            r#"{"language": {"en_us": {"name": "English", "region": "US"}}}"#,
+        )
+        .write(&dir.path().join("assets"));
+    let (code, document) = run_json(
+        &dir,
+        &[
+            "locales",
+            version.to_str().unwrap(),
+            "--hashed-assets",
+            store.dir.to_str().unwrap(),
+            "--missing",
+            "DE_DE",
+            "--missing",
+            "fr_fr",
+        ],
+    );
+
+    assert_eq!(code, Some(1));
+    assert_snapshot("locales", &document);
+}
+
+#[test]
+fn fonts_closure() {
+    let dir = common::temp_dir();
+    let version = common::write_manifest(&dir.path().join("versions"), "1.0", "\"assets\": \"5\"");
+    JarBuilder::client()
+        .file(
+            "assets/minecraft/font/default.json",
+            r#"{"providers": [
+                {"type": "bitmap", "file": "minecraft:font/ascii.png", "chars": []},
+                {"type": "reference", "id": "minecraft:include/space"}
+            ]}"#,
+        )
+        .file("assets/minecraft/textures/font/ascii.png", "PNG ascii")
+        .file("assets/minecraft/textures/block/stone.png", "PNG stone")
+        .write(&version.join("1.0.jar"));
+    let store = AssetStoreBuilder::typical()
+        .file(
+            "minecraft/font/include/space.json",
+            r#"{"providers": [{"type": "space", "advances": {" ": 4}}]}"#,
+        )
+        .write(&dir.path().join("assets"));
+
+    let (code, document) = run_json(
+        &dir,
+        &[
+            "version",
+            version.to_str().unwrap(),
+            "--assets",
+            "--hashed-assets",
+            store.dir.to_str().unwrap(),
+            "--only",
+            "fonts-closure",
+        ],
+    );
+
+    assert_eq!(code, Some(0));
+    assert_snapshot("fonts_closure", &document);
+}
+
+#[test]
+fn jar_closure() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client()
+        .file(
+            "assets/minecraft/blockstates/stone.json",
+            r#"{"variants": {"": {"model": "minecraft:block/stone"}}}"#,
+        )
+        .file(
+            "assets/minecraft/models/block/stone.json",
+            r#"{"parent": "minecraft:block/cube_all", "textures": {"all": "minecraft:block/stone"}}"#,
+        )
+        .file("assets/minecraft/textures/block/stone.png", "PNG stone")
+        .file("assets/minecraft/textures/block/dirt.png", "PNG dirt")
+        .write(&dir.path().join("client.jar"));
+
+    let (code, document) = run_json(
+        &dir,
+        &[
+            "jar",
+            jar.to_str().unwrap(),
+            "--assets",
+            "--closure",
+            "assets/minecraft/blockstates/stone.json",
+        ],
+    );
+
+    assert_eq!(code, Some(0));
+    assert_snapshot("jar_closure", &document);
+}
+
+#[test]
+fn jar_redistributable() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client()
+        .file("pack.png", "PNG pack")
+        .file("assets/minecraft/models/block/stone.json", "{}")
+        .file("assets/minecraft/textures/block/stone.png", "PNG stone")
+        .file("assets/minecraft/textures/gui/widgets.png", "PNG widgets")
+        .write(&dir.path().join("client.jar"));
+
+    let (code, document) = run_json(
+        &dir,
+        &[
+            "jar",
+            jar.to_str().unwrap(),
+            "--assets",
+            "--redistributable",
+            "--redistribute",
+            "assets/minecraft/textures/gui/**",
+        ],
+    );
+
+    assert_eq!(code, Some(0));
+    assert_snapshot("jar_redistributable", &document);
+}
+
+#[test]
+fn jar_include_exclude() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client()
+        .file("assets/minecraft/textures/block/stone.png", "PNG stone")
+        .write(&dir.path().join("client.jar"));
+
+    let (code, document) = run_json(
+        &dir,
+        &[
+            "jar",
+            jar.to_str().unwrap(),
+            "--assets",
+            "--include",
+            "*.json",
+            "--include",
+            "assets/minecraft/textures/**",
+            "--exclude",
+            "shared.json",
+        ],
+    );
+
+    assert_eq!(code, Some(0));
+    assert_snapshot("jar_include_exclude", &document);
+}
+
+#[test]
+fn jar_namespaces() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client()
+        .file("assets/realms/lang/de_de.json", "{}")
+        .file("data/realms/tags/items/logs.json", "{}")
+        .write(&dir.path().join("client.jar"));
+
+    let (code, document) = run_json(
+        &dir,
+        &[
+            "jar",
+            jar.to_str().unwrap(),
+            "--assets",
+            "--data",
+            "--namespace",
+            "realms",
+            "--namespace",
+            "unknown",
+        ],
+    );
+
+    assert_eq!(code, Some(0));
+    assert_snapshot("jar_namespaces", &document);
+}
+
+#[test]
+fn hashed_namespaces() {
+    let dir = common::temp_dir();
+    let store = AssetStoreBuilder::typical()
+        .file("realms/lang/de_de.json", "{}")
+        .write(&dir.path().join("assets"));
+
+    let (code, document) = run_json(
+        &dir,
+        &[
+            "hashed",
+            store.dir.to_str().unwrap(),
+            "--index",
+            store.index_file.to_str().unwrap(),
+            "--namespace",
+            "realms",
+            "--namespace",
+            "unknown",
+        ],
+    );
+
+    // The missing object isn't in the namespace, so it doesn't fail.
+    assert_eq!(code, Some(0));
+    let paths: Vec<_> = document["files"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|file| file["path"].as_str().unwrap())
+        .collect();
+    assert_eq!(paths, ["assets/realms/lang/de_de.json"]);
+    assert_eq!(
+        document["warnings"],
+        serde_json::json!([{
+            "path": "assets/unknown",
+            "message": "namespace 'unknown' matched 0 files",
+        }])
+    );
+}
+
+#[test]
+fn existing_files_are_skipped_on_conflict() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client().write(&dir.path().join("client.jar"));
+    let edited = dir.path().join("out/assets/minecraft/lang/en_us.json");
+    fs::create_dir_all(edited.parent().unwrap()).unwrap();
+    fs::write(&edited, "edited").unwrap();
+
+    let (code, document) = run_json(
+        &dir,
+        &[
+            "jar",
+            jar.to_str().unwrap(),
+            "--assets",
+            "--on-conflict",
+            "skip",
+        ],
+    );
+
+    assert_eq!(code, Some(0));
+    assert_eq!(document["options"]["overwrite"], "skip");
+    assert_eq!(document["summary"]["skipped"], 1);
+    assert_eq!(fs::read_to_string(&edited).unwrap(), "edited");
+}
+
+#[test]
+fn prompting_on_conflict_needs_a_terminal() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client().write(&dir.path().join("client.jar"));
+
+    let (code, document) = run_json(
+        &dir,
+        &[
+            "jar",
+            jar.to_str().unwrap(),
+            "--assets",
+            "--on-conflict",
+            "prompt",
+        ],
+    );
+
+    assert_eq!(code, Some(1));
+    assert_eq!(
+        document["error"],
+        "--on-conflict prompt needs a terminal to ask on: pass --on-conflict skip or overwrite \
+         instead"
+    );
+}
+
+/// Without a session bus to deliver notifications to, the exit code is still
+/// that of the extraction.
+#[cfg(all(feature = "notify", unix, not(target_os = "macos")))]
+#[test]
+fn undelivered_notifications_dont_change_the_exit_code() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client().write(&dir.path().join("client.jar"));
+    let store = AssetStoreBuilder::typical().write(&dir.path().join("assets"));
+    fs::create_dir(dir.path().join("out")).unwrap();
+    let extract = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_extract-minecraft-assets"))
+            .args(args)
+            .args(["--notify", "--yes", "--porcelain", "--output"])
+            .arg(dir.path().join("out"))
+            .env("HOME", dir.path())
+            .env("XDG_CONFIG_HOME", dir.path().join(".config"))
+            .env(
+                "DBUS_SESSION_BUS_ADDRESS",
+                format!("unix:path={}", dir.path().join("no-bus").display()),
+            )
+            .output()
+            .unwrap()
+    };
+
+    let output = extract(&["jar", jar.to_str().unwrap(), "--assets"]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(0), "{stderr}");
+    assert!(stderr.contains("Failed to show notification: "), "{stderr}");
+
+    let output = extract(&[
+        "hashed",
+        store.dir.to_str().unwrap(),
+        "--index",
+        store.index_file.to_str().unwrap(),
+    ]);
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn catalog() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client()
+        .file("assets/minecraft/textures/block/stone.png", "PNG stone")
+        .write(&dir.path().join("client.jar"));
+    let (code, _) = run_json(
+        &dir,
+        &[
+            "jar",
+            jar.to_str().unwrap(),
+            "--assets",
+            "--catalog",
+            "json",
+        ],
+    );
+    let catalog = fs::read(dir.path().join("out/catalog.json")).unwrap();
+
+    assert_eq!(code, Some(0));
+    assert_snapshot("catalog", &serde_json::from_slice(&catalog).unwrap());
+}
+
+#[test]
+fn dry_runs_write_nothing_but_fail_as_extraction_would() {
+    let dir = common::temp_dir();
+    let store = AssetStoreBuilder::typical().write(&dir.path().join("assets"));
+    let (code, document) = run_json(
+        &dir,
+        &[
+            "hashed",
+            store.dir.to_str().unwrap(),
+            "--index",
+            store.index_file.to_str().unwrap(),
+            "--dry-run",
+        ],
+    );
+
+    assert_eq!(code, Some(2));
+    assert_eq!(document["summary"]["failures"], 1);
+    assert_ne!(document["summary"]["files"], 0);
+    assert_eq!(fs::read_dir(dir.path().join("out")).unwrap().count(), 0);
+
+    // Subcommands which write files themselves can't be dry run.
+    let (code, document) = run_json(&dir, &["all", "--dry-run", "--assets"]);
+    assert_eq!(code, Some(1));
+    assert!(document["error"].as_str().unwrap().contains("--dry-run"));
+}
+
+#[test]
+fn dry_runs_list_files_on_stdout() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client().write(&dir.path().join("client.jar"));
+    let output_dir = dir.path().join("out");
+    fs::create_dir(&output_dir).unwrap();
+    let dry_run = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_extract-minecraft-assets"))
+            .args([
+                "jar",
+                jar.to_str().unwrap(),
+                "--assets",
+                "--dry-run",
+                "--output",
+            ])
+            .arg(&output_dir)
+            .args(args)
+            .env("HOME", dir.path())
+            .env("XDG_CONFIG_HOME", dir.path().join(".config"))
+            .output()
+            .unwrap()
+    };
+    let lang = output_dir.join("assets/minecraft/lang/en_us.json");
+
+    let output = dry_run(&[]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(output.status.success());
+    assert!(
+        stdout
+            .lines()
+            .any(|line| line == format!("Would extract '{}'", lang.display())),
+        "{stdout}"
+    );
+    assert!(!String::from_utf8_lossy(&output.stderr).contains("Would extract"));
+
+    let output = dry_run(&["--porcelain"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        stdout.lines().any(|line| Path::new(line) == lang),
+        "{stdout}"
+    );
+    assert!(stdout.lines().all(|line| line.starts_with('/')), "{stdout}");
+
+    // Each path is listed once, followed by a NUL byte.
+    let output = dry_run(&["--print0"]);
+    let paths: Vec<_> = output
+        .stdout
+        .strip_suffix(b"\0")
+        .unwrap()
+        .split(|&byte| byte == b'\0')
+        .collect();
+    assert_eq!(paths.len(), 3);
+    assert!(paths.contains(&lang.to_str().unwrap().as_bytes()));
+
+    // The document is all there is on stdout.
+    let (code, document) = run_json(
+        &dir,
+        &["jar", jar.to_str().unwrap(), "--assets", "--dry-run"],
+    );
+    assert_eq!(code, Some(0));
+    assert_eq!(document["summary"]["files"], 3);
+    assert_eq!(fs::read_dir(&output_dir).unwrap().count(), 0);
+}
+
+#[test]
+fn synced_extractions_delete_stale_files() {
+    let dir = common::temp_dir();
+    let old_jar = JarBuilder::client()
+        .file("assets/minecraft/textures/old/gone.png", "png")
+        .write(&dir.path().join("old.jar"));
+    let new_jar = JarBuilder::client().write(&dir.path().join("new.jar"));
+    let out = dir.path().join("out");
+    let run = |jar: &Path, extra: &[&str]| {
+        let args = ["jar", jar.to_str().unwrap(), "--assets", "--sync"];
+        run_json(&dir, &[&args[..], extra].concat())
+    };
+
+    let (code, _) = run(&old_jar, &[]);
+    assert_eq!(code, Some(0));
+    fs::write(out.join("assets/minecraft/notes.txt"), "mine\n").unwrap();
+
+    // A dry run only lists what would be deleted.
+    let (code, document) = run(&new_jar, &["--sync-dry-run"]);
+    assert_eq!(code, Some(0));
+    assert_eq!(document["summary"]["deleted"], 0);
+    assert!(out.join("assets/minecraft/textures/old/gone.png").is_file());
+
+    let (code, document) = run(&new_jar, &[]);
+    assert_eq!(code, Some(0));
+    assert_eq!(document["summary"]["deleted"], 1);
+    assert!(!out.join("assets/minecraft/textures").exists());
+    assert!(out.join("assets/minecraft/notes.txt").is_file());
+
+    let (code, document) = run(&new_jar, &["--sync-delete-unknown"]);
+    assert_eq!(code, Some(0));
+    assert_eq!(document["summary"]["deleted"], 1);
+    assert!(!out.join("assets/minecraft/notes.txt").exists());
+
+    // Subcommands which extract part of their source can't be synced.
+    let (code, document) = run_json(&dir, &["all", "--sync", "--assets"]);
+    assert_eq!(code, Some(1));
+    assert!(document["error"].as_str().unwrap().contains("--sync"));
+}
+
+#[cfg(feature = "git")]
+#[test]
+fn extractions_are_committed_to_git() {
+    let dir = common::temp_dir();
+    let version = common::write_manifest(&dir.path().join("versions"), "1.0", "\"assets\": \"5\"");
+    JarBuilder::client().write(&version.join("1.0.jar"));
+    let out = dir.path().join("out");
+    let args = [
+        "version",
+        version.to_str().unwrap(),
+        "--data",
+        "--git-commit",
+        "--git-init",
+        "--git-tag",
+    ];
+
+    let (code, _) = run_json(&dir, &args);
+    assert_eq!(code, Some(0));
+    let repository = git2::Repository::open(&out).unwrap();
+    let head = repository.head().unwrap().peel_to_commit().unwrap();
+    assert!(
+        head.summary()
+            .unwrap()
+            .starts_with("Extract data of 1.0 (index 5)")
+    );
+    assert!(head.message().unwrap().contains(env!("CARGO_PKG_VERSION")));
+    assert!(
+        head.tree()
+            .unwrap()
+            .get_path(Path::new("data/minecraft/tags/blocks/logs.json"))
+            .is_ok()
+    );
+    assert_eq!(repository.revparse_single("1.0").unwrap().id(), head.id());
+
+    // Staged changes aren't committed along with the extracted files.
+    fs::write(out.join("notes.txt"), "mine\n").unwrap();
+    let mut index = repository.index().unwrap();
+    index.add_path(Path::new("notes.txt")).unwrap();
+    index.write().unwrap();
+    let (code, document) = run_json(&dir, &args[..5]);
+    assert_eq!(code, Some(1));
+    assert!(document["error"].as_str().unwrap().contains("notes.txt"));
+    assert_eq!(
+        repository.head().unwrap().peel_to_commit().unwrap().id(),
+        head.id()
+    );
+}
+
+#[test]
+fn du() {
+    let dir = common::temp_dir();
+    let version = common::write_manifest(&dir.path().join("versions"), "1.0", "\"assets\": \"5\"");
+    JarBuilder::client().write(&version.join("1.0.jar"));
+    let store = AssetStoreBuilder::typical()
+        .index_id("5")
+        .write(&dir.path().join("assets"));
+    let (code, document) = run_json(
+        &dir,
+        &[
+            "du",
+            "version",
+            version.to_str().unwrap(),
+            "--assets",
+            "--data",
+            "--hashed-assets",
+            store.dir.to_str().unwrap(),
+            "--redistributable",
+        ],
+    );
+
+    assert_eq!(code, Some(0), "{document}");
+    assert_snapshot("du", &document);
+}
+
+#[test]
+fn du_matches_what_is_extracted() {
+    let dir = common::temp_dir();
+    let version = common::write_manifest(&dir.path().join("versions"), "1.0", "\"assets\": \"5\"");
+    JarBuilder::client().write(&version.join("1.0.jar"));
+    let store = AssetStoreBuilder::typical()
+        .index_id("5")
+        .write(&dir.path().join("assets"));
+    let args = [
+        "version",
+        version.to_str().unwrap(),
+        "--assets",
+        "--data",
+        "--hashed-assets",
+        store.dir.to_str().unwrap(),
+        "--redistributable",
+        "--namespace",
+        "minecraft",
+    ];
+
+    let (code, usage) = run_json(&dir, &[&["du"], &args[..]].concat());
+    assert_eq!(code, Some(0), "{usage}");
+    let (code, report) = run_json(&dir, &[&args[..], &["--dry-run"]].concat());
+    assert_eq!(code, Some(0), "{report}");
+
+    assert_ne!(usage["total"]["files"], 0);
+    assert_eq!(usage["total"]["files"], report["summary"]["files"]);
+    assert_eq!(usage["total"]["bytes"], report["summary"]["bytes"]);
+}
+
+#[test]
+fn tree() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client().write(&dir.path().join("client.jar"));
+    let (code, document) = run_json(
+        &dir,
+        &[
+            "tree",
+            "jar",
+            jar.to_str().unwrap(),
+            "--assets",
+            "--data",
+            "--max-depth",
+            "3",
+        ],
+    );
+
+    assert_eq!(code, Some(0), "{document}");
+    assert_snapshot("tree", &document);
+}
+
+#[test]
+fn clean_run_keeps_modified_files() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client().write(&dir.path().join("client.jar"));
+    let report = dir.path().join("report.json");
+    let report_arg = report.to_str().unwrap();
+    let (code, _) = run_json(
+        &dir,
+        &[
+            "jar",
+            jar.to_str().unwrap(),
+            "--data",
+            "--report",
+            report_arg,
+        ],
+    );
+    assert_eq!(code, Some(0));
+
+    let modified = dir.path().join("out/data/minecraft/shared.json");
+    fs::write(&modified, "{\"from\": \"me\"}\n").unwrap();
+
+    let (code, document) = run_json(&dir, &["clean-run", "--report", report_arg]);
+    assert_eq!(code, Some(2), "{document}");
+    assert_snapshot("clean_run", &document);
+    assert!(modified.is_file());
+    assert!(!dir.path().join("out/data/minecraft/tags").exists());
+
+    let (code, document) = run_json(&dir, &["clean-run", "--report", report_arg, "--force"]);
+    assert_eq!(code, Some(0), "{document}");
+    assert!(!dir.path().join("out/data").exists());
+}
+
+#[test]
+fn output_zip_is_split_into_volumes() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client()
+        .file("assets/minecraft/textures/block/stone.png", vec![7; 600])
+        .write(&dir.path().join("client.jar"));
+    let zip = dir.path().join("pack.zip");
+    let (code, document) = run_json(
+        &dir,
+        &[
+            "jar",
+            jar.to_str().unwrap(),
+            "--assets",
+            "--output-zip",
+            zip.to_str().unwrap(),
+            "--split",
+            "1K",
+        ],
+    );
+    assert_eq!(code, Some(0), "{document}");
+
+    // Nothing is written into the output directory.
+    assert!(!dir.path().join("out/assets").exists());
+    let volumes: Value =
+        serde_json::from_slice(&fs::read(dir.path().join("pack.volumes.json")).unwrap()).unwrap();
+    assert_eq!(volumes["volumes"].as_array().unwrap().len(), 2);
+    assert_eq!(
+        volumes["files"]["assets/minecraft/textures/block/stone.png"],
+        "pack.part1.zip"
+    );
+    assert!(
+        fs::metadata(dir.path().join("pack.part2.zip"))
+            .unwrap()
+            .len()
+            <= 1024
+    );
+}
+
+#[test]
+fn verify_report_exits_with_1_on_drift() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client().write(&dir.path().join("client.jar"));
+    let report = dir.path().join("report.json");
+    let report_arg = report.to_str().unwrap();
+    let (code, _) = run_json(
+        &dir,
+        &[
+            "jar",
+            jar.to_str().unwrap(),
+            "--data",
+            "--report",
+            report_arg,
+        ],
+    );
+    assert_eq!(code, Some(0));
+
+    let (code, document) = run_json(&dir, &["verify-report", "--report", report_arg]);
+    assert_eq!(code, Some(0), "{document}");
+    assert_eq!(document["untracked"], serde_json::json!([]));
+
+    fs::write(dir.path().join("out/data/minecraft/extra.json"), "{}\n").unwrap();
+
+    let (code, document) = run_json(&dir, &["verify-report", "--report", report_arg]);
+    assert_eq!(code, Some(1), "{document}");
+    assert_eq!(
+        document["untracked"],
+        serde_json::json!(["data/minecraft/extra.json"])
+    );
+}
+
+#[test]
+fn hash_path() {
+    let dir = common::temp_dir();
+    let store = AssetStoreBuilder::typical().write(&dir.path().join("assets"));
+    let index = store.index();
+    // The typical store is missing some of its objects.
+    let (object, object_file) = index
+        .objects
+        .values()
+        .map(|object| {
+            let object_file = store.dir.join("objects").join(object.hashed_file_path());
+            (object, object_file)
+        })
+        .filter(|(_, object_file)| object_file.is_file())
+        .min_by_key(|(object, _)| &object.hash)
+        .unwrap();
+    let stored = dir.path().join("stored.json");
+    fs::copy(object_file, &stored).unwrap();
+    let new = dir.path().join("new.json");
+    fs::write(&new, "{}").unwrap();
+
+    let (code, document) = run_json(
+        &dir,
+        &[
+            "hash-path",
+            stored.to_str().unwrap(),
+            new.to_str().unwrap(),
+            dir.path().join("gone.json").to_str().unwrap(),
+            "--hashed-assets",
+            store.dir.to_str().unwrap(),
+        ],
+    );
+
+    assert_eq!(code, Some(1), "{document}");
+    // Files are sorted by path when normalized.
+    assert_eq!(document["files"][1]["hash"], object.hash.as_str());
+    assert_eq!(document["files"][1]["exists"], true);
+    assert_snapshot("hash_path", &document);
+}
+
+#[test]
+fn hash_path_porcelain() {
+    let dir = common::temp_dir();
+    let store = AssetStoreBuilder::typical().write(&dir.path().join("assets"));
+    let new = dir.path().join("new.json");
+    fs::write(&new, "{}").unwrap();
+    let object = common::object(b"{}");
+
+    let (code, stdout, stderr) = run_porcelain(
+        &dir,
+        &[
+            "hash-path",
+            new.to_str().unwrap(),
+            dir.path().join("gone.json").to_str().unwrap(),
+            "--hashed-assets",
+            store.dir.to_str().unwrap(),
+        ],
+    );
+
+    assert_eq!(code, Some(1));
+    assert_eq!(
+        stdout,
+        format!(
+            "{}\t2\tobjects/{}\tmissing\t<temp>/new.json\n",
+            object.hash,
+            object.hashed_file_path().display()
+        )
+    );
+    assert!(
+        stderr.starts_with("Couldn't read '<temp>/gone.json': "),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn warnings_are_written_to_stderr() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client().write(&dir.path().join("client.jar"));
+    let (code, stdout, stderr) = run_porcelain(
+        &dir,
+        &[
+            "jar",
+            jar.to_str().unwrap(),
+            "--assets",
+            "--namespace",
+            "minecraft",
+            "--namespace",
+            "realms",
+        ],
+    );
+
+    assert_eq!(code, Some(0));
+    assert_eq!(stdout, "");
+    let lines: Vec<_> = stderr.lines().collect();
+    assert_eq!(lines.len(), 2, "{stderr}");
+    assert_eq!(
+        lines[0],
+        "Warning for 'assets/realms': namespace 'realms' matched 0 files"
+    );
+    assert!(lines[1].starts_with("Extraction complete: 3 files (65 B)"));
+}
+
+#[test]
+fn validate_pack() {
+    let dir = common::temp_dir();
+    let model = dir
+        .path()
+        .join("out/assets/minecraft/models/block/stone.json");
+    fs::create_dir_all(model.parent().unwrap()).unwrap();
+    fs::write(
+        &model,
+        r#"{"parent": "block/cube_all", "textures": {"all": "block/stone"}}"#,
+    )
+    .unwrap();
+
+    let (code, document) = run_json(&dir, &["validate-pack"]);
+
+    assert_eq!(code, Some(1), "{document}");
+    assert_snapshot("validate_pack", &document);
+}
+
+/// Writes `contents` to the config file used by [`run_json`].
+fn write_config(dir: &TempDir, contents: &str) {
+    let path = dir
+        .path()
+        .join(".config/extract-minecraft-assets/config.toml");
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, contents).unwrap();
+}
+
+#[test]
+fn large_extractions_need_confirmation() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client().write(&dir.path().join("client.jar"));
+    let out = dir.path().join("out");
+    fs::create_dir(&out).unwrap();
+    // Without --yes, and without a terminal to ask on.
+    let extract = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_extract-minecraft-assets"))
+            .args([
+                "jar",
+                jar.to_str().unwrap(),
+                "--assets",
+                "--json",
+                "--output",
+            ])
+            .arg(&out)
+            .args(args)
+            .env("HOME", dir.path())
+            .env("XDG_CONFIG_HOME", dir.path().join(".config"))
+            .stdin(Stdio::null())
+            .output()
+            .unwrap();
+        let document = serde_json::from_slice(&output.stdout).unwrap();
+
+        (output.status.code(), normalize(document, &dir))
+    };
+
+    let (code, document) = extract(&["--confirm-over", "64"]);
+    assert_eq!(code, Some(1));
+    assert_eq!(
+        document["error"],
+        "extracting 65 B into '<temp>/out' requires confirmation: pass --yes to proceed"
+    );
+    assert_eq!(fs::read_dir(&out).unwrap().count(), 0);
+
+    // Only the files which would be extracted count towards the size.
+    let (code, document) = extract(&["--confirm-over", "64", "--include", "*.json"]);
+    assert_eq!(code, Some(0), "{document}");
+    assert_eq!(document["summary"]["bytes"], 46);
+
+    // The threshold can be set in the config file, and is still overridden.
+    write_config(&dir, "confirm-over = \"10 B\"\n");
+    let (code, _) = extract(&[]);
+    assert_eq!(code, Some(1));
+    let (code, document) = extract(&["--confirm-over", "1 KiB"]);
+    assert_eq!(code, Some(0), "{document}");
+}
+
+#[test]
+fn profiles() {
+    let dir = common::temp_dir();
+    write_config(
+        &dir,
+        r#"
+[profile.archive]
+format-json = "pretty"
+validate-content = ["png", "json"]
+strict-permissions = false
+
+[profile.sounds]
+assets = true
+"#,
+    );
+
+    let (code, document) = run_json(&dir, &["profiles", "--format-json", "minify"]);
+
+    assert_eq!(code, Some(0));
+    assert_snapshot("profiles", &document);
+}
+
+#[test]
+fn profiles_are_overridden_by_the_command_line() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client().write(&dir.path().join("client.jar"));
+    write_config(
+        &dir,
+        r#"
+[profile.dev]
+assets = true
+format-json = "pretty"
+text-normalize = "lf"
+"#,
+    );
+    let jar = jar.to_str().unwrap();
+
+    let (code, document) = run_json(
+        &dir,
+        &["--profile", "dev", "jar", jar, "--format-json", "minify"],
+    );
+
+    assert_eq!(code, Some(0), "{document}");
+    assert_eq!(document["options"]["formatJson"], "minify");
+    assert_eq!(document["options"]["textNormalize"], "lf");
+    assert!(
+        document["files"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .all(|file| file["path"].as_str().unwrap().starts_with("assets/"))
+    );
+
+    let (code, document) = run_json(&dir, &["--profile", "release", "jar", jar]);
+    assert_eq!(code, Some(1));
+    assert_eq!(
+        document["error"],
+        "no profile 'release' in the config file; the available profiles are dev"
+    );
+
+    let (code, document) = run_json(&dir, &["--profile", "dev", "indexes"]);
+    assert_eq!(code, Some(1));
+    assert_eq!(
+        document["error"],
+        "invalid option 'assets' in profile 'dev': 'indexes' has no --assets option"
+    );
+}
+
+#[test]
+fn written_files_are_listed() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client()
+        .file("assets/minecraft/texts/two\nlines.txt", "Hello")
+        .write(&dir.path().join("client.jar"));
+    let jar = jar.to_str().unwrap();
+    let list = dir.path().join("written.txt");
+
+    let (code, document) = run_json(
+        &dir,
+        &[
+            "jar",
+            jar,
+            "--assets",
+            "--written-list",
+            list.to_str().unwrap(),
+        ],
+    );
+
+    assert_eq!(code, Some(0), "{document}");
+    let output_dir = dir.path().join("out");
+    let mut expected: Vec<_> = document["files"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|file| output_dir.join(file["path"].as_str().unwrap()))
+        .collect();
+    expected.sort();
+    assert!(expected.iter().any(|path| path.ends_with("two\nlines.txt")));
+
+    let listed = |contents: &[u8], terminator: u8| {
+        let mut paths: Vec<_> = contents
+            .strip_suffix(&[terminator])
+            .unwrap()
+            .split(|&byte| byte == terminator)
+            .map(|path| PathBuf::from(str::from_utf8(path).unwrap()))
+            .collect();
+        paths.sort();
+        paths
+    };
+    assert_eq!(listed(&fs::read(&list).unwrap(), b'\0'), expected);
+
+    // Extracting again prints nothing but the same paths to stdout.
+    let output = Command::new(env!("CARGO_BIN_EXE_extract-minecraft-assets"))
+        .args(["jar", jar, "--assets", "--print0", "--yes", "--output"])
+        .arg(&output_dir)
+        .env("HOME", dir.path())
+        .env("XDG_CONFIG_HOME", dir.path().join(".config"))
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(listed(&output.stdout, b'\0'), expected);
+}
+
+/// Sends a request with `method` for `target` to the server at `address`,
+/// returning the response's head and body.
+fn request(address: &str, method: &str, target: &str) -> (String, Vec<u8>) {
+    let mut stream = TcpStream::connect(address).unwrap();
+    write!(
+        stream,
+        "{method} {target} HTTP/1.1\r\nHost: {address}\r\n\r\n"
+    )
+    .unwrap();
+
+    let mut response = Vec::new();
+    stream.read_to_end(&mut response).unwrap();
+    let end = response
+        .windows(4)
+        .position(|window| window == b"\r\n\r\n")
+        .unwrap();
+
+    (
+        String::from_utf8(response[..end].to_vec()).unwrap(),
+        response[end + 4..].to_vec(),
+    )
+}
+
+#[test]
+fn serve() {
+    let dir = common::temp_dir();
+    let version = common::write_manifest(&dir.path().join("versions"), "1.0", "\"assets\": \"5\"");
+    JarBuilder::client().write(&version.join("1.0.jar"));
+    let store = AssetStoreBuilder::new()
+        .file("minecraft/lang/de_de.json", "{\"a\": \"b\"}\n")
+        .write(&dir.path().join("assets"));
+
+    let mut server = Command::new(env!("CARGO_BIN_EXE_extract-minecraft-assets"))
+        .args([
+            "serve",
+            version.to_str().unwrap(),
+            "--listen",
+            "127.0.0.1:0",
+        ])
+        .arg("--hashed-assets")
+        .arg(&store.dir)
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stderr = BufReader::new(server.stderr.take().unwrap());
+    let mut line = String::new();
+    stderr.read_line(&mut line).unwrap();
+    let address = line
+        .split("http://")
+        .nth(1)
+        .and_then(|rest| rest.split('/').next())
+        .unwrap_or_else(|| panic!("no address in '{line}'"))
+        .to_owned();
+
+    let (head, body) = request(&address, "GET", "/assets/minecraft/lang/de_de.json");
+    assert!(head.starts_with("HTTP/1.1 200 OK"), "{head}");
+    assert!(head.contains("Content-Type: application/json"), "{head}");
+    assert!(head.contains("Content-Length: 11"), "{head}");
+    assert_eq!(body, b"{\"a\": \"b\"}\n");
+
+    let (head, body) = request(&address, "HEAD", "/assets/minecraft/texts/splashes.txt");
+    assert!(head.contains("Content-Type: text/plain"), "{head}");
+    assert!(body.is_empty());
+
+    let (head, body) = request(&address, "GET", "/assets/minecraft/");
+    assert!(head.starts_with("HTTP/1.1 200 OK"), "{head}");
+    let listing: Value = serde_json::from_slice(&body).unwrap();
+    assert_snapshot("serve_listing", &listing);
+
+    for (method, target, status) in [
+        ("GET", "/assets/minecraft/missing.png", "404"),
+        ("GET", "/data/minecraft/", "404"),
+        ("GET", "/assets/../versions/1.0/1.0.json", "400"),
+        ("POST", "/assets/", "405"),
+    ] {
+        let (head, _) = request(&address, method, target);
+        assert!(
+            head.starts_with(&format!("HTTP/1.1 {status}")),
+            "{target}: {head}"
+        );
+    }
+
+    server.kill().unwrap();
+    server.wait().unwrap();
+}
+
+/// Returns the completions of the last of `words`, with the home directory
+/// set to `dir`.
+fn complete(dir: &TempDir, words: &[&str]) -> Vec<String> {
+    let output = Command::new(env!("CARGO_BIN_EXE_extract-minecraft-assets"))
+        .args(["__complete", "--"])
+        .args(words)
+        .env("HOME", dir.path())
+        .env("XDG_CONFIG_HOME", dir.path().join(".config"))
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(0));
+
+    String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(str::to_owned)
+        .collect()
+}
+
+#[test]
+fn installed_versions_indexes_and_locales_are_completed() {
+    let dir = common::temp_dir();
+
+    // Nothing is installed yet, so only subcommands and options complete.
+    assert!(complete(&dir, &["version", ""]).is_empty());
+    assert!(complete(&dir, &["lang", "1.20.1", "--locale", ""]).is_empty());
+    assert_eq!(
+        complete(&dir, &["dif"]),
+        ["diff-index", "diff-jar", "diff-output"]
+    );
+    assert_eq!(complete(&dir, &["jar", "--allow"]), ["--allow-empty"]);
+
+    let minecraft_dir = dir.path().join(".minecraft");
+    common::write_manifest(
+        &minecraft_dir.join("versions"),
+        "1.20.1",
+        "\"assets\": \"5\"",
+    );
+    common::write_manifest(
+        &minecraft_dir.join("versions"),
+        "1.19",
+        "\"assets\": \"1.19\"",
+    );
+    AssetStoreBuilder::new()
+        .index_id("5")
+        .file("minecraft/lang/de_de.json", "{}")
+        .file("minecraft/lang/fr_fr.json", "{}")
+        .file("realms/lang/es_es.json", "{}")
+        .write(&minecraft_dir.join("assets"));
+
+    assert_eq!(complete(&dir, &["version", ""]), ["1.19", "1.20.1"]);
+    assert_eq!(complete(&dir, &["du", "version", "1.2"]), ["1.20.1"]);
+    assert_eq!(complete(&dir, &["hashed", "--index", ""]), ["5"]);
+    assert_eq!(complete(&dir, &["hashed", "--index=5"]), ["--index=5"]);
+    assert_eq!(
+        complete(&dir, &["lang", "1.20.1", "--locale", ""]),
+        ["de_de", "fr_fr"]
+    );
+    assert_eq!(
+        complete(&dir, &["jar", "--duplicate-entries", ""]),
+        ["last", "first", "error"]
+    );
+}
+
+#[test]
+fn progress_is_not_displayed_when_redirected() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::new()
+        .dir("data/")
+        .file("assets/minecraft/lang/en_us.json", "{}")
+        .file("assets/minecraft/texts/credits.json", "corrupted credits")
+        .corrupted()
+        .file(
+            "assets/minecraft/texts/splashes.txt",
+            "Also try Terraria!\n",
+        )
+        .write(&dir.path().join("client.jar"));
+    let output_dir = dir.path().join("out");
+    fs::create_dir_all(&output_dir).unwrap();
+
+    // Even without --porcelain, no progress is displayed as stderr isn't a
+    // terminal.
+    let output = Command::new(env!("CARGO_BIN_EXE_extract-minecraft-assets"))
+        .args(["jar", jar.to_str().unwrap(), "--assets", "--yes"])
+        .args(["--namespace", "minecraft", "--namespace", "realms"])
+        .arg("--output")
+        .arg(&output_dir)
+        .env("HOME", dir.path())
+        .env("XDG_CONFIG_HOME", dir.path().join(".config"))
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    // Failures and warnings are plain lines, without any status lines.
+    assert!(!stderr.contains(['\r', '\x1b']), "{stderr:?}");
+    let lines: Vec<_> = stderr.lines().collect();
+    assert_eq!(lines.len(), 3, "{stderr:?}");
+    assert!(lines[0].starts_with("Failed to extract 'assets/minecraft/texts/credits.json'"));
+    assert_eq!(
+        lines[1],
+        "Warning for 'assets/realms': namespace 'realms' matched 0 files"
+    );
+    assert!(lines[2].starts_with("Extraction finished with 1 error: 2 files (21 B)"));
+    assert_eq!(output.status.code(), Some(2));
+}
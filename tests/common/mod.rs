@@ -0,0 +1,365 @@
+//! Builders for the files which integration tests extract from: jar files
+//! with a given entry tree, and `.minecraft/assets` directories with an index
+//! file generated from a map of files.
+//!
+//! Everything is written into a [`TempDir`] which is removed when dropped.
+
+// Not every test uses every builder.
+#![allow(dead_code)]
+
+use extract_minecraft_assets::hashed::{IndexFile, Object};
+use extract_minecraft_assets::util;
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{Cursor, Write};
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, DateTime, ZipWriter};
+
+/// Returns a new temporary directory.
+pub fn temp_dir() -> TempDir {
+    tempfile::tempdir().unwrap()
+}
+
+/// An entry of a [`JarBuilder`].
+struct JarEntry {
+    path: String,
+    /// The contents of a file, or [`None`] for a directory.
+    contents: Option<Vec<u8>>,
+    mode: Option<u32>,
+    modified: Option<DateTime>,
+    corrupted: bool,
+    unreadable: bool,
+    renamed: Option<String>,
+}
+
+/// Builds a jar file with a given tree of entries.
+///
+/// # Examples
+/// ```ignore
+/// let dir = common::temp_dir();
+/// let jar = JarBuilder::new()
+///     .dir("assets/")
+///     .file("assets/minecraft/lang/en_us.json", "{}")
+///     .mode(0o100600)
+///     .write(&dir.path().join("client.jar"));
+/// ```
+#[derive(Default)]
+pub struct JarBuilder {
+    entries: Vec<JarEntry>,
+}
+
+impl JarBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a jar with a typical layout: `assets`, `data`, classes outside
+    /// of either, and a `shared.json` in both `assets` and `data` which collide
+    /// when the top-level directory is ignored.
+    pub fn client() -> Self {
+        Self::new()
+            .dir("assets/")
+            .dir("assets/minecraft/")
+            .file(
+                "assets/minecraft/lang/en_us.json",
+                "{\"menu.quit\": \"Quit Game\"}\n",
+            )
+            .file(
+                "assets/minecraft/texts/splashes.txt",
+                "Also try Terraria!\n",
+            )
+            .mode(0o100600)
+            .file("assets/minecraft/shared.json", "{\"from\": \"assets\"}\n")
+            .dir("data/")
+            .file("data/minecraft/tags/blocks/logs.json", "{\"values\": []}\n")
+            .file("data/minecraft/shared.json", "{\"from\": \"data\"}\n")
+            .file(
+                "net/minecraft/client/main/Main.class",
+                [0xca, 0xfe, 0xba, 0xbe],
+            )
+            .file("pack.mcmeta", "{\"pack\": {\"pack_format\": 15}}\n")
+    }
+
+    /// Adds a directory entry at `path`, which should end with `/`.
+    pub fn dir(mut self, path: &str) -> Self {
+        self.entries.push(JarEntry {
+            path: path.to_owned(),
+            contents: None,
+            mode: Some(0o40755),
+            modified: None,
+            corrupted: false,
+            unreadable: false,
+            renamed: None,
+        });
+        self
+    }
+
+    /// Adds a file entry at `path` containing `contents`.
+    pub fn file(mut self, path: &str, contents: impl AsRef<[u8]>) -> Self {
+        self.entries.push(JarEntry {
+            path: path.to_owned(),
+            contents: Some(contents.as_ref().to_owned()),
+            mode: Some(0o100644),
+            modified: None,
+            corrupted: false,
+            unreadable: false,
+            renamed: None,
+        });
+        self
+    }
+
+    fn last(&mut self) -> &mut JarEntry {
+        self.entries.last_mut().expect("no entry has been added")
+    }
+
+    /// Sets the unix mode of the last entry.
+    pub fn mode(mut self, mode: u32) -> Self {
+        self.last().mode = Some(mode);
+        self
+    }
+
+    /// Sets the modification time of the last entry.
+    pub fn modified(mut self, year: u16, month: u8, day: u8) -> Self {
+        self.last().modified =
+            Some(DateTime::from_date_and_time(year, month, day, 0, 0, 0).unwrap());
+        self
+    }
+
+    /// Corrupts the contents of the last entry, so that reading it fails its
+    /// checksum.
+    ///
+    /// The contents of the entry must not appear earlier in the jar.
+    pub fn corrupted(mut self) -> Self {
+        self.last().corrupted = true;
+        self
+    }
+
+    /// Gives the last entry an unknown compression method in the central
+    /// directory, so that it can't be read at all although it is listed.
+    ///
+    /// The path of the entry must not appear in the contents of the jar.
+    pub fn unreadable(mut self) -> Self {
+        self.last().unreadable = true;
+        self
+    }
+
+    /// Renames the last entry to `path` once the jar is built, e.g. to give
+    /// several entries the same name, which `ZipWriter` refuses.
+    ///
+    /// `path` must be as long as the path of the entry, which must not appear
+    /// in the contents of the jar.
+    pub fn renamed(mut self, path: &str) -> Self {
+        assert_eq!(self.last().path.len(), path.len());
+        self.last().renamed = Some(path.to_owned());
+        self
+    }
+
+    /// Returns the contents of the jar file.
+    pub fn build(self) -> Vec<u8> {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+
+        for entry in &self.entries {
+            let mut options = SimpleFileOptions::default();
+            if let Some(mode) = entry.mode {
+                options = options.unix_permissions(mode);
+            }
+            if let Some(modified) = entry.modified {
+                options = options.last_modified_time(modified);
+            }
+            if entry.corrupted {
+                // Stored, so that the contents can be found in the archive.
+                options = options.compression_method(CompressionMethod::Stored);
+            }
+
+            match &entry.contents {
+                Some(contents) => {
+                    zip.start_file(entry.path.as_str(), options).unwrap();
+                    zip.write_all(contents).unwrap();
+                }
+                None => zip.add_directory(entry.path.as_str(), options).unwrap(),
+            }
+        }
+
+        let mut jar = zip.finish().unwrap().into_inner();
+
+        for entry in self.entries.iter().filter(|entry| entry.corrupted) {
+            let contents = entry
+                .contents
+                .as_deref()
+                .expect("only files can be corrupted");
+            let offset = jar
+                .windows(contents.len())
+                .position(|window| window == contents)
+                .unwrap();
+            jar[offset] ^= 0xff;
+        }
+        for entry in self.entries.iter().filter(|entry| entry.unreadable) {
+            // The name appears in the local header, then in the central
+            // directory after 46 bytes of its header, the compression method
+            // being at 10 of them.
+            let offset = jar
+                .windows(entry.path.len())
+                .enumerate()
+                .filter(|(_, window)| *window == entry.path.as_bytes())
+                .nth(1)
+                .unwrap()
+                .0;
+            jar[offset - 36..offset - 34].copy_from_slice(&97_u16.to_le_bytes());
+        }
+        for entry in &self.entries {
+            let Some(renamed) = &entry.renamed else {
+                continue;
+            };
+            // The name appears in both the local header and the central
+            // directory.
+            let offsets: Vec<_> = jar
+                .windows(entry.path.len())
+                .enumerate()
+                .filter(|(_, window)| *window == entry.path.as_bytes())
+                .map(|(offset, _)| offset)
+                .collect();
+            for offset in offsets {
+                jar[offset..offset + renamed.len()].copy_from_slice(renamed.as_bytes());
+            }
+        }
+
+        jar
+    }
+
+    /// Writes the jar file to `path`, returning `path`.
+    pub fn write(self, path: &Path) -> PathBuf {
+        fs::write(path, self.build()).unwrap();
+
+        path.to_owned()
+    }
+}
+
+/// Builds a `.minecraft/assets` directory containing hashed files and an
+/// index file listing them.
+///
+/// # Examples
+/// ```ignore
+/// let dir = common::temp_dir();
+/// let store = AssetStoreBuilder::new()
+///     .file("minecraft/lang/de_de.json", "{}")
+///     .write(dir.path());
+///
+/// let index = store.index();
+/// ```
+pub struct AssetStoreBuilder {
+    index_id: String,
+    files: BTreeMap<String, Vec<u8>>,
+    missing: BTreeMap<String, Vec<u8>>,
+}
+
+/// A `.minecraft/assets` directory written by an [`AssetStoreBuilder`].
+pub struct AssetStore {
+    /// The `assets` directory itself.
+    pub dir: PathBuf,
+    /// The index file within `indexes/`.
+    pub index_file: PathBuf,
+}
+
+impl AssetStoreBuilder {
+    /// Returns a builder of an empty store with the index file `5.json`.
+    pub fn new() -> Self {
+        Self {
+            index_id: "5".to_owned(),
+            files: BTreeMap::new(),
+            missing: BTreeMap::new(),
+        }
+    }
+
+    /// Returns a store with a language file and a sound, and a sound which is
+    /// listed in the index but missing.
+    pub fn typical() -> Self {
+        Self::new()
+            .file(
+                "minecraft/lang/de_de.json",
+                "{\"menu.quit\": \"Spiel beenden\"}\n",
+            )
+            .file(
+                "minecraft/sounds/ambient/cave/cave1.ogg",
+                "OggS fake cave sound\n",
+            )
+            .missing("minecraft/sounds/missing.ogg", "missing")
+    }
+
+    /// Sets the name of the index file, without the `json` extension.
+    pub fn index_id(mut self, index_id: &str) -> Self {
+        self.index_id = index_id.to_owned();
+        self
+    }
+
+    /// Adds the asset at `path`, relative to `assets`, containing `contents`.
+    pub fn file(mut self, path: &str, contents: impl AsRef<[u8]>) -> Self {
+        self.files
+            .insert(path.to_owned(), contents.as_ref().to_owned());
+        self
+    }
+
+    /// Lists the asset at `path` containing `contents` in the index, without
+    /// writing its hashed file.
+    pub fn missing(mut self, path: &str, contents: impl AsRef<[u8]>) -> Self {
+        self.missing
+            .insert(path.to_owned(), contents.as_ref().to_owned());
+        self
+    }
+
+    /// Writes the store into `dir`, which becomes the `assets` directory.
+    pub fn write(self, dir: &Path) -> AssetStore {
+        let mut index = IndexFile::default();
+
+        for (path, contents) in &self.files {
+            let object = object(contents);
+            let object_path = dir.join("objects").join(object.hashed_file_path());
+
+            fs::create_dir_all(object_path.parent().unwrap()).unwrap();
+            fs::write(object_path, contents).unwrap();
+            index.insert(path, object);
+        }
+        for (path, contents) in &self.missing {
+            index.insert(path, object(contents));
+        }
+
+        let index_file = dir.join("indexes").join(format!("{}.json", self.index_id));
+        fs::create_dir_all(index_file.parent().unwrap()).unwrap();
+        index.to_writer(File::create(&index_file).unwrap()).unwrap();
+
+        AssetStore {
+            dir: dir.to_owned(),
+            index_file,
+        }
+    }
+}
+
+impl AssetStore {
+    /// Reads the store's index file.
+    pub fn index(&self) -> IndexFile {
+        IndexFile::from_path(&self.index_file).unwrap()
+    }
+}
+
+/// Writes the manifest of the version `name` into `versions_dir`, with the
+/// JSON `fields` after its `id`, returning the version directory.
+pub fn write_manifest(versions_dir: &Path, name: &str, fields: &str) -> PathBuf {
+    let dir = versions_dir.join(name);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(
+        dir.join(format!("{name}.json")),
+        format!("{{\"id\": \"{name}\", {fields}}}"),
+    )
+    .unwrap();
+
+    dir
+}
+
+/// Returns the index object of a hashed file containing `contents`.
+pub fn object(contents: &[u8]) -> Object {
+    Object {
+        hash: util::sha1_hex(contents).unwrap(),
+        size: contents.len() as u64,
+    }
+}
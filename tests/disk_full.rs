@@ -0,0 +1,164 @@
+mod common;
+
+use common::{AssetStoreBuilder, JarBuilder};
+use extract_minecraft_assets::options::ExtractOptions;
+use extract_minecraft_assets::progress::NoProgress;
+use extract_minecraft_assets::report::{ExtractionReport, FileOutcome};
+use extract_minecraft_assets::vfs::{MemoryFs, Metadata, Vfs};
+use extract_minecraft_assets::{hashed, jar};
+use std::io::{self, Read};
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// A [`MemoryFs`] on a volume which is full after `capacity` bytes, at which
+/// point files are left partially written.
+struct FullFs {
+    fs: MemoryFs,
+    capacity: u64,
+    used: AtomicU64,
+}
+
+impl FullFs {
+    fn new(capacity: u64) -> Self {
+        Self {
+            fs: MemoryFs::new(),
+            capacity,
+            used: AtomicU64::new(0),
+        }
+    }
+}
+
+impl Vfs for FullFs {
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        self.fs.create_dir_all(path)
+    }
+
+    fn write_file(&self, path: &Path, contents: &mut dyn Read) -> io::Result<u64> {
+        let mut buffer = Vec::new();
+        contents.read_to_end(&mut buffer)?;
+
+        // Files may be written by several threads at once.
+        let mut fits = 0;
+        let _ = self
+            .used
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |used| {
+                fits = (self.capacity - used).min(buffer.len() as u64);
+                Some(used + fits)
+            });
+        self.fs.write_file(path, &mut &buffer[..fits as usize])?;
+
+        if fits < buffer.len() as u64 {
+            return Err(io::Error::from(io::ErrorKind::StorageFull));
+        }
+
+        Ok(fits)
+    }
+
+    fn set_unix_mode(&self, path: &Path, mode: u32) -> io::Result<()> {
+        self.fs.set_unix_mode(path, mode)
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<Metadata> {
+        self.fs.metadata(path)
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        self.fs.rename(from, to)
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        self.fs.remove_file(path)
+    }
+
+    fn remove_dir_all(&self, path: &Path) -> io::Result<()> {
+        self.fs.remove_dir_all(path)
+    }
+
+    fn copy(&self, from: &Path, to: &Path) -> io::Result<u64> {
+        self.fs.copy(from, to)
+    }
+}
+
+/// Checks that `report` stopped at the file which didn't fit, which was
+/// removed from `fs`, leaving `remaining_bytes` to extract.
+fn assert_stopped(report: &ExtractionReport, fs: &FullFs, remaining_bytes: u64) {
+    let last = report.files.last().unwrap();
+
+    assert!(report.summary.disk_full);
+    assert_eq!(report.summary.failures, 1);
+    assert!(matches!(last.outcome, FileOutcome::Failed { .. }));
+    assert_eq!(fs.fs.read(&Path::new("out").join(&last.path)), None);
+    assert_eq!(report.summary.remaining_bytes, remaining_bytes);
+    assert!(report.summary.to_string().contains("(disk full, "));
+}
+
+#[test]
+fn jar_extraction_stops_when_the_disk_is_full() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::new()
+        .file("assets/minecraft/a.txt", "0123456789")
+        .file("assets/minecraft/b.txt", "0123456789")
+        .file("assets/minecraft/c.txt", "0123456789")
+        .file("pack.mcmeta", "{}")
+        .write(&dir.path().join("client.jar"));
+
+    let fs = Arc::new(FullFs::new(15));
+    let options = ExtractOptions::new().output("out").vfs(fs.clone());
+    let report = jar::extract_jar(&jar, &options, &mut NoProgress).unwrap();
+
+    assert_eq!(report.summary.files, 1);
+    assert_eq!(report.files.len(), 2);
+    assert_stopped(&report, &fs, 20);
+}
+
+#[test]
+fn hashed_extraction_stops_when_the_disk_is_full() {
+    let dir = common::temp_dir();
+    let store = AssetStoreBuilder::new()
+        .file("minecraft/sounds/a.ogg", "0123456789")
+        .file("minecraft/sounds/b.ogg", "0123456789")
+        .write(&dir.path().join("assets"));
+
+    let fs = Arc::new(FullFs::new(5));
+    let options = ExtractOptions::new().output("out").vfs(fs.clone());
+    let report =
+        hashed::extract_hashed_assets(&store.dir, &store.index(), &options, &mut NoProgress)
+            .unwrap();
+
+    assert_eq!(report.summary.files, 0);
+    assert_eq!(report.files.len(), 1);
+    assert_stopped(&report, &fs, 20);
+}
+
+#[test]
+fn parallel_hashed_extraction_stops_when_the_disk_is_full() {
+    let dir = common::temp_dir();
+    let mut store = AssetStoreBuilder::new();
+    for i in 0..20 {
+        store = store.file(
+            &format!("minecraft/sounds/{i:02}.ogg"),
+            format!("{i:02}45678..."),
+        );
+    }
+    let store = store.write(&dir.path().join("assets"));
+
+    let fs = Arc::new(FullFs::new(35));
+    let options = ExtractOptions::new()
+        .output("out")
+        .vfs(fs.clone())
+        .threads(4);
+    let report =
+        hashed::extract_hashed_assets(&store.dir, &store.index(), &options, &mut NoProgress)
+            .unwrap();
+
+    assert!(report.summary.disk_full);
+    assert_eq!(report.summary.files, 3);
+    // Objects already started are finished, but no more are started.
+    assert!(report.files.len() < 20);
+    for file in &report.files {
+        if let FileOutcome::Failed { .. } = file.outcome {
+            assert_eq!(fs.fs.read(&Path::new("out").join(&file.path)), None);
+        }
+    }
+}
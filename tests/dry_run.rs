@@ -0,0 +1,68 @@
+mod common;
+
+use common::{AssetStoreBuilder, JarBuilder};
+use extract_minecraft_assets::hashed;
+use extract_minecraft_assets::jar::{self, ExtractedContents};
+use extract_minecraft_assets::options::{ExtractOptions, Overwrite};
+use extract_minecraft_assets::progress::NoProgress;
+use extract_minecraft_assets::report::FileOutcome;
+use extract_minecraft_assets::vfs::DryRunFs;
+use std::fs;
+use std::sync::Arc;
+
+#[test]
+fn dry_runs_report_every_file_without_writing_any() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client().write(&dir.path().join("client.jar"));
+    let store = AssetStoreBuilder::typical().write(&dir.path().join("assets"));
+    let out = dir.path().join("out");
+    fs::create_dir(&out).unwrap();
+    let options = ExtractOptions::new()
+        .output(&out)
+        .contents(ExtractedContents::all())
+        .vfs(Arc::new(DryRunFs));
+
+    let report = jar::extract_jar(&jar, &options, &mut NoProgress).unwrap()
+        + hashed::extract_hashed_assets(&store.dir, &store.index(), &options, &mut NoProgress)
+            .unwrap();
+
+    assert!(report.summary.files > 0);
+    assert!(report.summary.bytes > 0);
+    // The hashed file missing from the store still fails.
+    assert_eq!(report.summary.failures, 1);
+    assert_eq!(fs::read_dir(&out).unwrap().count(), 0);
+}
+
+#[test]
+fn dry_runs_skip_existing_files_as_extraction_would() {
+    let dir = common::temp_dir();
+    let store = AssetStoreBuilder::new()
+        .file("minecraft/lang/de_de.json", "{}\n")
+        .file("minecraft/lang/fr_fr.json", "{}\n")
+        .write(&dir.path().join("assets"));
+    let out = dir.path().join("out");
+    fs::create_dir_all(out.join("assets/minecraft/lang")).unwrap();
+    fs::write(out.join("assets/minecraft/lang/de_de.json"), "mine\n").unwrap();
+    let options = ExtractOptions::new()
+        .output(&out)
+        .overwrite(Overwrite::Skip)
+        .vfs(Arc::new(DryRunFs));
+
+    let report =
+        hashed::extract_hashed_assets(&store.dir, &store.index(), &options, &mut NoProgress)
+            .unwrap();
+
+    assert_eq!(report.summary.files, 1);
+    assert_eq!(report.summary.skipped, 1);
+    assert!(
+        report
+            .files
+            .iter()
+            .any(|file| file.outcome == FileOutcome::Skipped)
+    );
+    assert_eq!(
+        fs::read_to_string(out.join("assets/minecraft/lang/de_de.json")).unwrap(),
+        "mine\n"
+    );
+    assert!(!out.join("assets/minecraft/lang/fr_fr.json").exists());
+}
@@ -0,0 +1,66 @@
+mod common;
+
+use common::{AssetStoreBuilder, JarBuilder};
+use extract_minecraft_assets::duplicates::DuplicateFinder;
+use extract_minecraft_assets::jar::ExtractedContents;
+use std::path::PathBuf;
+
+#[test]
+fn duplicates_are_found_across_jar_and_index() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::new()
+        .file("assets/minecraft/lang/en_us.json", "{}\n")
+        .file("assets/minecraft/lang/en_gb.json", "{}\n")
+        .file(
+            "assets/minecraft/texts/splashes.txt",
+            "Also try Terraria!\n",
+        )
+        .file("data/minecraft/tags/blocks/logs.json", "{}\n")
+        .write(&dir.path().join("client.jar"));
+    let store = AssetStoreBuilder::typical()
+        .file("minecraft/lang/en_au.json", "{}\n")
+        .write(&dir.path().join("assets"));
+
+    let mut finder = DuplicateFinder::new();
+    finder.add_jar(&jar, &ExtractedContents::assets()).unwrap();
+    finder.add_index(&store.index());
+    let clusters = finder.clusters(0);
+
+    assert_eq!(clusters.len(), 1);
+    assert_eq!(
+        clusters[0].paths,
+        [
+            "assets/minecraft/lang/en_au.json",
+            "assets/minecraft/lang/en_gb.json",
+            "assets/minecraft/lang/en_us.json",
+        ]
+        .map(PathBuf::from)
+    );
+    assert_eq!((clusters[0].size, clusters[0].wasted_bytes), (3, 6));
+}
+
+#[test]
+fn duplicates_are_sorted_by_waste_and_filtered_by_size() {
+    let mut finder = DuplicateFinder::new();
+    for (path, hash, size) in [
+        ("a", "small", 10),
+        ("b", "small", 10),
+        ("c", "small", 10),
+        ("d", "large", 100),
+        ("e", "large", 100),
+        ("f", "unique", 1000),
+    ] {
+        finder.add(path.as_ref(), hash, size);
+    }
+
+    let wasted = |min_size| {
+        finder
+            .clusters(min_size)
+            .iter()
+            .map(|cluster| cluster.wasted_bytes)
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(wasted(0), [100, 20]);
+    assert_eq!(wasted(11), [100]);
+}
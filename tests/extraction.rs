@@ -0,0 +1,1083 @@
+mod common;
+
+use common::{AssetStore, AssetStoreBuilder, JarBuilder};
+use extract_minecraft_assets::error::ExtractError;
+use extract_minecraft_assets::hashed;
+use extract_minecraft_assets::jar::{self, ContentRoot, ExtractedContents, JarAssets};
+use extract_minecraft_assets::options::{
+    CancellationToken, DuplicateEntries, ExtractOptions, Overwrite, OverwritePrompt,
+};
+use extract_minecraft_assets::progress::{NoProgress, ProgressSink};
+use extract_minecraft_assets::report::{ExtractionReport, FileOutcome};
+use extract_minecraft_assets::search::FileSource;
+use extract_minecraft_assets::vfs::{MemoryFs, Vfs};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tempfile::TempDir;
+
+/// A typical jar file and hashed assets directory to extract from.
+struct Fixture {
+    _dir: TempDir,
+    jar: PathBuf,
+    store: AssetStore,
+}
+
+fn fixture() -> Fixture {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client().write(&dir.path().join("client.jar"));
+    let store = AssetStoreBuilder::typical().write(&dir.path().join("assets"));
+
+    Fixture {
+        _dir: dir,
+        jar,
+        store,
+    }
+}
+
+/// Returns options extracting into `out` within a new [`MemoryFs`].
+fn memory_options() -> (Arc<MemoryFs>, ExtractOptions) {
+    let fs = Arc::new(MemoryFs::new());
+    let options = ExtractOptions::new().output("out").vfs(fs.clone());
+
+    (fs, options)
+}
+
+fn extract_jar(options: &ExtractOptions) -> ExtractionReport {
+    jar::extract_jar(&fixture().jar, options, &mut NoProgress).unwrap()
+}
+
+fn extract_hashed_assets(options: &ExtractOptions) -> ExtractionReport {
+    let Fixture { store, .. } = &fixture();
+
+    hashed::extract_hashed_assets(&store.dir, &store.index(), options, &mut NoProgress).unwrap()
+}
+
+fn read(fs: &MemoryFs, path: &str) -> String {
+    String::from_utf8(fs.read(Path::new(path)).unwrap()).unwrap()
+}
+
+fn paths(paths: &[&str]) -> Vec<PathBuf> {
+    paths.iter().map(PathBuf::from).collect()
+}
+
+#[test]
+fn jar_extracts_assets_by_default() {
+    let (fs, options) = memory_options();
+    let report = extract_jar(&options);
+
+    assert_eq!(
+        fs.files(),
+        paths(&[
+            "out/assets/minecraft/lang/en_us.json",
+            "out/assets/minecraft/shared.json",
+            "out/assets/minecraft/texts/splashes.txt",
+        ])
+    );
+    assert_eq!(report.summary.files, 3);
+    assert_eq!(
+        read(&fs, "out/assets/minecraft/texts/splashes.txt"),
+        "Also try Terraria!\n"
+    );
+    assert_eq!(
+        fs.unix_mode(Path::new("out/assets/minecraft/texts/splashes.txt")),
+        Some(0o100600)
+    );
+}
+
+#[test]
+fn default_options_extract_assets_as_they_are() {
+    let options = ExtractOptions::default();
+
+    assert_eq!(options.output_dir, Path::new("."));
+    assert_eq!(options.contents, ExtractedContents::assets());
+    assert!(options.include.is_empty() && options.exclude.is_empty());
+    assert!(!options.ignore_top_level);
+    assert!(!options.strip_namespace);
+    assert_eq!(options.overwrite, Overwrite::Replace);
+    assert_eq!((options.threads, options.concurrency), (1, 16));
+    assert!(options.cancellation.is_none());
+}
+
+#[test]
+fn builder_options_are_combined() {
+    let (fs, options) = memory_options();
+    fs.create_dir_all(Path::new("out/minecraft")).unwrap();
+    fs.write_file(Path::new("out/minecraft/shared.json"), &mut "{}".as_bytes())
+        .unwrap();
+
+    let options = options
+        .contents(ExtractedContents::assets())
+        .ignore_top_level(true)
+        .overwrite(Overwrite::Skip);
+    let report = extract_jar(&options);
+
+    assert_eq!(
+        fs.files(),
+        paths(&[
+            "out/minecraft/lang/en_us.json",
+            "out/minecraft/shared.json",
+            "out/minecraft/texts/splashes.txt",
+        ])
+    );
+    assert_eq!(read(&fs, "out/minecraft/shared.json"), "{}");
+    assert_eq!((report.summary.files, report.summary.skipped), (2, 1));
+    assert_eq!(report.options, options);
+}
+
+#[test]
+fn jar_extracts_selected_contents() {
+    let (fs, options) = memory_options();
+    extract_jar(&options.contents(ExtractedContents::data()));
+
+    assert_eq!(
+        fs.files(),
+        paths(&[
+            "out/data/minecraft/shared.json",
+            "out/data/minecraft/tags/blocks/logs.json",
+        ])
+    );
+}
+
+#[test]
+fn jar_extracts_custom_roots() {
+    let (fs, options) = memory_options();
+    let contents =
+        ExtractedContents::none().with(ContentRoot::new("code", "net/minecraft", "classes"));
+    extract_jar(&options.contents(contents));
+
+    assert_eq!(fs.files(), paths(&["out/classes/client/main/Main.class"]));
+}
+
+#[test]
+fn jar_extracts_custom_roots_alongside_standard_ones() {
+    let (fs, options) = memory_options();
+    let contents = ExtractedContents::assets()
+        .with(ContentRoot::new("code", "net/minecraft", "classes"))
+        .with(ContentRoot::new("assets", "assets/minecraft/lang", "lang"));
+    let report = extract_jar(&options.contents(contents.clone()));
+
+    // The second `assets` root replaces the first.
+    assert_eq!(contents.roots().len(), 2);
+    assert_eq!(
+        fs.files(),
+        paths(&["out/classes/client/main/Main.class", "out/lang/en_us.json"])
+    );
+    assert_eq!(report.options.contents, contents);
+}
+
+#[test]
+fn contents_are_listed_grammatically() {
+    let code = ContentRoot::new("code", "net/minecraft", "classes");
+
+    assert_eq!(ExtractedContents::none().to_string(), "nothing");
+    assert_eq!(ExtractedContents::assets().to_string(), "assets");
+    assert_eq!(ExtractedContents::all().to_string(), "assets and data");
+    assert_eq!(
+        ExtractedContents::all().with(code).to_string(),
+        "assets, data and code"
+    );
+}
+
+#[test]
+fn jar_filter_limits_extracted_files() {
+    let (fs, options) = memory_options();
+    let report = jar::extract_jar_filtered(
+        &fixture().jar,
+        &options.contents(ExtractedContents::all()),
+        &mut NoProgress,
+        |path| {
+            path.extension()
+                .is_some_and(|extension| extension == "json")
+        },
+    )
+    .unwrap();
+
+    assert_eq!(report.summary.files, 4);
+    assert!(!fs.exists(Path::new("out/assets/minecraft/texts/splashes.txt")));
+}
+
+#[test]
+fn jar_assets_yield_selected_entries() {
+    let fixture = fixture();
+    let mut assets = JarAssets::open(&fixture.jar, ExtractedContents::all())
+        .unwrap()
+        .filter(|path| path.starts_with("data") || path.ends_with("en_us.json"));
+    let mut entries = Vec::new();
+
+    while let Some(mut entry) = assets.next_entry().unwrap() {
+        let mut contents = String::new();
+        entry.read_to_string(&mut contents).unwrap();
+
+        entries.push((
+            entry.output_path(false),
+            entry.root().name.clone(),
+            entry.size(),
+            contents,
+        ));
+    }
+
+    assert_eq!(
+        entries,
+        [
+            (
+                PathBuf::from("assets/minecraft/lang/en_us.json"),
+                "assets".to_owned(),
+                27,
+                "{\"menu.quit\": \"Quit Game\"}\n".to_owned()
+            ),
+            (PathBuf::from("data"), "data".to_owned(), 0, String::new()),
+            (
+                PathBuf::from("data/minecraft/tags/blocks/logs.json"),
+                "data".to_owned(),
+                15,
+                "{\"values\": []}\n".to_owned()
+            ),
+            (
+                PathBuf::from("data/minecraft/shared.json"),
+                "data".to_owned(),
+                17,
+                "{\"from\": \"data\"}\n".to_owned()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn jar_lists_top_level_dirs() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::new()
+        .dir("server/")
+        .dir("server/META-INF/")
+        .file("server/net/minecraft/server/Main.class", [0xca, 0xfe])
+        .file("server/version.json", "{}\n")
+        .write(&dir.path().join("server.jar"));
+
+    assert_eq!(jar::top_level_dirs(&jar).unwrap(), ["META-INF", "net"]);
+}
+
+#[test]
+fn jar_ignores_top_level() {
+    let (fs, options) = memory_options();
+    extract_jar(&options.ignore_top_level(true));
+
+    assert_eq!(
+        fs.files(),
+        paths(&[
+            "out/minecraft/lang/en_us.json",
+            "out/minecraft/shared.json",
+            "out/minecraft/texts/splashes.txt",
+        ])
+    );
+}
+
+#[test]
+fn jar_skips_existing_files() {
+    let (fs, options) = memory_options();
+    fs.create_dir_all(Path::new("out/assets/minecraft/lang"))
+        .unwrap();
+    fs.write_file(
+        Path::new("out/assets/minecraft/lang/en_us.json"),
+        &mut "{}".as_bytes(),
+    )
+    .unwrap();
+
+    let report = extract_jar(&options.overwrite(Overwrite::Skip));
+
+    assert_eq!(read(&fs, "out/assets/minecraft/lang/en_us.json"), "{}");
+    assert_eq!((report.summary.files, report.summary.skipped), (2, 1));
+    assert!(report.files.iter().any(|file| {
+        file.path == Path::new("assets/minecraft/lang/en_us.json")
+            && file.outcome == FileOutcome::Skipped
+    }));
+}
+
+#[test]
+fn jar_asks_whether_to_replace_existing_files() {
+    let (fs, options) = memory_options();
+    for path in [
+        "out/assets/minecraft/lang/en_us.json",
+        "out/assets/minecraft/texts/splashes.txt",
+    ] {
+        fs.create_dir_all(Path::new(path).parent().unwrap())
+            .unwrap();
+        fs.write_file(Path::new(path), &mut "edited".as_bytes())
+            .unwrap();
+    }
+
+    let asked = Arc::new(Mutex::new(Vec::new()));
+    let prompt = OverwritePrompt::new({
+        let asked = asked.clone();
+        move |path| {
+            asked.lock().unwrap().push(path.to_owned());
+            path.ends_with("splashes.txt")
+        }
+    });
+    let report = extract_jar(&options.overwrite(Overwrite::Ask).overwrite_prompt(prompt));
+
+    assert_eq!(read(&fs, "out/assets/minecraft/lang/en_us.json"), "edited");
+    assert_eq!(
+        read(&fs, "out/assets/minecraft/texts/splashes.txt"),
+        "Also try Terraria!\n"
+    );
+    assert_eq!(report.summary.skipped, 1);
+    // Only files which exist are asked about.
+    let mut asked = asked.lock().unwrap().clone();
+    asked.sort();
+    assert_eq!(
+        asked,
+        paths(&[
+            "out/assets/minecraft/lang/en_us.json",
+            "out/assets/minecraft/texts/splashes.txt",
+        ])
+    );
+}
+
+#[test]
+fn asking_without_a_prompt_keeps_existing_files() {
+    let (fs, options) = memory_options();
+    fs.create_dir_all(Path::new("out/assets/minecraft/lang"))
+        .unwrap();
+    fs.write_file(
+        Path::new("out/assets/minecraft/lang/de_de.json"),
+        &mut "{}".as_bytes(),
+    )
+    .unwrap();
+
+    let report = extract_hashed_assets(&options.overwrite(Overwrite::Ask));
+
+    assert_eq!(read(&fs, "out/assets/minecraft/lang/de_de.json"), "{}");
+    assert_eq!(report.summary.skipped, 1);
+}
+
+#[test]
+fn jar_replaces_existing_files() {
+    let (fs, options) = memory_options();
+    fs.create_dir_all(Path::new("out/assets/minecraft/lang"))
+        .unwrap();
+    fs.write_file(
+        Path::new("out/assets/minecraft/lang/en_us.json"),
+        &mut "{}".as_bytes(),
+    )
+    .unwrap();
+
+    let report = extract_jar(&options);
+
+    assert_eq!(
+        read(&fs, "out/assets/minecraft/lang/en_us.json"),
+        "{\"menu.quit\": \"Quit Game\"}\n"
+    );
+    assert_eq!((report.summary.files, report.summary.skipped), (3, 0));
+}
+
+#[test]
+fn colliding_roots_replace_earlier_files() {
+    let (fs, options) = memory_options();
+    let report = extract_jar(
+        &options
+            .contents(ExtractedContents::all())
+            .ignore_top_level(true),
+    );
+
+    assert_eq!(
+        read(&fs, "out/minecraft/shared.json"),
+        "{\"from\": \"data\"}\n"
+    );
+    assert_eq!(report.summary.files, 5);
+}
+
+#[test]
+fn colliding_roots_keep_earlier_files_when_skipping() {
+    let (fs, options) = memory_options();
+    let report = extract_jar(
+        &options
+            .contents(ExtractedContents::all())
+            .ignore_top_level(true)
+            .overwrite(Overwrite::Skip),
+    );
+
+    assert_eq!(
+        read(&fs, "out/minecraft/shared.json"),
+        "{\"from\": \"assets\"}\n"
+    );
+    assert_eq!((report.summary.files, report.summary.skipped), (4, 1));
+}
+
+#[test]
+fn hashed_assets_continue_past_missing_objects() {
+    let (fs, options) = memory_options();
+    let report = extract_hashed_assets(&options);
+
+    assert_eq!(
+        fs.files(),
+        paths(&[
+            "out/assets/minecraft/lang/de_de.json",
+            "out/assets/minecraft/sounds/ambient/cave/cave1.ogg",
+        ])
+    );
+    assert_eq!((report.summary.files, report.summary.failures), (2, 1));
+    assert_eq!(
+        report.failed().map(|file| &file.path).collect::<Vec<_>>(),
+        [Path::new("assets/minecraft/sounds/missing.ogg")]
+    );
+}
+
+#[test]
+fn reports_describe_every_file() {
+    let (_fs, options) = memory_options();
+    let report = extract_hashed_assets(&options.overwrite(Overwrite::Skip));
+
+    let mut files = report.files.clone();
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    let outcomes: Vec<_> = files
+        .iter()
+        .map(|file| (file.path.to_str().unwrap(), &file.outcome))
+        .collect();
+    assert_eq!(
+        outcomes[..2],
+        [
+            (
+                "assets/minecraft/lang/de_de.json",
+                &FileOutcome::Written { bytes: 31 }
+            ),
+            (
+                "assets/minecraft/sounds/ambient/cave/cave1.ogg",
+                &FileOutcome::Written { bytes: 21 }
+            ),
+        ]
+    );
+    assert_eq!(outcomes[2].0, "assets/minecraft/sounds/missing.ogg");
+    assert!(matches!(outcomes[2].1, FileOutcome::Failed { .. }));
+    assert!(
+        files
+            .iter()
+            .all(|file| file.source == FileSource::Hashed && file.hash.is_some())
+    );
+
+    assert_eq!(report.written().count(), 2);
+    assert_eq!(report.failed().count(), 1);
+    assert_eq!(report.summary.bytes, 52);
+    assert_eq!(report.options.output_dir, Path::new("out"));
+    assert_eq!(report.options.overwrite, Overwrite::Skip);
+
+    let json = serde_json::to_value(&report).unwrap();
+    assert_eq!(json["files"].as_array().unwrap().len(), 3);
+    assert_eq!(json["options"]["overwrite"], "skip");
+}
+
+#[test]
+fn hashed_assets_ignore_top_level() {
+    let (fs, options) = memory_options();
+    extract_hashed_assets(&options.ignore_top_level(true));
+
+    assert_eq!(
+        read(&fs, "out/minecraft/lang/de_de.json"),
+        "{\"menu.quit\": \"Spiel beenden\"}\n"
+    );
+}
+
+#[test]
+fn hashed_assets_skip_existing_files() {
+    let (fs, options) = memory_options();
+    fs.create_dir_all(Path::new("out/assets/minecraft/lang"))
+        .unwrap();
+    fs.write_file(
+        Path::new("out/assets/minecraft/lang/de_de.json"),
+        &mut "{}".as_bytes(),
+    )
+    .unwrap();
+
+    let report = extract_hashed_assets(&options.overwrite(Overwrite::Skip));
+
+    assert_eq!(read(&fs, "out/assets/minecraft/lang/de_de.json"), "{}");
+    assert_eq!(report.summary.skipped, 1);
+}
+
+/// Cancels its token as soon as a file starts being extracted.
+struct CancelOnFirstFile(CancellationToken);
+
+impl ProgressSink for CancelOnFirstFile {
+    fn file_started(&mut self, _path: &Path) {
+        self.0.cancel();
+    }
+}
+
+#[test]
+fn cancelled_extractions_return_partial_reports() {
+    let Fixture { jar, store, .. } = &fixture();
+
+    let token = CancellationToken::new();
+    token.cancel();
+    let (fs, options) = memory_options();
+    let report = jar::extract_jar(jar, &options.cancellation(token), &mut NoProgress).unwrap();
+
+    assert!(report.summary.cancelled);
+    assert_eq!(report.summary.files, 0);
+    assert!(fs.files().is_empty());
+
+    let token = CancellationToken::new();
+    let (fs, options) = memory_options();
+    let report = hashed::extract_hashed_assets(
+        &store.dir,
+        &store.index(),
+        &options.cancellation(token.clone()),
+        &mut CancelOnFirstFile(token),
+    )
+    .unwrap();
+
+    // The file being extracted is finished, but no more are started.
+    assert!(report.summary.cancelled);
+    assert_eq!(report.summary.files + report.summary.failures, 1);
+    assert_eq!(report.files.len(), 1);
+    assert_eq!(fs.files().len(), report.summary.files);
+}
+
+/// Returns a store whose index lists paths with spaces and percent-encoded
+/// sequences, as some third-party indexes do.
+fn encoded_paths_store(dir: &Path) -> AssetStore {
+    AssetStoreBuilder::new()
+        .file("minecraft/sounds/music/game/calm%201.ogg", "OggS calm\n")
+        .file(
+            "minecraft/textures/gui/title/background/panorama 0.png",
+            "\u{89}PNG panorama\n",
+        )
+        .file("minecraft/texts/100%.txt", "Also try Terraria!\n")
+        .file("minecraft/%2E%2E/escape.txt", "escape\n")
+        .write(&dir.join("assets"))
+}
+
+#[test]
+fn encoded_paths_are_decoded() {
+    let dir = common::temp_dir();
+    let store = encoded_paths_store(dir.path());
+    let output_dir = dir.path().join("out");
+
+    let options = ExtractOptions::new().output(&output_dir);
+    let report =
+        hashed::extract_hashed_assets(&store.dir, &store.index(), &options, &mut NoProgress)
+            .unwrap();
+
+    assert_eq!(report.summary.files, 4);
+    for path in [
+        "assets/minecraft/sounds/music/game/calm 1.ogg",
+        "assets/minecraft/textures/gui/title/background/panorama 0.png",
+        // Invalid sequences, and those which would leave their directory, aren't decoded.
+        "assets/minecraft/texts/100%.txt",
+        "assets/minecraft/%2E%2E/escape.txt",
+    ] {
+        assert!(output_dir.join(path).is_file(), "{path}");
+    }
+    assert!(
+        !output_dir
+            .join("assets/minecraft/sounds/music/game/calm%201.ogg")
+            .exists()
+    );
+}
+
+#[test]
+fn encoded_paths_are_kept_if_asked() {
+    let dir = common::temp_dir();
+    let store = encoded_paths_store(dir.path());
+    let output_dir = dir.path().join("out");
+
+    let options = ExtractOptions::new()
+        .output(&output_dir)
+        .decode_paths(false);
+    hashed::extract_hashed_assets(&store.dir, &store.index(), &options, &mut NoProgress).unwrap();
+
+    assert!(
+        output_dir
+            .join("assets/minecraft/sounds/music/game/calm%201.ogg")
+            .is_file()
+    );
+    assert!(
+        output_dir
+            .join("assets/minecraft/textures/gui/title/background/panorama 0.png")
+            .is_file()
+    );
+}
+
+#[test]
+fn files_in_the_way_of_directories_are_replaced() {
+    let (fs, options) = memory_options();
+    fs.create_dir_all(Path::new("out/assets")).unwrap();
+    fs.write_file(Path::new("out/assets/minecraft"), &mut "".as_bytes())
+        .unwrap();
+
+    let report = extract_jar(&options);
+
+    assert_eq!(report.summary.failures, 0);
+    assert_eq!(
+        read(&fs, "out/assets/minecraft/lang/en_us.json"),
+        "{\"menu.quit\": \"Quit Game\"}\n"
+    );
+}
+
+#[test]
+fn directories_in_the_way_of_files_fail() {
+    let (fs, options) = memory_options();
+    fs.create_dir_all(Path::new("out/assets/minecraft/lang/en_us.json"))
+        .unwrap();
+    fs.write_file(
+        Path::new("out/assets/minecraft/lang/en_us.json/notes.txt"),
+        &mut "keep me".as_bytes(),
+    )
+    .unwrap();
+
+    let report = extract_jar(&options);
+
+    assert_eq!(report.summary.failures, 1);
+    let file = report.failed().next().unwrap();
+    assert_eq!(file.path, Path::new("assets/minecraft/lang/en_us.json"));
+    assert_eq!(
+        file.outcome,
+        FileOutcome::Failed {
+            error:
+                "expected a file at 'out/assets/minecraft/lang/en_us.json', but found a directory"
+                    .to_owned()
+        }
+    );
+    // The directory is left as it is, and everything else is extracted.
+    assert_eq!(
+        read(&fs, "out/assets/minecraft/lang/en_us.json/notes.txt"),
+        "keep me"
+    );
+    assert!(
+        fs.read(Path::new("out/assets/minecraft/shared.json"))
+            .is_some()
+    );
+}
+
+#[test]
+fn directories_in_the_way_of_files_are_replaced_if_asked() {
+    let (fs, options) = memory_options();
+    fs.create_dir_all(Path::new("out/assets/minecraft/lang/de_de.json"))
+        .unwrap();
+    fs.write_file(
+        Path::new("out/assets/minecraft/lang/de_de.json/notes.txt"),
+        &mut "".as_bytes(),
+    )
+    .unwrap();
+
+    let report = extract_hashed_assets(&options.replace_dirs(true));
+
+    // Only the hashed file which is missing from the fixture fails.
+    assert_eq!(report.summary.failures, 1);
+    assert_eq!(
+        read(&fs, "out/assets/minecraft/lang/de_de.json"),
+        "{\"menu.quit\": \"Spiel beenden\"}\n"
+    );
+    assert!(
+        fs.read(Path::new("out/assets/minecraft/lang/de_de.json/notes.txt"))
+            .is_none()
+    );
+}
+
+#[test]
+fn conflicts_are_skipped_when_skipping_existing_files() {
+    let (fs, options) = memory_options();
+    fs.create_dir_all(Path::new("out/assets/minecraft/lang/en_us.json"))
+        .unwrap();
+    fs.write_file(Path::new("out/assets/minecraft/texts"), &mut "".as_bytes())
+        .unwrap();
+
+    let report = extract_jar(&options.overwrite(Overwrite::Skip));
+
+    assert_eq!(report.summary.failures, 0);
+    assert_eq!(report.summary.skipped, 2);
+    assert_eq!(read(&fs, "out/assets/minecraft/texts"), "");
+    assert!(
+        fs.read(Path::new("out/assets/minecraft/shared.json"))
+            .is_some()
+    );
+}
+
+#[test]
+fn write_errors_name_the_directory_which_couldnt_be_created() {
+    let (fs, options) = memory_options();
+    // The output directory itself is never removed.
+    fs.write_file(Path::new("out"), &mut "".as_bytes()).unwrap();
+
+    let report = extract_jar(&options);
+
+    let file = report
+        .failed()
+        .find(|file| file.path == Path::new("assets/minecraft/lang/en_us.json"))
+        .unwrap();
+    let FileOutcome::Failed { error } = &file.outcome else {
+        unreachable!();
+    };
+
+    // The directory which couldn't be created is named, not the file.
+    assert!(
+        error.starts_with("failed to write 'out/assets/minecraft/lang': 'out' is a file"),
+        "{error}"
+    );
+}
+
+/// Returns a jar with a `poisoned` entry between two which can be extracted.
+fn poisoned_jar(dir: &TempDir, poisoned: fn(JarBuilder) -> JarBuilder) -> PathBuf {
+    let jar = JarBuilder::new()
+        .file("pack.mcmeta", "{}")
+        .file("assets/minecraft/lang/en_us.json", "{}")
+        .file("assets/minecraft/texts/credits.json", "corrupted credits");
+
+    poisoned(jar)
+        .file(
+            "assets/minecraft/texts/splashes.txt",
+            "Also try Terraria!\n",
+        )
+        .write(&dir.path().join("client.jar"))
+}
+
+/// Asserts that only the poisoned entry of a [`poisoned_jar`] failed.
+fn assert_only_poisoned_failed(fs: &MemoryFs, report: &ExtractionReport) {
+    assert_eq!(report.summary.failures, 1);
+    assert_eq!(report.summary.files, 2);
+    assert_eq!(
+        report.failed().next().unwrap().path,
+        Path::new("assets/minecraft/texts/credits.json")
+    );
+    assert!(fs.exists(Path::new("out/assets/minecraft/lang/en_us.json")));
+    assert!(fs.exists(Path::new("out/assets/minecraft/texts/splashes.txt")));
+}
+
+#[test]
+fn jar_continues_past_corrupted_entries() {
+    let dir = common::temp_dir();
+    let jar = poisoned_jar(&dir, JarBuilder::corrupted);
+    let (fs, options) = memory_options();
+
+    let report = jar::extract_jar(&jar, &options, &mut NoProgress).unwrap();
+
+    assert_only_poisoned_failed(&fs, &report);
+}
+
+#[test]
+fn jar_continues_past_unreadable_entries() {
+    let dir = common::temp_dir();
+    let jar = poisoned_jar(&dir, JarBuilder::unreadable);
+    let (fs, options) = memory_options();
+
+    let report = jar::extract_jar(&jar, &options, &mut NoProgress).unwrap();
+
+    assert_only_poisoned_failed(&fs, &report);
+    let FileOutcome::Failed { error } = &report.failed().next().unwrap().outcome else {
+        unreachable!();
+    };
+    assert!(
+        error.starts_with("failed to read 'assets/minecraft/texts/credits.json' from jar file"),
+        "{error}"
+    );
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn async_jar_continues_past_poisoned_entries() {
+    use extract_minecraft_assets::asynchronous;
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+
+    for poisoned in [JarBuilder::corrupted, JarBuilder::unreadable] {
+        let dir = common::temp_dir();
+        let jar = poisoned_jar(&dir, poisoned);
+        let (fs, options) = memory_options();
+
+        let report = runtime
+            .block_on(asynchronous::extract_jar_async(&jar, &options, None))
+            .unwrap();
+
+        assert_only_poisoned_failed(&fs, &report);
+    }
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn async_hashed_assets_send_progress_events() {
+    use extract_minecraft_assets::asynchronous::{self, ProgressEvent};
+    use tokio::sync::mpsc;
+
+    let Fixture { store, .. } = &fixture();
+    let (fs, options) = memory_options();
+    let (sender, mut receiver) = mpsc::unbounded_channel();
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    let report = runtime
+        .block_on(asynchronous::extract_hashed_assets_async(
+            &store.dir,
+            &store.index(),
+            &options.concurrency(1),
+            Some(sender),
+        ))
+        .unwrap();
+
+    let mut events = Vec::new();
+    while let Ok(event) = receiver.try_recv() {
+        events.push(event);
+    }
+
+    assert!(matches!(
+        events.first(),
+        Some(ProgressEvent::Begin { total_files: 3, .. })
+    ));
+    let Some(ProgressEvent::Finish(summary)) = events.last() else {
+        panic!("the last event isn't Finish: {events:?}");
+    };
+    assert_eq!((summary.files, summary.failures), (2, 1));
+    assert_eq!(summary.files, report.summary.files);
+    let mut done: Vec<_> = events
+        .iter()
+        .filter_map(|event| match event {
+            ProgressEvent::FileDone { path, .. } => Some(path.clone()),
+            _ => None,
+        })
+        .collect();
+    done.sort();
+    assert_eq!(
+        done,
+        paths(&[
+            "assets/minecraft/lang/de_de.json",
+            "assets/minecraft/sounds/ambient/cave/cave1.ogg",
+        ])
+    );
+    let missing = Path::new("assets/minecraft/sounds/missing.ogg");
+    assert!(events.iter().any(|event| matches!(
+        event,
+        ProgressEvent::FileFailed { path, .. } if path == missing
+    )));
+    assert_eq!(fs.files().len(), 2);
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn async_extraction_stops_when_cancelled() {
+    use extract_minecraft_assets::asynchronous;
+
+    let token = CancellationToken::new();
+    token.cancel();
+    let (fs, options) = memory_options();
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    let report = runtime
+        .block_on(asynchronous::extract_jar_async(
+            &fixture().jar,
+            &options.cancellation(token),
+            None,
+        ))
+        .unwrap();
+
+    assert!(report.summary.cancelled);
+    assert_eq!(report.summary.files, 0);
+    assert!(fs.files().is_empty());
+}
+
+/// Returns a jar with two entries named `assets/minecraft/texts/end.txt`,
+/// along with one whose name only differs in case.
+fn duplicated_jar(dir: &TempDir) -> PathBuf {
+    JarBuilder::new()
+        .file("pack.mcmeta", "{}")
+        .file("assets/minecraft/texts/end.txt", "first end")
+        .file("assets/minecraft/texts/End.txt", "cased end")
+        .file("assets/minecraft/texts/pom.txt", "last end")
+        .renamed("assets/minecraft/texts/end.txt")
+        .write(&dir.path().join("client.jar"))
+}
+
+#[test]
+fn duplicate_entries_are_found_by_exact_name() {
+    let dir = common::temp_dir();
+    let jar = duplicated_jar(&dir);
+
+    assert_eq!(
+        jar::duplicate_entries(&jar, &ExtractedContents::assets()).unwrap(),
+        [(PathBuf::from("assets/minecraft/texts/end.txt"), 2)]
+    );
+    assert!(
+        jar::duplicate_entries(&jar, &ExtractedContents::data())
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[test]
+fn duplicate_entries_are_extracted_by_policy_with_a_warning() {
+    let dir = common::temp_dir();
+    let jar = duplicated_jar(&dir);
+
+    for (duplicate_entries, contents) in [
+        (DuplicateEntries::Last, "last end"),
+        (DuplicateEntries::First, "first end"),
+    ] {
+        let (fs, options) = memory_options();
+        let report = jar::extract_jar(
+            &jar,
+            &options.duplicate_entries(duplicate_entries),
+            &mut NoProgress,
+        )
+        .unwrap();
+
+        assert_eq!(report.summary.files, 2);
+        assert_eq!(read(&fs, "out/assets/minecraft/texts/end.txt"), contents);
+        assert_eq!(read(&fs, "out/assets/minecraft/texts/End.txt"), "cased end");
+        assert_eq!(report.warnings.len(), 1);
+        assert_eq!(
+            report.warnings[0].path,
+            Path::new("assets/minecraft/texts/end.txt")
+        );
+        assert!(report.warnings[0].message.contains("has 2 entries"));
+    }
+}
+
+#[test]
+fn duplicate_entries_can_fail_extraction() {
+    let dir = common::temp_dir();
+    let jar = duplicated_jar(&dir);
+    let (fs, options) = memory_options();
+
+    let error = jar::extract_jar(
+        &jar,
+        &options.duplicate_entries(DuplicateEntries::Error),
+        &mut NoProgress,
+    )
+    .unwrap_err();
+
+    assert!(
+        matches!(&error, ExtractError::DuplicateEntries { entries, .. } if entries.len() == 1),
+        "{error}"
+    );
+    assert!(fs.files().is_empty());
+}
+
+#[test]
+fn unsafe_entry_names_are_skipped_with_a_warning() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client()
+        .file("assets/minecraft/evil.txt", "evil")
+        .renamed("../../../../../../../evil")
+        .write(&dir.path().join("client.jar"));
+    let (fs, options) = memory_options();
+
+    let report = jar::extract_jar(&jar, &options, &mut NoProgress).unwrap();
+
+    assert!(!fs.exists(Path::new("evil.txt")));
+    assert_eq!(report.summary.failures, 0);
+    assert_eq!(report.warnings.len(), 1);
+    assert_eq!(
+        report.warnings[0].path,
+        Path::new("../../../../../../../evil")
+    );
+    assert!(
+        report.warnings[0]
+            .message
+            .contains("outside the output directory")
+    );
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn async_jar_extracts_first_duplicate_entries() {
+    use extract_minecraft_assets::asynchronous;
+
+    let dir = common::temp_dir();
+    let jar = duplicated_jar(&dir);
+    let (fs, options) = memory_options();
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+
+    let report = runtime
+        .block_on(asynchronous::extract_jar_async(
+            &jar,
+            &options.duplicate_entries(DuplicateEntries::First),
+            None,
+        ))
+        .unwrap();
+
+    assert_eq!(report.warnings.len(), 1);
+    assert_eq!(read(&fs, "out/assets/minecraft/texts/end.txt"), "first end");
+}
+
+#[test]
+fn diff_jars_only_reads_contents_when_asked() {
+    let dir = common::temp_dir();
+    let jar = || {
+        JarBuilder::new()
+            .file("pack.mcmeta", "{}")
+            .file("assets/minecraft/texts/credits.json", "corrupted credits")
+    };
+    let a = jar().write(&dir.path().join("a.jar"));
+    let b = jar().corrupted().write(&dir.path().join("b.jar"));
+    let contents = ExtractedContents::assets();
+
+    // The corrupted entry has the same checksum and size in the central
+    // directory, so only decompressing it can tell them apart.
+    assert!(jar::diff_jars(&a, &b, &contents, false).unwrap().is_empty());
+    assert_eq!(
+        jar::diff_jars(&a, &b, &contents, true).unwrap().changed[0].path,
+        Path::new("assets/minecraft/texts/credits.json")
+    );
+    assert!(jar::diff_jars(&a, &a, &contents, true).unwrap().is_empty());
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn async_jar_writes_through_vfs() {
+    use extract_minecraft_assets::asynchronous;
+
+    let (fs, options) = memory_options();
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    let report = runtime
+        .block_on(asynchronous::extract_jar_async(
+            &fixture().jar,
+            &options.contents(ExtractedContents::all()),
+            None,
+        ))
+        .unwrap();
+
+    assert_eq!(report.summary.files, 5);
+    assert_eq!(fs.files().len(), 5);
+    assert!(
+        fs.files()
+            .iter()
+            .all(|path| path.extension() != Some("part".as_ref()))
+    );
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn async_jar_handles_conflicts() {
+    use extract_minecraft_assets::asynchronous;
+
+    let (fs, options) = memory_options();
+    fs.create_dir_all(Path::new("out/assets/minecraft/lang/en_us.json"))
+        .unwrap();
+    fs.write_file(Path::new("out/assets/minecraft/texts"), &mut "".as_bytes())
+        .unwrap();
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    let report = runtime
+        .block_on(asynchronous::extract_jar_async(
+            &fixture().jar,
+            &options,
+            None,
+        ))
+        .unwrap();
+
+    // The directory fails, while the file is replaced.
+    assert_eq!(report.summary.failures, 1);
+    assert_eq!(
+        report.failed().next().unwrap().path,
+        Path::new("assets/minecraft/lang/en_us.json")
+    );
+    assert_eq!(
+        read(&fs, "out/assets/minecraft/texts/splashes.txt"),
+        "Also try Terraria!\n"
+    );
+}
@@ -0,0 +1,98 @@
+mod common;
+
+use common::{AssetStoreBuilder, JarBuilder};
+use extract_minecraft_assets::closure::{AssetSources, DanglingReference};
+use extract_minecraft_assets::fonts;
+use extract_minecraft_assets::search::FileSource;
+use std::path::{Path, PathBuf};
+
+const DEFAULT_FONT: &str = r#"{"providers": [
+    {"type": "reference", "id": "minecraft:include/space"},
+    {"type": "bitmap", "file": "minecraft:font/ascii.png", "ascent": 7, "chars": []},
+    {"type": "bitmap", "file": "minecraft:font/nonlatin_european.png", "chars": []},
+    {"type": "legacy_unicode", "sizes": "minecraft:font/glyph_sizes.bin", "template": "minecraft:font/unicode_page_%s.png"},
+    {"type": "unihex", "hex_file": "minecraft:font/unifont.zip", "size_overrides": []}
+]}"#;
+
+#[test]
+fn fonts_closure_follows_references_across_sources() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client()
+        .file("assets/minecraft/font/default.json", DEFAULT_FONT)
+        .file("assets/minecraft/textures/font/ascii.png", "PNG ascii")
+        .file(
+            "assets/minecraft/textures/font/unicode_page_00.png",
+            "PNG 00",
+        )
+        .file(
+            "assets/minecraft/textures/font/unicode_page_4e.png",
+            "PNG 4e",
+        )
+        .file("assets/minecraft/textures/block/stone.png", "PNG stone")
+        .write(&dir.path().join("client.jar"));
+    let store = AssetStoreBuilder::typical()
+        .file(
+            "minecraft/font/include/space.json",
+            r#"{"providers": [{"type": "space", "advances": {" ": 4}}]}"#,
+        )
+        .file("minecraft/font/glyph_sizes.bin", [0x0f, 0xf0])
+        .file("minecraft/font/unifont.zip", "PK unifont")
+        .write(&dir.path().join("assets"));
+
+    let mut sources = AssetSources::new()
+        .jar(&jar)
+        .unwrap()
+        .index(&store.dir, store.index());
+    let closure = fonts::fonts_closure(&mut sources).unwrap();
+
+    let files: Vec<_> = closure
+        .files
+        .iter()
+        .map(|file| (file.path.to_str().unwrap(), file.source))
+        .collect();
+    assert_eq!(
+        files,
+        [
+            ("minecraft/font/default.json", FileSource::Jar),
+            ("minecraft/font/glyph_sizes.bin", FileSource::Hashed),
+            ("minecraft/font/include/space.json", FileSource::Hashed),
+            ("minecraft/font/unifont.zip", FileSource::Hashed),
+            ("minecraft/textures/font/ascii.png", FileSource::Jar),
+            (
+                "minecraft/textures/font/unicode_page_00.png",
+                FileSource::Jar
+            ),
+            (
+                "minecraft/textures/font/unicode_page_4e.png",
+                FileSource::Jar
+            ),
+        ]
+    );
+    // Missing pages of a template aren't dangling, unlike missing textures.
+    assert_eq!(
+        closure.dangling,
+        [DanglingReference {
+            from: PathBuf::from("minecraft/font/default.json"),
+            path: PathBuf::from("minecraft/textures/font/nonlatin_european.png"),
+        }]
+    );
+    assert!(closure.errors.is_empty());
+}
+
+#[test]
+fn invalid_font_definitions_are_recorded() {
+    let dir = common::temp_dir();
+    let store = AssetStoreBuilder::new()
+        .file("minecraft/font/broken.json", "{\"providers\": [")
+        .write(&dir.path().join("assets"));
+
+    let mut sources = AssetSources::new().index(&store.dir, store.index());
+    let closure = fonts::fonts_closure(&mut sources).unwrap();
+
+    assert_eq!(closure.files.len(), 1);
+    assert_eq!(closure.errors.len(), 1);
+    assert_eq!(
+        closure.errors[0].path,
+        Path::new("minecraft/font/broken.json")
+    );
+}
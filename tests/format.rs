@@ -0,0 +1,123 @@
+mod common;
+
+use common::{AssetStoreBuilder, JarBuilder};
+use extract_minecraft_assets::format::{self, JsonFormat};
+use extract_minecraft_assets::options::ExtractOptions;
+use extract_minecraft_assets::progress::NoProgress;
+use extract_minecraft_assets::vfs::MemoryFs;
+use extract_minecraft_assets::{hashed, jar};
+use serde_json::Value;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Minified JSON with strings which look like JSON, escapes and nesting.
+const MINIFIED: &str = r#"{"z":{"b":[1,2.50,-3e2,{}],"a":[]},"text":"{[,:]} \"quoted\" \\","escaped":"\u00e9\n","nested":[[true,false,null]]}"#;
+
+#[test]
+fn formatting_then_minifying_round_trips() {
+    let pretty = format::format_json(MINIFIED.as_bytes(), JsonFormat::Pretty).unwrap();
+    let minified = format::format_json(&pretty, JsonFormat::Minify).unwrap();
+
+    assert_eq!(String::from_utf8(minified).unwrap(), MINIFIED);
+    assert_eq!(
+        serde_json::from_slice::<Value>(&pretty).unwrap(),
+        serde_json::from_str::<Value>(MINIFIED).unwrap()
+    );
+
+    // Keys stay in the order they appear.
+    let pretty = String::from_utf8(pretty).unwrap();
+    assert!(pretty.starts_with("{\n  \"z\": {\n    \"b\": [\n      1,\n      2.50,"));
+    assert!(pretty.find("\"text\"") < pretty.find("\"escaped\""));
+}
+
+#[test]
+fn invalid_json_is_not_formatted() {
+    assert!(format::format_json(b"{\"parent\": ", JsonFormat::Pretty).is_err());
+    assert!(format::format_json(b"{} {}", JsonFormat::Minify).is_err());
+}
+
+#[test]
+fn json_files_are_formatted_while_extracting() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client()
+        .file(
+            "assets/minecraft/models/block/stone.json",
+            r#"{"parent":"minecraft:block/cube_all","textures":{"all":"minecraft:block/stone"}}"#,
+        )
+        .file("assets/minecraft/models/block/broken.json", "{\"parent\":")
+        .file(
+            "assets/minecraft/textures/block/stone.png",
+            "{\"not\":\"json\"}",
+        )
+        .write(&dir.path().join("client.jar"));
+    let store = AssetStoreBuilder::new()
+        .file("minecraft/sounds.json", r#"{"ambient.cave":{"sounds":[]}}"#)
+        .write(&dir.path().join("assets"));
+
+    let fs = Arc::new(MemoryFs::new());
+    let options = ExtractOptions::new()
+        .output("out")
+        .vfs(fs.clone())
+        .format_json(Some(JsonFormat::Pretty));
+    let report = jar::extract_jar(&jar, &options, &mut NoProgress).unwrap()
+        + hashed::extract_hashed_assets(&store.dir, &store.index(), &options, &mut NoProgress)
+            .unwrap();
+
+    let read = |path: &str| String::from_utf8(fs.read(Path::new(path)).unwrap()).unwrap();
+    assert_eq!(
+        read("out/assets/minecraft/models/block/stone.json"),
+        "{\n  \"parent\": \"minecraft:block/cube_all\",\n  \"textures\": {\n    \"all\": \"minecraft:block/stone\"\n  }\n}"
+    );
+    assert_eq!(
+        read("out/assets/minecraft/sounds.json"),
+        "{\n  \"ambient.cave\": {\n    \"sounds\": []\n  }\n}"
+    );
+    // Only JSON files are formatted, and invalid ones are extracted as they are.
+    assert_eq!(
+        read("out/assets/minecraft/textures/block/stone.png"),
+        "{\"not\":\"json\"}"
+    );
+    assert_eq!(
+        read("out/assets/minecraft/models/block/broken.json"),
+        "{\"parent\":"
+    );
+
+    assert_eq!(report.warnings.len(), 1);
+    assert_eq!(
+        report.warnings[0].path,
+        Path::new("assets/minecraft/models/block/broken.json")
+    );
+    assert_eq!(report.summary.failures, 0);
+}
+
+#[test]
+fn json_files_are_minified_up_to_the_maximum_size() {
+    let dir = common::temp_dir();
+    let pretty = "{\n  \"animation\": {\n    \"frametime\": 2\n  }\n}\n";
+    let large = format!("[{}1]", "1, ".repeat(100));
+    let jar = JarBuilder::client()
+        .file("assets/minecraft/textures/block/fire_0.png.mcmeta", pretty)
+        .file("assets/minecraft/large.json", large.as_str())
+        .file("assets/minecraft/texts/credits.txt", " { } ")
+        .write(&dir.path().join("client.jar"));
+
+    let fs = Arc::new(MemoryFs::new());
+    let options = ExtractOptions::new()
+        .output("out")
+        .vfs(fs.clone())
+        .format_json(Some(JsonFormat::Minify))
+        .format_json_max_size(100);
+    let report = jar::extract_jar(&jar, &options, &mut NoProgress).unwrap();
+
+    let read = |path: &str| String::from_utf8(fs.read(Path::new(path)).unwrap()).unwrap();
+    assert_eq!(
+        read("out/assets/minecraft/textures/block/fire_0.png.mcmeta"),
+        "{\"animation\":{\"frametime\":2}}"
+    );
+    assert_eq!(read("out/assets/minecraft/large.json"), large);
+    assert_eq!(read("out/assets/minecraft/texts/credits.txt"), " { } ");
+
+    // The jar's other JSON files are minified too, saving 2 bytes each.
+    assert_eq!(report.summary.bytes_saved, 15 + 2 * 2);
+    assert!(report.warnings.is_empty());
+}
@@ -0,0 +1,107 @@
+mod common;
+
+use common::{AssetStoreBuilder, JarBuilder};
+use extract_minecraft_assets::grep::{Grep, GrepStats, LineHit, TextPattern};
+use extract_minecraft_assets::jar::ExtractedContents;
+use extract_minecraft_assets::search::FileSource;
+use std::path::PathBuf;
+
+fn substring(substring: &str) -> Grep {
+    Grep::new(TextPattern::Substring(substring.to_owned()))
+}
+
+#[test]
+fn lines_of_text_entries_are_matched() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client()
+        .file(
+            "data/minecraft/loot_table/chest.json",
+            "{\n  \"item\": \"minecraft:netherite_ingot\"\r\n}\n",
+        )
+        // Binary despite its extension, so it is sniffed out.
+        .file("data/minecraft/broken.json", b"netherite\0\x01\x02")
+        // Not a text extension, so it is never read.
+        .file("data/minecraft/structure/bastion.nbt", "netherite")
+        .write(&dir.path().join("client.jar"));
+
+    let mut hits = Vec::new();
+    let stats = substring("netherite")
+        .search_jar(&jar, &ExtractedContents::data(), &mut |hit| hits.push(hit))
+        .unwrap();
+
+    assert_eq!(
+        hits,
+        [LineHit {
+            source: FileSource::Jar,
+            path: PathBuf::from("data/minecraft/loot_table/chest.json"),
+            line: 2,
+            text: "  \"item\": \"minecraft:netherite_ingot\"".to_owned(),
+        }]
+    );
+    // `tags/blocks/logs.json`, `shared.json` and `chest.json`.
+    assert_eq!(
+        stats,
+        GrepStats {
+            searched: 3,
+            binary: 1,
+            missing: 0,
+        }
+    );
+}
+
+#[test]
+fn only_the_first_hit_of_each_file_is_found_if_asked() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client()
+        .file("assets/minecraft/texts/end.txt", "the end\nthe end\n")
+        .write(&dir.path().join("client.jar"));
+
+    let mut hits = Vec::new();
+    substring("end")
+        .first_per_file(true)
+        .search_jar(&jar, &ExtractedContents::assets(), &mut |hit| {
+            hits.push(hit)
+        })
+        .unwrap();
+
+    assert_eq!(hits.len(), 1);
+    assert_eq!(
+        hits[0].path,
+        PathBuf::from("assets/minecraft/texts/end.txt")
+    );
+}
+
+#[test]
+fn hashed_text_files_are_matched_by_regex() {
+    let dir = common::temp_dir();
+    let store = AssetStoreBuilder::typical()
+        .file(
+            "minecraft/lang/fr_fr.json",
+            "{\"menu.quit\": \"Quitter\"}\n",
+        )
+        .missing("minecraft/lang/es_es.json", "{\"menu.quit\": \"Salir\"}\n")
+        .write(&dir.path().join("assets"));
+
+    let mut hits = Vec::new();
+    let stats = Grep::new(TextPattern::regex(r#""menu\.quit": "[A-Z]"#).unwrap())
+        .search_index(&store.dir, &store.index(), &mut |hit| hits.push(hit))
+        .unwrap();
+
+    let paths: Vec<_> = hits.iter().map(|hit| &hit.path).collect();
+    assert_eq!(
+        paths,
+        [
+            &PathBuf::from("assets/minecraft/lang/de_de.json"),
+            &PathBuf::from("assets/minecraft/lang/fr_fr.json"),
+        ]
+    );
+    assert!(hits.iter().all(|hit| hit.source == FileSource::Hashed));
+    assert_eq!(stats.missing, 1);
+}
+
+#[test]
+fn an_invalid_regex_is_refused() {
+    let error = TextPattern::regex("(unclosed").unwrap_err();
+
+    assert_eq!(error.pattern, "(unclosed");
+}
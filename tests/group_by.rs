@@ -0,0 +1,79 @@
+mod common;
+
+use common::{AssetStoreBuilder, JarBuilder};
+use extract_minecraft_assets::catalog::Catalog;
+use extract_minecraft_assets::category::Category;
+use extract_minecraft_assets::jar::ExtractedContents;
+use extract_minecraft_assets::options::{ExtractOptions, GroupBy};
+use extract_minecraft_assets::progress::NoProgress;
+use extract_minecraft_assets::vfs::{MemoryFs, Vfs};
+use extract_minecraft_assets::{hashed, jar};
+use std::path::Path;
+use std::sync::Arc;
+
+#[test]
+fn files_are_grouped_by_extension() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client().write(&dir.path().join("client.jar"));
+    let store = AssetStoreBuilder::typical().write(&dir.path().join("assets"));
+
+    let fs = Arc::new(MemoryFs::new());
+    let options = ExtractOptions::new()
+        .output("out")
+        .vfs(fs.clone())
+        .group_by(Some(GroupBy::Extension));
+    let report = jar::extract_jar(&jar, &options, &mut NoProgress).unwrap()
+        + hashed::extract_hashed_assets(&store.dir, &store.index(), &options, &mut NoProgress)
+            .unwrap();
+
+    assert_eq!(
+        fs.read(Path::new("out/json/assets/minecraft/lang/en_us.json"))
+            .unwrap(),
+        b"{\"menu.quit\": \"Quit Game\"}\n"
+    );
+    assert!(
+        fs.read(Path::new("out/txt/assets/minecraft/texts/splashes.txt"))
+            .is_some()
+    );
+    // The jar's directory entries aren't extracted outside of the groups.
+    assert!(!fs.exists(Path::new("out/assets")));
+    assert!(report.files.iter().all(|file| {
+        file.path.starts_with("json")
+            || file.path.starts_with("txt")
+            || file.path.starts_with("ogg")
+    }));
+}
+
+#[test]
+fn files_are_grouped_by_category() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client().write(&dir.path().join("client.jar"));
+
+    let fs = Arc::new(MemoryFs::new());
+    let options = ExtractOptions::new()
+        .output("out")
+        .vfs(fs.clone())
+        .contents(ExtractedContents::all())
+        .ignore_top_level(true)
+        .group_by(Some(GroupBy::Category));
+    let report = jar::extract_jar(&jar, &options, &mut NoProgress).unwrap();
+
+    assert_eq!(report.summary.failures, 0);
+    assert!(
+        fs.read(Path::new("out/lang/minecraft/lang/en_us.json"))
+            .is_some()
+    );
+    assert!(
+        fs.read(Path::new("out/tags/minecraft/tags/blocks/logs.json"))
+            .is_some()
+    );
+    assert!(
+        fs.read(Path::new("out/other/minecraft/shared.json"))
+            .is_some()
+    );
+
+    // The catalog still sees the namespaces and categories of the files.
+    let catalog = Catalog::from_report(&report);
+    assert!(catalog.namespaces["minecraft"].contains_key(&Category::Lang));
+    assert!(catalog.namespaces["minecraft"].contains_key(&Category::Tags));
+}
@@ -0,0 +1,110 @@
+mod common;
+
+use common::{AssetStoreBuilder, JarBuilder};
+use extract_minecraft_assets::filter::{Pattern, PatternSet};
+use extract_minecraft_assets::options::ExtractOptions;
+use extract_minecraft_assets::progress::ProgressSink;
+use extract_minecraft_assets::usage::UsageEstimate;
+use extract_minecraft_assets::vfs::MemoryFs;
+use extract_minecraft_assets::{hashed, jar};
+use std::path::Path;
+use std::sync::Arc;
+
+/// Records the totals an extraction begins with.
+#[derive(Default)]
+struct Totals(Option<(usize, u64)>);
+
+impl ProgressSink for Totals {
+    fn begin(&mut self, total_files: usize, total_bytes: u64) {
+        self.0 = Some((total_files, total_bytes));
+    }
+}
+
+fn globs(globs: &[&str]) -> PatternSet {
+    globs
+        .iter()
+        .map(|glob| Pattern::glob(glob).unwrap())
+        .collect()
+}
+
+#[test]
+fn hashed_assets_are_filtered_before_being_read() {
+    let dir = common::temp_dir();
+    let store = AssetStoreBuilder::typical().write(&dir.path().join("assets"));
+
+    let fs = Arc::new(MemoryFs::new());
+    let options = ExtractOptions::new()
+        .output("out")
+        .vfs(fs.clone())
+        .include(globs(&["assets/minecraft/sounds/**"]))
+        .exclude(globs(&["missing.ogg"]));
+    let mut totals = Totals::default();
+    let report =
+        hashed::extract_hashed_assets(&store.dir, &store.index(), &options, &mut totals).unwrap();
+
+    // The missing object is never read, so it doesn't fail.
+    assert_eq!(report.summary.failures, 0);
+    assert_eq!(
+        fs.files(),
+        [Path::new(
+            "out/assets/minecraft/sounds/ambient/cave/cave1.ogg"
+        )]
+    );
+    assert_eq!(totals.0, Some((1, "OggS fake cave sound\n".len() as u64)));
+}
+
+#[test]
+fn exclusions_win_over_inclusions_in_jar_files() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client().write(&dir.path().join("client.jar"));
+
+    let fs = Arc::new(MemoryFs::new());
+    let options = ExtractOptions::new()
+        .output("out")
+        .vfs(fs.clone())
+        .include(globs(&["*.json"]))
+        .exclude(globs(&["assets/minecraft/shared.json"]));
+    let mut totals = Totals::default();
+    jar::extract_jar(&jar, &options, &mut totals).unwrap();
+
+    assert_eq!(
+        fs.files(),
+        [Path::new("out/assets/minecraft/lang/en_us.json")]
+    );
+    assert_eq!(totals.0.map(|(files, _)| files), Some(1));
+}
+
+#[test]
+fn everything_is_included_without_inclusions() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client().write(&dir.path().join("client.jar"));
+
+    let fs = Arc::new(MemoryFs::new());
+    let options = ExtractOptions::new()
+        .output("out")
+        .vfs(fs.clone())
+        .exclude(globs(&["assets/minecraft/texts/**"]));
+    jar::extract_jar(&jar, &options, &mut Totals::default()).unwrap();
+
+    assert_eq!(
+        fs.files(),
+        [
+            Path::new("out/assets/minecraft/lang/en_us.json"),
+            Path::new("out/assets/minecraft/shared.json"),
+        ]
+    );
+}
+
+#[test]
+fn estimates_only_count_included_files() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client().write(&dir.path().join("client.jar"));
+    let store = AssetStoreBuilder::typical().write(&dir.path().join("assets"));
+
+    let options = ExtractOptions::new().include(globs(&["*.json"]));
+    let mut estimate = UsageEstimate::new();
+    estimate.add_jar(&jar, &options, |_| true).unwrap();
+    estimate.add_index(&store.index(), &options);
+
+    assert_eq!(estimate.usage(None).total.files, 3);
+}
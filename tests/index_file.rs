@@ -0,0 +1,234 @@
+mod common;
+
+use common::AssetStoreBuilder;
+use extract_minecraft_assets::error::ExtractError;
+use extract_minecraft_assets::hashed::{IndexError, IndexFile, IndexFileLocation, Object};
+use std::fs;
+use std::path::Path;
+
+/// Reads the index file fixture `name`, returning its contents and the parsed index.
+fn fixture(name: &str) -> (Vec<u8>, IndexFile) {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name);
+    let contents = fs::read(&path).unwrap();
+    let index = IndexFile::from_path(&path).unwrap();
+
+    (contents, index)
+}
+
+fn serialize(index: &IndexFile) -> Vec<u8> {
+    let mut json = Vec::new();
+    index.to_writer(&mut json).unwrap();
+
+    json
+}
+
+#[test]
+fn round_trip_is_byte_compatible() {
+    let (contents, index) = fixture("index.json");
+    let json = serialize(&index);
+
+    assert_eq!(String::from_utf8(json.clone()), String::from_utf8(contents));
+    assert_eq!(IndexFile::from_reader(json.as_slice()).unwrap(), index);
+}
+
+#[test]
+fn round_trip_keeps_legacy_flags() {
+    let (contents, index) = fixture("legacy.json");
+
+    assert!(index.is_virtual);
+    assert!(!index.map_to_resources);
+    assert_eq!(serialize(&index), contents);
+}
+
+#[test]
+fn insert_and_remove() {
+    let (_, mut index) = fixture("index.json");
+    let len = index.len();
+    let object = Object {
+        hash: "bdf48ef6b5d0d23bbb02e17d04865216179f510a".to_owned(),
+        size: 42,
+    };
+
+    assert_eq!(index.insert("minecraft/custom.txt", object.clone()), None);
+    assert_eq!(index.len(), len + 1);
+    assert_eq!(index.get("minecraft/custom.txt").unwrap().size, 42);
+
+    let reparsed = IndexFile::from_reader(serialize(&index).as_slice()).unwrap();
+    assert_eq!(reparsed, index);
+
+    assert_eq!(index.remove("minecraft/custom.txt"), Some(object));
+    assert_eq!(index.remove("minecraft/custom.txt"), None);
+    assert_eq!(index.len(), len);
+}
+
+#[test]
+fn diff_finds_added_removed_and_changed() {
+    let (_, a) = fixture("index.json");
+    let mut b = a.clone();
+    let (removed, _) = a.objects.iter().next().unwrap();
+    let removed = removed.clone();
+    let changed = a
+        .objects
+        .keys()
+        .find(|path| **path != removed)
+        .unwrap()
+        .clone();
+
+    b.remove(&removed);
+    b.insert(
+        "minecraft/custom.txt",
+        Object {
+            hash: "bdf48ef6b5d0d23bbb02e17d04865216179f510a".to_owned(),
+            size: 42,
+        },
+    );
+    let old_size = a.objects[&changed].size;
+    b.insert(
+        &changed,
+        Object {
+            hash: "0000000000000000000000000000000000000000".to_owned(),
+            size: old_size + 8,
+        },
+    );
+
+    let diff = a.diff(&b);
+    assert_eq!(diff.only_in_a.len(), 1);
+    assert_eq!(diff.only_in_a[0].path, removed);
+    assert_eq!(diff.only_in_b[0].path, Path::new("minecraft/custom.txt"));
+    assert_eq!(diff.changed[0].path, changed);
+
+    let totals = diff.totals();
+    assert_eq!(totals.only_in_b.bytes, 42);
+    assert_eq!(totals.changed.bytes, 8);
+
+    assert!(a.diff(&a).is_empty());
+    assert_eq!(b.diff(&a).only_in_a, diff.only_in_b);
+}
+
+#[test]
+fn malformed_hashes_are_rejected() {
+    for hash in [
+        "",
+        "d",
+        "é133456a6e059ba613ba047e26e985d3c45b79e",
+        "DC8C00E137C7AF51A1D06BB04717EACFFAB44120",
+        "dc8c00e137c7af51a1d06bb04717eacffab4412",
+        "dc8c00e137c7af51a1d06bb04717eacffab44120a",
+        "dc8c00e137c7af51a1d06bb04717eacffab4412g",
+    ] {
+        let json = format!(r#"{{"objects": {{"pack.mcmeta": {{"hash": "{hash}", "size": 1}}}}}}"#);
+
+        let Err(IndexError::InvalidHash { path, hash: found }) =
+            IndexFile::from_reader(json.as_bytes())
+        else {
+            panic!("{hash:?} is accepted");
+        };
+        assert_eq!(path, Path::new("pack.mcmeta"));
+        assert_eq!(found, hash);
+    }
+}
+
+#[test]
+fn malformed_hash_error_names_first_path() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/invalid_hash.json");
+    let error = IndexFile::from_path(&path).unwrap_err();
+
+    assert_eq!(
+        error.to_string(),
+        format!(
+            "failed to load index file '{}': invalid hash \"é133456a6e059ba613ba047e26e985d3c45b79e\" \
+             for 'minecraft/lang/de_de.json': expected 40 lowercase hexadecimal characters",
+            path.display()
+        )
+    );
+}
+
+#[test]
+fn location_parses_existing_files_and_bare_names() {
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let file = fixtures.join("index.json");
+
+    assert!(matches!(
+        IndexFileLocation::parse(file.to_str().unwrap()),
+        Ok(IndexFileLocation::File(path)) if path == file
+    ));
+    assert!(matches!(
+        IndexFileLocation::parse("24"),
+        Ok(IndexFileLocation::Version(version)) if version == "24"
+    ));
+}
+
+#[test]
+fn location_rejects_missing_paths() {
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+
+    let Err(ExtractError::MissingIndex {
+        path,
+        suggestions,
+        available,
+    }) = IndexFileLocation::parse(fixtures.join("index.jsn").to_str().unwrap())
+    else {
+        panic!("a mistyped path is parsed");
+    };
+    assert_eq!(path, fixtures.join("index.jsn"));
+    assert_eq!(suggestions, ["index.json"]);
+    assert!(available.iter().any(|name| name == "index.json"));
+
+    // Relative paths are given as absolute paths.
+    for input in ["indexes/24", "24.json"] {
+        let Err(ExtractError::MissingIndex { path, .. }) = IndexFileLocation::parse(input) else {
+            panic!("'{input}' is parsed");
+        };
+        assert!(path.is_absolute(), "{}", path.display());
+        assert!(path.ends_with(input));
+    }
+}
+
+#[test]
+fn entries_point_at_the_hashed_files() {
+    let dir = common::temp_dir();
+    let store = AssetStoreBuilder::typical().write(&dir.path().join("assets"));
+    let index = IndexFile::from_path(&store.index_file).unwrap();
+
+    let mut entries: Vec<_> = index.entries().collect();
+    entries.sort_by_key(|entry| entry.virtual_path);
+    assert_eq!(
+        entries
+            .iter()
+            .map(|entry| (entry.virtual_path.to_str().unwrap(), entry.size))
+            .collect::<Vec<_>>(),
+        [
+            ("minecraft/lang/de_de.json", 31),
+            ("minecraft/sounds/ambient/cave/cave1.ogg", 21),
+            ("minecraft/sounds/missing.ogg", 7),
+        ]
+    );
+    assert_eq!(index.len(), 3);
+    assert_eq!(index.total_size(), 31 + 21 + 7);
+
+    let objects = store.dir.join("objects");
+    let cave = index
+        .get("minecraft/sounds/ambient/cave/cave1.ogg")
+        .unwrap();
+    assert_eq!(cave.hash, common::object(b"OggS fake cave sound\n").hash);
+    assert_eq!(cave.object_path, Path::new(&cave.hash[..2]).join(cave.hash));
+    assert_eq!(
+        fs::read(objects.join(&cave.object_path)).unwrap(),
+        b"OggS fake cave sound\n"
+    );
+    let missing = index.get("minecraft/sounds/missing.ogg").unwrap();
+    assert!(!objects.join(missing.object_path).exists());
+    assert_eq!(index.get("minecraft/sounds/other.ogg"), None);
+}
+
+#[test]
+fn invalid_json_is_located() {
+    let error = IndexFile::from_reader("{\n  \"objects\": {,}\n}".as_bytes()).unwrap_err();
+
+    assert!(
+        matches!(error, IndexError::Parse { line: 2, .. }),
+        "{error:?}"
+    );
+}
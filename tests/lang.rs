@@ -0,0 +1,270 @@
+mod common;
+
+use common::{AssetStoreBuilder, JarBuilder};
+use extract_minecraft_assets::lang::{LangFormat, LangMerger, LocaleList};
+use extract_minecraft_assets::options::ExtractOptions;
+use extract_minecraft_assets::progress::NoProgress;
+use extract_minecraft_assets::search::FileSource;
+use extract_minecraft_assets::vfs::{MemoryFs, Vfs};
+use extract_minecraft_assets::{hashed, jar};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+#[test]
+fn lang_files_are_merged_from_jar_and_index() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client()
+        .file(
+            "assets/realms/lang/en_us.json",
+            "{\"menu.quit\": \"Leave Realm\", \"realms.title\": \"Realms\"}\n",
+        )
+        .write(&dir.path().join("client.jar"));
+    let store = AssetStoreBuilder::typical()
+        .file(
+            "minecraft/lang/en_us.json",
+            "{\"menu.quit\": \"Quit Game\", \"menu.game\": \"Game Menu\"}\n",
+        )
+        .file("realms/lang/de_de.json", "{\"realms.title\": \"Realms\"}\n")
+        .write(&dir.path().join("assets"));
+
+    let mut merger = LangMerger::new();
+    merger.add_jar(&jar).unwrap();
+    merger.add_index(&store.dir, &store.index());
+    let merged = merger.merge();
+
+    let locales: Vec<_> = merged.iter().map(|merged| merged.locale.as_str()).collect();
+    assert_eq!(locales, ["de_de", "en_us"]);
+    assert!(merger.errors().is_empty());
+
+    let de_de = &merged[0];
+    assert_eq!(de_de.entries["menu.quit"], "Spiel beenden");
+    assert_eq!(de_de.entries["realms.title"], "Realms");
+    assert!(de_de.conflicts.is_empty());
+
+    // The hashed `minecraft` file replaces the jar's, and `realms` is layered on top.
+    let en_us = &merged[1];
+    assert_eq!(en_us.entries["menu.game"], "Game Menu");
+    assert_eq!(en_us.entries["menu.quit"], "Leave Realm");
+    assert_eq!(en_us.conflicts.len(), 1);
+    let values: Vec<_> = en_us.conflicts[0]
+        .values
+        .iter()
+        .map(|value| (value.namespace.as_str(), value.value.as_str()))
+        .collect();
+    assert_eq!(
+        values,
+        [("minecraft", "Quit Game"), ("realms", "Leave Realm")]
+    );
+}
+
+#[test]
+fn malformed_lang_files_are_reported_and_locales_filtered() {
+    let mut merger = LangMerger::new().locales(["de_de"]);
+    merger.add(
+        Path::new("minecraft/lang/de_de.json"),
+        FileSource::Hashed,
+        b"{\"menu.quit\": \"Spiel beenden\"}",
+    );
+    merger.add(
+        Path::new("realms/lang/de_de.json"),
+        FileSource::Jar,
+        b"{\"menu.quit\": ",
+    );
+    merger.add(
+        Path::new("minecraft/lang/fr_fr.json"),
+        FileSource::Hashed,
+        b"{\"menu.quit\": \"Quitter\"}",
+    );
+    merger.add(
+        Path::new("minecraft/texts/de_de.json"),
+        FileSource::Hashed,
+        b"{}",
+    );
+    let merged = merger.merge();
+
+    assert_eq!(merged.len(), 1);
+    assert_eq!(merged[0].entries.len(), 1);
+    assert_eq!(merger.errors().len(), 1);
+    assert_eq!(merger.errors()[0].path, Path::new("realms/lang/de_de.json"));
+    assert_eq!(merger.errors()[0].source, FileSource::Jar);
+}
+
+#[test]
+fn legacy_lang_files_are_converted_while_extracting() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client()
+        .file(
+            "assets/minecraft/lang/en_us.lang",
+            "# Menus\nmenu.quit=Quit Game\ntip=One\\nTwo\n",
+        )
+        .write(&dir.path().join("client.jar"));
+    let store = AssetStoreBuilder::new()
+        .file("minecraft/lang/de_de.lang", "menu.quit=Spiel beenden\n")
+        .file("minecraft/lang/fr_fr.lang", "menu.quit\n")
+        .write(&dir.path().join("assets"));
+
+    let fs = Arc::new(MemoryFs::new());
+    let options = ExtractOptions::new()
+        .output("out")
+        .vfs(fs.clone())
+        .convert_lang(Some(LangFormat::Json));
+    let report = jar::extract_jar(&jar, &options, &mut NoProgress).unwrap()
+        + hashed::extract_hashed_assets(&store.dir, &store.index(), &options, &mut NoProgress)
+            .unwrap();
+
+    let lang_files: Vec<_> = fs
+        .files()
+        .into_iter()
+        .filter(|path| path.starts_with("out/assets/minecraft/lang"))
+        .collect();
+    assert_eq!(
+        lang_files,
+        [
+            PathBuf::from("out/assets/minecraft/lang/de_de.json"),
+            PathBuf::from("out/assets/minecraft/lang/en_us.json"),
+            PathBuf::from("out/assets/minecraft/lang/fr_fr.lang"),
+        ]
+    );
+
+    let en_us: serde_json::Value = serde_json::from_slice(
+        &fs.read(Path::new("out/assets/minecraft/lang/en_us.json"))
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        en_us,
+        serde_json::json!({"menu.quit": "Quit Game", "tip": "One\nTwo"})
+    );
+
+    // Files which can't be converted are extracted as they are, with a warning.
+    assert_eq!(report.warnings.len(), 1);
+    assert_eq!(
+        report.warnings[0].path,
+        Path::new("assets/minecraft/lang/fr_fr.lang")
+    );
+    assert_eq!(report.summary.failures, 0);
+}
+
+#[test]
+fn converted_lang_files_keep_their_originals() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client()
+        .file(
+            "assets/realms/lang/en_us.json",
+            "{\"menu.quit\": \"Quit Game\", \"key=with#\": \"a\\\\b\"}",
+        )
+        .write(&dir.path().join("client.jar"));
+
+    let fs = Arc::new(MemoryFs::new());
+    let options = ExtractOptions::new()
+        .output("out")
+        .vfs(fs.clone())
+        .convert_lang(Some(LangFormat::Lang))
+        .keep_original(true);
+    let report = jar::extract_jar(&jar, &options, &mut NoProgress).unwrap();
+
+    assert!(fs.exists(Path::new("out/assets/realms/lang/en_us.json")));
+    assert_eq!(
+        fs.read(Path::new("out/assets/realms/lang/en_us.lang"))
+            .unwrap(),
+        b"key\\=with#=a\\\\b\nmenu.quit=Quit Game\n"
+    );
+    assert!(report.warnings.is_empty());
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn lang_files_are_converted_by_async_extraction() {
+    use extract_minecraft_assets::asynchronous;
+
+    let dir = common::temp_dir();
+    let store = AssetStoreBuilder::new()
+        .file("minecraft/lang/de_de.lang", "menu.quit=Spiel beenden\n")
+        .write(&dir.path().join("assets"));
+
+    let fs = Arc::new(MemoryFs::new());
+    let options = ExtractOptions::new()
+        .output("out")
+        .vfs(fs.clone())
+        .convert_lang(Some(LangFormat::Json));
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    let report = runtime
+        .block_on(asynchronous::extract_hashed_assets_async(
+            &store.dir,
+            &store.index(),
+            &options,
+            None,
+        ))
+        .unwrap();
+
+    assert_eq!(report.summary.files, 1);
+    assert_eq!(
+        fs.files(),
+        [PathBuf::from("out/assets/minecraft/lang/de_de.json")]
+    );
+}
+
+#[test]
+fn locales_are_listed_from_jar_and_index() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client()
+        .file("assets/realms/lang/fr_fr.json", "{}\n")
+        .write(&dir.path().join("client.jar"));
+    let store = AssetStoreBuilder::typical()
+        .file(
+            "pack.mcmeta",
+            r#"{"language": {"de_de": {"name": "Deutsch", "region": "Deutschland"}}}"#,
+        )
+        .write(&dir.path().join("assets"));
+
+    let mut list = LocaleList::new();
+    list.add_jar(&jar).unwrap();
+    list.add_index(&store.dir, &store.index());
+    let locales = list.locales();
+
+    // Only the `minecraft` namespace has locales of its own.
+    assert_eq!(locales.len(), 2);
+    assert_eq!(locales[0].code, "de_de");
+    assert_eq!(locales[0].name.as_deref(), Some("Deutsch"));
+    assert_eq!(locales[0].region.as_deref(), Some("Deutschland"));
+    assert_eq!(locales[0].source, FileSource::Hashed);
+    assert_eq!(locales[1].code, "en_us");
+    assert_eq!(locales[1].name, None);
+    assert_eq!(locales[1].path, Path::new("minecraft/lang/en_us.json"));
+    assert_eq!(locales[1].source, FileSource::Jar);
+    assert!(!list.contains("fr_fr"));
+}
+
+#[test]
+fn legacy_locales_are_named_by_the_jar_list() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::new()
+        .file("assets/minecraft/lang/en_US.lang", "menu.quit=Quit Game\n")
+        .file(
+            "assets/minecraft/lang/_list",
+            "# Languages\nen_US=English (US)\n\nde_DE=Deutsch\n",
+        )
+        .file("pack.mcmeta", "{\"pack\": {\"pack_format\": 1}}\n")
+        .write(&dir.path().join("client.jar"));
+    let store = AssetStoreBuilder::new()
+        .file("minecraft/lang/de_DE.lang", "menu.quit=Spiel beenden\n")
+        .missing("pack.mcmeta", "{}")
+        .write(&dir.path().join("assets"));
+
+    let mut list = LocaleList::new();
+    list.add_jar(&jar).unwrap();
+    // The missing `pack.mcmeta` only means the names aren't known from it.
+    list.add_index(&store.dir, &store.index());
+    let locales = list.locales();
+
+    assert!(list.contains("en_us"));
+    assert_eq!(
+        locales
+            .iter()
+            .map(|locale| (locale.code.as_str(), locale.name.as_deref()))
+            .collect::<Vec<_>>(),
+        [("de_DE", Some("Deutsch")), ("en_US", Some("English (US)"))]
+    );
+}
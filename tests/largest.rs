@@ -0,0 +1,50 @@
+mod common;
+
+use common::{AssetStoreBuilder, JarBuilder};
+use extract_minecraft_assets::category::Category;
+use extract_minecraft_assets::jar::ExtractedContents;
+use extract_minecraft_assets::largest::LargestFiles;
+use extract_minecraft_assets::search::FileSource;
+use std::path::Path;
+
+#[test]
+fn largest_files_merge_jar_and_index_preferring_hashed() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client().write(&dir.path().join("client.jar"));
+    let store = AssetStoreBuilder::typical()
+        .file("minecraft/lang/en_us.json", "{}\n")
+        .write(&dir.path().join("assets"));
+
+    let mut files = LargestFiles::new();
+    files.add_jar(&jar, &ExtractedContents::all()).unwrap();
+    files.add_index(&store.index());
+    let largest = files.largest(usize::MAX);
+
+    let en_us: Vec<_> = largest
+        .iter()
+        .filter(|file| file.path == Path::new("assets/minecraft/lang/en_us.json"))
+        .collect();
+    assert_eq!(en_us.len(), 1);
+    assert_eq!((en_us[0].source, en_us[0].size), (FileSource::Hashed, 3));
+
+    assert!(largest.windows(2).all(|pair| pair[0].size >= pair[1].size));
+    assert_eq!(files.largest(2).len(), 2);
+}
+
+#[test]
+fn largest_files_are_classified_within_their_root() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client().write(&dir.path().join("client.jar"));
+
+    let mut files = LargestFiles::new();
+    files.add_jar(&jar, &ExtractedContents::data()).unwrap();
+    files.retain(|file| file.category == Category::Tags);
+    let largest = files.largest(10);
+
+    assert_eq!(largest.len(), 1);
+    assert_eq!(
+        largest[0].path,
+        Path::new("data/minecraft/tags/blocks/logs.json")
+    );
+    assert_eq!(largest[0].namespace.as_deref(), Some("minecraft"));
+}
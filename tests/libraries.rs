@@ -0,0 +1,165 @@
+mod common;
+
+use extract_minecraft_assets::libraries::{self, Artifact, LibraryStatus, Platform};
+use extract_minecraft_assets::util;
+use extract_minecraft_assets::version::VersionManifest;
+use std::fs;
+use std::path::Path;
+
+const LINUX: Platform = Platform {
+    os: "linux",
+    arch: "x86_64",
+    is_64_bit: true,
+};
+const MACOS: Platform = Platform {
+    os: "osx",
+    arch: "arm64",
+    is_64_bit: true,
+};
+const WINDOWS_32_BIT: Platform = Platform {
+    os: "windows",
+    arch: "x86",
+    is_64_bit: false,
+};
+
+/// Returns the JSON of an artifact at `path`, containing its own path.
+fn artifact(path: &str) -> String {
+    format!(
+        r#"{{"path": "{path}", "sha1": "{}", "size": {}, "url": "https://libraries.minecraft.net/{path}"}}"#,
+        util::sha1_hex(path.as_bytes()).unwrap(),
+        path.len()
+    )
+}
+
+fn manifest() -> VersionManifest {
+    let manifest = format!(
+        r#"{{
+            "id": "1.12.2",
+            "libraries": [
+                {{"name": "com.mojang:brigadier:1.0", "downloads": {{"artifact": {}}}}},
+                {{
+                    "name": "ca.weblite:java-objc-bridge:1.0",
+                    "downloads": {{"artifact": {}}},
+                    "rules": [{{"action": "allow", "os": {{"name": "osx"}}}}]
+                }},
+                {{
+                    "name": "net.java.jinput:jinput:2.0",
+                    "downloads": {{"artifact": {}}},
+                    "rules": [
+                        {{"action": "allow"}},
+                        {{"action": "disallow", "os": {{"name": "osx"}}}}
+                    ]
+                }},
+                {{
+                    "name": "org.lwjgl:lwjgl-platform:2.9",
+                    "downloads": {{"classifiers": {{
+                        "natives-linux": {},
+                        "natives-windows-32": {},
+                        "natives-windows-64": {}
+                    }}}},
+                    "natives": {{"linux": "natives-linux", "windows": "natives-windows-${{arch}}"}}
+                }},
+                {{"name": "com.mojang:brigadier:1.0", "downloads": {{"artifact": {}}}}}
+            ]
+        }}"#,
+        artifact("brigadier.jar"),
+        artifact("objc-bridge.jar"),
+        artifact("jinput.jar"),
+        artifact("lwjgl-natives-linux.jar"),
+        artifact("lwjgl-natives-windows-32.jar"),
+        artifact("lwjgl-natives-windows-64.jar"),
+        artifact("brigadier.jar"),
+    );
+
+    VersionManifest::from_reader(manifest.as_bytes()).unwrap()
+}
+
+fn paths(artifacts: Vec<&Artifact>) -> Vec<&Path> {
+    artifacts
+        .into_iter()
+        .map(|artifact| artifact.path.as_path())
+        .collect()
+}
+
+#[test]
+fn libraries_are_chosen_by_their_rules() {
+    let manifest = manifest();
+
+    assert_eq!(
+        paths(manifest.library_artifacts(&LINUX)),
+        [
+            Path::new("brigadier.jar"),
+            Path::new("jinput.jar"),
+            Path::new("lwjgl-natives-linux.jar"),
+        ]
+    );
+    assert_eq!(
+        paths(manifest.library_artifacts(&MACOS)),
+        [Path::new("brigadier.jar"), Path::new("objc-bridge.jar")]
+    );
+    assert_eq!(
+        paths(manifest.library_artifacts(&WINDOWS_32_BIT)),
+        [
+            Path::new("brigadier.jar"),
+            Path::new("jinput.jar"),
+            Path::new("lwjgl-natives-windows-32.jar"),
+        ]
+    );
+}
+
+#[test]
+fn libraries_are_copied_if_they_match() {
+    let dir = common::temp_dir();
+    let libraries_dir = dir.path().join("libraries");
+    let output_dir = dir.path().join("out");
+    let manifest = manifest();
+    let artifacts = manifest.library_artifacts(&LINUX);
+
+    let write = |path: &Path, contents: &str| {
+        let path = libraries_dir.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    };
+    write(&artifacts[0].path, "brigadier.jar");
+    write(&artifacts[1].path, "not jinput");
+
+    let statuses: Vec<_> = artifacts
+        .iter()
+        .map(|artifact| libraries::copy_library(&libraries_dir, artifact, &output_dir).unwrap())
+        .collect();
+
+    assert_eq!(
+        statuses,
+        [
+            LibraryStatus::Copied,
+            LibraryStatus::Mismatched {
+                sha1: util::sha1_hex("not jinput".as_bytes()).unwrap()
+            },
+            LibraryStatus::Missing,
+        ]
+    );
+    assert_eq!(
+        fs::read_to_string(output_dir.join("brigadier.jar")).unwrap(),
+        "brigadier.jar"
+    );
+    assert!(!output_dir.join("jinput.jar").exists());
+}
+
+#[test]
+fn paths_leaving_the_libraries_directory_are_refused() {
+    let dir = common::temp_dir();
+    let manifest = VersionManifest::from_reader(
+        format!(
+            r#"{{"id": "1.0", "libraries": [{{"name": "evil", "downloads": {{"artifact": {}}}}}]}}"#,
+            artifact("../evil.jar")
+        )
+        .as_bytes(),
+    )
+    .unwrap();
+    let artifacts = manifest.library_artifacts(&LINUX);
+    fs::write(dir.path().join("evil.jar"), "../evil.jar").unwrap();
+
+    let result = libraries::copy_library(&dir.path().join("libraries"), artifacts[0], dir.path());
+
+    assert!(result.is_err());
+}
@@ -0,0 +1,111 @@
+mod common;
+
+use common::AssetStoreBuilder;
+use extract_minecraft_assets::hashed;
+use extract_minecraft_assets::locked::LockedRetry;
+use extract_minecraft_assets::options::ExtractOptions;
+use extract_minecraft_assets::progress::NoProgress;
+use extract_minecraft_assets::vfs::MemoryFs;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+#[test]
+fn missing_files_are_not_retried() {
+    let dir = common::temp_dir();
+    let store = AssetStoreBuilder::typical().write(&dir.path().join("assets"));
+    let options = ExtractOptions::new()
+        .vfs(Arc::new(MemoryFs::new()))
+        .locked_retry(LockedRetry {
+            retries: 5,
+            delay: Duration::from_secs(1),
+        });
+
+    let start = Instant::now();
+    let report =
+        hashed::extract_hashed_assets(&store.dir, &store.index(), &options, &mut NoProgress)
+            .unwrap();
+
+    assert_eq!(report.summary.failures, 1);
+    assert!(start.elapsed() < Duration::from_secs(1));
+}
+
+#[cfg(windows)]
+mod windows {
+    use super::*;
+    use extract_minecraft_assets::report::FileOutcome;
+    use std::fs::{File, OpenOptions};
+    use std::os::windows::fs::OpenOptionsExt;
+    use std::path::Path;
+    use std::thread;
+
+    /// Opens `path` without sharing it, so that nothing else can open it
+    /// until it is closed.
+    fn lock(path: &Path) -> File {
+        OpenOptions::new()
+            .read(true)
+            .share_mode(0)
+            .open(path)
+            .unwrap()
+    }
+
+    #[test]
+    fn hashed_files_are_read_once_unlocked() {
+        let dir = common::temp_dir();
+        let store = AssetStoreBuilder::new()
+            .file("minecraft/lang/de_de.json", "{}\n")
+            .write(&dir.path().join("assets"));
+        let index = store.index();
+        let object_path = store
+            .dir
+            .join("objects")
+            .join(index.get("minecraft/lang/de_de.json").unwrap().object_path);
+
+        let file = lock(&object_path);
+        let unlock = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(150));
+            drop(file);
+        });
+
+        let options = ExtractOptions::new()
+            .vfs(Arc::new(MemoryFs::new()))
+            .locked_retry(LockedRetry {
+                retries: 10,
+                delay: Duration::from_millis(50),
+            });
+        let report =
+            hashed::extract_hashed_assets(&store.dir, &index, &options, &mut NoProgress).unwrap();
+        unlock.join().unwrap();
+
+        assert_eq!(report.summary.failures, 0);
+        assert_eq!(report.summary.files, 1);
+    }
+
+    #[test]
+    fn files_still_locked_fail_naming_the_other_process() {
+        let dir = common::temp_dir();
+        let store = AssetStoreBuilder::new()
+            .file("minecraft/lang/de_de.json", "{}\n")
+            .write(&dir.path().join("assets"));
+        let index = store.index();
+        let object_path = store
+            .dir
+            .join("objects")
+            .join(index.get("minecraft/lang/de_de.json").unwrap().object_path);
+
+        let _file = lock(&object_path);
+        let options = ExtractOptions::new()
+            .vfs(Arc::new(MemoryFs::new()))
+            .locked_retry(LockedRetry {
+                retries: 2,
+                delay: Duration::from_millis(10),
+            });
+        let report =
+            hashed::extract_hashed_assets(&store.dir, &index, &options, &mut NoProgress).unwrap();
+
+        let FileOutcome::Failed { error } = &report.files[0].outcome else {
+            panic!("unexpected outcome: {:?}", report.files[0].outcome);
+        };
+        assert!(error.contains("another process"), "{error}");
+        assert!(error.contains("tried 3 times"), "{error}");
+    }
+}
@@ -0,0 +1,121 @@
+mod common;
+
+use common::AssetStoreBuilder;
+use extract_minecraft_assets::lookup::{self, HashLookup};
+use std::fs;
+use std::path::PathBuf;
+
+#[test]
+fn lookup_finds_indexes_and_versions_using_a_hash() {
+    let dir = common::temp_dir();
+    let assets_dir = dir.path().join("assets");
+    let versions_dir = dir.path().join("versions");
+    AssetStoreBuilder::typical()
+        .index_id("5")
+        .write(&assets_dir);
+    AssetStoreBuilder::new()
+        .index_id("8")
+        .file(
+            "minecraft/lang/de_de.json",
+            "{\"menu.quit\": \"Spiel beenden\"}\n",
+        )
+        .file(
+            "realms/lang/de_de.json",
+            "{\"menu.quit\": \"Spiel beenden\"}\n",
+        )
+        .write(&assets_dir);
+    common::write_manifest(&versions_dir, "1.20.1", "\"assets\": \"5\"");
+    common::write_manifest(&versions_dir, "1.20.2", "\"assets\": \"8\"");
+    common::write_manifest(
+        &versions_dir,
+        "fabric-loader",
+        "\"inheritsFrom\": \"1.20.1\"",
+    );
+
+    let lookup = HashLookup::load(&assets_dir, &versions_dir);
+    let hash = common::object(b"{\"menu.quit\": \"Spiel beenden\"}\n").hash;
+    let usages = lookup.find(&hash[..8]);
+
+    assert!(lookup.errors().is_empty());
+    assert_eq!(usages.len(), 1);
+    assert_eq!(usages[0].hash, hash);
+
+    let indexes: Vec<_> = usages[0]
+        .indexes
+        .iter()
+        .map(|index| {
+            (
+                index.id.as_str(),
+                index.paths.clone(),
+                index.versions.clone(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        indexes,
+        [
+            (
+                "5",
+                vec![PathBuf::from("minecraft/lang/de_de.json")],
+                vec!["1.20.1".to_owned(), "fabric-loader".to_owned()],
+            ),
+            (
+                "8",
+                vec![
+                    PathBuf::from("minecraft/lang/de_de.json"),
+                    PathBuf::from("realms/lang/de_de.json"),
+                ],
+                vec!["1.20.2".to_owned()],
+            ),
+        ]
+    );
+}
+
+#[test]
+fn lookup_matches_every_hash_with_a_prefix_and_reports_bad_indexes() {
+    let dir = common::temp_dir();
+    let assets_dir = dir.path().join("assets");
+    AssetStoreBuilder::typical().write(&assets_dir);
+    fs::write(assets_dir.join("indexes/broken.json"), "{\"objects\": ").unwrap();
+
+    let lookup = HashLookup::load(&assets_dir, &dir.path().join("versions"));
+
+    assert_eq!(lookup.find("").len(), 3);
+    assert!(lookup.find("not a hash").is_empty());
+    assert_eq!(lookup.errors().len(), 1);
+}
+
+#[test]
+fn indexes_are_listed_with_the_versions_using_them() {
+    let dir = common::temp_dir();
+    let assets_dir = dir.path().join("assets");
+    let versions_dir = dir.path().join("versions");
+    let store = AssetStoreBuilder::typical()
+        .index_id("5")
+        .write(&assets_dir);
+    AssetStoreBuilder::new().index_id("17").write(&assets_dir);
+    fs::write(assets_dir.join("indexes/broken.json"), "{\"objects\": ").unwrap();
+    common::write_manifest(&versions_dir, "1.20.1", "\"assets\": \"5\"");
+    common::write_manifest(
+        &versions_dir,
+        "fabric-loader",
+        "\"inheritsFrom\": \"1.20.1\"",
+    );
+
+    let indexes = lookup::list_indexes(&assets_dir, &versions_dir);
+    let ids: Vec<_> = indexes.iter().map(|index| index.id.as_str()).collect();
+    assert_eq!(ids, ["17", "5", "broken"]);
+
+    let index = &indexes[1];
+    assert_eq!(index.objects, Some(store.index().len()));
+    assert_eq!(index.total_size, Some(store.index().total_size()));
+    assert_eq!(index.size, fs::metadata(&store.index_file).unwrap().len());
+    assert_eq!(index.versions, ["1.20.1", "fabric-loader"]);
+    assert_eq!(index.error, None);
+
+    // Unused and unreadable index files are listed too.
+    assert!(indexes[0].versions.is_empty());
+    assert_eq!(indexes[0].objects, Some(0));
+    assert_eq!(indexes[2].objects, None);
+    assert!(indexes[2].error.is_some());
+}
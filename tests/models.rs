@@ -0,0 +1,139 @@
+mod common;
+
+use common::JarBuilder;
+use extract_minecraft_assets::closure::{AssetSources, DanglingReference};
+use extract_minecraft_assets::models;
+use std::path::PathBuf;
+
+/// Returns a jar with the blockstate, models and textures of oak stairs, and
+/// an item model with overrides.
+fn jar() -> JarBuilder {
+    JarBuilder::client()
+        .file(
+            "assets/minecraft/blockstates/oak_stairs.json",
+            r#"{"variants": {
+                "facing=east,half=bottom": {"model": "minecraft:block/oak_stairs"},
+                "facing=east,half=top": [
+                    {"model": "minecraft:block/oak_stairs_inner"},
+                    {"model": "block/oak_stairs", "y": 90}
+                ]
+            }}"#,
+        )
+        .file(
+            "assets/minecraft/models/block/oak_stairs.json",
+            r##"{"parent": "minecraft:block/stairs", "textures": {
+                "bottom": "minecraft:block/oak_planks",
+                "side": "minecraft:block/oak_planks",
+                "top": "#side"
+            }}"##,
+        )
+        .file(
+            "assets/minecraft/models/block/oak_stairs_inner.json",
+            r#"{"parent": "minecraft:block/inner_stairs", "textures": {
+                "side": "minecraft:block/missing_planks"
+            }}"#,
+        )
+        // A cycle, which must still end.
+        .file(
+            "assets/minecraft/models/block/stairs.json",
+            r#"{"parent": "minecraft:block/oak_stairs"}"#,
+        )
+        .file("assets/minecraft/textures/block/oak_planks.png", "PNG oak")
+        .file(
+            "assets/minecraft/textures/block/oak_planks.png.mcmeta",
+            "{\"animation\": {}}",
+        )
+        .file("assets/minecraft/textures/block/stone.png", "PNG stone")
+        .file(
+            "assets/minecraft/models/item/compass.json",
+            r#"{"parent": "builtin/generated", "overrides": [
+                {"predicate": {"angle": 0.5}, "model": "minecraft:item/compass_16"}
+            ]}"#,
+        )
+        .file(
+            "assets/minecraft/models/item/compass_16.json",
+            r#"{"parent": "minecraft:item/generated", "textures": {
+                "layer0": "minecraft:item/compass_16"
+            }}"#,
+        )
+        .file(
+            "assets/minecraft/models/item/generated.json",
+            r#"{"parent": "builtin/generated"}"#,
+        )
+        .file(
+            "assets/minecraft/textures/item/compass_16.png",
+            "PNG compass",
+        )
+}
+
+#[test]
+fn blockstate_closure_follows_models_and_textures() {
+    let dir = common::temp_dir();
+    let jar = jar().write(&dir.path().join("client.jar"));
+
+    let mut sources = AssetSources::new().jar(&jar).unwrap();
+    let closure = models::models_closure(
+        &mut sources,
+        [PathBuf::from("minecraft/blockstates/oak_stairs.json")],
+    )
+    .unwrap();
+
+    let files: Vec<_> = closure
+        .files
+        .iter()
+        .map(|file| file.path.to_str().unwrap())
+        .collect();
+    assert_eq!(
+        files,
+        [
+            "minecraft/blockstates/oak_stairs.json",
+            "minecraft/models/block/oak_stairs.json",
+            "minecraft/models/block/oak_stairs_inner.json",
+            "minecraft/models/block/stairs.json",
+            "minecraft/textures/block/oak_planks.png",
+            "minecraft/textures/block/oak_planks.png.mcmeta",
+        ]
+    );
+    assert_eq!(
+        closure.dangling,
+        [
+            DanglingReference {
+                from: PathBuf::from("minecraft/models/block/oak_stairs_inner.json"),
+                path: PathBuf::from("minecraft/models/block/inner_stairs.json"),
+            },
+            DanglingReference {
+                from: PathBuf::from("minecraft/models/block/oak_stairs_inner.json"),
+                path: PathBuf::from("minecraft/textures/block/missing_planks.png"),
+            },
+        ]
+    );
+}
+
+#[test]
+fn item_model_closure_follows_overrides() {
+    let dir = common::temp_dir();
+    let jar = jar().write(&dir.path().join("client.jar"));
+
+    let mut sources = AssetSources::new().jar(&jar).unwrap();
+    let closure = models::models_closure(
+        &mut sources,
+        [PathBuf::from("minecraft/models/item/compass.json")],
+    )
+    .unwrap();
+
+    let files: Vec<_> = closure
+        .files
+        .iter()
+        .map(|file| file.path.to_str().unwrap())
+        .collect();
+    assert_eq!(
+        files,
+        [
+            "minecraft/models/item/compass.json",
+            "minecraft/models/item/compass_16.json",
+            "minecraft/models/item/generated.json",
+            "minecraft/textures/item/compass_16.png",
+        ]
+    );
+    assert!(closure.dangling.is_empty());
+}
@@ -0,0 +1,112 @@
+mod common;
+
+use common::JarBuilder;
+use extract_minecraft_assets::jar::{self, ExtractedContents};
+use extract_minecraft_assets::nbt::{self, NbtError, NbtFormat};
+use extract_minecraft_assets::options::ExtractOptions;
+use extract_minecraft_assets::progress::NoProgress;
+use extract_minecraft_assets::vfs::{MemoryFs, Vfs};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+fn fixture(name: &str) -> Vec<u8> {
+    fs::read(
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures")
+            .join(name),
+    )
+    .unwrap()
+}
+
+#[test]
+fn gzipped_structures_are_converted_to_snbt() {
+    let snbt = nbt::convert(&fixture("structure.nbt"), NbtFormat::Snbt).unwrap();
+
+    assert_eq!(
+        String::from_utf8(snbt).unwrap(),
+        String::from_utf8(fixture("structure.snbt")).unwrap()
+    );
+}
+
+#[test]
+fn truncated_files_are_refused() {
+    let error = nbt::read(&[10, 0, 0, 3, 0, 1, b'a', 0]).unwrap_err();
+    assert!(matches!(error, NbtError::Io(_)), "{error:?}");
+
+    let error = nbt::read(&[8, 0, 0, 0, 0]).unwrap_err();
+    assert!(matches!(error, NbtError::RootNotCompound), "{error:?}");
+}
+
+#[test]
+fn nbt_files_of_data_are_converted_while_extracting() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client()
+        .file(
+            "data/minecraft/structure/igloo/top.nbt",
+            fixture("structure.nbt"),
+        )
+        .file("data/minecraft/structure/broken.nbt", "not nbt")
+        .write(&dir.path().join("client.jar"));
+
+    let fs = Arc::new(MemoryFs::new());
+    let options = ExtractOptions::new()
+        .output("out")
+        .vfs(fs.clone())
+        .contents(ExtractedContents::data())
+        .convert_nbt(Some(NbtFormat::Snbt));
+    let report = jar::extract_jar(&jar, &options, &mut NoProgress).unwrap();
+
+    let structures: Vec<_> = fs
+        .files()
+        .into_iter()
+        .filter(|path| path.starts_with("out/data/minecraft/structure"))
+        .collect();
+    assert_eq!(
+        structures,
+        [
+            PathBuf::from("out/data/minecraft/structure/broken.nbt"),
+            PathBuf::from("out/data/minecraft/structure/igloo/top.snbt"),
+        ]
+    );
+    assert_eq!(
+        fs.read(Path::new("out/data/minecraft/structure/igloo/top.snbt"))
+            .unwrap(),
+        fixture("structure.snbt")
+    );
+
+    // Files which can't be parsed are extracted as they are, with a warning.
+    assert_eq!(report.warnings.len(), 1);
+    assert_eq!(
+        report.warnings[0].path,
+        Path::new("data/minecraft/structure/broken.nbt")
+    );
+    assert_eq!(report.summary.failures, 0);
+}
+
+#[test]
+fn converted_nbt_files_keep_their_originals() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client()
+        .file(
+            "data/minecraft/structure/igloo/top.nbt",
+            fixture("structure.nbt"),
+        )
+        .write(&dir.path().join("client.jar"));
+
+    let fs = Arc::new(MemoryFs::new());
+    let options = ExtractOptions::new()
+        .output("out")
+        .vfs(fs.clone())
+        .contents(ExtractedContents::data())
+        .convert_nbt(Some(NbtFormat::Snbt))
+        .keep_original(true);
+    jar::extract_jar(&jar, &options, &mut NoProgress).unwrap();
+
+    assert_eq!(
+        fs.read(Path::new("out/data/minecraft/structure/igloo/top.nbt"))
+            .unwrap(),
+        fixture("structure.nbt")
+    );
+    assert!(fs.exists(Path::new("out/data/minecraft/structure/igloo/top.snbt")));
+}
@@ -0,0 +1,173 @@
+mod common;
+
+use common::JarBuilder;
+use extract_minecraft_assets::jar::{self, ExtractedContents};
+use extract_minecraft_assets::options::ExtractOptions;
+use extract_minecraft_assets::pack::{self, FormatRange, Overlay, PackError};
+use extract_minecraft_assets::progress::NoProgress;
+use extract_minecraft_assets::vfs::MemoryFs;
+use serde_json::{Value, json};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Parses each of the `overlays`.
+fn overlays(overlays: &[&str]) -> Vec<Overlay> {
+    overlays
+        .iter()
+        .map(|overlay| overlay.parse().unwrap())
+        .collect()
+}
+
+/// Returns the `pack.mcmeta` written over `existing`, as JSON.
+fn build(
+    existing: Option<Value>,
+    pack_format: Option<u32>,
+    overlays: &[Overlay],
+) -> Result<Value, PackError> {
+    let existing = existing.map(|existing| serde_json::to_vec(&existing).unwrap());
+    let pack_mcmeta = pack::pack_mcmeta(existing.as_deref(), pack_format, overlays)?;
+
+    Ok(serde_json::from_slice(&pack_mcmeta).unwrap())
+}
+
+#[test]
+fn overlays_are_parsed() {
+    assert_eq!(
+        "new_ui:18-22".parse::<Overlay>().unwrap(),
+        Overlay {
+            directory: "new_ui".to_owned(),
+            formats: FormatRange {
+                min_inclusive: 18,
+                max_inclusive: 22
+            },
+        }
+    );
+    assert_eq!(
+        "old.v1:15".parse::<Overlay>().unwrap().formats,
+        FormatRange {
+            min_inclusive: 15,
+            max_inclusive: 15
+        }
+    );
+
+    for malformed in [
+        "new_ui",
+        "new_ui:",
+        ":18",
+        "new_ui:18-",
+        "new_ui:a-b",
+        "new_ui:-1",
+    ] {
+        assert!(malformed.parse::<Overlay>().is_err(), "{malformed}");
+    }
+    assert!(matches!(
+        "New UI:18".parse::<Overlay>(),
+        Err(PackError::InvalidDirectory(_))
+    ));
+    assert!(matches!(
+        "overlays/new_ui:18".parse::<Overlay>(),
+        Err(PackError::InvalidDirectory(_))
+    ));
+    assert!(matches!(
+        "new_ui:22-18".parse::<Overlay>(),
+        Err(PackError::ReversedFormats { min: 22, max: 18 })
+    ));
+}
+
+#[test]
+fn pack_mcmeta_is_written() {
+    let pack_mcmeta = build(None, Some(18), &overlays(&["a:15-17", "b:18"])).unwrap();
+
+    assert_eq!(
+        pack_mcmeta,
+        json!({
+            "pack": {"pack_format": 18, "description": "Extracted Minecraft assets"},
+            "overlays": {"entries": [
+                {"directory": "a", "formats": {"min_inclusive": 15, "max_inclusive": 17}},
+                {"directory": "b", "formats": {"min_inclusive": 18, "max_inclusive": 18}},
+            ]},
+        })
+    );
+    assert!(matches!(
+        build(None, None, &[]),
+        Err(PackError::MissingPackFormat)
+    ));
+}
+
+#[test]
+fn existing_pack_mcmeta_is_updated() {
+    let existing = json!({
+        "pack": {"pack_format": 15, "description": "My pack"},
+        "overlays": {"entries": [
+            {"directory": "old", "formats": [10, 12]},
+            {"directory": "new_ui", "formats": 13},
+        ]},
+        "filter": {"block": []},
+    });
+    let pack_mcmeta = build(Some(existing.clone()), None, &overlays(&["new_ui:18-22"])).unwrap();
+
+    assert_eq!(pack_mcmeta["pack"], existing["pack"]);
+    assert_eq!(pack_mcmeta["filter"], existing["filter"]);
+    assert_eq!(
+        pack_mcmeta["overlays"]["entries"],
+        json!([
+            {"directory": "old", "formats": [10, 12]},
+            {"directory": "new_ui", "formats": {"min_inclusive": 18, "max_inclusive": 22}},
+        ])
+    );
+
+    // Overlapping the existing overlays isn't allowed either.
+    assert!(matches!(
+        build(Some(existing), None, &overlays(&["newer:12-14"])),
+        Err(PackError::OverlappingOverlays { first, second, .. }) if first == "old" && second == "newer"
+    ));
+}
+
+#[test]
+fn overlapping_or_duplicate_overlays_are_rejected() {
+    assert!(matches!(
+        build(None, Some(18), &overlays(&["a:15-18", "b:18-20"])),
+        Err(PackError::OverlappingOverlays { .. })
+    ));
+    assert!(matches!(
+        build(None, Some(18), &overlays(&["a:15", "a:16"])),
+        Err(PackError::DuplicateOverlay(directory)) if directory == "a"
+    ));
+    assert!(matches!(
+        build(Some(json!([])), Some(18), &[]),
+        Err(PackError::InvalidPackMcmeta(_))
+    ));
+}
+
+#[test]
+fn assets_are_placed_in_overlays() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client().write(&dir.path().join("client.jar"));
+
+    for keep_base in [false, true] {
+        let fs = Arc::new(MemoryFs::new());
+        let options = ExtractOptions::new()
+            .output("out")
+            .vfs(fs.clone())
+            .contents(ExtractedContents::all());
+        let report = jar::extract_jar(&jar, &options, &mut NoProgress).unwrap();
+
+        let placed =
+            pack::place_in_overlays(&report, &overlays(&["a:15", "b:16-18"]), keep_base).unwrap();
+
+        let files = fs.files();
+        assert_eq!(placed, 3 * 2);
+        for overlay in ["a", "b"] {
+            assert!(files.contains(&PathBuf::from(format!(
+                "out/{overlay}/assets/minecraft/lang/en_us.json"
+            ))));
+        }
+        assert_eq!(
+            files.contains(&PathBuf::from("out/assets/minecraft/lang/en_us.json")),
+            keep_base
+        );
+        // Only assets are placed in overlays.
+        assert!(files.contains(&PathBuf::from("out/data/minecraft/shared.json")));
+        assert!(!files.iter().any(|path| path.starts_with("out/a/data")));
+    }
+}
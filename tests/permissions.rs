@@ -0,0 +1,133 @@
+mod common;
+
+use common::JarBuilder;
+use extract_minecraft_assets::jar;
+use extract_minecraft_assets::options::ExtractOptions;
+use extract_minecraft_assets::progress::NoProgress;
+use extract_minecraft_assets::report::FileOutcome;
+use extract_minecraft_assets::vfs::{MemoryFs, Metadata, Vfs};
+use std::io::{self, Read};
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// A [`MemoryFs`] which fails to set permissions, like an exFAT drive,
+/// counting how many times it was asked to.
+#[derive(Default)]
+struct NoPermissionsFs {
+    fs: MemoryFs,
+    attempts: AtomicUsize,
+}
+
+impl Vfs for NoPermissionsFs {
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        self.fs.create_dir_all(path)
+    }
+
+    fn write_file(&self, path: &Path, contents: &mut dyn Read) -> io::Result<u64> {
+        self.fs.write_file(path, contents)
+    }
+
+    fn set_unix_mode(&self, _path: &Path, _mode: u32) -> io::Result<()> {
+        self.attempts.fetch_add(1, Ordering::Relaxed);
+
+        Err(io::Error::from(io::ErrorKind::PermissionDenied))
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<Metadata> {
+        self.fs.metadata(path)
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        self.fs.rename(from, to)
+    }
+
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        self.fs.remove_file(path)
+    }
+
+    fn remove_dir_all(&self, path: &Path) -> io::Result<()> {
+        self.fs.remove_dir_all(path)
+    }
+
+    fn copy(&self, from: &Path, to: &Path) -> io::Result<u64> {
+        self.fs.copy(from, to)
+    }
+}
+
+#[test]
+fn extraction_completes_without_permissions() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client().write(&dir.path().join("client.jar"));
+
+    let fs = Arc::new(NoPermissionsFs::default());
+    let options = ExtractOptions::new().output("out").vfs(fs.clone());
+    let report = jar::extract_jar(&jar, &options, &mut NoProgress).unwrap();
+
+    assert_eq!(report.summary.failures, 0);
+    assert!(report.summary.files > 0);
+    assert_eq!(
+        fs.fs
+            .read(Path::new("out/assets/minecraft/lang/en_us.json")),
+        Some(b"{\"menu.quit\": \"Quit Game\"}\n".to_vec())
+    );
+    // Only the first failure is tried and warned about.
+    assert_eq!(fs.attempts.load(Ordering::Relaxed), 1);
+    assert_eq!(report.warnings.len(), 1);
+    assert!(report.summary.permissions_not_set > 1);
+    assert!(report.summary.to_string().contains(&format!(
+        "{} without permissions",
+        report.summary.permissions_not_set
+    )));
+}
+
+#[test]
+fn strict_permissions_fail_each_file() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client().write(&dir.path().join("client.jar"));
+
+    let fs = Arc::new(NoPermissionsFs::default());
+    let options = ExtractOptions::new()
+        .output("out")
+        .vfs(fs.clone())
+        .strict_permissions(true);
+    let report = jar::extract_jar(&jar, &options, &mut NoProgress).unwrap();
+
+    // Every file and directory is tried, and fails.
+    assert_eq!(report.summary.files, 0);
+    assert_eq!(report.summary.permissions_not_set, 0);
+    assert_eq!(report.summary.failures, fs.attempts.load(Ordering::Relaxed));
+    assert!(report.summary.failures > 1);
+    assert!(report.failed().all(|file| matches!(
+        &file.outcome,
+        FileOutcome::Failed { error } if error.starts_with("failed to write")
+    )));
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn async_extraction_completes_without_permissions() {
+    use extract_minecraft_assets::asynchronous;
+
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client().write(&dir.path().join("client.jar"));
+
+    let fs = Arc::new(NoPermissionsFs::default());
+    let options = ExtractOptions::new().output("out").vfs(fs.clone());
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    let report = runtime
+        .block_on(asynchronous::extract_jar_async(&jar, &options, None))
+        .unwrap();
+
+    assert_eq!(report.summary.failures, 0);
+    assert!(
+        fs.fs
+            .read(Path::new("out/assets/minecraft/lang/en_us.json"))
+            .is_some()
+    );
+    assert_eq!(fs.attempts.load(Ordering::Relaxed), 1);
+    assert_eq!(report.warnings.len(), 1);
+    assert!(report.summary.permissions_not_set > 1);
+}
@@ -0,0 +1,124 @@
+mod common;
+
+use common::{AssetStoreBuilder, JarBuilder};
+use extract_minecraft_assets::format::JsonFormat;
+use extract_minecraft_assets::jar::ExtractedContents;
+use extract_minecraft_assets::options::ExtractOptions;
+use extract_minecraft_assets::pool::{ContentPool, PoolSummary, Pooled};
+use extract_minecraft_assets::progress::NoProgress;
+use extract_minecraft_assets::{hashed, jar, util};
+use std::fs;
+use std::path::Path;
+
+/// Whether `a` and `b` are hard links to the same file.
+#[cfg(unix)]
+fn same_file(a: &Path, b: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    fs::metadata(a).unwrap().ino() == fs::metadata(b).unwrap().ino()
+}
+
+#[test]
+fn identical_files_are_stored_once() {
+    let dir = common::temp_dir();
+    let pool = ContentPool::new(dir.path().join("pool"));
+    let (a, b, c) = (
+        dir.path().join("a.txt"),
+        dir.path().join("b.txt"),
+        dir.path().join("c.txt"),
+    );
+    fs::write(&a, "same\n").unwrap();
+    fs::write(&b, "same\n").unwrap();
+    fs::write(&c, "different\n").unwrap();
+
+    assert_eq!(pool.add(&a, None).unwrap(), Pooled::Added);
+    assert_eq!(pool.add(&b, None).unwrap(), Pooled::Linked);
+    assert_eq!(pool.add(&c, None).unwrap(), Pooled::Added);
+
+    let hash = util::sha1_hex("same\n".as_bytes()).unwrap();
+    assert!(
+        pool.path(&hash)
+            .starts_with(dir.path().join("pool").join(&hash[..2]))
+    );
+    assert_eq!(fs::read_to_string(&b).unwrap(), "same\n");
+    assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 4);
+    #[cfg(unix)]
+    {
+        assert!(same_file(&a, &b));
+        assert!(same_file(&b, &pool.path(&hash)));
+        assert!(!same_file(&a, &c));
+    }
+}
+
+#[test]
+fn extractions_are_deduplicated_across_output_dirs() {
+    let dir = common::temp_dir();
+    let store = AssetStoreBuilder::new()
+        .file(
+            "minecraft/lang/de_de.json",
+            "{\"menu.quit\": \"Spiel beenden\"}\n",
+        )
+        .write(&dir.path().join("assets"));
+    let pool = ContentPool::new(dir.path().join("out/.pool"));
+
+    let mut summaries = Vec::new();
+    for (version, splashes) in [("1.0", "Also try Terraria!\n"), ("1.1", "Now with more!\n")] {
+        let jar = JarBuilder::new()
+            .file("pack.mcmeta", "{}\n")
+            .file("assets/minecraft/lang/en_us.json", "{}\n")
+            .file("assets/minecraft/texts/splashes.txt", splashes)
+            .write(&dir.path().join(format!("{version}.jar")));
+        let options = ExtractOptions::new()
+            .output(dir.path().join("out").join(version))
+            .contents(ExtractedContents::assets());
+
+        let report = jar::extract_jar(&jar, &options, &mut NoProgress).unwrap()
+            + hashed::extract_hashed_assets(&store.dir, &store.index(), &options, &mut NoProgress)
+                .unwrap();
+        summaries.push(pool.add_extracted(&report).unwrap());
+    }
+
+    // The second version only stores its new splashes.
+    assert_eq!(summaries[0].linked, 0);
+    assert_eq!(
+        summaries[1],
+        PoolSummary {
+            files: 3,
+            bytes: summaries[0].bytes - 4,
+            linked: 2,
+            linked_bytes: summaries[0].bytes - "Also try Terraria!\n".len() as u64,
+        }
+    );
+    assert_eq!(summaries[1].stored_bytes(), "Now with more!\n".len() as u64);
+
+    // Hashed assets are pooled by the hashes in their index file.
+    let object = common::object(b"{\"menu.quit\": \"Spiel beenden\"}\n");
+    let lang_file = dir.path().join("out/1.1/assets/minecraft/lang/de_de.json");
+    assert_eq!(
+        fs::read(pool.path(&object.hash)).unwrap(),
+        fs::read(&lang_file).unwrap()
+    );
+    #[cfg(unix)]
+    assert!(same_file(&lang_file, &pool.path(&object.hash)));
+}
+
+#[test]
+fn transformed_hashed_assets_are_pooled_by_their_contents() {
+    let dir = common::temp_dir();
+    let store = AssetStoreBuilder::new()
+        .file("minecraft/lang/de_de.json", "{\"a\": 1}")
+        .write(&dir.path().join("assets"));
+    let pool = ContentPool::new(dir.path().join("pool"));
+    let options = ExtractOptions::new()
+        .output(dir.path().join("out"))
+        .format_json(Some(JsonFormat::Minify));
+
+    let report =
+        hashed::extract_hashed_assets(&store.dir, &store.index(), &options, &mut NoProgress)
+            .unwrap();
+    pool.add_extracted(&report).unwrap();
+
+    let hash = util::sha1_hex("{\"a\":1}".as_bytes()).unwrap();
+    assert_eq!(fs::read_to_string(pool.path(&hash)).unwrap(), "{\"a\":1}");
+    assert!(!pool.path(&common::object(b"{\"a\": 1}").hash).exists());
+}
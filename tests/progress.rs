@@ -0,0 +1,438 @@
+//! Tests of the events extractions send to a `ProgressSink`, and of the
+//! frames drawn by a `MultiProgress`, which are compared with snapshots.
+//!
+//! Run with `UPDATE_SNAPSHOTS=1` to rewrite the snapshots after an
+//! intentional change.
+
+mod common;
+
+use common::{AssetStoreBuilder, JarBuilder};
+use extract_minecraft_assets::error::ExtractError;
+use extract_minecraft_assets::hashed;
+use extract_minecraft_assets::jar::{self, ExtractedContents};
+use extract_minecraft_assets::options::{ExtractOptions, Overwrite};
+use extract_minecraft_assets::progress::{
+    FrameTarget, MultiProgress, ProgressSink, Summary, WorkerEvent,
+};
+use extract_minecraft_assets::vfs::{MemoryFs, Vfs};
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// A [`ProgressSink`] which records every event it is sent.
+#[derive(Default)]
+struct RecordedEvents(Vec<String>);
+
+impl ProgressSink for RecordedEvents {
+    fn begin(&mut self, total_files: usize, total_bytes: u64) {
+        self.0
+            .push(format!("begin {total_files} files, {total_bytes} bytes"));
+    }
+
+    fn file_started(&mut self, path: &Path) {
+        self.0.push(format!("started {}", path.display()));
+    }
+
+    fn file_done(&mut self, path: &Path, bytes: u64) {
+        self.0
+            .push(format!("done {} ({bytes} bytes)", path.display()));
+    }
+
+    fn file_skipped(&mut self, path: &Path) {
+        self.0.push(format!("skipped {}", path.display()));
+    }
+
+    fn file_failed(&mut self, path: &Path, _error: &ExtractError) {
+        self.0.push(format!("failed {}", path.display()));
+    }
+
+    fn file_warning(&mut self, path: &Path, message: &str) {
+        self.0
+            .push(format!("warning {}: {message}", path.display()));
+    }
+
+    fn finish(&mut self, summary: &Summary) {
+        self.0.push(format!(
+            "finish {} files, {} skipped, {} failed",
+            summary.files, summary.skipped, summary.failures
+        ));
+    }
+}
+
+#[test]
+fn extractions_send_every_event_in_order() {
+    let dir = common::temp_dir();
+    // `data` is there so that `assets` isn't taken to be the jar's top-level
+    // directory.
+    let jar = JarBuilder::new()
+        .dir("assets/")
+        .dir("data/")
+        .file("assets/minecraft/lang/en_us.json", "{}\n")
+        .file("assets/minecraft/shared.json", "{\"from\": \"assets\"}\n")
+        .file(
+            "assets/minecraft/texts/splashes.txt",
+            "Also try Terraria!\n",
+        )
+        .corrupted()
+        .write(&dir.path().join("client.jar"));
+
+    let fs = Arc::new(MemoryFs::new());
+    fs.create_dir_all(Path::new("out/assets/minecraft"))
+        .unwrap();
+    fs.write_file(
+        Path::new("out/assets/minecraft/shared.json"),
+        &mut "{}".as_bytes(),
+    )
+    .unwrap();
+    let options = ExtractOptions::new()
+        .output("out")
+        .vfs(fs)
+        .overwrite(Overwrite::Skip)
+        .threads(1);
+    let mut events = RecordedEvents::default();
+    jar::extract_jar(&jar, &options, &mut events).unwrap();
+
+    assert_eq!(
+        events.0,
+        [
+            "begin 3 files, 41 bytes",
+            "started assets/minecraft/lang/en_us.json",
+            "done assets/minecraft/lang/en_us.json (3 bytes)",
+            "skipped assets/minecraft/shared.json",
+            "started assets/minecraft/texts/splashes.txt",
+            "failed assets/minecraft/texts/splashes.txt",
+            "finish 1 files, 1 skipped, 1 failed",
+        ]
+    );
+}
+
+/// A version's jar file and hashed assets are extracted in two phases sharing
+/// one sink, each beginning with its own totals, known up front.
+#[test]
+fn phases_begin_with_their_totals() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client().write(&dir.path().join("client.jar"));
+    let store = AssetStoreBuilder::typical().write(&dir.path().join("assets"));
+    let index = store.index();
+
+    let options = ExtractOptions::new()
+        .output("out")
+        .vfs(Arc::new(MemoryFs::new()));
+    let mut events = RecordedEvents::default();
+    let report = jar::extract_jar(&jar, &options, &mut events).unwrap()
+        + hashed::extract_hashed_assets(&store.dir, &index, &options, &mut events).unwrap();
+
+    let jar_files = jar::list_files(&jar, &ExtractedContents::assets()).unwrap();
+    let jar_bytes: u64 = jar_files.iter().map(|(_, size)| size).sum();
+    let phases: Vec<_> = events
+        .0
+        .iter()
+        .filter(|event| event.starts_with("begin") || event.starts_with("finish"))
+        .collect();
+    assert_eq!(
+        phases,
+        [
+            &format!("begin {} files, {jar_bytes} bytes", jar_files.len()),
+            "finish 3 files, 0 skipped, 0 failed",
+            &format!("begin {} files, {} bytes", index.len(), index.total_size()),
+            // The hashed file missing from the store fails.
+            "finish 2 files, 0 skipped, 1 failed",
+        ]
+    );
+
+    let done = events
+        .0
+        .iter()
+        .filter(|event| event.starts_with("done"))
+        .count();
+    assert_eq!(report.summary.files, done);
+    assert_eq!(report.summary.failures, 1);
+}
+
+/// A [`ProgressSink`] which counts the bytes an extraction begins with and
+/// the bytes of each file done.
+#[derive(Default)]
+struct CountedBytes {
+    total: u64,
+    done: u64,
+}
+
+impl ProgressSink for CountedBytes {
+    fn begin(&mut self, _total_files: usize, total_bytes: u64) {
+        self.total = total_bytes;
+    }
+
+    fn file_done(&mut self, _path: &Path, bytes: u64) {
+        self.done += bytes;
+    }
+}
+
+/// The bytes of each file done add up to the total the extraction began
+/// with, whether it comes from the sizes in the index file or the sizes of
+/// the jar's entries.
+#[test]
+fn bytes_done_add_up_to_the_total() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client().write(&dir.path().join("client.jar"));
+    let store = AssetStoreBuilder::new()
+        .file(
+            "minecraft/lang/de_de.json",
+            "{\"menu.quit\": \"Beenden\"}\n",
+        )
+        .file("minecraft/sounds/music/game/calm1.ogg", "OggS fake music\n")
+        .write(&dir.path().join("assets"));
+
+    let options = ExtractOptions::new()
+        .output("out")
+        .vfs(Arc::new(MemoryFs::new()));
+
+    let mut bytes = CountedBytes::default();
+    let report = jar::extract_jar(&jar, &options, &mut bytes).unwrap();
+    assert_eq!(bytes.total, 65);
+    assert_eq!((bytes.done, report.summary.bytes), (65, 65));
+
+    let mut bytes = CountedBytes::default();
+    let report =
+        hashed::extract_hashed_assets(&store.dir, &store.index(), &options, &mut bytes).unwrap();
+    assert_eq!(bytes.total, store.index().total_size());
+    assert_eq!(bytes.total, 41);
+    assert_eq!(bytes.done, bytes.total);
+    assert_eq!(report.summary.bytes, bytes.total);
+}
+
+/// A [`FrameTarget`] of a given size which records what is drawn on it.
+#[derive(Clone)]
+struct RecordedFrames {
+    size: Option<(usize, usize)>,
+    /// The frames drawn, with [`None`] for each time the target was cleared.
+    frames: Arc<Mutex<Vec<Option<Vec<String>>>>>,
+}
+
+impl RecordedFrames {
+    fn new(size: Option<(usize, usize)>) -> Self {
+        Self {
+            size,
+            frames: Arc::default(),
+        }
+    }
+
+    /// Returns the frames drawn, each followed by a separator line.
+    fn render(&self) -> String {
+        let frames = self.frames.lock().unwrap();
+
+        frames
+            .iter()
+            .map(|frame| match frame {
+                Some(lines) => lines.join("\n") + "\n---\n",
+                None => String::from("(cleared)\n"),
+            })
+            .collect()
+    }
+}
+
+impl FrameTarget for RecordedFrames {
+    fn size(&self) -> Option<(usize, usize)> {
+        self.size
+    }
+
+    fn draw(&mut self, lines: &[String]) -> io::Result<()> {
+        self.frames.lock().unwrap().push(Some(lines.to_vec()));
+        Ok(())
+    }
+
+    fn clear(&mut self) -> io::Result<()> {
+        self.frames.lock().unwrap().push(None);
+        Ok(())
+    }
+}
+
+/// Asserts that `actual` matches the snapshot called `name`, or rewrites the
+/// snapshot if `UPDATE_SNAPSHOTS` is set.
+fn assert_snapshot(name: &str, actual: &str) {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/snapshots")
+        .join(name)
+        .with_extension("txt");
+
+    if env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        fs::write(&path, actual).unwrap();
+    } else {
+        let expected = fs::read_to_string(&path).unwrap_or_default();
+
+        assert!(
+            expected == actual,
+            "{name} doesn't match its snapshot, rerun with UPDATE_SNAPSHOTS=1 if this is intended\n\
+             expected:\n{expected}\nactual:\n{actual}"
+        );
+    }
+}
+
+/// Sends the events of two workers extracting three files between them to a
+/// [`MultiProgress`] drawing onto `target`.
+fn run_workers(target: &RecordedFrames) {
+    let progress = MultiProgress::spawn(target.clone(), 3, 2, Duration::ZERO);
+    let sender = progress.sender();
+    let started = |worker, path: &str| WorkerEvent::Started {
+        worker,
+        path: PathBuf::from(path),
+    };
+
+    for event in [
+        started(0, "assets/minecraft/lang/en_us.json"),
+        started(
+            1,
+            "data/minecraft/worldgen/template_pool/village/plains/houses.json",
+        ),
+        WorkerEvent::Finished { worker: 0 },
+        started(0, "assets/minecraft/sounds/ambient/cave/cave1.ogg"),
+        WorkerEvent::Finished { worker: 1 },
+        WorkerEvent::Idle { worker: 1 },
+        WorkerEvent::Finished { worker: 0 },
+        WorkerEvent::Idle { worker: 0 },
+    ] {
+        sender.send(event).unwrap();
+    }
+
+    progress.finish().unwrap();
+}
+
+#[test]
+fn workers_are_drawn_on_lines_of_their_own() {
+    let target = RecordedFrames::new(Some((60, 24)));
+    run_workers(&target);
+
+    assert_snapshot("multi_progress", &target.render());
+}
+
+#[test]
+fn frames_collapse_to_a_single_line_when_workers_dont_fit() {
+    // Too short for the overall bar and both workers.
+    let short = RecordedFrames::new(Some((60, 3)));
+    run_workers(&short);
+    // Too narrow for the paths to be recognizable.
+    let narrow = RecordedFrames::new(Some((30, 24)));
+    run_workers(&narrow);
+    // Not a terminal.
+    let not_a_terminal = RecordedFrames::new(None);
+    run_workers(&not_a_terminal);
+
+    assert_snapshot(
+        "multi_progress_single_line",
+        &format!(
+            "{}\n{}\n{}",
+            short.render(),
+            narrow.render(),
+            not_a_terminal.render()
+        ),
+    );
+}
+
+#[test]
+fn frames_are_cleared_when_dropped_early() {
+    let target = RecordedFrames::new(Some((60, 24)));
+    let progress = MultiProgress::spawn(target.clone(), 10, 1, Duration::ZERO);
+
+    let mut sink = progress.sink(0);
+    sink.file_started(Path::new("assets/minecraft/lang/en_us.json"));
+    drop(sink);
+    drop(progress);
+
+    let frames = target.frames.lock().unwrap();
+    assert_eq!(frames.len(), 2);
+    assert_eq!(frames.last(), Some(&None));
+}
+
+/// A [`ProgressSink`] which displays the workers of an extraction with a
+/// [`MultiProgress`] drawing onto a [`RecordedFrames`].
+struct WorkerDisplay {
+    target: RecordedFrames,
+    total_files: usize,
+    display: Option<MultiProgress>,
+}
+
+impl ProgressSink for WorkerDisplay {
+    fn begin(&mut self, total_files: usize, _total_bytes: u64) {
+        self.total_files = total_files;
+    }
+
+    fn finish(&mut self, _summary: &Summary) {
+        if let Some(display) = self.display.take() {
+            display.finish().unwrap();
+        }
+    }
+
+    fn workers(&mut self, workers: usize) -> Option<Sender<WorkerEvent>> {
+        let display = MultiProgress::spawn(
+            self.target.clone(),
+            self.total_files,
+            workers,
+            Duration::ZERO,
+        );
+        let sender = display.sender().clone();
+        self.display = Some(display);
+
+        Some(sender)
+    }
+}
+
+#[test]
+fn parallel_extractions_report_each_worker() {
+    let dir = common::temp_dir();
+    let store = AssetStoreBuilder::typical().write(&dir.path().join("assets"));
+
+    let fs = Arc::new(MemoryFs::new());
+    fs.create_dir_all(Path::new("out/assets/minecraft/lang"))
+        .unwrap();
+    fs.write_file(
+        Path::new("out/assets/minecraft/lang/de_de.json"),
+        &mut "{}".as_bytes(),
+    )
+    .unwrap();
+    let options = ExtractOptions::new()
+        .output("out")
+        .vfs(fs)
+        .overwrite(Overwrite::Skip)
+        .threads(2);
+    let target = RecordedFrames::new(Some((60, 24)));
+    let mut display = WorkerDisplay {
+        target: target.clone(),
+        total_files: 0,
+        display: None,
+    };
+    let report =
+        hashed::extract_hashed_assets(&store.dir, &store.index(), &options, &mut display).unwrap();
+
+    assert_eq!(
+        (
+            report.summary.files,
+            report.summary.skipped,
+            report.summary.failures
+        ),
+        (1, 1, 1)
+    );
+
+    let frames = target.frames.lock().unwrap();
+    // Only the files given to a worker are drawn, and the skipped file is
+    // counted all the same.
+    let drawn: Vec<_> = frames.iter().flatten().flatten().collect();
+    assert!(drawn.iter().any(|line| line.ends_with("cave/cave1.ogg")));
+    assert!(
+        drawn
+            .iter()
+            .any(|line| line.ends_with("sounds/missing.ogg"))
+    );
+    assert!(!drawn.iter().any(|line| line.ends_with("de_de.json")));
+    assert_eq!(
+        frames[frames.len() - 2],
+        Some(vec![
+            format!("[{}] 3/3 files", "#".repeat(40)),
+            "  #1 idle".to_owned(),
+            "  #2 idle".to_owned(),
+        ])
+    );
+    assert_eq!(frames.last(), Some(&None));
+}
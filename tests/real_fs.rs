@@ -0,0 +1,120 @@
+//! Extraction to the real filesystem, for behaviour which [`MemoryFs`] can't
+//! cover.
+//!
+//! [`MemoryFs`]: extract_minecraft_assets::vfs::MemoryFs
+
+mod common;
+
+use common::{AssetStoreBuilder, JarBuilder};
+use extract_minecraft_assets::diff::OutputDiff;
+use extract_minecraft_assets::hashed;
+use extract_minecraft_assets::jar::{self, ExtractedContents};
+use extract_minecraft_assets::options::ExtractOptions;
+use extract_minecraft_assets::progress::NoProgress;
+use std::fs;
+use std::path::PathBuf;
+
+#[test]
+fn jar_creates_directories_and_files() {
+    let temp_dir = common::temp_dir();
+    let jar = JarBuilder::client().write(&temp_dir.path().join("client.jar"));
+    let dir = temp_dir.path().join("out");
+    let options = ExtractOptions::new().output(&dir);
+
+    let report = jar::extract_jar(&jar, &options, &mut NoProgress).unwrap();
+
+    assert_eq!(report.summary.files, 3);
+    assert_eq!(
+        fs::read_to_string(dir.join("assets/minecraft/lang/en_us.json")).unwrap(),
+        "{\"menu.quit\": \"Quit Game\"}\n"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn jar_preserves_unix_permissions() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_dir = common::temp_dir();
+    let jar = JarBuilder::client().write(&temp_dir.path().join("client.jar"));
+    let dir = temp_dir.path().join("out");
+    let options = ExtractOptions::new().output(&dir);
+
+    jar::extract_jar(&jar, &options, &mut NoProgress).unwrap();
+
+    let mode = |path: &str| fs::metadata(dir.join(path)).unwrap().permissions().mode() & 0o777;
+    assert_eq!(mode("assets/minecraft/texts/splashes.txt"), 0o600);
+    assert_eq!(mode("assets/minecraft/lang/en_us.json"), 0o644);
+    assert_eq!(mode("assets/minecraft"), 0o755);
+}
+
+#[test]
+fn hashed_assets_are_copied() {
+    let temp_dir = common::temp_dir();
+    let store = AssetStoreBuilder::typical().write(&temp_dir.path().join("assets"));
+    let dir = temp_dir.path().join("out");
+    let options = ExtractOptions::new().output(&dir);
+
+    let report =
+        hashed::extract_hashed_assets(&store.dir, &store.index(), &options, &mut NoProgress)
+            .unwrap();
+
+    assert_eq!((report.summary.files, report.summary.failures), (2, 1));
+    assert_eq!(
+        fs::read(dir.join("assets/minecraft/sounds/ambient/cave/cave1.ogg")).unwrap(),
+        b"OggS fake cave sound\n"
+    );
+}
+
+#[test]
+fn diff_output_finds_drift_from_jar() {
+    let temp_dir = common::temp_dir();
+    let jar = JarBuilder::client().write(&temp_dir.path().join("client.jar"));
+    let dir = temp_dir.path().join("out");
+    let options = ExtractOptions::new()
+        .output(&dir)
+        .contents(ExtractedContents::all());
+    let contents = ExtractedContents::all();
+
+    jar::extract_jar(&jar, &options, &mut NoProgress).unwrap();
+    assert!(
+        jar::diff_output(&jar, &dir, &contents, false)
+            .unwrap()
+            .is_empty()
+    );
+
+    fs::write(dir.join("assets/minecraft/lang/en_us.json"), "{}").unwrap();
+    fs::remove_file(dir.join("data/minecraft/shared.json")).unwrap();
+    fs::write(dir.join("assets/minecraft/extra.json"), "{}").unwrap();
+    fs::write(dir.join("outside.txt"), "not compared").unwrap();
+
+    assert_eq!(
+        jar::diff_output(&jar, &dir, &contents, false).unwrap(),
+        OutputDiff {
+            modified: vec![PathBuf::from("assets/minecraft/lang/en_us.json")],
+            missing: vec![PathBuf::from("data/minecraft/shared.json")],
+            extra: vec![PathBuf::from("assets/minecraft/extra.json")],
+        }
+    );
+}
+
+#[test]
+fn diff_output_finds_drift_from_index() {
+    let temp_dir = common::temp_dir();
+    let store = AssetStoreBuilder::typical().write(&temp_dir.path().join("assets"));
+    let dir = temp_dir.path().join("out");
+    let options = ExtractOptions::new().output(&dir).ignore_top_level(true);
+    let index = store.index();
+
+    hashed::extract_hashed_assets(&store.dir, &index, &options, &mut NoProgress).unwrap();
+    fs::write(dir.join("minecraft/lang/de_de.json"), "{}").unwrap();
+
+    assert_eq!(
+        index.diff_output(&dir, true).unwrap(),
+        OutputDiff {
+            modified: vec![PathBuf::from("minecraft/lang/de_de.json")],
+            missing: vec![PathBuf::from("minecraft/sounds/missing.ogg")],
+            extra: Vec::new(),
+        }
+    );
+}
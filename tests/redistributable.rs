@@ -0,0 +1,110 @@
+mod common;
+
+use common::{AssetStoreBuilder, JarBuilder};
+use extract_minecraft_assets::filter::{Pattern, PatternSet};
+use extract_minecraft_assets::options::ExtractOptions;
+use extract_minecraft_assets::progress::NoProgress;
+use extract_minecraft_assets::redistributable::{EXCLUSIONS, Redistributable};
+use extract_minecraft_assets::vfs::MemoryFs;
+use extract_minecraft_assets::{hashed, jar};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+#[test]
+fn exclusions_are_valid_globs() {
+    for exclusion in EXCLUSIONS {
+        assert!(Pattern::glob(exclusion.glob).is_ok(), "{}", exclusion.glob);
+    }
+}
+
+#[test]
+fn artwork_and_recordings_are_left_out() {
+    let redistributable = Redistributable::new();
+    let name = |path: &str| {
+        redistributable
+            .exclusion(Path::new(path))
+            .map(|exclusion| exclusion.name)
+    };
+
+    assert_eq!(
+        name("assets/minecraft/textures/block/stone.png"),
+        Some("textures")
+    );
+    assert_eq!(
+        name("assets/minecraft/textures/block/fire_0.png.mcmeta"),
+        Some("textures")
+    );
+    assert_eq!(
+        name("assets/minecraft/sounds/ambient/cave/cave1.ogg"),
+        Some("sound files")
+    );
+    assert_eq!(
+        name("assets/minecraft/font/unifont.zip"),
+        Some("font glyph bitmaps")
+    );
+    assert!(name("pack.png").is_some());
+    assert!(name("assets/minecraft/music/game/calm1.ogg").is_some());
+
+    for path in [
+        "assets/minecraft/models/block/stone.json",
+        "assets/minecraft/blockstates/stone.json",
+        "assets/minecraft/lang/en_us.json",
+        "assets/minecraft/font/default.json",
+        "assets/minecraft/sounds.json",
+        "data/minecraft/recipes/stone.json",
+    ] {
+        assert_eq!(name(path), None, "{path}");
+    }
+}
+
+#[test]
+fn included_files_are_kept() {
+    let redistributable = Redistributable::new().include(
+        PatternSet::new().with(Pattern::glob("assets/minecraft/textures/gui/**").unwrap()),
+    );
+
+    assert!(redistributable.allows(Path::new("assets/minecraft/textures/gui/widgets.png")));
+    assert!(!redistributable.allows(Path::new("assets/minecraft/textures/block/stone.png")));
+}
+
+#[test]
+fn only_redistributable_files_are_extracted() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client()
+        .file("pack.png", "PNG pack")
+        .file("assets/minecraft/models/block/stone.json", "{}")
+        .file("assets/minecraft/textures/block/stone.png", "PNG stone")
+        .write(&dir.path().join("client.jar"));
+    let store = AssetStoreBuilder::new()
+        .file("minecraft/sounds.json", "{}")
+        .file("minecraft/sounds/ambient/cave/cave1.ogg", "OGG cave")
+        .file("minecraft/lang/de_de.json", "{}")
+        .write(&dir.path().join("assets"));
+
+    let redistributable = Redistributable::new();
+    let index = store
+        .index()
+        .filtered(|path| redistributable.allows(&Path::new("assets").join(path)));
+
+    let fs = Arc::new(MemoryFs::new());
+    let options = ExtractOptions::new().output("out").vfs(fs.clone());
+    let report = jar::extract_jar_filtered(&jar, &options, &mut NoProgress, |path| {
+        redistributable.allows(path)
+    })
+    .unwrap()
+        + hashed::extract_hashed_assets(&store.dir, &index, &options, &mut NoProgress).unwrap();
+
+    let files: Vec<PathBuf> = fs.files();
+    assert!(files.contains(&PathBuf::from(
+        "out/assets/minecraft/models/block/stone.json"
+    )));
+    assert!(files.contains(&PathBuf::from("out/assets/minecraft/sounds.json")));
+    assert!(files.contains(&PathBuf::from("out/assets/minecraft/lang/de_de.json")));
+    assert!(
+        files
+            .iter()
+            .all(|path| redistributable.allows(path.strip_prefix("out").unwrap())),
+        "{files:?}"
+    );
+    assert_eq!(report.summary.failures, 0);
+}
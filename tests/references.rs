@@ -0,0 +1,114 @@
+mod common;
+
+use common::JarBuilder;
+use extract_minecraft_assets::closure::AssetSources;
+use extract_minecraft_assets::references::{self, ReferencingFile};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Writes the `files` into `assets_dir`, at paths within `assets`.
+fn write_pack(assets_dir: &Path, files: &[(&str, &str)]) {
+    for (path, contents) in files {
+        let path = assets_dir.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+}
+
+/// A pack overriding vanilla's stone, with a model of its own.
+fn pack(assets_dir: &Path) {
+    write_pack(
+        assets_dir,
+        &[
+            (
+                "minecraft/blockstates/stone.json",
+                r#"{"variants": {"": [{"model": "block/stone"}, {"model": "mypack:block/mossy"}]}}"#,
+            ),
+            (
+                "minecraft/models/block/stone.json",
+                r#"{"parent": "block/cube_all", "textures": {"all": "block/stone"}}"#,
+            ),
+            (
+                "mypack/models/block/mossy.json",
+                r#"{"parent": "minecraft:block/cube_all", "textures": {"all": "mypack:block/moss"}}"#,
+            ),
+            ("minecraft/textures/block/stone.png", "PNG stone"),
+            (
+                "mypack/font/runes.json",
+                r#"{"providers": [{"type": "bitmap", "file": "mypack:font/runes.png", "chars": []}]}"#,
+            ),
+            ("mypack/textures/font/runes.png", "PNG runes"),
+            (
+                "mypack/sounds.json",
+                r#"{"rune.hum": {"sounds": ["mypack:rune/hum", "mypack:rune/gone"]}}"#,
+            ),
+            ("mypack/sounds/rune/hum.ogg", "OggS hum"),
+            ("minecraft/lang/en_us.json", "{not json, but not checked"),
+            ("minecraft/models/item/broken.json", "{\"parent\": "),
+        ],
+    );
+}
+
+#[test]
+fn references_to_missing_files_are_grouped_by_file() {
+    let dir = common::temp_dir();
+    pack(dir.path());
+
+    let check = references::check(dir.path(), None).unwrap();
+
+    assert_eq!(check.checked, 6);
+    assert_eq!(
+        check.files,
+        [
+            ReferencingFile {
+                path: PathBuf::from("minecraft/models/block/stone.json"),
+                dangling: vec![PathBuf::from("minecraft/models/block/cube_all.json")],
+            },
+            ReferencingFile {
+                path: PathBuf::from("mypack/models/block/mossy.json"),
+                dangling: vec![
+                    PathBuf::from("minecraft/models/block/cube_all.json"),
+                    PathBuf::from("mypack/textures/block/moss.png"),
+                ],
+            },
+            ReferencingFile {
+                path: PathBuf::from("mypack/sounds.json"),
+                dangling: vec![PathBuf::from("mypack/sounds/rune/gone.ogg")],
+            },
+        ]
+    );
+    assert_eq!(check.dangling(), 4);
+    assert_eq!(
+        check
+            .errors
+            .iter()
+            .map(|error| &error.path)
+            .collect::<Vec<_>>(),
+        [Path::new("minecraft/models/item/broken.json")]
+    );
+}
+
+#[test]
+fn vanilla_files_are_found_in_the_base() {
+    let dir = common::temp_dir();
+    let assets_dir = dir.path().join("pack/assets");
+    pack(&assets_dir);
+    let jar = JarBuilder::client()
+        .file(
+            "assets/minecraft/models/block/cube_all.json",
+            r#"{"parent": "block/cube"}"#,
+        )
+        .write(&dir.path().join("client.jar"));
+    let base = AssetSources::new().jar(&jar).unwrap();
+
+    let check = references::check(&assets_dir, Some(&base)).unwrap();
+
+    let dangling: Vec<_> = check.files.iter().flat_map(|file| &file.dangling).collect();
+    assert_eq!(
+        dangling,
+        [
+            Path::new("mypack/textures/block/moss.png"),
+            Path::new("mypack/sounds/rune/gone.ogg"),
+        ]
+    );
+}
@@ -0,0 +1,201 @@
+#![cfg(feature = "remote")]
+
+mod common;
+
+use common::JarBuilder;
+use extract_minecraft_assets::jar::{self, ExtractedContents};
+use extract_minecraft_assets::options::ExtractOptions;
+use extract_minecraft_assets::progress::NoProgress;
+use extract_minecraft_assets::remote::RangeReader;
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::net::TcpListener;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+/// A local HTTP server serving `contents`, with range requests if `ranges` is
+/// set.
+struct Server {
+    url: String,
+    requests: Arc<AtomicUsize>,
+}
+
+impl Server {
+    fn start(contents: Vec<u8>, ranges: bool) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/client.jar", listener.local_addr().unwrap());
+        let requests = Arc::new(AtomicUsize::new(0));
+
+        let counter = requests.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                counter.fetch_add(1, Ordering::SeqCst);
+
+                let mut range = None;
+                let mut reader = BufReader::new(&stream);
+                let mut line = String::new();
+                while reader.read_line(&mut line).unwrap() > 2 {
+                    if let Some(value) = line.trim_end().strip_prefix("Range: bytes=") {
+                        range = Some(value.to_owned());
+                    }
+                    line.clear();
+                }
+
+                let len = contents.len();
+                let (status, headers, body) = match range.filter(|_| ranges) {
+                    Some(range) => {
+                        let (start, end) = range.split_once('-').unwrap();
+                        let (start, end) = if start.is_empty() {
+                            (len.saturating_sub(end.parse().unwrap()), len - 1)
+                        } else {
+                            let end = end.parse().unwrap_or(len - 1).min(len - 1);
+                            (start.parse().unwrap(), end)
+                        };
+                        (
+                            "206 Partial Content",
+                            format!("Content-Range: bytes {start}-{end}/{len}\r\n"),
+                            &contents[start..=end],
+                        )
+                    }
+                    None => ("200 OK", String::new(), &contents[..]),
+                };
+
+                write!(
+                    stream,
+                    "HTTP/1.1 {status}\r\n{headers}Content-Length: {}\r\nConnection: close\r\n\r\n",
+                    body.len()
+                )
+                .unwrap();
+                stream.write_all(body).unwrap();
+            }
+        });
+
+        Self { url, requests }
+    }
+
+    fn requests(&self) -> usize {
+        self.requests.load(Ordering::SeqCst)
+    }
+}
+
+/// Returns `len` bytes which don't compress.
+fn noise(len: usize) -> Vec<u8> {
+    let mut state = 0x2545_f491_u32;
+
+    (0..len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state as u8
+        })
+        .collect()
+}
+
+/// A jar file whose few small files are outweighed by large ones.
+fn jar() -> Vec<u8> {
+    JarBuilder::client()
+        .file("assets/minecraft/textures/block/stone.png", noise(1 << 20))
+        .file("net/minecraft/client/Blocks.class", noise(1 << 20))
+        .build()
+}
+
+fn extract_lang(jar_file: &Path, reader: impl Read + Seek, output: &Path) {
+    let options = ExtractOptions::new()
+        .output(output)
+        .contents(ExtractedContents::assets());
+
+    jar::extract_jar_from(jar_file, reader, &options, &mut NoProgress, |path| {
+        path.starts_with("assets/minecraft/lang")
+    })
+    .unwrap();
+}
+
+#[test]
+fn reads_like_the_file() {
+    let jar = jar();
+    let server = Server::start(jar.clone(), true);
+
+    let mut reader = RangeReader::open(&server.url).unwrap();
+    assert_eq!(reader.len(), jar.len() as u64);
+
+    for position in [0, 70_000, 1 << 20, jar.len() as u64 - 10] {
+        let mut read = [0; 100];
+        reader.seek(SeekFrom::Start(position)).unwrap();
+        let len = reader.read(&mut read).unwrap();
+
+        let position = position as usize;
+        assert!(len > 0);
+        assert_eq!(read[..len], jar[position..position + len]);
+    }
+
+    let mut all = Vec::new();
+    reader.rewind().unwrap();
+    reader.read_to_end(&mut all).unwrap();
+    assert_eq!(all, jar);
+    // Only the partial block at the start of the end fetched first is
+    // fetched again.
+    let bytes = reader.stats().bytes;
+    assert!((jar.len() as u64..jar.len() as u64 + 64 * 1024).contains(&bytes));
+    assert_eq!(reader.stats().requests, server.requests());
+}
+
+#[test]
+fn only_the_extracted_entries_are_fetched() {
+    let dir = common::temp_dir();
+    let jar = jar();
+    let jar_file = dir.path().join("client.jar");
+    fs::write(&jar_file, &jar).unwrap();
+    let server = Server::start(jar.clone(), true);
+
+    let mut reader = RangeReader::open(&server.url).unwrap();
+    extract_lang(
+        Path::new(&server.url),
+        &mut reader,
+        &dir.path().join("remote"),
+    );
+    extract_lang(
+        &jar_file,
+        fs::File::open(&jar_file).unwrap(),
+        &dir.path().join("local"),
+    );
+
+    let lang = "assets/minecraft/lang/en_us.json";
+    assert_eq!(
+        fs::read(dir.path().join("remote").join(lang)).unwrap(),
+        fs::read(dir.path().join("local").join(lang)).unwrap()
+    );
+    assert!(
+        !dir.path()
+            .join("remote/assets/minecraft/texts/splashes.txt")
+            .exists()
+    );
+
+    let stats = reader.stats();
+    assert!(stats.ranges);
+    assert!(stats.bytes < jar.len() as u64 / 4, "{stats:?}");
+    assert_eq!(stats.requests, server.requests());
+}
+
+#[test]
+fn the_whole_file_is_downloaded_without_ranges() {
+    let dir = common::temp_dir();
+    let jar = jar();
+    let server = Server::start(jar.clone(), false);
+
+    let mut reader = RangeReader::open(&server.url).unwrap();
+    extract_lang(Path::new(&server.url), &mut reader, dir.path());
+
+    assert_eq!(
+        fs::read_to_string(dir.path().join("assets/minecraft/lang/en_us.json")).unwrap(),
+        "{\"menu.quit\": \"Quit Game\"}\n"
+    );
+
+    let stats = reader.stats();
+    assert!(!stats.ranges);
+    assert_eq!(stats.requests, 1);
+    assert_eq!(stats.bytes, jar.len() as u64);
+}
@@ -0,0 +1,88 @@
+mod common;
+
+use common::{AssetStoreBuilder, JarBuilder};
+use extract_minecraft_assets::filter::{Pattern, PatternSet};
+use extract_minecraft_assets::jar::{self, ExtractedContents};
+use extract_minecraft_assets::options::ExtractOptions;
+use extract_minecraft_assets::progress::NoProgress;
+use extract_minecraft_assets::search::{self, FileSource};
+use std::fs;
+use std::path::PathBuf;
+
+fn paths(hits: &[search::Hit]) -> Vec<PathBuf> {
+    hits.iter().map(|hit| hit.path.clone()).collect()
+}
+
+#[test]
+fn search_matches_jar_paths_within_the_selected_contents() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client().write(&dir.path().join("client.jar"));
+    let patterns = PatternSet::new()
+        .with(Pattern::glob("*.json").unwrap())
+        .with(Pattern::regex(r"\.mcmeta$").unwrap());
+
+    let all = search::search_jar(&jar, &ExtractedContents::all(), &patterns).unwrap();
+    let data = search::search_jar(&jar, &ExtractedContents::data(), &patterns).unwrap();
+
+    assert!(all.iter().all(|hit| hit.source == FileSource::Jar));
+    assert!(paths(&all).contains(&PathBuf::from("assets/minecraft/lang/en_us.json")));
+    assert_eq!(
+        paths(&data),
+        [
+            "data/minecraft/shared.json",
+            "data/minecraft/tags/blocks/logs.json",
+        ]
+        .map(PathBuf::from)
+    );
+}
+
+#[test]
+fn search_matches_index_paths_within_assets() {
+    let dir = common::temp_dir();
+    let store = AssetStoreBuilder::typical().write(&dir.path().join("assets"));
+    let index = store.index();
+
+    let sounds = search::search_index(
+        &index,
+        &PatternSet::new().with(Pattern::glob("assets/*/sounds/**").unwrap()),
+    );
+    let none = search::search_index(
+        &index,
+        &PatternSet::new().with(Pattern::glob("sounds/**").unwrap()),
+    );
+
+    assert_eq!(
+        paths(&sounds),
+        [
+            "assets/minecraft/sounds/ambient/cave/cave1.ogg",
+            "assets/minecraft/sounds/missing.ogg",
+        ]
+        .map(PathBuf::from)
+    );
+    assert!(sounds.iter().all(|hit| hit.object_path.is_some()));
+    assert!(none.is_empty());
+}
+
+#[test]
+fn search_matches_the_same_files_as_a_filtered_extraction() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client().write(&dir.path().join("client.jar"));
+    let patterns = PatternSet::new().with(Pattern::glob("*.json").unwrap());
+    let output_dir = dir.path().join("out");
+    fs::create_dir(&output_dir).unwrap();
+    let options = ExtractOptions::new()
+        .output(&output_dir)
+        .contents(ExtractedContents::all());
+
+    let hits = search::search_jar(&jar, &ExtractedContents::all(), &patterns).unwrap();
+    let report = jar::extract_jar_filtered(&jar, &options, &mut NoProgress, |path| {
+        patterns.matches(path)
+    })
+    .unwrap();
+
+    let mut extracted: Vec<_> = report.files.into_iter().map(|file| file.path).collect();
+    extracted.sort();
+
+    assert!(!hits.is_empty());
+    assert_eq!(paths(&hits), extracted);
+}
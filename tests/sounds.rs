@@ -0,0 +1,188 @@
+mod common;
+
+use common::{AssetStore, AssetStoreBuilder};
+use extract_minecraft_assets::hashed;
+use extract_minecraft_assets::options::ExtractOptions;
+use extract_minecraft_assets::progress::NoProgress;
+use extract_minecraft_assets::report::ExtractionReport;
+use extract_minecraft_assets::sounds::{BrokenSound, SoundEvents, SoundLinks};
+use extract_minecraft_assets::vfs::MemoryFs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+const SOUNDS_JSON: &str = r#"{
+    "entity.zombie.ambient": {
+        "sounds": ["mob/zombie/say1", {"name": "mob/zombie/say2", "weight": 2}]
+    },
+    "entity.zombie_villager.ambient": {
+        "sounds": [
+            {"name": "entity.zombie.ambient", "type": "event"},
+            "mob/zombie_villager/say1"
+        ]
+    },
+    "ambient.cave": {
+        "sounds": ["ambient/cave/cave1", "missing"]
+    }
+}"#;
+
+/// Extracts a store with `sounds.json` into `out` within a [`MemoryFs`].
+fn extract(dir: &Path) -> (AssetStore, Arc<MemoryFs>, ExtractOptions, ExtractionReport) {
+    let store = AssetStoreBuilder::typical()
+        .file("minecraft/sounds.json", SOUNDS_JSON)
+        .file("minecraft/sounds/mob/zombie/say1.ogg", "OggS say1\n")
+        .file("minecraft/sounds/mob/zombie/say2.ogg", "OggS say2\n")
+        .file(
+            "minecraft/sounds/mob/zombie_villager/say1.ogg",
+            "OggS villager\n",
+        )
+        .file("minecraft/sounds/unused.ogg", "OggS unused\n")
+        .write(&dir.join("assets"));
+
+    let fs = Arc::new(MemoryFs::new());
+    let options = ExtractOptions::new().output("out").vfs(fs.clone());
+    let report =
+        hashed::extract_hashed_assets(&store.dir, &store.index(), &options, &mut NoProgress)
+            .unwrap();
+
+    (store, fs, options, report)
+}
+
+fn paths(paths: &[&str]) -> Vec<PathBuf> {
+    paths.iter().map(PathBuf::from).collect()
+}
+
+#[test]
+fn sound_files_are_organized_by_event() {
+    let dir = common::temp_dir();
+    let (store, fs, options, report) = extract(dir.path());
+
+    let sound_events = SoundEvents::from_index(&store.dir, &store.index())
+        .unwrap()
+        .unwrap();
+    let organized = sound_events
+        .organize(&report, &options, SoundLinks::HardLink, true)
+        .unwrap();
+
+    assert_eq!(organized.events, 3);
+    assert_eq!(organized.files, 6);
+    assert_eq!(organized.missing, paths(&["minecraft/sounds/missing.ogg"]));
+    assert_eq!(
+        organized.unreferenced,
+        paths(&["assets/minecraft/sounds/unused.ogg"])
+    );
+
+    let read = |path: &str| fs.read(Path::new(path));
+    assert_eq!(
+        read("out/sounds-by-event/entity.zombie.ambient/2.ogg"),
+        Some(b"OggS say2\n".to_vec())
+    );
+    // Referencing another event plays its sounds, followed by the event's own.
+    assert_eq!(
+        read("out/sounds-by-event/entity.zombie_villager.ambient/2.ogg"),
+        Some(b"OggS say2\n".to_vec())
+    );
+    assert_eq!(
+        read("out/sounds-by-event/entity.zombie_villager.ambient/3.ogg"),
+        Some(b"OggS villager\n".to_vec())
+    );
+    assert_eq!(
+        read("out/sounds-by-event/ambient.cave/1.ogg"),
+        Some(b"OggS fake cave sound\n".to_vec())
+    );
+    assert!(read("out/sounds-by-event/ambient.cave/2.ogg").is_none());
+    assert!(read("out/assets/minecraft/sounds/mob/zombie/say1.ogg").is_some());
+}
+
+#[test]
+fn only_organized_sound_files_are_kept() {
+    let dir = common::temp_dir();
+    let (store, fs, options, report) = extract(dir.path());
+
+    let sound_events = SoundEvents::from_index(&store.dir, &store.index())
+        .unwrap()
+        .unwrap();
+    sound_events
+        .organize(&report, &options, SoundLinks::Copy, false)
+        .unwrap();
+
+    let sounds: Vec<_> = fs
+        .files()
+        .into_iter()
+        .filter(|path| path.extension().is_some_and(|extension| extension == "ogg"))
+        .collect();
+    assert_eq!(
+        sounds,
+        paths(&[
+            "out/assets/minecraft/sounds/unused.ogg",
+            "out/sounds-by-event/ambient.cave/1.ogg",
+            "out/sounds-by-event/entity.zombie.ambient/1.ogg",
+            "out/sounds-by-event/entity.zombie.ambient/2.ogg",
+            "out/sounds-by-event/entity.zombie_villager.ambient/1.ogg",
+            "out/sounds-by-event/entity.zombie_villager.ambient/2.ogg",
+            "out/sounds-by-event/entity.zombie_villager.ambient/3.ogg",
+        ])
+    );
+}
+
+#[test]
+fn sounds_missing_from_the_index_are_found() {
+    let dir = common::temp_dir();
+    let store = AssetStoreBuilder::new()
+        .file("minecraft/sounds.json", SOUNDS_JSON)
+        .file("minecraft/sounds/ambient/cave/cave1.ogg", "OggS cave\n")
+        .file("minecraft/sounds/mob/zombie/say1.ogg", "OggS say1\n")
+        .missing("minecraft/sounds/mob/zombie/say2.ogg", "OggS say2\n")
+        .file(
+            "minecraft/sounds/mob/zombie_villager/say1.ogg",
+            "OggS villager\n",
+        )
+        .write(&dir.path().join("assets"));
+    let index = store.index();
+    let sound_events = SoundEvents::from_index(&store.dir, &index)
+        .unwrap()
+        .unwrap();
+
+    let check = sound_events.check_index(&store.dir, &index);
+
+    assert_eq!((check.events, check.files), (3, 5));
+    assert_eq!(
+        check.broken["ambient.cave"],
+        [BrokenSound::NotInIndex {
+            path: PathBuf::from("minecraft/sounds/missing.ogg")
+        }]
+    );
+    assert_eq!(
+        check.broken["entity.zombie.ambient"],
+        [BrokenSound::MissingObject {
+            path: PathBuf::from("minecraft/sounds/mob/zombie/say2.ogg"),
+            hash: index
+                .get("minecraft/sounds/mob/zombie/say2.ogg")
+                .unwrap()
+                .hash
+                .to_owned(),
+        }]
+    );
+    // The event played by another isn't reported again for it.
+    assert!(!check.broken.contains_key("entity.zombie_villager.ambient"));
+}
+
+#[test]
+fn sounds_playing_undefined_events_are_found() {
+    let sound_events = SoundEvents::from_slice(
+        br#"{
+            "block.note_block.harp": {"sounds": [{"name": "minecraft:block.note_block.bass", "type": "event"}]},
+            "block.note_block.bass": {"sounds": [{"name": "block.note_block.guitar", "type": "event"}]}
+        }"#,
+    )
+    .unwrap();
+
+    let check = sound_events.check_index(Path::new("assets"), &Default::default());
+
+    assert_eq!(check.broken.len(), 1);
+    assert_eq!(
+        check.broken["block.note_block.bass"],
+        [BrokenSound::UnknownEvent {
+            event: "block.note_block.guitar".to_owned()
+        }]
+    );
+}
@@ -0,0 +1,117 @@
+mod common;
+
+use common::{AssetStoreBuilder, JarBuilder};
+use extract_minecraft_assets::category::{self, Category};
+use extract_minecraft_assets::jar::ExtractedContents;
+use extract_minecraft_assets::stats::{Stats, Total};
+use std::path::Path;
+
+#[test]
+fn categories_follow_the_directory_within_the_namespace() {
+    let cases = [
+        ("minecraft/textures/block/stone.png", Category::Textures),
+        ("minecraft/models/block/stone.json", Category::Models),
+        ("minecraft/blockstates/stone.json", Category::Blockstates),
+        ("minecraft/sounds/ambient/cave/cave1.ogg", Category::Sounds),
+        ("minecraft/sounds.json", Category::Sounds),
+        ("realms/lang/de_de.json", Category::Lang),
+        ("minecraft/font/default.json", Category::Fonts),
+        (
+            "minecraft/shaders/core/rendertype_solid.vsh",
+            Category::Shaders,
+        ),
+        ("minecraft/particles/flame.json", Category::Particles),
+        ("minecraft/atlases/blocks.json", Category::Atlases),
+        ("minecraft/texts/splashes.txt", Category::Texts),
+        ("minecraft/worldgen/biome/plains.json", Category::Worldgen),
+        ("minecraft/recipes/stick.json", Category::Recipes),
+        ("minecraft/recipe/stick.json", Category::Recipes),
+        (
+            "minecraft/loot_tables/chests/igloo_chest.json",
+            Category::LootTables,
+        ),
+        (
+            "minecraft/loot_table/chests/igloo_chest.json",
+            Category::LootTables,
+        ),
+        (
+            "minecraft/advancement/story/root.json",
+            Category::Advancements,
+        ),
+        ("minecraft/tags/blocks/logs.json", Category::Tags),
+        ("minecraft/structure/igloo/top.nbt", Category::Structures),
+        ("minecraft/icons/icon_16x16.png", Category::Other),
+        ("textures/stone.png", Category::Other),
+        ("pack.mcmeta", Category::Other),
+    ];
+
+    for (path, category) in cases {
+        assert_eq!(Category::of(Path::new(path)), category, "{path}");
+    }
+}
+
+#[test]
+fn every_category_has_a_distinct_name() {
+    let mut names: Vec<_> = Category::ALL
+        .iter()
+        .map(|category| category.name())
+        .collect();
+    names.sort_unstable();
+    names.dedup();
+
+    assert_eq!(names.len(), Category::ALL.len());
+    assert_eq!(Category::LootTables.to_string(), "loot_tables");
+}
+
+#[test]
+fn namespaces_need_a_file_within_them() {
+    assert_eq!(
+        category::namespace(Path::new("minecraft/lang/en_us.json")),
+        Some("minecraft")
+    );
+    assert_eq!(
+        category::namespace(Path::new("minecraft/sounds.json")),
+        Some("minecraft")
+    );
+    assert_eq!(category::namespace(Path::new("pack.mcmeta")), None);
+}
+
+#[test]
+fn jar_stats_are_relative_to_each_root() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client().write(&dir.path().join("client.jar"));
+    let stats = Stats::of_jar(&jar, &ExtractedContents::all()).unwrap();
+
+    assert_eq!(stats.total.files, 5);
+    assert_eq!(stats.namespaces.keys().collect::<Vec<_>>(), ["minecraft"]);
+    assert_eq!(
+        stats.categories.keys().collect::<Vec<_>>(),
+        [
+            &Category::Lang,
+            &Category::Texts,
+            &Category::Tags,
+            &Category::Other
+        ]
+    );
+    assert_eq!(stats.categories[&Category::Other].files, 2);
+}
+
+#[test]
+fn version_stats_combine_jar_and_index() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client().write(&dir.path().join("client.jar"));
+    let store = AssetStoreBuilder::typical().write(&dir.path().join("assets"));
+
+    let stats = Stats::of_jar(&jar, &ExtractedContents::assets()).unwrap()
+        + Stats::of_index(&store.index());
+
+    assert_eq!(stats.total.files, 6);
+    assert_eq!(stats.categories[&Category::Lang].files, 2);
+    assert_eq!(
+        stats.categories[&Category::Sounds],
+        Total {
+            files: 2,
+            bytes: 21 + 7
+        }
+    );
+}
@@ -0,0 +1,142 @@
+mod common;
+
+use common::{AssetStoreBuilder, JarBuilder};
+use extract_minecraft_assets::error::ExtractError;
+use extract_minecraft_assets::jar::ExtractedContents;
+use extract_minecraft_assets::options::ExtractOptions;
+use extract_minecraft_assets::progress::NoProgress;
+use extract_minecraft_assets::vfs::{MemoryFs, Vfs};
+use extract_minecraft_assets::{hashed, jar};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+#[test]
+fn namespace_is_stripped() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client().write(&dir.path().join("client.jar"));
+    let store = AssetStoreBuilder::typical().write(&dir.path().join("assets"));
+
+    let fs = Arc::new(MemoryFs::new());
+    let options = ExtractOptions::new()
+        .output("out")
+        .vfs(fs.clone())
+        .strip_namespace(true);
+    let report = jar::extract_jar(&jar, &options, &mut NoProgress).unwrap()
+        + hashed::extract_hashed_assets(&store.dir, &store.index(), &options, &mut NoProgress)
+            .unwrap();
+
+    assert_eq!(
+        fs.read(Path::new("out/assets/lang/en_us.json")).unwrap(),
+        b"{\"menu.quit\": \"Quit Game\"}\n"
+    );
+    assert!(fs.read(Path::new("out/assets/lang/de_de.json")).is_some());
+    assert!(fs.read(Path::new("out/assets/shared.json")).is_some());
+    // Not even the jar's `assets/minecraft/` directory entry is created.
+    assert!(!fs.exists(Path::new("out/assets/minecraft")));
+    assert!(
+        report
+            .files
+            .iter()
+            .all(|file| !file.path.starts_with("assets/minecraft"))
+    );
+}
+
+#[test]
+fn namespace_is_stripped_with_ignore_top_level() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client().write(&dir.path().join("client.jar"));
+
+    let fs = Arc::new(MemoryFs::new());
+    let options = ExtractOptions::new()
+        .output("out")
+        .vfs(fs.clone())
+        .ignore_top_level(true)
+        .strip_namespace(true);
+    jar::extract_jar(&jar, &options, &mut NoProgress).unwrap();
+
+    assert!(fs.read(Path::new("out/lang/en_us.json")).is_some());
+    assert!(fs.read(Path::new("out/texts/splashes.txt")).is_some());
+}
+
+#[test]
+fn multiple_namespaces_are_rejected_before_writing() {
+    let dir = common::temp_dir();
+    let store = AssetStoreBuilder::typical()
+        .file("realms/lang/de_de.json", "{}")
+        .write(&dir.path().join("assets"));
+
+    let fs = Arc::new(MemoryFs::new());
+    let options = ExtractOptions::new()
+        .output("out")
+        .vfs(fs.clone())
+        .strip_namespace(true);
+    let error =
+        hashed::extract_hashed_assets(&store.dir, &store.index(), &options, &mut NoProgress)
+            .unwrap_err();
+
+    let ExtractError::MultipleNamespaces { namespaces } = error else {
+        panic!("unexpected error: {error}");
+    };
+    assert_eq!(namespaces, ["minecraft", "realms"]);
+    assert!(fs.files().is_empty());
+}
+
+#[test]
+fn collisions_are_rejected_before_writing() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client().write(&dir.path().join("client.jar"));
+
+    let fs = Arc::new(MemoryFs::new());
+    let options = ExtractOptions::new()
+        .output("out")
+        .vfs(fs.clone())
+        .contents(ExtractedContents::all())
+        .ignore_top_level(true)
+        .strip_namespace(true);
+    let error = jar::extract_jar(&jar, &options, &mut NoProgress).unwrap_err();
+
+    let ExtractError::OutputCollisions { collisions } = error else {
+        panic!("unexpected error: {error}");
+    };
+    assert_eq!(
+        collisions,
+        [(
+            PathBuf::from("shared.json"),
+            vec![
+                PathBuf::from("assets/minecraft/shared.json"),
+                PathBuf::from("data/minecraft/shared.json"),
+            ]
+        )]
+    );
+    assert!(fs.files().is_empty());
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn namespace_is_stripped_by_async_extraction() {
+    use extract_minecraft_assets::asynchronous;
+
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client().write(&dir.path().join("client.jar"));
+
+    let fs = Arc::new(MemoryFs::new());
+    let options = ExtractOptions::new()
+        .output("out")
+        .vfs(fs.clone())
+        .contents(ExtractedContents::all())
+        .strip_namespace(true);
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    let report = runtime
+        .block_on(asynchronous::extract_jar_async(&jar, &options, None))
+        .unwrap();
+
+    assert_eq!(report.summary.failures, 0);
+    assert!(fs.read(Path::new("out/assets/lang/en_us.json")).is_some());
+    assert!(
+        fs.read(Path::new("out/data/tags/blocks/logs.json"))
+            .is_some()
+    );
+    assert!(fs.read(Path::new("out/data/shared.json")).is_some());
+}
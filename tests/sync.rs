@@ -0,0 +1,107 @@
+mod common;
+
+use common::{AssetStoreBuilder, JarBuilder};
+use extract_minecraft_assets::jar::ExtractedContents;
+use extract_minecraft_assets::options::ExtractOptions;
+use extract_minecraft_assets::progress::NoProgress;
+use extract_minecraft_assets::search::FileSource;
+use extract_minecraft_assets::{hashed, jar, sync};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[test]
+fn files_removed_from_the_source_are_deleted() {
+    let dir = common::temp_dir();
+    let output_dir = dir.path().join("out");
+    let options = ExtractOptions::new()
+        .output(&output_dir)
+        .contents(ExtractedContents::all());
+
+    let old_jar = JarBuilder::client()
+        .file("assets/minecraft/textures/old/gone.png", "png")
+        .write(&dir.path().join("old.jar"));
+    let report = jar::extract_jar(&old_jar, &options, &mut NoProgress).unwrap();
+    let plan = sync::plan(&report, &[FileSource::Jar]).unwrap();
+    assert!(plan.stale.is_empty() && plan.unknown.is_empty());
+    plan.apply(&output_dir, false).unwrap();
+
+    let new_jar = JarBuilder::client().write(&dir.path().join("new.jar"));
+    let report = jar::extract_jar(&new_jar, &options, &mut NoProgress).unwrap();
+    let plan = sync::plan(&report, &[FileSource::Jar]).unwrap();
+    assert_eq!(
+        plan.stale,
+        [PathBuf::from("assets/minecraft/textures/old/gone.png")]
+    );
+    assert_eq!(plan.apply(&output_dir, false).unwrap(), 1);
+
+    // The directories left empty are removed, up to the one extracted into.
+    assert!(!output_dir.join("assets/minecraft/textures").exists());
+    assert!(
+        output_dir
+            .join("assets/minecraft/lang/en_us.json")
+            .is_file()
+    );
+}
+
+#[test]
+fn files_added_by_the_user_are_kept_unless_asked() {
+    let dir = common::temp_dir();
+    let output_dir = dir.path().join("out");
+    let options = ExtractOptions::new()
+        .output(&output_dir)
+        .contents(ExtractedContents::all());
+    let jar = JarBuilder::client().write(&dir.path().join("client.jar"));
+
+    let report = jar::extract_jar(&jar, &options, &mut NoProgress).unwrap();
+    sync::plan(&report, &[FileSource::Jar])
+        .unwrap()
+        .apply(&output_dir, false)
+        .unwrap();
+
+    let added = Path::new("assets/minecraft/lang/my_notes.txt");
+    let outside = Path::new("notes.txt");
+    fs::write(output_dir.join(added), "mine\n").unwrap();
+    fs::write(output_dir.join(outside), "mine\n").unwrap();
+
+    let report = jar::extract_jar(&jar, &options, &mut NoProgress).unwrap();
+    let plan = sync::plan(&report, &[FileSource::Jar]).unwrap();
+    assert!(plan.stale.is_empty());
+    assert_eq!(plan.unknown, [added]);
+
+    assert_eq!(plan.apply(&output_dir, false).unwrap(), 0);
+    assert!(output_dir.join(added).is_file());
+
+    // Files outside of the directories extracted into are never touched.
+    assert_eq!(plan.apply(&output_dir, true).unwrap(), 1);
+    assert!(!output_dir.join(added).exists());
+    assert!(output_dir.join(outside).is_file());
+}
+
+#[test]
+fn files_of_other_sources_are_kept() {
+    let dir = common::temp_dir();
+    let output_dir = dir.path().join("out");
+    let options = ExtractOptions::new().output(&output_dir);
+    let jar = JarBuilder::client().write(&dir.path().join("client.jar"));
+    let store = AssetStoreBuilder::new()
+        .file("minecraft/sounds/ambient/cave1.ogg", "OggS")
+        .write(&dir.path().join("assets"));
+
+    let report = jar::extract_jar(&jar, &options, &mut NoProgress).unwrap();
+    sync::plan(&report, &[FileSource::Jar])
+        .unwrap()
+        .apply(&output_dir, true)
+        .unwrap();
+
+    // The jar's files aren't stale because the hashed assets don't have them.
+    let report =
+        hashed::extract_hashed_assets(&store.dir, &store.index(), &options, &mut NoProgress)
+            .unwrap();
+    let plan = sync::plan(&report, &[FileSource::Hashed]).unwrap();
+    assert!(plan.stale.is_empty() && plan.unknown.is_empty());
+    plan.apply(&output_dir, true).unwrap();
+
+    let report = jar::extract_jar(&jar, &options, &mut NoProgress).unwrap();
+    let plan = sync::plan(&report, &[FileSource::Jar]).unwrap();
+    assert!(plan.stale.is_empty() && plan.unknown.is_empty());
+}
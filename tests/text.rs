@@ -0,0 +1,182 @@
+mod common;
+
+use common::{AssetStoreBuilder, JarBuilder};
+use extract_minecraft_assets::format::JsonFormat;
+use extract_minecraft_assets::options::ExtractOptions;
+use extract_minecraft_assets::progress::NoProgress;
+use extract_minecraft_assets::text::{self, LineEnding};
+use extract_minecraft_assets::vfs::MemoryFs;
+use extract_minecraft_assets::{hashed, jar};
+use std::path::Path;
+use std::sync::Arc;
+
+#[test]
+fn line_endings_are_normalized() {
+    let mixed = b"\xEF\xBB\xBFone\r\ntwo\nthree\rfour";
+
+    assert_eq!(
+        text::normalize(mixed, LineEnding::Lf).unwrap(),
+        b"one\ntwo\nthree\nfour"
+    );
+    assert_eq!(
+        text::normalize(mixed, LineEnding::Crlf).unwrap(),
+        b"one\r\ntwo\r\nthree\r\nfour"
+    );
+    assert_eq!(
+        text::normalize(b"one\r\n\r\n", LineEnding::Crlf).unwrap(),
+        b"one\r\n\r\n"
+    );
+}
+
+#[test]
+fn binary_contents_are_not_normalized() {
+    assert_eq!(
+        text::normalize(b"\x89PNG\r\n\x1a\n\0", LineEnding::Lf),
+        None
+    );
+    assert_eq!(text::normalize(b"\xFF\xFE\r\n", LineEnding::Lf), None);
+}
+
+#[test]
+fn only_text_files_are_recognized() {
+    for path in [
+        "assets/minecraft/texts/splashes.txt",
+        "assets/minecraft/lang/en_us.lang",
+        "assets/minecraft/shaders/core/position.vsh",
+        "pack.mcmeta",
+    ] {
+        assert!(text::is_text(Path::new(path)), "{path}");
+    }
+    for path in [
+        "assets/minecraft/textures/block/stone.png",
+        "assets/minecraft/sounds/ambient/cave/cave1.ogg",
+        "assets/minecraft/font/unifont.zip",
+        "assets/minecraft/texts/end.txt.bak",
+    ] {
+        assert!(!text::is_text(Path::new(path)), "{path}");
+    }
+}
+
+#[test]
+fn text_files_are_normalized_while_extracting() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client()
+        .file("assets/minecraft/texts/credits.txt", "Credits\nMojang\n")
+        .file("assets/minecraft/texts/end.txt", "Already\r\nCRLF\r\n")
+        .file("assets/minecraft/texts/broken.txt", "Not\0text\n")
+        .file("assets/minecraft/textures/block/stone.png", "PNG\n")
+        .write(&dir.path().join("client.jar"));
+    let store = AssetStoreBuilder::new()
+        .file("minecraft/lang/de_de.lang", "\u{feff}menu.quit=Beenden\n")
+        .write(&dir.path().join("assets"));
+
+    let fs = Arc::new(MemoryFs::new());
+    let options = ExtractOptions::new()
+        .output("out")
+        .vfs(fs.clone())
+        .text_normalize(Some(LineEnding::Crlf));
+    let report = jar::extract_jar(&jar, &options, &mut NoProgress).unwrap()
+        + hashed::extract_hashed_assets(&store.dir, &store.index(), &options, &mut NoProgress)
+            .unwrap();
+
+    let read = |path: &str| fs.read(Path::new(path)).unwrap();
+    assert_eq!(
+        read("out/assets/minecraft/texts/credits.txt"),
+        b"Credits\r\nMojang\r\n"
+    );
+    assert_eq!(
+        read("out/assets/minecraft/texts/end.txt"),
+        b"Already\r\nCRLF\r\n"
+    );
+    assert_eq!(
+        read("out/assets/minecraft/lang/de_de.lang"),
+        b"menu.quit=Beenden\r\n"
+    );
+    // Binary files are left untouched, whatever their extension.
+    assert_eq!(
+        read("out/assets/minecraft/texts/broken.txt"),
+        b"Not\0text\n"
+    );
+    assert_eq!(
+        read("out/assets/minecraft/textures/block/stone.png"),
+        b"PNG\n"
+    );
+
+    assert_eq!(report.warnings.len(), 1);
+    assert_eq!(
+        report.warnings[0].path,
+        Path::new("assets/minecraft/texts/broken.txt")
+    );
+    // The jar's own splashes and JSON files are normalized too, but files
+    // which already end lines with CRLF aren't counted.
+    assert_eq!(report.summary.normalized, 2 + 3);
+    assert_eq!(report.summary.failures, 0);
+}
+
+#[test]
+fn formatted_json_files_are_normalized() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client()
+        .file(
+            "assets/minecraft/models/block/stone.json",
+            r#"{"parent":"block/cube_all"}"#,
+        )
+        .write(&dir.path().join("client.jar"));
+
+    let fs = Arc::new(MemoryFs::new());
+    let options = ExtractOptions::new()
+        .output("out")
+        .vfs(fs.clone())
+        .format_json(Some(JsonFormat::Pretty))
+        .text_normalize(Some(LineEnding::Crlf));
+    jar::extract_jar(&jar, &options, &mut NoProgress).unwrap();
+
+    assert_eq!(
+        fs.read(Path::new("out/assets/minecraft/models/block/stone.json"))
+            .unwrap(),
+        b"{\r\n  \"parent\": \"block/cube_all\"\r\n}"
+    );
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn text_files_are_normalized_by_async_extraction() {
+    use extract_minecraft_assets::asynchronous;
+
+    let dir = common::temp_dir();
+    let store = AssetStoreBuilder::new()
+        .file("minecraft/lang/de_de.lang", "menu.quit=Beenden\r\n")
+        .file("minecraft/sounds/ambient/cave/cave1.ogg", "OggS\r\n")
+        .write(&dir.path().join("assets"));
+
+    let fs = Arc::new(MemoryFs::new());
+    let options = ExtractOptions::new()
+        .output("out")
+        .vfs(fs.clone())
+        .text_normalize(Some(LineEnding::Lf));
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    let report = runtime
+        .block_on(asynchronous::extract_hashed_assets_async(
+            &store.dir,
+            &store.index(),
+            &options,
+            None,
+        ))
+        .unwrap();
+
+    assert_eq!(report.summary.normalized, 1);
+    assert_eq!(
+        fs.read(Path::new("out/assets/minecraft/lang/de_de.lang"))
+            .unwrap(),
+        b"menu.quit=Beenden\n"
+    );
+    assert_eq!(
+        fs.read(Path::new(
+            "out/assets/minecraft/sounds/ambient/cave/cave1.ogg"
+        ))
+        .unwrap(),
+        b"OggS\r\n"
+    );
+}
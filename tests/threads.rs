@@ -0,0 +1,117 @@
+mod common;
+
+use common::AssetStoreBuilder;
+use extract_minecraft_assets::error::ExtractError;
+use extract_minecraft_assets::hashed;
+use extract_minecraft_assets::options::ExtractOptions;
+use extract_minecraft_assets::progress::{NoProgress, ProgressSink, Summary};
+use extract_minecraft_assets::vfs::MemoryFs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Writes a store of 40 language files, and one listed but missing.
+fn store(dir: &Path) -> common::AssetStore {
+    let mut store = AssetStoreBuilder::new().missing("minecraft/sounds/missing.ogg", "missing");
+    for i in 0..40 {
+        store = store.file(
+            &format!("minecraft/lang/{i:02}.json"),
+            format!("{{\"key\": {i}}}\n"),
+        );
+    }
+
+    store.write(dir)
+}
+
+#[test]
+fn parallel_extraction_writes_the_same_files() {
+    let dir = common::temp_dir();
+    let store = store(&dir.path().join("assets"));
+
+    let extract = |threads| {
+        let fs = Arc::new(MemoryFs::new());
+        let options = ExtractOptions::new()
+            .output("out")
+            .vfs(fs.clone())
+            .threads(threads);
+        let report =
+            hashed::extract_hashed_assets(&store.dir, &store.index(), &options, &mut NoProgress)
+                .unwrap();
+
+        (report, fs)
+    };
+    let (serial, serial_fs) = extract(1);
+    let (parallel, parallel_fs) = extract(8);
+
+    assert_eq!(parallel.summary.files, 40);
+    assert_eq!(parallel.summary.files, serial.summary.files);
+    assert_eq!(parallel.summary.bytes, serial.summary.bytes);
+    assert_eq!(parallel.summary.failures, 1);
+    assert_eq!(parallel_fs.files(), serial_fs.files());
+    for path in parallel_fs.files() {
+        assert_eq!(parallel_fs.read(&path), serial_fs.read(&path));
+    }
+
+    let mut serial_paths: Vec<_> = serial.files.iter().map(|file| &file.path).collect();
+    let mut parallel_paths: Vec<_> = parallel.files.iter().map(|file| &file.path).collect();
+    serial_paths.sort();
+    parallel_paths.sort();
+    assert_eq!(parallel_paths, serial_paths);
+    assert!(parallel.files.iter().all(|file| file.hash.is_some()));
+}
+
+/// Records the events it receives.
+#[derive(Default)]
+struct Events {
+    total: usize,
+    started: Vec<PathBuf>,
+    done: usize,
+    failed: Vec<String>,
+    finished: bool,
+}
+
+impl ProgressSink for Events {
+    fn begin(&mut self, total_files: usize, _total_bytes: u64) {
+        self.total = total_files;
+    }
+
+    fn file_started(&mut self, path: &Path) {
+        self.started.push(path.to_owned());
+    }
+
+    fn file_done(&mut self, _path: &Path, _bytes: u64) {
+        self.done += 1;
+    }
+
+    fn file_failed(&mut self, _path: &Path, error: &ExtractError) {
+        self.failed.push(error.to_string());
+    }
+
+    fn finish(&mut self, _summary: &Summary) {
+        self.finished = true;
+    }
+}
+
+#[test]
+fn progress_of_every_thread_is_reported_to_the_sink() {
+    let dir = common::temp_dir();
+    let store = store(&dir.path().join("assets"));
+
+    let mut events = Events::default();
+    let options = ExtractOptions::new()
+        .vfs(Arc::new(MemoryFs::new()))
+        .threads(4);
+    hashed::extract_hashed_assets(&store.dir, &store.index(), &options, &mut events).unwrap();
+
+    events.started.sort();
+    events.started.dedup();
+    assert_eq!(events.total, 41);
+    assert_eq!(events.started.len(), 41);
+    assert_eq!(events.done, 40);
+    assert_eq!(events.failed.len(), 1);
+    assert!(
+        events.failed[0].contains("missing.ogg"),
+        "{:?}",
+        events.failed
+    );
+    assert!(events.finished);
+}
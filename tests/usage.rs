@@ -0,0 +1,90 @@
+mod common;
+
+use common::{AssetStoreBuilder, JarBuilder};
+use extract_minecraft_assets::jar::ExtractedContents;
+use extract_minecraft_assets::options::ExtractOptions;
+use extract_minecraft_assets::search::FileSource;
+use extract_minecraft_assets::usage::{TreeNode, Usage, UsageEstimate};
+
+#[test]
+fn hashed_assets_replace_jar_files_at_the_same_path() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client()
+        .file("assets/minecraft/lang/de_de.json", "{}\n")
+        .write(&dir.path().join("client.jar"));
+    let store = AssetStoreBuilder::new()
+        .file(
+            "minecraft/lang/de_de.json",
+            "{\"menu.quit\": \"Spiel beenden\"}\n",
+        )
+        .file("minecraft/sounds/ambient/cave/cave1.ogg", "OggS")
+        .write(&dir.path().join("assets"));
+    let options = ExtractOptions::new().contents(ExtractedContents::all());
+
+    let mut estimate = UsageEstimate::new();
+    estimate.add_jar(&jar, &options, |_| true).unwrap();
+    estimate.add_index(&store.index(), &options);
+    let usage = estimate.usage(None);
+
+    // en_us.json, splashes.txt, both shared.json and logs.json.
+    assert_eq!(usage.sources[&FileSource::Jar].files, 5);
+    assert_eq!(usage.sources[&FileSource::Hashed].files, 2);
+    assert_eq!(usage.directories["assets"].files, 5);
+    assert_eq!(usage.directories["data"].files, 2);
+    assert_eq!(usage.total.files, 7);
+    assert_eq!(usage.total.allocated, None);
+}
+
+#[test]
+fn files_are_grouped_by_where_they_would_be_written() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client().write(&dir.path().join("client.jar"));
+    let options = ExtractOptions::new()
+        .contents(ExtractedContents::assets())
+        .ignore_top_level(true);
+
+    let mut estimate = UsageEstimate::new();
+    estimate
+        .add_jar(&jar, &options, |path| !path.ends_with("shared.json"))
+        .unwrap();
+    let usage = estimate.usage(Some(512));
+
+    assert_eq!(usage.directories.keys().collect::<Vec<_>>(), ["minecraft"]);
+    assert_eq!(
+        usage.total,
+        Usage {
+            files: 2,
+            bytes: 27 + 19,
+            allocated: Some(1024),
+        }
+    );
+}
+
+#[test]
+fn the_tree_follows_where_files_would_be_written() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client().write(&dir.path().join("client.jar"));
+    let options = ExtractOptions::new()
+        .contents(ExtractedContents::all())
+        .strip_namespace(true);
+
+    let mut estimate = UsageEstimate::new();
+    estimate.add_jar(&jar, &options, |_| true).unwrap();
+    let tree = estimate.tree(Some(2));
+
+    let names = |node: &TreeNode| -> Vec<String> {
+        node.children
+            .iter()
+            .map(|child| child.name.clone())
+            .collect()
+    };
+    assert_eq!(names(&tree), ["assets", "data"]);
+    let assets = &tree.children[0];
+    assert_eq!(names(assets), ["lang", "shared.json", "texts"]);
+    // Directories at the maximum depth still count their files.
+    let lang = &assets.children[0];
+    assert!(lang.is_dir && lang.children.is_empty());
+    assert_eq!((lang.files, lang.bytes), (1, 27));
+    assert!(!assets.children[1].is_dir);
+    assert_eq!(tree.files, 5);
+}
@@ -0,0 +1,109 @@
+use extract_minecraft_assets::util::{self, decode_path, display_width, truncate_middle};
+use std::path::Path;
+
+#[test]
+fn wide_and_zero_width_characters_are_measured() {
+    assert_eq!(display_width("sounds/ambient/cave/cave1.ogg"), 29);
+    assert_eq!(display_width("lang/森林.json"), 14);
+    assert_eq!(display_width("한국어"), 6);
+    assert_eq!(display_width("e\u{301}"), 1);
+    assert_eq!(display_width(""), 0);
+}
+
+#[test]
+fn long_paths_are_truncated_in_the_middle() {
+    let path = "data/minecraft/worldgen/template_pool/village/plains/houses.json";
+
+    for max_width in 0..path.len() {
+        let truncated = truncate_middle(path, max_width);
+
+        assert!(display_width(&truncated) <= max_width, "{truncated}");
+        if max_width > 0 {
+            assert!(truncated.contains('…'), "{truncated}");
+        }
+    }
+    assert_eq!(truncate_middle(path, 20), "data/minec…uses.json");
+    assert_eq!(truncate_middle(path, 1), "…");
+    assert_eq!(truncate_middle(path, 0), "");
+    assert_eq!(truncate_middle(path, path.len()), path);
+}
+
+#[test]
+fn wide_characters_are_never_split_across_the_limit() {
+    let path = "textures/森林/草原/砂漠.png";
+
+    for max_width in 0..=display_width(path) {
+        let truncated = truncate_middle(path, max_width);
+
+        assert!(display_width(&truncated) <= max_width, "{truncated}");
+    }
+    // `森` would take the start over its 10 columns, so it is left out.
+    assert_eq!(truncate_middle(path, 21), "textures/…/砂漠.png");
+    assert_eq!(truncate_middle(path, 22), "textures/森…/砂漠.png");
+}
+
+#[test]
+fn paths_are_decoded_per_component() {
+    for (path, expected) in [
+        (
+            "minecraft/sounds/calm%201.ogg",
+            "minecraft/sounds/calm 1.ogg",
+        ),
+        ("minecraft/lang/caf%C3%A9.json", "minecraft/lang/café.json"),
+        ("minecraft/panorama 0.png", "minecraft/panorama 0.png"),
+        // Invalid sequences are kept as they are.
+        ("minecraft/100%.txt", "minecraft/100%.txt"),
+        ("minecraft/%zz%+1.txt", "minecraft/%zz%+1.txt"),
+        // So are components which would decode to invalid UTF-8 or leave
+        // their directory.
+        ("minecraft/%FF.txt", "minecraft/%FF.txt"),
+        ("minecraft/a%2Fb.txt", "minecraft/a%2Fb.txt"),
+        ("minecraft/a%5Cb.txt", "minecraft/a%5Cb.txt"),
+        ("%2E%2E/x%20y", "%2E%2E/x y"),
+    ] {
+        assert_eq!(decode_path(Path::new(path)), Path::new(expected), "{path}");
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn directories_are_found_and_described_through_symlinks() {
+    use std::fs;
+    use std::os::unix::fs::symlink;
+
+    let dir = tempfile::tempdir().unwrap();
+    let dir = dir.path().canonicalize().unwrap();
+    let drive = dir.join("drive/minecraft");
+    fs::create_dir_all(drive.join("assets")).unwrap();
+    symlink(&drive, dir.join(".minecraft")).unwrap();
+    symlink(dir.join("unmounted"), dir.join("broken")).unwrap();
+
+    let assets = dir.join(".minecraft/assets");
+    assert!(util::is_dir(&assets));
+    assert_eq!(
+        util::display_resolved(&assets),
+        format!(
+            "'{}' (resolved to '{}')",
+            assets.display(),
+            drive.join("assets").display()
+        )
+    );
+
+    let broken = dir.join("broken/assets");
+    assert!(!util::is_dir(&broken));
+    assert_eq!(
+        util::display_resolved(&broken),
+        format!(
+            "'{}' ('{}' is a broken symlink to '{}')",
+            broken.display(),
+            dir.join("broken").display(),
+            dir.join("unmounted").display()
+        )
+    );
+
+    let plain = dir.join("drive");
+    assert_eq!(
+        util::display_resolved(&plain),
+        format!("'{}'", plain.display())
+    );
+}
@@ -0,0 +1,157 @@
+mod common;
+
+use common::{AssetStoreBuilder, JarBuilder};
+use extract_minecraft_assets::options::ExtractOptions;
+use extract_minecraft_assets::progress::NoProgress;
+use extract_minecraft_assets::validate::{self, ContentCheck, ContentFormat};
+use extract_minecraft_assets::vfs::MemoryFs;
+use extract_minecraft_assets::{hashed, jar};
+use std::path::Path;
+use std::sync::Arc;
+
+/// The start of a valid 16x16 RGBA PNG file, up to the end of its `IHDR`
+/// chunk.
+const PNG: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR\0\0\0\x10\0\0\0\x10\x08\x06\0\0\0";
+
+#[test]
+fn valid_contents_pass() {
+    assert_eq!(validate::validate(ContentFormat::Png, PNG), Ok(()));
+    assert_eq!(
+        validate::validate(ContentFormat::Ogg, b"OggS\0\x02"),
+        Ok(())
+    );
+    assert_eq!(
+        validate::validate(ContentFormat::Json, b"{\"a\": [1]}\n"),
+        Ok(())
+    );
+    assert_eq!(
+        validate::validate(ContentFormat::Nbt, b"\x1f\x8b\x08\0"),
+        Ok(())
+    );
+}
+
+#[test]
+fn invalid_contents_fail_with_a_reason() {
+    let reason = |format, contents: &[u8]| validate::validate(format, contents).unwrap_err();
+
+    assert_eq!(
+        reason(ContentFormat::Png, b"GIF89a"),
+        "invalid PNG: missing the PNG signature"
+    );
+    assert_eq!(
+        reason(ContentFormat::Png, &PNG[..20]),
+        "invalid PNG: missing the IHDR chunk"
+    );
+    let mut zero_width = PNG.to_vec();
+    zero_width[16..20].fill(0);
+    assert_eq!(
+        reason(ContentFormat::Png, &zero_width),
+        "invalid PNG: the image is 0x16"
+    );
+    assert!(reason(ContentFormat::Ogg, b"RIFF").contains("OggS"));
+    assert!(reason(ContentFormat::Json, b"{\"a\": ").starts_with("invalid JSON: "));
+    assert_eq!(
+        reason(ContentFormat::Nbt, b"\n\0\0"),
+        "invalid NBT: not gzip-compressed"
+    );
+}
+
+#[test]
+fn contents_are_checked_in_pieces() {
+    let mut check = ContentCheck::new(ContentFormat::Png);
+    for byte in PNG {
+        check.update(&[*byte]);
+    }
+    check.update(&[0; 4096]);
+
+    assert_eq!(check.finish(), Ok(()));
+}
+
+#[test]
+fn formats_are_recognized_by_extension() {
+    assert_eq!(
+        ContentFormat::of(Path::new("minecraft/textures/block/stone.png")),
+        Some(ContentFormat::Png)
+    );
+    assert_eq!(
+        ContentFormat::of(Path::new("pack.mcmeta")),
+        Some(ContentFormat::Json)
+    );
+    assert_eq!(
+        ContentFormat::of(Path::new("minecraft/texts/end.txt")),
+        None
+    );
+}
+
+#[test]
+fn invalid_files_are_written_with_warnings() {
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client()
+        .file("assets/minecraft/textures/block/stone.png", PNG)
+        .file("assets/minecraft/textures/block/dirt.png", "not a PNG")
+        .file("assets/minecraft/models/block/dirt.json", "{\"parent\": ")
+        .write(&dir.path().join("client.jar"));
+    let store = AssetStoreBuilder::typical()
+        .file("minecraft/sounds/ambient/cave/cave2.ogg", "RIFF")
+        .write(&dir.path().join("assets"));
+
+    let fs = Arc::new(MemoryFs::new());
+    let options = ExtractOptions::new()
+        .output("out")
+        .vfs(fs.clone())
+        .validate_content([ContentFormat::Png, ContentFormat::Ogg]);
+    let report = jar::extract_jar(&jar, &options, &mut NoProgress).unwrap()
+        + hashed::extract_hashed_assets(&store.dir, &store.index(), &options, &mut NoProgress)
+            .unwrap();
+
+    // The typical store's cave1.ogg doesn't start with a full capture
+    // pattern either, and JSON isn't checked.
+    let mut invalid: Vec<_> = report
+        .warnings
+        .iter()
+        .map(|warning| warning.path.to_str().unwrap())
+        .collect();
+    invalid.sort();
+    assert_eq!(
+        invalid,
+        [
+            "assets/minecraft/sounds/ambient/cave/cave1.ogg",
+            "assets/minecraft/sounds/ambient/cave/cave2.ogg",
+            "assets/minecraft/textures/block/dirt.png",
+        ]
+    );
+    // Invalid files are counted apart from the typical store's missing sound.
+    assert_eq!(report.summary.invalid, 3);
+    assert_eq!(report.summary.failures, 1);
+    assert_eq!(
+        fs.read(Path::new("out/assets/minecraft/textures/block/dirt.png")),
+        Some(b"not a PNG".to_vec())
+    );
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn invalid_files_are_reported_by_async_extraction() {
+    use extract_minecraft_assets::asynchronous;
+
+    let dir = common::temp_dir();
+    let jar = JarBuilder::client()
+        .file("assets/minecraft/textures/block/dirt.png", "not a PNG")
+        .file("assets/minecraft/models/block/dirt.json", "{\"parent\": ")
+        .write(&dir.path().join("client.jar"));
+
+    let fs = Arc::new(MemoryFs::new());
+    let options = ExtractOptions::new()
+        .output("out")
+        .vfs(fs.clone())
+        .validate_content(ContentFormat::ALL);
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    let report = runtime
+        .block_on(asynchronous::extract_jar_async(&jar, &options, None))
+        .unwrap();
+
+    assert_eq!(report.summary.invalid, 2);
+    assert_eq!(report.warnings.len(), 2);
+}
@@ -0,0 +1,183 @@
+mod common;
+
+use common::AssetStoreBuilder;
+use extract_minecraft_assets::error::ExtractError;
+use extract_minecraft_assets::hashed::{self, ObjectMismatch};
+use extract_minecraft_assets::options::{ExtractOptions, Verify};
+use extract_minecraft_assets::progress::NoProgress;
+use extract_minecraft_assets::report::FileOutcome;
+use extract_minecraft_assets::vfs::MemoryFs;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Writes a store whose hashed files for `de_de.json` and `cave1.ogg` are
+/// corrupted, the first keeping its size and the second not.
+fn corrupted_store(dir: &Path) -> common::AssetStore {
+    let store = AssetStoreBuilder::new()
+        .file("minecraft/lang/de_de.json", "{\"a\": 1}\n")
+        .file("minecraft/lang/fr_fr.json", "{\"a\": 2}\n")
+        .file(
+            "minecraft/sounds/ambient/cave/cave1.ogg",
+            "OggS fake sound\n",
+        )
+        .write(dir);
+    let index = store.index();
+
+    for (path, contents) in [
+        ("minecraft/lang/de_de.json", "{\"a\": 3}\n"),
+        ("minecraft/sounds/ambient/cave/cave1.ogg", "OggS\n"),
+    ] {
+        let object_path = store
+            .dir
+            .join("objects")
+            .join(index.get(path).unwrap().object_path);
+        fs::write(object_path, contents).unwrap();
+    }
+
+    store
+}
+
+#[test]
+fn corrupted_hashed_files_are_extracted_with_warnings() {
+    let dir = common::temp_dir();
+    let store = corrupted_store(&dir.path().join("assets"));
+
+    let fs = Arc::new(MemoryFs::new());
+    let options = ExtractOptions::new()
+        .output("out")
+        .vfs(fs.clone())
+        .verify(Some(Verify::Warn));
+    let report =
+        hashed::extract_hashed_assets(&store.dir, &store.index(), &options, &mut NoProgress)
+            .unwrap();
+
+    assert_eq!(report.summary.files, 3);
+    assert_eq!(report.summary.unverified, 2);
+    assert_eq!(report.summary.failures, 0);
+
+    let mut warnings: Vec<_> = report.warnings.iter().collect();
+    warnings.sort_by_key(|warning| &warning.path);
+    assert_eq!(
+        warnings[0].path,
+        Path::new("assets/minecraft/lang/de_de.json")
+    );
+    assert!(
+        warnings[0].message.contains("it has SHA-1 hash"),
+        "{}",
+        warnings[0].message
+    );
+    assert!(
+        warnings[1]
+            .message
+            .ends_with("it is 5 bytes rather than 16"),
+        "{}",
+        warnings[1].message
+    );
+    assert_eq!(
+        fs.read(Path::new("out/assets/minecraft/lang/de_de.json"))
+            .unwrap(),
+        b"{\"a\": 3}\n"
+    );
+    assert!(report.summary.to_string().contains("2 failed verification"));
+}
+
+#[test]
+fn corrupted_hashed_files_fail_strict_verification() {
+    let dir = common::temp_dir();
+    let store = corrupted_store(&dir.path().join("assets"));
+
+    let fs = Arc::new(MemoryFs::new());
+    let options = ExtractOptions::new()
+        .output("out")
+        .vfs(fs.clone())
+        .verify(Some(Verify::Strict));
+    let report =
+        hashed::extract_hashed_assets(&store.dir, &store.index(), &options, &mut NoProgress)
+            .unwrap();
+
+    assert_eq!(report.summary.files, 1);
+    assert_eq!(report.summary.unverified, 2);
+    assert_eq!(report.summary.failures, 2);
+    assert!(report.warnings.is_empty());
+    assert_eq!(
+        fs.files(),
+        [PathBuf::from("out/assets/minecraft/lang/fr_fr.json")]
+    );
+
+    let failed = report
+        .files
+        .iter()
+        .find(|file| file.path == Path::new("assets/minecraft/lang/de_de.json"))
+        .unwrap();
+    assert!(matches!(failed.outcome, FileOutcome::Failed { .. }));
+}
+
+#[test]
+fn hashed_files_are_not_verified_by_default() {
+    let dir = common::temp_dir();
+    let store = corrupted_store(&dir.path().join("assets"));
+
+    let options = ExtractOptions::new().vfs(Arc::new(MemoryFs::new()));
+    let report =
+        hashed::extract_hashed_assets(&store.dir, &store.index(), &options, &mut NoProgress)
+            .unwrap();
+
+    assert_eq!(report.summary.files, 3);
+    assert_eq!(report.summary.unverified, 0);
+    assert!(report.warnings.is_empty());
+}
+
+#[test]
+fn mismatches_name_the_asset() {
+    let error = ExtractError::CorruptObject {
+        path: PathBuf::from("minecraft/lang/de_de.json"),
+        mismatch: ObjectMismatch::Size {
+            expected: 16,
+            actual: 5,
+        },
+    };
+
+    assert_eq!(
+        error.to_string(),
+        "hashed file for 'minecraft/lang/de_de.json' doesn't match the index file: \
+         it is 5 bytes rather than 16"
+    );
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn corrupted_hashed_files_are_verified_by_async_extraction() {
+    use extract_minecraft_assets::asynchronous;
+
+    let dir = common::temp_dir();
+    let store = corrupted_store(&dir.path().join("assets"));
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    let extract = |verify| {
+        let options = ExtractOptions::new()
+            .vfs(Arc::new(MemoryFs::new()))
+            .verify(Some(verify));
+
+        runtime
+            .block_on(asynchronous::extract_hashed_assets_async(
+                &store.dir,
+                &store.index(),
+                &options,
+                None,
+            ))
+            .unwrap()
+    };
+
+    let report = extract(Verify::Warn);
+    assert_eq!(report.summary.files, 3);
+    assert_eq!(report.summary.unverified, 2);
+    assert_eq!(report.warnings.len(), 2);
+
+    let report = extract(Verify::Strict);
+    assert_eq!(report.summary.files, 1);
+    assert_eq!(report.summary.unverified, 2);
+    assert_eq!(report.summary.failures, 2);
+}
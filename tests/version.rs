@@ -0,0 +1,334 @@
+mod common;
+
+use common::{AssetStoreBuilder, JarBuilder};
+use extract_minecraft_assets::error::ExtractError;
+use extract_minecraft_assets::hashed::{self, IndexFile};
+use extract_minecraft_assets::jar::{self, ExtractedContents};
+use extract_minecraft_assets::options::ExtractOptions;
+use extract_minecraft_assets::progress::NoProgress;
+use extract_minecraft_assets::version::{InvalidVersionReason, Version, VersionManifest};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+fn minecraft_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/minecraft")
+}
+
+#[test]
+fn resolves_names_within_minecraft_dir() {
+    let version = Version::resolve_in("1.20.1", &minecraft_dir()).unwrap();
+
+    assert_eq!(version.name(), "1.20.1");
+    assert_eq!(version.path(), minecraft_dir().join("versions/1.20.1"));
+    assert_eq!(
+        version.jar_file(),
+        minecraft_dir().join("versions/1.20.1/1.20.1.jar")
+    );
+}
+
+#[test]
+fn resolves_paths() {
+    let dir = minecraft_dir().join("versions/1.20.1");
+    let version = Version::resolve_in(dir.to_str().unwrap(), Path::new("nowhere")).unwrap();
+
+    assert_eq!(version, Version::from_dir(dir));
+}
+
+#[test]
+fn normalizes_trailing_separators_and_dot_components() {
+    let dir = minecraft_dir().join("versions/1.20.1");
+
+    for input in [
+        format!("{}/", dir.display()),
+        format!("{}/.", dir.display()),
+        format!("{}/./", dir.display()),
+        format!("{}/../1.20.1", dir.display()),
+    ] {
+        let version = Version::resolve_in(&input, Path::new("nowhere")).unwrap();
+
+        assert_eq!(version.name(), "1.20.1", "{input}");
+        assert_eq!(version.jar_file().file_name().unwrap(), "1.20.1.jar");
+        assert_eq!(version.manifest_file().file_name().unwrap(), "1.20.1.json");
+    }
+
+    let version = Version::resolve_in("1.20.1/", &minecraft_dir()).unwrap();
+    assert_eq!(version.name(), "1.20.1");
+}
+
+#[test]
+fn resolves_paths_ending_in_parent_dir() {
+    let dir = minecraft_dir().join("versions/1.20.1");
+    let input = format!("{}/../1.20.1/..", dir.display());
+    let version = Version::resolve_in(&input, Path::new("nowhere")).unwrap();
+
+    assert_eq!(version.name(), "versions");
+    assert_eq!(
+        version.path(),
+        minecraft_dir().join("versions").canonicalize().unwrap()
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn rejects_directories_without_a_name() {
+    let error = Version::resolve_in("/", Path::new("nowhere")).unwrap_err();
+
+    assert_eq!(error.version, "/");
+    assert_eq!(error.reason, InvalidVersionReason::Unnamed);
+    assert!(error.suggestions.is_empty());
+}
+
+#[cfg(unix)]
+#[test]
+fn resolves_names_through_symlinked_versions_dirs() {
+    use std::os::unix::fs::symlink;
+
+    let dir = tempfile::tempdir().unwrap();
+    let linked_dir = dir.path().join("minecraft");
+    fs::create_dir_all(&linked_dir).unwrap();
+    symlink(
+        minecraft_dir().join("versions"),
+        linked_dir.join("versions"),
+    )
+    .unwrap();
+
+    let version = Version::resolve_in("1.20.1", &linked_dir).unwrap();
+
+    // The path goes through the symlink rather than being resolved.
+    assert_eq!(version.path(), linked_dir.join("versions/1.20.1"));
+    assert!(version.manifest_file().is_file());
+}
+
+#[cfg(unix)]
+#[test]
+fn symlinked_version_dirs_are_named_by_the_symlink() {
+    use std::os::unix::fs::symlink;
+
+    let dir = tempfile::tempdir().unwrap();
+    let versions_dir = dir.path().join("minecraft/versions");
+    let target = dir.path().join("backup/1.20.1-old");
+    fs::create_dir_all(&versions_dir).unwrap();
+    fs::create_dir_all(&target).unwrap();
+    symlink(&target, versions_dir.join("1.20.1")).unwrap();
+
+    let version = Version::resolve_in("1.20.1", &dir.path().join("minecraft")).unwrap();
+
+    assert_eq!(version.name(), "1.20.1");
+    assert_eq!(version.jar_file(), versions_dir.join("1.20.1/1.20.1.jar"));
+
+    // A version named by the path of the symlink is found too.
+    let link = versions_dir.join("1.20.1");
+    let version = Version::resolve_in(link.to_str().unwrap(), Path::new("nowhere")).unwrap();
+    assert_eq!(version.name(), "1.20.1");
+}
+
+#[cfg(unix)]
+#[test]
+fn broken_symlinked_version_dirs_are_not_found() {
+    use std::os::unix::fs::symlink;
+
+    let dir = tempfile::tempdir().unwrap();
+    let versions_dir = dir.path().join("minecraft/versions");
+    fs::create_dir_all(&versions_dir).unwrap();
+    symlink(dir.path().join("unmounted"), versions_dir.join("1.20.1")).unwrap();
+
+    let error = Version::resolve_in("1.20.1", &dir.path().join("minecraft")).unwrap_err();
+
+    assert!(
+        matches!(error.reason, InvalidVersionReason::NotFound { .. }),
+        "{error}"
+    );
+}
+
+#[test]
+fn suggests_similar_versions() {
+    let error = Version::resolve_in("1.20.2", &minecraft_dir()).unwrap_err();
+
+    assert_eq!(error.version, "1.20.2");
+    assert_eq!(
+        error.reason,
+        InvalidVersionReason::NotFound {
+            versions_dir: minecraft_dir().join("versions"),
+            available: vec!["1.20.1".to_owned(), "fabric-loader-1.20.1".to_owned()],
+        }
+    );
+    assert_eq!(error.suggestions, ["1.20.1"]);
+    assert_eq!(
+        error.to_string(),
+        format!(
+            "invalid version '1.20.2': no directory exists at that path nor with that name in \
+             '{}' (did you mean '1.20.1'?); installed versions are '1.20.1', \
+             'fabric-loader-1.20.1'",
+            minecraft_dir().join("versions").display()
+        )
+    );
+}
+
+#[test]
+fn missing_minecraft_dirs_are_told_apart_from_missing_versions() {
+    let dir = tempfile::tempdir().unwrap();
+    let minecraft_dir = dir.path().join(".minecraft");
+
+    let error = Version::resolve_in("1.20.1", &minecraft_dir).unwrap_err();
+    assert_eq!(
+        error.reason,
+        InvalidVersionReason::NoMinecraftDir {
+            probed: vec![minecraft_dir.clone()]
+        }
+    );
+    assert!(
+        error
+            .to_string()
+            .contains("no `.minecraft` directory was found at"),
+        "{error}"
+    );
+
+    fs::create_dir_all(&minecraft_dir).unwrap();
+    let error = Version::resolve_in("1.20.1", &minecraft_dir).unwrap_err();
+    assert_eq!(
+        error.reason,
+        InvalidVersionReason::NoVersionsDir {
+            path: minecraft_dir.join("versions")
+        }
+    );
+
+    fs::create_dir_all(minecraft_dir.join("versions")).unwrap();
+    let error = Version::resolve_in("1.20.1", &minecraft_dir).unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .ends_with(", which has no versions installed"),
+        "{error}"
+    );
+
+    // Paths aren't looked up by name at all.
+    let error = Version::resolve_in("versions/1.20.1", &minecraft_dir).unwrap_err();
+    assert_eq!(error.reason, InvalidVersionReason::NoSuchDirectory);
+}
+
+#[test]
+fn installed_versions_have_a_jar_and_manifest() {
+    let dir = tempfile::tempdir().unwrap();
+    let versions_dir = dir.path().join("versions");
+    for (name, files) in [
+        ("1.20.2", &["1.20.2.jar", "1.20.2.json"][..]),
+        ("1.20.1", &["1.20.1.jar", "1.20.1.json"]),
+        ("fabric-loader-1.20.1", &["fabric-loader-1.20.1.json"]),
+        ("empty", &[]),
+    ] {
+        fs::create_dir_all(versions_dir.join(name)).unwrap();
+        for file in files {
+            fs::write(versions_dir.join(name).join(file), "{}").unwrap();
+        }
+    }
+    fs::write(versions_dir.join("launcher_profiles.json"), "{}").unwrap();
+
+    let installed = Version::installed(&versions_dir);
+
+    assert_eq!(
+        installed,
+        [
+            Version::from_dir(versions_dir.join("1.20.1")),
+            Version::from_dir(versions_dir.join("1.20.2")),
+        ]
+    );
+    assert!(Version::installed(&dir.path().join("missing")).is_empty());
+}
+
+#[test]
+fn reads_manifest() {
+    let version = Version::resolve_in("1.20.1", &minecraft_dir()).unwrap();
+    let manifest = version.manifest().unwrap();
+
+    assert_eq!(manifest.id, "1.20.1");
+    assert_eq!(manifest.index_id(), Some("5"));
+    assert_eq!(manifest.asset_index.unwrap().total_size, Some(618422291));
+    assert_eq!(manifest.downloads["client"].size, Some(23028853));
+    assert_eq!(manifest.inherits_from, None);
+    assert_eq!(
+        version.index_file(Path::new("assets")).unwrap(),
+        Path::new("assets/indexes/5.json")
+    );
+}
+
+#[test]
+fn minimal_manifest_only_needs_the_index_id() {
+    let manifest = VersionManifest::from_reader(
+        r#"{"id": "1.0", "assetIndex": {"id": "1"}, "downloads": {"client": {}}}"#.as_bytes(),
+    )
+    .unwrap();
+
+    assert_eq!(manifest.index_id(), Some("1"));
+    assert_eq!(manifest.asset_index.unwrap().sha1, None);
+    assert_eq!(manifest.downloads["client"].url, None);
+}
+
+#[test]
+fn version_with_minimal_manifest_finds_its_index() {
+    let dir = tempfile::tempdir().unwrap();
+    let version_dir = dir.path().join("versions/1.0");
+    fs::create_dir_all(&version_dir).unwrap();
+    fs::write(
+        version_dir.join("1.0.json"),
+        r#"{"id": "1.0", "assetIndex": {"id": "1"}}"#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        Version::from_dir(version_dir)
+            .index_file(Path::new("assets"))
+            .unwrap(),
+        Path::new("assets/indexes/1.json")
+    );
+}
+
+#[test]
+fn inheriting_manifest_has_no_index() {
+    let version = Version::resolve_in("fabric-loader-1.20.1", &minecraft_dir()).unwrap();
+    let manifest = version.manifest().unwrap();
+
+    assert_eq!(manifest.inherits_from.as_deref(), Some("1.20.1"));
+    assert!(manifest.downloads.is_empty());
+    assert!(matches!(
+        version.index_file(Path::new("assets")),
+        Err(ExtractError::NoAssetIndex { .. })
+    ));
+}
+
+/// An installed version can be found and extracted with the library alone,
+/// as a launcher embedding it would.
+#[test]
+fn installed_versions_are_extracted_through_the_library() {
+    let dir = common::temp_dir();
+    let minecraft_dir = dir.path().join(".minecraft");
+    let version_dir =
+        common::write_manifest(&minecraft_dir.join("versions"), "1.0", "\"assets\": \"5\"");
+    JarBuilder::client().write(&version_dir.join("1.0.jar"));
+    AssetStoreBuilder::new()
+        .file("minecraft/lang/de_de.json", "{}\n")
+        .write(&minecraft_dir.join("assets"));
+
+    let version = Version::resolve_in("1.0", &minecraft_dir).unwrap();
+    let index =
+        IndexFile::from_path(&version.index_file(&minecraft_dir.join("assets")).unwrap()).unwrap();
+    let options = ExtractOptions::new()
+        .output(dir.path().join("out"))
+        .contents(ExtractedContents::all());
+    let report = jar::extract_jar(&version.jar_file(), &options, &mut NoProgress).unwrap()
+        + hashed::extract_hashed_assets(
+            &minecraft_dir.join("assets"),
+            &index,
+            &options,
+            &mut NoProgress,
+        )
+        .unwrap();
+
+    assert_eq!(report.summary.failures, 0);
+    for path in [
+        "out/assets/minecraft/lang/en_us.json",
+        "out/assets/minecraft/lang/de_de.json",
+        "out/data/minecraft/tags/blocks/logs.json",
+    ] {
+        assert!(dir.path().join(path).is_file(), "{path} wasn't extracted");
+    }
+}